@@ -1,13 +1,21 @@
 //! Audio Handler - Mikrofon Capture und Playback
 //!
-//! Verwendet cpal für Cross-Platform Audio I/O.
-//! Opus-Encoding kann später hinzugefügt werden wenn vcpkg konfiguriert ist.
+//! Verwendet cpal für Cross-Platform Audio I/O. Kodiert wird außerhalb
+//! (siehe `opus_codec`), hier entstehen nur Frames mit `SAMPLE_RATE`.
 
+use super::limiter::{OutputLimiter, OutputLimiterConfig};
+use super::mixer::{PlaybackMixer, DEFAULT_PLAYBACK_SOURCE};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use cpal::{Device, SampleFormat, Stream, StreamConfig, SupportedStreamConfigRange};
+use cpal::{
+    Device, FromSample, Sample, SampleFormat, SizedSample, Stream, StreamConfig,
+    SupportedStreamConfigRange,
+};
 use parking_lot::Mutex;
 use ringbuf::{traits::*, HeapRb};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use thiserror::Error;
 
 // ============================================================================
@@ -21,11 +29,62 @@ pub const SAMPLE_RATE: u32 = 48000;
 pub const CHANNELS: u16 = 1;
 
 /// Frame Size in Samples (20ms @ 48kHz = 960 samples)
+///
+/// Standardwert für `read_frame`, die Vorpufferung wird immer in Frames
+/// dieser Größe gerechnet.
 pub const FRAME_SIZE: usize = 960;
 
+/// Von Opus unterstützte Frame-Größen bei 48kHz (2,5 bis 60ms)
+///
+/// Kleinere Frames senken die Latenz, erzeugen aber mehr Pakete und damit
+/// mehr Header-Overhead (IP/UDP/RTP sind ~40 Byte pro Paket, bei 10ms also
+/// doppelt so viel wie bei 20ms). Größere Frames sparen Bandbreite, jeder
+/// Paketverlust kostet dann aber mehr Audio.
+pub const OPUS_FRAME_SIZES: [usize; 6] = [120, 240, 480, 960, 1920, 2880];
+
 /// Buffer Size für Audio-Ring-Buffer
 const RING_BUFFER_SIZE: usize = FRAME_SIZE * 10;
 
+/// Standard-Vorpufferung vor dem Playback-Start (3 Frames = 60ms)
+pub const DEFAULT_PREFILL_FRAMES: usize = 3;
+
+/// Maximale Vorpufferung (muss in den Ring-Buffer passen)
+pub const MAX_PREFILL_FRAMES: usize = 8;
+
+/// Nachlauf nach `play_once`, damit der Treiber-Puffer vollständig abspielt
+const PLAY_ONCE_TAIL: Duration = Duration::from_millis(100);
+
+/// Wie oft `play_looped` auf den Stopp prüft
+const PLAY_LOOPED_POLL: Duration = Duration::from_millis(20);
+
+/// Vorlauf, mit dem `play_looped` den nächsten Durchlauf nachlegt
+const PLAY_LOOPED_LEAD: Duration = Duration::from_millis(100);
+
+/// Mixer-Quelle für das Prüfsignal des Echo-Checks
+pub const ECHO_CHECK_SOURCE: &str = "echo-check";
+
+/// Länge des Prüfsignals (muss in den Puffer einer Mixer-Quelle passen)
+pub const ECHO_PROBE_DURATION: Duration = Duration::from_millis(150);
+
+/// Maximale Verzögerung zwischen Lautsprecher und Mikrofon, die erkannt wird
+pub const ECHO_MAX_DELAY: Duration = Duration::from_millis(400);
+
+/// Korrelation, ab der das Prüfsignal als Echo gilt
+pub const ECHO_CORRELATION_THRESHOLD: f32 = 0.5;
+
+/// Maximale zusätzliche Verzögerung des gesendeten Audios
+pub const MAX_OUTPUT_DELAY_MS: u32 = 2000;
+
+/// Standard-Schwelle der Sprachaktivitätserkennung (RMS, etwa -40 dBFS)
+pub const DEFAULT_VAD_THRESHOLD: f32 = 0.01;
+
+/// Unterstützte Sample-Formate in absteigender Priorität
+///
+/// Intern wird immer mit f32 gearbeitet, andere Formate werden im
+/// Stream-Callback umgewandelt.
+const SUPPORTED_SAMPLE_FORMATS: [SampleFormat; 3] =
+    [SampleFormat::F32, SampleFormat::I16, SampleFormat::U16];
+
 // ============================================================================
 // ERROR TYPES
 // ============================================================================
@@ -44,8 +103,26 @@ pub enum AudioError {
     #[error("Failed to build audio stream: {0}")]
     StreamBuildError(String),
 
+    #[error("Microphone is in use by another application")]
+    DeviceBusy,
+
     #[error("Failed to start audio stream: {0}")]
     StreamPlayError(String),
+
+    #[error("Unknown playback source: {0}")]
+    UnknownSource(String),
+
+    #[error("Invalid prefill: {0} frames (max {MAX_PREFILL_FRAMES})")]
+    InvalidPrefill(usize),
+
+    #[error("Invalid frame size: {0} samples (Opus supports {OPUS_FRAME_SIZES:?})")]
+    InvalidFrameSize(usize),
+
+    #[error("Invalid output delay: {0}ms (max {MAX_OUTPUT_DELAY_MS}ms)")]
+    InvalidOutputDelay(u32),
+
+    #[error("Invalid VAD threshold: {0} (must be between 0.0 and 1.0)")]
+    InvalidVadThreshold(f32),
 }
 
 // ============================================================================
@@ -62,18 +139,50 @@ pub struct AudioHandler {
     input_stream: Option<Stream>,
     output_stream: Option<Stream>,
 
+    /// Vorab ermittelte Stream-Konfigurationen (siehe `warm_up`)
+    input_config: Option<DeviceConfig>,
+    output_config: Option<DeviceConfig>,
+    /// Gerätenamen zum Zeitpunkt der Konfigurationsermittlung
+    input_device_name: Option<String>,
+    output_device_name: Option<String>,
+
     /// Ring-Buffer für aufgenommenes Audio (Raw PCM)
     capture_buffer: Arc<Mutex<HeapRb<f32>>>,
 
-    /// Ring-Buffer für zu spielendes Audio (decoded PCM)
-    playback_buffer: Arc<Mutex<HeapRb<f32>>>,
+    /// Mixer für zu spielendes Audio (decoded PCM), eine Quelle pro Peer
+    playback_mixer: Arc<Mutex<PlaybackMixer>>,
+
+    /// Begrenzt das gemischte Signal vor der Ausgabe
+    output_limiter: Arc<Mutex<OutputLimiter>>,
+
+    /// Samples pro Frame für `read_frame` (siehe `OPUS_FRAME_SIZES`)
+    frame_size: usize,
 
     /// Mute-Status
     is_muted: Arc<Mutex<bool>>,
 
-    /// Audio Level (0.0 - 1.0) für Visualisierung
-    input_level: Arc<Mutex<f32>>,
-    output_level: Arc<Mutex<f32>>,
+    /// Geglättete Audio Level (0.0 - 1.0) für Visualisierung
+    input_level: Arc<Mutex<LevelMeter>>,
+    output_level: Arc<Mutex<LevelMeter>>,
+
+    /// Mitschnitt des Mikrofons während eines Echo-Checks (48kHz)
+    echo_recording: Arc<Mutex<Option<Vec<f32>>>>,
+
+    /// Feste Verzögerung zwischen Mikrofon und Encoder
+    output_delay: Arc<Mutex<DelayLine>>,
+
+    /// Sprachaktivitätserkennung über die gelesenen Frames
+    vad: Mutex<VoiceActivityDetector>,
+
+    /// Push-to-Talk: gesendet wird nur, solange die Taste gedrückt ist
+    push_to_talk: Mutex<PushToTalk>,
+}
+
+/// Stream-Konfiguration samt nativem Sample-Format des Geräts
+#[derive(Debug, Clone)]
+struct DeviceConfig {
+    stream: StreamConfig,
+    sample_format: SampleFormat,
 }
 
 // AudioHandler ist nicht automatisch Send wegen Stream
@@ -81,12 +190,24 @@ pub struct AudioHandler {
 unsafe impl Send for AudioHandler {}
 
 impl AudioHandler {
-    /// Erstellt einen neuen AudioHandler
+    /// Erstellt einen neuen AudioHandler mit den System-Standardgeräten
     pub fn new() -> Result<Self, AudioError> {
+        Self::with_devices(None, None)
+    }
+
+    /// Erstellt einen AudioHandler mit ausgewählten Geräten (nach Name)
+    ///
+    /// Nicht (mehr) vorhandene Geräte werden durch das Standardgerät
+    /// ersetzt, ob das passiert ist, zeigen `input_device_name` und
+    /// `output_device_name`.
+    pub fn with_devices(
+        input_name: Option<String>,
+        output_name: Option<String>,
+    ) -> Result<Self, AudioError> {
         let host = cpal::default_host();
 
-        let input_device = host.default_input_device();
-        let output_device = host.default_output_device();
+        let input_device = find_input_device(&host, input_name.as_deref());
+        let output_device = find_output_device(&host, output_name.as_deref());
 
         if input_device.is_none() {
             tracing::warn!("No audio input device found");
@@ -96,7 +217,12 @@ impl AudioHandler {
         }
 
         let capture_buffer = Arc::new(Mutex::new(HeapRb::new(RING_BUFFER_SIZE)));
-        let playback_buffer = Arc::new(Mutex::new(HeapRb::new(RING_BUFFER_SIZE)));
+
+        // Der Einzelanruf verwendet nur die Standard-Quelle
+        let mut mixer = PlaybackMixer::new(RING_BUFFER_SIZE);
+        mixer.add_source(DEFAULT_PLAYBACK_SOURCE);
+        mixer.set_prefill(DEFAULT_PREFILL_FRAMES * FRAME_SIZE);
+        let playback_mixer = Arc::new(Mutex::new(mixer));
 
         tracing::info!(
             "AudioHandler initialized: {}Hz, {} channel(s)",
@@ -105,18 +231,59 @@ impl AudioHandler {
         );
 
         Ok(Self {
+            input_device_name: input_device.as_ref().and_then(|d| d.name().ok()),
+            output_device_name: output_device.as_ref().and_then(|d| d.name().ok()),
             input_device,
             output_device,
             input_stream: None,
             output_stream: None,
+            input_config: None,
+            output_config: None,
             capture_buffer,
-            playback_buffer,
+            playback_mixer,
+            output_limiter: Arc::new(Mutex::new(OutputLimiter::default())),
+            frame_size: FRAME_SIZE,
             is_muted: Arc::new(Mutex::new(false)),
-            input_level: Arc::new(Mutex::new(0.0)),
-            output_level: Arc::new(Mutex::new(0.0)),
+            input_level: Arc::new(Mutex::new(LevelMeter::default())),
+            output_level: Arc::new(Mutex::new(LevelMeter::default())),
+            echo_recording: Arc::new(Mutex::new(None)),
+            output_delay: Arc::new(Mutex::new(DelayLine::new(0))),
+            vad: Mutex::new(VoiceActivityDetector::default()),
+            push_to_talk: Mutex::new(PushToTalk::default()),
         })
     }
 
+    /// Ermittelt die Stream-Konfigurationen vorab, ohne Streams zu starten
+    ///
+    /// Das Abfragen der unterstützten Konfigurationen ist je nach Treiber
+    /// langsam; mit gecachten Konfigurationen starten `start_capture` und
+    /// `start_playback` deutlich schneller.
+    pub fn warm_up(&mut self) -> Result<(), AudioError> {
+        if let Some(device) = &self.input_device {
+            self.input_config = Some(Self::find_best_input_config(device)?);
+        }
+        if let Some(device) = &self.output_device {
+            self.output_config = Some(Self::find_best_output_config(device)?);
+        }
+
+        tracing::debug!(
+            "Audio warmed up: input {:?}, output {:?}",
+            self.input_config,
+            self.output_config
+        );
+        Ok(())
+    }
+
+    /// Name des verwendeten Eingabegeräts
+    pub fn input_device_name(&self) -> Option<&str> {
+        self.input_device_name.as_deref()
+    }
+
+    /// Name des verwendeten Ausgabegeräts
+    pub fn output_device_name(&self) -> Option<&str> {
+        self.output_device_name.as_deref()
+    }
+
     /// Startet Audio Capture (Mikrofon)
     pub fn start_capture(&mut self) -> Result<(), AudioError> {
         let device = self
@@ -124,71 +291,37 @@ impl AudioHandler {
             .as_ref()
             .ok_or(AudioError::NoInputDevice)?;
 
-        // Beste Konfiguration finden
-        let config = Self::find_best_input_config(device)?;
+        // Beste Konfiguration finden (oder aus dem Warm-up übernehmen)
+        let config = match &self.input_config {
+            Some(config) => config.clone(),
+            None => Self::find_best_input_config(device)?,
+        };
 
         tracing::info!(
-            "Starting audio capture: {} Hz, {} channels",
-            config.sample_rate.0,
-            config.channels
+            "Starting audio capture: {} Hz, {} channels, {}",
+            config.stream.sample_rate.0,
+            config.stream.channels,
+            config.sample_format
         );
 
-        let capture_buffer = Arc::clone(&self.capture_buffer);
-        let is_muted = Arc::clone(&self.is_muted);
-        let input_level = Arc::clone(&self.input_level);
-        let target_sample_rate = SAMPLE_RATE;
-        let source_sample_rate = config.sample_rate.0;
-
-        let stream = device
-            .build_input_stream(
-                &config,
-                move |data: &[f32], _: &cpal::InputCallbackInfo| {
-                    let muted = *is_muted.lock();
-
-                    // Audio Level berechnen (RMS)
-                    let rms: f32 =
-                        (data.iter().map(|s| s * s).sum::<f32>() / data.len() as f32).sqrt();
-                    *input_level.lock() = rms.min(1.0);
-
-                    if muted {
-                        return;
-                    }
-
-                    // Resampling falls nötig (zu 48kHz)
-                    let samples: Vec<f32> = if source_sample_rate != target_sample_rate {
-                        // Einfaches Linear-Resampling
-                        let ratio = target_sample_rate as f32 / source_sample_rate as f32;
-                        let new_len = (data.len() as f32 * ratio) as usize;
-                        (0..new_len)
-                            .map(|i| {
-                                let src_idx = i as f32 / ratio;
-                                let idx = src_idx as usize;
-                                let frac = src_idx - idx as f32;
-                                let s1 = data.get(idx).copied().unwrap_or(0.0);
-                                let s2 = data.get(idx + 1).copied().unwrap_or(s1);
-                                s1 + (s2 - s1) * frac
-                            })
-                            .collect()
-                    } else {
-                        data.to_vec()
-                    };
-
-                    // In Ring-Buffer schreiben
-                    let mut buffer = capture_buffer.lock();
-                    for sample in samples {
-                        let _ = buffer.try_push(sample);
-                    }
-                },
-                |err| {
-                    tracing::error!("Audio capture error: {}", err);
-                },
-                None,
-            )
-            .map_err(|e| AudioError::StreamBuildError(e.to_string()))?;
-
-        stream
-            .play()
-            .map_err(|e| AudioError::StreamPlayError(e.to_string()))?;
+        let sink = CaptureSink {
+            capture_buffer: Arc::clone(&self.capture_buffer),
+            is_muted: Arc::clone(&self.is_muted),
+            input_level: Arc::clone(&self.input_level),
+            echo_recording: Arc::clone(&self.echo_recording),
+            output_delay: Arc::clone(&self.output_delay),
+            source_sample_rate: config.stream.sample_rate.0,
+            channels: config.stream.channels as usize,
+        };
+
+        let stream = match config.sample_format {
+            SampleFormat::F32 => Self::build_input_stream::<f32>(device, &config.stream, sink),
+            SampleFormat::I16 => Self::build_input_stream::<i16>(device, &config.stream, sink),
+            SampleFormat::U16 => Self::build_input_stream::<u16>(device, &config.stream, sink),
+            other => return Err(unsupported_sample_format(other)),
+        }?;
+
+        stream.play().map_err(input_play_error)?;
 
         self.input_stream = Some(stream);
         Ok(())
@@ -201,73 +334,190 @@ impl AudioHandler {
             .as_ref()
             .ok_or(AudioError::NoOutputDevice)?;
 
-        let config = Self::find_best_output_config(device)?;
+        let config = match &self.output_config {
+            Some(config) => config.clone(),
+            None => Self::find_best_output_config(device)?,
+        };
 
         tracing::info!(
-            "Starting audio playback: {} Hz, {} channels",
-            config.sample_rate.0,
-            config.channels
+            "Starting audio playback: {} Hz, {} channels, {}",
+            config.stream.sample_rate.0,
+            config.stream.channels,
+            config.sample_format
         );
 
-        let playback_buffer = Arc::clone(&self.playback_buffer);
-        let output_level = Arc::clone(&self.output_level);
-        let source_sample_rate = SAMPLE_RATE;
-        let target_sample_rate = config.sample_rate.0;
-        let channels = config.channels as usize;
+        let stream = Self::build_playback_stream(
+            device,
+            &config,
+            Arc::clone(&self.playback_mixer),
+            Arc::clone(&self.output_limiter),
+            Arc::clone(&self.output_level),
+        )?;
+
+        stream
+            .play()
+            .map_err(|e| AudioError::StreamPlayError(e.to_string()))?;
+
+        self.output_stream = Some(stream);
+        Ok(())
+    }
+
+    /// Spielt Samples (48kHz Mono) auf einem eigenen, kurzlebigen Output-Stream ab
+    ///
+    /// Verwendet Standardgerät und Konfiguration wie das Anruf-Playback, läuft
+    /// aber unabhängig davon: Ein aktiver Anruf wird nicht unterbrochen, das
+    /// System mischt beide Streams. Blockiert, bis alles abgespielt ist.
+    pub fn play_once(samples: &[f32]) -> Result<(), AudioError> {
+        if samples.is_empty() {
+            return Ok(());
+        }
+
+        let device = cpal::default_host()
+            .default_output_device()
+            .ok_or(AudioError::NoOutputDevice)?;
+        let config = Self::find_best_output_config(&device)?;
+
+        let mut mixer = PlaybackMixer::new(samples.len());
+        mixer.add_source(DEFAULT_PLAYBACK_SOURCE);
+        mixer.write(DEFAULT_PLAYBACK_SOURCE, samples);
+
+        let stream = Self::build_playback_stream(
+            &device,
+            &config,
+            Arc::new(Mutex::new(mixer)),
+            Arc::new(Mutex::new(OutputLimiter::default())),
+            Arc::new(Mutex::new(LevelMeter::default())),
+        )?;
+        stream
+            .play()
+            .map_err(|e| AudioError::StreamPlayError(e.to_string()))?;
+
+        let duration = Duration::from_secs_f32(samples.len() as f32 / SAMPLE_RATE as f32);
+        std::thread::sleep(duration + PLAY_ONCE_TAIL);
+        Ok(())
+    }
+
+    /// Spielt Samples (48kHz Mono) in Schleife ab, bis `stop` gesetzt wird
+    ///
+    /// Wie `play_once` auf einem eigenen Output-Stream, aber auf dem
+    /// ausgewählten Ausgabegerät (`None` = System-Standard). Blockiert bis zum
+    /// Stopp, der nach spätestens `PLAY_LOOPED_POLL` greift.
+    pub fn play_looped(
+        output_name: Option<&str>,
+        samples: &[f32],
+        stop: &AtomicBool,
+    ) -> Result<(), AudioError> {
+        if samples.is_empty() {
+            return Ok(());
+        }
+
+        let device = find_output_device(&cpal::default_host(), output_name)
+            .ok_or(AudioError::NoOutputDevice)?;
+        let config = Self::find_best_output_config(&device)?;
+
+        // Platz für zwei Durchläufe, der nächste wird vor dem Ende nachgelegt
+        let mut mixer = PlaybackMixer::new(samples.len() * 2);
+        mixer.add_source(DEFAULT_PLAYBACK_SOURCE);
+        mixer.write(DEFAULT_PLAYBACK_SOURCE, samples);
+        let mixer = Arc::new(Mutex::new(mixer));
+
+        let stream = Self::build_playback_stream(
+            &device,
+            &config,
+            Arc::clone(&mixer),
+            Arc::new(Mutex::new(OutputLimiter::default())),
+            Arc::new(Mutex::new(LevelMeter::default())),
+        )?;
+        stream
+            .play()
+            .map_err(|e| AudioError::StreamPlayError(e.to_string()))?;
+
+        let cycle = Duration::from_secs_f32(samples.len() as f32 / SAMPLE_RATE as f32);
+        let mut next_write = Instant::now() + cycle.saturating_sub(PLAY_LOOPED_LEAD);
+        while !stop.load(Ordering::Relaxed) {
+            std::thread::sleep(PLAY_LOOPED_POLL);
+            if Instant::now() >= next_write {
+                mixer.lock().write(DEFAULT_PLAYBACK_SOURCE, samples);
+                next_write += cycle;
+            }
+        }
+        Ok(())
+    }
+
+    /// Baut einen Playback-Stream, der aus dem gegebenen Mixer liest
+    fn build_playback_stream(
+        device: &Device,
+        config: &DeviceConfig,
+        playback_mixer: Arc<Mutex<PlaybackMixer>>,
+        output_limiter: Arc<Mutex<OutputLimiter>>,
+        output_level: Arc<Mutex<LevelMeter>>,
+    ) -> Result<Stream, AudioError> {
+        let source = PlaybackSource {
+            playback_mixer,
+            output_limiter,
+            output_level,
+            target_sample_rate: config.stream.sample_rate.0,
+            channels: config.stream.channels as usize,
+        };
+
+        match config.sample_format {
+            SampleFormat::F32 => Self::build_output_stream::<f32>(device, &config.stream, source),
+            SampleFormat::I16 => Self::build_output_stream::<i16>(device, &config.stream, source),
+            SampleFormat::U16 => Self::build_output_stream::<u16>(device, &config.stream, source),
+            other => Err(unsupported_sample_format(other)),
+        }
+    }
+
+    /// Baut den Capture-Stream für das native Sample-Format `T`
+    fn build_input_stream<T>(
+        device: &Device,
+        config: &StreamConfig,
+        sink: CaptureSink,
+    ) -> Result<Stream, AudioError>
+    where
+        T: SizedSample,
+        f32: FromSample<T>,
+    {
+        device
+            .build_input_stream(
+                config,
+                move |data: &[T], _: &cpal::InputCallbackInfo| {
+                    sink.push(&samples_to_f32(data));
+                },
+                |err| {
+                    tracing::error!("Audio capture error: {}", err);
+                },
+                None,
+            )
+            .map_err(input_build_error)
+    }
 
-        let stream = device
+    /// Baut den Playback-Stream für das native Sample-Format `T`
+    fn build_output_stream<T>(
+        device: &Device,
+        config: &StreamConfig,
+        source: PlaybackSource,
+    ) -> Result<Stream, AudioError>
+    where
+        T: SizedSample + FromSample<f32>,
+    {
+        // Zwischenpuffer wächst nur, wenn der Treiber größere Blöcke anfordert
+        let mut scratch: Vec<f32> = Vec::new();
+
+        device
             .build_output_stream(
-                &config,
-                move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
-                    let mut buffer = playback_buffer.lock();
-                    let mut level_sum = 0.0f32;
-                    let mut sample_count = 0;
-
-                    // Mono zu Stereo (falls nötig) und Resampling
-                    let samples_needed = data.len() / channels;
-                    let ratio = source_sample_rate as f32 / target_sample_rate as f32;
-                    let source_samples_needed = (samples_needed as f32 * ratio) as usize;
-
-                    for i in 0..samples_needed {
-                        // Source index berechnen
-                        let src_idx = (i as f32 * ratio) as usize;
-
-                        // Sample aus Buffer lesen
-                        let sample = if src_idx < source_samples_needed {
-                            buffer.try_pop().unwrap_or(0.0)
-                        } else {
-                            0.0
-                        };
-
-                        level_sum += sample.abs();
-                        sample_count += 1;
-
-                        // Auf alle Kanäle verteilen
-                        for c in 0..channels {
-                            if let Some(s) = data.get_mut(i * channels + c) {
-                                *s = sample;
-                            }
-                        }
-                    }
-
-                    // Level aktualisieren
-                    if sample_count > 0 {
-                        *output_level.lock() = (level_sum / sample_count as f32).min(1.0);
-                    }
+                config,
+                move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+                    scratch.resize(data.len(), 0.0);
+                    source.fill(&mut scratch);
+                    write_f32_samples(&scratch, data);
                 },
                 |err| {
                     tracing::error!("Audio playback error: {}", err);
                 },
                 None,
             )
-            .map_err(|e| AudioError::StreamBuildError(e.to_string()))?;
-
-        stream
-            .play()
-            .map_err(|e| AudioError::StreamPlayError(e.to_string()))?;
-
-        self.output_stream = Some(stream);
-        Ok(())
+            .map_err(|e| AudioError::StreamBuildError(e.to_string()))
     }
 
     /// Stoppt alle Audio-Streams
@@ -277,33 +527,171 @@ impl AudioHandler {
         tracing::info!("Audio streams stopped");
     }
 
+    /// Setzt die Frame-Größe in Samples (nur Opus-Größen, siehe `OPUS_FRAME_SIZES`)
+    ///
+    /// Gilt ab dem nächsten `read_frame`, bereits aufgenommenes Audio bleibt erhalten.
+    pub fn set_frame_size(&mut self, frame_size: usize) -> Result<(), AudioError> {
+        validate_frame_size(frame_size)?;
+        self.frame_size = frame_size;
+        Ok(())
+    }
+
+    /// Gibt die Frame-Größe in Samples zurück
+    pub fn frame_size(&self) -> usize {
+        self.frame_size
+    }
+
     /// Liest einen Frame von aufgenommenem Audio
+    ///
+    /// Mit aktivierter Sprachaktivitätserkennung kommt bei anhaltender Stille
+    /// ein leerer Frame (wie Opus DTX): Das Audio ist verbraucht, soll aber
+    /// nicht gesendet werden. Ebenso bei Push-to-Talk ohne gedrückte Taste.
     pub fn read_frame(&self) -> Option<Vec<f32>> {
-        let mut buffer = self.capture_buffer.lock();
-        if buffer.occupied_len() >= FRAME_SIZE {
-            let mut frame = Vec::with_capacity(FRAME_SIZE);
-            for _ in 0..FRAME_SIZE {
+        let frame = {
+            let mut buffer = self.capture_buffer.lock();
+            if buffer.occupied_len() < self.frame_size {
+                return None;
+            }
+            let mut frame = Vec::with_capacity(self.frame_size);
+            for _ in 0..self.frame_size {
                 if let Some(sample) = buffer.try_pop() {
                     frame.push(sample);
                 }
             }
+            frame
+        };
+
+        if !self.push_to_talk.lock().transmits() {
+            return Some(Vec::new());
+        }
+        if self.vad.lock().process(&frame) {
             Some(frame)
         } else {
-            None
+            Some(Vec::new())
         }
     }
 
-    /// Schreibt Audio-Samples in den Playback-Buffer
+    /// Aktiviert die Unterdrückung von Stille in `read_frame`
+    ///
+    /// `is_voice_active` wird auch ohne Unterdrückung ausgewertet.
+    pub fn set_vad_enabled(&self, enabled: bool) {
+        self.vad.lock().enabled = enabled;
+    }
+
+    /// Setzt die RMS-Schwelle, ab der ein Frame als Sprache gilt (0.0 - 1.0)
+    pub fn set_vad_threshold(&self, threshold: f32) -> Result<(), AudioError> {
+        validate_vad_threshold(threshold)?;
+        self.vad.lock().threshold = threshold;
+        Ok(())
+    }
+
+    /// Gibt zurück, ob zuletzt gesprochen wurde (inkl. Nachlaufzeit)
+    pub fn is_voice_active(&self) -> bool {
+        self.vad.lock().active
+    }
+
+    /// Aktiviert Push-to-Talk, deaktiviert wird sofort wieder gesendet
+    pub fn set_ptt_enabled(&self, enabled: bool) {
+        self.push_to_talk.lock().enabled = enabled;
+    }
+
+    /// Gibt zurück, ob Push-to-Talk aktiv ist
+    pub fn is_ptt_enabled(&self) -> bool {
+        self.push_to_talk.lock().enabled
+    }
+
+    /// Setzt, ob die Push-to-Talk-Taste gedrückt ist
+    pub fn set_ptt_active(&self, active: bool) {
+        self.push_to_talk.lock().active = active;
+    }
+
+    /// Gibt zurück, ob die Push-to-Talk-Taste gedrückt ist
+    pub fn is_ptt_active(&self) -> bool {
+        self.push_to_talk.lock().active
+    }
+
+    /// Schreibt Audio-Samples in die Standard-Quelle des Playback-Mixers
     pub fn write_samples(&self, samples: &[f32]) {
-        let mut buffer = self.playback_buffer.lock();
-        for sample in samples {
-            let _ = buffer.try_push(*sample);
+        self.playback_mixer
+            .lock()
+            .write(DEFAULT_PLAYBACK_SOURCE, samples);
+    }
+
+    /// Fügt eine benannte Playback-Quelle hinzu (z.B. ein weiterer Peer)
+    ///
+    /// Gibt `false` zurück, wenn die Quelle bereits existiert.
+    pub fn add_playback_source(&self, id: &str) -> bool {
+        self.playback_mixer.lock().add_source(id)
+    }
+
+    /// Schreibt Audio-Samples in eine benannte Playback-Quelle
+    pub fn write_samples_for(&self, id: &str, samples: &[f32]) -> Result<(), AudioError> {
+        if self.playback_mixer.lock().write(id, samples) {
+            Ok(())
+        } else {
+            Err(AudioError::UnknownSource(id.to_string()))
         }
     }
 
+    /// Entfernt eine Playback-Quelle samt ungespielter Samples
+    pub fn remove_playback_source(&self, id: &str) -> bool {
+        self.playback_mixer.lock().remove_source(id)
+    }
+
+    /// Setzt die Verstärkung einer Playback-Quelle
+    pub fn set_source_gain(&self, id: &str, gain: f32) -> Result<(), AudioError> {
+        if self.playback_mixer.lock().set_gain(id, gain) {
+            Ok(())
+        } else {
+            Err(AudioError::UnknownSource(id.to_string()))
+        }
+    }
+
+    /// Setzt die Vorpufferung des Playbacks in Frames
+    ///
+    /// Mehr Frames verzögern den Start, verhindern aber Aussetzer, wenn die
+    /// ersten Pakete ungleichmäßig eintreffen. Ist bereits genug gepuffert,
+    /// läuft ein aktives Playback ohne Unterbrechung weiter.
+    pub fn set_prefill_frames(&self, frames: usize) -> Result<(), AudioError> {
+        validate_prefill_frames(frames)?;
+        self.playback_mixer.lock().set_prefill(frames * FRAME_SIZE);
+        Ok(())
+    }
+
+    /// Verzögert das gesendete Audio um eine feste Zeit (0 = keine Verzögerung)
+    ///
+    /// Für Tests des Jitter Buffers und zum Angleichen an andere Audio-Wege.
+    /// Beim Ändern wird die Verzögerungsleitung mit Stille neu gefüllt.
+    pub fn set_output_delay(&self, delay_ms: u32) -> Result<(), AudioError> {
+        validate_output_delay(delay_ms)?;
+        self.output_delay
+            .lock()
+            .set_delay(samples_for(Duration::from_millis(delay_ms as u64)));
+        Ok(())
+    }
+
+    /// Setzt den Limiter des Playbacks (gilt ab dem nächsten Audio-Block)
+    pub fn set_output_limiter(&self, config: OutputLimiterConfig) {
+        self.output_limiter.lock().set_config(config);
+    }
+
     /// Setzt den Mute-Status
+    ///
+    /// Stummgeschaltet zeigt der Input-Pegel sofort 0, statt langsam abzufallen.
+    /// Noch nicht gelesenes Audio wird verworfen, danach liefert die Aufnahme
+    /// nur noch Stille.
     pub fn set_muted(&self, muted: bool) {
-        *self.is_muted.lock() = muted;
+        {
+            // Unter dem Buffer-Lock, siehe `CaptureSink::write`
+            let mut buffer = self.capture_buffer.lock();
+            *self.is_muted.lock() = muted;
+            if muted {
+                buffer.clear();
+            }
+        }
+        if muted {
+            self.input_level.lock().reset();
+        }
         tracing::debug!("Audio muted: {}", muted);
     }
 
@@ -312,13 +700,41 @@ impl AudioHandler {
         *self.is_muted.lock()
     }
 
+    /// Spielt das Prüfsignal des Echo-Checks und schneidet das Mikrofon mit
+    ///
+    /// Das Signal läuft über eine eigene, leise Mixer-Quelle und damit parallel
+    /// zum Gespräch; der Mitschnitt zweigt nur ab, das Mikrofon-Audio geht
+    /// unverändert an die Gegenstelle. Den Mitschnitt liefert `finish_echo_check`.
+    pub fn start_echo_check(&self) {
+        *self.echo_recording.lock() = Some(Vec::new());
+
+        let mut mixer = self.playback_mixer.lock();
+        mixer.add_source(ECHO_CHECK_SOURCE);
+        mixer.write(ECHO_CHECK_SOURCE, &echo_probe());
+    }
+
+    /// Beendet den Echo-Check und gibt den Mitschnitt zurück
+    ///
+    /// `None`, wenn nichts mitgeschnitten wurde (z.B. stummgeschaltet) oder
+    /// kein Check lief. Ausgewertet wird mit `echo_correlation`.
+    pub fn finish_echo_check(&self) -> Option<Vec<f32>> {
+        self.playback_mixer.lock().remove_source(ECHO_CHECK_SOURCE);
+        self.echo_recording
+            .lock()
+            .take()
+            .filter(|recording| !recording.is_empty())
+    }
+
     /// Gibt die Audio-Levels zurück (input, output)
     pub fn get_levels(&self) -> (f32, f32) {
-        (*self.input_level.lock(), *self.output_level.lock())
+        (
+            self.input_level.lock().level(),
+            self.output_level.lock().level(),
+        )
     }
 
     /// Findet die beste Input-Konfiguration
-    fn find_best_input_config(device: &Device) -> Result<StreamConfig, AudioError> {
+    fn find_best_input_config(device: &Device) -> Result<DeviceConfig, AudioError> {
         let configs = device
             .supported_input_configs()
             .map_err(|e| AudioError::UnsupportedConfig(e.to_string()))?;
@@ -327,7 +743,7 @@ impl AudioHandler {
     }
 
     /// Findet die beste Output-Konfiguration
-    fn find_best_output_config(device: &Device) -> Result<StreamConfig, AudioError> {
+    fn find_best_output_config(device: &Device) -> Result<DeviceConfig, AudioError> {
         let configs = device
             .supported_output_configs()
             .map_err(|e| AudioError::UnsupportedConfig(e.to_string()))?;
@@ -338,43 +754,579 @@ impl AudioHandler {
     /// Wählt die beste Konfiguration aus einer Liste
     fn select_best_config(
         configs: Vec<SupportedStreamConfigRange>,
-    ) -> Result<StreamConfig, AudioError> {
-        // Priorität: 48kHz > 44.1kHz > andere, F32 > I16 > andere
+    ) -> Result<DeviceConfig, AudioError> {
+        // Priorität: 48kHz vor anderen Raten, innerhalb davon F32 > I16 > U16
         let target_rate = cpal::SampleRate(SAMPLE_RATE);
+        let supports_target_rate = |config: &SupportedStreamConfigRange| {
+            config.min_sample_rate() <= target_rate && config.max_sample_rate() >= target_rate
+        };
+        let with_format = |format: SampleFormat| {
+            configs
+                .iter()
+                .filter(move |config| config.sample_format() == format)
+        };
 
         // Versuche exakt 48kHz zu finden
-        for config in &configs {
-            if config.min_sample_rate() <= target_rate
-                && config.max_sample_rate() >= target_rate
-                && config.sample_format() == SampleFormat::F32
-            {
-                return Ok(config.with_sample_rate(target_rate).into());
+        for format in SUPPORTED_SAMPLE_FORMATS {
+            if let Some(config) = with_format(format).find(|c| supports_target_rate(c)) {
+                return Ok(DeviceConfig {
+                    stream: config.with_sample_rate(target_rate).into(),
+                    sample_format: format,
+                });
+            }
+        }
+
+        // Fallback auf höchste verfügbare Rate im besten Format
+        for format in SUPPORTED_SAMPLE_FORMATS {
+            if let Some(config) = with_format(format).next() {
+                return Ok(DeviceConfig {
+                    stream: config.with_max_sample_rate().into(),
+                    sample_format: format,
+                });
+            }
+        }
+
+        Err(AudioError::UnsupportedConfig(match configs.first() {
+            Some(config) => format!(
+                "No supported sample format (device offers {})",
+                config.sample_format()
+            ),
+            None => "No suitable audio configuration found".to_string(),
+        }))
+    }
+}
+
+// ============================================================================
+// STREAM CALLBACKS
+// ============================================================================
+
+/// Zustand des Capture-Callbacks (arbeitet auf f32-Samples)
+struct CaptureSink {
+    capture_buffer: Arc<Mutex<HeapRb<f32>>>,
+    is_muted: Arc<Mutex<bool>>,
+    input_level: Arc<Mutex<LevelMeter>>,
+    echo_recording: Arc<Mutex<Option<Vec<f32>>>>,
+    output_delay: Arc<Mutex<DelayLine>>,
+    source_sample_rate: u32,
+    channels: usize,
+}
+
+impl CaptureSink {
+    /// Misst den Pegel, resampelt auf 48kHz und schreibt in den Ring-Buffer
+    ///
+    /// Stummgeschaltet wird nicht gemessen, der Pegel bleibt bei 0. Statt des
+    /// Audios kommt gleich viel Stille im Ring-Buffer an, damit der Sende-Takt
+    /// weiterläuft. Noch verzögertes Audio wird dabei verworfen.
+    fn push(&self, data: &[f32]) {
+        if *self.is_muted.lock() {
+            self.input_level.lock().reset();
+            self.output_delay.lock().reset();
+            self.write(vec![0.0; self.resampled_len(data.len())]);
+            return;
+        }
+
+        // Audio Level berechnen (RMS)
+        if !data.is_empty() {
+            let block = block_duration(data.len() / self.channels, self.source_sample_rate);
+            self.input_level.lock().update(rms(data), block);
+        }
+
+        // Resampling falls nötig (zu 48kHz)
+        let target_sample_rate = SAMPLE_RATE;
+        let samples: Vec<f32> = if self.source_sample_rate != target_sample_rate {
+            // Einfaches Linear-Resampling
+            let ratio = target_sample_rate as f32 / self.source_sample_rate as f32;
+            (0..self.resampled_len(data.len()))
+                .map(|i| {
+                    let src_idx = i as f32 / ratio;
+                    let idx = src_idx as usize;
+                    let frac = src_idx - idx as f32;
+                    let s1 = data.get(idx).copied().unwrap_or(0.0);
+                    let s2 = data.get(idx + 1).copied().unwrap_or(s1);
+                    s1 + (s2 - s1) * frac
+                })
+                .collect()
+        } else {
+            data.to_vec()
+        };
+
+        // Während eines Echo-Checks zusätzlich mitschneiden
+        if let Some(recording) = self.echo_recording.lock().as_mut() {
+            let room = echo_recording_len().saturating_sub(recording.len());
+            recording.extend(samples.iter().take(room));
+        }
+
+        // Optionale Verzögerung, danach in Ring-Buffer schreiben
+        let samples = self.output_delay.lock().process(samples);
+        self.write(samples);
+    }
+
+    /// Anzahl der Samples mit `SAMPLE_RATE` für `len` Samples der Quelle
+    fn resampled_len(&self, len: usize) -> usize {
+        if self.source_sample_rate == SAMPLE_RATE {
+            return len;
+        }
+        (len as f32 * SAMPLE_RATE as f32 / self.source_sample_rate as f32) as usize
+    }
+
+    /// Schreibt Samples in den Ring-Buffer, stummgeschaltet nur Stille
+    ///
+    /// Der Mute-Status wird unter dem Buffer-Lock erneut geprüft: `set_muted`
+    /// leert den Buffer unter demselben Lock, ein währenddessen aktiviertes
+    /// Mute lässt so keinen Rest des Blocks durch.
+    fn write(&self, samples: Vec<f32>) {
+        let mut buffer = self.capture_buffer.lock();
+        let muted = *self.is_muted.lock();
+        for sample in samples {
+            let _ = buffer.try_push(if muted { 0.0 } else { sample });
+        }
+    }
+}
+
+/// Zustand des Playback-Callbacks (arbeitet auf f32-Samples)
+struct PlaybackSource {
+    playback_mixer: Arc<Mutex<PlaybackMixer>>,
+    output_limiter: Arc<Mutex<OutputLimiter>>,
+    output_level: Arc<Mutex<LevelMeter>>,
+    target_sample_rate: u32,
+    channels: usize,
+}
+
+impl PlaybackSource {
+    /// Füllt einen interleaved Ausgabe-Block aus dem Mixer
+    fn fill(&self, data: &mut [f32]) {
+        let mut mixer = self.playback_mixer.lock();
+        let mut limiter = self.output_limiter.lock();
+        let mut level_sum = 0.0f32;
+        let mut sample_count = 0;
+
+        // Mono zu Stereo (falls nötig) und Resampling
+        let samples_needed = data.len() / self.channels;
+        let ratio = SAMPLE_RATE as f32 / self.target_sample_rate as f32;
+        let source_samples_needed = (samples_needed as f32 * ratio) as usize;
+
+        for i in 0..samples_needed {
+            // Source index berechnen
+            let src_idx = (i as f32 * ratio) as usize;
+
+            // Gemischtes Sample aus allen Quellen lesen und begrenzen
+            let sample = if src_idx < source_samples_needed {
+                limiter.process(mixer.next_sample())
+            } else {
+                0.0
+            };
+
+            level_sum += sample.abs();
+            sample_count += 1;
+
+            // Auf alle Kanäle verteilen
+            for c in 0..self.channels {
+                if let Some(s) = data.get_mut(i * self.channels + c) {
+                    *s = sample;
+                }
             }
         }
 
-        // Fallback auf beste verfügbare Konfiguration
-        for config in &configs {
-            if config.sample_format() == SampleFormat::F32 {
-                let rate = if config.min_sample_rate() <= target_rate
-                    && config.max_sample_rate() >= target_rate
-                {
-                    target_rate
-                } else {
-                    config.max_sample_rate()
-                };
-                return Ok(config.with_sample_rate(rate).into());
+        // Level aktualisieren
+        if sample_count > 0 {
+            let block = block_duration(sample_count, self.target_sample_rate);
+            self.output_level
+                .lock()
+                .update(level_sum / sample_count as f32, block);
+        }
+    }
+}
+
+// ============================================================================
+// VOICE ACTIVITY DETECTION
+// ============================================================================
+
+/// Stille, nach der die Sprachaktivitätserkennung abschaltet
+///
+/// Überbrückt Pausen zwischen Wörtern, damit leise Silbenenden nicht
+/// abgeschnitten werden.
+const VAD_HANGOVER: Duration = Duration::from_millis(300);
+
+/// Energiebasierte Sprachaktivitätserkennung
+///
+/// Ein Frame gilt als Sprache, wenn sein RMS die Schwelle erreicht. Still
+/// wird es erst nach `VAD_HANGOVER` ohne Sprache, unabhängig von der
+/// Frame-Größe.
+#[derive(Debug, Clone, Copy)]
+struct VoiceActivityDetector {
+    /// Stille unterdrücken (sonst wird nur erkannt)
+    enabled: bool,
+    threshold: f32,
+    /// Samples seit dem letzten Frame mit Sprache
+    silent_samples: usize,
+    active: bool,
+}
+
+impl Default for VoiceActivityDetector {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold: DEFAULT_VAD_THRESHOLD,
+            silent_samples: 0,
+            active: false,
+        }
+    }
+}
+
+impl VoiceActivityDetector {
+    /// Wertet einen Frame aus und gibt zurück, ob er gesendet werden soll
+    fn process(&mut self, frame: &[f32]) -> bool {
+        if rms(frame) >= self.threshold {
+            self.silent_samples = 0;
+            self.active = true;
+        } else {
+            self.silent_samples += frame.len();
+            if self.silent_samples >= samples_for(VAD_HANGOVER) {
+                self.active = false;
             }
         }
+        self.active || !self.enabled
+    }
+}
+
+// ============================================================================
+// PUSH-TO-TALK
+// ============================================================================
+
+/// Push-to-Talk-Zustand für `read_frame`
+#[derive(Debug, Clone, Copy, Default)]
+struct PushToTalk {
+    enabled: bool,
+    /// Taste gedrückt
+    active: bool,
+}
+
+impl PushToTalk {
+    /// Gibt zurück, ob aufgenommene Frames gesendet werden
+    fn transmits(&self) -> bool {
+        !self.enabled || self.active
+    }
+}
+
+// ============================================================================
+// LEVEL METER
+// ============================================================================
+
+/// Anstiegszeit der Pegelanzeige (Spitzen sind sofort sichtbar)
+const LEVEL_ATTACK: Duration = Duration::from_millis(10);
+
+/// Abfallzeit der Pegelanzeige (sinkt langsam wie bei einem VU-Meter)
+const LEVEL_RELEASE: Duration = Duration::from_millis(300);
+
+/// Pegel mit schnellem Anstieg und langsamem Abfall
+///
+/// Die Rohwerte pro Callback springen stark, geglättet wirkt die Anzeige
+/// ruhig. Die Glättung rechnet mit der Dauer des Blocks, damit sie nicht
+/// von der Puffergröße des Treibers abhängt.
+#[derive(Debug, Default, Clone, Copy)]
+struct LevelMeter {
+    level: f32,
+}
+
+impl LevelMeter {
+    /// Nimmt den Rohpegel eines Blocks auf
+    fn update(&mut self, raw: f32, block: Duration) {
+        let raw = raw.clamp(0.0, 1.0);
+        let time_constant = if raw > self.level {
+            LEVEL_ATTACK
+        } else {
+            LEVEL_RELEASE
+        };
+        let coefficient = 1.0 - (-block.as_secs_f32() / time_constant.as_secs_f32()).exp();
+        self.level += (raw - self.level) * coefficient;
+    }
+
+    /// Gibt den geglätteten Pegel zurück
+    fn level(&self) -> f32 {
+        self.level
+    }
+
+    /// Setzt den Pegel ohne Ausklingen auf 0
+    fn reset(&mut self) {
+        self.level = 0.0;
+    }
+}
+
+/// Effektivwert eines Blocks (0.0 für einen leeren Block)
+fn rms(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+}
+
+/// Dauer eines Blocks mit `frames` Samples pro Kanal
+/// Sucht das Eingabegerät mit diesem Namen, sonst das Standardgerät
+fn find_input_device(host: &cpal::Host, name: Option<&str>) -> Option<Device> {
+    if let Some(name) = name {
+        match host.input_devices() {
+            Ok(mut devices) => {
+                if let Some(device) = devices.find(|d| d.name().is_ok_and(|n| n == name)) {
+                    return Some(device);
+                }
+                tracing::warn!("Audio input device {:?} not found, using default", name);
+            }
+            Err(e) => tracing::warn!("Failed to list audio input devices: {}", e),
+        }
+    }
+    host.default_input_device()
+}
+
+/// Sucht das Ausgabegerät mit diesem Namen, sonst das Standardgerät
+fn find_output_device(host: &cpal::Host, name: Option<&str>) -> Option<Device> {
+    if let Some(name) = name {
+        match host.output_devices() {
+            Ok(mut devices) => {
+                if let Some(device) = devices.find(|d| d.name().is_ok_and(|n| n == name)) {
+                    return Some(device);
+                }
+                tracing::warn!("Audio output device {:?} not found, using default", name);
+            }
+            Err(e) => tracing::warn!("Failed to list audio output devices: {}", e),
+        }
+    }
+    host.default_output_device()
+}
+
+fn block_duration(frames: usize, sample_rate: u32) -> Duration {
+    Duration::from_secs_f32(frames as f32 / sample_rate.max(1) as f32)
+}
+
+// ============================================================================
+// DELAY LINE
+// ============================================================================
+
+/// Verzögerungsleitung mit fester Länge (48kHz)
+///
+/// Gibt genauso viele Samples aus, wie hineingehen; die ersten `delay`
+/// Samples sind Stille.
+#[derive(Debug)]
+struct DelayLine {
+    delay: usize,
+    buffer: VecDeque<f32>,
+}
+
+impl DelayLine {
+    fn new(delay: usize) -> Self {
+        let mut line = Self {
+            delay,
+            buffer: VecDeque::new(),
+        };
+        line.reset();
+        line
+    }
+
+    fn set_delay(&mut self, delay: usize) {
+        self.delay = delay;
+        self.reset();
+    }
+
+    /// Füllt die Leitung mit Stille
+    fn reset(&mut self) {
+        self.buffer.clear();
+        self.buffer.resize(self.delay, 0.0);
+    }
+
+    fn process(&mut self, samples: Vec<f32>) -> Vec<f32> {
+        if self.delay == 0 {
+            return samples;
+        }
+        let count = samples.len();
+        self.buffer.extend(samples);
+        self.buffer.drain(..count).collect()
+    }
+}
+
+// ============================================================================
+// ECHO CHECK
+// ============================================================================
+
+/// Start- und Endfrequenz des Prüfsignals
+const ECHO_PROBE_SWEEP_HZ: (f32, f32) = (600.0, 2400.0);
+
+/// Amplitude des Prüfsignals (leise, um das Gespräch nicht zu stören)
+const ECHO_PROBE_AMPLITUDE: f32 = 0.15;
+
+/// Mindestenergie des Mitschnitts, darunter gilt er als Stille
+const ECHO_MIN_ENERGY: f32 = 1e-6;
+
+/// Erzeugt das Prüfsignal: ein kurzer Sweep mit weichen Flanken (48kHz)
+///
+/// Ein Sweep korreliert im Gegensatz zu einem reinen Sinus nur bei passender
+/// Verzögerung stark, Sprache am Mikrofon erzeugt kaum Fehlalarme.
+pub fn echo_probe() -> Vec<f32> {
+    let len = samples_for(ECHO_PROBE_DURATION);
+    let fade = len / 10;
+    let (start_hz, end_hz) = ECHO_PROBE_SWEEP_HZ;
+    let duration = ECHO_PROBE_DURATION.as_secs_f32();
+
+    (0..len)
+        .map(|i| {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            // Phase eines linearen Sweeps
+            let phase = start_hz * t + (end_hz - start_hz) * t * t / (2.0 * duration);
+            let envelope = (i.min(len - 1 - i) as f32 / fade as f32).min(1.0);
+            (2.0 * std::f32::consts::PI * phase).sin() * envelope * ECHO_PROBE_AMPLITUDE
+        })
+        .collect()
+}
+
+/// Maximale normierte Kreuzkorrelation zwischen Prüfsignal und Mitschnitt
+///
+/// Sucht über alle Verzögerungen, bei denen das Signal vollständig im
+/// Mitschnitt liegt. Unabhängig von der Lautstärke des Echos: 1.0 bedeutet,
+/// dass der Ausschnitt ein skaliertes Abbild des Prüfsignals ist.
+pub fn echo_correlation(probe: &[f32], recording: &[f32]) -> f32 {
+    if probe.is_empty() || recording.len() < probe.len() {
+        return 0.0;
+    }
+    let probe_energy: f32 = probe.iter().map(|s| s * s).sum();
+
+    // Energie des Ausschnitts gleitend mitführen
+    let mut window_energy: f32 = recording[..probe.len()].iter().map(|s| s * s).sum();
+    let mut best = 0.0f32;
+
+    for lag in 0..=recording.len() - probe.len() {
+        if lag > 0 {
+            let leaving = recording[lag - 1];
+            let entering = recording[lag + probe.len() - 1];
+            window_energy = (window_energy - leaving * leaving + entering * entering).max(0.0);
+        }
+        if window_energy < ECHO_MIN_ENERGY {
+            continue;
+        }
+
+        let dot: f32 = probe
+            .iter()
+            .zip(&recording[lag..])
+            .map(|(p, r)| p * r)
+            .sum();
+        best = best.max(dot / (probe_energy * window_energy).sqrt());
+    }
+    best.min(1.0)
+}
+
+/// Länge des Mitschnitts: Prüfsignal plus maximale Verzögerung
+fn echo_recording_len() -> usize {
+    samples_for(ECHO_PROBE_DURATION + ECHO_MAX_DELAY)
+}
+
+/// Anzahl Samples bei 48kHz für eine Dauer
+fn samples_for(duration: Duration) -> usize {
+    (duration.as_secs_f64() * SAMPLE_RATE as f64) as usize
+}
+
+// ============================================================================
+// SAMPLE CONVERSION
+// ============================================================================
+
+/// Wandelt Samples im nativen Geräteformat in f32 (-1.0 bis 1.0)
+pub fn samples_to_f32<T>(data: &[T]) -> Vec<f32>
+where
+    T: Sample,
+    f32: FromSample<T>,
+{
+    data.iter()
+        .map(|sample| sample.to_sample::<f32>())
+        .collect()
+}
 
-        // Nehme erste verfügbare Konfiguration
-        if let Some(config) = configs.first() {
-            return Ok(config.with_max_sample_rate().into());
+/// Schreibt f32-Samples im nativen Geräteformat
+///
+/// Werte außerhalb von -1.0 bis 1.0 werden begrenzt, damit Ganzzahl-Formate
+/// nicht überlaufen.
+pub fn write_f32_samples<T>(samples: &[f32], out: &mut [T])
+where
+    T: Sample + FromSample<f32>,
+{
+    for (out, sample) in out.iter_mut().zip(samples) {
+        *out = sample.clamp(-1.0, 1.0).to_sample::<T>();
+    }
+}
+
+/// HRESULT `AUDCLNT_E_DEVICE_IN_USE`: WASAPI-Gerät exklusiv von einer anderen App belegt
+const WASAPI_DEVICE_IN_USE: &str = "0x8889000a";
+
+/// Ordnet Fehler beim Öffnen des Mikrofons ein
+///
+/// Eine exklusive Belegung durch eine andere App soll nicht als
+/// unverständlicher Treiberfehler beim Nutzer ankommen. ALSA meldet `EBUSY`
+/// als `DeviceNotAvailable`, WASAPI und CoreAudio (Hog Mode) nur als
+/// backend-spezifischen Text.
+fn input_build_error(error: cpal::BuildStreamError) -> AudioError {
+    match error {
+        cpal::BuildStreamError::DeviceNotAvailable if cfg!(target_os = "linux") => {
+            AudioError::DeviceBusy
         }
+        cpal::BuildStreamError::BackendSpecific { ref err }
+            if is_device_busy_message(&err.description) =>
+        {
+            AudioError::DeviceBusy
+        }
+        other => AudioError::StreamBuildError(other.to_string()),
+    }
+}
+
+/// Ordnet Fehler beim Starten des Mikrofon-Streams ein (siehe `input_build_error`)
+fn input_play_error(error: cpal::PlayStreamError) -> AudioError {
+    match error {
+        cpal::PlayStreamError::BackendSpecific { ref err }
+            if is_device_busy_message(&err.description) =>
+        {
+            AudioError::DeviceBusy
+        }
+        other => AudioError::StreamPlayError(other.to_string()),
+    }
+}
+
+/// Erkennt Fehlertexte der Backends, die auf ein belegtes Gerät hinweisen
+fn is_device_busy_message(description: &str) -> bool {
+    let description = description.to_ascii_lowercase();
+    description.contains(WASAPI_DEVICE_IN_USE)
+        || description.contains("device or resource busy")
+        || description.contains("hog mode")
+}
+
+/// Fehler für Geräte mit einem Sample-Format, das nicht umgewandelt wird
+fn unsupported_sample_format(format: SampleFormat) -> AudioError {
+    AudioError::UnsupportedConfig(format!("Unsupported sample format: {}", format))
+}
+
+/// Prüft, ob eine Vorpufferung in den Playback-Buffer passt
+pub fn validate_prefill_frames(frames: usize) -> Result<(), AudioError> {
+    if frames > MAX_PREFILL_FRAMES {
+        return Err(AudioError::InvalidPrefill(frames));
+    }
+    Ok(())
+}
+
+/// Prüft, ob eine Verzögerung des gesendeten Audios erlaubt ist
+pub fn validate_output_delay(delay_ms: u32) -> Result<(), AudioError> {
+    if delay_ms > MAX_OUTPUT_DELAY_MS {
+        return Err(AudioError::InvalidOutputDelay(delay_ms));
+    }
+    Ok(())
+}
 
-        Err(AudioError::UnsupportedConfig(
-            "No suitable audio configuration found".to_string(),
-        ))
+/// Prüft, ob eine Schwelle der Sprachaktivitätserkennung gültig ist
+pub fn validate_vad_threshold(threshold: f32) -> Result<(), AudioError> {
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err(AudioError::InvalidVadThreshold(threshold));
     }
+    Ok(())
+}
+
+/// Prüft, ob Opus Frames dieser Größe kodieren kann
+pub fn validate_frame_size(frame_size: usize) -> Result<(), AudioError> {
+    if !OPUS_FRAME_SIZES.contains(&frame_size) {
+        return Err(AudioError::InvalidFrameSize(frame_size));
+    }
+    Ok(())
 }
 
 impl Default for AudioHandler {
@@ -382,3 +1334,354 @@ impl Default for AudioHandler {
         Self::new().expect("Failed to create AudioHandler")
     }
 }
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_busy_microphone_is_reported_as_device_busy() {
+        let backend = |description: &str| cpal::BuildStreamError::BackendSpecific {
+            err: cpal::BackendSpecificError {
+                description: description.to_string(),
+            },
+        };
+
+        // WASAPI: windows::core::Error mit HRESULT im Text
+        assert!(matches!(
+            input_build_error(backend(
+                "The device is being used by another application. (0x8889000A)"
+            )),
+            AudioError::DeviceBusy
+        ));
+        assert!(matches!(
+            input_build_error(backend("Device or resource busy")),
+            AudioError::DeviceBusy
+        ));
+        assert!(matches!(
+            input_build_error(backend("Invalid sample rate")),
+            AudioError::StreamBuildError(_)
+        ));
+        assert!(matches!(
+            input_build_error(cpal::BuildStreamError::StreamConfigNotSupported),
+            AudioError::StreamBuildError(_)
+        ));
+    }
+
+    #[test]
+    fn test_level_meter_attacks_fast_and_releases_slowly() {
+        let block = Duration::from_millis(20);
+        let mut meter = LevelMeter::default();
+
+        // Ein lauter Block ist sofort fast vollständig sichtbar
+        meter.update(0.8, block);
+        assert!(meter.level() > 0.65);
+        meter.update(0.8, block);
+        assert!((meter.level() - 0.8).abs() < 0.02);
+
+        // Stille lässt den Pegel nur langsam fallen
+        meter.update(0.0, block);
+        assert!(meter.level() > 0.7);
+        for _ in 0..50 {
+            meter.update(0.0, block);
+        }
+        assert!(meter.level() < 0.05);
+
+        // Übersteuerte Rohwerte werden begrenzt
+        for _ in 0..10 {
+            meter.update(3.0, block);
+        }
+        assert!(meter.level() <= 1.0);
+    }
+
+    #[test]
+    fn test_muted_capture_reports_zero_level() {
+        let sink = CaptureSink {
+            capture_buffer: Arc::new(Mutex::new(HeapRb::new(RING_BUFFER_SIZE))),
+            is_muted: Arc::new(Mutex::new(false)),
+            input_level: Arc::new(Mutex::new(LevelMeter::default())),
+            echo_recording: Arc::new(Mutex::new(None)),
+            output_delay: Arc::new(Mutex::new(DelayLine::new(0))),
+            source_sample_rate: SAMPLE_RATE,
+            channels: 1,
+        };
+
+        sink.push(&[0.5; FRAME_SIZE]);
+        assert!(sink.input_level.lock().level() > 0.4);
+        assert_eq!(sink.capture_buffer.lock().occupied_len(), FRAME_SIZE);
+
+        // Stummgeschaltet: Pegel sofort 0, statt Audio kommt Stille an
+        *sink.is_muted.lock() = true;
+        sink.push(&[0.5; FRAME_SIZE]);
+        assert_eq!(sink.input_level.lock().level(), 0.0);
+        let buffer = sink.capture_buffer.lock();
+        assert_eq!(buffer.occupied_len(), 2 * FRAME_SIZE);
+        assert!(buffer.iter().skip(FRAME_SIZE).all(|sample| *sample == 0.0));
+    }
+
+    #[test]
+    fn test_muting_clears_capture_buffer() {
+        let audio = AudioHandler::new().unwrap();
+        let sink = CaptureSink {
+            capture_buffer: Arc::clone(&audio.capture_buffer),
+            is_muted: Arc::clone(&audio.is_muted),
+            input_level: Arc::clone(&audio.input_level),
+            echo_recording: Arc::clone(&audio.echo_recording),
+            output_delay: Arc::clone(&audio.output_delay),
+            source_sample_rate: SAMPLE_RATE,
+            channels: 1,
+        };
+
+        // Anderthalb Frames Sprache warten auf den Encoder
+        sink.push(&[0.5; FRAME_SIZE + FRAME_SIZE / 2]);
+        audio.set_muted(true);
+        assert_eq!(audio.capture_buffer.lock().occupied_len(), 0);
+        assert_eq!(audio.read_frame(), None);
+
+        // Stummgeschaltet laufen nur noch Stille-Frames durch
+        sink.push(&[0.5; FRAME_SIZE]);
+        assert_eq!(audio.read_frame(), Some(vec![0.0; FRAME_SIZE]));
+
+        // Schnelles Umschalten: kein Rest des Audios von vor dem Mute
+        sink.push(&[0.5; FRAME_SIZE / 2]);
+        audio.set_muted(false);
+        audio.set_muted(true);
+        audio.set_muted(false);
+        sink.push(&[0.25; FRAME_SIZE]);
+        assert_eq!(audio.read_frame(), Some(vec![0.25; FRAME_SIZE]));
+        assert_eq!(audio.read_frame(), None);
+    }
+
+    #[test]
+    fn test_echo_correlation_detects_delayed_probe() {
+        let probe = echo_probe();
+        assert_eq!(probe.len(), samples_for(ECHO_PROBE_DURATION));
+        assert!(probe.len() <= RING_BUFFER_SIZE);
+
+        // Leises Echo nach 120ms, überlagert von Rauschen
+        let delay = samples_for(Duration::from_millis(120));
+        let mut noise_state = 1u32;
+        let mut recording: Vec<f32> = (0..echo_recording_len())
+            .map(|_| {
+                noise_state = noise_state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                ((noise_state >> 16) as f32 / 32768.0 - 1.0) * 0.005
+            })
+            .collect();
+        for (i, sample) in probe.iter().enumerate() {
+            recording[delay + i] += sample * 0.3;
+        }
+        assert!(echo_correlation(&probe, &recording) > ECHO_CORRELATION_THRESHOLD);
+
+        // Ohne Echo: Stille bzw. ein anderer Ton
+        recording.fill(0.0);
+        assert_eq!(echo_correlation(&probe, &recording), 0.0);
+        let tone: Vec<f32> = (0..echo_recording_len())
+            .map(|i| (i as f32 * 2.0 * std::f32::consts::PI * 220.0 / SAMPLE_RATE as f32).sin())
+            .collect();
+        assert!(echo_correlation(&probe, &tone) < ECHO_CORRELATION_THRESHOLD);
+    }
+
+    #[test]
+    fn test_echo_check_records_without_diverting_capture() {
+        let audio = AudioHandler::new().unwrap();
+        let sink = CaptureSink {
+            capture_buffer: Arc::clone(&audio.capture_buffer),
+            is_muted: Arc::clone(&audio.is_muted),
+            input_level: Arc::clone(&audio.input_level),
+            echo_recording: Arc::clone(&audio.echo_recording),
+            output_delay: Arc::clone(&audio.output_delay),
+            source_sample_rate: SAMPLE_RATE,
+            channels: 1,
+        };
+        assert_eq!(audio.finish_echo_check(), None);
+
+        audio.start_echo_check();
+        assert!(audio.playback_mixer.lock().has_source(ECHO_CHECK_SOURCE));
+
+        // Das Mikrofon hört das Prüfsignal direkt (ohne Verzögerung)
+        let probe = echo_probe();
+        sink.push(&probe);
+        sink.push(&[0.0; FRAME_SIZE]);
+        assert_eq!(
+            audio.capture_buffer.lock().occupied_len(),
+            probe.len() + FRAME_SIZE
+        );
+
+        let recording = audio.finish_echo_check().unwrap();
+        assert_eq!(recording.len(), probe.len() + FRAME_SIZE);
+        let correlation = echo_correlation(&probe, &recording);
+        assert!(correlation > 0.99, "correlation {}", correlation);
+        assert!(!audio.playback_mixer.lock().has_source(ECHO_CHECK_SOURCE));
+        assert_eq!(audio.finish_echo_check(), None);
+    }
+
+    #[test]
+    fn test_output_delay_shifts_captured_audio() {
+        let audio = AudioHandler::new().unwrap();
+        let sink = CaptureSink {
+            capture_buffer: Arc::clone(&audio.capture_buffer),
+            is_muted: Arc::clone(&audio.is_muted),
+            input_level: Arc::clone(&audio.input_level),
+            echo_recording: Arc::clone(&audio.echo_recording),
+            output_delay: Arc::clone(&audio.output_delay),
+            source_sample_rate: SAMPLE_RATE,
+            channels: 1,
+        };
+        assert!(matches!(
+            audio.set_output_delay(MAX_OUTPUT_DELAY_MS + 1),
+            Err(AudioError::InvalidOutputDelay(_))
+        ));
+
+        // 25ms = 1200 Samples: ein Impuls im ersten Frame erscheint 1200 Samples später
+        audio.set_output_delay(25).unwrap();
+        let mut impulse = vec![0.0; FRAME_SIZE];
+        impulse[10] = 1.0;
+        sink.push(&impulse);
+        sink.push(&[0.0; FRAME_SIZE]);
+
+        let mut received = audio.read_frame().unwrap();
+        received.extend(audio.read_frame().unwrap());
+        let position = received.iter().position(|sample| *sample == 1.0);
+        assert_eq!(position, Some(10 + 1200));
+
+        // Ohne Verzögerung kommt das Audio unverändert durch
+        audio.set_output_delay(0).unwrap();
+        sink.push(&impulse);
+        assert_eq!(audio.read_frame().unwrap(), impulse);
+    }
+
+    #[test]
+    fn test_read_frame_returns_configured_size() {
+        let mut audio = AudioHandler::new().unwrap();
+        assert!(matches!(
+            audio.set_frame_size(1000),
+            Err(AudioError::InvalidFrameSize(1000))
+        ));
+        assert_eq!(audio.frame_size(), FRAME_SIZE);
+
+        audio.capture_buffer.lock().push_slice(&[0.1; FRAME_SIZE]);
+        audio.set_frame_size(480).unwrap();
+        assert_eq!(audio.read_frame().map(|frame| frame.len()), Some(480));
+        assert_eq!(audio.read_frame().map(|frame| frame.len()), Some(480));
+        assert_eq!(audio.read_frame(), None);
+
+        // Ein 60ms-Frame wird erst geliefert, wenn er vollständig ist
+        audio.set_frame_size(2880).unwrap();
+        audio.capture_buffer.lock().push_slice(&[0.1; 1920]);
+        assert_eq!(audio.read_frame(), None);
+        audio.capture_buffer.lock().push_slice(&[0.1; 960]);
+        assert_eq!(audio.read_frame().map(|frame| frame.len()), Some(2880));
+    }
+
+    #[test]
+    fn test_vad_detects_voice_and_suppresses_silence() {
+        let audio = AudioHandler::new().unwrap();
+        let loud: Vec<f32> = (0..FRAME_SIZE)
+            .map(|i| 0.3 * (i as f32 * 0.05).sin())
+            .collect();
+        let silent = [0.001; FRAME_SIZE];
+
+        // Ohne Unterdrückung wird nur erkannt, Stille wird weiter geliefert
+        audio.capture_buffer.lock().push_slice(&silent);
+        assert_eq!(
+            audio.read_frame().map(|frame| frame.len()),
+            Some(FRAME_SIZE)
+        );
+        assert!(!audio.is_voice_active());
+        audio.capture_buffer.lock().push_slice(&loud);
+        assert_eq!(audio.read_frame().unwrap(), loud);
+        assert!(audio.is_voice_active());
+
+        // Kurze Pausen werden überbrückt, anhaltende Stille unterdrückt
+        audio.set_vad_enabled(true);
+        let hangover_frames = samples_for(VAD_HANGOVER) / FRAME_SIZE;
+        for _ in 1..hangover_frames {
+            audio.capture_buffer.lock().push_slice(&silent);
+            assert_eq!(
+                audio.read_frame().map(|frame| frame.len()),
+                Some(FRAME_SIZE)
+            );
+            assert!(audio.is_voice_active());
+        }
+        audio.capture_buffer.lock().push_slice(&silent);
+        assert_eq!(audio.read_frame(), Some(Vec::new()));
+        assert!(!audio.is_voice_active());
+
+        audio.capture_buffer.lock().push_slice(&loud);
+        assert_eq!(audio.read_frame().unwrap(), loud);
+        assert!(audio.is_voice_active());
+    }
+
+    #[test]
+    fn test_push_to_talk_gates_frames() {
+        let audio = AudioHandler::new().unwrap();
+        let voice = [0.2; FRAME_SIZE];
+
+        // Ohne Push-to-Talk wird immer gesendet
+        audio.set_ptt_active(false);
+        audio.capture_buffer.lock().push_slice(&voice);
+        assert_eq!(audio.read_frame().unwrap(), voice);
+
+        // Mit Push-to-Talk nur bei gedrückter Taste, das Audio wird trotzdem verbraucht
+        audio.set_ptt_enabled(true);
+        audio.capture_buffer.lock().push_slice(&voice);
+        assert_eq!(audio.read_frame(), Some(Vec::new()));
+        assert_eq!(audio.read_frame(), None);
+
+        audio.set_ptt_active(true);
+        audio.capture_buffer.lock().push_slice(&voice);
+        assert_eq!(audio.read_frame().unwrap(), voice);
+        audio.set_ptt_active(false);
+        audio.capture_buffer.lock().push_slice(&voice);
+        assert_eq!(audio.read_frame(), Some(Vec::new()));
+
+        // Zurück zum offenen Mikrofon: sofort wieder senden
+        audio.set_ptt_enabled(false);
+        audio.capture_buffer.lock().push_slice(&voice);
+        assert_eq!(audio.read_frame().unwrap(), voice);
+    }
+
+    #[test]
+    fn test_vad_threshold_is_validated() {
+        let audio = AudioHandler::new().unwrap();
+        assert!(matches!(
+            audio.set_vad_threshold(1.5),
+            Err(AudioError::InvalidVadThreshold(_))
+        ));
+        assert!(audio.set_vad_threshold(f32::NAN).is_err());
+
+        // Mit höherer Schwelle zählt leises Sprechen nicht mehr
+        audio.set_vad_threshold(0.5).unwrap();
+        audio.capture_buffer.lock().push_slice(&[0.3; FRAME_SIZE]);
+        audio.read_frame().unwrap();
+        assert!(!audio.is_voice_active());
+    }
+
+    #[test]
+    fn test_integer_samples_convert_to_f32() {
+        assert_eq!(samples_to_f32(&[i16::MIN, 0, 16384]), vec![-1.0, 0.0, 0.5]);
+        assert_eq!(samples_to_f32(&[0u16, 32768, 49152]), vec![-1.0, 0.0, 0.5]);
+        assert_eq!(samples_to_f32(&[0.25f32]), vec![0.25]);
+    }
+
+    #[test]
+    fn test_f32_samples_convert_to_integer_formats() {
+        let samples = [-1.0, 0.0, 0.5, 2.0, -3.0];
+
+        let mut i16_out = [0i16; 5];
+        write_f32_samples(&samples, &mut i16_out);
+        assert_eq!(i16_out, [i16::MIN, 0, 16384, i16::MAX, i16::MIN]);
+
+        let mut u16_out = [0u16; 5];
+        write_f32_samples(&samples, &mut u16_out);
+        assert_eq!(u16_out, [0, 32768, 49152, u16::MAX, 0]);
+
+        // Hin und zurück bleibt innerhalb der Quantisierung
+        let round_trip = samples_to_f32(&i16_out);
+        assert!((round_trip[2] - 0.5).abs() < 1.0 / 32768.0);
+    }
+}
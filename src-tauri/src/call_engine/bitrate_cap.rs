@@ -0,0 +1,106 @@
+//! Obergrenze für die Sende-Bitrate
+//!
+//! Für getaktete Verbindungen wird die ausgehende Bitrate begrenzt.
+//! webrtc-rs bietet für `RTCRtpSender` kein `setParameters` mit
+//! `maxBitrate`, daher setzt der Sende-Task die Grenze direkt am
+//! Opus-Encoder durch: Dieser kodiert mit der Obergrenze abzüglich des
+//! Overheads der RTP/UDP/IP-Header. Es werden keine Pakete verworfen, die
+//! Grenze gilt ab dem nächsten Frame, ohne Neuverhandlung.
+
+use super::audio::SAMPLE_RATE;
+use super::opus_codec::OPUS_BITRATE_RANGE;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Untergrenze, damit Sprache verständlich bleibt (inkl. RTP-Header)
+pub const MIN_BITRATE_CAP_BPS: u32 = 16_000;
+
+/// Header pro Paket: RTP (12 Byte), UDP (8 Byte) und IPv4 (20 Byte)
+const PACKET_OVERHEAD_BYTES: u32 = 12 + 8 + 20;
+
+// ============================================================================
+// BITRATE CAP
+// ============================================================================
+
+/// Prüft eine Obergrenze für die Sende-Bitrate
+pub fn validate_bitrate_cap(max_bps: u32) -> Result<(), String> {
+    if max_bps < MIN_BITRATE_CAP_BPS {
+        return Err(format!(
+            "bitrate cap must be at least {} bps, got {}",
+            MIN_BITRATE_CAP_BPS, max_bps
+        ));
+    }
+    Ok(())
+}
+
+/// Bitrate der Header bei einem Paket pro Frame aus `frame_size` Samples
+pub fn packet_overhead_bps(frame_size: usize) -> u32 {
+    let packets_per_second = SAMPLE_RATE as f64 / frame_size.max(1) as f64;
+    (PACKET_OVERHEAD_BYTES as f64 * 8.0 * packets_per_second).ceil() as u32
+}
+
+/// Bitrate, mit der der Opus-Encoder kodieren soll
+///
+/// Die gewünschte `codec_bitrate` wird auf die Obergrenze abzüglich des
+/// Header-Overheads begrenzt. Lässt der Overhead (sehr kleine Frames) keinen
+/// Platz mehr, kodiert Opus mit seiner kleinsten Bitrate.
+pub fn encoder_bitrate(codec_bitrate: u32, max_bps: Option<u32>, frame_size: usize) -> u32 {
+    let Some(max_bps) = max_bps else {
+        return codec_bitrate;
+    };
+    let payload_bps = max_bps.saturating_sub(packet_overhead_bps(frame_size));
+    codec_bitrate
+        .min(payload_bps)
+        .max(*OPUS_BITRATE_RANGE.start())
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 20ms bei 48kHz
+    const FRAME_20MS: usize = 960;
+
+    #[test]
+    fn test_cap_below_floor_is_rejected() {
+        assert!(validate_bitrate_cap(MIN_BITRATE_CAP_BPS - 1).is_err());
+        assert!(validate_bitrate_cap(MIN_BITRATE_CAP_BPS).is_ok());
+    }
+
+    #[test]
+    fn test_overhead_follows_packet_rate() {
+        // 50 Pakete pro Sekunde à 40 Byte Header
+        assert_eq!(packet_overhead_bps(FRAME_20MS), 16_000);
+        assert_eq!(packet_overhead_bps(FRAME_20MS * 2), 8_000);
+    }
+
+    #[test]
+    fn test_cap_limits_encoder_bitrate() {
+        // 40 kbps inkl. 16 kbps Header lassen 24 kbps für Opus
+        assert_eq!(encoder_bitrate(32_000, Some(40_000), FRAME_20MS), 24_000);
+        // Der Encoder plus Header bleibt unter der Grenze
+        let total =
+            encoder_bitrate(64_000, Some(48_000), FRAME_20MS) + packet_overhead_bps(FRAME_20MS);
+        assert!(total <= 48_000);
+    }
+
+    #[test]
+    fn test_bitrate_below_cap_is_unchanged() {
+        assert_eq!(encoder_bitrate(32_000, None, FRAME_20MS), 32_000);
+        assert_eq!(encoder_bitrate(32_000, Some(64_000), FRAME_20MS), 32_000);
+    }
+
+    #[test]
+    fn test_cap_never_goes_below_opus_minimum() {
+        assert_eq!(
+            encoder_bitrate(32_000, Some(MIN_BITRATE_CAP_BPS), 120),
+            *OPUS_BITRATE_RANGE.start()
+        );
+    }
+}
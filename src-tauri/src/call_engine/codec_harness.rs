@@ -0,0 +1,194 @@
+//! Test-Harness für Audio-Codecs
+//!
+//! Schickt PCM Frame für Frame durch einen Encoder und Decoder und misst,
+//! wie gut das rekonstruierte Signal zum Original passt (SNR, Energie,
+//! Korrelation). So lässt sich die Codec-Integration ohne WebRTC und ohne
+//! Audio-Hardware testen.
+//!
+//! Einen `OpusCodec` gibt es noch nicht (audiopus ist in der Cargo.toml
+//! deaktiviert, solange vcpkg nicht eingerichtet ist). Encoder und Decoder
+//! werden deshalb als Closures übergeben, ein späterer Opus-Wrapper lässt
+//! sich ohne Änderung am Harness einsetzen.
+
+use super::audio::SAMPLE_RATE;
+
+// ============================================================================
+// ROUND TRIP
+// ============================================================================
+
+/// Kodiert und dekodiert PCM in Frames von `frame_size` Samples
+///
+/// Der letzte Frame wird mit Stille aufgefüllt, das Ergebnis hat dieselbe
+/// Länge wie die Eingabe.
+pub fn round_trip<E, D>(pcm: &[f32], frame_size: usize, mut encode: E, mut decode: D) -> Vec<f32>
+where
+    E: FnMut(&[f32]) -> Vec<u8>,
+    D: FnMut(&[u8]) -> Vec<f32>,
+{
+    let mut output = Vec::with_capacity(pcm.len() + frame_size);
+    let mut frame = Vec::with_capacity(frame_size);
+
+    for chunk in pcm.chunks(frame_size) {
+        frame.clear();
+        frame.extend_from_slice(chunk);
+        frame.resize(frame_size, 0.0);
+
+        let packet = encode(&frame);
+        output.extend(decode(&packet));
+    }
+
+    output.resize(pcm.len(), 0.0);
+    output
+}
+
+/// Erzeugt einen Sinuston bei `SAMPLE_RATE`
+pub fn sine_wave(frequency_hz: f32, amplitude: f32, samples: usize) -> Vec<f32> {
+    (0..samples)
+        .map(|i| {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            amplitude * (2.0 * std::f32::consts::PI * frequency_hz * t).sin()
+        })
+        .collect()
+}
+
+// ============================================================================
+// SIGNAL QUALITY
+// ============================================================================
+
+/// Signal-Rausch-Abstand des rekonstruierten Signals in dB
+///
+/// Gibt `f32::INFINITY` zurück, wenn beide Signale identisch sind.
+pub fn snr_db(original: &[f32], reconstructed: &[f32]) -> f32 {
+    let (signal, noise) =
+        original
+            .iter()
+            .zip(reconstructed)
+            .fold((0.0f64, 0.0f64), |(signal, noise), (&o, &r)| {
+                let error = (o - r) as f64;
+                (signal + (o as f64).powi(2), noise + error * error)
+            });
+
+    if noise == 0.0 {
+        return f32::INFINITY;
+    }
+    (10.0 * (signal / noise).log10()) as f32
+}
+
+/// Verhältnis der Signalenergie (rekonstruiert / original)
+pub fn energy_ratio(original: &[f32], reconstructed: &[f32]) -> f32 {
+    let original_energy = energy(original);
+    if original_energy == 0.0 {
+        return if energy(reconstructed) == 0.0 {
+            1.0
+        } else {
+            f32::INFINITY
+        };
+    }
+    (energy(reconstructed) / original_energy) as f32
+}
+
+/// Normierte Korrelation zweier Signale (-1.0 bis 1.0)
+pub fn correlation(a: &[f32], b: &[f32]) -> f32 {
+    let len = a.len().min(b.len());
+    let (a, b) = (&a[..len], &b[..len]);
+
+    let denominator = (energy(a) * energy(b)).sqrt();
+    if denominator == 0.0 {
+        return 0.0;
+    }
+    let dot: f64 = a.iter().zip(b).map(|(&x, &y)| x as f64 * y as f64).sum();
+    (dot / denominator) as f32
+}
+
+/// Sucht die Verzögerung (in Samples) mit der höchsten Korrelation
+///
+/// Codecs mit Lookahead geben das Signal verzögert zurück. Gibt die
+/// Verzögerung und die Korrelation bei dieser Verzögerung zurück.
+pub fn best_alignment(original: &[f32], reconstructed: &[f32], max_lag: usize) -> (usize, f32) {
+    (0..=max_lag.min(reconstructed.len()))
+        .map(|lag| (lag, correlation(original, &reconstructed[lag..])))
+        .fold((0, f32::MIN), |best, candidate| {
+            if candidate.1 > best.1 {
+                candidate
+            } else {
+                best
+            }
+        })
+}
+
+/// Summe der quadrierten Samples
+fn energy(samples: &[f32]) -> f64 {
+    samples.iter().map(|&s| (s as f64).powi(2)).sum()
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::call_engine::FRAME_SIZE;
+
+    /// Verlustbehafteter Test-Codec: 8-Bit-Quantisierung
+    fn encode_8bit(frame: &[f32]) -> Vec<u8> {
+        frame
+            .iter()
+            .map(|s| ((s.clamp(-1.0, 1.0) * 127.0).round() as i8) as u8)
+            .collect()
+    }
+
+    fn decode_8bit(packet: &[u8]) -> Vec<f32> {
+        packet.iter().map(|&b| b as i8 as f32 / 127.0).collect()
+    }
+
+    #[test]
+    fn test_lossy_round_trip_is_within_tolerance() {
+        // 1 Sekunde plus ein halber Frame, damit Auffüllen geprüft wird
+        let pcm = sine_wave(440.0, 0.5, SAMPLE_RATE as usize + FRAME_SIZE / 2);
+        let output = round_trip(&pcm, FRAME_SIZE, encode_8bit, decode_8bit);
+
+        assert_eq!(output.len(), pcm.len());
+        assert!(snr_db(&pcm, &output) > 30.0);
+        assert!((energy_ratio(&pcm, &output) - 1.0).abs() < 0.05);
+        assert!(correlation(&pcm, &output) > 0.99);
+
+        // Verlustfreier Codec
+        let lossless = round_trip(
+            &pcm,
+            FRAME_SIZE,
+            |frame| frame.iter().flat_map(|s| s.to_le_bytes()).collect(),
+            |packet| {
+                packet
+                    .chunks_exact(4)
+                    .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                    .collect()
+            },
+        );
+        assert_eq!(snr_db(&pcm, &lossless), f32::INFINITY);
+    }
+
+    #[test]
+    fn test_best_alignment_finds_codec_delay() {
+        const DELAY: usize = 120;
+        // Zwei Töne, damit sich die Korrelation nicht periodisch wiederholt
+        let pcm: Vec<f32> = sine_wave(300.0, 0.4, FRAME_SIZE * 10)
+            .iter()
+            .zip(sine_wave(437.0, 0.4, FRAME_SIZE * 10))
+            .map(|(a, b)| a + b)
+            .collect();
+
+        // Decoder mit Lookahead: gibt das Signal um DELAY Samples verzögert aus
+        let mut history = vec![0.0f32; DELAY];
+        let output = round_trip(&pcm, FRAME_SIZE, encode_8bit, |packet| {
+            history.extend(decode_8bit(packet));
+            history.drain(..FRAME_SIZE).collect()
+        });
+
+        // Ohne Ausgleich passt die Phase nicht, mit Ausgleich schon
+        assert!(correlation(&pcm, &output) < 0.9);
+        let (lag, aligned) = best_alignment(&pcm, &output, 480);
+        assert_eq!(lag, DELAY);
+        assert!(aligned > 0.99);
+    }
+}
@@ -0,0 +1,372 @@
+//! Selbsttest der Erreichbarkeit vor einem Anruf
+//!
+//! `CallEngine::test_connectivity` sammelt mit einer Wegwerf-Peer-Connection
+//! ICE Candidates gegen die konfigurierten ICE Server. Hier werden die
+//! Candidates ausgewertet und das Mapping-Verhalten des NAT bestimmt: Von
+//! einem einzigen UDP-Socket gehen Binding Requests an zwei STUN-Server.
+//! Melden beide dieselbe öffentliche Adresse, hängt das Mapping nicht vom
+//! Ziel ab. Sonst liegt ein symmetrisches NAT vor, hinter dem Anrufe meist
+//! nur über TURN zustande kommen.
+
+use super::ice_health::{stun_binding_request, xor_mapped_address, ProbeTarget, ProbeTransport};
+use super::ice_log::{summarize_candidate, CandidateDirection};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+use tokio::net::{lookup_host, UdpSocket};
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Maximale Wartezeit auf das Ende des ICE Gatherings
+pub const CONNECTIVITY_GATHER_TIMEOUT: Duration = Duration::from_secs(8);
+
+/// Maximale Wartezeit auf eine Binding Response beim NAT-Test
+const MAPPING_PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Anzahl der STUN-Server, deren Antworten verglichen werden
+const MAPPING_PROBE_SERVERS: usize = 2;
+
+// ============================================================================
+// REPORT
+// ============================================================================
+
+/// Mapping-Verhalten des NAT (RFC 4787)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NatBehavior {
+    /// Die öffentliche Adresse gehört zu einem eigenen Interface
+    NoNat,
+    /// Gleiche öffentliche Adresse für alle Ziele, direkte Verbindungen gelingen meist
+    EndpointIndependent,
+    /// Öffentliche Adresse hängt vom Ziel ab (symmetrisches NAT), TURN nötig
+    EndpointDependent,
+    /// Nicht bestimmbar (weniger als zwei STUN-Server haben geantwortet)
+    Unknown,
+}
+
+/// Anzahl gesammelter Candidates pro Typ
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CandidateCounts {
+    pub host: usize,
+    pub srflx: usize,
+    pub relay: usize,
+}
+
+/// Ergebnis des Erreichbarkeits-Tests
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConnectivityReport {
+    pub candidates: CandidateCounts,
+    /// Maskierte öffentliche Adressen der srflx Candidates
+    pub public_addresses: Vec<String>,
+    pub nat_behavior: NatBehavior,
+    pub stun_configured: bool,
+    pub turn_configured: bool,
+    /// STUN konfiguriert, aber kein srflx Candidate: STUN ist vermutlich blockiert
+    pub stun_blocked: bool,
+    /// TURN konfiguriert, aber kein Relay Candidate
+    pub turn_failed: bool,
+    /// Das Gathering wurde nicht innerhalb des Timeouts abgeschlossen
+    pub gathering_timed_out: bool,
+    pub duration_ms: u64,
+}
+
+/// Ergebnis des ICE Gatherings
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GatheredCandidates {
+    /// `candidate:` Zeilen in der Reihenfolge des Eintreffens
+    pub candidates: Vec<String>,
+    pub timed_out: bool,
+}
+
+/// Sammelt Candidates, bis der Sender geschlossen wird oder der Timeout abläuft
+///
+/// Der Sender wird geschlossen, sobald das Gathering abgeschlossen ist.
+pub async fn collect_candidates(
+    rx: &mut mpsc::UnboundedReceiver<String>,
+    timeout: Duration,
+) -> GatheredCandidates {
+    let deadline = Instant::now() + timeout;
+    let mut gathered = GatheredCandidates::default();
+    loop {
+        match tokio::time::timeout_at(deadline, rx.recv()).await {
+            Ok(Some(candidate)) => gathered.candidates.push(candidate),
+            Ok(None) => return gathered,
+            Err(_) => {
+                gathered.timed_out = true;
+                return gathered;
+            }
+        }
+    }
+}
+
+/// Wertet die gesammelten Candidates und die beim NAT-Test gemeldeten Adressen aus
+pub fn build_report(
+    gathered: &GatheredCandidates,
+    mapped: &[SocketAddr],
+    ice_server_urls: &[String],
+    elapsed: Duration,
+) -> ConnectivityReport {
+    let mut candidates = CandidateCounts::default();
+    let mut host_ips = HashSet::new();
+    let mut public_addresses = Vec::new();
+
+    for line in &gathered.candidates {
+        let Some(summary) = summarize_candidate(line, CandidateDirection::Local) else {
+            continue;
+        };
+        match summary.candidate_type.as_str() {
+            "host" => {
+                candidates.host += 1;
+                host_ips.extend(candidate_ip(line));
+            }
+            "srflx" => {
+                candidates.srflx += 1;
+                if !public_addresses.contains(&summary.address) {
+                    public_addresses.push(summary.address);
+                }
+            }
+            "relay" => candidates.relay += 1,
+            _ => {}
+        }
+    }
+
+    let stun_configured = ice_server_urls.iter().any(|url| url.starts_with("stun:"));
+    let turn_configured = ice_server_urls
+        .iter()
+        .any(|url| url.starts_with("turn:") || url.starts_with("turns:"));
+
+    ConnectivityReport {
+        stun_blocked: stun_configured && candidates.srflx == 0,
+        turn_failed: turn_configured && candidates.relay == 0,
+        candidates,
+        public_addresses,
+        nat_behavior: classify_nat(mapped, &host_ips),
+        stun_configured,
+        turn_configured,
+        gathering_timed_out: gathered.timed_out,
+        duration_ms: elapsed.as_millis() as u64,
+    }
+}
+
+/// Adresse einer `candidate:` Zeile (unmaskiert, nur für den Vergleich)
+fn candidate_ip(candidate: &str) -> Option<IpAddr> {
+    candidate.split_whitespace().nth(4)?.parse().ok()
+}
+
+/// Bestimmt das Mapping-Verhalten aus den öffentlichen Adressen eines Sockets
+fn classify_nat(mapped: &[SocketAddr], local_ips: &HashSet<IpAddr>) -> NatBehavior {
+    match mapped {
+        [] => NatBehavior::Unknown,
+        [first, ..] if local_ips.contains(&first.ip()) => NatBehavior::NoNat,
+        [_] => NatBehavior::Unknown,
+        [first, rest @ ..] if rest.iter().all(|address| address == first) => {
+            NatBehavior::EndpointIndependent
+        }
+        _ => NatBehavior::EndpointDependent,
+    }
+}
+
+// ============================================================================
+// NAT MAPPING PROBE
+// ============================================================================
+
+/// Fragt von einem UDP-Socket aus STUN-Server nach der öffentlichen Adresse
+///
+/// Abgefragt werden Server mit verschiedenen IPs, bis zwei geantwortet haben.
+pub async fn probe_mapped_addresses(ice_server_urls: &[String]) -> Vec<SocketAddr> {
+    let socket = match UdpSocket::bind("0.0.0.0:0").await {
+        Ok(socket) => socket,
+        Err(e) => {
+            tracing::warn!("NAT mapping probe: failed to bind socket: {}", e);
+            return Vec::new();
+        }
+    };
+
+    let mut queried: Vec<IpAddr> = Vec::new();
+    let mut mapped = Vec::new();
+    for url in ice_server_urls
+        .iter()
+        .filter(|url| url.starts_with("stun:"))
+    {
+        let Ok(target) = ProbeTarget::parse(url) else {
+            continue;
+        };
+        if target.transport != ProbeTransport::Udp {
+            continue;
+        }
+        let Ok(mut addresses) = lookup_host((target.host.as_str(), target.port)).await else {
+            continue;
+        };
+        // Derselbe Server unter anderem Namen sagt nichts über das Mapping aus
+        let Some(server) = addresses.find(|address| address.is_ipv4()) else {
+            continue;
+        };
+        if queried.contains(&server.ip()) {
+            continue;
+        }
+        queried.push(server.ip());
+
+        match stun_mapped_address(&socket, server).await {
+            Some(address) => mapped.push(address),
+            None => tracing::debug!("NAT mapping probe: no response from {}", url),
+        }
+        if mapped.len() == MAPPING_PROBE_SERVERS {
+            break;
+        }
+    }
+    mapped
+}
+
+/// Sendet einen Binding Request und wartet auf die öffentliche Adresse
+async fn stun_mapped_address(socket: &UdpSocket, server: SocketAddr) -> Option<SocketAddr> {
+    let transaction_id: [u8; 12] = rand::random();
+    socket
+        .send_to(&stun_binding_request(&transaction_id), server)
+        .await
+        .ok()?;
+
+    // Späte Antworten eines vorher gefragten Servers werden übersprungen
+    let deadline = Instant::now() + MAPPING_PROBE_TIMEOUT;
+    let mut buf = [0u8; 1024];
+    loop {
+        let (len, from) = tokio::time::timeout_at(deadline, socket.recv_from(&mut buf))
+            .await
+            .ok()?
+            .ok()?;
+        if from != server {
+            continue;
+        }
+        if let Some(address) = xor_mapped_address(&buf[..len], &transaction_id) {
+            return Some(address);
+        }
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOST: &str = "candidate:1 1 udp 2130706431 192.168.1.23 54321 typ host";
+    const SRFLX: &str =
+        "candidate:2 1 udp 1694498815 203.0.113.9 61000 typ srflx raddr 192.168.1.23 rport 54321";
+    const RELAY: &str =
+        "candidate:3 1 udp 16777215 198.51.100.4 3478 typ relay raddr 203.0.113.9 rport 61000";
+
+    fn urls(urls: &[&str]) -> Vec<String> {
+        urls.iter().map(|url| url.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_gathering_timeout_is_reported() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        tx.send(HOST.to_string()).unwrap();
+
+        // Gathering läuft noch (Sender offen): Timeout greift
+        let gathered = collect_candidates(&mut rx, Duration::from_millis(50)).await;
+        assert!(gathered.timed_out);
+        assert_eq!(gathered.candidates, vec![HOST.to_string()]);
+
+        let report = build_report(
+            &gathered,
+            &[],
+            &urls(&["stun:stun.example.org:3478"]),
+            Duration::from_millis(50),
+        );
+        assert!(report.gathering_timed_out);
+        assert!(report.stun_blocked);
+        assert!(!report.turn_failed);
+        assert_eq!(report.candidates.host, 1);
+        assert_eq!(report.nat_behavior, NatBehavior::Unknown);
+
+        // Gathering abgeschlossen (Sender geschlossen)
+        tx.send(SRFLX.to_string()).unwrap();
+        drop(tx);
+        let gathered = collect_candidates(&mut rx, Duration::from_secs(5)).await;
+        assert!(!gathered.timed_out);
+        assert_eq!(gathered.candidates, vec![SRFLX.to_string()]);
+    }
+
+    #[test]
+    fn test_report_counts_candidate_types() {
+        let gathered = GatheredCandidates {
+            candidates: vec![HOST.to_string(), SRFLX.to_string(), RELAY.to_string()],
+            timed_out: false,
+        };
+        let mapped: Vec<SocketAddr> = vec![
+            "203.0.113.9:61000".parse().unwrap(),
+            "203.0.113.9:61000".parse().unwrap(),
+        ];
+        let report = build_report(
+            &gathered,
+            &mapped,
+            &urls(&["stun:stun.example.org", "turn:turn.example.org"]),
+            Duration::from_millis(1200),
+        );
+
+        assert_eq!(
+            report.candidates,
+            CandidateCounts {
+                host: 1,
+                srflx: 1,
+                relay: 1
+            }
+        );
+        assert_eq!(report.public_addresses, vec!["203.0.113.x".to_string()]);
+        assert_eq!(report.nat_behavior, NatBehavior::EndpointIndependent);
+        assert!(!report.stun_blocked && !report.turn_failed);
+        assert_eq!(report.duration_ms, 1200);
+
+        // Ohne Relay Candidate ist TURN gescheitert
+        let gathered = GatheredCandidates {
+            candidates: vec![HOST.to_string(), SRFLX.to_string()],
+            timed_out: false,
+        };
+        let report = build_report(
+            &gathered,
+            &[],
+            &urls(&["turn:turn.example.org"]),
+            Duration::ZERO,
+        );
+        assert!(report.turn_failed);
+        assert!(!report.stun_configured && !report.stun_blocked);
+    }
+
+    #[test]
+    fn test_classify_nat_behavior() {
+        let local: HashSet<IpAddr> = ["192.168.1.23".parse().unwrap()].into_iter().collect();
+        let address = |address: &str| -> SocketAddr { address.parse().unwrap() };
+
+        assert_eq!(classify_nat(&[], &local), NatBehavior::Unknown);
+        assert_eq!(
+            classify_nat(&[address("203.0.113.9:61000")], &local),
+            NatBehavior::Unknown
+        );
+        assert_eq!(
+            classify_nat(&[address("192.168.1.23:40000")], &local),
+            NatBehavior::NoNat
+        );
+        assert_eq!(
+            classify_nat(
+                &[address("203.0.113.9:61000"), address("203.0.113.9:61000")],
+                &local
+            ),
+            NatBehavior::EndpointIndependent
+        );
+        assert_eq!(
+            classify_nat(
+                &[address("203.0.113.9:61000"), address("203.0.113.9:61004")],
+                &local
+            ),
+            NatBehavior::EndpointDependent
+        );
+    }
+}
@@ -0,0 +1,204 @@
+//! Empfangsseite: RTP-Payload zu PCM für den Playback-Mixer
+//!
+//! Jede Audio-Payload eines RTP-Pakets enthält genau einen Frame, das
+//! Depacketizing beschränkt sich daher auf das Dekodieren der Payload.
+//! Opus wird direkt mit `SAMPLE_RATE` dekodiert. G.711 (PCMU/PCMA) kommt
+//! ohne externe Bibliothek aus und wird von 8kHz auf `SAMPLE_RATE`
+//! hochgerechnet. Für G.722 fehlt der Decoder noch, dessen Pakete werden
+//! verworfen.
+
+use super::audio::SAMPLE_RATE;
+use super::opus_codec::OpusFrameDecoder;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Abtastrate von G.711
+const G711_SAMPLE_RATE: u32 = 8000;
+
+/// Ausgabe-Samples pro G.711-Sample
+const G711_UPSAMPLE_FACTOR: usize = (SAMPLE_RATE / G711_SAMPLE_RATE) as usize;
+
+// ============================================================================
+// CODEC
+// ============================================================================
+
+/// Codec eines empfangenen Audio-Tracks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReceiveCodec {
+    /// Opus (bevorzugter Codec)
+    Opus,
+    /// G.711 µ-law
+    Pcmu,
+    /// G.711 A-law
+    Pcma,
+    /// Ausgehandelt, aber (noch) ohne Decoder
+    Unsupported,
+}
+
+impl ReceiveCodec {
+    /// Bestimmt den Codec aus dem MIME-Type des Tracks (z.B. "audio/PCMU")
+    pub fn from_mime_type(mime_type: &str) -> Self {
+        if mime_type.eq_ignore_ascii_case("audio/opus") {
+            ReceiveCodec::Opus
+        } else if mime_type.eq_ignore_ascii_case("audio/PCMU") {
+            ReceiveCodec::Pcmu
+        } else if mime_type.eq_ignore_ascii_case("audio/PCMA") {
+            ReceiveCodec::Pcma
+        } else {
+            ReceiveCodec::Unsupported
+        }
+    }
+}
+
+// ============================================================================
+// DECODER
+// ============================================================================
+
+/// Dekodiert die Payloads eines Tracks zu Mono-Samples mit `SAMPLE_RATE`
+#[derive(Debug)]
+pub struct RtpAudioDecoder {
+    codec: ReceiveCodec,
+    /// Zustand des Opus-Decoders (nur bei `ReceiveCodec::Opus`)
+    opus: Option<OpusFrameDecoder>,
+    /// Letztes Sample des vorigen Pakets (für die Interpolation)
+    last_sample: f32,
+}
+
+impl RtpAudioDecoder {
+    /// Erstellt den Decoder, ohne Opus-Decoder gilt der Codec als nicht unterstützt
+    pub fn new(codec: ReceiveCodec) -> Self {
+        let opus = match codec {
+            ReceiveCodec::Opus => match OpusFrameDecoder::new() {
+                Ok(decoder) => Some(decoder),
+                Err(e) => {
+                    tracing::warn!("Failed to create Opus decoder: {}", e);
+                    None
+                }
+            },
+            _ => None,
+        };
+        let codec = match (codec, &opus) {
+            (ReceiveCodec::Opus, None) => ReceiveCodec::Unsupported,
+            _ => codec,
+        };
+        Self {
+            codec,
+            opus,
+            last_sample: 0.0,
+        }
+    }
+
+    /// Prüft, ob Pakete dieses Tracks abgespielt werden können
+    pub fn is_supported(&self) -> bool {
+        self.codec != ReceiveCodec::Unsupported
+    }
+
+    /// Dekodiert eine RTP-Payload, `None` ohne passenden Decoder
+    pub fn decode(&mut self, payload: &[u8]) -> Option<Vec<f32>> {
+        let expand: fn(u8) -> i16 = match self.codec {
+            ReceiveCodec::Opus => return self.decode_opus(payload),
+            ReceiveCodec::Pcmu => ulaw_to_linear,
+            ReceiveCodec::Pcma => alaw_to_linear,
+            ReceiveCodec::Unsupported => return None,
+        };
+
+        // Lineare Interpolation von 8kHz auf 48kHz
+        let mut samples = Vec::with_capacity(payload.len() * G711_UPSAMPLE_FACTOR);
+        for byte in payload {
+            let sample = expand(*byte) as f32 / 32768.0;
+            for step in 1..=G711_UPSAMPLE_FACTOR {
+                let t = step as f32 / G711_UPSAMPLE_FACTOR as f32;
+                samples.push(self.last_sample + (sample - self.last_sample) * t);
+            }
+            self.last_sample = sample;
+        }
+        Some(samples)
+    }
+
+    /// Dekodiert eine Opus-Payload, beschädigte Pakete werden verworfen
+    fn decode_opus(&mut self, payload: &[u8]) -> Option<Vec<f32>> {
+        let decoder = self.opus.as_mut()?;
+        match decoder.decode(payload) {
+            Ok(samples) => Some(samples),
+            Err(e) => {
+                tracing::debug!("Dropping undecodable Opus packet: {}", e);
+                None
+            }
+        }
+    }
+}
+
+/// G.711 µ-law zu 16-Bit PCM
+fn ulaw_to_linear(byte: u8) -> i16 {
+    let byte = !byte;
+    let exponent = (byte >> 4) & 0x07;
+    let mantissa = (byte & 0x0F) as i16;
+    let magnitude = (((mantissa << 3) + 0x84) << exponent) - 0x84;
+    if byte & 0x80 != 0 {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+/// G.711 A-law zu 16-Bit PCM
+fn alaw_to_linear(byte: u8) -> i16 {
+    let byte = byte ^ 0x55;
+    let exponent = (byte >> 4) & 0x07;
+    let mantissa = (byte & 0x0F) as i16;
+    let magnitude = match exponent {
+        0 => (mantissa << 4) + 0x08,
+        _ => ((mantissa << 4) + 0x108) << (exponent - 1),
+    };
+    if byte & 0x80 != 0 {
+        magnitude
+    } else {
+        -magnitude
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_g711_expansion_matches_reference_values() {
+        assert_eq!(ulaw_to_linear(0xFF), 0);
+        assert_eq!(ulaw_to_linear(0x80), 32124);
+        assert_eq!(ulaw_to_linear(0x00), -32124);
+        assert_eq!(alaw_to_linear(0xD5), 8);
+        assert_eq!(alaw_to_linear(0x55), -8);
+        assert_eq!(alaw_to_linear(0xAA), 32256);
+    }
+
+    #[test]
+    fn test_g711_packet_is_upsampled_to_playback_rate() {
+        assert_eq!(
+            ReceiveCodec::from_mime_type("audio/pcmu"),
+            ReceiveCodec::Pcmu
+        );
+        let mut decoder = RtpAudioDecoder::new(ReceiveCodec::Pcmu);
+
+        // 20ms bei 8kHz werden 20ms bei 48kHz
+        let samples = decoder.decode(&[0x80; 160]).unwrap();
+        assert_eq!(samples.len(), 960);
+        // Übergang von Stille zum ersten Sample, danach konstant
+        assert!(samples[0] > 0.0 && samples[0] < samples[5]);
+        assert!(samples[6..]
+            .iter()
+            .all(|sample| (*sample - 32124.0 / 32768.0).abs() < 1e-6));
+    }
+
+    #[test]
+    fn test_unsupported_codec_yields_no_samples() {
+        let mut decoder = RtpAudioDecoder::new(ReceiveCodec::from_mime_type("audio/G722"));
+        assert!(!decoder.is_supported());
+        assert!(decoder.decode(&[0xFC, 0xFF, 0xFE]).is_none());
+    }
+}
@@ -0,0 +1,130 @@
+//! Überwachung der System-Standardgeräte
+//!
+//! Erkennt, wenn der Nutzer im Betriebssystem ein anderes Standard-Mikrofon
+//! oder einen anderen Standard-Lautsprecher wählt, damit die UI anbieten kann,
+//! den laufenden Anruf auf das neue Gerät umzustellen.
+//!
+//! # Plattform-Einschränkungen
+//!
+//! cpal bietet keine Benachrichtigung bei Geräteänderungen, die Standardgeräte
+//! werden deshalb in festen Abständen abgefragt. Änderungen fallen also erst
+//! mit bis zu `DEFAULT_DEVICE_POLL_INTERVAL` Verzögerung auf.
+//!
+//! - Windows (WASAPI) und macOS (CoreAudio) melden den Namen des echten
+//!   Standardgeräts, Wechsel werden zuverlässig erkannt.
+//! - Linux (ALSA) meldet meist nur das virtuelle Gerät `default`, hinter dem
+//!   PulseAudio bzw. PipeWire routen. Ein Wechsel im Sound-Server ist dort
+//!   nicht sichtbar, es kommt also kein Event.
+
+use cpal::traits::{DeviceTrait, HostTrait};
+use serde::Serialize;
+use std::time::Duration;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Abstand zwischen zwei Abfragen der Standardgeräte
+pub const DEFAULT_DEVICE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+// ============================================================================
+// TYPES
+// ============================================================================
+
+/// Art eines Audio-Geräts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceKind {
+    Input,
+    Output,
+}
+
+/// Geänderte Standardgerät-Zuordnung
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DefaultDeviceChange {
+    pub kind: DeviceKind,
+    /// Neues Standardgerät (`None` wenn keines mehr vorhanden ist)
+    pub name: Option<String>,
+    /// Bisheriges Standardgerät
+    pub previous: Option<String>,
+}
+
+/// Momentaufnahme der System-Standardgeräte
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DefaultDevices {
+    pub input: Option<String>,
+    pub output: Option<String>,
+}
+
+impl DefaultDevices {
+    /// Fragt die aktuellen Standardgeräte beim Audio-Host ab
+    ///
+    /// Blockiert je nach Plattform einige Millisekunden.
+    pub fn query() -> Self {
+        let host = cpal::default_host();
+        Self {
+            input: host.default_input_device().and_then(|d| d.name().ok()),
+            output: host.default_output_device().and_then(|d| d.name().ok()),
+        }
+    }
+
+    /// Gibt die Änderungen gegenüber einer früheren Momentaufnahme zurück
+    pub fn changes_since(&self, previous: &DefaultDevices) -> Vec<DefaultDeviceChange> {
+        let mut changes = Vec::new();
+        if self.input != previous.input {
+            changes.push(DefaultDeviceChange {
+                kind: DeviceKind::Input,
+                name: self.input.clone(),
+                previous: previous.input.clone(),
+            });
+        }
+        if self.output != previous.output {
+            changes.push(DefaultDeviceChange {
+                kind: DeviceKind::Output,
+                name: self.output.clone(),
+                previous: previous.output.clone(),
+            });
+        }
+        changes
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn devices(input: Option<&str>, output: Option<&str>) -> DefaultDevices {
+        DefaultDevices {
+            input: input.map(str::to_string),
+            output: output.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_changes_since_reports_each_changed_kind() {
+        let before = devices(Some("Built-in Mic"), Some("Speakers"));
+
+        assert!(before.changes_since(&before).is_empty());
+
+        let after = devices(Some("Built-in Mic"), Some("Headset"));
+        assert_eq!(
+            after.changes_since(&before),
+            vec![DefaultDeviceChange {
+                kind: DeviceKind::Output,
+                name: Some("Headset".to_string()),
+                previous: Some("Speakers".to_string()),
+            }]
+        );
+
+        // Gerät entfernt, kein Standardgerät mehr
+        let unplugged = devices(None, Some("Headset"));
+        let changes = unplugged.changes_since(&after);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].kind, DeviceKind::Input);
+        assert_eq!(changes[0].name, None);
+    }
+}
@@ -3,29 +3,70 @@
 //! Verwaltet WebRTC Peer Connections und koordiniert
 //! Audio Capture/Playback.
 //!
-//! Hinweis: Opus Encoding wird später hinzugefügt sobald
-//! CMake für die opus-sys Bindings verfügbar ist.
+//! Ein Anruf kann bis zu `MAX_CALL_PEERS` Teilnehmer mit je eigener Peer
+//! Connection haben (Konferenz). Jeder Teilnehmer hat einen eigenen State,
+//! der Gesamt-State des Anrufs wird daraus abgeleitet.
+//!
+//! Aufgenommene Frames kodiert ein Sende-Task mit Opus und schreibt sie als
+//! RTP in die Tracks aller Teilnehmer, empfangene Pakete werden je Track
+//! dekodiert und in den Playback-Mixer gegeben.
 
-use super::audio::{AudioError, AudioHandler, SAMPLE_RATE};
+use super::audio::{
+    echo_correlation, echo_probe, validate_frame_size, validate_output_delay,
+    validate_prefill_frames, validate_vad_threshold, AudioError, AudioHandler,
+    DEFAULT_PREFILL_FRAMES, DEFAULT_VAD_THRESHOLD, ECHO_CORRELATION_THRESHOLD, ECHO_MAX_DELAY,
+    ECHO_PROBE_DURATION, FRAME_SIZE, SAMPLE_RATE,
+};
+use super::bitrate_cap::{encoder_bitrate, validate_bitrate_cap};
+use super::connectivity::{
+    build_report, collect_candidates, probe_mapped_addresses, ConnectivityReport,
+    CONNECTIVITY_GATHER_TIMEOUT,
+};
+use super::depacketizer::{ReceiveCodec, RtpAudioDecoder};
+use super::ice_log::{summarize_candidate, CandidateDirection, CandidateSummary};
+use super::limiter::OutputLimiterConfig;
+use super::network_sim::NetworkSimulation;
+use super::opus_codec::{
+    validate_opus_bitrate, OpusFrameEncoder, DEFAULT_OPUS_BITRATE, OPUS_PAYLOAD_TYPE,
+};
+use super::permission::{check_microphone_permission, MicrophonePermission};
+use super::ringtone::{Ringtone, RingtoneStyle};
+use super::rtp_monitor::{
+    AudioDirection, OneWayAudioDetector, ReceiveQuality, RtpCounters, RTP_MONITOR_INTERVAL,
+};
+use crate::crypto::KeyPair;
+use crate::events::EVENT_CHANNEL_CAPACITY;
+use crate::lan_discovery::public_key_from_lan_peer_id;
 use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::net::IpAddr;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
 use thiserror::Error;
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, mpsc, Notify};
 use webrtc::api::interceptor_registry::register_default_interceptors;
 use webrtc::api::media_engine::MediaEngine;
 use webrtc::api::setting_engine::SettingEngine;
-use webrtc::api::APIBuilder;
+use webrtc::api::{APIBuilder, API};
+use webrtc::dtls_transport::dtls_transport_state::RTCDtlsTransportState;
 use webrtc::ice_transport::ice_candidate::RTCIceCandidateInit;
 use webrtc::ice_transport::ice_server::RTCIceServer;
 use webrtc::interceptor::registry::Registry;
 use webrtc::peer_connection::configuration::RTCConfiguration;
+use webrtc::peer_connection::offer_answer_options::RTCOfferOptions;
 use webrtc::peer_connection::peer_connection_state::RTCPeerConnectionState;
+use webrtc::peer_connection::policy::ice_transport_policy::RTCIceTransportPolicy;
 use webrtc::peer_connection::sdp::session_description::RTCSessionDescription;
 use webrtc::peer_connection::RTCPeerConnection;
-use webrtc::rtp_transceiver::rtp_codec::RTCRtpCodecCapability;
+use webrtc::rtp::header::Header;
+use webrtc::rtp::packet::Packet;
+use webrtc::rtp_transceiver::rtp_codec::{
+    RTCRtpCodecCapability, RTCRtpCodecParameters, RTPCodecType,
+};
+use webrtc::stats::StatsReportType;
 use webrtc::track::track_local::track_local_static_rtp::TrackLocalStaticRTP;
-use webrtc::track::track_local::TrackLocal;
+use webrtc::track::track_local::{TrackLocal, TrackLocalWriter};
 
 // ============================================================================
 // ERROR TYPES
@@ -45,8 +86,26 @@ pub enum CallEngineError {
     #[error("Already in a call")]
     AlreadyInCall,
 
+    #[error("Call is no longer active (cancelled by the caller)")]
+    CallNoLongerActive,
+
+    #[error("Call is full ({0} peers)")]
+    TooManyPeers(usize),
+
     #[error("Invalid SDP: {0}")]
     InvalidSdp(String),
+
+    #[error("Invalid ICE candidate: {0}")]
+    InvalidCandidate(String),
+
+    #[error("Identity verification failed: {0}")]
+    IdentityVerification(String),
+
+    #[error("Microphone permission denied")]
+    MicrophonePermissionDenied,
+
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
 }
 
 // ============================================================================
@@ -66,19 +125,518 @@ pub enum CallState {
     Connecting { peer_id: String },
     /// Anruf aktiv
     Connected { peer_id: String },
+    /// Verbindung im Anruf unterbrochen, wird per ICE Restart wiederhergestellt
+    Reconnecting { peer_id: String },
     /// Anruf beendet
     Ended,
 }
 
+impl CallState {
+    /// Gegenstelle, sofern ein Anruf läuft
+    pub fn peer_id(&self) -> Option<&str> {
+        match self {
+            CallState::Calling { peer_id }
+            | CallState::Ringing { peer_id, .. }
+            | CallState::Connecting { peer_id }
+            | CallState::Connected { peer_id }
+            | CallState::Reconnecting { peer_id } => Some(peer_id),
+            CallState::Idle | CallState::Ended => None,
+        }
+    }
+
+    /// Fortschritt des Verbindungsaufbaus (für den Gesamt-State)
+    fn progress(&self) -> u8 {
+        match self {
+            CallState::Idle | CallState::Ended => 0,
+            CallState::Ringing { .. } => 1,
+            CallState::Calling { .. } => 2,
+            CallState::Connecting { .. } => 3,
+            CallState::Reconnecting { .. } => 4,
+            CallState::Connected { .. } => 5,
+        }
+    }
+}
+
+/// Leitet den Gesamt-State eines Anrufs aus den States der Teilnehmer ab
+///
+/// Der am weitesten fortgeschrittene Teilnehmer bestimmt den Gesamt-State.
+/// Bei Gleichstand bleibt die Gegenstelle aus `current` bestehen, sonst
+/// gewinnt die kleinere Peer-ID. Ohne Teilnehmer ist der Anruf beendet.
+pub fn aggregate_call_state<'a>(
+    peer_states: impl IntoIterator<Item = &'a CallState>,
+    current: &CallState,
+) -> CallState {
+    let is_current = |state: &CallState| state.peer_id() == current.peer_id();
+
+    peer_states
+        .into_iter()
+        .filter(|state| state.peer_id().is_some())
+        .max_by(|a, b| {
+            a.progress()
+                .cmp(&b.progress())
+                .then_with(|| is_current(a).cmp(&is_current(b)))
+                .then_with(|| b.peer_id().cmp(&a.peer_id()))
+        })
+        .cloned()
+        .unwrap_or(CallState::Ended)
+}
+
+/// Neuer State eines Teilnehmers nach einer Änderung seiner Verbindung
+///
+/// `None` lässt den State unverändert, `Some(None)` entfernt den Teilnehmer.
+/// Eine unterbrochene Verbindung im Anruf wird per ICE Restart
+/// wiederhergestellt (`Reconnecting`), erst `Failed` oder `Closed` beendet
+/// sie. Im Aufbau beendet bereits `Disconnected` den Teilnehmer.
+fn next_peer_state(
+    connection: RTCPeerConnectionState,
+    peer_id: &str,
+    current: &CallState,
+) -> Option<Option<CallState>> {
+    let peer_id = peer_id.to_string();
+    match (connection, current) {
+        (
+            RTCPeerConnectionState::Connected,
+            CallState::Calling { .. }
+            | CallState::Connecting { .. }
+            | CallState::Reconnecting { .. },
+        ) => Some(Some(CallState::Connected { peer_id })),
+        (RTCPeerConnectionState::Disconnected, CallState::Connected { .. }) => {
+            Some(Some(CallState::Reconnecting { peer_id }))
+        }
+        (RTCPeerConnectionState::Disconnected, CallState::Reconnecting { .. }) => None,
+        (
+            RTCPeerConnectionState::Disconnected
+            | RTCPeerConnectionState::Failed
+            | RTCPeerConnectionState::Closed,
+            _,
+        ) => Some(None),
+        _ => None,
+    }
+}
+
+/// Art eines Call-States (ohne zugehörige Daten)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CallStateKind {
+    Idle,
+    Calling,
+    Ringing,
+    Connecting,
+    Connected,
+    Reconnecting,
+    Ended,
+}
+
+/// Serialisierbare Darstellung von `CallState` für das Frontend
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CallStateInfo {
+    pub kind: CallStateKind,
+    /// Gegenstelle, sofern ein Anruf läuft
+    pub peer_id: Option<String>,
+    /// Username der Gegenstelle (beim Klingeln vom Anrufer, sonst ggf. ergänzt)
+    pub username: Option<String>,
+}
+
+impl From<&CallState> for CallStateInfo {
+    fn from(state: &CallState) -> Self {
+        let (kind, peer_id, username) = match state {
+            CallState::Idle => (CallStateKind::Idle, None, None),
+            CallState::Calling { peer_id } => (CallStateKind::Calling, Some(peer_id), None),
+            CallState::Ringing { peer_id, username } => {
+                (CallStateKind::Ringing, Some(peer_id), Some(username))
+            }
+            CallState::Connecting { peer_id } => (CallStateKind::Connecting, Some(peer_id), None),
+            CallState::Connected { peer_id } => (CallStateKind::Connected, Some(peer_id), None),
+            CallState::Reconnecting { peer_id } => {
+                (CallStateKind::Reconnecting, Some(peer_id), None)
+            }
+            CallState::Ended => (CallStateKind::Ended, None, None),
+        };
+        Self {
+            kind,
+            peer_id: peer_id.cloned(),
+            username: username.cloned(),
+        }
+    }
+}
+
 /// Events die vom CallEngine ausgelöst werden
 #[derive(Debug, Clone)]
 pub enum CallEvent {
+    /// Gesamt-State des Anrufs
     StateChanged(CallState),
-    IceCandidate { candidate: String },
-    AudioLevel { input: f32, output: f32 },
+    /// State eines einzelnen Teilnehmers (`Ended`, wenn er den Anruf verlassen hat)
+    PeerStateChanged {
+        peer_id: String,
+        state: CallState,
+    },
+    IceCandidate {
+        /// Teilnehmer, zu dessen Verbindung der Candidate gehört
+        /// (`None` bei einer vorgewärmten Verbindung)
+        peer_id: Option<String>,
+        candidate: String,
+    },
+    AudioLevel {
+        input: f32,
+        output: f32,
+    },
+    /// DTLS Fingerprints nach erfolgreichem Verbindungsaufbau
+    DtlsFingerprints(DtlsFingerprints),
+    /// Die Fingerprint-Signatur des Peers wurde mit diesem Public Key verifiziert
+    PeerIdentityVerified {
+        peer_id: String,
+        public_key: String,
+    },
+    /// Gesammelter oder empfangener ICE Candidate (nur bei ausführlichem ICE-Logging)
+    IceCandidateLogged(CandidateSummary),
+    /// Anruf abgebrochen, weil das Mikrofon nicht freigegeben ist
+    PermissionRequired {
+        permission: MicrophonePermission,
+    },
+    /// Anruf abgebrochen, weil eine andere App das Mikrofon belegt
+    MicrophoneBusy,
+    /// Im verbundenen Anruf fließt Audio nur in eine Richtung
+    OneWayAudio {
+        direction: AudioDirection,
+    },
+    /// Der Echo-Check hat das Prüfsignal im Mikrofon wiedergefunden
+    /// (Kopfhörer oder Echounterdrückung empfehlen)
+    EchoDetected {
+        correlation: f32,
+    },
+    /// ICE ist im Verbindungsaufbau gescheitert, der Aufbau wird einmal mit
+    /// frischen Peer Connections wiederholt. Bei `outgoing` muss der Anrufer
+    /// über `retry_call` ein neues Offer senden, sonst wartet die Engine darauf.
+    Retrying {
+        peer_id: String,
+        outgoing: bool,
+    },
+    /// Die Verbindung zu einem Teilnehmer ist unterbrochen (`Reconnecting`)
+    ///
+    /// Nur eine Seite darf das neue Offer senden (siehe `keeps_own_offer`):
+    /// Sie holt es über `restart_ice` und sendet es wie beim Anrufstart.
+    /// Ohne Erfolg endet der Teilnehmer nach `ICE_RESTART_TIMEOUT`.
+    RenegotiationNeeded {
+        peer_id: String,
+    },
+    /// Dauer des verbundenen Anrufs, einmal pro Sekunde
+    Duration {
+        seconds: u64,
+    },
+    /// Verbindungsqualität, alle `STATS_INTERVAL` während `Connected`
+    Stats(ConnectionStats),
+    /// Mikrofon wurde stumm- bzw. wieder eingeschaltet
+    MuteChanged(bool),
     Error(String),
 }
 
+/// Ausgewählte Audio-Geräte nach Name (`None` = System-Standard)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct AudioDeviceSelection {
+    pub input: Option<String>,
+    pub output: Option<String>,
+}
+
+/// Verbindungsqualität des laufenden Anrufs
+///
+/// Ergänzt `CallStats` um die Werte, die Nutzer bei schlechter Qualität
+/// melden können. Fehlende Werte sind 0.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct ConnectionStats {
+    /// Round Trip Time des gewählten Candidate Pairs in Millisekunden
+    pub rtt_ms: f64,
+    /// Verlorene eingehende RTP-Pakete
+    pub packets_lost: u64,
+    /// Jitter der eingehenden RTP-Pakete in Millisekunden
+    pub jitter_ms: f64,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+}
+
+/// Momentaufnahme des Anrufs für Diagnoseberichte
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CallStats {
+    pub state: CallStateInfo,
+    /// Teilnehmer mit aufgebauter Peer Connection
+    pub connected_peers: usize,
+    /// Gesendete RTP-Pakete seit Programmstart
+    pub packets_sent: u64,
+    /// Empfangene RTP-Pakete seit Programmstart
+    pub packets_received: u64,
+    /// Gewähltes ICE Candidate Pair der Hauptverbindung (unmaskiert)
+    pub selected_candidate_pair: Option<String>,
+}
+
+/// Ergebnis der Prüfung eines eingehenden Anrufs auf Glare
+///
+/// Glare: Beide Seiten rufen sich gleichzeitig an und jede erhält das Offer
+/// der anderen, während sie selbst noch `Calling` ist.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IncomingCallResolution {
+    /// Kein Konflikt, der Anruf klingelt normal
+    Ring,
+    /// Glare: Das eigene Offer bleibt bestehen, das eingehende wird ignoriert
+    KeepOwnOffer,
+    /// Glare: Das eigene Offer wurde verworfen und das eingehende angenommen
+    AcceptedRemoteOffer { answer_sdp: String },
+    /// Erneutes Offer nach gescheitertem Verbindungsaufbau, ohne Klingeln angenommen
+    AcceptedRetry { answer_sdp: String },
+    /// ICE Restart eines verbundenen Teilnehmers, auf der bestehenden Verbindung angenommen
+    AcceptedIceRestart { answer_sdp: String },
+}
+
+/// Entscheidet bei Glare, wessen Offer bestehen bleibt
+///
+/// Beide Seiten kommen ohne Absprache zum selben Ergebnis: Die lexikographisch
+/// kleinere Peer-ID bleibt Anrufer.
+pub fn keeps_own_offer(own_peer_id: &str, remote_peer_id: &str) -> bool {
+    own_peer_id < remote_peer_id
+}
+
+/// Lokale Session Description (für manuelles Signaling)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LocalDescription {
+    /// "offer" oder "answer"
+    pub sdp_type: String,
+    pub sdp: String,
+}
+
+// ============================================================================
+// DTLS FINGERPRINTS
+// ============================================================================
+
+/// Fingerprint eines DTLS-Zertifikats (`a=fingerprint:<algorithm> <value>`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DtlsFingerprint {
+    pub algorithm: String,
+    pub value: String,
+}
+
+/// Lokaler und entfernter DTLS Fingerprint einer Verbindung
+///
+/// Diese Fingerprints sichern die Medienebene (DTLS-SRTP) ab und sind
+/// unabhängig von der Ed25519-Signatur der Signaling-Nachrichten: Die Signatur
+/// beweist, wer eine Nachricht gesendet hat, der Fingerprint, mit wem die
+/// Audio-Verbindung tatsächlich verschlüsselt ist. Stimmen die Fingerprints
+/// beider Seiten (out-of-band verglichen) überein, gibt es keinen MITM.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DtlsFingerprints {
+    pub local: Option<DtlsFingerprint>,
+    pub remote: Option<DtlsFingerprint>,
+}
+
+/// Mit der Fingerprint-Signatur verifizierte Identität der Gegenstelle
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RemoteIdentity {
+    pub peer_id: String,
+    pub public_key: String,
+    /// Gegen einen vorher bekannten Key geprüft (statt Trust on First Use)
+    pub pinned: bool,
+}
+
+/// Verschlüsselungsstatus eines Anrufs
+///
+/// Wird aus der aktiven Peer Connection gelesen, damit die UI die
+/// Verschlüsselung nur anzeigt, wenn sie tatsächlich ausgehandelt wurde.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SecurityInfo {
+    /// DTLS-Handshake abgeschlossen und SRTP-Profil ausgehandelt
+    pub dtls_srtp_active: bool,
+    /// Zustand des DTLS Transports (z.B. "connected")
+    pub dtls_state: String,
+    /// Ausgehandeltes SRTP-Profil, z.B. `SRTP_AEAD_AES_128_GCM`
+    pub srtp_profile: Option<String>,
+    pub fingerprints: DtlsFingerprints,
+    /// Verifizierte Identität, `None` bei unsigniertem SDP
+    pub remote_identity: Option<RemoteIdentity>,
+}
+
+impl SecurityInfo {
+    /// Ende-zu-Ende verschlüsselt und die Gegenstelle ist verifiziert
+    pub fn is_end_to_end_verified(&self) -> bool {
+        self.dtls_srtp_active && self.remote_identity.is_some()
+    }
+}
+
+/// Parst und validiert einen ICE Candidate im JSON-Format (`RTCIceCandidateInit`)
+pub fn parse_ice_candidate(candidate_json: &str) -> Result<RTCIceCandidateInit, CallEngineError> {
+    let candidate: RTCIceCandidateInit = serde_json::from_str(candidate_json)
+        .map_err(|e| CallEngineError::InvalidCandidate(e.to_string()))?;
+
+    if !candidate.candidate.trim_start().starts_with("candidate:") {
+        return Err(CallEngineError::InvalidCandidate(
+            "missing 'candidate:' attribute".to_string(),
+        ));
+    }
+
+    Ok(candidate)
+}
+
+/// Loggt einen Candidate mit maskierter Adresse und meldet ihn als Event
+fn log_candidate(
+    candidate: &str,
+    direction: CandidateDirection,
+    event_tx: &broadcast::Sender<CallEvent>,
+) {
+    match summarize_candidate(candidate, direction) {
+        Some(summary) => {
+            tracing::info!(
+                "ICE candidate ({:?}): {} {} {}:{}",
+                summary.direction,
+                summary.candidate_type,
+                summary.protocol,
+                summary.address,
+                summary.port
+            );
+            let _ = event_tx.send(CallEvent::IceCandidateLogged(summary));
+        }
+        None => tracing::debug!("ICE candidate ({:?}) could not be summarized", direction),
+    }
+}
+
+/// Liest den ersten DTLS Fingerprint aus einem SDP
+pub fn parse_dtls_fingerprint(sdp: &str) -> Option<DtlsFingerprint> {
+    sdp.lines()
+        .filter_map(|line| line.trim().strip_prefix("a=fingerprint:"))
+        .find_map(|value| {
+            let (algorithm, fingerprint) = value.split_once(' ')?;
+            Some(DtlsFingerprint {
+                algorithm: algorithm.to_lowercase(),
+                value: fingerprint.trim().to_uppercase(),
+            })
+        })
+}
+
+/// Maximale Wartezeit beim Schließen der Peer Connection im Shutdown/Drop
+const SHUTDOWN_CLOSE_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Wie lange `Ended` nach einem Anruf stehen bleibt, bevor auf `Idle` gewechselt wird
+const ENDED_RESET_DELAY: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Maximales Alter einer vorgewärmten Peer Connection
+///
+/// Danach sind NAT-Mappings der gesammelten Candidates möglicherweise schon
+/// abgelaufen, es wird eine neue Verbindung aufgebaut.
+const PREWARM_MAX_AGE: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Maximale Anzahl gleichzeitig verbundener Gegenstellen in einem Anruf
+pub const MAX_CALL_PEERS: usize = 2;
+
+/// Wie lange ein Answer auf die Peer Connection des Anrufers wartet
+///
+/// Zwischen `Calling` und dem Hinterlegen der Verbindung wird das Offer
+/// erstellt. Ein sehr schnelles Answer kann in diese Lücke fallen.
+const CONNECTION_READY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Wie lange der Angerufene nach gescheitertem Verbindungsaufbau auf das
+/// erneute Offer wartet, bevor der Anruf endet
+const SETUP_RETRY_OFFER_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Wie lange eine unterbrochene Verbindung per ICE Restart wiederhergestellt
+/// werden darf, bevor der Teilnehmer den Anruf verlässt
+const ICE_RESTART_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Abstand der `CallEvent::Duration` Events
+const CALL_TIMER_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Abstand der `CallEvent::Stats` Events
+const STATS_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Wie oft der Sende-Task nach fertigen Frames schaut (kürzer als jeder Frame)
+const AUDIO_SEND_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(5);
+
+// ============================================================================
+// IDENTITY BINDING
+// ============================================================================
+
+/// SDP-Attribut mit dem Ed25519 Public Key des Absenders
+const SDP_IDENTITY_KEY_ATTR: &str = "a=x-identity-key:";
+
+/// SDP-Attribut mit der Ed25519-Signatur über den DTLS Fingerprint
+const SDP_FINGERPRINT_SIGNATURE_ATTR: &str = "a=x-fingerprint-signature:";
+
+/// Signiert den DTLS Fingerprint eines lokalen SDP mit der Ed25519-Identität
+///
+/// Public Key und Signatur werden als Session-Attribute vor der ersten
+/// Media-Section eingefügt. Ein manipulierter Fingerprint (MITM durch den
+/// Signaling-Server) fällt damit bei der Gegenstelle auf.
+pub fn sign_sdp_fingerprint(sdp: &str, keypair: &KeyPair) -> String {
+    let Some(fingerprint) = parse_dtls_fingerprint(sdp) else {
+        return sdp.to_string();
+    };
+
+    let signature = keypair.sign_dtls_fingerprint(&fingerprint.algorithm, &fingerprint.value);
+    let attributes = format!(
+        "{}{}\r\n{}{}\r\n",
+        SDP_IDENTITY_KEY_ATTR,
+        keypair.public_key_base64(),
+        SDP_FINGERPRINT_SIGNATURE_ATTR,
+        signature
+    );
+
+    // Direkt vor der ersten Media-Section einfügen (Session-Level)
+    let mut signed = sdp.to_string();
+    match sdp.find("\nm=") {
+        Some(pos) => signed.insert_str(pos + 1, &attributes),
+        None => signed.push_str(&attributes),
+    }
+    signed
+}
+
+/// Prüft die Fingerprint-Signatur eines entfernten SDP
+///
+/// Ist `expected_public_key` gesetzt (gepinnter Key oder LAN-Peer), muss das
+/// SDP mit genau diesem Key signiert sein. Ohne erwarteten Key wird eine
+/// vorhandene Signatur gegen den mitgeschickten Key geprüft und SDPs älterer
+/// Clients ohne Signatur werden akzeptiert. Ob dem mitgeschickten Key
+/// vertraut wird (Trust on First Use), entscheidet der Aufrufer.
+///
+/// Gibt den verifizierten Public Key zurück, `None` bei unsigniertem SDP.
+pub fn verify_sdp_fingerprint(
+    sdp: &str,
+    expected_public_key: Option<&str>,
+) -> Result<Option<String>, CallEngineError> {
+    let attribute = |prefix: &str| {
+        sdp.lines()
+            .find_map(|line| line.trim().strip_prefix(prefix))
+            .map(|value| value.trim().to_string())
+    };
+
+    let (public_key, signature) = match (
+        attribute(SDP_IDENTITY_KEY_ATTR),
+        attribute(SDP_FINGERPRINT_SIGNATURE_ATTR),
+    ) {
+        (Some(key), Some(signature)) => (key, signature),
+        _ if expected_public_key.is_some() => {
+            return Err(CallEngineError::IdentityVerification(
+                "missing fingerprint signature".to_string(),
+            ));
+        }
+        _ => return Ok(None),
+    };
+
+    if let Some(expected) = expected_public_key {
+        if expected != public_key {
+            return Err(CallEngineError::IdentityVerification(
+                "identity key does not match the known key".to_string(),
+            ));
+        }
+    }
+
+    let fingerprint = parse_dtls_fingerprint(sdp)
+        .ok_or_else(|| CallEngineError::InvalidSdp("missing DTLS fingerprint".to_string()))?;
+
+    KeyPair::verify_dtls_fingerprint(
+        &public_key,
+        &fingerprint.algorithm,
+        &fingerprint.value,
+        &signature,
+    )
+    .map_err(|e| CallEngineError::IdentityVerification(e.to_string()))?;
+
+    Ok(Some(public_key))
+}
+
 // ============================================================================
 // ICE SERVER CONFIGURATION
 // ============================================================================
@@ -113,478 +671,4421 @@ pub fn default_ice_servers() -> Vec<RTCIceServer> {
     ]
 }
 
+/// Welche ICE Candidates verwendet werden dürfen
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IceTransportPolicy {
+    /// Host-, Server-Reflexive- und Relay-Candidates
+    #[default]
+    All,
+    /// Nur über TURN: die eigene IP-Adresse bleibt der Gegenstelle verborgen
+    Relay,
+}
+
+impl From<IceTransportPolicy> for RTCIceTransportPolicy {
+    fn from(policy: IceTransportPolicy) -> Self {
+        match policy {
+            IceTransportPolicy::All => RTCIceTransportPolicy::All,
+            IceTransportPolicy::Relay => RTCIceTransportPolicy::Relay,
+        }
+    }
+}
+
+/// Prüft, ob unter den ICE Servern ein TURN-Server ist
+fn has_turn_server(ice_servers: &[RTCIceServer]) -> bool {
+    ice_servers
+        .iter()
+        .flat_map(|server| &server.urls)
+        .any(|url| url.starts_with("turn:") || url.starts_with("turns:"))
+}
+
+// ============================================================================
+// CODECS
+// ============================================================================
+
+/// Beschreibung eines registrierten Codecs (für Diagnose und Einstellungen)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CodecInfo {
+    pub mime_type: String,
+    pub clock_rate: u32,
+    pub channels: u16,
+    pub payload_type: u8,
+    pub sdp_fmtp_line: String,
+}
+
+impl From<&RTCRtpCodecParameters> for CodecInfo {
+    fn from(codec: &RTCRtpCodecParameters) -> Self {
+        Self {
+            mime_type: codec.capability.mime_type.clone(),
+            clock_rate: codec.capability.clock_rate,
+            channels: codec.capability.channels,
+            payload_type: codec.payload_type,
+            sdp_fmtp_line: codec.capability.sdp_fmtp_line.clone(),
+        }
+    }
+}
+
 // ============================================================================
 // CALL ENGINE
 // ============================================================================
 
+/// Vorab erstellte Peer Connection mit lokalem Offer und laufendem ICE Gathering
+struct PrewarmedConnection {
+    pc: Arc<RTCPeerConnection>,
+    audio_track: Arc<TrackLocalStaticRTP>,
+    offer_sdp: String,
+    created_at: std::time::Instant,
+}
+
+/// Teilnehmer eines Anrufs mit eigener Peer Connection
+struct PeerSession {
+    /// State dieses Teilnehmers (nie `Idle` oder `Ended`)
+    state: CallState,
+    /// `None`, solange der Anruf klingelt oder noch aufgebaut wird
+    pc: Option<Arc<RTCPeerConnection>>,
+    /// Lokaler Audio-Track zu diesem Teilnehmer (ausgehendes RTP)
+    local_track: Option<Arc<TrackLocalStaticRTP>>,
+    /// Erstmals `Connected` (bleibt bei kurzen Unterbrechungen erhalten)
+    connected_at: Option<std::time::Instant>,
+}
+
+/// Teilnehmer des aktuellen Anrufs nach Peer-ID
+type PeerSessions = Arc<Mutex<HashMap<String, PeerSession>>>;
+
 /// WebRTC Call Engine
 pub struct CallEngine {
+    /// Gesamt-State, abgeleitet aus den States der Teilnehmer
     state: Arc<Mutex<CallState>>,
-    peer_connection: Arc<Mutex<Option<Arc<RTCPeerConnection>>>>,
+    /// Zählt beendete Anrufe, damit ein verzögerter Reset nur den eigenen
+    /// `Ended`-State auf `Idle` setzt
+    ended_generation: Arc<AtomicU64>,
+    peers: PeerSessions,
     audio_handler: Arc<Mutex<Option<AudioHandler>>>,
+    /// Vorbereiteter Audio Handler mit gecachten Konfigurationen
+    prewarmed_audio: Mutex<Option<AudioHandler>>,
+    /// Vorgewärmte Peer Connection für den nächsten ausgehenden Anruf
+    prewarmed_connection: Mutex<Option<PrewarmedConnection>>,
+    /// Vorpufferung des Playbacks in Frames (Latenz vs. Robustheit)
+    playback_prefill_frames: Mutex<usize>,
+    /// Samples pro Audio-Frame (Latenz vs. Paket-Overhead)
+    audio_frame_size: Mutex<usize>,
+    /// Mikrofon und Lautsprecher für folgende Anrufe
+    audio_devices: Mutex<AudioDeviceSelection>,
+    /// Limiter für das Playback (Schutz vor Übersteuerung)
+    output_limiter: Mutex<OutputLimiterConfig>,
+    /// Zusätzliche Verzögerung des gesendeten Audios in Millisekunden
+    output_delay_ms: Mutex<u32>,
+    /// Gewünschter Mute-Status, gilt auch für folgende Anrufe
+    muted: Mutex<bool>,
+    /// Stille nicht senden (Sprachaktivitätserkennung)
+    vad_enabled: Mutex<bool>,
+    /// RMS-Schwelle der Sprachaktivitätserkennung
+    vad_threshold: Mutex<f32>,
+    /// Push-to-Talk statt offenem Mikrofon, gilt auch für folgende Anrufe
+    ptt_enabled: Mutex<bool>,
+    /// Push-to-Talk-Taste gedrückt
+    ptt_active: Mutex<bool>,
+    /// Klingelton, solange ein eingehender Anruf klingelt
+    ringtone: Ringtone,
+    /// ICE Candidates je Peer, die vor der Remote Description eingetroffen sind
+    pending_candidates: Arc<Mutex<HashMap<String, Vec<RTCIceCandidateInit>>>>,
+    /// Lokal gesammelte ICE Candidates (JSON) des aktuellen Anrufs
+    local_candidates: Arc<Mutex<Vec<String>>>,
+    /// Eigene Identität zum Signieren des DTLS Fingerprints
+    identity: Mutex<Option<Arc<KeyPair>>>,
+    /// Erwartete Public Keys der Peers (gepinnt oder aus der LAN Peer-ID)
+    expected_peer_keys: Mutex<HashMap<String, String>>,
+    /// Vom Verzeichnis (Signaling-Server) gemeldete Public Keys der Peers
+    directory_peer_keys: Mutex<HashMap<String, String>>,
+    /// Verifizierte Identität der Gegenstelle, die den Gesamt-State bestimmt
+    remote_identity: Mutex<Option<RemoteIdentity>>,
+    /// Simulierte Netzwerkbedingungen für den ausgehenden RTP-Pfad (nur Debug)
+    network_simulation: Arc<Mutex<NetworkSimulation>>,
+    /// Obergrenze für die Sende-Bitrate in bps (getaktete Verbindungen)
+    max_bitrate: Arc<Mutex<Option<u32>>>,
+    /// Ziel-Bitrate des Opus-Encoders in bps
+    codec_bitrate: Arc<AtomicU32>,
+    /// Tasks des laufenden Anrufs (Audio senden, Dauer und Statistik melden)
+    call_tasks: Mutex<Vec<tokio::task::JoinHandle<()>>>,
+    /// Verlust und Jitter der empfangenen Streams nach SSRC
+    receive_quality: Arc<Mutex<HashMap<u32, ReceiveQuality>>>,
+    /// Loggt alle ICE Candidates mit maskierter Adresse
+    verbose_ice_logging: Arc<AtomicBool>,
+    /// Kurzer Echo-Check nach dem Verbindungsaufbau (Standard: aus)
+    echo_check_enabled: Arc<AtomicBool>,
+    /// Einmal neu aufbauen, wenn ICE vor dem Verbinden scheitert (Standard: aus)
+    setup_retry_enabled: AtomicBool,
+    /// Teilnehmer, deren Verbindungsaufbau noch wiederholt werden darf
+    setup_retry_allowed: Arc<Mutex<HashSet<String>>>,
+    /// Gesendete und empfangene RTP-Pakete (für die Erkennung einseitigen Audios)
+    rtp_counters: Arc<RtpCounters>,
+    /// Weckt Answers, die auf `attach_connection` warten
+    connection_attached: Notify,
     event_tx: broadcast::Sender<CallEvent>,
-    ice_servers: Vec<RTCIceServer>,
+    /// STUN-Server und vom Nutzer konfigurierte TURN-Server
+    ice_servers: Mutex<Vec<RTCIceServer>>,
+    /// Relay-only versteckt Host- und Server-Reflexive-Candidates
+    ice_transport_policy: Mutex<IceTransportPolicy>,
 }
 
 impl CallEngine {
     /// Erstellt eine neue CallEngine
     pub fn new() -> Self {
-        let (event_tx, _) = broadcast::channel(100);
+        let (event_tx, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
 
         Self {
             state: Arc::new(Mutex::new(CallState::Idle)),
-            peer_connection: Arc::new(Mutex::new(None)),
+            ended_generation: Arc::new(AtomicU64::new(0)),
+            peers: Arc::new(Mutex::new(HashMap::new())),
             audio_handler: Arc::new(Mutex::new(None)),
+            prewarmed_audio: Mutex::new(None),
+            prewarmed_connection: Mutex::new(None),
+            playback_prefill_frames: Mutex::new(DEFAULT_PREFILL_FRAMES),
+            audio_frame_size: Mutex::new(FRAME_SIZE),
+            audio_devices: Mutex::new(AudioDeviceSelection::default()),
+            output_limiter: Mutex::new(OutputLimiterConfig::default()),
+            output_delay_ms: Mutex::new(0),
+            muted: Mutex::new(false),
+            vad_enabled: Mutex::new(false),
+            vad_threshold: Mutex::new(DEFAULT_VAD_THRESHOLD),
+            ptt_enabled: Mutex::new(false),
+            ptt_active: Mutex::new(false),
+            ringtone: Ringtone::new(),
+            pending_candidates: Arc::new(Mutex::new(HashMap::new())),
+            local_candidates: Arc::new(Mutex::new(Vec::new())),
+            identity: Mutex::new(None),
+            expected_peer_keys: Mutex::new(HashMap::new()),
+            directory_peer_keys: Mutex::new(HashMap::new()),
+            remote_identity: Mutex::new(None),
+            network_simulation: Arc::new(Mutex::new(NetworkSimulation::default())),
+            max_bitrate: Arc::new(Mutex::new(None)),
+            codec_bitrate: Arc::new(AtomicU32::new(DEFAULT_OPUS_BITRATE)),
+            call_tasks: Mutex::new(Vec::new()),
+            receive_quality: Arc::new(Mutex::new(HashMap::new())),
+            verbose_ice_logging: Arc::new(AtomicBool::new(false)),
+            echo_check_enabled: Arc::new(AtomicBool::new(false)),
+            setup_retry_enabled: AtomicBool::new(false),
+            setup_retry_allowed: Arc::new(Mutex::new(HashSet::new())),
+            rtp_counters: Arc::new(RtpCounters::default()),
+            connection_attached: Notify::new(),
             event_tx,
-            ice_servers: default_ice_servers(),
+            ice_servers: Mutex::new(default_ice_servers()),
+            ice_transport_policy: Mutex::new(IceTransportPolicy::default()),
         }
     }
 
-    /// Setzt optionale TURN-Server Credentials
-    #[allow(dead_code)]
-    pub fn set_turn_server(&mut self, url: String, username: String, credential: String) {
-        self.ice_servers.push(RTCIceServer {
+    /// Fügt einen TURN-Server hinzu, gilt für alle danach aufgebauten Verbindungen
+    pub fn add_turn_server(
+        &self,
+        url: String,
+        username: String,
+        credential: String,
+    ) -> Result<(), CallEngineError> {
+        if !(url.starts_with("turn:") || url.starts_with("turns:")) {
+            return Err(CallEngineError::InvalidConfig(format!(
+                "Not a TURN server url: {}",
+                url
+            )));
+        }
+
+        tracing::info!("Adding TURN server {}", url);
+        self.ice_servers.lock().push(RTCIceServer {
             urls: vec![url],
             username,
             credential,
             ..Default::default()
         });
+        self.discard_prewarmed_connection();
+        Ok(())
     }
 
-    /// Gibt einen Event-Receiver zurück
-    pub fn subscribe(&self) -> broadcast::Receiver<CallEvent> {
-        self.event_tx.subscribe()
+    /// Entfernt alle TURN-Server, die STUN-Server bleiben erhalten
+    ///
+    /// Relay-only ist ohne TURN-Server nicht möglich, die Policy fällt dann
+    /// auf `All` zurück.
+    pub fn clear_turn_servers(&self) {
+        self.ice_servers
+            .lock()
+            .retain(|server| !has_turn_server(std::slice::from_ref(server)));
+
+        let mut policy = self.ice_transport_policy.lock();
+        if *policy == IceTransportPolicy::Relay {
+            tracing::warn!("TURN servers cleared, falling back to ICE transport policy 'all'");
+            *policy = IceTransportPolicy::All;
+        }
+        drop(policy);
+
+        self.discard_prewarmed_connection();
     }
 
-    /// Gibt den aktuellen Call-Status zurück
-    pub fn state(&self) -> CallState {
-        self.state.lock().clone()
+    /// Gibt die aktuell konfigurierten ICE Server zurück
+    fn ice_servers(&self) -> Vec<RTCIceServer> {
+        self.ice_servers.lock().clone()
     }
 
-    /// Startet einen ausgehenden Anruf
-    ///
-    /// Gibt das SDP Offer zurück, das an den Peer gesendet werden muss.
-    pub async fn start_call(&self, peer_id: String) -> Result<String, CallEngineError> {
-        // Prüfen ob bereits ein Anruf aktiv ist
-        {
-            let state = self.state.lock();
-            if *state != CallState::Idle {
-                return Err(CallEngineError::AlreadyInCall);
-            }
+    /// Verwirft die vorgewärmte Verbindung, ihre Candidates stammen noch von
+    /// den alten ICE Servern
+    fn discard_prewarmed_connection(&self) {
+        let prewarmed = self.prewarmed_connection.lock().take();
+        if let Some(prewarmed) = prewarmed {
+            Self::close_blocking(prewarmed.pc);
         }
+    }
 
-        // State aktualisieren
-        self.set_state(CallState::Calling {
-            peer_id: peer_id.clone(),
-        });
-
-        // Peer Connection erstellen
-        let pc = self.create_peer_connection().await?;
+    /// Setzt die ICE Transport Policy für künftige Anrufe
+    ///
+    /// `Relay` ist nur mit konfiguriertem TURN-Server möglich, sonst käme
+    /// keine Verbindung zustande. LAN-Anrufe sind nicht betroffen.
+    pub fn set_ice_transport_policy(
+        &self,
+        policy: IceTransportPolicy,
+    ) -> Result<(), CallEngineError> {
+        if policy == IceTransportPolicy::Relay && !has_turn_server(&self.ice_servers.lock()) {
+            return Err(CallEngineError::InvalidConfig(
+                "Relay-only mode requires a TURN server".to_string(),
+            ));
+        }
 
-        // Audio Track hinzufügen
-        let audio_track = Arc::new(TrackLocalStaticRTP::new(
-            RTCRtpCodecCapability {
-                mime_type: "audio/opus".to_string(),
-                clock_rate: SAMPLE_RATE,
-                channels: 1,
-                ..Default::default()
-            },
-            "audio".to_string(),
-            "call-app".to_string(),
-        ));
+        let previous = std::mem::replace(&mut *self.ice_transport_policy.lock(), policy);
+        if previous != policy {
+            // Vorgewärmte Candidates wurden noch mit der alten Policy gesammelt
+            if let Some(prewarmed) = self.prewarmed_connection.lock().take() {
+                tokio::spawn(async move {
+                    let _ = prewarmed.pc.close().await;
+                });
+            }
+        }
 
-        pc.add_track(Arc::clone(&audio_track) as Arc<dyn TrackLocal + Send + Sync>)
-            .await
-            .map_err(|e| CallEngineError::WebRTC(e.to_string()))?;
+        tracing::info!("ICE transport policy: {:?}", policy);
+        Ok(())
+    }
 
-        // SDP Offer erstellen
-        let offer = pc
-            .create_offer(None)
-            .await
-            .map_err(|e| CallEngineError::WebRTC(e.to_string()))?;
+    /// Gibt die aktuelle ICE Transport Policy zurück
+    pub fn ice_transport_policy(&self) -> IceTransportPolicy {
+        *self.ice_transport_policy.lock()
+    }
 
-        // Local Description setzen
-        pc.set_local_description(offer.clone())
-            .await
-            .map_err(|e| CallEngineError::WebRTC(e.to_string()))?;
+    /// Gibt die URLs der konfigurierten ICE Server zurück (ohne TURN Credentials)
+    pub fn ice_server_urls(&self) -> Vec<String> {
+        self.ice_servers
+            .lock()
+            .iter()
+            .flat_map(|server| server.urls.iter().cloned())
+            .collect()
+    }
 
-        // Peer Connection speichern
-        *self.peer_connection.lock() = Some(pc);
+    /// Setzt die Identität, mit der der eigene DTLS Fingerprint signiert wird
+    pub fn set_identity(&self, keypair: Arc<KeyPair>) {
+        *self.identity.lock() = Some(keypair);
+    }
 
-        // Audio initialisieren
-        self.init_audio()?;
+    /// Hinterlegt den erwarteten Public Key eines Peers
+    ///
+    /// SDPs dieses Peers werden nur mit einer gültigen Fingerprint-Signatur
+    /// dieses Keys akzeptiert.
+    pub fn expect_peer_key(&self, peer_id: String, public_key: String) {
+        self.expected_peer_keys.lock().insert(peer_id, public_key);
+    }
 
-        Ok(offer.sdp)
+    /// Hinterlegt den vom Verzeichnis gemeldeten Public Key eines Peers
+    ///
+    /// Anders als bei `expect_peer_key` bleibt die Signatur optional, ein im
+    /// SDP mitgeschickter Key wird aber nur bei Übereinstimmung anerkannt.
+    pub fn set_directory_key(&self, peer_id: String, public_key: String) {
+        self.directory_peer_keys.lock().insert(peer_id, public_key);
     }
 
-    /// Akzeptiert einen eingehenden Anruf
+    /// Setzt simulierte Netzwerkbedingungen für ausgehende RTP-Pakete
     ///
-    /// `offer_sdp` ist das SDP Offer vom Anrufer.
-    /// Gibt das SDP Answer zurück, das an den Anrufer gesendet werden muss.
-    pub async fn accept_call(
+    /// Wirkt nur in Debug-Builds. Alle Werte 0 deaktivieren die Simulation.
+    pub fn set_network_simulation(
         &self,
-        peer_id: String,
-        offer_sdp: String,
-    ) -> Result<String, CallEngineError> {
-        // Prüfen ob bereits ein Anruf aktiv ist
-        {
-            let state = self.state.lock();
-            match &*state {
-                CallState::Ringing { .. } => {}
-                CallState::Idle => {}
-                _ => return Err(CallEngineError::AlreadyInCall),
-            }
+        loss_pct: f32,
+        added_latency_ms: u32,
+        jitter_ms: u32,
+    ) -> Result<(), CallEngineError> {
+        let simulation = NetworkSimulation::new(loss_pct, added_latency_ms, jitter_ms)
+            .map_err(CallEngineError::InvalidConfig)?;
+
+        if !cfg!(debug_assertions) && simulation.is_active() {
+            tracing::warn!("Network simulation is ignored in release builds");
         }
 
-        // State aktualisieren
-        self.set_state(CallState::Connecting {
-            peer_id: peer_id.clone(),
-        });
+        tracing::info!("Network simulation: {:?}", simulation);
+        *self.network_simulation.lock() = simulation;
+        Ok(())
+    }
 
-        // Peer Connection erstellen
-        let pc = self.create_peer_connection().await?;
+    /// Gibt die aktuelle Netzwerk-Simulation zurück
+    pub fn network_simulation(&self) -> NetworkSimulation {
+        *self.network_simulation.lock()
+    }
 
-        // Remote Description setzen (das Offer)
-        let offer = RTCSessionDescription::offer(offer_sdp)
-            .map_err(|e| CallEngineError::InvalidSdp(e.to_string()))?;
+    /// Aktiviert oder deaktiviert das ausführliche ICE-Logging
+    ///
+    /// Gesammelte und empfangene Candidates werden dann mit Typ und maskierter
+    /// Adresse geloggt und als `CallEvent::IceCandidateLogged` gemeldet.
+    pub fn set_verbose_ice_logging(&self, enabled: bool) {
+        self.verbose_ice_logging.store(enabled, Ordering::Relaxed);
+    }
 
-        pc.set_remote_description(offer)
-            .await
-            .map_err(|e| CallEngineError::WebRTC(e.to_string()))?;
+    /// Prüft, ob ausführliches ICE-Logging aktiv ist
+    pub fn verbose_ice_logging(&self) -> bool {
+        self.verbose_ice_logging.load(Ordering::Relaxed)
+    }
 
-        // Audio Track hinzufügen
-        let audio_track = Arc::new(TrackLocalStaticRTP::new(
-            RTCRtpCodecCapability {
-                mime_type: "audio/opus".to_string(),
-                clock_rate: SAMPLE_RATE,
-                channels: 1,
-                ..Default::default()
-            },
-            "audio".to_string(),
-            "call-app".to_string(),
-        ));
+    /// Aktiviert den Echo-Check nach dem Verbindungsaufbau
+    ///
+    /// Einmal pro Anruf wird direkt nach dem Verbinden ein kurzes, leises
+    /// Prüfsignal abgespielt. Findet es sich im Mikrofon wieder, wird
+    /// `CallEvent::EchoDetected` gemeldet.
+    pub fn set_echo_check_enabled(&self, enabled: bool) {
+        self.echo_check_enabled.store(enabled, Ordering::Relaxed);
+    }
 
-        pc.add_track(Arc::clone(&audio_track) as Arc<dyn TrackLocal + Send + Sync>)
-            .await
-            .map_err(|e| CallEngineError::WebRTC(e.to_string()))?;
+    /// Prüft, ob der Echo-Check aktiv ist
+    pub fn echo_check_enabled(&self) -> bool {
+        self.echo_check_enabled.load(Ordering::Relaxed)
+    }
 
-        // SDP Answer erstellen
-        let answer = pc
-            .create_answer(None)
+    /// Aktiviert den einmaligen neuen Verbindungsaufbau bei ICE-Fehlern
+    ///
+    /// Scheitert ICE bei einem Anruf über den Signaling-Server, bevor er
+    /// verbunden war, werden Offer und Answer einmal mit frischen Peer
+    /// Connections wiederholt, statt den Anruf zu beenden. Gilt für Anrufe,
+    /// die danach beginnen. Ohne die Einstellung beim Angerufenen klingelt
+    /// dort das erneute Offer wie ein neuer Anruf.
+    pub fn set_setup_retry_enabled(&self, enabled: bool) {
+        self.setup_retry_enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Prüft, ob der neue Verbindungsaufbau bei ICE-Fehlern aktiv ist
+    pub fn setup_retry_enabled(&self) -> bool {
+        self.setup_retry_enabled.load(Ordering::Relaxed)
+    }
+
+    /// Bricht einen laufenden Echo-Check ohne Ergebnis ab
+    pub fn skip_echo_check(&self) {
+        if let Some(audio) = self.audio_handler.lock().as_ref() {
+            if audio.finish_echo_check().is_some() {
+                tracing::info!("Echo check skipped");
+            }
+        }
+    }
+
+    /// Gibt einen Event-Receiver zurück
+    pub fn subscribe(&self) -> broadcast::Receiver<CallEvent> {
+        self.event_tx.subscribe()
+    }
+
+    /// Gibt den aktuellen Call-Status zurück
+    pub fn state(&self) -> CallState {
+        self.state.lock().clone()
+    }
+
+    /// Gibt die States aller Teilnehmer zurück (sortiert nach Peer-ID)
+    pub fn peer_states(&self) -> Vec<CallState> {
+        let mut states: Vec<CallState> = self
+            .peers
+            .lock()
+            .values()
+            .map(|session| session.state.clone())
+            .collect();
+        states.sort_by(|a, b| a.peer_id().cmp(&b.peer_id()));
+        states
+    }
+
+    /// Gibt zurück, ob ein Peer am aktuellen Anruf beteiligt ist
+    pub fn has_peer(&self, peer_id: &str) -> bool {
+        self.peers.lock().contains_key(peer_id)
+    }
+
+    /// Gibt die in der MediaEngine registrierten Codecs zurück (Audio vor Video)
+    ///
+    /// Die MediaEngine gibt ihre Codecs nur über die Parameter eines Senders
+    /// heraus. Dafür wird kurz eine Peer Connection erstellt, ohne ICE zu starten.
+    pub async fn supported_codecs(&self) -> Result<Vec<CodecInfo>, CallEngineError> {
+        let pc = Self::build_api()?
+            .new_peer_connection(RTCConfiguration::default())
+            .await
+            .map_err(|e| CallEngineError::WebRTC(e.to_string()))?;
+
+        let mut codecs = Vec::new();
+        for kind in [RTPCodecType::Audio, RTPCodecType::Video] {
+            let transceiver = match pc.add_transceiver_from_kind(kind, None).await {
+                Ok(transceiver) => transceiver,
+                Err(e) => {
+                    let _ = pc.close().await;
+                    return Err(CallEngineError::WebRTC(e.to_string()));
+                }
+            };
+            let parameters = transceiver.sender().await.get_parameters().await;
+            codecs.extend(parameters.rtp_parameters.codecs.iter().map(CodecInfo::from));
+        }
+
+        let _ = pc.close().await;
+        Ok(codecs)
+    }
+
+    /// Startet einen ausgehenden Anruf
+    ///
+    /// Gibt das SDP Offer zurück, das an den Peer gesendet werden muss.
+    pub async fn start_call(&self, peer_id: String) -> Result<String, CallEngineError> {
+        let sdp = self
+            .start_call_with(peer_id.clone(), self.ice_servers(), false)
+            .await?;
+        self.allow_setup_retry(&peer_id);
+        Ok(sdp)
+    }
+
+    /// Startet einen ausgehenden Anruf im lokalen Netzwerk
+    ///
+    /// Ohne STUN-Server und ohne Trickle ICE: es wird gewartet bis alle
+    /// Host-Candidates gesammelt sind, das zurückgegebene SDP Offer ist vollständig.
+    pub async fn start_lan_call(&self, peer_id: String) -> Result<String, CallEngineError> {
+        self.start_call_with(peer_id, Vec::new(), true).await
+    }
+
+    /// Akzeptiert einen eingehenden Anruf
+    ///
+    /// `offer_sdp` ist das SDP Offer vom Anrufer.
+    /// Gibt das SDP Answer zurück, das an den Anrufer gesendet werden muss.
+    /// Klingelt der Anruf nicht mehr (vom Anrufer abgebrochen oder beendet),
+    /// schlägt das mit `CallNoLongerActive` fehl, statt ein Answer zu
+    /// erzeugen, auf das niemand mehr wartet.
+    pub async fn accept_call(
+        &self,
+        peer_id: String,
+        offer_sdp: String,
+    ) -> Result<String, CallEngineError> {
+        let sdp = self
+            .accept_call_with(peer_id.clone(), offer_sdp, self.ice_servers(), false, true)
+            .await?;
+        self.allow_setup_retry(&peer_id);
+        Ok(sdp)
+    }
+
+    /// Akzeptiert ein direkt übergebenes Offer ohne vorheriges Klingeln
+    ///
+    /// Für manuelles Signaling und die Glare-Auflösung. Läuft bereits ein
+    /// Anruf, schlägt das mit `AlreadyInCall` fehl.
+    pub async fn accept_offer(
+        &self,
+        peer_id: String,
+        offer_sdp: String,
+    ) -> Result<String, CallEngineError> {
+        self.accept_call_with(peer_id, offer_sdp, self.ice_servers(), false, false)
+            .await
+    }
+
+    /// Akzeptiert einen eingehenden Anruf aus dem lokalen Netzwerk
+    ///
+    /// Das zurückgegebene SDP Answer enthält bereits alle Host-Candidates.
+    pub async fn accept_lan_call(
+        &self,
+        peer_id: String,
+        offer_sdp: String,
+    ) -> Result<String, CallEngineError> {
+        self.accept_call_with(peer_id, offer_sdp, Vec::new(), true, true)
+            .await
+    }
+
+    /// Baut einen ausgehenden Anruf nach gescheitertem ICE neu auf
+    ///
+    /// Nach `CallEvent::Retrying` mit `outgoing`: erstellt eine neue Peer
+    /// Connection mit frischem ICE Gathering und gibt das neue SDP Offer
+    /// zurück, das wie beim Anrufstart an den Peer gesendet werden muss.
+    pub async fn retry_call(&self, peer_id: &str) -> Result<String, CallEngineError> {
+        let awaiting = self.peers.lock().get(peer_id).is_some_and(|session| {
+            matches!(session.state, CallState::Calling { .. }) && session.pc.is_none()
+        });
+        if !awaiting {
+            return Err(CallEngineError::NoActiveCall);
+        }
+
+        self.local_candidates.lock().clear();
+        let (pc, audio_track, sdp) = self
+            .create_offer_connection(self.ice_servers(), false, Some(peer_id))
+            .await
+            .map_err(|e| {
+                self.end_peer_call(peer_id);
+                e
+            })?;
+        let sdp = self.sign_local_sdp(sdp);
+
+        self.attach_connection(peer_id, pc, audio_track)?;
+
+        tracing::info!("Retrying call to {} with fresh ICE", peer_id);
+        Ok(sdp)
+    }
+
+    /// Erstellt ein Offer mit ICE Restart für eine unterbrochene Verbindung
+    ///
+    /// Nach `CallEvent::RenegotiationNeeded`: Die bestehende Peer Connection
+    /// (samt DTLS und Tracks) bleibt erhalten, nur ICE wird neu ausgehandelt.
+    /// Das Offer muss wie beim Anrufstart an den Peer gesendet werden, dessen
+    /// Answer geht wie gewohnt an `handle_answer`.
+    pub async fn restart_ice(&self, peer_id: &str) -> Result<String, CallEngineError> {
+        let pc = self
+            .peers
+            .lock()
+            .get(peer_id)
+            .filter(|session| matches!(session.state, CallState::Reconnecting { .. }))
+            .and_then(|session| session.pc.clone())
+            .ok_or(CallEngineError::NoActiveCall)?;
+
+        let options = RTCOfferOptions {
+            ice_restart: true,
+            ..Default::default()
+        };
+        let offer = pc
+            .create_offer(Some(options))
+            .await
+            .map_err(|e| CallEngineError::WebRTC(e.to_string()))?;
+        pc.set_local_description(offer.clone())
+            .await
+            .map_err(|e| CallEngineError::WebRTC(e.to_string()))?;
+
+        tracing::info!("Restarting ICE with {}", peer_id);
+        Ok(self.sign_local_sdp(offer.sdp))
+    }
+
+    /// Beantwortet das ICE Restart Offer eines verbundenen Teilnehmers
+    ///
+    /// `None`, wenn der Absender keine bestehende Verbindung hat. Der DTLS
+    /// Fingerprint ändert sich beim ICE Restart nicht: Das Offer wird wie beim
+    /// Verbindungsaufbau geprüft und abgelehnt, wenn sein Fingerprint von dem
+    /// der bestehenden Verbindung abweicht.
+    async fn accept_ice_restart(
+        &self,
+        peer_id: &str,
+        offer_sdp: String,
+    ) -> Option<Result<String, CallEngineError>> {
+        let pc = self
+            .peers
+            .lock()
+            .get(peer_id)
+            .filter(|session| {
+                matches!(
+                    session.state,
+                    CallState::Connected { .. } | CallState::Reconnecting { .. }
+                )
+            })
+            .and_then(|session| session.pc.clone())?;
+
+        tracing::info!("Accepting ICE restart from {}", peer_id);
+        Some(
+            async {
+                self.verify_remote_sdp(peer_id, &offer_sdp)?;
+                let current = pc
+                    .remote_description()
+                    .await
+                    .and_then(|description| parse_dtls_fingerprint(&description.sdp));
+                if current.is_none() || current != parse_dtls_fingerprint(&offer_sdp) {
+                    return Err(CallEngineError::IdentityVerification(
+                        "DTLS fingerprint changed during ICE restart".to_string(),
+                    ));
+                }
+
+                let offer = RTCSessionDescription::offer(offer_sdp)
+                    .map_err(|e| CallEngineError::InvalidSdp(e.to_string()))?;
+                pc.set_remote_description(offer)
+                    .await
+                    .map_err(|e| CallEngineError::WebRTC(e.to_string()))?;
+                let answer = pc
+                    .create_answer(None)
+                    .await
+                    .map_err(|e| CallEngineError::WebRTC(e.to_string()))?;
+                pc.set_local_description(answer.clone())
+                    .await
+                    .map_err(|e| CallEngineError::WebRTC(e.to_string()))?;
+                self.flush_pending_candidates(peer_id, &pc).await;
+                Ok(self.sign_local_sdp(answer.sdp))
+            }
+            .await,
+        )
+    }
+
+    /// Baut einen ausgehenden Anruf mit den gegebenen ICE Servern auf
+    ///
+    /// Bei `wait_for_gathering` wird das SDP erst nach Abschluss des
+    /// ICE Gatherings zurückgegeben (für Signaling ohne Trickle ICE).
+    async fn start_call_with(
+        &self,
+        peer_id: String,
+        ice_servers: Vec<RTCIceServer>,
+        wait_for_gathering: bool,
+    ) -> Result<String, CallEngineError> {
+        // Prüfen ob bereits ein Anruf aktiv ist (`Ended` zählt als Idle)
+        {
+            let state = self.state.lock();
+            if !matches!(*state, CallState::Idle | CallState::Ended) {
+                return Err(CallEngineError::AlreadyInCall);
+            }
+        }
+        self.ensure_microphone_permission().await?;
+
+        // Candidates eines früheren Anrufs verwerfen
+        self.pending_candidates.lock().clear();
+
+        // State aktualisieren
+        self.set_peer_state(
+            &peer_id,
+            CallState::Calling {
+                peer_id: peer_id.clone(),
+            },
+        );
+
+        // Vorgewärmte Verbindung übernehmen (nur mit Trickle ICE, LAN-Anrufe
+        // verwenden andere ICE Server), sonst eine neue aufbauen
+        let prewarmed = if wait_for_gathering {
+            None
+        } else {
+            self.take_prewarmed_connection()
+        };
+        let (pc, audio_track, sdp) = match prewarmed {
+            Some(prewarmed) => {
+                tracing::info!("Using prewarmed peer connection");
+                // Bereits gesammelte Candidates gleich im Offer mitschicken
+                let sdp = prewarmed
+                    .pc
+                    .local_description()
+                    .await
+                    .map(|description| description.sdp)
+                    .unwrap_or(prewarmed.offer_sdp);
+                (prewarmed.pc, prewarmed.audio_track, sdp)
+            }
+            None => {
+                self.local_candidates.lock().clear();
+                self.create_offer_connection(ice_servers, wait_for_gathering, Some(&peer_id))
+                    .await?
+            }
+        };
+        let sdp = self.sign_local_sdp(sdp);
+
+        // Peer Connection speichern
+        self.attach_connection(&peer_id, pc, audio_track)?;
+
+        // Audio initialisieren
+        self.init_audio()?;
+        self.add_peer_audio(&peer_id);
+
+        Ok(sdp)
+    }
+
+    /// Holt einen weiteren Teilnehmer in den verbundenen Anruf (Konferenz)
+    ///
+    /// Gibt das SDP Offer zurück, das an den neuen Teilnehmer gesendet werden
+    /// muss. Das Mikrofon-Audio geht an alle Teilnehmer, deren Audio wird
+    /// lokal gemischt.
+    pub async fn add_peer(&self, peer_id: String) -> Result<String, CallEngineError> {
+        self.add_peer_with(peer_id, self.ice_servers(), false).await
+    }
+
+    /// Holt einen weiteren Teilnehmer aus dem lokalen Netzwerk in den Anruf
+    pub async fn add_lan_peer(&self, peer_id: String) -> Result<String, CallEngineError> {
+        self.add_peer_with(peer_id, Vec::new(), true).await
+    }
+
+    /// Baut die Verbindung zu einem weiteren Teilnehmer auf
+    async fn add_peer_with(
+        &self,
+        peer_id: String,
+        ice_servers: Vec<RTCIceServer>,
+        wait_for_gathering: bool,
+    ) -> Result<String, CallEngineError> {
+        if !matches!(self.state(), CallState::Connected { .. }) {
+            return Err(CallEngineError::NoActiveCall);
+        }
+        self.check_peer_capacity(&peer_id)?;
+
+        self.pending_candidates.lock().remove(&peer_id);
+        self.set_peer_state(
+            &peer_id,
+            CallState::Calling {
+                peer_id: peer_id.clone(),
+            },
+        );
+
+        let (pc, audio_track, sdp) = self
+            .create_offer_connection(ice_servers, wait_for_gathering, Some(&peer_id))
+            .await
+            .map_err(|e| {
+                self.end_peer_call(&peer_id);
+                e
+            })?;
+        let sdp = self.sign_local_sdp(sdp);
+
+        self.attach_connection(&peer_id, pc, audio_track)?;
+        self.add_peer_audio(&peer_id);
+
+        tracing::info!("Adding {} to the call", peer_id);
+        Ok(sdp)
+    }
+
+    /// Prüft, ob ein weiterer Teilnehmer in den Anruf passt
+    fn check_peer_capacity(&self, peer_id: &str) -> Result<(), CallEngineError> {
+        let peers = self.peers.lock();
+        if peers
+            .get(peer_id)
+            .is_some_and(|session| session.pc.is_some())
+        {
+            return Err(CallEngineError::AlreadyInCall);
+        }
+
+        let connected = peers
+            .values()
+            .filter(|session| session.pc.is_some())
+            .count();
+        if connected >= MAX_CALL_PEERS {
+            return Err(CallEngineError::TooManyPeers(MAX_CALL_PEERS));
+        }
+        Ok(())
+    }
+
+    /// Hinterlegt Peer Connection und Audio-Track beim Teilnehmer
+    ///
+    /// Wurde der Teilnehmer inzwischen aufgelegt, wird die Verbindung wieder
+    /// geschlossen. Wartende Answers werden in beiden Fällen geweckt.
+    fn attach_connection(
+        &self,
+        peer_id: &str,
+        pc: Arc<RTCPeerConnection>,
+        audio_track: Arc<TrackLocalStaticRTP>,
+    ) -> Result<(), CallEngineError> {
+        let attached = match self.peers.lock().get_mut(peer_id) {
+            Some(session) => {
+                session.pc = Some(Arc::clone(&pc));
+                session.local_track = Some(audio_track);
+                true
+            }
+            None => false,
+        };
+        self.connection_attached.notify_waiters();
+
+        if attached {
+            return Ok(());
+        }
+        tokio::spawn(async move {
+            let _ = pc.close().await;
+        });
+        Err(CallEngineError::NoActiveCall)
+    }
+
+    /// Gibt die Peer Connection eines Teilnehmers zurück, sobald sie hinterlegt ist
+    ///
+    /// Beim Anrufer wartet ein Answer, das vor dem Ende von `start_call`
+    /// eintrifft, bis zu `CONNECTION_READY_TIMEOUT` auf die Verbindung.
+    /// Zusätzlich wird zurückgegeben, ob der Teilnehmer noch `Calling` ist.
+    async fn wait_for_connection(
+        &self,
+        peer_id: &str,
+    ) -> Result<(Arc<RTCPeerConnection>, bool), CallEngineError> {
+        let deadline = tokio::time::Instant::now() + CONNECTION_READY_TIMEOUT;
+        loop {
+            // Vor der Prüfung registrieren, damit kein Wecken verloren geht
+            let attached = self.connection_attached.notified();
+            {
+                let peers = self.peers.lock();
+                let session = peers.get(peer_id).ok_or(CallEngineError::NoActiveCall)?;
+                let calling = matches!(session.state, CallState::Calling { .. });
+                match &session.pc {
+                    Some(pc) => return Ok((Arc::clone(pc), calling)),
+                    None if !calling => return Err(CallEngineError::NoActiveCall),
+                    None => {}
+                }
+            }
+
+            tracing::debug!(
+                "Answer from {} arrived early, waiting for connection",
+                peer_id
+            );
+            if tokio::time::timeout_at(deadline, attached).await.is_err() {
+                return Err(CallEngineError::NoActiveCall);
+            }
+        }
+    }
+
+    /// Erstellt eine Peer Connection mit Audio-Track und setzt das lokale Offer
+    ///
+    /// Damit beginnt das ICE Gathering. Bei `wait_for_gathering` enthält das
+    /// zurückgegebene SDP bereits alle Candidates.
+    async fn create_offer_connection(
+        &self,
+        ice_servers: Vec<RTCIceServer>,
+        wait_for_gathering: bool,
+        peer_id: Option<&str>,
+    ) -> Result<(Arc<RTCPeerConnection>, Arc<TrackLocalStaticRTP>, String), CallEngineError> {
+        // Peer Connection erstellen
+        let pc = self.create_peer_connection(ice_servers, peer_id).await?;
+
+        // Audio Track hinzufügen
+        let audio_track = Arc::new(TrackLocalStaticRTP::new(
+            RTCRtpCodecCapability {
+                mime_type: "audio/opus".to_string(),
+                clock_rate: SAMPLE_RATE,
+                channels: 1,
+                ..Default::default()
+            },
+            "audio".to_string(),
+            "call-app".to_string(),
+        ));
+
+        pc.add_track(Arc::clone(&audio_track) as Arc<dyn TrackLocal + Send + Sync>)
+            .await
+            .map_err(|e| CallEngineError::WebRTC(e.to_string()))?;
+
+        // SDP Offer erstellen
+        let offer = pc
+            .create_offer(None)
+            .await
+            .map_err(|e| CallEngineError::WebRTC(e.to_string()))?;
+
+        // Promise vor set_local_description holen, sonst kann das Ende verpasst werden
+        let mut gathering_complete = pc.gathering_complete_promise().await;
+
+        // Local Description setzen
+        pc.set_local_description(offer.clone())
             .await
             .map_err(|e| CallEngineError::WebRTC(e.to_string()))?;
 
-        // Local Description setzen
-        pc.set_local_description(answer.clone())
-            .await
-            .map_err(|e| CallEngineError::WebRTC(e.to_string()))?;
+        let sdp = if wait_for_gathering {
+            let _ = gathering_complete.recv().await;
+            Self::complete_local_sdp(&pc).await?
+        } else {
+            offer.sdp
+        };
+
+        Ok((pc, audio_track, sdp))
+    }
+
+    /// Wärmt eine Peer Connection für den nächsten ausgehenden Anruf vor
+    ///
+    /// Erstellt Verbindung, Audio-Track und Offer und startet damit das ICE
+    /// Gathering (STUN/TURN-Anfragen laufen, bevor der Nutzer anruft).
+    /// `start_call` übernimmt die Verbindung, solange sie nicht älter als
+    /// `PREWARM_MAX_AGE` ist. Eingehende Anrufe profitieren nicht, weil dort
+    /// das Answer zum fremden Offer passen muss.
+    pub async fn prewarm(&self) -> Result<(), CallEngineError> {
+        if !matches!(self.state(), CallState::Idle | CallState::Ended) {
+            return Err(CallEngineError::AlreadyInCall);
+        }
+        if self
+            .prewarmed_connection
+            .lock()
+            .as_ref()
+            .is_some_and(|prewarmed| prewarmed.created_at.elapsed() < PREWARM_MAX_AGE)
+        {
+            return Ok(());
+        }
+
+        // Candidates gehören ab jetzt zur vorgewärmten Verbindung
+        self.local_candidates.lock().clear();
+        let (pc, audio_track, offer_sdp) = self
+            .create_offer_connection(self.ice_servers(), false, None)
+            .await?;
+
+        // Inzwischen gestarteter Anruf hat eine eigene Verbindung aufgebaut
+        if !matches!(self.state(), CallState::Idle | CallState::Ended) {
+            tokio::spawn(async move {
+                let _ = pc.close().await;
+            });
+            return Err(CallEngineError::AlreadyInCall);
+        }
+
+        let previous = self
+            .prewarmed_connection
+            .lock()
+            .replace(PrewarmedConnection {
+                pc,
+                audio_track,
+                offer_sdp,
+                created_at: std::time::Instant::now(),
+            });
+        if let Some(previous) = previous {
+            tokio::spawn(async move {
+                let _ = previous.pc.close().await;
+            });
+        }
+
+        tracing::info!("Peer connection prewarmed");
+        Ok(())
+    }
+
+    /// Entnimmt die vorgewärmte Verbindung, sofern sie noch verwendbar ist
+    fn take_prewarmed_connection(&self) -> Option<PrewarmedConnection> {
+        let prewarmed = self.prewarmed_connection.lock().take()?;
+
+        let usable = prewarmed.created_at.elapsed() < PREWARM_MAX_AGE
+            && !matches!(
+                prewarmed.pc.connection_state(),
+                RTCPeerConnectionState::Failed | RTCPeerConnectionState::Closed
+            );
+        if usable {
+            return Some(prewarmed);
+        }
+
+        tracing::info!("Discarding stale prewarmed peer connection");
+        tokio::spawn(async move {
+            let _ = prewarmed.pc.close().await;
+        });
+        None
+    }
+
+    /// Akzeptiert einen eingehenden Anruf mit den gegebenen ICE Servern
+    async fn accept_call_with(
+        &self,
+        peer_id: String,
+        offer_sdp: String,
+        ice_servers: Vec<RTCIceServer>,
+        wait_for_gathering: bool,
+        require_ringing: bool,
+    ) -> Result<String, CallEngineError> {
+        // Angenommen wird ein klingelnder Anruf oder, ohne laufenden Anruf,
+        // ein direkt übergebenes Offer (manuelles Signaling)
+        let ringing = self.is_ringing(&peer_id);
+        if require_ringing && !ringing {
+            return Err(CallEngineError::CallNoLongerActive);
+        }
+        // Ein erneutes Offer nach gescheitertem Aufbau ersetzt die alte Verbindung
+        let retrying = self.is_awaiting_setup_retry(&peer_id);
+        if !ringing && !retrying && self.state() != CallState::Idle {
+            return Err(CallEngineError::AlreadyInCall);
+        }
+        self.check_peer_capacity(&peer_id)?;
+
+        // Läuft bereits ein Anruf, kommt der Anrufer als weiterer Teilnehmer
+        // dazu. Bei einem erneuten Offer läuft Audio ebenfalls schon.
+        let joining = retrying
+            || self
+                .peers
+                .lock()
+                .values()
+                .any(|session| session.pc.is_some());
+        if !joining {
+            self.ensure_microphone_permission().await?;
+        }
+
+        // Der Anrufer kann während der Berechtigungsabfrage abgebrochen haben
+        if require_ringing && !self.is_ringing(&peer_id) {
+            return Err(CallEngineError::CallNoLongerActive);
+        }
+        self.stop_ringtone();
+
+        // Fingerprint-Signatur prüfen, bevor Medien ausgehandelt werden
+        self.verify_remote_sdp(&peer_id, &offer_sdp)?;
+
+        // State aktualisieren
+        self.set_peer_state(
+            &peer_id,
+            CallState::Connecting {
+                peer_id: peer_id.clone(),
+            },
+        );
+
+        // Peer Connection erstellen
+        let pc = self
+            .create_peer_connection(ice_servers, Some(&peer_id))
+            .await?;
+
+        // Remote Description setzen (das Offer)
+        let offer = RTCSessionDescription::offer(offer_sdp)
+            .map_err(|e| CallEngineError::InvalidSdp(e.to_string()))?;
+
+        pc.set_remote_description(offer)
+            .await
+            .map_err(|e| CallEngineError::WebRTC(e.to_string()))?;
+
+        // Während des Klingelns eingetroffene Candidates anwenden
+        self.flush_pending_candidates(&peer_id, &pc).await;
+
+        // Audio Track hinzufügen
+        let audio_track = Arc::new(TrackLocalStaticRTP::new(
+            RTCRtpCodecCapability {
+                mime_type: "audio/opus".to_string(),
+                clock_rate: SAMPLE_RATE,
+                channels: 1,
+                ..Default::default()
+            },
+            "audio".to_string(),
+            "call-app".to_string(),
+        ));
+
+        pc.add_track(Arc::clone(&audio_track) as Arc<dyn TrackLocal + Send + Sync>)
+            .await
+            .map_err(|e| CallEngineError::WebRTC(e.to_string()))?;
+
+        // SDP Answer erstellen
+        let answer = pc
+            .create_answer(None)
+            .await
+            .map_err(|e| CallEngineError::WebRTC(e.to_string()))?;
+
+        let mut gathering_complete = pc.gathering_complete_promise().await;
+
+        // Local Description setzen
+        pc.set_local_description(answer.clone())
+            .await
+            .map_err(|e| CallEngineError::WebRTC(e.to_string()))?;
+
+        let sdp = if wait_for_gathering {
+            let _ = gathering_complete.recv().await;
+            Self::complete_local_sdp(&pc).await?
+        } else {
+            answer.sdp
+        };
+        let sdp = self.sign_local_sdp(sdp);
+
+        // Peer Connection speichern
+        self.attach_connection(&peer_id, pc, audio_track)?;
+
+        // Audio initialisieren (in der Konferenz läuft es bereits)
+        if !joining {
+            self.init_audio()?;
+        }
+        self.add_peer_audio(&peer_id);
+
+        Ok(sdp)
+    }
+
+    /// Schreibt ein ausgehendes RTP-Paket in die Audio-Tracks aller Teilnehmer
+    ///
+    /// In Debug-Builds läuft das Paket vorher durch die Netzwerk-Simulation
+    /// und wird ggf. verworfen oder verzögert geschrieben.
+    pub async fn write_rtp(&self, packet: Packet) -> Result<(), CallEngineError> {
+        self.rtp_writer().write(packet).await
+    }
+
+    /// Sendepfad mit den Zuständen dieser Engine (für den Sende-Task)
+    fn rtp_writer(&self) -> RtpWriter {
+        RtpWriter {
+            peers: Arc::clone(&self.peers),
+            network_simulation: Arc::clone(&self.network_simulation),
+            rtp_counters: Arc::clone(&self.rtp_counters),
+        }
+    }
+
+    /// Signiert den DTLS Fingerprint des lokalen SDP (falls eine Identität gesetzt ist)
+    fn sign_local_sdp(&self, sdp: String) -> String {
+        match self.identity.lock().as_ref() {
+            Some(keypair) => sign_sdp_fingerprint(&sdp, keypair),
+            None => sdp,
+        }
+    }
+
+    /// Prüft die Fingerprint-Signatur im SDP eines Peers
+    ///
+    /// Die Identität wird nur für die Gegenstelle des Gesamt-States gemerkt,
+    /// später hinzukommende Teilnehmer werden nur gemeldet.
+    fn verify_remote_sdp(&self, peer_id: &str, sdp: &str) -> Result<(), CallEngineError> {
+        let expected = self.expected_peer_keys.lock().get(peer_id).cloned();
+        let verified = verify_sdp_fingerprint(sdp, expected.as_deref())?;
+        let verified = match (verified, &expected) {
+            (Some(public_key), None) => self.check_declared_key(peer_id, public_key)?,
+            (verified, _) => verified,
+        };
+
+        let is_primary = match self.state().peer_id() {
+            Some(primary) => primary == peer_id,
+            None => true,
+        };
+        if is_primary {
+            *self.remote_identity.lock() = verified.clone().map(|public_key| RemoteIdentity {
+                peer_id: peer_id.to_string(),
+                public_key,
+                pinned: expected.is_some(),
+            });
+        }
+
+        match verified {
+            Some(public_key) => {
+                let _ = self.event_tx.send(CallEvent::PeerIdentityVerified {
+                    peer_id: peer_id.to_string(),
+                    public_key,
+                });
+            }
+            None => tracing::warn!("Peer {} sent an unsigned DTLS fingerprint", peer_id),
+        }
+
+        Ok(())
+    }
+
+    /// Prüft einen im SDP mitgeschickten Key, für den kein Key erwartet wird
+    ///
+    /// Anerkannt wird er nur, wenn die LAN Peer-ID oder das Verzeichnis
+    /// denselben Key nennt. Sonst könnte jeder, der das Signaling
+    /// kontrolliert, beim ersten Anruf seinen eigenen Key pinnen lassen. Ein
+    /// abweichender Key wird abgelehnt, ein unbestätigter nur nicht anerkannt.
+    fn check_declared_key(
+        &self,
+        peer_id: &str,
+        public_key: String,
+    ) -> Result<Option<String>, CallEngineError> {
+        let vouched = match public_key_from_lan_peer_id(peer_id) {
+            Some(key) => Some(key.to_string()),
+            None => self.directory_peer_keys.lock().get(peer_id).cloned(),
+        };
+        match vouched {
+            Some(vouched) if vouched == public_key => Ok(Some(public_key)),
+            Some(_) => Err(CallEngineError::IdentityVerification(
+                "identity key does not match the directory key".to_string(),
+            )),
+            None => {
+                tracing::warn!(
+                    "Peer {} declared an identity key that nothing confirms, not trusting it",
+                    peer_id
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    /// Wendet die gepufferten ICE Candidates eines Peers auf die Peer Connection an
+    async fn flush_pending_candidates(&self, peer_id: &str, pc: &RTCPeerConnection) {
+        let pending = self
+            .pending_candidates
+            .lock()
+            .remove(peer_id)
+            .unwrap_or_default();
+        if pending.is_empty() {
+            return;
+        }
+
+        tracing::debug!("Applying {} buffered ICE candidate(s)", pending.len());
+        for candidate in pending {
+            if let Err(e) = pc.add_ice_candidate(candidate).await {
+                tracing::warn!("Failed to add buffered ICE candidate: {}", e);
+            }
+        }
+    }
+
+    /// Gibt die Local Description inkl. aller gesammelten Candidates zurück
+    async fn complete_local_sdp(pc: &RTCPeerConnection) -> Result<String, CallEngineError> {
+        pc.local_description()
+            .await
+            .map(|desc| desc.sdp)
+            .ok_or_else(|| CallEngineError::WebRTC("No local description".to_string()))
+    }
+
+    /// Verarbeitet das SDP Answer eines angerufenen Teilnehmers
+    ///
+    /// Trifft das Answer ein, bevor `start_call` die Peer Connection
+    /// hinterlegt hat, wird kurz auf sie gewartet.
+    pub async fn handle_answer(
+        &self,
+        peer_id: &str,
+        answer_sdp: String,
+    ) -> Result<(), CallEngineError> {
+        let (pc, calling) = self.wait_for_connection(peer_id).await?;
+
+        // Fingerprint-Signatur prüfen, bevor Medien akzeptiert werden
+        if calling {
+            if let Err(e) = self.verify_remote_sdp(peer_id, &answer_sdp) {
+                tracing::error!("Rejecting answer from {}: {}", peer_id, e);
+                self.end_peer_call(peer_id);
+                return Err(e);
+            }
+        }
+
+        let answer = RTCSessionDescription::answer(answer_sdp)
+            .map_err(|e| CallEngineError::InvalidSdp(e.to_string()))?;
+
+        pc.set_remote_description(answer)
+            .await
+            .map_err(|e| CallEngineError::WebRTC(e.to_string()))?;
+
+        // Vor dem Answer eingetroffene Candidates anwenden
+        self.flush_pending_candidates(peer_id, &pc).await;
+
+        Ok(())
+    }
+
+    /// Fügt einen ICE Candidate eines Teilnehmers hinzu
+    ///
+    /// Candidates, die vor der Remote Description eintreffen (z.B. während
+    /// des Klingelns oder vor dem Answer), werden gepuffert und angewendet,
+    /// sobald die Remote Description gesetzt ist.
+    pub async fn add_ice_candidate(
+        &self,
+        peer_id: &str,
+        candidate_json: String,
+    ) -> Result<(), CallEngineError> {
+        let candidate = parse_ice_candidate(&candidate_json)?;
+
+        if self.verbose_ice_logging() {
+            log_candidate(
+                &candidate.candidate,
+                CandidateDirection::Remote,
+                &self.event_tx,
+            );
+        }
+
+        let pc = self
+            .peers
+            .lock()
+            .get(peer_id)
+            .and_then(|session| session.pc.clone());
+        let pc = match pc {
+            Some(pc) if pc.remote_description().await.is_some() => pc,
+            _ => {
+                tracing::debug!("Buffering ICE candidate until remote description is set");
+                self.pending_candidates
+                    .lock()
+                    .entry(peer_id.to_string())
+                    .or_default()
+                    .push(candidate);
+                return Ok(());
+            }
+        };
+
+        pc.add_ice_candidate(candidate)
+            .await
+            .map_err(|e| CallEngineError::WebRTC(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Gibt die Peer Connection zur Gegenstelle des Gesamt-States zurück
+    fn primary_connection(&self) -> Option<Arc<RTCPeerConnection>> {
+        let state = self.state();
+        self.peers.lock().get(state.peer_id()?)?.pc.clone()
+    }
+
+    /// Gibt die lokale Session Description (Offer/Answer) zurück
+    pub async fn local_description(&self) -> Option<LocalDescription> {
+        let pc = self.primary_connection()?;
+        pc.local_description().await.map(|desc| LocalDescription {
+            sdp_type: desc.sdp_type.to_string(),
+            sdp: desc.sdp,
+        })
+    }
+
+    /// Gibt alle bisher lokal gesammelten ICE Candidates (JSON) zurück
+    pub fn local_candidates(&self) -> Vec<String> {
+        self.local_candidates.lock().clone()
+    }
+
+    /// Gibt die DTLS Fingerprints der aktiven Verbindung zurück
+    ///
+    /// Webrtc-rs prüft das Zertifikat der Gegenstelle gegen den Fingerprint
+    /// aus deren SDP, daher entsprechen die Werte dem ausgehandelten DTLS.
+    pub async fn dtls_fingerprints(&self) -> Result<DtlsFingerprints, CallEngineError> {
+        let pc = self
+            .primary_connection()
+            .ok_or(CallEngineError::NoActiveCall)?;
+
+        Ok(Self::read_dtls_fingerprints(&pc).await)
+    }
+
+    /// Gibt den Verschlüsselungsstatus des aktiven Anrufs zurück
+    ///
+    /// Der DTLS Transport wird über den Sender des Audio-Tracks gelesen,
+    /// Audio-only Anrufe haben keinen SCTP Transport mit Daten.
+    pub async fn security_info(&self) -> Result<SecurityInfo, CallEngineError> {
+        let pc = self
+            .primary_connection()
+            .ok_or(CallEngineError::NoActiveCall)?;
+
+        let dtls_transport = match pc.get_senders().await.first() {
+            Some(sender) => sender.transport(),
+            None => pc.sctp().transport(),
+        };
+        let dtls_state = dtls_transport.state();
+        let srtp_profile = match dtls_transport.conn().await {
+            Some(conn) if dtls_state == RTCDtlsTransportState::Connected => {
+                Some(format!("{:?}", conn.selected_srtpprotection_profile()).to_uppercase())
+            }
+            _ => None,
+        };
+
+        Ok(SecurityInfo {
+            dtls_srtp_active: srtp_profile.is_some(),
+            dtls_state: dtls_state.to_string(),
+            srtp_profile,
+            fingerprints: Self::read_dtls_fingerprints(&pc).await,
+            remote_identity: self.remote_identity.lock().clone(),
+        })
+    }
+
+    /// Gibt RTP-Zähler und gewähltes Candidate Pair für Diagnoseberichte zurück
+    pub async fn call_stats(&self) -> CallStats {
+        let selected_candidate_pair = match self.primary_connection() {
+            Some(pc) => pc
+                .sctp()
+                .transport()
+                .ice_transport()
+                .get_selected_candidate_pair()
+                .await
+                .map(|pair| pair.to_string()),
+            None => None,
+        };
+        let connected_peers = self
+            .peers
+            .lock()
+            .values()
+            .filter(|session| session.pc.is_some())
+            .count();
+        let (packets_sent, packets_received) = self.rtp_counters.snapshot();
+
+        CallStats {
+            state: CallStateInfo::from(&self.state()),
+            connected_peers,
+            packets_sent,
+            packets_received,
+            selected_candidate_pair,
+        }
+    }
+
+    /// Prüft vor einem Anruf, welche Candidates die ICE Server liefern
+    ///
+    /// Eine Wegwerf-Peer-Connection sammelt Candidates gegen die
+    /// konfigurierten Server (unabhängig von der ICE Transport Policy),
+    /// parallel wird das Mapping-Verhalten des NAT bestimmt.
+    pub async fn test_connectivity(&self) -> Result<ConnectivityReport, CallEngineError> {
+        let started = std::time::Instant::now();
+        let urls = self.ice_server_urls();
+
+        let pc = Self::build_api()?
+            .new_peer_connection(RTCConfiguration {
+                ice_servers: self.ice_servers(),
+                ..Default::default()
+            })
+            .await
+            .map_err(|e| CallEngineError::WebRTC(e.to_string()))?;
+
+        // Ende des Gatherings (`None`) schließt den Kanal
+        let (candidate_tx, mut candidate_rx) = mpsc::unbounded_channel();
+        let candidate_tx = Mutex::new(Some(candidate_tx));
+        pc.on_ice_candidate(Box::new(move |candidate| {
+            let mut sender = candidate_tx.lock();
+            match candidate {
+                Some(candidate) => {
+                    if let (Some(tx), Ok(json)) = (sender.as_ref(), candidate.to_json()) {
+                        let _ = tx.send(json.candidate);
+                    }
+                }
+                None => {
+                    sender.take();
+                }
+            }
+            Box::pin(async {})
+        }));
+
+        // Ein Audio-Transceiver, damit das Offer eine Media Section hat
+        let gathering = async {
+            pc.add_transceiver_from_kind(RTPCodecType::Audio, None)
+                .await
+                .map_err(|e| CallEngineError::WebRTC(e.to_string()))?;
+            let offer = pc
+                .create_offer(None)
+                .await
+                .map_err(|e| CallEngineError::WebRTC(e.to_string()))?;
+            pc.set_local_description(offer)
+                .await
+                .map_err(|e| CallEngineError::WebRTC(e.to_string()))?;
+            Ok::<_, CallEngineError>(
+                collect_candidates(&mut candidate_rx, CONNECTIVITY_GATHER_TIMEOUT).await,
+            )
+        };
+        let (gathered, mapped) = tokio::join!(gathering, probe_mapped_addresses(&urls));
+
+        if let Err(e) = pc.close().await {
+            tracing::debug!("Failed to close connectivity test connection: {}", e);
+        }
+
+        let report = build_report(&gathered?, &mapped, &urls, started.elapsed());
+        tracing::info!("Connectivity test: {:?}", report);
+        Ok(report)
+    }
+
+    /// Gibt die verifizierte Identität der Gegenstelle zurück
+    pub fn remote_identity(&self) -> Option<RemoteIdentity> {
+        self.remote_identity.lock().clone()
+    }
+
+    /// Lehnt einen eingehenden Anruf ab
+    ///
+    /// Ein laufender Anruf mit anderen Teilnehmern bleibt bestehen.
+    pub fn reject_call(&self, peer_id: &str) {
+        self.end_peer_call(peer_id);
+    }
+
+    /// Beendet das Klingeln eines eingehenden Anrufs nur lokal
+    ///
+    /// Im Gegensatz zu `reject_call` erfährt der Anrufer davon nichts, der
+    /// Anruf läuft bei ihm bis zum Timeout weiter. Klingelt sonst niemand und
+    /// läuft kein Anruf, geht der State direkt auf `Idle` (ohne `Ended`).
+    pub fn dismiss_incoming_call(&self, peer_id: &str) -> Result<(), CallEngineError> {
+        let others = {
+            let peers = self.peers.lock();
+            let ringing = peers
+                .get(peer_id)
+                .is_some_and(|session| matches!(session.state, CallState::Ringing { .. }));
+            if !ringing {
+                return Err(CallEngineError::NoActiveCall);
+            }
+            peers.len() > 1
+        };
+
+        self.pending_candidates.lock().remove(peer_id);
+        if others {
+            self.apply_peer_state(peer_id, None);
+        } else {
+            self.peers.lock().remove(peer_id);
+            let _ = self.event_tx.send(CallEvent::PeerStateChanged {
+                peer_id: peer_id.to_string(),
+                state: CallState::Ended,
+            });
+            self.set_state(CallState::Idle);
+        }
+        tracing::info!("Dismissed incoming call from {}", peer_id);
+        Ok(())
+    }
+
+    /// Beendet die Verbindung zu einem Teilnehmer
+    ///
+    /// Ist kein anderer Teilnehmer verbunden, wird der gesamte Anruf wie bei
+    /// `end_call` beendet. Gehört `peer_id` nicht zum Anruf, passiert nichts.
+    pub fn end_peer_call(&self, peer_id: &str) {
+        if !self.has_peer(peer_id) {
+            tracing::debug!("Ignoring hangup from non-participant {}", peer_id);
+            return;
+        }
+
+        let others_connected = self
+            .peers
+            .lock()
+            .iter()
+            .any(|(id, session)| id != peer_id && session.pc.is_some());
+        if !others_connected {
+            self.end_call();
+            return;
+        }
+
+        self.pending_candidates.lock().remove(peer_id);
+        self.setup_retry_allowed.lock().remove(peer_id);
+        {
+            let mut remote_identity = self.remote_identity.lock();
+            if remote_identity
+                .as_ref()
+                .is_some_and(|identity| identity.peer_id == peer_id)
+            {
+                *remote_identity = None;
+            }
+        }
+
+        let removed = self.apply_peer_state(peer_id, None);
+        if let Some(pc) = removed.and_then(|session| session.pc) {
+            tokio::spawn(async move {
+                let _ = pc.close().await;
+            });
+        }
+        tracing::info!("{} left the call", peer_id);
+    }
+
+    /// Beendet den aktuellen Anruf mit allen Teilnehmern
+    pub fn end_call(&self) {
+        self.pending_candidates.lock().clear();
+        self.local_candidates.lock().clear();
+        self.setup_retry_allowed.lock().clear();
+        self.remote_identity.lock().take();
+
+        // Audio stoppen
+        self.stop_audio();
+
+        // Peer Connections schließen
+        for (peer_id, pc) in self.take_peers() {
+            let _ = self.event_tx.send(CallEvent::PeerStateChanged {
+                peer_id,
+                state: CallState::Ended,
+            });
+            if let Some(pc) = pc {
+                tokio::spawn(async move {
+                    let _ = pc.close().await;
+                });
+            }
+        }
+
+        // State aktualisieren
+        let generation = self.ended_generation.fetch_add(1, Ordering::SeqCst) + 1;
+        self.set_state(CallState::Ended);
+
+        // Kurz warten und dann auf Idle setzen, außer es wurde inzwischen
+        // schon ein neuer Anruf gestartet (oder auch schon wieder beendet)
+        let state = Arc::clone(&self.state);
+        let ended_generation = Arc::clone(&self.ended_generation);
+        let event_tx = self.event_tx.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(ENDED_RESET_DELAY).await;
+            {
+                let mut state = state.lock();
+                if *state != CallState::Ended
+                    || ended_generation.load(Ordering::SeqCst) != generation
+                {
+                    return;
+                }
+                *state = CallState::Idle;
+            }
+            let _ = event_tx.send(CallEvent::StateChanged(CallState::Idle));
+        });
+    }
+
+    /// Setzt die Engine aus jedem State sofort auf `Idle` zurück
+    ///
+    /// Notausgang, falls der State hängen bleibt: Alle Peer Connections (auch
+    /// eine vorgewärmte) werden geschlossen, Audio gestoppt, gepufferte
+    /// Candidates verworfen und auf eine Verbindung wartende Answers
+    /// abgebrochen. Anders als bei `end_call` gibt es kein `Ended` dazwischen.
+    /// Mehrfaches Aufrufen ist unschädlich.
+    pub fn force_reset(&self) {
+        tracing::warn!("Forcing call engine reset (state was {:?})", self.state());
+
+        self.pending_candidates.lock().clear();
+        self.local_candidates.lock().clear();
+        self.setup_retry_allowed.lock().clear();
+        self.remote_identity.lock().take();
+        self.stop_audio();
+
+        let prewarmed = self.prewarmed_connection.lock().take();
+        let mut connections: Vec<_> = prewarmed
+            .map(|prewarmed| prewarmed.pc)
+            .into_iter()
+            .collect();
+        for (peer_id, pc) in self.take_peers() {
+            let _ = self.event_tx.send(CallEvent::PeerStateChanged {
+                peer_id,
+                state: CallState::Ended,
+            });
+            connections.extend(pc);
+        }
+        for pc in connections {
+            tokio::spawn(async move {
+                let _ = pc.close().await;
+            });
+        }
+
+        // Wartende Answers finden keinen Teilnehmer mehr und geben auf
+        self.connection_attached.notify_waiters();
+
+        self.set_state(CallState::Idle);
+    }
+
+    /// Beendet die Engine: schließt die Peer Connection und stoppt Audio
+    ///
+    /// Im Gegensatz zu `end_call` wird auf das Schließen gewartet (mit Timeout),
+    /// damit die Gegenstelle beim App-Beenden nicht hängen bleibt.
+    pub async fn shutdown(&self) {
+        self.stop_audio();
+
+        let prewarmed = self.prewarmed_connection.lock().take();
+        if let Some(prewarmed) = prewarmed {
+            let _ = tokio::time::timeout(SHUTDOWN_CLOSE_TIMEOUT, prewarmed.pc.close()).await;
+        }
+
+        let peers = self.take_peers();
+        for pc in peers.into_iter().filter_map(|(_, pc)| pc) {
+            if tokio::time::timeout(SHUTDOWN_CLOSE_TIMEOUT, pc.close())
+                .await
+                .is_err()
+            {
+                tracing::warn!("Timed out closing peer connection during shutdown");
+            }
+        }
+
+        *self.state.lock() = CallState::Idle;
+    }
+
+    /// Bereitet Audio-Geräte und Stream-Konfigurationen für den nächsten Anruf vor
+    ///
+    /// Es werden keine Streams gestartet. Ein bereits vorbereiteter Handler
+    /// bleibt erhalten, bis ihn ein Gerätewechsel verwirft.
+    pub fn warm_up_audio(&self) -> Result<(), CallEngineError> {
+        if self.prewarmed_audio.lock().is_some() {
+            return Ok(());
+        }
+
+        let mut audio = self.create_audio_handler()?;
+        audio.warm_up()?;
+        *self.prewarmed_audio.lock() = Some(audio);
+
+        tracing::info!("Audio warmed up");
+        Ok(())
+    }
+
+    /// Verwirft den vorbereiteten Audio Handler nach einem Gerätewechsel
+    ///
+    /// Die Geräte werden nur hier und bei einer neuen Geräteauswahl neu
+    /// ermittelt, nicht bei jedem Anrufstart.
+    pub fn invalidate_prewarmed_audio(&self) {
+        if self.prewarmed_audio.lock().take().is_some() {
+            tracing::info!("Audio devices changed, discarding warmed-up audio");
+        }
+    }
+
+    /// Verwirft das eigene Offer ohne Hangup an die Gegenstelle (Glare)
+    ///
+    /// Gepufferte Candidates bleiben erhalten: Vor einem Answer können sie nur
+    /// von der Gegenstelle zu deren Offer stammen, das gleich angenommen wird.
+    fn abandon_outgoing_call(&self) {
+        self.local_candidates.lock().clear();
+        self.remote_identity.lock().take();
+        self.stop_audio();
+
+        for pc in self.take_peers().into_iter().filter_map(|(_, pc)| pc) {
+            tokio::spawn(async move {
+                let _ = pc.close().await;
+            });
+        }
+
+        self.set_state(CallState::Idle);
+    }
+
+    /// Entfernt alle Teilnehmer und gibt ihre Peer Connections zurück
+    ///
+    /// Der Lock ist danach wieder frei, die Verbindungen können gefahrlos
+    /// geschlossen werden (der State-Handler liest die Teilnehmer).
+    fn take_peers(&self) -> Vec<(String, Option<Arc<RTCPeerConnection>>)> {
+        self.peers
+            .lock()
+            .drain()
+            .map(|(peer_id, session)| (peer_id, session.pc))
+            .collect()
+    }
+
+    /// Stoppt und verwirft den Audio Handler samt den Tasks des Anrufs
+    fn stop_audio(&self) {
+        for task in self.call_tasks.lock().drain(..) {
+            task.abort();
+        }
+        if let Some(mut audio) = self.audio_handler.lock().take() {
+            audio.stop();
+        }
+    }
+
+    /// Schließt eine Peer Connection blockierend (für `Drop`)
+    ///
+    /// Innerhalb eines Multi-Thread-Runtimes wird per `block_in_place` gewartet,
+    /// in einem Current-Thread-Runtime kann nicht blockiert werden, dort wird
+    /// das Schließen nur noch angestoßen. Ohne Runtime wird ein temporäres erstellt.
+    fn close_blocking(pc: Arc<RTCPeerConnection>) {
+        let close = async move {
+            let _ = tokio::time::timeout(SHUTDOWN_CLOSE_TIMEOUT, pc.close()).await;
+        };
+
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => match handle.runtime_flavor() {
+                tokio::runtime::RuntimeFlavor::CurrentThread => {
+                    handle.spawn(close);
+                }
+                _ => tokio::task::block_in_place(|| handle.block_on(close)),
+            },
+            Err(_) => match tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(runtime) => runtime.block_on(close),
+                Err(e) => tracing::error!("Failed to close peer connection on drop: {}", e),
+            },
+        }
+    }
+
+    /// Setzt Mute-Status
+    ///
+    /// Ohne laufendes Audio wird der Status vorgemerkt und beim Start des
+    /// nächsten Anrufs angewendet. Eine Änderung meldet `CallEvent::MuteChanged`.
+    pub fn set_muted(&self, muted: bool) {
+        let changed = std::mem::replace(&mut *self.muted.lock(), muted) != muted;
+        if let Some(audio) = self.audio_handler.lock().as_ref() {
+            audio.set_muted(muted);
+        }
+        if changed {
+            let _ = self.event_tx.send(CallEvent::MuteChanged(muted));
+        }
+    }
+
+    /// Gibt Mute-Status zurück
+    pub fn is_muted(&self) -> bool {
+        *self.muted.lock()
+    }
+
+    /// Setzt die Vorpufferung des Playbacks in Frames (à 20ms)
+    ///
+    /// Gilt für den laufenden und alle folgenden Anrufe.
+    pub fn set_playback_prefill_frames(&self, frames: usize) -> Result<(), CallEngineError> {
+        validate_prefill_frames(frames)?;
+        *self.playback_prefill_frames.lock() = frames;
+
+        if let Some(audio) = self.audio_handler.lock().as_ref() {
+            audio.set_prefill_frames(frames)?;
+        }
+        Ok(())
+    }
+
+    /// Gibt die Vorpufferung des Playbacks in Frames zurück
+    pub fn playback_prefill_frames(&self) -> usize {
+        *self.playback_prefill_frames.lock()
+    }
+
+    /// Setzt die Größe der Audio-Frames in Samples (siehe `OPUS_FRAME_SIZES`)
+    ///
+    /// 10ms Frames (480) senken die Latenz, 40 oder 60ms (1920, 2880) sparen
+    /// auf guten Verbindungen Paket-Overhead. Gilt für den laufenden und alle
+    /// folgenden Anrufe.
+    pub fn set_audio_frame_size(&self, frame_size: usize) -> Result<(), CallEngineError> {
+        validate_frame_size(frame_size)?;
+        *self.audio_frame_size.lock() = frame_size;
+
+        if let Some(audio) = self.audio_handler.lock().as_mut() {
+            audio.set_frame_size(frame_size)?;
+        }
+        Ok(())
+    }
+
+    /// Gibt die Größe der Audio-Frames in Samples zurück
+    pub fn audio_frame_size(&self) -> usize {
+        *self.audio_frame_size.lock()
+    }
+
+    /// Wählt das Mikrofon nach Name (`None` = System-Standard)
+    ///
+    /// Gilt ab dem nächsten Anruf. Ist das Gerät dann nicht vorhanden, wird
+    /// das Standardgerät verwendet und ein `CallEvent::Error` gesendet.
+    pub fn set_input_device(&self, name: Option<String>) {
+        tracing::info!("Audio input device: {:?}", name);
+        self.audio_devices.lock().input = name;
+        // Vorbereiteter Handler gehört noch zur alten Auswahl
+        self.prewarmed_audio.lock().take();
+    }
+
+    /// Wählt den Lautsprecher nach Name (`None` = System-Standard)
+    ///
+    /// Gilt wie `set_input_device` ab dem nächsten Anruf.
+    pub fn set_output_device(&self, name: Option<String>) {
+        tracing::info!("Audio output device: {:?}", name);
+        self.audio_devices.lock().output = name;
+        self.prewarmed_audio.lock().take();
+    }
+
+    /// Gibt die ausgewählten Audio-Geräte zurück
+    pub fn audio_devices(&self) -> AudioDeviceSelection {
+        self.audio_devices.lock().clone()
+    }
+
+    /// Verzögert das gesendete Audio um eine feste Zeit (0 bis `MAX_OUTPUT_DELAY_MS`)
+    ///
+    /// Diagnose- und Interop-Werkzeug, z.B. um den Jitter Buffer der
+    /// Gegenstelle zu testen. Gilt für den laufenden und alle folgenden Anrufe.
+    pub fn set_output_delay(&self, delay_ms: u32) -> Result<(), CallEngineError> {
+        validate_output_delay(delay_ms)?;
+        *self.output_delay_ms.lock() = delay_ms;
+
+        if let Some(audio) = self.audio_handler.lock().as_ref() {
+            audio.set_output_delay(delay_ms)?;
+        }
+        Ok(())
+    }
+
+    /// Gibt die Verzögerung des gesendeten Audios in Millisekunden zurück
+    pub fn output_delay(&self) -> u32 {
+        *self.output_delay_ms.lock()
+    }
+
+    /// Aktiviert den Limiter des Playbacks mit einer Obergrenze in dBFS
+    ///
+    /// Spitzen über der Grenze werden weich abgesenkt statt hart
+    /// abgeschnitten. Gilt sofort für den laufenden und alle folgenden Anrufe.
+    pub fn set_output_limiter(
+        &self,
+        enabled: bool,
+        ceiling_db: f32,
+    ) -> Result<(), CallEngineError> {
+        let config = OutputLimiterConfig::new(enabled, ceiling_db)
+            .map_err(CallEngineError::InvalidConfig)?;
+        *self.output_limiter.lock() = config;
+
+        if let Some(audio) = self.audio_handler.lock().as_ref() {
+            audio.set_output_limiter(config);
+        }
+        Ok(())
+    }
+
+    /// Gibt die Einstellungen des Limiters zurück
+    pub fn output_limiter(&self) -> OutputLimiterConfig {
+        *self.output_limiter.lock()
+    }
+
+    /// Sendet bei anhaltender Stille keine Pakete mehr
+    ///
+    /// Gilt sofort für den laufenden und alle folgenden Anrufe.
+    pub fn set_vad_enabled(&self, enabled: bool) {
+        *self.vad_enabled.lock() = enabled;
+        if let Some(audio) = self.audio_handler.lock().as_ref() {
+            audio.set_vad_enabled(enabled);
+        }
+    }
+
+    /// Gibt zurück, ob Stille unterdrückt wird
+    pub fn vad_enabled(&self) -> bool {
+        *self.vad_enabled.lock()
+    }
+
+    /// Schaltet zwischen Push-to-Talk und offenem Mikrofon um
+    ///
+    /// Gilt sofort für den laufenden und alle folgenden Anrufe. Mit
+    /// Push-to-Talk wird erst gesendet, wenn die Taste gedrückt wird.
+    pub fn set_ptt_enabled(&self, enabled: bool) {
+        *self.ptt_enabled.lock() = enabled;
+        *self.ptt_active.lock() = false;
+        if let Some(audio) = self.audio_handler.lock().as_ref() {
+            audio.set_ptt_enabled(enabled);
+            audio.set_ptt_active(false);
+        }
+    }
+
+    /// Gibt zurück, ob Push-to-Talk aktiv ist
+    pub fn ptt_enabled(&self) -> bool {
+        *self.ptt_enabled.lock()
+    }
+
+    /// Setzt, ob die Push-to-Talk-Taste gedrückt ist (ohne Push-to-Talk wirkungslos)
+    pub fn set_ptt_active(&self, active: bool) {
+        *self.ptt_active.lock() = active;
+        if let Some(audio) = self.audio_handler.lock().as_ref() {
+            audio.set_ptt_active(active);
+        }
+    }
+
+    /// Gibt zurück, ob die Push-to-Talk-Taste gedrückt ist
+    pub fn ptt_active(&self) -> bool {
+        *self.ptt_active.lock()
+    }
+
+    /// Setzt die RMS-Schwelle, ab der ein Frame als Sprache gilt (0.0 - 1.0)
+    pub fn set_vad_threshold(&self, threshold: f32) -> Result<(), CallEngineError> {
+        validate_vad_threshold(threshold)?;
+        *self.vad_threshold.lock() = threshold;
+
+        if let Some(audio) = self.audio_handler.lock().as_ref() {
+            audio.set_vad_threshold(threshold)?;
+        }
+        Ok(())
+    }
+
+    /// Gibt die Schwelle der Sprachaktivitätserkennung zurück
+    pub fn vad_threshold(&self) -> f32 {
+        *self.vad_threshold.lock()
+    }
+
+    /// Startet den Klingelton auf dem ausgewählten Lautsprecher
+    ///
+    /// Passiert automatisch beim Klingeln eines eingehenden Anrufs. Der
+    /// Klingelton endet, sobald kein Teilnehmer mehr klingelt.
+    pub fn start_ringtone(&self) {
+        self.ringtone.start(self.audio_devices().output);
+    }
+
+    /// Beendet den Klingelton (z.B. beim Annehmen)
+    pub fn stop_ringtone(&self) {
+        self.ringtone.stop();
+    }
+
+    /// Gibt zurück, ob gerade der Klingelton läuft
+    pub fn is_ringtone_playing(&self) -> bool {
+        self.ringtone.is_playing()
+    }
+
+    /// Aktiviert oder deaktiviert den Klingelton
+    pub fn set_ringtone_enabled(&self, enabled: bool) {
+        tracing::info!("Ringtone enabled: {}", enabled);
+        self.ringtone.set_enabled(enabled);
+    }
+
+    /// Gibt zurück, ob bei eingehenden Anrufen geklingelt wird
+    pub fn ringtone_enabled(&self) -> bool {
+        self.ringtone.is_enabled()
+    }
+
+    /// Wählt den Klingelton (gilt ab dem nächsten Klingeln)
+    pub fn set_ringtone_style(&self, style: RingtoneStyle) {
+        tracing::info!("Ringtone: {:?}", style);
+        self.ringtone.set_style(style);
+    }
+
+    /// Gibt den gewählten Klingelton zurück
+    pub fn ringtone_style(&self) -> RingtoneStyle {
+        self.ringtone.style()
+    }
+
+    /// Gibt zurück, ob gerade gesprochen wird (`false` ohne Anruf)
+    pub fn is_voice_active(&self) -> bool {
+        self.audio_handler
+            .lock()
+            .as_ref()
+            .is_some_and(|audio| audio.is_voice_active())
+    }
+
+    /// Begrenzt die Sende-Bitrate (`None` hebt die Grenze auf)
+    ///
+    /// Der Sende-Task senkt die Bitrate des Opus-Encoders ab dem nächsten
+    /// Frame, gilt also auch für den laufenden Anruf, ohne Neuverhandlung.
+    /// Unterhalb von `MIN_BITRATE_CAP_BPS` wird abgelehnt.
+    pub fn set_max_bitrate(&self, max_bps: Option<u32>) -> Result<(), CallEngineError> {
+        if let Some(bps) = max_bps {
+            validate_bitrate_cap(bps).map_err(CallEngineError::InvalidConfig)?;
+        }
+
+        tracing::info!("Max send bitrate: {:?} bps", max_bps);
+        *self.max_bitrate.lock() = max_bps;
+        Ok(())
+    }
+
+    /// Gibt die Obergrenze der Sende-Bitrate zurück
+    pub fn max_bitrate(&self) -> Option<u32> {
+        *self.max_bitrate.lock()
+    }
+
+    /// Setzt die Ziel-Bitrate des Opus-Encoders (siehe `OPUS_BITRATE_RANGE`)
+    ///
+    /// Der Sende-Task übernimmt sie ab dem nächsten Frame, gilt also auch für
+    /// den laufenden Anruf. Eine `set_max_bitrate`-Grenze begrenzt sie dabei
+    /// auf die Obergrenze abzüglich der Header.
+    pub fn set_codec_bitrate(&self, bps: u32) -> Result<(), CallEngineError> {
+        validate_opus_bitrate(bps).map_err(|e| CallEngineError::InvalidConfig(e.to_string()))?;
+        tracing::info!("Opus bitrate: {} bps", bps);
+        self.codec_bitrate.store(bps, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Gibt die Ziel-Bitrate des Opus-Encoders in bps zurück
+    pub fn codec_bitrate(&self) -> u32 {
+        self.codec_bitrate.load(Ordering::Relaxed)
+    }
+
+    /// Dauer des verbundenen Anrufs (`None` außerhalb von `Connected`)
+    ///
+    /// Gezählt ab dem Verbinden des am längsten verbundenen Teilnehmers.
+    pub fn call_duration(&self) -> Option<std::time::Duration> {
+        Self::connected_duration(&self.state, &self.peers)
+    }
+
+    fn connected_duration(
+        state: &Mutex<CallState>,
+        peers: &Mutex<HashMap<String, PeerSession>>,
+    ) -> Option<std::time::Duration> {
+        if !matches!(
+            *state.lock(),
+            CallState::Connected { .. } | CallState::Reconnecting { .. }
+        ) {
+            return None;
+        }
+        peers
+            .lock()
+            .values()
+            .filter_map(|session| session.connected_at)
+            .min()
+            .map(|connected_at| connected_at.elapsed())
+    }
+
+    /// Sendet `CallEvent::Duration`, solange der Anruf verbunden ist
+    ///
+    /// Läuft ab dem Start von Audio bis `stop_audio` und schweigt, solange
+    /// noch keine Verbindung besteht.
+    async fn run_call_timer(
+        state: Arc<Mutex<CallState>>,
+        peers: PeerSessions,
+        event_tx: broadcast::Sender<CallEvent>,
+    ) {
+        let mut interval = tokio::time::interval(CALL_TIMER_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            interval.tick().await;
+            if let Some(duration) = Self::connected_duration(&state, &peers) {
+                let _ = event_tx.send(CallEvent::Duration {
+                    seconds: duration.as_secs(),
+                });
+            }
+        }
+    }
+
+    /// Gibt RTT, Verlust, Jitter und übertragene Bytes des Anrufs zurück
+    ///
+    /// Solange webrtc-rs noch keine Werte gesammelt hat, sind sie 0. Ohne
+    /// aufgebaute Peer Connection gibt es keine Statistik.
+    pub async fn get_stats(&self) -> Result<ConnectionStats, CallEngineError> {
+        if !self
+            .peers
+            .lock()
+            .values()
+            .any(|session| session.pc.is_some())
+        {
+            return Err(CallEngineError::NoActiveCall);
+        }
+        Ok(Self::collect_connection_stats(&self.peers, &self.receive_quality).await)
+    }
+
+    /// Fasst die Stats aller Peer Connections zusammen
+    ///
+    /// Bytes werden summiert, RTT und Jitter sind der schlechteste Wert
+    /// über alle Teilnehmer.
+    async fn collect_connection_stats(
+        peers: &PeerSessions,
+        receive_quality: &Mutex<HashMap<u32, ReceiveQuality>>,
+    ) -> ConnectionStats {
+        let connections: Vec<Arc<RTCPeerConnection>> = peers
+            .lock()
+            .values()
+            .filter_map(|session| session.pc.clone())
+            .collect();
+
+        let mut stats = ConnectionStats::default();
+        for pc in connections {
+            let report = pc.get_stats().await;
+            for entry in report.reports.values() {
+                match entry {
+                    StatsReportType::CandidatePair(pair) if pair.nominated => {
+                        stats.rtt_ms = stats.rtt_ms.max(pair.current_round_trip_time * 1000.0);
+                    }
+                    StatsReportType::OutboundRTP(outbound) => {
+                        stats.bytes_sent += outbound.bytes_sent;
+                    }
+                    StatsReportType::InboundRTP(inbound) => {
+                        stats.bytes_received += inbound.bytes_received;
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        for quality in receive_quality.lock().values() {
+            stats.packets_lost += quality.packets_lost();
+            stats.jitter_ms = stats.jitter_ms.max(quality.jitter_ms());
+        }
+        stats
+    }
+
+    /// Sendet alle `STATS_INTERVAL` `CallEvent::Stats`, solange der Anruf verbunden ist
+    async fn run_stats_reporter(
+        state: Arc<Mutex<CallState>>,
+        peers: PeerSessions,
+        receive_quality: Arc<Mutex<HashMap<u32, ReceiveQuality>>>,
+        event_tx: broadcast::Sender<CallEvent>,
+    ) {
+        let mut interval = tokio::time::interval(STATS_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            interval.tick().await;
+            if !matches!(*state.lock(), CallState::Connected { .. }) {
+                continue;
+            }
+            let stats = Self::collect_connection_stats(&peers, &receive_quality).await;
+            let _ = event_tx.send(CallEvent::Stats(stats));
+        }
+    }
+
+    /// Gibt Audio-Levels zurück (input, output)
+    pub fn audio_levels(&self) -> (f32, f32) {
+        self.audio_handler
+            .lock()
+            .as_ref()
+            .map(|a| a.get_levels())
+            .unwrap_or((0.0, 0.0))
+    }
+
+    /// Registriert einen eingehenden Anruf
+    ///
+    /// Während eines laufenden Anrufs klingelt er als weiterer Teilnehmer,
+    /// der Gesamt-State bleibt dabei unverändert.
+    pub fn register_incoming_call(&self, peer_id: String, username: String) {
+        self.set_peer_state(&peer_id.clone(), CallState::Ringing { peer_id, username });
+
+        // Während eines laufenden Anrufs kündigt nur die UI den Anrufer an
+        if matches!(self.state(), CallState::Ringing { .. }) {
+            self.start_ringtone();
+        }
+    }
+
+    /// Verarbeitet ein eingehendes Offer inklusive Glare-Auflösung
+    ///
+    /// Rufen wir den Absender gerade selbst an, wird deterministisch über
+    /// `keeps_own_offer` entschieden, sodass genau ein Anruf übrig bleibt.
+    /// Ohne eigene Peer-ID (nicht registriert) gibt es keinen Glare. Das
+    /// erneute Offer nach gescheitertem Verbindungsaufbau wird direkt angenommen,
+    /// ebenso ein ICE Restart eines bereits verbundenen Teilnehmers.
+    pub async fn handle_incoming_offer(
+        &self,
+        own_peer_id: Option<&str>,
+        from_peer_id: String,
+        from_username: String,
+        offer_sdp: String,
+    ) -> Result<IncomingCallResolution, CallEngineError> {
+        if self.prepare_setup_retry(&from_peer_id) {
+            tracing::info!("Accepting retried offer from {}", from_peer_id);
+            let answer_sdp = self.accept_offer(from_peer_id, offer_sdp).await?;
+            return Ok(IncomingCallResolution::AcceptedRetry { answer_sdp });
+        }
+        if let Some(answer_sdp) = self
+            .accept_ice_restart(&from_peer_id, offer_sdp.clone())
+            .await
+        {
+            return Ok(IncomingCallResolution::AcceptedIceRestart {
+                answer_sdp: answer_sdp?,
+            });
+        }
+
+        let calling_sender = matches!(
+            self.state(),
+            CallState::Calling { ref peer_id } if *peer_id == from_peer_id
+        );
+
+        let own_peer_id = match own_peer_id {
+            Some(own_peer_id) if calling_sender => own_peer_id,
+            _ => {
+                self.register_incoming_call(from_peer_id, from_username);
+                return Ok(IncomingCallResolution::Ring);
+            }
+        };
+
+        if keeps_own_offer(own_peer_id, &from_peer_id) {
+            tracing::info!("Glare with {}: keeping own offer", from_peer_id);
+            return Ok(IncomingCallResolution::KeepOwnOffer);
+        }
+
+        tracing::info!("Glare with {}: accepting remote offer", from_peer_id);
+        self.abandon_outgoing_call();
+        let answer_sdp = self.accept_offer(from_peer_id, offer_sdp).await?;
+        Ok(IncomingCallResolution::AcceptedRemoteOffer { answer_sdp })
+    }
+
+    // ========================================================================
+    // PRIVATE METHODS
+    // ========================================================================
+
+    /// Erstellt eine neue Peer Connection
+    ///
+    /// `peer_id` ist bei einer vorgewärmten Verbindung noch nicht bekannt.
+    async fn create_peer_connection(
+        &self,
+        ice_servers: Vec<RTCIceServer>,
+        peer_id: Option<&str>,
+    ) -> Result<Arc<RTCPeerConnection>, CallEngineError> {
+        let api = Self::build_api()?;
+        let config = self.rtc_configuration(ice_servers);
+
+        // Peer Connection erstellen
+        let pc = Arc::new(
+            api.new_peer_connection(config)
+                .await
+                .map_err(|e| CallEngineError::WebRTC(e.to_string()))?,
+        );
+
+        // Event Handler registrieren
+        self.setup_peer_connection_handlers(Arc::clone(&pc), peer_id.map(str::to_string))
+            .await;
+
+        Ok(pc)
+    }
+
+    /// RTCConfiguration mit ICE Servern und der aktuellen Transport Policy
+    fn rtc_configuration(&self, ice_servers: Vec<RTCIceServer>) -> RTCConfiguration {
+        // LAN-Anrufe (ohne ICE Server) laufen immer direkt, Relay-only gilt
+        // nur für Anrufe über das Internet
+        let ice_transport_policy = if ice_servers.is_empty() {
+            IceTransportPolicy::All
+        } else {
+            self.ice_transport_policy()
+        };
+
+        RTCConfiguration {
+            ice_servers,
+            ice_transport_policy: ice_transport_policy.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Erstellt die WebRTC API mit Codecs, Interceptors und Netzwerk-Filtern
+    fn build_api() -> Result<API, CallEngineError> {
+        // Media Engine mit Opus konfigurieren
+        let mut media_engine = MediaEngine::default();
+        media_engine
+            .register_default_codecs()
+            .map_err(|e| CallEngineError::WebRTC(e.to_string()))?;
+
+        // Interceptors für RTCP, NACK etc.
+        let mut registry = Registry::new();
+        registry = register_default_interceptors(registry, &mut media_engine)
+            .map_err(|e| CallEngineError::WebRTC(e.to_string()))?;
+
+        // Setting Engine für Netzwerk-Konfiguration
+        let mut setting_engine = SettingEngine::default();
+
+        // Interface-Filter: Exclude virtual adapters and problematic interfaces
+        setting_engine.set_interface_filter(Box::new(|interface_name: &str| {
+            let name_lower = interface_name.to_lowercase();
+
+            // Exclude virtual and problematic interfaces
+            let excluded = [
+                "hyper-v",
+                "vmware",
+                "virtualbox",
+                "docker",
+                "vethernet",
+                "bluetooth",
+                "loopback",
+                "teredo",
+                "isatap",
+                "6to4",
+            ];
+
+            for pattern in excluded {
+                if name_lower.contains(pattern) {
+                    tracing::debug!("Excluding interface: {}", interface_name);
+                    return false;
+                }
+            }
+
+            true
+        }));
+
+        // IP-Filter: Only use valid, routable IP addresses
+        setting_engine.set_ip_filter(Box::new(|ip: IpAddr| {
+            match ip {
+                IpAddr::V4(ipv4) => {
+                    // Exclude link-local (169.254.x.x) and loopback
+                    if ipv4.is_link_local() || ipv4.is_loopback() {
+                        return false;
+                    }
+                    // Exclude APIPA addresses
+                    let octets = ipv4.octets();
+                    if octets[0] == 169 && octets[1] == 254 {
+                        return false;
+                    }
+                    true
+                }
+                IpAddr::V6(ipv6) => {
+                    // Exclude link-local (fe80::) and loopback IPv6
+                    if ipv6.is_loopback() {
+                        return false;
+                    }
+                    // Exclude link-local IPv6 (starts with fe80)
+                    let segments = ipv6.segments();
+                    if segments[0] == 0xfe80 {
+                        return false;
+                    }
+                    true
+                }
+            }
+        }));
+
+        // API erstellen mit SettingEngine
+        Ok(APIBuilder::new()
+            .with_media_engine(media_engine)
+            .with_interceptor_registry(registry)
+            .with_setting_engine(setting_engine)
+            .build())
+    }
+
+    /// Liest lokalen und entfernten Fingerprint aus den Session Descriptions
+    async fn read_dtls_fingerprints(pc: &RTCPeerConnection) -> DtlsFingerprints {
+        let local = pc
+            .local_description()
+            .await
+            .and_then(|desc| parse_dtls_fingerprint(&desc.sdp));
+        let remote = pc
+            .remote_description()
+            .await
+            .and_then(|desc| parse_dtls_fingerprint(&desc.sdp));
+
+        DtlsFingerprints { local, remote }
+    }
+
+    /// Registriert Event Handler für die Peer Connection
+    ///
+    /// `peer_id` ist der Teilnehmer, für den die Verbindung aufgebaut wird
+    /// (`None` bei einer vorgewärmten Verbindung).
+    async fn setup_peer_connection_handlers(
+        &self,
+        pc: Arc<RTCPeerConnection>,
+        peer_id: Option<String>,
+    ) {
+        let event_tx = self.event_tx.clone();
+
+        // Connection State Handler
+        // Weak-Referenz, da der Handler in der Peer Connection selbst gespeichert wird
+        let state = Arc::clone(&self.state);
+        let event_tx_clone = event_tx.clone();
+        let pc_weak: Weak<RTCPeerConnection> = Arc::downgrade(&pc);
+        let peers = Arc::clone(&self.peers);
+        let rtp_counters = Arc::clone(&self.rtp_counters);
+        let audio_handler = Arc::clone(&self.audio_handler);
+        let echo_check_enabled = Arc::clone(&self.echo_check_enabled);
+        let setup_retry_allowed = Arc::clone(&self.setup_retry_allowed);
+        let pending_candidates = Arc::clone(&self.pending_candidates);
+        pc.on_peer_connection_state_change(Box::new(move |s: RTCPeerConnectionState| {
+            tracing::info!("Peer connection state: {:?}", s);
+
+            // Nur Verbindungen von Teilnehmern bestimmen den Call-State,
+            // nicht z.B. eine verworfene vorgewärmte oder bereits beendete
+            let session = pc_weak
+                .upgrade()
+                .and_then(|pc| Self::session_of(&peers, &pc));
+
+            // Scheitert ICE noch im Aufbau, wird dieser einmal wiederholt
+            if let (RTCPeerConnectionState::Failed, Some((peer_id, current))) = (s, &session) {
+                let outgoing = matches!(current, CallState::Calling { .. });
+                let setup = outgoing || matches!(current, CallState::Connecting { .. });
+                if setup && setup_retry_allowed.lock().remove(peer_id) {
+                    Self::begin_setup_retry(
+                        &peers,
+                        &state,
+                        &audio_handler,
+                        &pending_candidates,
+                        &event_tx_clone,
+                        peer_id,
+                        outgoing,
+                    );
+                    return Box::pin(async {});
+                }
+            }
+
+            let update = session.and_then(|(peer_id, current)| {
+                let peer_state = next_peer_state(s, &peer_id, &current)?;
+                Some((peer_id, current, peer_state))
+            });
+
+            if let Some((peer_id, previous, peer_state)) = update {
+                let connected = matches!(peer_state, Some(CallState::Connected { .. }));
+                let reconnected = matches!(previous, CallState::Reconnecting { .. });
+                let reconnecting = matches!(peer_state, Some(CallState::Reconnecting { .. }));
+                if connected {
+                    setup_retry_allowed.lock().remove(&peer_id);
+                }
+                let removed = Self::update_peer_state(
+                    &peers,
+                    &state,
+                    &audio_handler,
+                    &event_tx_clone,
+                    &peer_id,
+                    peer_state,
+                );
+
+                // Die Verbindung gehört zu keinem Teilnehmer mehr
+                if let Some(pc) = removed.and_then(|session| session.pc) {
+                    tokio::spawn(async move {
+                        let _ = pc.close().await;
+                    });
+                }
+
+                // Unterbrochene Verbindung: ICE Restart anstoßen und nach
+                // `ICE_RESTART_TIMEOUT` ohne Erfolg aufgeben
+                if reconnecting {
+                    tracing::info!("Connection to {} interrupted, restarting ICE", peer_id);
+                    let _ = event_tx_clone.send(CallEvent::RenegotiationNeeded {
+                        peer_id: peer_id.clone(),
+                    });
+                    tokio::spawn(Self::expire_ice_restart(
+                        pc_weak.clone(),
+                        Arc::clone(&peers),
+                        Arc::clone(&state),
+                        Arc::clone(&audio_handler),
+                        event_tx_clone.clone(),
+                        peer_id.clone(),
+                    ));
+                }
+
+                // Echo-Check nur beim ersten Teilnehmer, also einmal pro Anruf
+                if connected
+                    && !reconnected
+                    && echo_check_enabled.load(Ordering::Relaxed)
+                    && peers.lock().len() == 1
+                {
+                    tokio::spawn(Self::run_echo_check(
+                        Arc::clone(&audio_handler),
+                        event_tx_clone.clone(),
+                    ));
+                }
+
+                // Mit dem Verbindungsaufbau beginnt die Überwachung der RTP-Pakete
+                if connected {
+                    tokio::spawn(Self::monitor_one_way_audio(
+                        pc_weak.clone(),
+                        Arc::clone(&peers),
+                        peer_id,
+                        Arc::clone(&rtp_counters),
+                        Arc::clone(&audio_handler),
+                        event_tx_clone.clone(),
+                    ));
+                }
+            }
+
+            // Nach dem Verbindungsaufbau die DTLS Fingerprints melden
+            let pc_weak = pc_weak.clone();
+            let event_tx = event_tx_clone.clone();
+            let connected = s == RTCPeerConnectionState::Connected;
+            Box::pin(async move {
+                if !connected {
+                    return;
+                }
+                if let Some(pc) = pc_weak.upgrade() {
+                    let fingerprints = Self::read_dtls_fingerprints(&pc).await;
+                    let _ = event_tx.send(CallEvent::DtlsFingerprints(fingerprints));
+                }
+            })
+        }));
+
+        // Teilnehmer-ID für den Track Handler (der ICE Handler übernimmt `peer_id`)
+        let track_peer_id = peer_id.clone();
+
+        // ICE Candidate Handler
+        let event_tx_clone = event_tx.clone();
+        let local_candidates = Arc::clone(&self.local_candidates);
+        let verbose_ice_logging = Arc::clone(&self.verbose_ice_logging);
+        let pc_weak: Weak<RTCPeerConnection> = Arc::downgrade(&pc);
+        let peers = Arc::clone(&self.peers);
+        pc.on_ice_candidate(Box::new(move |candidate| {
+            if let Some(c) = candidate {
+                if let Ok(json) = c.to_json() {
+                    if verbose_ice_logging.load(Ordering::Relaxed) {
+                        log_candidate(&json.candidate, CandidateDirection::Local, &event_tx_clone);
+                    }
+                    if let Ok(candidate_str) = serde_json::to_string(&json) {
+                        local_candidates.lock().push(candidate_str.clone());
+
+                        // Eine vorgewärmte Verbindung gehört erst nach dem
+                        // Anrufstart zu einem Teilnehmer
+                        let peer_id = peer_id.clone().or_else(|| {
+                            let pc = pc_weak.upgrade()?;
+                            Self::session_of(&peers, &pc).map(|(peer_id, _)| peer_id)
+                        });
+                        let _ = event_tx_clone.send(CallEvent::IceCandidate {
+                            peer_id,
+                            candidate: candidate_str,
+                        });
+                    }
+                }
+            }
+            Box::pin(async {})
+        }));
+
+        // Track Handler (für eingehendes Audio)
+        // Dekodiertes Audio geht in die Mixer-Quelle des Teilnehmers
+        let rtp_counters = Arc::clone(&self.rtp_counters);
+        let audio_handler = Arc::clone(&self.audio_handler);
+        let receive_quality = Arc::clone(&self.receive_quality);
+        let pc_weak: Weak<RTCPeerConnection> = Arc::downgrade(&pc);
+        let peers = Arc::clone(&self.peers);
+        pc.on_track(Box::new(move |track, _, _| {
+            let rtp_counters = Arc::clone(&rtp_counters);
+            let audio_handler = Arc::clone(&audio_handler);
+            let receive_quality = Arc::clone(&receive_quality);
+
+            // Eine vorgewärmte Verbindung gehört erst nach dem Anrufstart zu
+            // einem Teilnehmer, Tracks kommen aber erst danach an
+            let source_id = track_peer_id.clone().or_else(|| {
+                let pc = pc_weak.upgrade()?;
+                Self::session_of(&peers, &pc).map(|(peer_id, _)| peer_id)
+            });
+
+            Box::pin(async move {
+                let codec = track.codec();
+                tracing::info!("Received track: {:?}", codec);
+                let mut decoder =
+                    RtpAudioDecoder::new(ReceiveCodec::from_mime_type(&codec.capability.mime_type));
+                if !decoder.is_supported() {
+                    tracing::warn!(
+                        "No decoder for {}, incoming audio is not played",
+                        codec.capability.mime_type
+                    );
+                }
+
+                // Pakete auch ohne Decoder lesen, damit der Empfang gezählt wird
+                let ssrc = track.ssrc();
+                let clock_rate = codec.capability.clock_rate;
+                tokio::spawn(async move {
+                    while let Ok((packet, _)) = track.read_rtp().await {
+                        rtp_counters.record_received();
+                        receive_quality
+                            .lock()
+                            .entry(ssrc)
+                            .or_insert_with(|| ReceiveQuality::new(clock_rate))
+                            .observe(
+                                packet.header.sequence_number,
+                                packet.header.timestamp,
+                                std::time::Instant::now(),
+                            );
+                        if let Some(samples) = decoder.decode(&packet.payload) {
+                            Self::play_received(&audio_handler, source_id.as_deref(), &samples);
+                        }
+                    }
+                });
+            })
+        }));
+    }
+
+    /// Übergibt empfangene Samples an die Mixer-Quelle des Teilnehmers
+    ///
+    /// Ohne bekannten Teilnehmer gehen sie in die Standard-Quelle. Vor dem
+    /// Start von Audio oder nach dem Auflegen werden sie verworfen.
+    fn play_received(
+        audio_handler: &Mutex<Option<AudioHandler>>,
+        source_id: Option<&str>,
+        samples: &[f32],
+    ) {
+        let audio_handler = audio_handler.lock();
+        let Some(audio) = audio_handler.as_ref() else {
+            return;
+        };
+        match source_id {
+            Some(source_id) => {
+                let _ = audio.write_samples_for(source_id, samples);
+            }
+            None => audio.write_samples(samples),
+        }
+    }
+
+    /// Beendet den Teilnehmer, wenn der ICE Restart nicht rechtzeitig gelingt
+    async fn expire_ice_restart(
+        pc: Weak<RTCPeerConnection>,
+        peers: PeerSessions,
+        state: Arc<Mutex<CallState>>,
+        audio_handler: Arc<Mutex<Option<AudioHandler>>>,
+        event_tx: broadcast::Sender<CallEvent>,
+        peer_id: String,
+    ) {
+        tokio::time::sleep(ICE_RESTART_TIMEOUT).await;
+
+        let Some(pc) = pc.upgrade() else {
+            return;
+        };
+        let still_reconnecting = matches!(
+            Self::session_of(&peers, &pc),
+            Some((current, CallState::Reconnecting { .. })) if current == peer_id
+        );
+        if !still_reconnecting {
+            return;
+        }
+
+        tracing::warn!("ICE restart with {} timed out, leaving call", peer_id);
+        Self::update_peer_state(&peers, &state, &audio_handler, &event_tx, &peer_id, None);
+        let _ = pc.close().await;
+    }
+
+    /// Sucht den Teilnehmer zu einer Peer Connection (Peer-ID und State)
+    fn session_of(
+        peers: &Mutex<HashMap<String, PeerSession>>,
+        pc: &Arc<RTCPeerConnection>,
+    ) -> Option<(String, CallState)> {
+        peers.lock().iter().find_map(|(peer_id, session)| {
+            session
+                .pc
+                .as_ref()
+                .is_some_and(|active| Arc::ptr_eq(active, pc))
+                .then(|| (peer_id.clone(), session.state.clone()))
+        })
+    }
+
+    /// Meldet einseitiges Audio, solange der Teilnehmer über `pc` verbunden ist
+    ///
+    /// Während der Stummschaltung wird nichts gemeldet, dort ist ausbleibendes
+    /// ausgehendes Audio gewollt. Die RTP-Zähler gelten für den ganzen Anruf,
+    /// mit mehreren Teilnehmern ist keine Aussage pro Richtung möglich.
+    async fn monitor_one_way_audio(
+        pc: Weak<RTCPeerConnection>,
+        peers: PeerSessions,
+        peer_id: String,
+        rtp_counters: Arc<RtpCounters>,
+        audio_handler: Arc<Mutex<Option<AudioHandler>>>,
+        event_tx: broadcast::Sender<CallEvent>,
+    ) {
+        let mut detector = OneWayAudioDetector::default();
+        let mut interval = tokio::time::interval(RTP_MONITOR_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            let connected = pc.upgrade().and_then(|pc| Self::session_of(&peers, &pc));
+            if !matches!(connected, Some((current, CallState::Connected { .. })) if current == peer_id)
+            {
+                break;
+            }
+
+            let muted = audio_handler
+                .lock()
+                .as_ref()
+                .is_some_and(|audio| audio.is_muted());
+            let conference = peers.lock().len() > 1;
+            if muted || conference {
+                detector.reset();
+                continue;
+            }
+
+            let (sent, received) = rtp_counters.snapshot();
+            if let Some(direction) = detector.observe(sent, received, std::time::Instant::now()) {
+                tracing::warn!("One-way audio detected, {:?} audio missing", direction);
+                let _ = event_tx.send(CallEvent::OneWayAudio { direction });
+            }
+        }
+    }
+
+    /// Spielt das Prüfsignal, wertet den Mitschnitt aus und meldet ggf. Echo
+    ///
+    /// Stummgeschaltet entfällt der Check. Wurde er per `skip_echo_check`
+    /// abgebrochen oder der Anruf beendet, fehlt der Mitschnitt und es wird
+    /// nichts gemeldet.
+    async fn run_echo_check(
+        audio_handler: Arc<Mutex<Option<AudioHandler>>>,
+        event_tx: broadcast::Sender<CallEvent>,
+    ) {
+        match audio_handler.lock().as_ref() {
+            Some(audio) if !audio.is_muted() => audio.start_echo_check(),
+            _ => return,
+        }
+
+        tokio::time::sleep(ECHO_PROBE_DURATION + ECHO_MAX_DELAY).await;
+
+        let recording = audio_handler
+            .lock()
+            .as_ref()
+            .and_then(AudioHandler::finish_echo_check);
+        let Some(recording) = recording else {
+            return;
+        };
+
+        let correlation =
+            tokio::task::spawn_blocking(move || echo_correlation(&echo_probe(), &recording))
+                .await
+                .unwrap_or(0.0);
+        tracing::info!("Echo check correlation: {:.2}", correlation);
+
+        if correlation >= ECHO_CORRELATION_THRESHOLD {
+            tracing::warn!("Echo detected (correlation {:.2})", correlation);
+            let _ = event_tx.send(CallEvent::EchoDetected { correlation });
+        }
+    }
+
+    /// Prüft vor dem Anruf, ob das Mikrofon freigegeben ist
+    ///
+    /// Bei verweigerter Berechtigung wird `PermissionRequired` gemeldet, statt
+    /// später in `init_audio` mit einem allgemeinen Stream-Fehler abzubrechen.
+    /// Prüft, ob ein eingehender Anruf dieses Peers noch klingelt
+    fn is_ringing(&self, peer_id: &str) -> bool {
+        self.peers
+            .lock()
+            .get(peer_id)
+            .is_some_and(|session| matches!(session.state, CallState::Ringing { .. }))
+    }
+
+    /// Erlaubt einmal den neuen Verbindungsaufbau, sofern aktiviert
+    fn allow_setup_retry(&self, peer_id: &str) {
+        if self.setup_retry_enabled() {
+            self.setup_retry_allowed.lock().insert(peer_id.to_string());
+        }
+    }
+
+    /// Prüft, ob der Angerufene auf das erneute Offer dieses Teilnehmers wartet
+    fn is_awaiting_setup_retry(&self, peer_id: &str) -> bool {
+        self.peers.lock().get(peer_id).is_some_and(|session| {
+            matches!(session.state, CallState::Connecting { .. }) && session.pc.is_none()
+        })
+    }
+
+    /// Prüft, ob ein eingehendes Offer einen gescheiterten Aufbau wiederholt
+    ///
+    /// Der Anrufer kann sein Scheitern vor uns bemerken. Eine noch nicht als
+    /// gescheitert erkannte Verbindung wird dann hier geschlossen, sofern die
+    /// Wiederholung für den Teilnehmer noch erlaubt ist.
+    fn prepare_setup_retry(&self, peer_id: &str) -> bool {
+        let stale = {
+            let mut peers = self.peers.lock();
+            let Some(session) = peers.get_mut(peer_id) else {
+                return false;
+            };
+            if !matches!(session.state, CallState::Connecting { .. }) {
+                return false;
+            }
+            if session.pc.is_some() && !self.setup_retry_allowed.lock().remove(peer_id) {
+                return false;
+            }
+            session.local_track = None;
+            session.pc.take()
+        };
+
+        self.pending_candidates.lock().remove(peer_id);
+        if let Some(pc) = stale {
+            tokio::spawn(async move {
+                let _ = pc.close().await;
+            });
+        }
+        true
+    }
+
+    /// Verwirft die gescheiterte Verbindung, der Teilnehmer bleibt im Anruf
+    ///
+    /// Der Anrufer bleibt `Calling` und sendet nach `CallEvent::Retrying` über
+    /// `retry_call` ein neues Offer. Der Angerufene bleibt `Connecting` und
+    /// beendet den Anruf, wenn bis `SETUP_RETRY_OFFER_TIMEOUT` kein neues
+    /// Offer angenommen wurde.
+    fn begin_setup_retry(
+        peers: &PeerSessions,
+        state: &Arc<Mutex<CallState>>,
+        audio_handler: &Arc<Mutex<Option<AudioHandler>>>,
+        pending_candidates: &Mutex<HashMap<String, Vec<RTCIceCandidateInit>>>,
+        event_tx: &broadcast::Sender<CallEvent>,
+        peer_id: &str,
+        outgoing: bool,
+    ) {
+        tracing::warn!(
+            "ICE failed while setting up call with {}, retrying",
+            peer_id
+        );
+
+        let failed = peers.lock().get_mut(peer_id).and_then(|session| {
+            session.local_track = None;
+            session.pc.take()
+        });
+        if let Some(pc) = failed {
+            tokio::spawn(async move {
+                let _ = pc.close().await;
+            });
+        }
+        // Candidates der alten Verbindung passen nicht zur neuen
+        pending_candidates.lock().remove(peer_id);
+
+        let _ = event_tx.send(CallEvent::Retrying {
+            peer_id: peer_id.to_string(),
+            outgoing,
+        });
+
+        if outgoing {
+            return;
+        }
+        let peers = Arc::clone(peers);
+        let state = Arc::clone(state);
+        let audio_handler = Arc::clone(audio_handler);
+        let event_tx = event_tx.clone();
+        let peer_id = peer_id.to_string();
+        tokio::spawn(async move {
+            tokio::time::sleep(SETUP_RETRY_OFFER_TIMEOUT).await;
+            let still_waiting = peers.lock().get(&peer_id).is_some_and(|session| {
+                matches!(session.state, CallState::Connecting { .. }) && session.pc.is_none()
+            });
+            if still_waiting {
+                tracing::warn!("No retried offer from {}, ending call", peer_id);
+                Self::update_peer_state(&peers, &state, &audio_handler, &event_tx, &peer_id, None);
+            }
+        });
+    }
+
+    async fn ensure_microphone_permission(&self) -> Result<(), CallEngineError> {
+        let permission = tokio::task::spawn_blocking(check_microphone_permission)
+            .await
+            .unwrap_or(MicrophonePermission::Undetermined);
+
+        if permission == MicrophonePermission::Denied {
+            tracing::warn!("Microphone permission denied, not starting call");
+            let _ = self
+                .event_tx
+                .send(CallEvent::PermissionRequired { permission });
+            return Err(CallEngineError::MicrophonePermissionDenied);
+        }
+        Ok(())
+    }
+
+    /// Initialisiert Audio
+    fn init_audio(&self) -> Result<(), CallEngineError> {
+        let mut audio = self.take_audio_handler()?;
+        self.report_missing_devices(&audio);
+        audio.set_prefill_frames(*self.playback_prefill_frames.lock())?;
+        audio.set_frame_size(*self.audio_frame_size.lock())?;
+        audio.set_output_limiter(*self.output_limiter.lock());
+        audio.set_output_delay(*self.output_delay_ms.lock())?;
+        audio.set_muted(*self.muted.lock());
+        audio.set_vad_enabled(*self.vad_enabled.lock());
+        audio.set_vad_threshold(*self.vad_threshold.lock())?;
+        audio.set_ptt_enabled(*self.ptt_enabled.lock());
+        audio.set_ptt_active(*self.ptt_active.lock());
+        if let Err(e) = audio.start_capture() {
+            if matches!(e, AudioError::DeviceBusy) {
+                tracing::warn!("Microphone is in use by another application");
+                let _ = self.event_tx.send(CallEvent::MicrophoneBusy);
+            }
+            return Err(e.into());
+        }
+        audio.start_playback()?;
+        *self.audio_handler.lock() = Some(audio);
+
+        self.receive_quality.lock().clear();
+
+        // Sende-Task kodiert Frames mit `AudioHandler::frame_size`
+        let tasks = vec![
+            tokio::spawn(run_audio_sender(
+                Arc::clone(&self.audio_handler),
+                self.rtp_writer(),
+                Arc::clone(&self.codec_bitrate),
+                Arc::clone(&self.max_bitrate),
+            )),
+            tokio::spawn(Self::run_call_timer(
+                Arc::clone(&self.state),
+                Arc::clone(&self.peers),
+                self.event_tx.clone(),
+            )),
+            tokio::spawn(Self::run_stats_reporter(
+                Arc::clone(&self.state),
+                Arc::clone(&self.peers),
+                Arc::clone(&self.receive_quality),
+                self.event_tx.clone(),
+            )),
+        ];
+        for previous in std::mem::replace(&mut *self.call_tasks.lock(), tasks) {
+            previous.abort();
+        }
+
+        Ok(())
+    }
+
+    /// Erstellt einen Audio Handler mit den ausgewählten Geräten
+    fn create_audio_handler(&self) -> Result<AudioHandler, CallEngineError> {
+        let devices = self.audio_devices();
+        Ok(AudioHandler::with_devices(devices.input, devices.output)?)
+    }
+
+    /// Gibt den vorbereiteten Audio Handler zurück oder erstellt einen neuen
+    ///
+    /// Ein Gerätewechsel hat den vorbereiteten Handler bereits verworfen
+    /// (siehe `invalidate_prewarmed_audio`), die Geräte werden hier also
+    /// nicht erneut abgefragt.
+    fn take_audio_handler(&self) -> Result<AudioHandler, CallEngineError> {
+        let prewarmed = self.prewarmed_audio.lock().take();
+        match prewarmed {
+            Some(audio) => Ok(audio),
+            None => self.create_audio_handler(),
+        }
+    }
+
+    /// Meldet ausgewählte Geräte, die durch das Standardgerät ersetzt wurden
+    fn report_missing_devices(&self, audio: &AudioHandler) {
+        let devices = self.audio_devices();
+        let selections = [
+            ("input", devices.input, audio.input_device_name()),
+            ("output", devices.output, audio.output_device_name()),
+        ];
+        for (kind, selected, used) in selections {
+            let Some(selected) = selected else {
+                continue;
+            };
+            if used != Some(selected.as_str()) {
+                let _ = self.event_tx.send(CallEvent::Error(format!(
+                    "Audio {} device \"{}\" is not available, using the default device",
+                    kind, selected
+                )));
+            }
+        }
+    }
+
+    /// Fügt dem Playback-Mixer eine Quelle für den Teilnehmer hinzu
+    fn add_peer_audio(&self, peer_id: &str) {
+        if let Some(audio) = self.audio_handler.lock().as_ref() {
+            audio.add_playback_source(peer_id);
+        }
+    }
+
+    /// Aktualisiert den State und sendet Event
+    fn set_state(&self, new_state: CallState) {
+        if !matches!(new_state, CallState::Ringing { .. }) {
+            self.ringtone.stop();
+        }
+        *self.state.lock() = new_state.clone();
+        let _ = self.event_tx.send(CallEvent::StateChanged(new_state));
+    }
+
+    /// Setzt den State eines Teilnehmers (legt ihn bei Bedarf an)
+    fn set_peer_state(&self, peer_id: &str, peer_state: CallState) {
+        self.apply_peer_state(peer_id, Some(peer_state));
+    }
+
+    /// Wie `update_peer_state`, beendet außerdem den Klingelton, sobald kein
+    /// Teilnehmer mehr klingelt
+    fn apply_peer_state(
+        &self,
+        peer_id: &str,
+        peer_state: Option<CallState>,
+    ) -> Option<PeerSession> {
+        let removed = Self::update_peer_state(
+            &self.peers,
+            &self.state,
+            &self.audio_handler,
+            &self.event_tx,
+            peer_id,
+            peer_state,
+        );
+        let ringing = self
+            .peers
+            .lock()
+            .values()
+            .any(|session| matches!(session.state, CallState::Ringing { .. }));
+        if !ringing {
+            self.ringtone.stop();
+        }
+        removed
+    }
+
+    /// Aktualisiert den State eines Teilnehmers und leitet den Gesamt-State ab
+    ///
+    /// `None` entfernt den Teilnehmer samt Mixer-Quelle, seine Session wird
+    /// zurückgegeben. Ändert sich der Gesamt-State, wird zusätzlich
+    /// `StateChanged` gesendet.
+    fn update_peer_state(
+        peers: &Mutex<HashMap<String, PeerSession>>,
+        state: &Mutex<CallState>,
+        audio_handler: &Mutex<Option<AudioHandler>>,
+        event_tx: &broadcast::Sender<CallEvent>,
+        peer_id: &str,
+        peer_state: Option<CallState>,
+    ) -> Option<PeerSession> {
+        let current = state.lock().clone();
+        let (removed, aggregate) = {
+            let mut peers = peers.lock();
+            let removed = match &peer_state {
+                Some(peer_state) => {
+                    let session = peers
+                        .entry(peer_id.to_string())
+                        .or_insert_with(|| PeerSession {
+                            state: peer_state.clone(),
+                            pc: None,
+                            local_track: None,
+                            connected_at: None,
+                        });
+                    if matches!(peer_state, CallState::Connected { .. }) {
+                        session
+                            .connected_at
+                            .get_or_insert_with(std::time::Instant::now);
+                    }
+                    session.state = peer_state.clone();
+                    None
+                }
+                None => peers.remove(peer_id),
+            };
+            let aggregate =
+                aggregate_call_state(peers.values().map(|session| &session.state), &current);
+            (removed, aggregate)
+        };
+
+        if peer_state.is_none() {
+            if let Some(audio) = audio_handler.lock().as_ref() {
+                audio.remove_playback_source(peer_id);
+            }
+        }
+
+        let _ = event_tx.send(CallEvent::PeerStateChanged {
+            peer_id: peer_id.to_string(),
+            state: peer_state.unwrap_or(CallState::Ended),
+        });
+        if aggregate != current {
+            *state.lock() = aggregate.clone();
+            let _ = event_tx.send(CallEvent::StateChanged(aggregate));
+        }
+        removed
+    }
+}
+
+impl Default for CallEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for CallEngine {
+    fn drop(&mut self) {
+        self.ringtone.stop();
+        self.stop_audio();
+
+        // Lock vor dem Schließen freigeben, der State-Handler liest die
+        // Teilnehmer beim Übergang nach `Closed`
+        for pc in self.take_peers().into_iter().filter_map(|(_, pc)| pc) {
+            tracing::info!("CallEngine dropped with an open peer connection, closing it");
+            Self::close_blocking(pc);
+        }
+        if let Some(prewarmed) = self.prewarmed_connection.get_mut().take() {
+            Self::close_blocking(prewarmed.pc);
+        }
+    }
+}
+
+impl std::fmt::Debug for CallEngine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CallEngine")
+            .field("state", &self.state())
+            .field("is_muted", &self.is_muted())
+            .finish()
+    }
+}
+
+// ============================================================================
+// RTP SENDING
+// ============================================================================
+
+/// Sendepfad für ausgehende RTP-Pakete
+///
+/// Teilt die Zustände mit der `CallEngine`, damit der Sende-Task dieselben
+/// Zähler und dieselbe Netzwerk-Simulation nutzt wie `write_rtp`.
+#[derive(Clone)]
+struct RtpWriter {
+    peers: PeerSessions,
+    #[cfg_attr(not(debug_assertions), allow(dead_code))]
+    network_simulation: Arc<Mutex<NetworkSimulation>>,
+    rtp_counters: Arc<RtpCounters>,
+}
+
+impl RtpWriter {
+    async fn write(&self, packet: Packet) -> Result<(), CallEngineError> {
+        let tracks: Vec<Arc<TrackLocalStaticRTP>> = self
+            .peers
+            .lock()
+            .values()
+            .filter_map(|session| session.local_track.clone())
+            .collect();
+        if tracks.is_empty() {
+            return Err(CallEngineError::NoActiveCall);
+        }
+
+        self.rtp_counters.record_sent();
+
+        #[cfg(debug_assertions)]
+        {
+            let simulation = *self.network_simulation.lock();
+            if simulation.is_active() {
+                let Some(delay) = simulation.packet_delay(&mut rand::thread_rng()) else {
+                    return Ok(());
+                };
+                if !delay.is_zero() {
+                    tokio::spawn(async move {
+                        tokio::time::sleep(delay).await;
+                        for track in tracks {
+                            let _ = track.write_rtp(&packet).await;
+                        }
+                    });
+                    return Ok(());
+                }
+            }
+        }
+
+        // Ein fehlerhafter Track hält die anderen Teilnehmer nicht auf
+        let mut result = Ok(());
+        for track in tracks {
+            if let Err(e) = track.write_rtp(&packet).await {
+                result = Err(CallEngineError::WebRTC(e.to_string()));
+            }
+        }
+        result
+    }
+}
+
+/// Verpackt Opus-Payloads in fortlaufend nummerierte RTP-Pakete
+///
+/// SSRC und Payload Type setzt der Track beim Schreiben je Verbindung neu,
+/// Sequenznummer und Timestamp beginnen zufällig (RFC 3550).
+struct RtpPacketizer {
+    sequence_number: u16,
+    timestamp: u32,
+    /// Das nächste Paket beginnt nach unterdrückter Stille
+    talkspurt: bool,
+}
+
+impl RtpPacketizer {
+    fn new() -> Self {
+        Self {
+            sequence_number: rand::random(),
+            timestamp: rand::random(),
+            talkspurt: true,
+        }
+    }
+
+    /// Erstellt das Paket für einen Frame aus `samples` Samples
+    ///
+    /// Das erste Paket nach Stille trägt das Marker-Bit (RFC 3551).
+    fn packetize(&mut self, payload: Vec<u8>, samples: usize) -> Packet {
+        let packet = Packet {
+            header: Header {
+                version: 2,
+                marker: std::mem::take(&mut self.talkspurt),
+                payload_type: OPUS_PAYLOAD_TYPE,
+                sequence_number: self.sequence_number,
+                timestamp: self.timestamp,
+                ..Default::default()
+            },
+            payload: payload.into(),
+        };
+        self.sequence_number = self.sequence_number.wrapping_add(1);
+        self.timestamp = self.timestamp.wrapping_add(samples as u32);
+        packet
+    }
+
+    /// Überspringt einen nicht gesendeten Frame
+    ///
+    /// Nur der Timestamp läuft weiter, damit die Gegenstelle die Pause
+    /// richtig einordnet; die Sequenznummern bleiben lückenlos.
+    fn skip(&mut self, samples: usize) {
+        self.timestamp = self.timestamp.wrapping_add(samples as u32);
+        self.talkspurt = true;
+    }
+}
+
+/// Bitrate für den Encoder des Sende-Tasks aus Ziel-Bitrate und Obergrenze
+fn sender_bitrate(
+    codec_bitrate: &AtomicU32,
+    max_bitrate: &Mutex<Option<u32>>,
+    frame_size: usize,
+) -> u32 {
+    encoder_bitrate(
+        codec_bitrate.load(Ordering::Relaxed),
+        *max_bitrate.lock(),
+        frame_size,
+    )
+}
+
+/// Kodiert aufgenommene Frames mit Opus und sendet sie an alle Teilnehmer
+///
+/// Läuft, bis der Audio Handler verworfen oder der Task abgebrochen wird.
+/// Vor dem Verbinden gibt es noch keine Tracks, die Frames werden dann
+/// trotzdem gelesen, damit nach dem Verbinden kein altes Audio ankommt.
+/// Ziel-Bitrate und Obergrenze werden bei jedem Durchlauf neu angewendet,
+/// Änderungen an einer der beiden gelten also ab dem nächsten Frame.
+async fn run_audio_sender(
+    audio_handler: Arc<Mutex<Option<AudioHandler>>>,
+    writer: RtpWriter,
+    codec_bitrate: Arc<AtomicU32>,
+    max_bitrate: Arc<Mutex<Option<u32>>>,
+) {
+    let target_bitrate =
+        |frame_size: usize| sender_bitrate(&codec_bitrate, &max_bitrate, frame_size);
+
+    let Some(frame_size) = audio_handler.lock().as_ref().map(AudioHandler::frame_size) else {
+        return;
+    };
+    let mut encoder = match OpusFrameEncoder::new(target_bitrate(frame_size)) {
+        Ok(encoder) => encoder,
+        Err(e) => {
+            tracing::error!("Failed to create Opus encoder, not sending audio: {}", e);
+            return;
+        }
+    };
+    let mut packetizer = RtpPacketizer::new();
+    let mut interval = tokio::time::interval(AUDIO_SEND_POLL_INTERVAL);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    loop {
+        interval.tick().await;
+
+        let Some(frame_size) = audio_handler.lock().as_ref().map(AudioHandler::frame_size) else {
+            return;
+        };
+        if let Err(e) = encoder.set_bitrate(target_bitrate(frame_size)) {
+            tracing::warn!("Failed to change Opus bitrate: {}", e);
+        }
+
+        loop {
+            let (frame, frame_size) = match audio_handler.lock().as_ref() {
+                Some(audio) => (audio.read_frame(), audio.frame_size()),
+                None => return,
+            };
+            let Some(frame) = frame else {
+                break;
+            };
+            // Leerer Frame: Stille, die nicht gesendet wird
+            if frame.is_empty() {
+                packetizer.skip(frame_size);
+                continue;
+            }
+
+            let payload = match encoder.encode(&frame) {
+                Ok(payload) => payload,
+                Err(e) => {
+                    tracing::warn!("Failed to encode audio frame: {}", e);
+                    continue;
+                }
+            };
+            let packet = packetizer.packetize(payload, frame.len());
+            match writer.write(packet).await {
+                Ok(()) | Err(CallEngineError::NoActiveCall) => {}
+                Err(e) => tracing::debug!("Failed to send audio frame: {}", e),
+            }
+        }
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use webrtc::api::media_engine::{MIME_TYPE_OPUS, MIME_TYPE_PCMU, MIME_TYPE_VP8};
+
+    #[test]
+    fn test_mute_is_remembered_before_audio_starts() {
+        let engine = CallEngine::new();
+        let mut rx = engine.subscribe();
+
+        engine.set_muted(true);
+        engine.set_muted(true);
+        assert!(engine.is_muted());
+        assert!(matches!(rx.try_recv(), Ok(CallEvent::MuteChanged(true))));
+        assert!(rx.try_recv().is_err());
+
+        engine.set_muted(false);
+        assert!(!engine.is_muted());
+        assert!(matches!(rx.try_recv(), Ok(CallEvent::MuteChanged(false))));
+    }
+
+    #[test]
+    fn test_push_to_talk_applies_to_running_audio() {
+        let engine = CallEngine::new();
+        engine.set_ptt_active(true);
+        engine.set_ptt_enabled(true);
+        // Umschalten lässt die Taste los
+        assert!(engine.ptt_enabled());
+        assert!(!engine.ptt_active());
+
+        *engine.audio_handler.lock() = Some(AudioHandler::new().unwrap());
+        engine.set_ptt_enabled(true);
+        engine.set_ptt_active(true);
+        {
+            let audio = engine.audio_handler.lock();
+            let audio = audio.as_ref().unwrap();
+            assert!(audio.is_ptt_enabled() && audio.is_ptt_active());
+        }
+
+        engine.set_ptt_enabled(false);
+        let audio = engine.audio_handler.lock();
+        assert!(!audio.as_ref().unwrap().is_ptt_enabled());
+    }
+
+    #[test]
+    fn test_init_audio_reuses_prewarmed_handler() {
+        let engine = CallEngine::new();
+        engine.warm_up_audio().unwrap();
+        // Markiert den vorbereiteten Handler, ein neuer hätte `FRAME_SIZE`
+        engine
+            .prewarmed_audio
+            .lock()
+            .as_mut()
+            .unwrap()
+            .set_frame_size(480)
+            .unwrap();
+
+        // Erneutes Vorbereiten behält den Handler samt Konfigurationen
+        engine.warm_up_audio().unwrap();
+        let audio = engine.take_audio_handler().unwrap();
+        assert_eq!(audio.frame_size(), 480);
+        assert!(engine.prewarmed_audio.lock().is_none());
+
+        // Nach einem Gerätewechsel wird neu ermittelt
+        engine.warm_up_audio().unwrap();
+        engine.invalidate_prewarmed_audio();
+        assert!(engine.prewarmed_audio.lock().is_none());
+        assert_eq!(
+            engine.take_audio_handler().unwrap().frame_size(),
+            FRAME_SIZE
+        );
+    }
+
+    #[test]
+    fn test_relay_policy_requires_turn_server() {
+        let engine = CallEngine::new();
+        assert_eq!(engine.ice_transport_policy(), IceTransportPolicy::All);
+        assert!(matches!(
+            engine.set_ice_transport_policy(IceTransportPolicy::Relay),
+            Err(CallEngineError::InvalidConfig(_))
+        ));
+        assert_eq!(engine.ice_transport_policy(), IceTransportPolicy::All);
+
+        engine
+            .add_turn_server(
+                "turn:turn.example.com:3478".to_string(),
+                "user".to_string(),
+                "secret".to_string(),
+            )
+            .unwrap();
+        engine
+            .set_ice_transport_policy(IceTransportPolicy::Relay)
+            .unwrap();
+        assert_eq!(engine.ice_transport_policy(), IceTransportPolicy::Relay);
+        assert_eq!(
+            RTCIceTransportPolicy::from(engine.ice_transport_policy()),
+            RTCIceTransportPolicy::Relay
+        );
+
+        // Ohne TURN-Server fällt die Policy zurück
+        engine.clear_turn_servers();
+        assert_eq!(engine.ice_transport_policy(), IceTransportPolicy::All);
+    }
+
+    #[test]
+    fn test_turn_servers_apply_to_new_connections() {
+        let engine = CallEngine::new();
+        assert!(engine
+            .add_turn_server(
+                "stun:stun.example.com".to_string(),
+                String::new(),
+                String::new()
+            )
+            .is_err());
+
+        engine
+            .add_turn_server(
+                "turns:turn.example.com:5349".to_string(),
+                "user".to_string(),
+                "secret".to_string(),
+            )
+            .unwrap();
+        let config = engine.rtc_configuration(engine.ice_servers());
+        let turn = config
+            .ice_servers
+            .iter()
+            .find(|server| server.urls == ["turns:turn.example.com:5349"])
+            .unwrap();
+        assert_eq!(
+            (turn.username.as_str(), turn.credential.as_str()),
+            ("user", "secret")
+        );
+        assert!(engine
+            .ice_server_urls()
+            .contains(&"turns:turn.example.com:5349".to_string()));
+
+        // STUN-Server bleiben beim Entfernen erhalten
+        engine.clear_turn_servers();
+        let config = engine.rtc_configuration(engine.ice_servers());
+        assert_eq!(config.ice_servers.len(), default_ice_servers().len());
+        assert!(!has_turn_server(&config.ice_servers));
+    }
+
+    #[test]
+    fn test_parse_dtls_fingerprint() {
+        let sdp = "v=0\r\n\
+                   o=- 123 2 IN IP4 127.0.0.1\r\n\
+                   a=fingerprint:SHA-256 ab:cd:EF:01\r\n\
+                   m=audio 9 UDP/TLS/RTP/SAVPF 111\r\n";
+
+        let fingerprint = parse_dtls_fingerprint(sdp).unwrap();
+        assert_eq!(fingerprint.algorithm, "sha-256");
+        assert_eq!(fingerprint.value, "AB:CD:EF:01");
+    }
+
+    const TEST_SDP: &str = "v=0\r\n\
+                            o=- 123 2 IN IP4 127.0.0.1\r\n\
+                            s=-\r\n\
+                            t=0 0\r\n\
+                            m=audio 9 UDP/TLS/RTP/SAVPF 111\r\n\
+                            a=fingerprint:sha-256 AB:CD:EF:01\r\n";
+
+    #[test]
+    fn test_signed_fingerprint_verifies_against_known_key() {
+        let keypair = KeyPair::generate();
+        let signed = sign_sdp_fingerprint(TEST_SDP, &keypair);
+
+        // Attribute stehen vor der ersten Media-Section
+        assert!(signed.find(SDP_IDENTITY_KEY_ATTR).unwrap() < signed.find("m=audio").unwrap());
+
+        let public_key = keypair.public_key_base64();
+        assert_eq!(
+            verify_sdp_fingerprint(&signed, Some(&public_key)).unwrap(),
+            Some(public_key.clone())
+        );
+        assert_eq!(
+            verify_sdp_fingerprint(&signed, None).unwrap(),
+            Some(public_key)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_verified_remote_identity_is_recorded_and_cleared() {
+        let keypair = KeyPair::generate();
+        let public_key = keypair.public_key_base64();
+        let signed = sign_sdp_fingerprint(TEST_SDP, &keypair);
+
+        let engine = CallEngine::new();
+        engine.set_directory_key("peer-a".to_string(), public_key.clone());
+        engine.verify_remote_sdp("peer-a", &signed).unwrap();
+        assert!(!engine.remote_identity().unwrap().pinned);
+
+        engine.expect_peer_key("peer-a".to_string(), public_key.clone());
+        engine.verify_remote_sdp("peer-a", &signed).unwrap();
+        assert_eq!(
+            engine.remote_identity(),
+            Some(RemoteIdentity {
+                peer_id: "peer-a".to_string(),
+                public_key,
+                pinned: true,
+            })
+        );
+
+        engine.end_call();
+        assert_eq!(engine.remote_identity(), None);
+        assert!(matches!(
+            engine.security_info().await,
+            Err(CallEngineError::NoActiveCall)
+        ));
+    }
+
+    #[test]
+    fn test_declared_key_needs_confirmation() {
+        let keypair = KeyPair::generate();
+        let public_key = keypair.public_key_base64();
+        let signed = sign_sdp_fingerprint(TEST_SDP, &keypair);
+        let engine = CallEngine::new();
+        let mut rx = engine.subscribe();
+
+        // Nichts bestätigt den Key: Anruf geht weiter, der Key wird nicht anerkannt
+        engine.verify_remote_sdp("peer-a", &signed).unwrap();
+        assert_eq!(engine.remote_identity(), None);
+        assert!(rx.try_recv().is_err());
+
+        // Das Verzeichnis nennt einen anderen Key
+        engine.set_directory_key(
+            "peer-a".to_string(),
+            KeyPair::generate().public_key_base64(),
+        );
+        assert!(matches!(
+            engine.verify_remote_sdp("peer-a", &signed),
+            Err(CallEngineError::IdentityVerification(_))
+        ));
+
+        // Die LAN Peer-ID enthält den Key
+        let lan_peer = crate::lan_discovery::lan_peer_id(&public_key);
+        engine.verify_remote_sdp(&lan_peer, &signed).unwrap();
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(CallEvent::PeerIdentityVerified { peer_id, .. }) if peer_id == lan_peer
+        ));
+    }
+
+    #[test]
+    fn test_tampered_fingerprint_is_rejected() {
+        let keypair = KeyPair::generate();
+        let signed = sign_sdp_fingerprint(TEST_SDP, &keypair);
+
+        // Signaling-Server tauscht den Fingerprint aus
+        let tampered = signed.replace("AB:CD:EF:01", "11:22:33:44");
+        assert!(matches!(
+            verify_sdp_fingerprint(&tampered, None),
+            Err(CallEngineError::IdentityVerification(_))
+        ));
+
+        // Signaling-Server signiert mit eigenem Key neu
+        let resigned = sign_sdp_fingerprint(TEST_SDP, &KeyPair::generate());
+        assert!(verify_sdp_fingerprint(&resigned, Some(&keypair.public_key_base64())).is_err());
+
+        // Signatur entfernt, obwohl ein Key erwartet wird
+        assert!(verify_sdp_fingerprint(TEST_SDP, Some(&keypair.public_key_base64())).is_err());
+        assert_eq!(verify_sdp_fingerprint(TEST_SDP, None).unwrap(), None);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_drop_closes_peer_connection() {
+        let engine = CallEngine::new();
+        let pc = engine
+            .create_peer_connection(Vec::new(), Some("peer"))
+            .await
+            .unwrap();
+        engine.set_peer_state(
+            "peer",
+            CallState::Connecting {
+                peer_id: "peer".to_string(),
+            },
+        );
+        engine.peers.lock().get_mut("peer").unwrap().pc = Some(Arc::clone(&pc));
+
+        drop(engine);
+
+        assert_eq!(pc.connection_state(), RTCPeerConnectionState::Closed);
+    }
+
+    #[test]
+    fn test_glare_keeps_exactly_one_offer() {
+        assert!(keeps_own_offer("peer-a", "peer-b"));
+        assert!(!keeps_own_offer("peer-b", "peer-a"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_glare_collapses_into_a_single_call() {
+        let alice = CallEngine::new();
+        let bob = CallEngine::new();
+
+        // Beide rufen sich gleichzeitig an (Audio kann in CI fehlschlagen)
+        let _ = alice
+            .start_call_with("peer-b".to_string(), Vec::new(), false)
+            .await;
+        let _ = bob
+            .start_call_with("peer-a".to_string(), Vec::new(), false)
+            .await;
+        let offer_a = alice.local_description().await.unwrap().sdp;
+        let offer_b = bob.local_description().await.unwrap().sdp;
+
+        // Die Offers kreuzen sich
+        let at_alice = alice
+            .handle_incoming_offer(
+                Some("peer-a"),
+                "peer-b".to_string(),
+                "bob".to_string(),
+                offer_b,
+            )
+            .await;
+        let at_bob = bob
+            .handle_incoming_offer(
+                Some("peer-b"),
+                "peer-a".to_string(),
+                "alice".to_string(),
+                offer_a,
+            )
+            .await;
+
+        assert_eq!(at_alice.unwrap(), IncomingCallResolution::KeepOwnOffer);
+        assert!(!matches!(at_bob, Err(CallEngineError::AlreadyInCall)));
+        assert_eq!(
+            alice.state(),
+            CallState::Calling {
+                peer_id: "peer-b".to_string()
+            }
+        );
+        assert_eq!(
+            bob.state(),
+            CallState::Connecting {
+                peer_id: "peer-a".to_string()
+            }
+        );
+        assert_eq!(bob.local_description().await.unwrap().sdp_type, "answer");
+
+        // Ohne eigenen Anruf klingelt es normal
+        let carol = CallEngine::new();
+        let resolution = carol
+            .handle_incoming_offer(
+                Some("peer-c"),
+                "peer-a".to_string(),
+                "alice".to_string(),
+                String::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resolution, IncomingCallResolution::Ring);
+        assert!(matches!(carol.state(), CallState::Ringing { .. }));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_call_again_immediately_after_hangup() {
+        let engine = CallEngine::new();
+        engine.set_state(CallState::Calling {
+            peer_id: "peer-a".to_string(),
+        });
+        engine.end_call();
+        assert_eq!(engine.state(), CallState::Ended);
+
+        // Audio kann in CI ohne Geräte fehlschlagen, entscheidend ist der State-Check
+        let result = engine
+            .start_call_with("peer-b".to_string(), Vec::new(), false)
+            .await;
+        assert!(!matches!(result, Err(CallEngineError::AlreadyInCall)));
+
+        // Der verzögerte Reset darf den neuen Anruf nicht überschreiben
+        tokio::time::sleep(ENDED_RESET_DELAY * 2).await;
+        assert_eq!(
+            engine.state(),
+            CallState::Calling {
+                peer_id: "peer-b".to_string()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delayed_reset_keeps_ended_state_of_next_call() {
+        let engine = CallEngine::new();
+        engine.set_state(CallState::Calling {
+            peer_id: "peer-a".to_string(),
+        });
+        engine.end_call();
+
+        // Zweiter Anruf kurz vor Ablauf des ersten Resets, ebenfalls beendet
+        tokio::time::sleep(ENDED_RESET_DELAY * 4 / 5).await;
+        engine.set_state(CallState::Calling {
+            peer_id: "peer-b".to_string(),
+        });
+        engine.end_call();
+
+        // Der Reset des ersten Anrufs kürzt das Ended des zweiten nicht ab
+        tokio::time::sleep(ENDED_RESET_DELAY * 2 / 5).await;
+        assert_eq!(engine.state(), CallState::Ended);
+
+        tokio::time::sleep(ENDED_RESET_DELAY).await;
+        assert_eq!(engine.state(), CallState::Idle);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_start_call_reuses_prewarmed_connection() {
+        let engine = CallEngine::new();
+        engine.prewarm().await.unwrap();
+        let prewarmed = Arc::clone(&engine.prewarmed_connection.lock().as_ref().unwrap().pc);
+
+        // Erneutes Vorwärmen behält die frische Verbindung
+        engine.prewarm().await.unwrap();
+        assert!(Arc::ptr_eq(
+            &prewarmed,
+            &engine.prewarmed_connection.lock().as_ref().unwrap().pc
+        ));
+
+        // Audio kann in CI ohne Geräte fehlschlagen, die Verbindung ist dann schon gesetzt
+        let _ = engine.start_call("peer".to_string()).await;
+        assert!(engine.prewarmed_connection.lock().is_none());
+        let active = engine.peers.lock()["peer"].pc.clone().unwrap();
+        assert!(Arc::ptr_eq(&prewarmed, &active));
+
+        // Während eines Anrufs wird nicht vorgewärmt
+        assert!(matches!(
+            engine.prewarm().await,
+            Err(CallEngineError::AlreadyInCall)
+        ));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_answer_waits_for_connection_of_starting_call() {
+        let engine = Arc::new(CallEngine::new());
+        engine.set_peer_state(
+            "peer",
+            CallState::Calling {
+                peer_id: "peer".to_string(),
+            },
+        );
+
+        // Das Offer ist erstellt, aber noch nicht beim Teilnehmer hinterlegt
+        let (pc, audio_track, offer_sdp) = engine
+            .create_offer_connection(Vec::new(), true, Some("peer"))
+            .await
+            .unwrap();
+
+        let callee_engine = CallEngine::new();
+        let callee = callee_engine
+            .create_peer_connection(Vec::new(), None)
+            .await
+            .unwrap();
+        callee
+            .set_remote_description(RTCSessionDescription::offer(offer_sdp).unwrap())
+            .await
+            .unwrap();
+        let answer = callee.create_answer(None).await.unwrap();
+        callee.set_local_description(answer.clone()).await.unwrap();
+
+        // Das Answer trifft sofort ein
+        let pending = tokio::spawn({
+            let engine = Arc::clone(&engine);
+            async move { engine.handle_answer("peer", answer.sdp).await }
+        });
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        assert!(!pending.is_finished());
+
+        engine
+            .attach_connection("peer", Arc::clone(&pc), audio_track)
+            .unwrap();
+        pending.await.unwrap().unwrap();
+        assert!(pc.remote_description().await.is_some());
+
+        // Ohne laufenden Anruf wird nicht gewartet
+        assert!(matches!(
+            engine.handle_answer("other", String::new()).await,
+            Err(CallEngineError::NoActiveCall)
+        ));
+        engine.end_call();
+        let _ = callee.close().await;
+    }
+
+    #[test]
+    fn test_call_state_info_keeps_peer_details() {
+        let ringing = CallStateInfo::from(&CallState::Ringing {
+            peer_id: "peer".to_string(),
+            username: "alice".to_string(),
+        });
+        assert_eq!(
+            serde_json::to_value(&ringing).unwrap(),
+            serde_json::json!({"kind": "ringing", "peer_id": "peer", "username": "alice"})
+        );
+
+        let connected = CallStateInfo::from(&CallState::Connected {
+            peer_id: "peer".to_string(),
+        });
+        assert_eq!(connected.kind, CallStateKind::Connected);
+        assert_eq!(connected.peer_id.as_deref(), Some("peer"));
+        assert_eq!(connected.username, None);
+
+        let idle = CallStateInfo::from(&CallState::Idle);
+        assert_eq!(idle.kind, CallStateKind::Idle);
+        assert_eq!(idle.peer_id, None);
+    }
+
+    #[test]
+    fn test_aggregate_state_follows_most_advanced_peer() {
+        let calling = |peer_id: &str| CallState::Calling {
+            peer_id: peer_id.to_string(),
+        };
+        let connected = |peer_id: &str| CallState::Connected {
+            peer_id: peer_id.to_string(),
+        };
+
+        assert_eq!(aggregate_call_state([], &CallState::Idle), CallState::Ended);
+        assert_eq!(
+            aggregate_call_state(&[calling("peer-a"), connected("peer-b")], &CallState::Idle),
+            connected("peer-b")
+        );
+
+        // Bei Gleichstand bleibt die bisherige Gegenstelle, sonst die kleinere Peer-ID
+        let both = [connected("peer-a"), connected("peer-b")];
+        assert_eq!(
+            aggregate_call_state(&both, &connected("peer-b")),
+            connected("peer-b")
+        );
+        assert_eq!(
+            aggregate_call_state(&both, &CallState::Idle),
+            connected("peer-a")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dropped_connection_reconnects_via_ice_restart() {
+        let engine = CallEngine::new();
+        let mut rx = engine.subscribe();
+        let connected = CallState::Connected {
+            peer_id: "peer-a".to_string(),
+        };
+        let reconnecting = CallState::Reconnecting {
+            peer_id: "peer-a".to_string(),
+        };
+        engine.set_peer_state("peer-a", connected.clone());
+
+        // Ohne Unterbrechung gibt es nichts neu auszuhandeln
+        assert!(matches!(
+            engine.restart_ice("peer-a").await,
+            Err(CallEngineError::NoActiveCall)
+        ));
+
+        // Disconnected im Anruf beendet ihn nicht, sondern startet ICE neu
+        let state = next_peer_state(RTCPeerConnectionState::Disconnected, "peer-a", &connected);
+        assert_eq!(state, Some(Some(reconnecting.clone())));
+        engine.set_peer_state("peer-a", reconnecting.clone());
+        assert_eq!(engine.state(), reconnecting);
+        assert_eq!(
+            CallStateInfo::from(&reconnecting).kind,
+            CallStateKind::Reconnecting
+        );
+        assert_eq!(
+            next_peer_state(
+                RTCPeerConnectionState::Disconnected,
+                "peer-a",
+                &reconnecting
+            ),
+            None
+        );
+
+        // Gelingt der Restart, ist der Anruf wieder verbunden
+        let state = next_peer_state(RTCPeerConnectionState::Connected, "peer-a", &reconnecting);
+        assert_eq!(state, Some(Some(connected.clone())));
+        engine.set_peer_state("peer-a", connected.clone());
+        assert_eq!(engine.state(), connected);
+
+        let mut states = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            if let CallEvent::StateChanged(state) = event {
+                states.push(state);
+            }
+        }
+        assert_eq!(
+            states,
+            vec![connected.clone(), reconnecting.clone(), connected]
+        );
+
+        // Failed beendet den Teilnehmer, im Aufbau auch schon Disconnected
+        for current in [
+            &reconnecting,
+            &CallState::Connecting {
+                peer_id: "peer-a".to_string(),
+            },
+        ] {
+            assert_eq!(
+                next_peer_state(RTCPeerConnectionState::Failed, "peer-a", current),
+                Some(None)
+            );
+        }
+        assert_eq!(
+            next_peer_state(
+                RTCPeerConnectionState::Disconnected,
+                "peer-a",
+                &CallState::Calling {
+                    peer_id: "peer-a".to_string()
+                }
+            ),
+            Some(None)
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_ice_restart_with_changed_fingerprint_is_rejected() {
+        let engine = CallEngine::new();
+        engine.set_peer_state(
+            "peer-a",
+            CallState::Connected {
+                peer_id: "peer-a".to_string(),
+            },
+        );
+        let pc = engine
+            .create_peer_connection(Vec::new(), Some("peer-a"))
+            .await
+            .unwrap();
+        engine.peers.lock().get_mut("peer-a").unwrap().pc = Some(Arc::clone(&pc));
+
+        // Verbindung zum ursprünglichen Offer des Teilnehmers
+        let caller_engine = CallEngine::new();
+        let (caller, _, offer_sdp) = caller_engine
+            .create_offer_connection(Vec::new(), false, None)
+            .await
+            .unwrap();
+        pc.set_remote_description(RTCSessionDescription::offer(offer_sdp.clone()).unwrap())
+            .await
+            .unwrap();
+        let answer = pc.create_answer(None).await.unwrap();
+        pc.set_local_description(answer).await.unwrap();
+
+        // Ein weitergeleitetes Restart-Offer tauscht den Fingerprint aus
+        let fingerprint = parse_dtls_fingerprint(&offer_sdp).unwrap();
+        let tampered = offer_sdp.replace(&fingerprint.value, "11:22:33:44");
+        let result = engine.accept_ice_restart("peer-a", tampered).await;
+        assert!(matches!(
+            result,
+            Some(Err(CallEngineError::IdentityVerification(_)))
+        ));
+        let remote = pc.remote_description().await.unwrap();
+        assert_eq!(parse_dtls_fingerprint(&remote.sdp), Some(fingerprint));
+
+        engine.end_call();
+        let _ = caller.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_conference_keeps_running_when_one_peer_leaves() {
+        let engine = CallEngine::new();
+        let connected = |peer_id: &str| CallState::Connected {
+            peer_id: peer_id.to_string(),
+        };
+
+        // Ohne verbundenen Anruf kann niemand hinzugefügt werden
+        assert!(matches!(
+            engine.add_peer("peer-b".to_string()).await,
+            Err(CallEngineError::NoActiveCall)
+        ));
+
+        for peer_id in ["peer-a", "peer-b"] {
+            engine.set_peer_state(peer_id, connected(peer_id));
+            let pc = engine
+                .create_peer_connection(Vec::new(), Some(peer_id))
+                .await
+                .unwrap();
+            engine.peers.lock().get_mut(peer_id).unwrap().pc = Some(pc);
+        }
+        assert_eq!(engine.state(), connected("peer-a"));
+        assert_eq!(
+            engine.peer_states(),
+            vec![connected("peer-a"), connected("peer-b")]
+        );
+
+        // Konferenz ist voll
+        assert!(matches!(
+            engine.add_peer("peer-c".to_string()).await,
+            Err(CallEngineError::TooManyPeers(MAX_CALL_PEERS))
+        ));
+
+        // Ein weiterer Anrufer klingelt, ohne den Gesamt-State zu ändern
+        engine.register_incoming_call("peer-c".to_string(), "carol".to_string());
+        assert_eq!(engine.state(), connected("peer-a"));
+        engine.reject_call("peer-c");
 
-        // Peer Connection speichern
-        *self.peer_connection.lock() = Some(pc);
+        engine.end_peer_call("peer-a");
+        assert_eq!(engine.state(), connected("peer-b"));
+        assert_eq!(engine.peer_states(), vec![connected("peer-b")]);
 
-        // Audio initialisieren
-        self.init_audio()?;
+        engine.end_peer_call("peer-b");
+        assert_eq!(engine.state(), CallState::Ended);
+        assert!(engine.peer_states().is_empty());
+    }
 
-        Ok(answer.sdp)
+    #[tokio::test]
+    async fn test_hangup_from_non_participant_is_ignored() {
+        let engine = CallEngine::new();
+        let connected = CallState::Connected {
+            peer_id: "peer-a".to_string(),
+        };
+        engine.set_peer_state("peer-a", connected.clone());
+        let pc = engine
+            .create_peer_connection(Vec::new(), Some("peer-a"))
+            .await
+            .unwrap();
+        engine.peers.lock().get_mut("peer-a").unwrap().pc = Some(pc);
+
+        engine.end_peer_call("peer-x");
+        assert_eq!(engine.state(), connected);
+        assert_eq!(engine.peer_states(), vec![connected.clone()]);
+
+        engine.end_peer_call("peer-a");
+        assert_eq!(engine.state(), CallState::Ended);
     }
 
-    /// Verarbeitet das SDP Answer vom Angerufenen
-    pub async fn handle_answer(&self, answer_sdp: String) -> Result<(), CallEngineError> {
-        let pc = self
-            .peer_connection
-            .lock()
-            .clone()
-            .ok_or(CallEngineError::NoActiveCall)?;
+    #[test]
+    fn test_dismiss_incoming_call_returns_to_idle() {
+        let engine = CallEngine::new();
+        let mut rx = engine.subscribe();
 
-        let answer = RTCSessionDescription::answer(answer_sdp)
-            .map_err(|e| CallEngineError::InvalidSdp(e.to_string()))?;
+        // Nur klingelnde Anrufe lassen sich verwerfen
+        assert!(matches!(
+            engine.dismiss_incoming_call("peer-a"),
+            Err(CallEngineError::NoActiveCall)
+        ));
 
-        pc.set_remote_description(answer)
-            .await
-            .map_err(|e| CallEngineError::WebRTC(e.to_string()))?;
+        engine.register_incoming_call("peer-a".to_string(), "alice".to_string());
+        engine.register_incoming_call("peer-b".to_string(), "bob".to_string());
+        engine.dismiss_incoming_call("peer-b").unwrap();
+        assert!(matches!(engine.state(), CallState::Ringing { .. }));
+        assert_eq!(engine.peer_states().len(), 1);
 
-        Ok(())
+        engine.dismiss_incoming_call("peer-a").unwrap();
+        assert_eq!(engine.state(), CallState::Idle);
+        assert!(engine.peer_states().is_empty());
+
+        // Kein `Ended` dazwischen
+        let mut states = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            if let CallEvent::StateChanged(state) = event {
+                states.push(state);
+            }
+        }
+        assert_eq!(states.last(), Some(&CallState::Idle));
+        assert!(!states.contains(&CallState::Ended));
     }
 
-    /// Fügt einen ICE Candidate hinzu
-    pub async fn add_ice_candidate(&self, candidate_json: String) -> Result<(), CallEngineError> {
-        let pc = self
-            .peer_connection
-            .lock()
-            .clone()
-            .ok_or(CallEngineError::NoActiveCall)?;
+    #[tokio::test]
+    async fn test_ringtone_plays_while_ringing() {
+        let engine = CallEngine::new();
+        engine.register_incoming_call("peer-a".to_string(), "alice".to_string());
+        assert!(engine.is_ringtone_playing());
 
-        let candidate: RTCIceCandidateInit = serde_json::from_str(&candidate_json)
-            .map_err(|e| CallEngineError::WebRTC(e.to_string()))?;
+        // Klingelt noch jemand, läuft der Klingelton weiter
+        engine.register_incoming_call("peer-b".to_string(), "bob".to_string());
+        engine.dismiss_incoming_call("peer-b").unwrap();
+        assert!(engine.is_ringtone_playing());
 
-        pc.add_ice_candidate(candidate)
-            .await
-            .map_err(|e| CallEngineError::WebRTC(e.to_string()))?;
+        // Mit dem Annehmen verstummt er
+        engine.set_peer_state(
+            "peer-a",
+            CallState::Connecting {
+                peer_id: "peer-a".to_string(),
+            },
+        );
+        assert!(!engine.is_ringtone_playing());
+        engine.force_reset();
 
-        Ok(())
-    }
+        engine.register_incoming_call("peer-c".to_string(), "carol".to_string());
+        assert!(engine.is_ringtone_playing());
+        engine.dismiss_incoming_call("peer-c").unwrap();
+        assert!(!engine.is_ringtone_playing());
 
-    /// Lehnt einen eingehenden Anruf ab
-    pub fn reject_call(&self) {
-        self.end_call();
+        // Deaktiviert bleibt es still
+        engine.set_ringtone_enabled(false);
+        engine.register_incoming_call("peer-d".to_string(), "dave".to_string());
+        assert!(!engine.is_ringtone_playing());
     }
 
-    /// Beendet den aktuellen Anruf
-    pub fn end_call(&self) {
-        // Audio stoppen
-        if let Some(mut audio) = self.audio_handler.lock().take() {
-            audio.stop();
-        }
+    #[tokio::test]
+    async fn test_accept_after_caller_cancelled_is_rejected() {
+        let engine = CallEngine::new();
 
-        // Peer Connection schließen
-        if let Some(pc) = self.peer_connection.lock().take() {
-            tokio::spawn(async move {
-                let _ = pc.close().await;
-            });
-        }
+        // Nie geklingelt: kein Anruf, der angenommen werden könnte
+        let result = engine
+            .accept_call("peer-a".to_string(), "v=0".to_string())
+            .await;
+        assert!(matches!(result, Err(CallEngineError::CallNoLongerActive)));
 
-        // State aktualisieren
-        self.set_state(CallState::Ended);
+        // Klingeln, dann legt der Anrufer auf, bevor angenommen wird
+        engine.register_incoming_call("peer-a".to_string(), "alice".to_string());
+        engine.end_peer_call("peer-a");
+        assert_eq!(engine.state(), CallState::Ended);
 
-        // Kurz warten und dann auf Idle setzen
-        let state = Arc::clone(&self.state);
-        let event_tx = self.event_tx.clone();
-        tokio::spawn(async move {
-            tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-            *state.lock() = CallState::Idle;
-            let _ = event_tx.send(CallEvent::StateChanged(CallState::Idle));
-        });
+        let result = engine
+            .accept_call("peer-a".to_string(), "v=0".to_string())
+            .await;
+        assert!(matches!(result, Err(CallEngineError::CallNoLongerActive)));
+        assert!(engine.peer_states().is_empty());
     }
 
-    /// Setzt Mute-Status
-    pub fn set_muted(&self, muted: bool) {
-        if let Some(audio) = self.audio_handler.lock().as_ref() {
-            audio.set_muted(muted);
+    #[tokio::test]
+    async fn test_failed_setup_is_retried_once_with_fresh_offer() {
+        let alice = CallEngine::new();
+        let bob = CallEngine::new();
+        bob.set_setup_retry_enabled(true);
+        let mut alice_rx = alice.subscribe();
+
+        // Bob hat Alices Anruf angenommen (Audio kann in CI fehlschlagen)
+        let _ = alice
+            .start_call_with("peer-b".to_string(), Vec::new(), false)
+            .await;
+        let offer = alice.local_description().await.unwrap().sdp;
+        bob.register_incoming_call("peer-a".to_string(), "alice".to_string());
+        let _ = bob
+            .accept_call_with("peer-a".to_string(), offer.clone(), Vec::new(), false, true)
+            .await;
+        bob.allow_setup_retry("peer-a");
+
+        // Ohne gescheiterten Aufbau gibt es nichts zu wiederholen
+        assert!(matches!(
+            alice.retry_call("peer-b").await,
+            Err(CallEngineError::NoActiveCall)
+        ));
+
+        // ICE scheitert bei Alice, sie bleibt `Calling` und erstellt ein neues Offer
+        CallEngine::begin_setup_retry(
+            &alice.peers,
+            &alice.state,
+            &alice.audio_handler,
+            &alice.pending_candidates,
+            &alice.event_tx,
+            "peer-b",
+            true,
+        );
+        let mut retrying = false;
+        while let Ok(event) = alice_rx.try_recv() {
+            retrying |= matches!(event, CallEvent::Retrying { outgoing: true, .. });
         }
-    }
+        assert!(retrying);
+        let retry_offer = alice.retry_call("peer-b").await.unwrap();
+        assert_ne!(retry_offer, offer);
+        assert_eq!(
+            alice.state(),
+            CallState::Calling {
+                peer_id: "peer-b".to_string()
+            }
+        );
 
-    /// Gibt Mute-Status zurück
-    pub fn is_muted(&self) -> bool {
-        self.audio_handler
-            .lock()
-            .as_ref()
-            .map(|a| a.is_muted())
-            .unwrap_or(false)
+        // Bob nimmt das neue Offer ohne Klingeln an, aber nur einmal
+        let resolution = bob
+            .handle_incoming_offer(
+                Some("peer-b"),
+                "peer-a".to_string(),
+                "alice".to_string(),
+                retry_offer,
+            )
+            .await
+            .unwrap();
+        assert!(matches!(
+            resolution,
+            IncomingCallResolution::AcceptedRetry { .. }
+        ));
+        assert_eq!(
+            bob.state(),
+            CallState::Connecting {
+                peer_id: "peer-a".to_string()
+            }
+        );
+        assert!(!bob.prepare_setup_retry("peer-a"));
     }
 
-    /// Gibt Audio-Levels zurück (input, output)
-    pub fn audio_levels(&self) -> (f32, f32) {
-        self.audio_handler
+    #[tokio::test]
+    async fn test_force_reset_returns_to_idle_from_any_state() {
+        let engine = CallEngine::new();
+        let mut rx = engine.subscribe();
+
+        // Hängengebliebener Aufbau mit gepuffertem Candidate
+        engine.set_peer_state(
+            "peer-a",
+            CallState::Connecting {
+                peer_id: "peer-a".to_string(),
+            },
+        );
+        engine
+            .pending_candidates
             .lock()
-            .as_ref()
-            .map(|a| a.get_levels())
-            .unwrap_or((0.0, 0.0))
+            .insert("peer-a".to_string(), vec![RTCIceCandidateInit::default()]);
+
+        engine.force_reset();
+        assert_eq!(engine.state(), CallState::Idle);
+        assert!(engine.peer_states().is_empty());
+        assert!(engine.pending_candidates.lock().is_empty());
+
+        // Erneut aufrufen ist unschädlich und meldet wieder Idle
+        engine.force_reset();
+        assert_eq!(engine.state(), CallState::Idle);
+
+        let mut peer_ended = false;
+        let mut states = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            match event {
+                CallEvent::PeerStateChanged { peer_id, state } => {
+                    peer_ended |= peer_id == "peer-a" && state == CallState::Ended;
+                }
+                CallEvent::StateChanged(state) => states.push(state),
+                _ => {}
+            }
+        }
+        assert!(peer_ended);
+        assert_eq!(states.last(), Some(&CallState::Idle));
+        assert!(!states.contains(&CallState::Ended));
     }
 
-    /// Registriert einen eingehenden Anruf
-    pub fn register_incoming_call(&self, peer_id: String, username: String) {
-        self.set_state(CallState::Ringing { peer_id, username });
+    #[tokio::test]
+    async fn test_supported_codecs_prefers_opus() {
+        let engine = CallEngine::new();
+        let codecs = engine.supported_codecs().await.unwrap();
+
+        assert_eq!(codecs[0].mime_type, MIME_TYPE_OPUS);
+        assert_eq!(codecs[0].clock_rate, SAMPLE_RATE);
+        assert_eq!(codecs[0].payload_type, OPUS_PAYLOAD_TYPE);
+        // Alle Defaults der MediaEngine, auch die Video-Codecs
+        assert!(codecs.iter().any(|c| c.mime_type == MIME_TYPE_PCMU));
+        assert!(codecs.iter().any(|c| c.mime_type == MIME_TYPE_VP8));
+        let first_video = codecs
+            .iter()
+            .position(|c| c.mime_type.starts_with("video/"))
+            .unwrap();
+        assert!(codecs[..first_video]
+            .iter()
+            .all(|c| c.mime_type.starts_with("audio/")));
     }
 
-    // ========================================================================
-    // PRIVATE METHODS
-    // ========================================================================
+    #[test]
+    fn test_codec_bitrate_is_clamped_to_max_bitrate() {
+        let engine = CallEngine::new();
+        let target = || sender_bitrate(&engine.codec_bitrate, &engine.max_bitrate, FRAME_SIZE);
+        let mut encoder = OpusFrameEncoder::new(target()).unwrap();
+        assert_eq!(encoder.bitrate(), DEFAULT_OPUS_BITRATE);
 
-    /// Erstellt eine neue Peer Connection
-    async fn create_peer_connection(&self) -> Result<Arc<RTCPeerConnection>, CallEngineError> {
-        // Media Engine mit Opus konfigurieren
-        let mut media_engine = MediaEngine::default();
-        media_engine
-            .register_default_codecs()
-            .map_err(|e| CallEngineError::WebRTC(e.to_string()))?;
+        // 40 kbps abzüglich 16 kbps Header bei 20ms-Frames
+        engine.set_codec_bitrate(64_000).unwrap();
+        engine.set_max_bitrate(Some(40_000)).unwrap();
+        encoder.set_bitrate(target()).unwrap();
+        assert_eq!(encoder.bitrate(), 24_000);
+        // Die gewünschte Bitrate bleibt erhalten
+        assert_eq!(engine.codec_bitrate(), 64_000);
 
-        // Interceptors für RTCP, NACK etc.
-        let mut registry = Registry::new();
-        registry = register_default_interceptors(registry, &mut media_engine)
-            .map_err(|e| CallEngineError::WebRTC(e.to_string()))?;
+        // Eine niedrigere Ziel-Bitrate gilt weiter unter der Grenze
+        engine.set_codec_bitrate(20_000).unwrap();
+        encoder.set_bitrate(target()).unwrap();
+        assert_eq!(encoder.bitrate(), 20_000);
 
-        // Setting Engine für Netzwerk-Konfiguration
-        let mut setting_engine = SettingEngine::default();
+        // Ohne Grenze wieder die volle Ziel-Bitrate
+        engine.set_codec_bitrate(64_000).unwrap();
+        engine.set_max_bitrate(None).unwrap();
+        encoder.set_bitrate(target()).unwrap();
+        assert_eq!(encoder.bitrate(), 64_000);
+    }
 
-        // Interface-Filter: Exclude virtual adapters and problematic interfaces
-        setting_engine.set_interface_filter(Box::new(|interface_name: &str| {
-            let name_lower = interface_name.to_lowercase();
+    #[test]
+    fn test_call_duration_grows_while_connected() {
+        let engine = CallEngine::new();
+        engine.set_peer_state(
+            "peer-a",
+            CallState::Connecting {
+                peer_id: "peer-a".to_string(),
+            },
+        );
+        assert!(engine.call_duration().is_none());
 
-            // Exclude virtual and problematic interfaces
-            let excluded = [
-                "hyper-v",
-                "vmware",
-                "virtualbox",
-                "docker",
-                "vethernet",
-                "bluetooth",
-                "loopback",
-                "teredo",
-                "isatap",
-                "6to4",
-            ];
+        engine.set_peer_state(
+            "peer-a",
+            CallState::Connected {
+                peer_id: "peer-a".to_string(),
+            },
+        );
+        let first = engine.call_duration().unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let second = engine.call_duration().unwrap();
+        assert!(second >= first + std::time::Duration::from_millis(20));
 
-            for pattern in excluded {
-                if name_lower.contains(pattern) {
-                    tracing::debug!("Excluding interface: {}", interface_name);
-                    return false;
-                }
-            }
+        engine.set_state(CallState::Ended);
+        assert!(engine.call_duration().is_none());
+        engine.set_state(CallState::Idle);
+        assert!(engine.call_duration().is_none());
+    }
 
-            true
-        }));
+    #[test]
+    fn test_audio_device_selection() {
+        let engine = CallEngine::new();
+        assert_eq!(engine.audio_devices(), AudioDeviceSelection::default());
 
-        // IP-Filter: Only use valid, routable IP addresses
-        setting_engine.set_ip_filter(Box::new(|ip: IpAddr| {
-            match ip {
-                IpAddr::V4(ipv4) => {
-                    // Exclude link-local (169.254.x.x) and loopback
-                    if ipv4.is_link_local() || ipv4.is_loopback() {
-                        return false;
-                    }
-                    // Exclude APIPA addresses
-                    let octets = ipv4.octets();
-                    if octets[0] == 169 && octets[1] == 254 {
-                        return false;
-                    }
-                    true
-                }
-                IpAddr::V6(ipv6) => {
-                    // Exclude link-local (fe80::) and loopback IPv6
-                    if ipv6.is_loopback() {
-                        return false;
-                    }
-                    // Exclude link-local IPv6 (starts with fe80)
-                    let segments = ipv6.segments();
-                    if segments[0] == 0xfe80 {
-                        return false;
-                    }
-                    true
-                }
-            }
-        }));
+        engine.set_input_device(Some("USB Headset".to_string()));
+        engine.set_output_device(Some("USB Headset".to_string()));
+        engine.set_input_device(None);
 
-        // API erstellen mit SettingEngine
-        let api = APIBuilder::new()
-            .with_media_engine(media_engine)
-            .with_interceptor_registry(registry)
-            .with_setting_engine(setting_engine)
-            .build();
+        assert_eq!(
+            engine.audio_devices(),
+            AudioDeviceSelection {
+                input: None,
+                output: Some("USB Headset".to_string()),
+            }
+        );
+    }
 
-        // RTCConfiguration mit ICE Servern
-        let config = RTCConfiguration {
-            ice_servers: self.ice_servers.clone(),
-            ..Default::default()
+    #[test]
+    fn test_packetizer_advances_sequence_and_timestamp() {
+        let mut packetizer = RtpPacketizer {
+            sequence_number: u16::MAX,
+            timestamp: u32::MAX - 100,
+            talkspurt: false,
         };
 
-        // Peer Connection erstellen
-        let pc = Arc::new(
-            api.new_peer_connection(config)
-                .await
-                .map_err(|e| CallEngineError::WebRTC(e.to_string()))?,
+        let first = packetizer.packetize(vec![1, 2, 3], FRAME_SIZE);
+        let second = packetizer.packetize(vec![4], FRAME_SIZE);
+
+        assert_eq!(first.header.payload_type, OPUS_PAYLOAD_TYPE);
+        assert_eq!(first.payload.as_ref(), &[1, 2, 3]);
+        assert_eq!(second.header.sequence_number, 0);
+        assert_eq!(
+            second.header.timestamp,
+            first.header.timestamp.wrapping_add(FRAME_SIZE as u32)
         );
+    }
 
-        // Event Handler registrieren
-        self.setup_peer_connection_handlers(Arc::clone(&pc)).await;
+    #[test]
+    fn test_packetizer_skips_suppressed_silence() {
+        let mut packetizer = RtpPacketizer::new();
+        let first = packetizer.packetize(vec![1], FRAME_SIZE);
+        let second = packetizer.packetize(vec![2], FRAME_SIZE);
+        assert!(first.header.marker);
+        assert!(!second.header.marker);
 
-        Ok(pc)
+        // Zwei Frames Stille: Timestamp springt, Sequenznummer nicht
+        packetizer.skip(FRAME_SIZE);
+        packetizer.skip(FRAME_SIZE);
+        let third = packetizer.packetize(vec![3], FRAME_SIZE);
+        assert!(third.header.marker);
+        assert_eq!(
+            third.header.sequence_number,
+            second.header.sequence_number.wrapping_add(1)
+        );
+        assert_eq!(
+            third.header.timestamp,
+            second.header.timestamp.wrapping_add(3 * FRAME_SIZE as u32)
+        );
     }
 
-    /// Registriert Event Handler für die Peer Connection
-    async fn setup_peer_connection_handlers(&self, pc: Arc<RTCPeerConnection>) {
-        let state = Arc::clone(&self.state);
-        let event_tx = self.event_tx.clone();
+    const HOST_CANDIDATE: &str = r#"{"candidate":"candidate:1 1 udp 2130706431 192.168.1.2 54321 typ host","sdpMid":"0","sdpMLineIndex":0}"#;
 
-        // Connection State Handler
-        let state_clone = Arc::clone(&state);
-        let event_tx_clone = event_tx.clone();
-        pc.on_peer_connection_state_change(Box::new(move |s: RTCPeerConnectionState| {
-            tracing::info!("Peer connection state: {:?}", s);
+    #[tokio::test]
+    async fn test_candidate_before_remote_description_is_buffered() {
+        let engine = CallEngine::new();
 
-            let new_state = match s {
-                RTCPeerConnectionState::Connected => {
-                    let current = state_clone.lock();
-                    if let CallState::Connecting { ref peer_id }
-                    | CallState::Calling { ref peer_id } = *current
-                    {
-                        Some(CallState::Connected {
-                            peer_id: peer_id.clone(),
-                        })
-                    } else {
-                        None
-                    }
-                }
-                RTCPeerConnectionState::Disconnected
-                | RTCPeerConnectionState::Failed
-                | RTCPeerConnectionState::Closed => Some(CallState::Ended),
-                _ => None,
-            };
+        // Candidate trifft vor dem Offer/Answer ein
+        engine
+            .add_ice_candidate("peer-a", HOST_CANDIDATE.to_string())
+            .await
+            .unwrap();
+        assert_eq!(engine.pending_candidates.lock()["peer-a"].len(), 1);
 
-            if let Some(new_state) = new_state {
-                *state_clone.lock() = new_state.clone();
-                let _ = event_tx_clone.send(CallEvent::StateChanged(new_state));
+        engine.end_call();
+        assert!(engine.pending_candidates.lock().is_empty());
+    }
+
+    /// Sammelt die gemeldeten ICE Candidates eines Engines
+    fn drain_candidates(rx: &mut broadcast::Receiver<CallEvent>) -> Vec<String> {
+        let mut candidates = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            if let CallEvent::IceCandidate { candidate, .. } = event {
+                candidates.push(candidate);
             }
+        }
+        candidates
+    }
 
-            Box::pin(async {})
-        }));
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_call_connects_when_candidates_arrive_before_answer() {
+        let alice = CallEngine::new();
+        let mut alice_rx = alice.subscribe();
+        alice.set_peer_state(
+            "peer-b",
+            CallState::Calling {
+                peer_id: "peer-b".to_string(),
+            },
+        );
+        let (pc, audio_track, offer_sdp) = alice
+            .create_offer_connection(Vec::new(), false, Some("peer-b"))
+            .await
+            .unwrap();
+        alice
+            .attach_connection("peer-b", Arc::clone(&pc), audio_track)
+            .unwrap();
 
-        // ICE Candidate Handler
-        let event_tx_clone = event_tx.clone();
-        pc.on_ice_candidate(Box::new(move |candidate| {
-            if let Some(c) = candidate {
-                if let Ok(json) = c.to_json() {
-                    if let Ok(candidate_str) = serde_json::to_string(&json) {
-                        let _ = event_tx_clone.send(CallEvent::IceCandidate {
-                            candidate: candidate_str,
-                        });
-                    }
-                }
-            }
-            Box::pin(async {})
-        }));
+        // Bob antwortet per Trickle ICE: Das Answer enthält keine Candidates
+        let bob_engine = CallEngine::new();
+        let mut bob_rx = bob_engine.subscribe();
+        let bob = bob_engine
+            .create_peer_connection(Vec::new(), None)
+            .await
+            .unwrap();
+        bob.set_remote_description(RTCSessionDescription::offer(offer_sdp).unwrap())
+            .await
+            .unwrap();
+        let answer = bob.create_answer(None).await.unwrap();
+        let mut gathering_complete = bob.gathering_complete_promise().await;
+        bob.set_local_description(answer.clone()).await.unwrap();
+        let _ = gathering_complete.recv().await;
 
-        // Track Handler (für eingehendes Audio)
-        // TODO: Echtes Audio-Handling implementieren wenn Opus verfügbar ist
-        pc.on_track(Box::new(move |track, _, _| {
-            Box::pin(async move {
-                tracing::info!("Received track: {:?}", track.codec());
-                // Placeholder: Audio-Handling wird später implementiert
-                // wenn Opus Encoding/Decoding verfügbar ist
-            })
-        }));
-    }
+        // Bobs Candidates überholen das Answer
+        let bob_candidates = drain_candidates(&mut bob_rx);
+        assert!(!bob_candidates.is_empty());
+        for candidate in &bob_candidates {
+            alice
+                .add_ice_candidate("peer-b", candidate.clone())
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            alice.pending_candidates.lock()["peer-b"].len(),
+            bob_candidates.len()
+        );
 
-    /// Initialisiert Audio
-    fn init_audio(&self) -> Result<(), CallEngineError> {
-        // Audio Handler erstellen
-        let mut audio = AudioHandler::new()?;
-        audio.start_capture()?;
-        audio.start_playback()?;
-        *self.audio_handler.lock() = Some(audio);
+        alice.handle_answer("peer-b", answer.sdp).await.unwrap();
+        assert!(!alice.pending_candidates.lock().contains_key("peer-b"));
 
-        // TODO: Opus Encoder/Decoder hinzufügen wenn CMake verfügbar
+        // Alices Candidates gehen normal an Bob, bis die Verbindung steht
+        let connected = CallState::Connected {
+            peer_id: "peer-b".to_string(),
+        };
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(10);
+        while alice.state() != connected && tokio::time::Instant::now() < deadline {
+            for candidate in drain_candidates(&mut alice_rx) {
+                let _ = bob
+                    .add_ice_candidate(parse_ice_candidate(&candidate).unwrap())
+                    .await;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+        assert_eq!(alice.state(), connected);
 
-        Ok(())
+        alice.end_call();
+        let _ = bob.close().await;
     }
 
-    /// Aktualisiert den State und sendet Event
-    fn set_state(&self, new_state: CallState) {
-        *self.state.lock() = new_state.clone();
-        let _ = self.event_tx.send(CallEvent::StateChanged(new_state));
-    }
-}
+    #[tokio::test]
+    async fn test_malformed_candidate_is_rejected() {
+        let engine = CallEngine::new();
 
-impl Default for CallEngine {
-    fn default() -> Self {
-        Self::new()
+        assert!(matches!(
+            engine
+                .add_ice_candidate("peer-a", "not json".to_string())
+                .await,
+            Err(CallEngineError::InvalidCandidate(_))
+        ));
+        assert!(matches!(
+            engine
+                .add_ice_candidate("peer-a", r#"{"candidate":"bogus"}"#.to_string())
+                .await,
+            Err(CallEngineError::InvalidCandidate(_))
+        ));
+        assert!(engine.pending_candidates.lock().is_empty());
     }
-}
 
-impl std::fmt::Debug for CallEngine {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("CallEngine")
-            .field("state", &self.state())
-            .field("is_muted", &self.is_muted())
-            .finish()
+    #[test]
+    fn test_parse_dtls_fingerprint_missing() {
+        let sdp = "v=0\r\nm=audio 9 UDP/TLS/RTP/SAVPF 111\r\n";
+        assert!(parse_dtls_fingerprint(sdp).is_none());
     }
 }
@@ -0,0 +1,457 @@
+//! Erreichbarkeit der ICE Server über die Zeit
+//!
+//! Für selbst betriebene STUN/TURN-Server wird im Leerlauf regelmäßig geprüft,
+//! ob die konfigurierten Server antworten. Eine kurze Historie pro Server
+//! hilft bei sporadischen Verbindungsproblemen ("manchmal klappt es nicht").
+//!
+//! - UDP (`stun:`, `turn:`): STUN Binding Request, gemessen wird die
+//!   Round-Trip-Zeit bis zur passenden Antwort. TURN-Server beantworten
+//!   Binding Requests ohne Authentifizierung.
+//! - TCP/TLS (`?transport=tcp`, `turns:`): nur der Verbindungsaufbau wird
+//!   gemessen, ohne TLS-Handshake und ohne Allocation.
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::{Duration, Instant};
+use tokio::net::{lookup_host, TcpStream, UdpSocket};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Abstand zwischen zwei Prüfrunden
+pub const ICE_HEALTH_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Wartezeit nach dem Start bis zur ersten Prüfrunde
+pub const ICE_HEALTH_STARTUP_DELAY: Duration = Duration::from_secs(30);
+
+/// Anzahl gespeicherter Prüfungen pro Server
+pub const ICE_HEALTH_HISTORY_LEN: usize = 24;
+
+/// Maximale Wartezeit auf eine Antwort
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Standard-Port für `stun:` und `turn:` (RFC 8489)
+pub(super) const DEFAULT_PORT: u16 = 3478;
+
+/// Standard-Port für `turns:`
+const DEFAULT_TLS_PORT: u16 = 5349;
+
+/// Magic Cookie im STUN-Header
+const STUN_MAGIC_COOKIE: u32 = 0x2112_A442;
+
+/// Nachrichtentypen Binding Request und Binding Success Response
+const STUN_BINDING_REQUEST: u16 = 0x0001;
+const STUN_BINDING_SUCCESS: u16 = 0x0101;
+
+/// Attribut mit der öffentlichen Adresse in der Binding Response
+const STUN_ATTR_XOR_MAPPED_ADDRESS: u16 = 0x0020;
+
+// ============================================================================
+// SERVER URLS
+// ============================================================================
+
+/// Transport, über den ein ICE Server erreicht wird
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ProbeTransport {
+    Udp,
+    Tcp,
+    Tls,
+}
+
+/// Aus einer ICE Server URL ermitteltes Prüfziel
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct ProbeTarget {
+    pub(super) host: String,
+    pub(super) port: u16,
+    pub(super) transport: ProbeTransport,
+}
+
+impl ProbeTarget {
+    /// Liest `stun:`, `turn:` und `turns:` URLs (RFC 7064/7065)
+    pub(super) fn parse(url: &str) -> Result<Self, String> {
+        let (scheme, rest) = url
+            .split_once(':')
+            .ok_or_else(|| format!("invalid ICE server url: {}", url))?;
+        let (address, query) = match rest.split_once('?') {
+            Some((address, query)) => (address, Some(query)),
+            None => (rest, None),
+        };
+        let tcp = query.is_some_and(|query| query.eq_ignore_ascii_case("transport=tcp"));
+
+        let transport = match scheme {
+            "stun" | "turn" if tcp => ProbeTransport::Tcp,
+            "stun" | "turn" => ProbeTransport::Udp,
+            "stuns" | "turns" => ProbeTransport::Tls,
+            _ => return Err(format!("unsupported ICE server scheme: {}", scheme)),
+        };
+        let default_port = if transport == ProbeTransport::Tls {
+            DEFAULT_TLS_PORT
+        } else {
+            DEFAULT_PORT
+        };
+
+        // IPv6-Adressen stehen in eckigen Klammern
+        let (host, port) = if let Some(v6) = address.strip_prefix('[') {
+            let (host, port) = v6
+                .split_once(']')
+                .ok_or_else(|| format!("invalid ICE server url: {}", url))?;
+            (host, port.strip_prefix(':'))
+        } else {
+            match address.rsplit_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (address, None),
+            }
+        };
+        let port = match port {
+            Some(port) => port
+                .parse()
+                .map_err(|_| format!("invalid port in ICE server url: {}", url))?,
+            None => default_port,
+        };
+        if host.is_empty() {
+            return Err(format!("missing host in ICE server url: {}", url));
+        }
+
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            transport,
+        })
+    }
+}
+
+// ============================================================================
+// STUN
+// ============================================================================
+
+/// Baut einen STUN Binding Request ohne Attribute
+pub(super) fn stun_binding_request(transaction_id: &[u8; 12]) -> [u8; 20] {
+    let mut request = [0u8; 20];
+    request[0..2].copy_from_slice(&STUN_BINDING_REQUEST.to_be_bytes());
+    // Länge der Attribute: 0
+    request[4..8].copy_from_slice(&STUN_MAGIC_COOKIE.to_be_bytes());
+    request[8..20].copy_from_slice(transaction_id);
+    request
+}
+
+/// Prüft, ob eine Antwort die Binding Success Response zur Anfrage ist
+fn is_binding_success(response: &[u8], transaction_id: &[u8; 12]) -> bool {
+    response.len() >= 20
+        && response[0..2] == STUN_BINDING_SUCCESS.to_be_bytes()
+        && response[4..8] == STUN_MAGIC_COOKIE.to_be_bytes()
+        && &response[8..20] == transaction_id
+}
+
+/// Liest die öffentliche Adresse (XOR-MAPPED-ADDRESS) aus einer Binding Response
+pub(super) fn xor_mapped_address(response: &[u8], transaction_id: &[u8; 12]) -> Option<SocketAddr> {
+    if !is_binding_success(response, transaction_id) {
+        return None;
+    }
+
+    let mut attributes = &response[20..];
+    while attributes.len() >= 4 {
+        let kind = u16::from_be_bytes([attributes[0], attributes[1]]);
+        let len = u16::from_be_bytes([attributes[2], attributes[3]]) as usize;
+        let value = attributes.get(4..4 + len)?;
+
+        if kind == STUN_ATTR_XOR_MAPPED_ADDRESS && len >= 8 {
+            let cookie = STUN_MAGIC_COOKIE.to_be_bytes();
+            let port = u16::from_be_bytes([value[2], value[3]]) ^ (STUN_MAGIC_COOKIE >> 16) as u16;
+            let ip = match (value[1], len) {
+                (0x01, 8) => {
+                    let mut octets = [0u8; 4];
+                    for (i, octet) in octets.iter_mut().enumerate() {
+                        *octet = value[4 + i] ^ cookie[i];
+                    }
+                    IpAddr::V4(Ipv4Addr::from(octets))
+                }
+                (0x02, 20) => {
+                    // IPv6 wird mit Magic Cookie und Transaction ID maskiert
+                    let mut octets = [0u8; 16];
+                    for (i, octet) in octets.iter_mut().enumerate() {
+                        let mask = if i < 4 {
+                            cookie[i]
+                        } else {
+                            transaction_id[i - 4]
+                        };
+                        *octet = value[4 + i] ^ mask;
+                    }
+                    IpAddr::V6(Ipv6Addr::from(octets))
+                }
+                _ => return None,
+            };
+            return Some(SocketAddr::new(ip, port));
+        }
+
+        // Attribute sind auf 4 Bytes aufgefüllt
+        let padded = (len + 3) & !3;
+        attributes = attributes.get(4 + padded..)?;
+    }
+    None
+}
+
+// ============================================================================
+// PROBING
+// ============================================================================
+
+/// Ergebnis einer einzelnen Prüfung
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IceProbeResult {
+    /// Zeitpunkt der Prüfung (Unix-Millisekunden)
+    pub checked_at: i64,
+    pub reachable: bool,
+    /// Round-Trip-Zeit (UDP) bzw. Dauer des Verbindungsaufbaus (TCP/TLS)
+    pub rtt_ms: Option<u32>,
+    pub error: Option<String>,
+}
+
+/// Prüft, ob ein ICE Server erreichbar ist
+pub async fn probe_ice_server(url: &str) -> IceProbeResult {
+    let checked_at = chrono::Utc::now().timestamp_millis();
+
+    let result = match ProbeTarget::parse(url) {
+        Ok(target) => tokio::time::timeout(PROBE_TIMEOUT, probe_target(&target))
+            .await
+            .unwrap_or_else(|_| Err("timed out".to_string())),
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(rtt) => IceProbeResult {
+            checked_at,
+            reachable: true,
+            rtt_ms: Some(rtt.as_millis().min(u32::MAX as u128) as u32),
+            error: None,
+        },
+        Err(error) => IceProbeResult {
+            checked_at,
+            reachable: false,
+            rtt_ms: None,
+            error: Some(error),
+        },
+    }
+}
+
+async fn probe_target(target: &ProbeTarget) -> Result<Duration, String> {
+    let address = lookup_host((target.host.as_str(), target.port))
+        .await
+        .map_err(|e| format!("DNS lookup failed: {}", e))?
+        .next()
+        .ok_or_else(|| "DNS lookup returned no addresses".to_string())?;
+
+    match target.transport {
+        ProbeTransport::Tcp | ProbeTransport::Tls => {
+            let started = Instant::now();
+            TcpStream::connect(address)
+                .await
+                .map_err(|e| format!("connect failed: {}", e))?;
+            Ok(started.elapsed())
+        }
+        ProbeTransport::Udp => {
+            let bind = if address.is_ipv6() {
+                "[::]:0"
+            } else {
+                "0.0.0.0:0"
+            };
+            let socket = UdpSocket::bind(bind).await.map_err(|e| e.to_string())?;
+            socket.connect(address).await.map_err(|e| e.to_string())?;
+
+            let transaction_id: [u8; 12] = rand::random();
+            let started = Instant::now();
+            socket
+                .send(&stun_binding_request(&transaction_id))
+                .await
+                .map_err(|e| format!("send failed: {}", e))?;
+
+            let mut buf = [0u8; 1024];
+            loop {
+                let len = socket
+                    .recv(&mut buf)
+                    .await
+                    .map_err(|e| format!("receive failed: {}", e))?;
+                if is_binding_success(&buf[..len], &transaction_id) {
+                    return Ok(started.elapsed());
+                }
+            }
+        }
+    }
+}
+
+// ============================================================================
+// HISTORY
+// ============================================================================
+
+/// Zusammenfassung der letzten Prüfungen eines Servers
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IceServerHealth {
+    pub url: String,
+    /// Anteil erfolgreicher Prüfungen (0.0 - 1.0), `None` ohne Prüfung
+    pub success_rate: Option<f32>,
+    /// Mittlere Round-Trip-Zeit der erfolgreichen Prüfungen
+    pub avg_rtt_ms: Option<u32>,
+    /// Letzte Prüfungen, älteste zuerst
+    pub history: Vec<IceProbeResult>,
+}
+
+impl IceServerHealth {
+    fn summarize(url: &str, history: Vec<IceProbeResult>) -> Self {
+        let rtts: Vec<u32> = history.iter().filter_map(|probe| probe.rtt_ms).collect();
+        let success_rate = (!history.is_empty()).then(|| rtts.len() as f32 / history.len() as f32);
+        let avg_rtt_ms = (!rtts.is_empty())
+            .then(|| (rtts.iter().map(|rtt| *rtt as u64).sum::<u64>() / rtts.len() as u64) as u32);
+
+        Self {
+            url: url.to_string(),
+            success_rate,
+            avg_rtt_ms,
+            history,
+        }
+    }
+}
+
+/// Kurze Historie der Prüfergebnisse pro Server-URL
+#[derive(Debug, Default)]
+pub struct IceHealthMonitor {
+    history: Mutex<HashMap<String, VecDeque<IceProbeResult>>>,
+}
+
+impl IceHealthMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Speichert ein Prüfergebnis, ältere fallen nach `ICE_HEALTH_HISTORY_LEN` heraus
+    pub fn record(&self, url: &str, result: IceProbeResult) {
+        let mut history = self.history.lock();
+        let entries = history.entry(url.to_string()).or_default();
+        if entries.len() == ICE_HEALTH_HISTORY_LEN {
+            entries.pop_front();
+        }
+        entries.push_back(result);
+    }
+
+    /// Gibt die Historie für die konfigurierten Server zurück
+    ///
+    /// Server, die nicht mehr konfiguriert sind, werden verworfen.
+    pub fn report(&self, urls: &[String]) -> Vec<IceServerHealth> {
+        let mut history = self.history.lock();
+        history.retain(|url, _| urls.contains(url));
+
+        urls.iter()
+            .map(|url| {
+                let entries = history
+                    .get(url)
+                    .map(|entries| entries.iter().cloned().collect())
+                    .unwrap_or_default();
+                IceServerHealth::summarize(url, entries)
+            })
+            .collect()
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ice_server_urls() {
+        let target = ProbeTarget::parse("stun:stun.l.google.com:19302").unwrap();
+        assert_eq!(target.host, "stun.l.google.com");
+        assert_eq!(target.port, 19302);
+        assert_eq!(target.transport, ProbeTransport::Udp);
+
+        let target = ProbeTarget::parse("turn:turn.example.org?transport=tcp").unwrap();
+        assert_eq!((target.port, target.transport), (3478, ProbeTransport::Tcp));
+
+        let target = ProbeTarget::parse("turns:turn.example.org").unwrap();
+        assert_eq!((target.port, target.transport), (5349, ProbeTransport::Tls));
+
+        let target = ProbeTarget::parse("stun:[2001:db8::1]:3479").unwrap();
+        assert_eq!((target.host.as_str(), target.port), ("2001:db8::1", 3479));
+
+        assert!(ProbeTarget::parse("http://example.org").is_err());
+        assert!(ProbeTarget::parse("stun:example.org:abc").is_err());
+    }
+
+    #[test]
+    fn test_binding_response_must_match_transaction() {
+        let transaction_id = [7u8; 12];
+        let request = stun_binding_request(&transaction_id);
+        // Eine Anfrage ist keine Antwort
+        assert!(!is_binding_success(&request, &transaction_id));
+
+        let mut response = request;
+        response[0..2].copy_from_slice(&STUN_BINDING_SUCCESS.to_be_bytes());
+        assert!(is_binding_success(&response, &transaction_id));
+        assert!(!is_binding_success(&response, &[8u8; 12]));
+        assert!(!is_binding_success(&response[..19], &transaction_id));
+    }
+
+    #[test]
+    fn test_xor_mapped_address_is_decoded() {
+        let transaction_id = [7u8; 12];
+        let mut response = stun_binding_request(&transaction_id).to_vec();
+        response[0..2].copy_from_slice(&STUN_BINDING_SUCCESS.to_be_bytes());
+        assert_eq!(xor_mapped_address(&response, &transaction_id), None);
+
+        // 203.0.113.9:54321, maskiert mit dem Magic Cookie
+        let cookie = STUN_MAGIC_COOKIE.to_be_bytes();
+        let port = 54321u16 ^ (STUN_MAGIC_COOKIE >> 16) as u16;
+        let mut attribute = vec![0x00, 0x20, 0x00, 0x08, 0x00, 0x01];
+        attribute.extend_from_slice(&port.to_be_bytes());
+        for (i, octet) in [203u8, 0, 113, 9].iter().enumerate() {
+            attribute.push(octet ^ cookie[i]);
+        }
+        // Ein vorangehendes unbekanntes Attribut (SOFTWARE) wird übersprungen
+        response.extend_from_slice(&[0x80, 0x22, 0x00, 0x03, b'a', b'b', b'c', 0x00]);
+        response.extend_from_slice(&attribute);
+        let attributes_len = (response.len() - 20) as u16;
+        response[2..4].copy_from_slice(&attributes_len.to_be_bytes());
+
+        assert_eq!(
+            xor_mapped_address(&response, &transaction_id),
+            Some("203.0.113.9:54321".parse().unwrap())
+        );
+        assert_eq!(xor_mapped_address(&response, &[8u8; 12]), None);
+    }
+
+    #[test]
+    fn test_history_is_bounded_and_summarized() {
+        let monitor = IceHealthMonitor::new();
+        let url = "stun:stun.example.org:3478".to_string();
+        let probe = |rtt_ms: Option<u32>| IceProbeResult {
+            checked_at: 0,
+            reachable: rtt_ms.is_some(),
+            rtt_ms,
+            error: None,
+        };
+
+        for _ in 0..ICE_HEALTH_HISTORY_LEN {
+            monitor.record(&url, probe(None));
+        }
+        monitor.record(&url, probe(Some(20)));
+        monitor.record(&url, probe(Some(40)));
+        monitor.record("stun:removed.example.org", probe(Some(10)));
+
+        let report = monitor.report(&[url.clone(), "stun:new.example.org".to_string()]);
+        assert_eq!(report[0].history.len(), ICE_HEALTH_HISTORY_LEN);
+        assert_eq!(report[0].avg_rtt_ms, Some(30));
+        assert_eq!(
+            report[0].success_rate,
+            Some(2.0 / ICE_HEALTH_HISTORY_LEN as f32)
+        );
+        assert_eq!(report[1].success_rate, None);
+        assert!(report[1].history.is_empty());
+        assert!(!monitor
+            .history
+            .lock()
+            .contains_key("stun:removed.example.org"));
+    }
+}
@@ -0,0 +1,127 @@
+//! ICE Candidate Diagnose
+//!
+//! Zerlegt ICE Candidates für ausführliches Logging und maskiert dabei die
+//! Adressen, damit geteilte Logs keine vollständigen privaten IPs enthalten.
+
+use serde::Serialize;
+use std::net::IpAddr;
+
+// ============================================================================
+// TYPES
+// ============================================================================
+
+/// Herkunft eines geloggten Candidates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CandidateDirection {
+    /// Lokal gesammelt
+    Local,
+    /// Von der Gegenstelle empfangen
+    Remote,
+}
+
+/// Zusammenfassung eines ICE Candidates mit maskierter Adresse
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CandidateSummary {
+    pub direction: CandidateDirection,
+    /// host, srflx, prflx oder relay
+    pub candidate_type: String,
+    /// udp oder tcp
+    pub protocol: String,
+    pub address: String,
+    pub port: u16,
+}
+
+// ============================================================================
+// PARSING
+// ============================================================================
+
+/// Zerlegt eine `candidate:` Zeile (RFC 8839) in eine maskierte Zusammenfassung
+///
+/// Format: `candidate:<foundation> <component> <protocol> <priority> <address> <port> typ <type> ...`
+pub fn summarize_candidate(
+    candidate: &str,
+    direction: CandidateDirection,
+) -> Option<CandidateSummary> {
+    let line = candidate.trim();
+    let line = line.strip_prefix("a=").unwrap_or(line);
+    let line = line.strip_prefix("candidate:")?;
+
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.len() < 8 || fields[6] != "typ" {
+        return None;
+    }
+
+    Some(CandidateSummary {
+        direction,
+        candidate_type: fields[7].to_lowercase(),
+        protocol: fields[2].to_lowercase(),
+        address: redact_address(fields[4]),
+        port: fields[5].parse().ok()?,
+    })
+}
+
+/// Maskiert eine Candidate-Adresse
+///
+/// IPv4: letztes Oktett, IPv6: alles nach den ersten vier Gruppen,
+/// mDNS-Namen (`*.local`) werden komplett ersetzt.
+pub fn redact_address(address: &str) -> String {
+    match address.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => {
+            let [a, b, c, _] = ip.octets();
+            format!("{}.{}.{}.x", a, b, c)
+        }
+        Ok(IpAddr::V6(ip)) => {
+            let segments = ip.segments();
+            format!(
+                "{:x}:{:x}:{:x}:{:x}:x:x:x:x",
+                segments[0], segments[1], segments[2], segments[3]
+            )
+        }
+        Err(_) if address.ends_with(".local") => "<mdns>.local".to_string(),
+        Err(_) => "<redacted>".to_string(),
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summarize_host_candidate() {
+        let summary = summarize_candidate(
+            "candidate:1 1 UDP 2130706431 192.168.1.23 54321 typ host",
+            CandidateDirection::Local,
+        )
+        .unwrap();
+
+        assert_eq!(summary.candidate_type, "host");
+        assert_eq!(summary.protocol, "udp");
+        assert_eq!(summary.address, "192.168.1.x");
+        assert_eq!(summary.port, 54321);
+    }
+
+    #[test]
+    fn test_summarize_relay_candidate_with_related_address() {
+        let summary = summarize_candidate(
+            "candidate:3 1 udp 16777215 2001:db8:85a3:1:2:3:4:5 3478 typ relay raddr 10.0.0.1 rport 9",
+            CandidateDirection::Remote,
+        )
+        .unwrap();
+
+        assert_eq!(summary.candidate_type, "relay");
+        assert_eq!(summary.address, "2001:db8:85a3:1:x:x:x:x");
+        assert!(!format!("{:?}", summary).contains("10.0.0.1"));
+    }
+
+    #[test]
+    fn test_redact_non_ip_addresses() {
+        assert_eq!(redact_address("abcd-1234.local"), "<mdns>.local");
+        assert_eq!(redact_address("example.com"), "<redacted>");
+        assert!(summarize_candidate("not a candidate", CandidateDirection::Local).is_none());
+    }
+}
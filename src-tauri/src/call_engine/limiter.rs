@@ -0,0 +1,181 @@
+//! Limiter für das Playback
+//!
+//! Mehrere laute Quellen oder ein übersteuernder Peer können das Summensignal
+//! über 0 dBFS treiben. Der Mixer staucht solche Spitzen per `soft_clip`
+//! nur gegen 1.0; der Limiter hält das Signal dagegen unter einer wählbaren
+//! Obergrenze. Die Verstärkung sinkt sofort, sobald eine Spitze die Grenze
+//! überschreiten würde, und erholt sich danach langsam, damit keine
+//! hörbaren Pumpeffekte entstehen.
+
+use super::audio::SAMPLE_RATE;
+use serde::{Deserialize, Serialize};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Standard-Obergrenze (1 dB Headroom gegen Inter-Sample-Peaks)
+pub const DEFAULT_LIMITER_CEILING_DB: f32 = -1.0;
+
+/// Erlaubter Bereich der Obergrenze in dBFS
+pub const LIMITER_CEILING_RANGE_DB: std::ops::RangeInclusive<f32> = -12.0..=0.0;
+
+/// Zeit, in der sich die Verstärkung nach einer Spitze erholt (Zeitkonstante)
+const RELEASE_SECS: f32 = 0.08;
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// Einstellungen des Limiters
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct OutputLimiterConfig {
+    pub enabled: bool,
+    /// Obergrenze in dBFS (z.B. -1.0)
+    pub ceiling_db: f32,
+}
+
+impl OutputLimiterConfig {
+    /// Erstellt und validiert eine Konfiguration
+    pub fn new(enabled: bool, ceiling_db: f32) -> Result<Self, String> {
+        if !LIMITER_CEILING_RANGE_DB.contains(&ceiling_db) {
+            return Err(format!(
+                "limiter ceiling must be between {} and {} dBFS, got {}",
+                LIMITER_CEILING_RANGE_DB.start(),
+                LIMITER_CEILING_RANGE_DB.end(),
+                ceiling_db
+            ));
+        }
+        Ok(Self {
+            enabled,
+            ceiling_db,
+        })
+    }
+
+    /// Obergrenze als linearer Faktor
+    fn ceiling(&self) -> f32 {
+        10f32.powf(self.ceiling_db / 20.0)
+    }
+}
+
+impl Default for OutputLimiterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ceiling_db: DEFAULT_LIMITER_CEILING_DB,
+        }
+    }
+}
+
+// ============================================================================
+// LIMITER
+// ============================================================================
+
+/// Peak-Limiter mit sofortigem Attack und weichem Release
+#[derive(Debug, Clone)]
+pub struct OutputLimiter {
+    config: OutputLimiterConfig,
+    ceiling: f32,
+    /// Aktuelle Verstärkung (1.0 = unverändert)
+    gain: f32,
+    release_coeff: f32,
+}
+
+impl OutputLimiter {
+    pub fn new(config: OutputLimiterConfig) -> Self {
+        Self {
+            config,
+            ceiling: config.ceiling(),
+            gain: 1.0,
+            release_coeff: (-1.0 / (RELEASE_SECS * SAMPLE_RATE as f32)).exp(),
+        }
+    }
+
+    /// Gibt die aktuelle Konfiguration zurück
+    pub fn config(&self) -> OutputLimiterConfig {
+        self.config
+    }
+
+    /// Übernimmt eine neue Konfiguration, die laufende Dämpfung bleibt erhalten
+    pub fn set_config(&mut self, config: OutputLimiterConfig) {
+        self.config = config;
+        self.ceiling = config.ceiling();
+        if !config.enabled {
+            self.gain = 1.0;
+        }
+    }
+
+    /// Begrenzt ein Sample auf die Obergrenze
+    pub fn process(&mut self, sample: f32) -> f32 {
+        if !self.config.enabled {
+            return sample;
+        }
+
+        let magnitude = sample.abs();
+        let needed = if magnitude > self.ceiling {
+            self.ceiling / magnitude
+        } else {
+            1.0
+        };
+
+        if needed < self.gain {
+            // Attack: sofort so weit absenken, dass die Spitze unter der Grenze bleibt
+            self.gain = needed;
+        } else {
+            // Release: von unten an die benötigte Verstärkung annähern
+            self.gain = needed + (self.gain - needed) * self.release_coeff;
+        }
+        sample * self.gain
+    }
+}
+
+impl Default for OutputLimiter {
+    fn default() -> Self {
+        Self::new(OutputLimiterConfig::default())
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limiter_keeps_over_unity_signal_below_ceiling() {
+        let config = OutputLimiterConfig::new(true, -1.0).unwrap();
+        let ceiling = config.ceiling();
+        let mut limiter = OutputLimiter::new(config);
+
+        // 440 Hz mit doppelter Vollaussteuerung
+        let loud: Vec<f32> = (0..SAMPLE_RATE as usize / 10)
+            .map(|i| 2.0 * (i as f32 * 2.0 * std::f32::consts::PI * 440.0 / 48_000.0).sin())
+            .collect();
+        let peak = loud
+            .iter()
+            .map(|sample| limiter.process(*sample).abs())
+            .fold(0.0f32, f32::max);
+        assert!(peak <= ceiling + 1e-6, "peak {} above {}", peak, ceiling);
+        assert!(peak > ceiling * 0.9);
+
+        // Nach der Spitze erholt sich die Verstärkung, leise Signale bleiben unverändert
+        for _ in 0..SAMPLE_RATE {
+            limiter.process(0.1);
+        }
+        assert!((limiter.process(0.1) - 0.1).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_disabled_limiter_passes_through() {
+        let mut limiter = OutputLimiter::default();
+        assert_eq!(limiter.process(1.5), 1.5);
+
+        limiter.set_config(OutputLimiterConfig::new(true, 0.0).unwrap());
+        assert!(limiter.process(1.5) <= 1.0);
+
+        assert!(OutputLimiterConfig::new(true, 0.5).is_err());
+        assert!(OutputLimiterConfig::new(true, -20.0).is_err());
+    }
+}
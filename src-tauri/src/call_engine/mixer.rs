@@ -0,0 +1,251 @@
+//! Playback Mixer - Mischt mehrere eingehende Audio-Quellen
+//!
+//! Jede Quelle (z.B. ein Peer in einem Gruppenanruf) hat einen eigenen
+//! Ring-Buffer und eine eigene Verstärkung. Beim Abspielen werden die
+//! Quellen Sample für Sample summiert und gegen Übersteuerung begrenzt.
+
+use ringbuf::{traits::*, HeapRb};
+use std::collections::HashMap;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// ID der Standard-Quelle für den Einzelanruf
+pub const DEFAULT_PLAYBACK_SOURCE: &str = "default";
+
+/// Ab diesem Pegel wird das Summensignal weich begrenzt
+const SOFT_CLIP_KNEE: f32 = 0.8;
+
+/// Maximale Verstärkung pro Quelle
+pub const MAX_SOURCE_GAIN: f32 = 4.0;
+
+// ============================================================================
+// MIXER
+// ============================================================================
+
+/// Eine einzelne Audio-Quelle im Mixer
+struct MixerSource {
+    buffer: HeapRb<f32>,
+    gain: f32,
+}
+
+/// Mischt beliebig viele benannte Quellen zu einem Mono-Signal
+pub struct PlaybackMixer {
+    sources: HashMap<String, MixerSource>,
+    buffer_size: usize,
+    /// Anzahl Samples, die vor dem Abspielen gepuffert sein müssen
+    prefill: usize,
+    /// Ob die Vorpufferung erreicht wurde und abgespielt wird
+    primed: bool,
+}
+
+impl PlaybackMixer {
+    /// Erstellt einen leeren Mixer mit der angegebenen Buffer-Größe pro Quelle
+    pub fn new(buffer_size: usize) -> Self {
+        Self {
+            sources: HashMap::new(),
+            buffer_size,
+            prefill: 0,
+            primed: true,
+        }
+    }
+
+    /// Setzt die Vorpufferung in Samples
+    ///
+    /// Bis eine Quelle so viele Samples gepuffert hat, liefert der Mixer
+    /// Stille. Das verhindert Aussetzer direkt nach dem Start des Playbacks.
+    pub fn set_prefill(&mut self, samples: usize) {
+        self.prefill = samples.min(self.buffer_size);
+        self.primed = self.prefill == 0;
+    }
+
+    /// Prüft, ob die Vorpufferung erreicht wurde
+    pub fn is_primed(&self) -> bool {
+        self.primed
+    }
+
+    /// Fügt eine Quelle hinzu
+    ///
+    /// Gibt `false` zurück, wenn die Quelle bereits existiert.
+    pub fn add_source(&mut self, id: &str) -> bool {
+        if self.sources.contains_key(id) {
+            return false;
+        }
+        self.sources.insert(
+            id.to_string(),
+            MixerSource {
+                buffer: HeapRb::new(self.buffer_size),
+                gain: 1.0,
+            },
+        );
+        true
+    }
+
+    /// Entfernt eine Quelle samt ungespielter Samples
+    pub fn remove_source(&mut self, id: &str) -> bool {
+        self.sources.remove(id).is_some()
+    }
+
+    /// Prüft, ob eine Quelle existiert
+    pub fn has_source(&self, id: &str) -> bool {
+        self.sources.contains_key(id)
+    }
+
+    /// Gibt die IDs aller Quellen zurück
+    pub fn source_ids(&self) -> Vec<String> {
+        self.sources.keys().cloned().collect()
+    }
+
+    /// Setzt die Verstärkung einer Quelle (0.0 bis `MAX_SOURCE_GAIN`)
+    pub fn set_gain(&mut self, id: &str, gain: f32) -> bool {
+        match self.sources.get_mut(id) {
+            Some(source) => {
+                source.gain = gain.clamp(0.0, MAX_SOURCE_GAIN);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Schreibt Samples in den Buffer einer Quelle
+    ///
+    /// Bei vollem Buffer werden überzählige Samples verworfen.
+    /// Gibt `false` zurück, wenn die Quelle nicht existiert.
+    pub fn write(&mut self, id: &str, samples: &[f32]) -> bool {
+        match self.sources.get_mut(id) {
+            Some(source) => {
+                for sample in samples {
+                    let _ = source.buffer.try_push(*sample);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Gibt die Anzahl gepufferter Samples einer Quelle zurück
+    pub fn buffered(&self, id: &str) -> usize {
+        self.sources
+            .get(id)
+            .map(|s| s.buffer.occupied_len())
+            .unwrap_or(0)
+    }
+
+    /// Liest ein gemischtes Sample aus allen Quellen
+    ///
+    /// Quellen ohne Daten tragen Stille bei.
+    pub fn next_sample(&mut self) -> f32 {
+        if !self.primed {
+            if !self
+                .sources
+                .values()
+                .any(|s| s.buffer.occupied_len() >= self.prefill)
+            {
+                return 0.0;
+            }
+            self.primed = true;
+        }
+
+        let sum: f32 = self
+            .sources
+            .values_mut()
+            .map(|s| s.buffer.try_pop().map(|v| v * s.gain).unwrap_or(0.0))
+            .sum();
+        soft_clip(sum)
+    }
+}
+
+/// Begrenzt ein Sample weich auf den Bereich [-1.0, 1.0]
+///
+/// Unterhalb des Knies bleibt das Signal unverändert, darüber wird es
+/// über tanh sanft gegen 1.0 gestaucht statt hart abgeschnitten.
+pub fn soft_clip(sample: f32) -> f32 {
+    let magnitude = sample.abs();
+    if magnitude <= SOFT_CLIP_KNEE {
+        return sample;
+    }
+
+    let headroom = 1.0 - SOFT_CLIP_KNEE;
+    let compressed = SOFT_CLIP_KNEE + headroom * ((magnitude - SOFT_CLIP_KNEE) / headroom).tanh();
+    compressed.copysign(sample)
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_source_passes_through() {
+        let mut mixer = PlaybackMixer::new(16);
+        assert!(mixer.add_source(DEFAULT_PLAYBACK_SOURCE));
+        mixer.write(DEFAULT_PLAYBACK_SOURCE, &[0.1, -0.2]);
+
+        assert_eq!(mixer.next_sample(), 0.1);
+        assert_eq!(mixer.next_sample(), -0.2);
+        assert_eq!(mixer.next_sample(), 0.0);
+    }
+
+    #[test]
+    fn test_sources_are_summed_with_gain() {
+        let mut mixer = PlaybackMixer::new(16);
+        mixer.add_source("a");
+        mixer.add_source("b");
+        mixer.set_gain("b", 0.5);
+
+        mixer.write("a", &[0.25, 0.25]);
+        mixer.write("b", &[0.5]);
+
+        assert!((mixer.next_sample() - 0.5).abs() < 1e-6);
+        // Quelle "b" ist leer und trägt Stille bei
+        assert!((mixer.next_sample() - 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_mix_is_clip_protected() {
+        let mut mixer = PlaybackMixer::new(16);
+        for id in ["a", "b", "c"] {
+            mixer.add_source(id);
+            mixer.write(id, &[0.9, -0.9]);
+        }
+
+        let positive = mixer.next_sample();
+        let negative = mixer.next_sample();
+        assert!(positive > SOFT_CLIP_KNEE && positive <= 1.0);
+        assert_eq!(positive, -negative);
+    }
+
+    #[test]
+    fn test_playback_is_silent_until_prefill_is_reached() {
+        let mut mixer = PlaybackMixer::new(16);
+        mixer.add_source(DEFAULT_PLAYBACK_SOURCE);
+        mixer.set_prefill(4);
+
+        mixer.write(DEFAULT_PLAYBACK_SOURCE, &[0.5, 0.5, 0.5]);
+        assert_eq!(mixer.next_sample(), 0.0);
+        assert!(!mixer.is_primed());
+        // Stille verbraucht keine gepufferten Samples
+        assert_eq!(mixer.buffered(DEFAULT_PLAYBACK_SOURCE), 3);
+
+        mixer.write(DEFAULT_PLAYBACK_SOURCE, &[0.5]);
+        assert_eq!(mixer.next_sample(), 0.5);
+        assert!(mixer.is_primed());
+    }
+
+    #[test]
+    fn test_unknown_and_removed_sources() {
+        let mut mixer = PlaybackMixer::new(16);
+        assert!(!mixer.write("missing", &[0.5]));
+
+        mixer.add_source("a");
+        assert!(!mixer.add_source("a"));
+        mixer.write("a", &[0.5]);
+        assert!(mixer.remove_source("a"));
+        assert!(!mixer.has_source("a"));
+        assert_eq!(mixer.next_sample(), 0.0);
+    }
+}
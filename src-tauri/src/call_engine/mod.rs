@@ -3,11 +3,68 @@
 //! Dieses Modul verwaltet:
 //! - WebRTC Peer Connections
 //! - Audio Capture (Mikrofon)
-//! - Audio Playback (Lautsprecher)
-//! - Opus Encoding/Decoding
+//! - Audio Playback (Lautsprecher) mit Mixer für mehrere Quellen und Limiter
+//! - Dekodieren empfangener RTP-Pakete (Opus, G.711) für das Playback
+//! - Überwachung der System-Standardgeräte
+//! - Erreichbarkeit der STUN/TURN-Server über die Zeit
+//! - Selbsttest der Erreichbarkeit (Candidate-Typen, NAT-Verhalten)
+//! - Erkennung einseitigen Audios, Paketverlust und Jitter anhand der RTP-Pakete
+//! - Echo-Check mit kurzem Prüfsignal nach dem Verbindungsaufbau
+//! - Obergrenze für die Sende-Bitrate
+//! - Abfrage der Mikrofon-Berechtigung
+//! - Kurze UI-Sounds (Verbinden, Auflegen, Nachricht)
+//! - Klingelton für eingehende Anrufe
+//! - Opus Encoding/Decoding mit einstellbarer Bitrate
 
 mod audio;
+mod bitrate_cap;
+#[cfg(test)]
+pub mod codec_harness;
+mod connectivity;
+mod depacketizer;
+mod device_watch;
 mod engine;
+mod ice_health;
+mod ice_log;
+mod limiter;
+mod mixer;
+mod network_sim;
+mod opus_codec;
+mod permission;
+mod ringtone;
+mod rtp_monitor;
+mod sound_effects;
 
-pub use audio::{AudioError, AudioHandler, FRAME_SIZE, SAMPLE_RATE};
-pub use engine::{CallEngine, CallEngineError, CallEvent, CallState};
+pub use audio::{
+    AudioError, AudioHandler, DEFAULT_PREFILL_FRAMES, FRAME_SIZE, MAX_OUTPUT_DELAY_MS,
+    MAX_PREFILL_FRAMES, OPUS_FRAME_SIZES, SAMPLE_RATE,
+};
+pub use bitrate_cap::MIN_BITRATE_CAP_BPS;
+pub use connectivity::{CandidateCounts, ConnectivityReport, NatBehavior};
+pub use device_watch::{
+    DefaultDeviceChange, DefaultDevices, DeviceKind, DEFAULT_DEVICE_POLL_INTERVAL,
+};
+pub use engine::{
+    keeps_own_offer, parse_dtls_fingerprint, parse_ice_candidate, AudioDeviceSelection, CallEngine,
+    CallEngineError, CallEvent, CallState, CallStateInfo, CallStateKind, CallStats, CodecInfo,
+    ConnectionStats, DtlsFingerprint, DtlsFingerprints, IceTransportPolicy, IncomingCallResolution,
+    LocalDescription, RemoteIdentity, SecurityInfo,
+};
+pub use ice_health::{
+    probe_ice_server, IceHealthMonitor, IceProbeResult, IceServerHealth, ICE_HEALTH_INTERVAL,
+    ICE_HEALTH_STARTUP_DELAY,
+};
+pub use ice_log::{redact_address, summarize_candidate, CandidateDirection, CandidateSummary};
+pub use limiter::OutputLimiterConfig;
+pub use mixer::{soft_clip, PlaybackMixer, DEFAULT_PLAYBACK_SOURCE, MAX_SOURCE_GAIN};
+pub use network_sim::{NetworkSimulation, MAX_SIMULATED_DELAY_MS};
+pub use opus_codec::{DEFAULT_OPUS_BITRATE, OPUS_BITRATE_RANGE};
+pub use permission::{
+    check_microphone_permission, request_microphone_permission, MicrophonePermission,
+};
+pub use ringtone::{Ringtone, RingtoneStyle};
+pub use rtp_monitor::AudioDirection;
+pub use sound_effects::{
+    test_tone, SoundEffect, SoundEffectError, SoundEffects, MAX_EFFECT_DURATION_SECS,
+    MAX_TEST_TONE_DURATION, TEST_TONE_FREQUENCY_RANGE,
+};
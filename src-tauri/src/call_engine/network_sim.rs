@@ -0,0 +1,121 @@
+//! Netzwerk-Simulation für Tests
+//!
+//! Simuliert Paketverlust, Latenz und Jitter auf dem ausgehenden RTP-Pfad,
+//! damit Jitter Buffer, FEC und Qualitätswarnungen ohne echtes schlechtes
+//! Netzwerk getestet werden können. Wird nur in Debug-Builds angewendet.
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Maximale simulierte Zusatzlatenz bzw. Jitter
+pub const MAX_SIMULATED_DELAY_MS: u32 = 5000;
+
+// ============================================================================
+// NETWORK SIMULATION
+// ============================================================================
+
+/// Parameter der Netzwerk-Simulation
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct NetworkSimulation {
+    /// Paketverlust in Prozent (0.0 - 100.0)
+    pub loss_pct: f32,
+    /// Zusätzliche konstante Latenz in Millisekunden
+    pub added_latency_ms: u32,
+    /// Maximale zufällige Abweichung der Latenz in Millisekunden (±)
+    pub jitter_ms: u32,
+}
+
+impl NetworkSimulation {
+    /// Erstellt und validiert eine Simulation
+    pub fn new(loss_pct: f32, added_latency_ms: u32, jitter_ms: u32) -> Result<Self, String> {
+        if !(0.0..=100.0).contains(&loss_pct) {
+            return Err(format!(
+                "loss_pct must be between 0 and 100, got {}",
+                loss_pct
+            ));
+        }
+        if added_latency_ms > MAX_SIMULATED_DELAY_MS || jitter_ms > MAX_SIMULATED_DELAY_MS {
+            return Err(format!(
+                "latency and jitter must not exceed {} ms",
+                MAX_SIMULATED_DELAY_MS
+            ));
+        }
+
+        Ok(Self {
+            loss_pct,
+            added_latency_ms,
+            jitter_ms,
+        })
+    }
+
+    /// Prüft, ob die Simulation Pakete beeinflusst
+    pub fn is_active(&self) -> bool {
+        self.loss_pct > 0.0 || self.added_latency_ms > 0 || self.jitter_ms > 0
+    }
+
+    /// Entscheidet über das Schicksal eines Pakets
+    ///
+    /// Gibt `None` zurück, wenn das Paket verworfen wird, sonst die Verzögerung.
+    pub fn packet_delay<R: Rng>(&self, rng: &mut R) -> Option<Duration> {
+        if self.loss_pct > 0.0 && rng.gen::<f32>() * 100.0 < self.loss_pct {
+            return None;
+        }
+
+        let jitter = if self.jitter_ms > 0 {
+            let jitter_ms = self.jitter_ms as i64;
+            rng.gen_range(-jitter_ms..=jitter_ms)
+        } else {
+            0
+        };
+        let delay_ms = (self.added_latency_ms as i64 + jitter).max(0) as u64;
+
+        Some(Duration::from_millis(delay_ms))
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_inactive_simulation_passes_packets_through() {
+        let sim = NetworkSimulation::default();
+        let mut rng = StdRng::seed_from_u64(1);
+
+        assert!(!sim.is_active());
+        assert_eq!(sim.packet_delay(&mut rng), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_loss_and_jitter_stay_in_bounds() {
+        let sim = NetworkSimulation::new(25.0, 100, 40).unwrap();
+        let mut rng = StdRng::seed_from_u64(42);
+
+        let results: Vec<_> = (0..1000).map(|_| sim.packet_delay(&mut rng)).collect();
+        let dropped = results.iter().filter(|r| r.is_none()).count();
+
+        // ~25% Verlust bei 1000 Paketen
+        assert!((150..350).contains(&dropped), "dropped {}", dropped);
+        for delay in results.into_iter().flatten() {
+            assert!(delay >= Duration::from_millis(60) && delay <= Duration::from_millis(140));
+        }
+    }
+
+    #[test]
+    fn test_invalid_parameters_are_rejected() {
+        assert!(NetworkSimulation::new(-1.0, 0, 0).is_err());
+        assert!(NetworkSimulation::new(101.0, 0, 0).is_err());
+        assert!(NetworkSimulation::new(0.0, MAX_SIMULATED_DELAY_MS + 1, 0).is_err());
+    }
+}
@@ -0,0 +1,215 @@
+//! Opus Encoding und Decoding für den Audio-Pfad
+//!
+//! Gesendet wird Mono mit `SAMPLE_RATE`, jedes RTP-Paket enthält genau einen
+//! Frame. Die Frame-Größe gibt der `AudioHandler` vor (siehe
+//! `OPUS_FRAME_SIZES`), die Bitrate lässt sich während des Anrufs ändern.
+
+use super::audio::SAMPLE_RATE;
+use opus::{Application, Bitrate, Channels, Decoder, Encoder};
+use std::fmt;
+use std::ops::RangeInclusive;
+use thiserror::Error;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// RTP Payload Type von Opus (wie von `register_default_codecs` registriert)
+pub const OPUS_PAYLOAD_TYPE: u8 = 111;
+
+/// Standard-Bitrate des Encoders (gute Sprachqualität bei Mono)
+pub const DEFAULT_OPUS_BITRATE: u32 = 32_000;
+
+/// Von Opus unterstützte Bitraten in bps
+pub const OPUS_BITRATE_RANGE: RangeInclusive<u32> = MIN_OPUS_BITRATE..=MAX_OPUS_BITRATE;
+
+const MIN_OPUS_BITRATE: u32 = 6_000;
+const MAX_OPUS_BITRATE: u32 = 510_000;
+
+/// Obergrenze für ein kodiertes Paket (Empfehlung von libopus)
+const MAX_OPUS_PACKET_SIZE: usize = 4000;
+
+/// Längster Opus-Frame (120ms bei 48kHz)
+const MAX_OPUS_FRAME_SAMPLES: usize = SAMPLE_RATE as usize * 120 / 1000;
+
+// ============================================================================
+// ERROR TYPES
+// ============================================================================
+
+#[derive(Error, Debug)]
+pub enum OpusCodecError {
+    #[error("Opus error: {0}")]
+    Opus(#[from] opus::Error),
+
+    #[error("Invalid Opus bitrate: {0} bps (supported: {MIN_OPUS_BITRATE}-{MAX_OPUS_BITRATE})")]
+    InvalidBitrate(u32),
+}
+
+/// Prüft, ob Opus die Bitrate unterstützt
+pub fn validate_opus_bitrate(bps: u32) -> Result<(), OpusCodecError> {
+    if !OPUS_BITRATE_RANGE.contains(&bps) {
+        return Err(OpusCodecError::InvalidBitrate(bps));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// ENCODER
+// ============================================================================
+
+/// Kodiert Mono-Frames mit `SAMPLE_RATE` zu Opus-Payloads
+pub struct OpusFrameEncoder {
+    encoder: Encoder,
+    bitrate: u32,
+    buffer: Vec<u8>,
+}
+
+impl OpusFrameEncoder {
+    pub fn new(bitrate: u32) -> Result<Self, OpusCodecError> {
+        validate_opus_bitrate(bitrate)?;
+        let mut encoder = Encoder::new(SAMPLE_RATE, Channels::Mono, Application::Voip)?;
+        encoder.set_bitrate(Bitrate::Bits(bitrate as i32))?;
+        Ok(Self {
+            encoder,
+            bitrate,
+            buffer: vec![0; MAX_OPUS_PACKET_SIZE],
+        })
+    }
+
+    /// Ändert die Bitrate ab dem nächsten Frame
+    pub fn set_bitrate(&mut self, bitrate: u32) -> Result<(), OpusCodecError> {
+        if bitrate == self.bitrate {
+            return Ok(());
+        }
+        validate_opus_bitrate(bitrate)?;
+        self.encoder.set_bitrate(Bitrate::Bits(bitrate as i32))?;
+        self.bitrate = bitrate;
+        Ok(())
+    }
+
+    pub fn bitrate(&self) -> u32 {
+        self.bitrate
+    }
+
+    /// Kodiert einen Frame (Länge aus `OPUS_FRAME_SIZES`)
+    pub fn encode(&mut self, frame: &[f32]) -> Result<Vec<u8>, OpusCodecError> {
+        let len = self.encoder.encode_float(frame, &mut self.buffer)?;
+        Ok(self.buffer[..len].to_vec())
+    }
+}
+
+impl fmt::Debug for OpusFrameEncoder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OpusFrameEncoder")
+            .field("bitrate", &self.bitrate)
+            .finish_non_exhaustive()
+    }
+}
+
+// ============================================================================
+// DECODER
+// ============================================================================
+
+/// Dekodiert Opus-Payloads zu Mono-Samples mit `SAMPLE_RATE`
+pub struct OpusFrameDecoder {
+    decoder: Decoder,
+    buffer: Vec<f32>,
+}
+
+impl OpusFrameDecoder {
+    pub fn new() -> Result<Self, OpusCodecError> {
+        Ok(Self {
+            decoder: Decoder::new(SAMPLE_RATE, Channels::Mono)?,
+            buffer: vec![0.0; MAX_OPUS_FRAME_SAMPLES],
+        })
+    }
+
+    /// Dekodiert die Payload eines RTP-Pakets
+    pub fn decode(&mut self, payload: &[u8]) -> Result<Vec<f32>, OpusCodecError> {
+        let samples = self
+            .decoder
+            .decode_float(payload, &mut self.buffer, false)?;
+        Ok(self.buffer[..samples].to_vec())
+    }
+}
+
+impl fmt::Debug for OpusFrameDecoder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OpusFrameDecoder").finish_non_exhaustive()
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::call_engine::FRAME_SIZE;
+
+    fn sine(frequency: f32, samples: usize) -> Vec<f32> {
+        (0..samples)
+            .map(|i| {
+                0.5 * (2.0 * std::f32::consts::PI * frequency * i as f32 / SAMPLE_RATE as f32).sin()
+            })
+            .collect()
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn test_sine_survives_encode_decode_loopback() {
+        let mut encoder = OpusFrameEncoder::new(DEFAULT_OPUS_BITRATE).unwrap();
+        let mut decoder = OpusFrameDecoder::new().unwrap();
+
+        let input = sine(440.0, FRAME_SIZE * 50);
+        let mut output = Vec::with_capacity(input.len());
+        for frame in input.chunks(FRAME_SIZE) {
+            let payload = encoder.encode(frame).unwrap();
+            assert!(!payload.is_empty() && payload.len() < MAX_OPUS_PACKET_SIZE);
+            let decoded = decoder.decode(&payload).unwrap();
+            assert_eq!(decoded.len(), FRAME_SIZE);
+            output.extend(decoded);
+        }
+
+        // Der Codec verzögert um einige Millisekunden: beste Ausrichtung suchen
+        // und den eingeschwungenen Teil vergleichen
+        let window = FRAME_SIZE * 30;
+        let start = FRAME_SIZE * 10;
+        let (delay, error) = (0..FRAME_SIZE)
+            .map(|delay| {
+                let diff: Vec<f32> = (start..start + window)
+                    .map(|i| output[i + delay] - input[i])
+                    .collect();
+                (delay, rms(&diff))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap();
+
+        let signal = rms(&input[start..start + window]);
+        let decoded = rms(&output[start + delay..start + delay + window]);
+        assert!(
+            error < signal * 0.25,
+            "error {} vs signal {}",
+            error,
+            signal
+        );
+        assert!((decoded - signal).abs() < signal * 0.2);
+    }
+
+    #[test]
+    fn test_bitrate_is_validated() {
+        assert!(matches!(
+            OpusFrameEncoder::new(1_000),
+            Err(OpusCodecError::InvalidBitrate(1_000))
+        ));
+        let mut encoder = OpusFrameEncoder::new(DEFAULT_OPUS_BITRATE).unwrap();
+        encoder.set_bitrate(64_000).unwrap();
+        assert_eq!(encoder.bitrate(), 64_000);
+        assert!(encoder.set_bitrate(600_000).is_err());
+        assert_eq!(encoder.bitrate(), 64_000);
+    }
+}
@@ -0,0 +1,139 @@
+//! Mikrofon-Berechtigung des Betriebssystems
+//!
+//! macOS und Windows geben das Mikrofon erst nach Zustimmung des Nutzers frei.
+//! Ohne Freigabe scheitert `start_capture` mit einem allgemeinen Stream-Fehler
+//! (oder liefert nur Stille), was beim ersten Start schwer zu deuten ist.
+//!
+//! - Windows: Datenschutz-Einstellungen aus der Registry (ConsentStore)
+//! - macOS: ohne AVFoundation-Bindings nicht abfragbar, der System-Dialog
+//!   erscheint beim ersten Öffnen eines Eingabe-Streams
+//! - Linux und andere: keine Berechtigung auf Betriebssystem-Ebene
+
+use super::audio::AudioHandler;
+use serde::Serialize;
+
+// ============================================================================
+// PERMISSION STATE
+// ============================================================================
+
+/// Stand der Mikrofon-Berechtigung
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MicrophonePermission {
+    Granted,
+    Denied,
+    /// Noch nicht entschieden oder auf dieser Plattform nicht abfragbar
+    Undetermined,
+}
+
+/// Fragt die Mikrofon-Berechtigung beim Betriebssystem ab
+///
+/// Kann (unter Windows) einen Prozess starten, daher nicht im async-Kontext
+/// direkt aufrufen.
+pub fn check_microphone_permission() -> MicrophonePermission {
+    query_os_permission()
+}
+
+/// Fordert die Mikrofon-Berechtigung an, wo das System das erlaubt
+///
+/// Ist sie noch offen, wird kurz ein Eingabe-Stream geöffnet. Unter macOS
+/// erscheint dabei der System-Dialog. Windows bietet Apps keine Anfrage an,
+/// dort muss der Nutzer die Datenschutz-Einstellung selbst ändern.
+pub fn request_microphone_permission() -> MicrophonePermission {
+    let permission = check_microphone_permission();
+    if permission != MicrophonePermission::Undetermined {
+        return permission;
+    }
+
+    match AudioHandler::new().and_then(|mut audio| audio.start_capture()) {
+        Ok(()) => tracing::info!("Opened input stream to request microphone permission"),
+        Err(e) => tracing::warn!("Failed to open input stream for permission request: {}", e),
+    }
+    check_microphone_permission()
+}
+
+// ============================================================================
+// PLATFORM QUERIES
+// ============================================================================
+
+#[cfg(target_os = "windows")]
+fn query_os_permission() -> MicrophonePermission {
+    use std::os::windows::process::CommandExt;
+
+    /// Kein Konsolenfenster für `reg.exe` öffnen
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    const CONSENT_KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\CapabilityAccessManager\ConsentStore\microphone";
+
+    let query = |key: String| {
+        std::process::Command::new("reg")
+            .args(["query", &key, "/v", "Value"])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| parse_consent_value(&String::from_utf8_lossy(&output.stdout)))
+    };
+
+    // Gerätweit, für den Nutzer und speziell für Desktop-Apps (nicht aus dem Store)
+    let device = query(format!(r"HKLM\{}", CONSENT_KEY));
+    let user = query(format!(r"HKCU\{}", CONSENT_KEY));
+    let desktop_apps = query(format!(r"HKCU\{}\NonPackaged", CONSENT_KEY));
+
+    if [device, user, desktop_apps].contains(&Some(MicrophonePermission::Denied)) {
+        return MicrophonePermission::Denied;
+    }
+    user.unwrap_or(MicrophonePermission::Undetermined)
+}
+
+#[cfg(target_os = "macos")]
+fn query_os_permission() -> MicrophonePermission {
+    MicrophonePermission::Undetermined
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn query_os_permission() -> MicrophonePermission {
+    MicrophonePermission::Granted
+}
+
+/// Liest den `Value`-Eintrag aus der Ausgabe von `reg query`
+///
+/// Beispielzeile: `    Value    REG_SZ    Allow`
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn parse_consent_value(output: &str) -> Option<MicrophonePermission> {
+    let line = output.lines().find(|line| line.contains("REG_SZ"))?;
+    match line.split_whitespace().last()? {
+        "Allow" => Some(MicrophonePermission::Granted),
+        "Deny" => Some(MicrophonePermission::Denied),
+        _ => None,
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_consent_value() {
+        let output = |value: &str| {
+            format!(
+                "\r\nHKEY_CURRENT_USER\\Software\\...\\microphone\r\n    Value    REG_SZ    {}\r\n\r\n",
+                value
+            )
+        };
+
+        assert_eq!(
+            parse_consent_value(&output("Allow")),
+            Some(MicrophonePermission::Granted)
+        );
+        assert_eq!(
+            parse_consent_value(&output("Deny")),
+            Some(MicrophonePermission::Denied)
+        );
+        assert_eq!(parse_consent_value(&output("Prompt")), None);
+        assert_eq!(parse_consent_value(""), None);
+    }
+}
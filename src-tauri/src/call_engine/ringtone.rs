@@ -0,0 +1,221 @@
+//! Klingelton für eingehende Anrufe
+//!
+//! Ein synthetisiertes Muster (Töne samt Pause) wird in Schleife gespielt,
+//! bis `Ringtone::stop` aufgerufen wird. Wie die UI-Sounds läuft der
+//! Klingelton auf einem eigenen Output-Stream (siehe
+//! `AudioHandler::play_looped`), aber auf dem für Anrufe ausgewählten Gerät.
+
+use super::audio::{AudioHandler, SAMPLE_RATE};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Lautstärke des Klingeltons
+const RINGTONE_GAIN: f32 = 0.3;
+
+/// Ein- und Ausblenden jedes Tons gegen Knacken
+const RINGTONE_FADE_MS: u32 = 10;
+
+// ============================================================================
+// RINGTONE STYLE
+// ============================================================================
+
+/// Auswählbare Klingeltöne
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RingtoneStyle {
+    /// Doppelton wie beim Festnetztelefon
+    #[default]
+    Classic,
+    /// Absteigende Dreiklang-Folge, dezenter
+    Chime,
+}
+
+impl RingtoneStyle {
+    /// Synthetisiert einen Durchlauf samt Pause (48kHz Mono)
+    pub fn samples(self) -> Vec<f32> {
+        let mut samples = Vec::new();
+        match self {
+            RingtoneStyle::Classic => {
+                for _ in 0..2 {
+                    samples.extend(tone(&[440.0, 480.0], 400));
+                    samples.extend(silence(200));
+                }
+                samples.extend(silence(1600));
+            }
+            RingtoneStyle::Chime => {
+                for frequency in [1046.5, 784.0, 659.3] {
+                    samples.extend(tone(&[frequency], 180));
+                    samples.extend(silence(60));
+                }
+                samples.extend(silence(1500));
+            }
+        }
+        samples
+    }
+}
+
+// ============================================================================
+// RINGTONE
+// ============================================================================
+
+/// Spielt den Klingelton ab, sofern aktiviert
+pub struct Ringtone {
+    enabled: AtomicBool,
+    style: Mutex<RingtoneStyle>,
+    /// Stopp-Signal des laufenden Klingeltons
+    playing: Mutex<Option<Arc<AtomicBool>>>,
+}
+
+impl Default for Ringtone {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Ringtone {
+    /// Erstellt den Player mit dem Standard-Klingelton (aktiviert)
+    pub fn new() -> Self {
+        Self {
+            enabled: AtomicBool::new(true),
+            style: Mutex::new(RingtoneStyle::default()),
+            playing: Mutex::new(None),
+        }
+    }
+
+    /// Aktiviert oder deaktiviert den Klingelton, ein laufender verstummt sofort
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+        if !enabled {
+            self.stop();
+        }
+    }
+
+    /// Gibt zurück, ob bei eingehenden Anrufen geklingelt wird
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Wählt den Klingelton (gilt ab dem nächsten Klingeln)
+    pub fn set_style(&self, style: RingtoneStyle) {
+        *self.style.lock() = style;
+    }
+
+    /// Gibt den gewählten Klingelton zurück
+    pub fn style(&self) -> RingtoneStyle {
+        *self.style.lock()
+    }
+
+    /// Startet den Klingelton auf dem Ausgabegerät (`None` = System-Standard)
+    ///
+    /// Ohne Wirkung, wenn deaktiviert oder bereits klingelnd.
+    pub fn start(&self, output_device: Option<String>) {
+        if !self.is_enabled() {
+            return;
+        }
+        let stop = {
+            let mut playing = self.playing.lock();
+            if playing.is_some() {
+                return;
+            }
+            Arc::clone(playing.insert(Arc::new(AtomicBool::new(false))))
+        };
+        let samples = self.style().samples();
+
+        // Der Stream ist nicht Send, daher ein eigener Thread wie bei den UI-Sounds
+        let spawned = std::thread::Builder::new()
+            .name("ringtone".to_string())
+            .spawn(move || {
+                if let Err(e) = AudioHandler::play_looped(output_device.as_deref(), &samples, &stop)
+                {
+                    tracing::warn!("Failed to play ringtone: {}", e);
+                }
+            });
+        if let Err(e) = spawned {
+            tracing::warn!("Failed to start ringtone thread: {}", e);
+        }
+    }
+
+    /// Beendet den Klingelton (ohne Wirkung, wenn keiner läuft)
+    pub fn stop(&self) {
+        if let Some(stop) = self.playing.lock().take() {
+            stop.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Gibt zurück, ob gerade geklingelt wird
+    pub fn is_playing(&self) -> bool {
+        self.playing.lock().is_some()
+    }
+}
+
+// ============================================================================
+// SYNTHESIS
+// ============================================================================
+
+/// Summe gleich lauter Sinustöne mit kurzem Ein- und Ausblenden
+fn tone(frequencies: &[f32], duration_ms: u32) -> Vec<f32> {
+    let len = (SAMPLE_RATE * duration_ms / 1000) as usize;
+    let fade = (SAMPLE_RATE * RINGTONE_FADE_MS / 1000) as usize;
+    let gain = RINGTONE_GAIN / frequencies.len() as f32;
+
+    (0..len)
+        .map(|i| {
+            let edge = i.min(len - 1 - i);
+            let envelope = (edge as f32 / fade as f32).min(1.0);
+            let t = i as f32 / SAMPLE_RATE as f32;
+            let sum: f32 = frequencies
+                .iter()
+                .map(|frequency| (std::f32::consts::TAU * frequency * t).sin())
+                .sum();
+            gain * envelope * sum
+        })
+        .collect()
+}
+
+fn silence(duration_ms: u32) -> Vec<f32> {
+    vec![0.0; (SAMPLE_RATE * duration_ms / 1000) as usize]
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ringtones_are_audible_and_end_with_a_pause() {
+        for style in [RingtoneStyle::Classic, RingtoneStyle::Chime] {
+            let samples = style.samples();
+            let secs = samples.len() as f32 / SAMPLE_RATE as f32;
+            assert!((1.0..=4.0).contains(&secs), "{:?}: {}s", style, secs);
+            assert!(samples.iter().all(|s| s.abs() <= RINGTONE_GAIN));
+            assert!(samples.iter().any(|s| s.abs() > RINGTONE_GAIN / 2.0));
+
+            // Zwischen zwei Durchläufen bleibt es still
+            let pause = SAMPLE_RATE as usize;
+            assert!(samples[samples.len() - pause..].iter().all(|s| *s == 0.0));
+        }
+    }
+
+    #[test]
+    fn test_disabling_stops_the_ringtone() {
+        let ringtone = Ringtone::new();
+        ringtone.set_enabled(false);
+        ringtone.start(None);
+        assert!(!ringtone.is_playing());
+
+        ringtone.set_enabled(true);
+        ringtone.start(None);
+        assert!(ringtone.is_playing());
+        ringtone.set_enabled(false);
+        assert!(!ringtone.is_playing());
+    }
+}
@@ -0,0 +1,294 @@
+//! RTP-Statistik eines Anrufs
+//!
+//! Zählt gesendete und empfangene RTP-Pakete und erkennt daraus einseitiges
+//! Audio ("Ich höre sie, aber sie hören mich nicht"). Fließen Pakete nur in
+//! eine Richtung, obwohl der Anruf verbunden ist, liegt das meist an
+//! Stummschaltung, fehlender Mikrofon-Berechtigung oder einer Firewall.
+//!
+//! Für empfangene Streams werden außerdem Paketverlust und Jitter nach
+//! RFC 3550 berechnet, da webrtc-rs beides nicht in seinen Stats liefert.
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Abstand, in dem die Zähler während eines Anrufs geprüft werden
+pub const RTP_MONITOR_INTERVAL: Duration = Duration::from_secs(1);
+
+/// So lange muss eine Richtung ausbleiben, bevor einseitiges Audio gemeldet wird
+pub const ONE_WAY_AUDIO_TIMEOUT: Duration = Duration::from_secs(5);
+
+// ============================================================================
+// COUNTERS
+// ============================================================================
+
+/// Paketzähler für beide Richtungen
+#[derive(Debug, Default)]
+pub struct RtpCounters {
+    sent: AtomicU64,
+    received: AtomicU64,
+}
+
+impl RtpCounters {
+    /// Zählt ein gesendetes Paket
+    pub fn record_sent(&self) {
+        self.sent.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Zählt ein empfangenes Paket
+    pub fn record_received(&self) {
+        self.received.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Gibt die Zählerstände zurück (gesendet, empfangen)
+    pub fn snapshot(&self) -> (u64, u64) {
+        (
+            self.sent.load(Ordering::Relaxed),
+            self.received.load(Ordering::Relaxed),
+        )
+    }
+}
+
+// ============================================================================
+// ONE-WAY AUDIO
+// ============================================================================
+
+/// Richtung, in der kein Audio ankommt
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AudioDirection {
+    /// Von der Gegenstelle kommt nichts an
+    Inbound,
+    /// Es wird nichts gesendet, die Gegenstelle hört uns nicht
+    Outbound,
+}
+
+/// Erkennt einseitiges Audio anhand aufeinanderfolgender Zählerstände
+///
+/// Jede Störung wird nur einmal gemeldet. Fließt wieder Audio in beide
+/// Richtungen (oder in keine), beginnt die Erkennung von vorn.
+#[derive(Debug, Default)]
+pub struct OneWayAudioDetector {
+    /// Letzter Zählerstand (gesendet, empfangen) und Zeitpunkt
+    last: Option<(u64, u64, Instant)>,
+    /// Fehlende Richtung und Beginn der Störung
+    stalled: Option<(AudioDirection, Instant)>,
+    reported: bool,
+}
+
+impl OneWayAudioDetector {
+    /// Verarbeitet neue Zählerstände
+    ///
+    /// Gibt die fehlende Richtung zurück, sobald sie länger als
+    /// `ONE_WAY_AUDIO_TIMEOUT` ausbleibt, während die andere fließt.
+    pub fn observe(&mut self, sent: u64, received: u64, now: Instant) -> Option<AudioDirection> {
+        let (last_sent, last_received, last_at) = self.last.replace((sent, received, now))?;
+
+        let missing = match (sent > last_sent, received > last_received) {
+            (true, false) => Some(AudioDirection::Inbound),
+            (false, true) => Some(AudioDirection::Outbound),
+            _ => None,
+        };
+        let Some(direction) = missing else {
+            self.stalled = None;
+            self.reported = false;
+            return None;
+        };
+
+        // Die Störung begann bereits nach der vorherigen Messung
+        let since = match self.stalled {
+            Some((stalled, since)) if stalled == direction => since,
+            _ => {
+                self.stalled = Some((direction, last_at));
+                self.reported = false;
+                last_at
+            }
+        };
+
+        if self.reported || now.duration_since(since) < ONE_WAY_AUDIO_TIMEOUT {
+            return None;
+        }
+        self.reported = true;
+        Some(direction)
+    }
+
+    /// Verwirft den bisherigen Verlauf (z.B. während Stummschaltung)
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+// ============================================================================
+// RECEIVE QUALITY
+// ============================================================================
+
+/// Paketverlust und Jitter eines empfangenen RTP-Streams (RFC 3550, Anhang A)
+#[derive(Debug)]
+pub struct ReceiveQuality {
+    clock_rate: u32,
+    /// Erste und höchste Sequenznummer, um Überläufe erweitert
+    first_sequence: Option<u64>,
+    highest_sequence: u64,
+    received: u64,
+    /// Ankunft und RTP-Timestamp des vorigen Pakets
+    last: Option<(Instant, u32)>,
+    /// Geglätteter Jitter in Timestamp-Einheiten
+    jitter: f64,
+}
+
+impl ReceiveQuality {
+    pub fn new(clock_rate: u32) -> Self {
+        Self {
+            clock_rate: clock_rate.max(1),
+            first_sequence: None,
+            highest_sequence: 0,
+            received: 0,
+            last: None,
+            jitter: 0.0,
+        }
+    }
+
+    /// Verarbeitet ein empfangenes Paket
+    pub fn observe(&mut self, sequence_number: u16, timestamp: u32, arrival: Instant) {
+        self.received += 1;
+
+        let extended = match self.first_sequence {
+            None => {
+                self.first_sequence = Some(sequence_number as u64);
+                sequence_number as u64
+            }
+            Some(_) => extend_sequence(self.highest_sequence, sequence_number),
+        };
+        self.highest_sequence = self.highest_sequence.max(extended);
+
+        // Differenz der Transitzeiten zweier aufeinanderfolgender Pakete
+        if let Some((last_arrival, last_timestamp)) = self.last.replace((arrival, timestamp)) {
+            let arrival_delta =
+                arrival.duration_since(last_arrival).as_secs_f64() * self.clock_rate as f64;
+            let timestamp_delta = timestamp.wrapping_sub(last_timestamp) as i32 as f64;
+            let deviation = (arrival_delta - timestamp_delta).abs();
+            self.jitter += (deviation - self.jitter) / 16.0;
+        }
+    }
+
+    /// Erwartete, aber nicht empfangene Pakete
+    pub fn packets_lost(&self) -> u64 {
+        let Some(first) = self.first_sequence else {
+            return 0;
+        };
+        let expected = self.highest_sequence - first + 1;
+        expected.saturating_sub(self.received)
+    }
+
+    /// Jitter in Millisekunden
+    pub fn jitter_ms(&self) -> f64 {
+        self.jitter / self.clock_rate as f64 * 1000.0
+    }
+}
+
+/// Erweitert eine 16-Bit Sequenznummer relativ zur bisher höchsten
+///
+/// Umsortierte Pakete liegen knapp darunter, nach einem Überlauf zählt die
+/// Nummer in den nächsten Zyklus.
+fn extend_sequence(highest: u64, sequence_number: u16) -> u64 {
+    let delta = sequence_number.wrapping_sub(highest as u16) as i16;
+    highest.saturating_add_signed(delta as i64)
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_one_way_audio_is_reported_once_after_timeout() {
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let mut detector = OneWayAudioDetector::default();
+
+        // Beide Richtungen fließen
+        assert_eq!(detector.observe(0, 0, at(0)), None);
+        assert_eq!(detector.observe(50, 50, at(1)), None);
+
+        // Ab jetzt kommt nichts mehr an
+        for secs in 2..6 {
+            assert_eq!(detector.observe(secs * 50, 50, at(secs)), None);
+        }
+        assert_eq!(
+            detector.observe(300, 50, at(6)),
+            Some(AudioDirection::Inbound)
+        );
+        assert_eq!(detector.observe(350, 50, at(7)), None);
+
+        // Erholung setzt die Erkennung zurück, jetzt fehlt die andere Richtung
+        assert_eq!(detector.observe(400, 100, at(8)), None);
+        for secs in 9..13 {
+            assert_eq!(detector.observe(400, secs * 50, at(secs)), None);
+        }
+        assert_eq!(
+            detector.observe(400, 650, at(13)),
+            Some(AudioDirection::Outbound)
+        );
+    }
+
+    #[test]
+    fn test_receive_quality_counts_loss_across_wraparound() {
+        let start = Instant::now();
+        let mut quality = ReceiveQuality::new(48000);
+
+        // 65534, 65535, (0 fehlt), 1, 3, 2 (umsortiert), (4 fehlt), 5
+        for (index, sequence) in [65534u16, 65535, 1, 3, 2, 5].into_iter().enumerate() {
+            let at = start + Duration::from_millis(20 * index as u64);
+            quality.observe(sequence, 960 * sequence as u32, at);
+        }
+
+        assert_eq!(quality.packets_lost(), 2);
+    }
+
+    #[test]
+    fn test_receive_quality_jitter() {
+        let start = Instant::now();
+        let mut steady = ReceiveQuality::new(48000);
+        let mut jittery = ReceiveQuality::new(48000);
+
+        for i in 0..200u32 {
+            // Timestamps laufen nach 100 Paketen über
+            let timestamp = (u32::MAX - 96_000).wrapping_add(i * 960);
+            steady.observe(
+                i as u16,
+                timestamp,
+                start + Duration::from_millis(20 * i as u64),
+            );
+
+            // Abwechselnd 5ms zu früh und zu spät
+            let offset = if i % 2 == 0 { 15 } else { 25 };
+            let arrival = start + Duration::from_millis(20 * i as u64 + offset);
+            jittery.observe(i as u16, timestamp, arrival);
+        }
+
+        assert_eq!(steady.packets_lost(), 0);
+        assert!(steady.jitter_ms() < 0.01);
+        // Abweichung zwischen zwei Paketen ist 10ms, der Jitter nähert sich ihr an
+        assert!((jittery.jitter_ms() - 10.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_silence_in_both_directions_is_not_one_way() {
+        let start = Instant::now();
+        let mut detector = OneWayAudioDetector::default();
+
+        for secs in 0..20 {
+            assert_eq!(
+                detector.observe(0, 0, start + Duration::from_secs(secs)),
+                None
+            );
+        }
+    }
+}
@@ -0,0 +1,455 @@
+//! Kurze UI-Sounds
+//!
+//! Ein "Bloop" beim Verbindungsaufbau, ein "Boop" beim Auflegen und ein
+//! Hinweiston für Nachrichten. Die Standard-Sounds werden synthetisiert, jeder
+//! kann durch eine WAV-Datei ersetzt werden.
+//!
+//! Abgespielt wird auf einem eigenen, kurzlebigen Output-Stream (siehe
+//! `AudioHandler::play_once`), ein laufender Anruf bleibt davon unberührt.
+//!
+//! Außerdem erzeugt `test_tone` einen reinen Sinuston zum Prüfen der
+//! Lautsprecher in den Einstellungen.
+
+use super::audio::{AudioHandler, SAMPLE_RATE};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Maximale Länge eines Sounds (auch für eigene Dateien)
+pub const MAX_EFFECT_DURATION_SECS: u32 = 3;
+
+/// Lautstärke der synthetisierten Sounds
+const EFFECT_GAIN: f32 = 0.3;
+
+/// Erlaubte Frequenzen für den Testton (Hörbereich)
+pub const TEST_TONE_FREQUENCY_RANGE: RangeInclusive<f32> = 20.0..=20_000.0;
+
+/// Maximale Länge des Testtons
+pub const MAX_TEST_TONE_DURATION: Duration = Duration::from_secs(10);
+
+/// Lautstärke des Testtons (leiser als Vollaussteuerung, schont Ohren und Boxen)
+const TEST_TONE_GAIN: f32 = 0.25;
+
+/// Ein- und Ausblenden des Testtons gegen Knacken
+const TEST_TONE_FADE: Duration = Duration::from_millis(10);
+
+// ============================================================================
+// ERROR TYPES
+// ============================================================================
+
+#[derive(Error, Debug)]
+pub enum SoundEffectError {
+    #[error("Failed to read sound file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid WAV file: {0}")]
+    InvalidWav(String),
+
+    #[error("Sound is too long (max {MAX_EFFECT_DURATION_SECS} seconds)")]
+    TooLong,
+
+    #[error("Invalid test tone: {0}")]
+    InvalidTone(String),
+}
+
+// ============================================================================
+// SOUND EFFECTS
+// ============================================================================
+
+/// Verfügbare UI-Sounds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SoundEffect {
+    /// Anruf verbunden
+    Connect,
+    /// Anruf beendet
+    Disconnect,
+    /// Eingegangene Nachricht (z.B. Rückruf-Bitte)
+    Message,
+}
+
+impl SoundEffect {
+    /// Rückmeldung zum eigenen Anruf (Verbinden, Auflegen), kein Hinweis auf
+    /// ein anderes Ereignis
+    pub fn is_call_feedback(self) -> bool {
+        matches!(self, SoundEffect::Connect | SoundEffect::Disconnect)
+    }
+
+    /// Synthetisiert den Standard-Sound (48kHz Mono)
+    pub fn default_samples(self) -> Vec<f32> {
+        match self {
+            SoundEffect::Connect => sweep(440.0, 880.0, 120),
+            SoundEffect::Disconnect => sweep(660.0, 330.0, 150),
+            SoundEffect::Message => {
+                let mut samples = sweep(880.0, 880.0, 90);
+                samples.extend(sweep(1320.0, 1320.0, 120));
+                samples
+            }
+        }
+    }
+}
+
+/// Spielt UI-Sounds ab, sofern aktiviert
+pub struct SoundEffects {
+    enabled: AtomicBool,
+    /// Hinweis-Sounds während eines verbundenen Anrufs unterdrücken
+    quiet_during_call: AtomicBool,
+    /// Eigene Sounds statt der Standard-Sounds
+    overrides: Mutex<HashMap<SoundEffect, Arc<Vec<f32>>>>,
+}
+
+impl Default for SoundEffects {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SoundEffects {
+    /// Erstellt den Player mit Standard-Sounds, Sounds sind aktiviert und
+    /// bleiben während eines Anrufs still
+    pub fn new() -> Self {
+        Self {
+            enabled: AtomicBool::new(true),
+            quiet_during_call: AtomicBool::new(true),
+            overrides: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Aktiviert oder deaktiviert alle Sounds
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Gibt zurück, ob Sounds abgespielt werden
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Legt fest, ob Hinweis-Sounds während eines Anrufs unterdrückt werden
+    pub fn set_quiet_during_call(&self, quiet: bool) {
+        self.quiet_during_call.store(quiet, Ordering::Relaxed);
+    }
+
+    /// Gibt zurück, ob Hinweis-Sounds während eines Anrufs unterdrückt werden
+    pub fn is_quiet_during_call(&self) -> bool {
+        self.quiet_during_call.load(Ordering::Relaxed)
+    }
+
+    /// Prüft, ob ein Sound im aktuellen Anruf-Zustand abgespielt werden darf
+    ///
+    /// Rückmeldungen zum Anruf selbst sind immer erlaubt, alles andere würde
+    /// im Gespräch stören.
+    pub fn allows(&self, effect: SoundEffect, in_call: bool) -> bool {
+        !in_call || !self.is_quiet_during_call() || effect.is_call_feedback()
+    }
+
+    /// Ersetzt einen Sound durch eine WAV-Datei (`None` stellt den Standard wieder her)
+    pub fn set_override(
+        &self,
+        effect: SoundEffect,
+        path: Option<&Path>,
+    ) -> Result<(), SoundEffectError> {
+        let Some(path) = path else {
+            self.overrides.lock().remove(&effect);
+            return Ok(());
+        };
+
+        let samples = decode_wav(&std::fs::read(path)?)?;
+        self.overrides.lock().insert(effect, Arc::new(samples));
+        tracing::info!("Using custom sound for {:?}", effect);
+        Ok(())
+    }
+
+    /// Spielt einen Sound im Hintergrund ab (ohne Wirkung, wenn deaktiviert)
+    pub fn play(&self, effect: SoundEffect) {
+        if !self.is_enabled() {
+            return;
+        }
+        let samples = self.samples(effect);
+
+        // Der Stream ist nicht Send und blockiert bis zum Ende, daher ein eigener Thread
+        let spawned = std::thread::Builder::new()
+            .name("sound-effect".to_string())
+            .spawn(move || {
+                if let Err(e) = AudioHandler::play_once(&samples) {
+                    tracing::warn!("Failed to play sound effect {:?}: {}", effect, e);
+                }
+            });
+        if let Err(e) = spawned {
+            tracing::warn!("Failed to start sound effect thread: {}", e);
+        }
+    }
+
+    /// Gibt die Samples eines Sounds zurück (eigener Sound oder Standard)
+    fn samples(&self, effect: SoundEffect) -> Arc<Vec<f32>> {
+        self.overrides
+            .lock()
+            .get(&effect)
+            .cloned()
+            .unwrap_or_else(|| Arc::new(effect.default_samples()))
+    }
+}
+
+// ============================================================================
+// SYNTHESIS
+// ============================================================================
+
+/// Sinus mit linearem Frequenzverlauf und weicher Hüllkurve
+fn sweep(start_hz: f32, end_hz: f32, duration_ms: u32) -> Vec<f32> {
+    let len = (SAMPLE_RATE * duration_ms / 1000) as usize;
+    let mut phase = 0.0f32;
+
+    (0..len)
+        .map(|i| {
+            let t = i as f32 / len as f32;
+            let freq = start_hz + (end_hz - start_hz) * t;
+            phase =
+                (phase + std::f32::consts::TAU * freq / SAMPLE_RATE as f32) % std::f32::consts::TAU;
+
+            // Kurzer Anstieg gegen Knacken, danach quadratisches Ausklingen
+            let envelope = (t / 0.05).min(1.0) * (1.0 - t).powi(2);
+            EFFECT_GAIN * envelope * phase.sin()
+        })
+        .collect()
+}
+
+/// Erzeugt einen reinen Sinuston (48kHz Mono) mit kurzem Ein- und Ausblenden
+pub fn test_tone(frequency_hz: f32, duration: Duration) -> Result<Vec<f32>, SoundEffectError> {
+    if !TEST_TONE_FREQUENCY_RANGE.contains(&frequency_hz) {
+        return Err(SoundEffectError::InvalidTone(format!(
+            "frequency {} Hz outside {:?}",
+            frequency_hz, TEST_TONE_FREQUENCY_RANGE
+        )));
+    }
+    if duration.is_zero() || duration > MAX_TEST_TONE_DURATION {
+        return Err(SoundEffectError::InvalidTone(format!(
+            "duration {:?} must be between 0 and {:?}",
+            duration, MAX_TEST_TONE_DURATION
+        )));
+    }
+
+    let len = (duration.as_secs_f32() * SAMPLE_RATE as f32) as usize;
+    let fade = ((TEST_TONE_FADE.as_secs_f32() * SAMPLE_RATE as f32) as usize).min(len / 2);
+    let step = std::f32::consts::TAU * frequency_hz / SAMPLE_RATE as f32;
+
+    Ok((0..len)
+        .map(|i| {
+            let edge = i.min(len - 1 - i);
+            let envelope = if edge < fade {
+                edge as f32 / fade as f32
+            } else {
+                1.0
+            };
+            TEST_TONE_GAIN * envelope * (step * i as f32).sin()
+        })
+        .collect())
+}
+
+// ============================================================================
+// WAV DECODING
+// ============================================================================
+
+/// Dekodiert eine WAV-Datei (PCM 16 Bit oder Float 32 Bit) zu 48kHz Mono
+fn decode_wav(data: &[u8]) -> Result<Vec<f32>, SoundEffectError> {
+    let invalid = |reason: &str| SoundEffectError::InvalidWav(reason.to_string());
+
+    if data.len() < 12 || &data[0..4] != b"RIFF" || &data[8..12] != b"WAVE" {
+        return Err(invalid("missing RIFF/WAVE header"));
+    }
+
+    let mut format = None;
+    let mut samples = None;
+    let mut offset = 12;
+    while offset + 8 <= data.len() {
+        let id = &data[offset..offset + 4];
+        let size = u32::from_le_bytes([
+            data[offset + 4],
+            data[offset + 5],
+            data[offset + 6],
+            data[offset + 7],
+        ]) as usize;
+        let body = data
+            .get(offset + 8..offset + 8 + size)
+            .ok_or_else(|| invalid("truncated chunk"))?;
+
+        match id {
+            b"fmt " if body.len() >= 16 => {
+                let audio_format = u16::from_le_bytes([body[0], body[1]]);
+                let channels = u16::from_le_bytes([body[2], body[3]]);
+                let sample_rate = u32::from_le_bytes([body[4], body[5], body[6], body[7]]);
+                let bits = u16::from_le_bytes([body[14], body[15]]);
+                format = Some((audio_format, channels, sample_rate, bits));
+            }
+            b"data" => samples = Some(body),
+            _ => {}
+        }
+        // Chunks sind auf gerade Länge aufgefüllt
+        offset += 8 + size + size % 2;
+    }
+
+    let (audio_format, channels, sample_rate, bits) =
+        format.ok_or_else(|| invalid("missing fmt chunk"))?;
+    let body = samples.ok_or_else(|| invalid("missing data chunk"))?;
+    if channels == 0 || sample_rate == 0 {
+        return Err(invalid("no channels"));
+    }
+
+    let interleaved: Vec<f32> = match (audio_format, bits) {
+        (1, 16) => body
+            .chunks_exact(2)
+            .map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0)
+            .collect(),
+        (3, 32) => body
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect(),
+        _ => {
+            return Err(invalid(&format!(
+                "unsupported format {} with {} bits",
+                audio_format, bits
+            )))
+        }
+    };
+
+    let frames = interleaved.len() / channels as usize;
+    if frames > (sample_rate * MAX_EFFECT_DURATION_SECS) as usize {
+        return Err(SoundEffectError::TooLong);
+    }
+
+    let mono: Vec<f32> = interleaved
+        .chunks_exact(channels as usize)
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect();
+    Ok(resample_linear(&mono, sample_rate, SAMPLE_RATE))
+}
+
+/// Einfaches lineares Resampling
+fn resample_linear(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
+    if from_rate == to_rate {
+        return samples.to_vec();
+    }
+
+    let ratio = to_rate as f64 / from_rate as f64;
+    let len = (samples.len() as f64 * ratio) as usize;
+    (0..len)
+        .map(|i| {
+            let src = i as f64 / ratio;
+            let idx = src as usize;
+            let frac = (src - idx as f64) as f32;
+            let s1 = samples.get(idx).copied().unwrap_or(0.0);
+            let s2 = samples.get(idx + 1).copied().unwrap_or(s1);
+            s1 + (s2 - s1) * frac
+        })
+        .collect()
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Baut eine WAV-Datei mit 16-Bit PCM
+    fn wav_pcm16(channels: u16, sample_rate: u32, samples: &[i16]) -> Vec<u8> {
+        let data_len = (samples.len() * 2) as u32;
+        let mut wav = Vec::new();
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+        wav.extend_from_slice(b"WAVE");
+        wav.extend_from_slice(b"fmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes());
+        wav.extend_from_slice(&channels.to_le_bytes());
+        wav.extend_from_slice(&sample_rate.to_le_bytes());
+        wav.extend_from_slice(&(sample_rate * channels as u32 * 2).to_le_bytes());
+        wav.extend_from_slice(&(channels * 2).to_le_bytes());
+        wav.extend_from_slice(&16u16.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&data_len.to_le_bytes());
+        for sample in samples {
+            wav.extend_from_slice(&sample.to_le_bytes());
+        }
+        wav
+    }
+
+    #[test]
+    fn test_default_effects_are_short_and_in_range() {
+        for effect in [
+            SoundEffect::Connect,
+            SoundEffect::Disconnect,
+            SoundEffect::Message,
+        ] {
+            let samples = effect.default_samples();
+            assert!(!samples.is_empty());
+            assert!(samples.len() < SAMPLE_RATE as usize / 2);
+            assert!(samples.iter().all(|s| s.abs() <= EFFECT_GAIN));
+            assert!(samples.iter().any(|s| s.abs() > EFFECT_GAIN / 4.0));
+        }
+    }
+
+    #[test]
+    fn test_notifications_are_quiet_during_call() {
+        let effects = SoundEffects::new();
+        assert!(effects.allows(SoundEffect::Message, false));
+        assert!(!effects.allows(SoundEffect::Message, true));
+        assert!(effects.allows(SoundEffect::Disconnect, true));
+
+        effects.set_quiet_during_call(false);
+        assert!(effects.allows(SoundEffect::Message, true));
+    }
+
+    #[test]
+    fn test_test_tone_is_validated_and_faded() {
+        let samples = test_tone(1000.0, Duration::from_millis(500)).unwrap();
+        assert_eq!(samples.len(), SAMPLE_RATE as usize / 2);
+        assert_eq!(samples[0], 0.0);
+        assert!(samples.last().unwrap().abs() < 0.01);
+        let peak = samples.iter().fold(0.0f32, |max, s| max.max(s.abs()));
+        assert!((peak - TEST_TONE_GAIN).abs() < 0.001);
+
+        // 1kHz bei 48kHz: 48 Samples pro Periode, eine Halbwelle später invertiert
+        assert!((samples[1000] + samples[1024]).abs() < 0.001);
+
+        assert!(test_tone(5.0, Duration::from_secs(1)).is_err());
+        assert!(test_tone(440.0, Duration::ZERO).is_err());
+        assert!(test_tone(440.0, MAX_TEST_TONE_DURATION + Duration::from_secs(1)).is_err());
+    }
+
+    #[test]
+    fn test_decode_stereo_wav_to_mono_48khz() {
+        // 24kHz Stereo, linker und rechter Kanal werden gemittelt
+        let wav = wav_pcm16(2, 24_000, &[16384, 0, 16384, 0, -16384, -16384]);
+        let samples = decode_wav(&wav).unwrap();
+
+        assert_eq!(samples.len(), 6);
+        assert_eq!(samples[0], 0.25);
+        assert_eq!(samples[2], 0.25);
+        assert_eq!(samples[4], -0.5);
+    }
+
+    #[test]
+    fn test_invalid_or_long_wav_is_rejected() {
+        assert!(matches!(
+            decode_wav(b"not a wav file"),
+            Err(SoundEffectError::InvalidWav(_))
+        ));
+
+        let too_long = vec![0i16; (8_000 * (MAX_EFFECT_DURATION_SECS + 1)) as usize];
+        assert!(matches!(
+            decode_wav(&wav_pcm16(1, 8_000, &too_long)),
+            Err(SoundEffectError::TooLong)
+        ));
+    }
+}
@@ -0,0 +1,186 @@
+//! Kontaktkarten zum Teilen der eigenen Identität
+//!
+//! Eine Kontaktkarte enthält Username und Public Key (optional die aktuelle
+//! Peer-ID) als kompakten, URL-sicheren Text, der sich als QR-Code anzeigen
+//! lässt. Maßgeblich ist der Public Key: Der Username ist nur ein Hinweis für
+//! die Anzeige, die Peer-ID kann sich bei der nächsten Registrierung ändern.
+//!
+//! Format: `call-app-card:<base64url(JSON)>`
+
+use super::encoding::{decode_base64url, encode_base64url};
+use super::KeyPair;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Präfix jeder Kontaktkarte
+pub const CONTACT_CARD_PREFIX: &str = "call-app-card:";
+
+/// Aktuelle Version des Kartenformats
+const CONTACT_CARD_VERSION: u8 = 1;
+
+/// Maximale Länge des Usernames auf einer Karte
+const MAX_CARD_USERNAME_LENGTH: usize = 64;
+
+// ============================================================================
+// ERROR TYPES
+// ============================================================================
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ContactCardError {
+    #[error("Not a contact card")]
+    MissingPrefix,
+
+    #[error("Invalid contact card encoding")]
+    InvalidEncoding,
+
+    #[error("Invalid contact card payload")]
+    InvalidPayload,
+
+    #[error("Unsupported contact card version: {0}")]
+    UnsupportedVersion(u8),
+
+    #[error("Invalid username on contact card")]
+    InvalidUsername,
+
+    #[error("Invalid public key on contact card")]
+    InvalidPublicKey,
+}
+
+// ============================================================================
+// CONTACT CARD
+// ============================================================================
+
+/// Inhalt einer Kontaktkarte
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ContactCard {
+    pub username: String,
+    pub public_key: String,
+    pub peer_id: Option<String>,
+}
+
+/// Serialisierte Form mit kurzen Feldnamen (hält den QR-Code klein)
+#[derive(Serialize, Deserialize)]
+struct CardPayload {
+    v: u8,
+    u: String,
+    k: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    p: Option<String>,
+}
+
+impl ContactCard {
+    /// Kodiert die Karte als URL-sicheren Text
+    pub fn encode(&self) -> String {
+        let payload = CardPayload {
+            v: CONTACT_CARD_VERSION,
+            u: self.username.clone(),
+            k: self.public_key.clone(),
+            p: self.peer_id.clone(),
+        };
+        let json = serde_json::to_vec(&payload).expect("card payload is serializable");
+        format!("{}{}", CONTACT_CARD_PREFIX, encode_base64url(&json))
+    }
+
+    /// Liest und validiert eine Kontaktkarte
+    pub fn parse(text: &str) -> Result<Self, ContactCardError> {
+        let encoded = text
+            .trim()
+            .strip_prefix(CONTACT_CARD_PREFIX)
+            .ok_or(ContactCardError::MissingPrefix)?;
+        let json = decode_base64url(encoded).map_err(|_| ContactCardError::InvalidEncoding)?;
+        let payload: CardPayload =
+            serde_json::from_slice(&json).map_err(|_| ContactCardError::InvalidPayload)?;
+
+        if payload.v != CONTACT_CARD_VERSION {
+            return Err(ContactCardError::UnsupportedVersion(payload.v));
+        }
+
+        let username = payload.u.trim();
+        if username.is_empty() || username.chars().count() > MAX_CARD_USERNAME_LENGTH {
+            return Err(ContactCardError::InvalidUsername);
+        }
+
+        let public_key = KeyPair::validate_public_key(&payload.k)
+            .map_err(|_| ContactCardError::InvalidPublicKey)?;
+
+        Ok(Self {
+            username: username.to_string(),
+            public_key,
+            peer_id: payload.p.filter(|p| !p.trim().is_empty()),
+        })
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contact_card_round_trip() {
+        let card = ContactCard {
+            username: "alice".to_string(),
+            public_key: KeyPair::generate().public_key_base64(),
+            peer_id: Some("peer-1".to_string()),
+        };
+
+        let encoded = card.encode();
+        assert!(encoded.starts_with(CONTACT_CARD_PREFIX));
+        assert!(!encoded.contains(['+', '/', '=']));
+        assert_eq!(ContactCard::parse(&encoded).unwrap(), card);
+
+        let without_peer = ContactCard {
+            peer_id: None,
+            ..card
+        };
+        assert_eq!(
+            ContactCard::parse(&without_peer.encode()).unwrap(),
+            without_peer
+        );
+    }
+
+    #[test]
+    fn test_invalid_contact_cards_are_rejected() {
+        assert_eq!(
+            ContactCard::parse("https://example.com"),
+            Err(ContactCardError::MissingPrefix)
+        );
+        assert_eq!(
+            ContactCard::parse("call-app-card:!!!"),
+            Err(ContactCardError::InvalidEncoding)
+        );
+
+        let card = |payload: serde_json::Value| {
+            format!(
+                "{}{}",
+                CONTACT_CARD_PREFIX,
+                encode_base64url(payload.to_string().as_bytes())
+            )
+        };
+        let public_key = KeyPair::generate().public_key_base64();
+
+        assert_eq!(
+            ContactCard::parse(&card(
+                serde_json::json!({"v": 2, "u": "a", "k": public_key})
+            )),
+            Err(ContactCardError::UnsupportedVersion(2))
+        );
+        assert_eq!(
+            ContactCard::parse(&card(
+                serde_json::json!({"v": 1, "u": " ", "k": public_key})
+            )),
+            Err(ContactCardError::InvalidUsername)
+        );
+        assert_eq!(
+            ContactCard::parse(&card(serde_json::json!({"v": 1, "u": "a", "k": "abc"}))),
+            Err(ContactCardError::InvalidPublicKey)
+        );
+    }
+}
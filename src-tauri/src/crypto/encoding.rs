@@ -0,0 +1,43 @@
+//! URL-sichere Kodierung für geteilte Identitätsdaten
+//!
+//! Alles, was Nutzer untereinander austauschen (Kontaktkarten, QR-Codes),
+//! wird als Base64url ohne Padding kodiert. So bleibt der Text in URLs und
+//! QR-Codes ohne weiteres Escaping gültig.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+
+/// Kodiert Bytes als Base64url ohne Padding
+pub fn encode_base64url(bytes: &[u8]) -> String {
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Dekodiert Base64url
+///
+/// Whitespace am Rand wird ignoriert, Padding wird toleriert, weil manche
+/// QR-Scanner es ergänzen.
+pub fn decode_base64url(text: &str) -> Result<Vec<u8>, base64::DecodeError> {
+    URL_SAFE_NO_PAD.decode(text.trim().trim_end_matches('='))
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64url_round_trip_is_url_safe() {
+        let bytes = [0xfb, 0xff, 0xfe, 0x00, 0x3e, 0x3f];
+        let encoded = encode_base64url(&bytes);
+
+        assert!(!encoded.contains(['+', '/', '=']));
+        assert_eq!(decode_base64url(&encoded).unwrap(), bytes);
+        assert_eq!(
+            decode_base64url(&format!(" {}==\n", encoded)).unwrap(),
+            bytes
+        );
+        assert!(decode_base64url("a+b/").is_err());
+    }
+}
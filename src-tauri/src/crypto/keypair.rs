@@ -3,18 +3,30 @@
 //! Generiert, speichert und lädt Ed25519 Schlüsselpaare.
 //! Der Private Key wird sicher im App-Datenverzeichnis gespeichert.
 //!
+//! Optional wird der Key mit einer Passphrase verschlüsselt abgelegt
+//! (Argon2id als KDF, XChaCha20-Poly1305). Verschlüsselte Dateien beginnen
+//! mit einer versionierten Kopfzeile, alte Klartext-Dateien (nur der
+//! Base64-kodierte Seed) werden weiterhin geladen.
+//!
 //! ## Verwendung
 //! ```rust
-//! let keypair = KeyPair::load_or_create()?;
+//! let (keypair, origin) = KeyPair::load_or_create()?;
 //! let signature = keypair.sign(b"Hello, World!")?;
 //! let public_key_base64 = keypair.public_key_base64();
 //! ```
 
+use crate::paths::app_data_dir;
+use argon2::Argon2;
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
-use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
 use rand::rngs::OsRng;
+use rand::RngCore;
+use sha2::{Digest, Sha512};
 use std::fs;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 // ============================================================================
@@ -34,6 +46,79 @@ pub enum KeyPairError {
 
     #[error("Failed to create signing key from bytes")]
     InvalidKey,
+
+    #[error("Invalid signature")]
+    InvalidSignature,
+
+    #[error("Wrong passphrase or corrupted key file")]
+    DecryptionFailed,
+
+    #[error("Failed to encrypt private key")]
+    EncryptionFailed,
+
+    #[error("Private key is encrypted, passphrase required")]
+    PassphraseRequired,
+
+    #[error("Private key is not encrypted")]
+    NotEncrypted,
+
+    #[error("Passphrase must not be empty")]
+    EmptyPassphrase,
+
+    #[error("Failed to derive key from passphrase: {0}")]
+    KeyDerivation(String),
+
+    #[error("Unsupported key file version: {0}")]
+    UnsupportedKeyFormat(String),
+}
+
+/// Präfix für signierte DTLS Fingerprints (Domain Separation)
+///
+/// Verhindert, dass eine Fingerprint-Signatur als Signatur einer
+/// Signaling-Nachricht wiederverwendet werden kann und umgekehrt.
+const DTLS_FINGERPRINT_CONTEXT: &str = "call-app-dtls-fingerprint:";
+
+/// Präfix für signierte Presence Beacons (Domain Separation)
+const PRESENCE_CONTEXT: &str = "call-app-presence:";
+
+/// Präfix für signierte SDP Offers (Domain Separation)
+const OFFER_CONTEXT: &str = "call-app-offer:";
+
+/// Präfix für signierte LAN-Nachrichten (Domain Separation)
+const LAN_MESSAGE_CONTEXT: &str = "call-app-lan:";
+
+/// Präfix für Key-Fingerprints (Domain Separation)
+const FINGERPRINT_CONTEXT: &str = "call-app-key-fingerprint:";
+
+/// Ziffern-Gruppen eines Key-Fingerprints (je 5 Ziffern aus 5 Bytes des Hashes)
+const FINGERPRINT_GROUPS: usize = 12;
+
+/// Kopfzeile verschlüsselter Key-Dateien, danach folgt `<version>:<base64>`
+///
+/// Klartext-Dateien enthalten nur Base64 und können nie so beginnen.
+const ENCRYPTED_KEY_HEADER: &str = "call-app-encrypted-key:";
+
+/// Aktuelle Version des verschlüsselten Formats
+const ENCRYPTED_KEY_VERSION: &str = "v1";
+
+/// Länge des Argon2-Salts in Bytes
+const SALT_LEN: usize = 16;
+
+/// Länge der XChaCha20-Nonce in Bytes
+const NONCE_LEN: usize = 24;
+
+// ============================================================================
+// KEYPAIR ORIGIN
+// ============================================================================
+
+/// Gibt an, ob das Schlüsselpaar neu erstellt oder von der Festplatte geladen wurde
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyPairOrigin {
+    /// Neue Identität (erster Start oder gelöschte Key-Datei)
+    Created,
+    /// Bestehende Identität geladen
+    Loaded,
 }
 
 // ============================================================================
@@ -53,17 +138,28 @@ impl KeyPair {
     /// - Windows: `%APPDATA%/com.kaufm.call-app/keys/private.key`
     /// - macOS: `~/Library/Application Support/com.kaufm.call-app/keys/private.key`
     /// - Linux: `~/.config/com.kaufm.call-app/keys/private.key`
-    pub fn load_or_create() -> Result<Self, KeyPairError> {
-        let key_path = Self::get_key_path()?;
+    ///
+    /// `CALL_APP_DATA_DIR` überschreibt das Verzeichnis (siehe `paths`).
+    ///
+    /// Zusätzlich wird zurückgegeben, welcher der beiden Wege genommen wurde.
+    pub fn load_or_create() -> Result<(Self, KeyPairOrigin), KeyPairError> {
+        let key_path = Self::get_key_path();
+        Self::load_or_create_at(&key_path)
+    }
 
+    /// Lädt oder erstellt ein Schlüsselpaar unter dem angegebenen Pfad
+    ///
+    /// Ist die Datei verschlüsselt, wird `PassphraseRequired` zurückgegeben;
+    /// der Key muss dann mit `load_encrypted` entsperrt werden.
+    pub fn load_or_create_at(key_path: &Path) -> Result<(Self, KeyPairOrigin), KeyPairError> {
         if key_path.exists() {
             tracing::info!("Loading existing keypair from {:?}", key_path);
-            Self::load_from_file(&key_path)
+            Ok((Self::load_from_file(key_path)?, KeyPairOrigin::Loaded))
         } else {
             tracing::info!("Creating new keypair at {:?}", key_path);
             let keypair = Self::generate();
-            keypair.save_to_file(&key_path)?;
-            Ok(keypair)
+            keypair.save_to_file(key_path)?;
+            Ok((keypair, KeyPairOrigin::Created))
         }
     }
 
@@ -74,9 +170,45 @@ impl KeyPair {
         Self { signing_key }
     }
 
-    /// Lädt ein Schlüsselpaar aus einer Datei
-    fn load_from_file(path: &PathBuf) -> Result<Self, KeyPairError> {
+    /// Entsperrt die verschlüsselte Identität im Standardpfad
+    pub fn unlock(passphrase: &str) -> Result<Self, KeyPairError> {
+        Self::load_encrypted(&Self::get_key_path(), passphrase)
+    }
+
+    /// Speichert den Key im Standardpfad neu
+    ///
+    /// Mit Passphrase verschlüsselt, mit `None` wieder im Klartext.
+    pub fn set_passphrase(&self, passphrase: Option<&str>) -> Result<(), KeyPairError> {
+        let key_path = Self::get_key_path();
+        match passphrase {
+            Some(passphrase) => self.save_encrypted(&key_path, passphrase),
+            None => self.save_to_file(&key_path),
+        }
+    }
+
+    /// Lädt ein mit `save_encrypted` gespeichertes Schlüsselpaar
+    ///
+    /// Eine falsche Passphrase ergibt `DecryptionFailed`.
+    pub fn load_encrypted(path: &Path, passphrase: &str) -> Result<Self, KeyPairError> {
+        let contents = fs::read_to_string(path)?;
+        let seed = open_seed(contents.trim(), passphrase)?;
+        Ok(Self {
+            signing_key: SigningKey::from_bytes(&seed),
+        })
+    }
+
+    /// Speichert den Private Key mit einer Passphrase verschlüsselt
+    pub fn save_encrypted(&self, path: &Path, passphrase: &str) -> Result<(), KeyPairError> {
+        let sealed = seal_seed(&self.signing_key.to_bytes(), passphrase)?;
+        write_key_file(path, &sealed)
+    }
+
+    /// Lädt ein Schlüsselpaar aus einer Klartext-Datei
+    fn load_from_file(path: &Path) -> Result<Self, KeyPairError> {
         let encoded = fs::read_to_string(path)?;
+        if is_encrypted(&encoded) {
+            return Err(KeyPairError::PassphraseRequired);
+        }
         let bytes = BASE64.decode(encoded.trim())?;
 
         if bytes.len() != 32 {
@@ -90,41 +222,17 @@ impl KeyPair {
     }
 
     /// Speichert den Private Key in einer Datei
-    fn save_to_file(&self, path: &PathBuf) -> Result<(), KeyPairError> {
-        // Parent-Verzeichnis erstellen falls nicht vorhanden
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent)?;
-        }
-
+    fn save_to_file(&self, path: &Path) -> Result<(), KeyPairError> {
         let encoded = BASE64.encode(self.signing_key.to_bytes());
-        fs::write(path, encoded)?;
-
-        // Datei-Berechtigungen setzen (nur unter Unix)
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::PermissionsExt;
-            let mut perms = fs::metadata(path)?.permissions();
-            perms.set_mode(0o600); // Nur Owner kann lesen/schreiben
-            fs::set_permissions(path, perms)?;
-        }
-
-        Ok(())
+        write_key_file(path, &encoded)
     }
 
-    /// Ermittelt den Pfad zur Key-Datei
-    fn get_key_path() -> Result<PathBuf, KeyPairError> {
-        let proj_dirs =
-            directories::ProjectDirs::from("com", "kaufm", "call-app").ok_or_else(|| {
-                std::io::Error::new(
-                    std::io::ErrorKind::NotFound,
-                    "Could not determine app data directory",
-                )
-            })?;
-
-        let mut path = proj_dirs.data_dir().to_path_buf();
+    /// Ermittelt den Pfad zur Key-Datei (siehe `paths::app_data_dir`)
+    fn get_key_path() -> PathBuf {
+        let mut path = app_data_dir();
         path.push("keys");
         path.push("private.key");
-        Ok(path)
+        path
     }
 
     /// Signiert Daten mit dem Private Key
@@ -155,6 +263,155 @@ impl KeyPair {
         self.signing_key.verifying_key()
     }
 
+    /// Fingerprint des eigenen Public Keys zum Vergleich über einen anderen Kanal
+    ///
+    /// Siehe `fingerprint_for`.
+    pub fn fingerprint(&self) -> String {
+        fingerprint_of(&self.public_key_bytes())
+    }
+
+    /// Signiert einen DTLS Fingerprint und gibt die Signatur als Base64 zurück
+    ///
+    /// Bindet die Medienebene (DTLS-SRTP) an die Ed25519-Identität.
+    pub fn sign_dtls_fingerprint(&self, algorithm: &str, fingerprint: &str) -> String {
+        self.sign_base64(Self::dtls_fingerprint_message(algorithm, fingerprint).as_bytes())
+    }
+
+    /// Prüft eine DTLS Fingerprint-Signatur gegen einen Public Key (Base64)
+    pub fn verify_dtls_fingerprint(
+        public_key_base64: &str,
+        algorithm: &str,
+        fingerprint: &str,
+        signature_base64: &str,
+    ) -> Result<(), KeyPairError> {
+        Self::verify_base64(
+            public_key_base64,
+            Self::dtls_fingerprint_message(algorithm, fingerprint).as_bytes(),
+            signature_base64,
+        )
+    }
+
+    /// Normalisierte Nachricht, über die ein DTLS Fingerprint signiert wird
+    fn dtls_fingerprint_message(algorithm: &str, fingerprint: &str) -> String {
+        format!(
+            "{}{} {}",
+            DTLS_FINGERPRINT_CONTEXT,
+            algorithm.to_lowercase(),
+            fingerprint.to_uppercase()
+        )
+    }
+
+    /// Signiert einen Presence Beacon (Peer-ID und Ausstellungszeitpunkt)
+    ///
+    /// Der Beacon wird vom Server nur weitergeleitet und kann von ihm nicht
+    /// gefälscht werden.
+    pub fn sign_presence(&self, peer_id: &str, issued_at: i64) -> String {
+        self.sign_base64(Self::presence_message(peer_id, issued_at).as_bytes())
+    }
+
+    /// Prüft die Signatur eines Presence Beacons gegen einen Public Key (Base64)
+    pub fn verify_presence(
+        public_key_base64: &str,
+        peer_id: &str,
+        issued_at: i64,
+        signature_base64: &str,
+    ) -> Result<(), KeyPairError> {
+        Self::verify_base64(
+            public_key_base64,
+            Self::presence_message(peer_id, issued_at).as_bytes(),
+            signature_base64,
+        )
+    }
+
+    /// Nachricht, über die ein Presence Beacon signiert wird
+    fn presence_message(peer_id: &str, issued_at: i64) -> String {
+        format!("{}{}:{}", PRESENCE_CONTEXT, peer_id, issued_at)
+    }
+
+    /// Signiert ein SDP Offer samt Absender und Empfänger
+    ///
+    /// Belegt, dass der Inhaber des Keys genau dieses Offer an genau diesen
+    /// Empfänger geschickt hat. Der Server kann es weder verändern noch
+    /// einem anderen Peer zustellen.
+    pub fn sign_offer(&self, from_peer_id: &str, to_peer_id: &str, sdp: &str) -> String {
+        self.sign_base64(Self::offer_message(from_peer_id, to_peer_id, sdp).as_bytes())
+    }
+
+    /// Prüft die Signatur eines SDP Offers gegen einen Public Key (Base64)
+    pub fn verify_offer(
+        public_key_base64: &str,
+        from_peer_id: &str,
+        to_peer_id: &str,
+        sdp: &str,
+        signature_base64: &str,
+    ) -> Result<(), KeyPairError> {
+        Self::verify_base64(
+            public_key_base64,
+            Self::offer_message(from_peer_id, to_peer_id, sdp).as_bytes(),
+            signature_base64,
+        )
+    }
+
+    /// Nachricht, über die ein SDP Offer signiert wird
+    fn offer_message(from_peer_id: &str, to_peer_id: &str, sdp: &str) -> String {
+        format!("{}{}:{}:{}", OFFER_CONTEXT, from_peer_id, to_peer_id, sdp)
+    }
+
+    /// Signiert eine LAN-Nachricht (serialisierter Inhalt samt Empfänger)
+    ///
+    /// Im LAN gibt es keinen Server, der Absender prüft: Nur die Signatur
+    /// belegt, dass die Nachricht vom Inhaber der Peer-ID stammt.
+    pub fn sign_lan_message(&self, payload: &str) -> String {
+        self.sign_base64(format!("{}{}", LAN_MESSAGE_CONTEXT, payload).as_bytes())
+    }
+
+    /// Prüft die Signatur einer LAN-Nachricht gegen einen Public Key (Base64)
+    pub fn verify_lan_message(
+        public_key_base64: &str,
+        payload: &str,
+        signature_base64: &str,
+    ) -> Result<(), KeyPairError> {
+        Self::verify_base64(
+            public_key_base64,
+            format!("{}{}", LAN_MESSAGE_CONTEXT, payload).as_bytes(),
+            signature_base64,
+        )
+    }
+
+    /// Prüft, ob ein Base64 Public Key ein gültiger Ed25519 Key ist
+    ///
+    /// Gibt den Key in kanonischer Base64-Form zurück (ohne Whitespace).
+    pub fn validate_public_key(public_key_base64: &str) -> Result<String, KeyPairError> {
+        let verifying_key = Self::decode_public_key(public_key_base64)?;
+        Ok(BASE64.encode(verifying_key.as_bytes()))
+    }
+
+    /// Dekodiert einen Base64 Public Key
+    fn decode_public_key(public_key_base64: &str) -> Result<VerifyingKey, KeyPairError> {
+        let key_bytes: [u8; 32] = BASE64
+            .decode(public_key_base64.trim())?
+            .try_into()
+            .map_err(|_| KeyPairError::InvalidKey)?;
+        VerifyingKey::from_bytes(&key_bytes).map_err(|_| KeyPairError::InvalidKey)
+    }
+
+    /// Prüft eine Base64-Signatur über `message` gegen einen Base64 Public Key
+    fn verify_base64(
+        public_key_base64: &str,
+        message: &[u8],
+        signature_base64: &str,
+    ) -> Result<(), KeyPairError> {
+        let verifying_key = Self::decode_public_key(public_key_base64)?;
+
+        let signature_bytes = BASE64.decode(signature_base64.trim())?;
+        let signature =
+            Signature::from_slice(&signature_bytes).map_err(|_| KeyPairError::InvalidSignature)?;
+
+        verifying_key
+            .verify(message, &signature)
+            .map_err(|_| KeyPairError::InvalidSignature)
+    }
+
     /// Erstellt eine signierte Nachricht für den Signaling-Server
     ///
     /// Die Signatur wird über den JSON-String aller Felder (außer signature)
@@ -166,6 +423,29 @@ impl KeyPair {
         self.sign_base64(payload_string.as_bytes())
     }
 
+    /// Prüft die Signatur einer Signaling-Nachricht gegen einen Public Key (Base64)
+    ///
+    /// Gegenstück zu `sign_message`, ein `signature`-Feld im Payload wird
+    /// ignoriert. Gibt `Ok(false)` zurück, wenn die Signatur nicht passt, und
+    /// einen Fehler bei ungültigem Key oder nicht dekodierbarer Signatur.
+    pub fn verify_message(
+        public_key_base64: &str,
+        payload: &serde_json::Value,
+        signature_base64: &str,
+    ) -> Result<bool, KeyPairError> {
+        let sorted = Self::sort_json_object(payload);
+        let payload_string = serde_json::to_string(&sorted).unwrap_or_default();
+        match Self::verify_base64(
+            public_key_base64,
+            payload_string.as_bytes(),
+            signature_base64,
+        ) {
+            Ok(()) => Ok(true),
+            Err(KeyPairError::InvalidSignature) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
     /// Sortiert ein JSON-Objekt alphabetisch nach Keys
     fn sort_json_object(value: &serde_json::Value) -> serde_json::Value {
         match value {
@@ -187,6 +467,154 @@ impl KeyPair {
     }
 }
 
+// ============================================================================
+// FINGERPRINT
+// ============================================================================
+
+/// Fingerprint eines Base64 Public Keys zum Vergleich über einen anderen Kanal
+///
+/// Wie die Sicherheitsnummern bei Signal: 60 Ziffern in Gruppen zu fünf,
+/// abgeleitet aus dem SHA-512 des Keys. Stimmen die Ziffern bei beiden
+/// Gesprächspartnern überein (z.B. vorgelesen am Telefon), gehört der Key
+/// wirklich zum Kontakt.
+pub fn fingerprint_for(public_key_base64: &str) -> Result<String, KeyPairError> {
+    let verifying_key = KeyPair::decode_public_key(public_key_base64)?;
+    Ok(fingerprint_of(verifying_key.as_bytes()))
+}
+
+fn fingerprint_of(public_key: &[u8; 32]) -> String {
+    let hash = Sha512::new()
+        .chain_update(FINGERPRINT_CONTEXT.as_bytes())
+        .chain_update(public_key)
+        .finalize();
+
+    hash.chunks_exact(5)
+        .take(FINGERPRINT_GROUPS)
+        .map(|chunk| {
+            let value = chunk
+                .iter()
+                .fold(0u64, |value, byte| (value << 8) | u64::from(*byte));
+            format!("{:05}", value % 100_000)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// ============================================================================
+// KEY FILE ENCRYPTION
+// ============================================================================
+
+/// Prüft, ob der Inhalt einer Key-Datei verschlüsselt ist
+fn is_encrypted(contents: &str) -> bool {
+    contents.trim_start().starts_with(ENCRYPTED_KEY_HEADER)
+}
+
+/// Verschlüsselt den Seed zu `<header><version>:<base64(salt|nonce|ciphertext)>`
+///
+/// Kopfzeile und Version gehen als Associated Data in die Authentifizierung ein.
+fn seal_seed(seed: &[u8; 32], passphrase: &str) -> Result<String, KeyPairError> {
+    if passphrase.is_empty() {
+        return Err(KeyPairError::EmptyPassphrase);
+    }
+
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut nonce);
+
+    let header = format!("{}{}", ENCRYPTED_KEY_HEADER, ENCRYPTED_KEY_VERSION);
+    let key = derive_key(passphrase, &salt)?;
+    let ciphertext = XChaCha20Poly1305::new(Key::from_slice(&key))
+        .encrypt(
+            XNonce::from_slice(&nonce),
+            Payload {
+                msg: seed,
+                aad: header.as_bytes(),
+            },
+        )
+        .map_err(|_| KeyPairError::EncryptionFailed)?;
+
+    let mut sealed = Vec::with_capacity(SALT_LEN + NONCE_LEN + ciphertext.len());
+    sealed.extend_from_slice(&salt);
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&ciphertext);
+    Ok(format!("{}:{}", header, BASE64.encode(sealed)))
+}
+
+/// Entschlüsselt den Seed aus dem Inhalt einer verschlüsselten Key-Datei
+fn open_seed(contents: &str, passphrase: &str) -> Result<[u8; 32], KeyPairError> {
+    let rest = contents
+        .strip_prefix(ENCRYPTED_KEY_HEADER)
+        .ok_or(KeyPairError::NotEncrypted)?;
+    let (version, encoded) = rest
+        .split_once(':')
+        .ok_or_else(|| KeyPairError::UnsupportedKeyFormat(rest.to_string()))?;
+    if version != ENCRYPTED_KEY_VERSION {
+        return Err(KeyPairError::UnsupportedKeyFormat(version.to_string()));
+    }
+
+    let sealed = BASE64.decode(encoded)?;
+    if sealed.len() < SALT_LEN + NONCE_LEN {
+        return Err(KeyPairError::DecryptionFailed);
+    }
+    let (salt, rest) = sealed.split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+
+    let header = format!("{}{}", ENCRYPTED_KEY_HEADER, version);
+    let key = derive_key(passphrase, salt)?;
+    let seed = XChaCha20Poly1305::new(Key::from_slice(&key))
+        .decrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: header.as_bytes(),
+            },
+        )
+        .map_err(|_| KeyPairError::DecryptionFailed)?;
+
+    let len = seed.len();
+    seed.try_into()
+        .map_err(|_| KeyPairError::InvalidKeyLength(len))
+}
+
+/// Leitet den Datei-Schlüssel mit Argon2id (Standardparameter) ab
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], KeyPairError> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| KeyPairError::KeyDerivation(e.to_string()))?;
+    Ok(key)
+}
+
+/// Schreibt eine Key-Datei atomar (temporäre Datei, dann Umbenennen)
+///
+/// Unter Unix ist die Datei von Anfang an nur für den Owner lesbar.
+fn write_key_file(path: &Path, contents: &str) -> Result<(), KeyPairError> {
+    // Parent-Verzeichnis erstellen falls nicht vorhanden
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let tmp_path = path.with_extension("tmp");
+    let _ = fs::remove_file(&tmp_path);
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600); // Nur Owner kann lesen/schreiben
+    }
+
+    let mut file = options.open(&tmp_path)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()?;
+    drop(file);
+
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
 impl std::fmt::Debug for KeyPair {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("KeyPair")
@@ -202,7 +630,6 @@ impl std::fmt::Debug for KeyPair {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use ed25519_dalek::Verifier;
 
     #[test]
     fn test_keypair_generation() {
@@ -253,4 +680,175 @@ mod tests {
         assert!(!signature.is_empty());
         assert!(BASE64.decode(&signature).is_ok());
     }
+
+    #[test]
+    fn test_verify_json_message() {
+        let keypair = KeyPair::generate();
+        let public_key = keypair.public_key_base64();
+
+        let payload = serde_json::json!({
+            "type": "ice_candidate",
+            "fromPeerId": "peer-alice",
+            "toPeerId": "peer-bob",
+            "candidate": "candidate:1 1 udp 2122260223 192.168.1.2 54321 typ host",
+            "timestamp": 1234567890
+        });
+        let signature = keypair.sign_message(&payload);
+
+        // Feldreihenfolge und mitgesendete Signatur spielen keine Rolle
+        let mut reordered = serde_json::json!({
+            "timestamp": 1234567890,
+            "toPeerId": "peer-bob",
+            "candidate": "candidate:1 1 udp 2122260223 192.168.1.2 54321 typ host",
+            "fromPeerId": "peer-alice",
+            "type": "ice_candidate"
+        });
+        reordered["signature"] = serde_json::Value::String(signature.clone());
+        assert!(KeyPair::verify_message(&public_key, &reordered, &signature).unwrap());
+
+        // Veränderter Inhalt oder fremder Key
+        let mut tampered = payload.clone();
+        tampered["candidate"] = "candidate:1 1 udp 2122260223 6.6.6.6 4444 typ host".into();
+        assert!(!KeyPair::verify_message(&public_key, &tampered, &signature).unwrap());
+        let other = KeyPair::generate().public_key_base64();
+        assert!(!KeyPair::verify_message(&other, &payload, &signature).unwrap());
+
+        // Kaputte Signatur oder kaputter Key sind Fehler
+        assert!(KeyPair::verify_message(&public_key, &payload, "not base64!").is_err());
+        assert!(KeyPair::verify_message("invalid", &payload, &signature).is_err());
+    }
+
+    #[test]
+    fn test_validate_public_key() {
+        let keypair = KeyPair::generate();
+        let public_key = keypair.public_key_base64();
+
+        assert_eq!(
+            KeyPair::validate_public_key(&format!(" {}\n", public_key)).unwrap(),
+            public_key
+        );
+        assert!(KeyPair::validate_public_key("not base64!").is_err());
+        assert!(KeyPair::validate_public_key(&BASE64.encode([1u8; 16])).is_err());
+    }
+
+    #[test]
+    fn test_dtls_fingerprint_signature() {
+        let keypair = KeyPair::generate();
+        let public_key = keypair.public_key_base64();
+        let signature = keypair.sign_dtls_fingerprint("sha-256", "ab:cd:ef");
+
+        // Groß-/Kleinschreibung des Fingerprints spielt keine Rolle
+        assert!(
+            KeyPair::verify_dtls_fingerprint(&public_key, "SHA-256", "AB:CD:EF", &signature)
+                .is_ok()
+        );
+        assert!(matches!(
+            KeyPair::verify_dtls_fingerprint(&public_key, "sha-256", "AB:CD:00", &signature),
+            Err(KeyPairError::InvalidSignature)
+        ));
+
+        let other = KeyPair::generate().public_key_base64();
+        assert!(
+            KeyPair::verify_dtls_fingerprint(&other, "sha-256", "AB:CD:EF", &signature).is_err()
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_identifies_the_key() {
+        let keypair = KeyPair::generate();
+        let fingerprint = keypair.fingerprint();
+
+        // 12 Gruppen zu je 5 Ziffern
+        let groups: Vec<&str> = fingerprint.split(' ').collect();
+        assert_eq!(groups.len(), FINGERPRINT_GROUPS);
+        assert!(groups
+            .iter()
+            .all(|group| group.len() == 5 && group.chars().all(|c| c.is_ascii_digit())));
+
+        // Gleicher Key, gleicher Fingerprint (auch mit Whitespace um den Key)
+        let public_key = keypair.public_key_base64();
+        assert_eq!(fingerprint_for(&public_key).unwrap(), fingerprint);
+        assert_eq!(
+            fingerprint_for(&format!(" {}\n", public_key)).unwrap(),
+            fingerprint
+        );
+
+        assert_ne!(KeyPair::generate().fingerprint(), fingerprint);
+        assert!(fingerprint_for("not base64!").is_err());
+    }
+
+    #[test]
+    fn test_load_or_create_reports_origin() {
+        let dir = std::env::temp_dir().join(format!("call-app-key-{}", uuid::Uuid::new_v4()));
+        let key_path = dir.join("private.key");
+
+        let (created, origin) = KeyPair::load_or_create_at(&key_path).unwrap();
+        assert_eq!(origin, KeyPairOrigin::Created);
+
+        let (loaded, origin) = KeyPair::load_or_create_at(&key_path).unwrap();
+        assert_eq!(origin, KeyPairOrigin::Loaded);
+        assert_eq!(created.public_key_base64(), loaded.public_key_base64());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_encrypted_key_round_trip() {
+        let dir = std::env::temp_dir().join(format!("call-app-key-{}", uuid::Uuid::new_v4()));
+        let key_path = dir.join("private.key");
+        let keypair = KeyPair::generate();
+
+        keypair.save_encrypted(&key_path, "correct horse").unwrap();
+        let contents = fs::read_to_string(&key_path).unwrap();
+        assert!(contents.starts_with("call-app-encrypted-key:v1:"));
+        assert!(!contents.contains(&BASE64.encode(keypair.signing_key.to_bytes())));
+
+        let loaded = KeyPair::load_encrypted(&key_path, "correct horse").unwrap();
+        assert_eq!(loaded.public_key_base64(), keypair.public_key_base64());
+
+        // Auto-Erkennung: verschlüsselte Datei wird nicht überschrieben
+        assert!(matches!(
+            KeyPair::load_or_create_at(&key_path),
+            Err(KeyPairError::PassphraseRequired)
+        ));
+        assert!(KeyPair::load_encrypted(&key_path, "correct horse").is_ok());
+
+        // Zurück zum Klartext-Format
+        keypair.save_to_file(&key_path).unwrap();
+        let (plain, origin) = KeyPair::load_or_create_at(&key_path).unwrap();
+        assert_eq!(origin, KeyPairOrigin::Loaded);
+        assert_eq!(plain.public_key_base64(), keypair.public_key_base64());
+        assert!(matches!(
+            KeyPair::load_encrypted(&key_path, "correct horse"),
+            Err(KeyPairError::NotEncrypted)
+        ));
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_wrong_passphrase_is_rejected() {
+        let keypair = KeyPair::generate();
+        let sealed = seal_seed(&keypair.signing_key.to_bytes(), "secret").unwrap();
+
+        assert!(matches!(
+            open_seed(&sealed, "Secret"),
+            Err(KeyPairError::DecryptionFailed)
+        ));
+        assert!(matches!(
+            seal_seed(&keypair.signing_key.to_bytes(), ""),
+            Err(KeyPairError::EmptyPassphrase)
+        ));
+
+        // Manipulierte Version ist nicht entschlüsselbar
+        let unknown = sealed.replacen(":v1:", ":v2:", 1);
+        assert!(matches!(
+            open_seed(&unknown, "secret"),
+            Err(KeyPairError::UnsupportedKeyFormat(_))
+        ));
+        assert_eq!(
+            open_seed(&sealed, "secret").unwrap(),
+            keypair.signing_key.to_bytes()
+        );
+    }
 }
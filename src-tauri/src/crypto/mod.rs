@@ -2,10 +2,16 @@
 //!
 //! Dieses Modul verwaltet die kryptographische Identität des Benutzers:
 //! - Generierung eines Ed25519 Schlüsselpaars beim ersten Start
-//! - Persistente Speicherung des Private Keys
+//! - Persistente Speicherung des Private Keys (optional passwortverschlüsselt)
 //! - Signierung von Nachrichten für den Signaling-Server
+//! - Kontaktkarten zum Teilen der eigenen Identität
+//! - Fingerprints der Public Keys zur Verifizierung über einen anderen Kanal
 //!
 
+mod contact_card;
+mod encoding;
 mod keypair;
 
-pub use keypair::{KeyPair, KeyPairError};
+pub use contact_card::{ContactCard, ContactCardError, CONTACT_CARD_PREFIX};
+pub use encoding::{decode_base64url, encode_base64url};
+pub use keypair::{fingerprint_for, KeyPair, KeyPairError, KeyPairOrigin};
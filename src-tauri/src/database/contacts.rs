@@ -3,12 +3,83 @@
 //! SQLite-Datenbank für lokale Kontaktverwaltung.
 //! Speichert peer_id, username und online-status.
 
+use crate::paths::app_data_dir;
 use parking_lot::Mutex;
-use rusqlite::{params, Connection, Result as SqliteResult};
+use rusqlite::{params, Connection, Result as SqliteResult, Row};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use thiserror::Error;
 
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Wie lange SQLite selbst auf eine gesperrte Datenbank wartet
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Maximale Länge einer Kontakt-Notiz in Zeichen
+pub const MAX_NOTES_LENGTH: usize = 1000;
+
+/// Schema-Migrationen, Migration `i` hebt die `user_version` auf `i + 1`
+///
+/// Nur neue Migrationen anhängen, bestehende nie ändern.
+const MIGRATIONS: &[&str] = &[
+    // 1: Notizen pro Kontakt
+    "ALTER TABLE contacts ADD COLUMN notes TEXT",
+    // 2: Nach einem Anruf automatisch angelegte Kontakte
+    "ALTER TABLE contacts ADD COLUMN auto_added INTEGER NOT NULL DEFAULT 0",
+    // 3: Sicherheitsnummer mit dem Kontakt abgeglichen
+    "ALTER TABLE contacts ADD COLUMN is_verified INTEGER NOT NULL DEFAULT 0",
+    // 4: Gelöschte Kontakte bleiben als Tombstone erhalten
+    "ALTER TABLE contacts ADD COLUMN deleted_at TEXT",
+    // 5: Public Key der Gegenstelle im Anrufverlauf (für Wahlwiederholung)
+    "ALTER TABLE call_history ADD COLUMN public_key TEXT",
+    // 6: Verpasste Anrufe als gesehen markieren (bestehende gelten als gesehen)
+    "ALTER TABLE call_history ADD COLUMN seen INTEGER NOT NULL DEFAULT 0;
+     UPDATE call_history SET seen = 1",
+    // 7: Metadaten der Gesprächsaufnahmen, verknüpft mit dem Anrufverlauf
+    "CREATE TABLE IF NOT EXISTS recordings (
+         id INTEGER PRIMARY KEY AUTOINCREMENT,
+         call_history_id INTEGER REFERENCES call_history(id) ON DELETE SET NULL,
+         peer_id TEXT NOT NULL,
+         path TEXT NOT NULL UNIQUE,
+         started_at INTEGER NOT NULL,
+         duration_ms INTEGER NOT NULL,
+         size_bytes INTEGER NOT NULL
+     );
+     CREATE INDEX IF NOT EXISTS idx_recordings_started_at ON recordings(started_at)",
+    // 8: Vom Nutzer konfigurierte TURN-Server
+    "CREATE TABLE IF NOT EXISTS turn_servers (
+         id INTEGER PRIMARY KEY AUTOINCREMENT,
+         url TEXT NOT NULL,
+         username TEXT NOT NULL,
+         credential TEXT NOT NULL
+     )",
+    // 9: Verlauf der Textnachrichten pro Peer
+    "CREATE TABLE IF NOT EXISTS messages (
+         id INTEGER PRIMARY KEY AUTOINCREMENT,
+         peer_id TEXT NOT NULL,
+         outgoing INTEGER NOT NULL,
+         body TEXT NOT NULL,
+         sent_at INTEGER NOT NULL
+     );
+     CREATE INDEX IF NOT EXISTS idx_messages_peer_id ON messages(peer_id, sent_at)",
+    // 10: Blockierte Kontakte (eingehende Anrufe werden abgewiesen)
+    "ALTER TABLE contacts ADD COLUMN blocked INTEGER NOT NULL DEFAULT 0",
+    // 11: Public Key pro Kontakt, bestehende übernehmen den gepinnten Key
+    "ALTER TABLE contacts ADD COLUMN public_key TEXT;
+     UPDATE contacts SET public_key =
+         (SELECT public_key FROM identity_keys WHERE identity_keys.peer_id = contacts.peer_id)",
+];
+
+/// Anzahl der Einträge in `get_missed_calls`
+pub const MISSED_CALLS_LIMIT: usize = 50;
+
+/// Spalten für `row_to_contact`, in dieser Reihenfolge
+const CONTACT_COLUMNS: &str = "id, peer_id, username, display_name, is_online, created_at, \
+     updated_at, notes, auto_added, is_verified, deleted_at, blocked, public_key";
+
 // ============================================================================
 // ERROR TYPES
 // ============================================================================
@@ -23,6 +94,12 @@ pub enum DatabaseError {
 
     #[error("Contact not found: {0}")]
     ContactNotFound(String),
+
+    #[error("Contact was deleted, restore it instead: {0}")]
+    ContactDeleted(String),
+
+    #[error("Notes too long: {0} characters (max {MAX_NOTES_LENGTH})")]
+    NotesTooLong(usize),
 }
 
 // ============================================================================
@@ -39,6 +116,155 @@ pub struct Contact {
     pub is_online: bool,
     pub created_at: String,
     pub updated_at: String,
+    /// Freitext-Notiz zum Kontakt
+    pub notes: Option<String>,
+    /// Automatisch nach einem Anruf angelegt (nicht manuell hinzugefügt)
+    pub auto_added: bool,
+    /// Sicherheitsnummer wurde vom Nutzer bestätigt
+    pub is_verified: bool,
+    /// Zeitpunkt des Löschens (nur bei gelöschten Kontakten gesetzt)
+    pub deleted_at: Option<String>,
+    /// Eingehende Anrufe werden automatisch abgewiesen
+    pub blocked: bool,
+    /// Public Key, an den die peer_id beim Hinzufügen gebunden wurde
+    pub public_key: Option<String>,
+}
+
+/// Offene Rückruf-Bitte eines anderen Peers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallbackRequest {
+    pub peer_id: String,
+    pub username: String,
+    /// Zeitpunkt der Anfrage (Unix-Millisekunden)
+    pub requested_at: i64,
+}
+
+/// Richtung eines Anrufs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CallDirection {
+    Outgoing,
+    Incoming,
+}
+
+impl CallDirection {
+    /// Wert in der Spalte `call_history.direction`
+    fn as_str(self) -> &'static str {
+        match self {
+            CallDirection::Outgoing => "outgoing",
+            CallDirection::Incoming => "incoming",
+        }
+    }
+}
+
+/// Gegenstelle des letzten ausgehenden Anrufs (für Wahlwiederholung)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LastDialed {
+    /// Peer-ID zum Zeitpunkt des Anrufs (kann inzwischen veraltet sein)
+    pub peer_id: String,
+    pub username: Option<String>,
+    /// Public Key, falls beim Anruf bekannt oder verifiziert
+    pub public_key: Option<String>,
+}
+
+/// Verpasster Anruf: eingehend, beendet, ohne Verbindungsaufbau
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MissedCall {
+    pub id: i64,
+    pub peer_id: String,
+    pub username: Option<String>,
+    /// Zeitpunkt des Anrufs (Unix-Millisekunden)
+    pub started_at: i64,
+    /// Bereits in der Liste angesehen (zählt nicht mehr für das Badge)
+    pub seen: bool,
+}
+
+/// Gespeicherte Gesprächsaufnahme
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Recording {
+    pub id: i64,
+    /// Zugehöriger Eintrag im Anrufverlauf (falls noch vorhanden)
+    pub call_history_id: Option<i64>,
+    pub peer_id: String,
+    /// Username aus dem Anrufverlauf
+    pub username: Option<String>,
+    /// Absoluter Pfad der Audiodatei
+    pub path: String,
+    /// Beginn der Aufnahme (Unix-Millisekunden)
+    pub started_at: i64,
+    pub duration_ms: i64,
+    pub size_bytes: i64,
+}
+
+/// Neue Aufnahme zum Eintragen in die Datenbank
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NewRecording {
+    pub call_history_id: Option<i64>,
+    pub peer_id: String,
+    pub path: String,
+    pub started_at: i64,
+    pub duration_ms: i64,
+    pub size_bytes: i64,
+}
+
+/// Vom Nutzer konfigurierter TURN-Server
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TurnServer {
+    pub url: String,
+    pub username: String,
+    pub credential: String,
+}
+
+/// Gespeicherte Textnachricht
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub id: i64,
+    pub peer_id: String,
+    /// Von uns gesendet (sonst empfangen)
+    pub outgoing: bool,
+    pub body: String,
+    /// Zeitpunkt des Sendens bzw. Empfangs (Unix-Millisekunden)
+    pub sent_at: i64,
+}
+
+/// Zusammengefasste Nutzungsstatistik über alle Anrufe
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageStats {
+    /// Alle ausgehenden Anrufversuche
+    pub calls_made: i64,
+    /// Angenommene eingehende Anrufe
+    pub calls_received: i64,
+    /// Summe der Gesprächsdauer (ab Verbindungsaufbau) in Sekunden
+    pub total_talk_time_secs: i64,
+    /// Anzahl gespeicherter Kontakte
+    pub contact_count: i64,
+}
+
+/// Sortierung der Kontaktliste
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContactSort {
+    /// Nach Username
+    #[default]
+    Alphabetical,
+    /// Zuletzt angerufene zuerst (ein- oder ausgehend), nie angerufene am Ende
+    LastCalled,
+    /// Häufigste Gesprächspartner zuerst (Anzahl der Anrufe im Verlauf)
+    Favorites,
+}
+
+impl ContactSort {
+    /// ORDER BY-Klausel für die Abfrage in `get_contacts_sorted`
+    fn order_by(self) -> &'static str {
+        match self {
+            Self::Alphabetical => "username ASC",
+            Self::LastCalled => "last_called_at IS NULL, last_called_at DESC, username ASC",
+            Self::Favorites => {
+                "COALESCE(call_count, 0) DESC, last_called_at IS NULL, last_called_at DESC, \
+                 username ASC"
+            }
+        }
+    }
 }
 
 /// Neuer Kontakt ohne ID (für INSERT)
@@ -47,6 +273,69 @@ pub struct NewContact {
     pub peer_id: String,
     pub username: String,
     pub display_name: Option<String>,
+    /// Vom Server gemeldeter Public Key (wird gepinnt, falls noch keiner bekannt)
+    pub public_key: Option<String>,
+}
+
+/// Kontakt aus einem Import (z.B. einem älteren Backup)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportedContact {
+    pub peer_id: String,
+    pub username: String,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub notes: Option<String>,
+}
+
+/// Umgang mit Kontakten, die beim Import bereits existieren (pro Feld)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    /// Bestehende Kontakte bleiben unverändert, nur neue werden angelegt
+    KeepExisting,
+    /// Importierte Werte ersetzen bestehende (leere Felder im Import löschen nichts)
+    PreferImported,
+    /// Bestehende Werte bleiben, leere Felder werden aus dem Import ergänzt
+    Merge,
+}
+
+impl ConflictPolicy {
+    /// Wählt den Wert eines Feldes aus bestehendem und importiertem Wert
+    fn resolve(self, existing: Option<&str>, imported: Option<&str>) -> Option<String> {
+        let chosen = match self {
+            ConflictPolicy::KeepExisting => existing,
+            ConflictPolicy::PreferImported => imported.or(existing),
+            ConflictPolicy::Merge => existing.or(imported),
+        };
+        chosen.map(str::to_string)
+    }
+}
+
+/// Durch einen Import geändertes Feld
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldChange {
+    pub field: &'static str,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+/// Durch einen Import geänderter Kontakt
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ContactChange {
+    pub peer_id: String,
+    pub changes: Vec<FieldChange>,
+}
+
+/// Ergebnis eines Kontakt-Imports
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ImportReport {
+    /// Neu angelegte Kontakte (Peer-IDs)
+    pub added: Vec<String>,
+    pub updated: Vec<ContactChange>,
+    pub unchanged: Vec<String>,
+    /// Gelöschte Kontakte werden nicht per Import wiederhergestellt
+    pub skipped_deleted: Vec<String>,
 }
 
 // ============================================================================
@@ -65,8 +354,12 @@ unsafe impl Sync for ContactsDatabase {}
 impl ContactsDatabase {
     /// Öffnet oder erstellt die Datenbank
     pub fn open() -> Result<Self, DatabaseError> {
-        let db_path = Self::get_database_path()?;
+        let db_path = Self::get_database_path();
+        Self::open_at(&db_path)
+    }
 
+    /// Öffnet oder erstellt die Datenbank unter einem bestimmten Pfad
+    pub fn open_at(db_path: &Path) -> Result<Self, DatabaseError> {
         // Parent-Verzeichnis erstellen
         if let Some(parent) = db_path.parent() {
             std::fs::create_dir_all(parent)?;
@@ -74,7 +367,13 @@ impl ContactsDatabase {
 
         tracing::info!("Opening database at {:?}", db_path);
 
-        let conn = Connection::open(&db_path)?;
+        let conn = Connection::open(db_path)?;
+
+        // WAL erlaubt gleichzeitiges Lesen während geschrieben wird,
+        // busy_timeout lässt SQLite kurze Sperren selbst abwarten
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))?;
+
         let db = Self {
             conn: Mutex::new(conn),
         };
@@ -94,19 +393,24 @@ impl ContactsDatabase {
         Ok(db)
     }
 
-    /// Ermittelt den Pfad zur Datenbank-Datei
-    fn get_database_path() -> Result<PathBuf, DatabaseError> {
-        let proj_dirs =
-            directories::ProjectDirs::from("com", "kaufm", "call-app").ok_or_else(|| {
-                std::io::Error::new(
-                    std::io::ErrorKind::NotFound,
-                    "Could not determine app data directory",
-                )
-            })?;
-
-        let mut path = proj_dirs.data_dir().to_path_buf();
+    /// Ermittelt den Pfad zur Datenbank-Datei (siehe `paths::app_data_dir`)
+    fn get_database_path() -> PathBuf {
+        let mut path = app_data_dir();
         path.push("contacts.db");
-        Ok(path)
+        path
+    }
+
+    /// Führt eine Schreib-Operation auf der Connection aus
+    ///
+    /// Alle Zugriffe laufen über denselben Connection-Lock. Sperren durch
+    /// andere Prozesse wartet SQLite über `busy_timeout` selbst ab, ohne
+    /// zusätzliches Wiederholen, das den aufrufenden Thread blockieren würde.
+    fn write<T>(
+        &self,
+        op: impl FnOnce(&Connection) -> SqliteResult<T>,
+    ) -> Result<T, DatabaseError> {
+        let conn = self.conn.lock();
+        op(&conn).map_err(DatabaseError::from)
     }
 
     /// Initialisiert das Datenbank-Schema
@@ -142,50 +446,268 @@ impl ContactsDatabase {
             [],
         )?;
 
-        Ok(())
-    }
+        // Offene Rückruf-Bitten, eine pro Peer
+        conn.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS callback_requests (
+                peer_id TEXT PRIMARY KEY,
+                username TEXT NOT NULL,
+                requested_at INTEGER NOT NULL
+            )
+            "#,
+            [],
+        )?;
+
+        // Gepinnte Ed25519 Public Keys (Trust on First Use), unabhängig von der
+        // Kontaktliste, damit auch Anrufe von Nicht-Kontakten geprüft werden
+        conn.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS identity_keys (
+                peer_id TEXT PRIMARY KEY,
+                public_key TEXT NOT NULL,
+                pinned_at TEXT NOT NULL DEFAULT (datetime('now'))
+            )
+            "#,
+            [],
+        )?;
+
+        // Anrufverlauf, Zeitstempel in Unix-Millisekunden
+        conn.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS call_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                peer_id TEXT NOT NULL,
+                username TEXT,
+                direction TEXT NOT NULL,
+                started_at INTEGER NOT NULL,
+                connected_at INTEGER,
+                ended_at INTEGER,
+                duration_secs INTEGER NOT NULL DEFAULT 0
+            )
+            "#,
+            [],
+        )?;
 
-    /// Fügt einen neuen Kontakt hinzu
-    pub fn add_contact(&self, contact: NewContact) -> Result<Contact, DatabaseError> {
-        let conn = self.conn.lock();
         conn.execute(
             r#"
-            INSERT INTO contacts (peer_id, username, display_name, is_online)
-            VALUES (?1, ?2, ?3, 0)
-            ON CONFLICT(peer_id) DO UPDATE SET
-                username = excluded.username,
-                display_name = COALESCE(excluded.display_name, display_name),
-                updated_at = datetime('now')
+            CREATE INDEX IF NOT EXISTS idx_call_history_peer_id ON call_history(peer_id)
             "#,
-            params![contact.peer_id, contact.username, contact.display_name],
+            [],
         )?;
 
+        Self::run_migrations(&conn)?;
+
+        Ok(())
+    }
+
+    /// Wendet alle noch nicht angewendeten Schema-Migrationen an
+    ///
+    /// Der Stand wird in `PRAGMA user_version` gespeichert, jede Migration
+    /// läuft in einer eigenen Transaktion.
+    fn run_migrations(conn: &Connection) -> Result<(), DatabaseError> {
+        let version: i64 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+
+        for (index, migration) in MIGRATIONS.iter().enumerate().skip(version.max(0) as usize) {
+            let tx = conn.unchecked_transaction()?;
+            tx.execute_batch(migration)?;
+            tx.pragma_update(None, "user_version", (index + 1) as i64)?;
+            tx.commit()?;
+            tracing::info!("Applied database migration {}", index + 1);
+        }
+
+        Ok(())
+    }
+
+    /// Liest einen Kontakt aus einer Zeile mit `CONTACT_COLUMNS`
+    fn row_to_contact(row: &Row) -> SqliteResult<Contact> {
+        Ok(Contact {
+            id: row.get(0)?,
+            peer_id: row.get(1)?,
+            username: row.get(2)?,
+            display_name: row.get(3)?,
+            is_online: row.get::<_, i32>(4)? != 0,
+            created_at: row.get(5)?,
+            updated_at: row.get(6)?,
+            notes: row.get(7)?,
+            auto_added: row.get::<_, i32>(8)? != 0,
+            is_verified: row.get::<_, i32>(9)? != 0,
+            deleted_at: row.get(10)?,
+            blocked: row.get::<_, i32>(11)? != 0,
+            public_key: row.get(12)?,
+        })
+    }
+
+    /// Fügt einen neuen Kontakt hinzu
+    ///
+    /// Ein gelöschter Kontakt wird nicht stillschweigend wiederhergestellt,
+    /// dafür gibt es `restore_contact`.
+    ///
+    /// Der Public Key wird wie bei `pin_identity_key` nur beim ersten Mal
+    /// übernommen: Ein bereits gepinnter Key hat Vorrang vor dem übergebenen.
+    pub fn add_contact(&self, contact: NewContact) -> Result<Contact, DatabaseError> {
+        let changed = self.write(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            if let Some(public_key) = &contact.public_key {
+                tx.execute(
+                    r#"
+                    INSERT OR IGNORE INTO identity_keys (peer_id, public_key)
+                    VALUES (?1, ?2)
+                    "#,
+                    params![contact.peer_id, public_key],
+                )?;
+            }
+            let changed = tx.execute(
+                r#"
+                INSERT INTO contacts (peer_id, username, display_name, is_online, public_key)
+                VALUES (?1, ?2, ?3, 0,
+                        (SELECT public_key FROM identity_keys WHERE peer_id = ?1))
+                ON CONFLICT(peer_id) DO UPDATE SET
+                    username = excluded.username,
+                    display_name = COALESCE(excluded.display_name, display_name),
+                    public_key = COALESCE(public_key, excluded.public_key),
+                    auto_added = 0,
+                    updated_at = datetime('now')
+                WHERE deleted_at IS NULL
+                "#,
+                params![contact.peer_id, contact.username, contact.display_name],
+            )?;
+            tx.commit()?;
+            Ok(changed)
+        })?;
+
+        if changed == 0 {
+            return Err(DatabaseError::ContactDeleted(contact.peer_id));
+        }
+        let conn = self.conn.lock();
         Self::get_contact_by_peer_id_inner(&conn, &contact.peer_id)
     }
 
+    /// Importiert Kontakte, bestehende werden nach `policy` abgeglichen
+    ///
+    /// Läuft in einer Transaktion: Entweder wird der gesamte Import
+    /// übernommen oder nichts. Gelöschte Kontakte bleiben gelöscht.
+    pub fn import_contacts(
+        &self,
+        contacts: &[ImportedContact],
+        policy: ConflictPolicy,
+    ) -> Result<ImportReport, DatabaseError> {
+        for contact in contacts {
+            if let Some(length) = contact.notes.as_ref().map(|n| n.trim().chars().count()) {
+                if length > MAX_NOTES_LENGTH {
+                    return Err(DatabaseError::NotesTooLong(length));
+                }
+            }
+        }
+
+        self.write(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            let mut report = ImportReport::default();
+
+            for imported in contacts {
+                let existing = tx.query_row(
+                    &format!(
+                        "SELECT {} FROM contacts WHERE peer_id = ?1",
+                        CONTACT_COLUMNS
+                    ),
+                    params![imported.peer_id],
+                    Self::row_to_contact,
+                );
+                let existing = match existing {
+                    Ok(existing) => existing,
+                    Err(rusqlite::Error::QueryReturnedNoRows) => {
+                        tx.execute(
+                            r#"
+                            INSERT INTO contacts (peer_id, username, display_name, notes, is_online)
+                            VALUES (?1, ?2, ?3, ?4, 0)
+                            "#,
+                            params![
+                                imported.peer_id,
+                                imported.username,
+                                non_empty(imported.display_name.as_deref()),
+                                non_empty(imported.notes.as_deref())
+                            ],
+                        )?;
+                        report.added.push(imported.peer_id.clone());
+                        continue;
+                    }
+                    Err(e) => return Err(e),
+                };
+
+                if existing.deleted_at.is_some() {
+                    report.skipped_deleted.push(existing.peer_id);
+                    continue;
+                }
+
+                let changes = merge_imported_contact(&existing, imported, policy);
+                if changes.is_empty() {
+                    report.unchanged.push(existing.peer_id);
+                    continue;
+                }
+
+                let new_value = |field: &str, old: Option<String>| {
+                    changes
+                        .iter()
+                        .find(|change| change.field == field)
+                        .map_or(old, |change| change.new.clone())
+                };
+                tx.execute(
+                    r#"
+                    UPDATE contacts
+                    SET username = ?2, display_name = ?3, notes = ?4, updated_at = datetime('now')
+                    WHERE peer_id = ?1
+                    "#,
+                    params![
+                        existing.peer_id,
+                        new_value("username", Some(existing.username.clone())),
+                        new_value("display_name", existing.display_name.clone()),
+                        new_value("notes", existing.notes.clone())
+                    ],
+                )?;
+                report.updated.push(ContactChange {
+                    peer_id: existing.peer_id,
+                    changes,
+                });
+            }
+
+            tx.commit()?;
+            Ok(report)
+        })
+    }
+
+    /// Legt einen Kontakt nach einem Anruf automatisch an
+    ///
+    /// Bestehende (auch gelöschte) Kontakte bleiben unverändert. Gibt den neuen
+    /// Kontakt zurück, oder `None`, wenn es den Kontakt bereits gab.
+    pub fn add_auto_contact(&self, contact: NewContact) -> Result<Option<Contact>, DatabaseError> {
+        let inserted = self.write(|conn| {
+            conn.execute(
+                r#"
+                INSERT INTO contacts (peer_id, username, display_name, is_online, auto_added)
+                VALUES (?1, ?2, ?3, 1, 1)
+                ON CONFLICT(peer_id) DO NOTHING
+                "#,
+                params![contact.peer_id, contact.username, contact.display_name],
+            )
+        })?;
+
+        if inserted == 0 {
+            return Ok(None);
+        }
+        self.get_contact_by_peer_id(&contact.peer_id).map(Some)
+    }
+
     /// Interne Hilfsfunktion mit Connection-Referenz
     fn get_contact_by_peer_id_inner(
         conn: &Connection,
         peer_id: &str,
     ) -> Result<Contact, DatabaseError> {
         conn.query_row(
-            r#"
-            SELECT id, peer_id, username, display_name, is_online, created_at, updated_at
-            FROM contacts
-            WHERE peer_id = ?1
-            "#,
+            &format!(
+                "SELECT {} FROM contacts WHERE peer_id = ?1 AND deleted_at IS NULL",
+                CONTACT_COLUMNS
+            ),
             params![peer_id],
-            |row| {
-                Ok(Contact {
-                    id: row.get(0)?,
-                    peer_id: row.get(1)?,
-                    username: row.get(2)?,
-                    display_name: row.get(3)?,
-                    is_online: row.get::<_, i32>(4)? != 0,
-                    created_at: row.get(5)?,
-                    updated_at: row.get(6)?,
-                })
-            },
+            Self::row_to_contact,
         )
         .map_err(|e| match e {
             rusqlite::Error::QueryReturnedNoRows => {
@@ -195,64 +717,123 @@ impl ContactsDatabase {
         })
     }
 
-    /// Holt einen Kontakt anhand der Peer-ID
+    /// Holt einen Kontakt anhand der Peer-ID (gelöschte zählen als nicht vorhanden)
     pub fn get_contact_by_peer_id(&self, peer_id: &str) -> Result<Contact, DatabaseError> {
         let conn = self.conn.lock();
         Self::get_contact_by_peer_id_inner(&conn, peer_id)
     }
 
-    /// Holt alle Kontakte
+    /// Holt alle Kontakte (ohne gelöschte)
     pub fn get_all_contacts(&self) -> Result<Vec<Contact>, DatabaseError> {
         let conn = self.conn.lock();
-        let mut stmt = conn.prepare(
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM contacts WHERE deleted_at IS NULL ORDER BY username ASC",
+            CONTACT_COLUMNS
+        ))?;
+
+        let contacts = stmt
+            .query_map([], Self::row_to_contact)?
+            .collect::<SqliteResult<Vec<Contact>>>()?;
+
+        Ok(contacts)
+    }
+
+    /// Holt alle Kontakte (ohne gelöschte) in der gewünschten Sortierung
+    ///
+    /// Der Anrufverlauf wird per LEFT JOIN einbezogen, Kontakte ohne Anruf
+    /// bleiben also enthalten und landen bei `LastCalled` am Ende.
+    pub fn get_contacts_sorted(&self, sort: ContactSort) -> Result<Vec<Contact>, DatabaseError> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(&format!(
             r#"
-            SELECT id, peer_id, username, display_name, is_online, created_at, updated_at
-            FROM contacts
-            ORDER BY username ASC
+            SELECT {} FROM contacts
+            LEFT JOIN (
+                SELECT peer_id AS call_peer_id,
+                       MAX(started_at) AS last_called_at,
+                       COUNT(*) AS call_count
+                FROM call_history
+                GROUP BY peer_id
+            ) ON call_peer_id = contacts.peer_id
+            WHERE deleted_at IS NULL
+            ORDER BY {}
             "#,
-        )?;
+            CONTACT_COLUMNS,
+            sort.order_by()
+        ))?;
 
         let contacts = stmt
-            .query_map([], |row| {
-                Ok(Contact {
-                    id: row.get(0)?,
-                    peer_id: row.get(1)?,
-                    username: row.get(2)?,
-                    display_name: row.get(3)?,
-                    is_online: row.get::<_, i32>(4)? != 0,
-                    created_at: row.get(5)?,
-                    updated_at: row.get(6)?,
-                })
-            })?
+            .query_map([], Self::row_to_contact)?
             .collect::<SqliteResult<Vec<Contact>>>()?;
 
         Ok(contacts)
     }
 
-    /// Aktualisiert den Online-Status eines Kontakts
-    pub fn set_online_status(&self, peer_id: &str, is_online: bool) -> Result<(), DatabaseError> {
+    /// Sucht Kontakte (ohne gelöschte) nach Teilen von Username oder Display-Namen
+    ///
+    /// Groß- und Kleinschreibung wird ignoriert, `%` und `_` in der Suche
+    /// gelten wörtlich. Online-Kontakte kommen zuerst, danach alphabetisch.
+    /// Eine leere Suche liefert alle Kontakte.
+    pub fn search_contacts(&self, query: &str) -> Result<Vec<Contact>, DatabaseError> {
+        let pattern = format!("%{}%", escape_like(query.trim()));
         let conn = self.conn.lock();
-        conn.execute(
+        let mut stmt = conn.prepare(&format!(
             r#"
-            UPDATE contacts
-            SET is_online = ?2, updated_at = datetime('now')
-            WHERE peer_id = ?1
+            SELECT {} FROM contacts
+            WHERE deleted_at IS NULL
+              AND (username LIKE ?1 ESCAPE '\' OR display_name LIKE ?1 ESCAPE '\')
+            ORDER BY is_online DESC, username ASC
             "#,
-            params![peer_id, is_online as i32],
-        )?;
+            CONTACT_COLUMNS
+        ))?;
+
+        let contacts = stmt
+            .query_map(params![pattern], Self::row_to_contact)?
+            .collect::<SqliteResult<Vec<Contact>>>()?;
+
+        Ok(contacts)
+    }
+
+    /// Holt alle gelöschten Kontakte (zuletzt gelöschte zuerst)
+    pub fn get_deleted_contacts(&self) -> Result<Vec<Contact>, DatabaseError> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM contacts WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC",
+            CONTACT_COLUMNS
+        ))?;
+
+        let contacts = stmt
+            .query_map([], Self::row_to_contact)?
+            .collect::<SqliteResult<Vec<Contact>>>()?;
+
+        Ok(contacts)
+    }
+
+    /// Aktualisiert den Online-Status eines Kontakts
+    pub fn set_online_status(&self, peer_id: &str, is_online: bool) -> Result<(), DatabaseError> {
+        self.write(|conn| {
+            conn.execute(
+                r#"
+                UPDATE contacts
+                SET is_online = ?2, updated_at = datetime('now')
+                WHERE peer_id = ?1
+                "#,
+                params![peer_id, is_online as i32],
+            )
+        })?;
         Ok(())
     }
 
     /// Setzt alle Kontakte auf offline
     pub fn set_all_offline(&self) -> Result<(), DatabaseError> {
-        let conn = self.conn.lock();
-        conn.execute(
-            r#"
-            UPDATE contacts
-            SET is_online = 0, updated_at = datetime('now')
-            "#,
-            [],
-        )?;
+        self.write(|conn| {
+            conn.execute(
+                r#"
+                UPDATE contacts
+                SET is_online = 0, updated_at = datetime('now')
+                "#,
+                [],
+            )
+        })?;
         Ok(())
     }
 
@@ -262,65 +843,806 @@ impl ContactsDatabase {
         peer_id: &str,
         display_name: Option<&str>,
     ) -> Result<(), DatabaseError> {
-        let conn = self.conn.lock();
-        conn.execute(
-            r#"
-            UPDATE contacts
-            SET display_name = ?2, updated_at = datetime('now')
-            WHERE peer_id = ?1
-            "#,
-            params![peer_id, display_name],
-        )?;
+        self.write(|conn| {
+            conn.execute(
+                r#"
+                UPDATE contacts
+                SET display_name = ?2, updated_at = datetime('now')
+                WHERE peer_id = ?1
+                "#,
+                params![peer_id, display_name],
+            )
+        })?;
         Ok(())
     }
 
-    /// Löscht einen Kontakt
-    pub fn delete_contact(&self, peer_id: &str) -> Result<(), DatabaseError> {
-        let conn = self.conn.lock();
-        conn.execute(
-            r#"
-            DELETE FROM contacts
-            WHERE peer_id = ?1
-            "#,
-            params![peer_id],
-        )?;
-        Ok(())
+    /// Übernimmt den Anzeigenamen aus dem Verzeichnis, falls keiner gesetzt ist
+    ///
+    /// Ein selbst vergebener Name wird nie überschrieben. Gibt zurück, ob der
+    /// Kontakt geändert wurde.
+    pub fn fill_display_name(
+        &self,
+        peer_id: &str,
+        display_name: &str,
+    ) -> Result<bool, DatabaseError> {
+        let updated = self.write(|conn| {
+            conn.execute(
+                r#"
+                UPDATE contacts
+                SET display_name = ?2, updated_at = datetime('now')
+                WHERE peer_id = ?1 AND deleted_at IS NULL
+                    AND (display_name IS NULL OR display_name = '')
+                "#,
+                params![peer_id, display_name],
+            )
+        })?;
+        Ok(updated > 0)
     }
-}
 
-// ============================================================================
-// TESTS
-// ============================================================================
+    /// Setzt die Notiz eines Kontakts
+    ///
+    /// Leere Notizen werden als `NULL` gespeichert.
+    pub fn set_contact_notes(
+        &self,
+        peer_id: &str,
+        notes: Option<&str>,
+    ) -> Result<Contact, DatabaseError> {
+        let notes = notes.map(str::trim).filter(|n| !n.is_empty());
+        if let Some(notes) = notes {
+            let length = notes.chars().count();
+            if length > MAX_NOTES_LENGTH {
+                return Err(DatabaseError::NotesTooLong(length));
+            }
+        }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        self.write(|conn| {
+            conn.execute(
+                r#"
+                UPDATE contacts
+                SET notes = ?2, updated_at = datetime('now')
+                WHERE peer_id = ?1
+                "#,
+                params![peer_id, notes],
+            )
+        })?;
 
-    #[test]
-    fn test_add_and_get_contact() {
-        let db = ContactsDatabase::open_in_memory().unwrap();
+        self.get_contact_by_peer_id(peer_id)
+    }
 
-        let new_contact = NewContact {
-            peer_id: "test-peer-id".to_string(),
-            username: "alice".to_string(),
-            display_name: Some("Alice".to_string()),
-        };
+    /// Markiert einen Kontakt als verifiziert (oder hebt die Markierung auf)
+    pub fn mark_verified(&self, peer_id: &str, verified: bool) -> Result<Contact, DatabaseError> {
+        let updated = self.write(|conn| {
+            conn.execute(
+                r#"
+                UPDATE contacts
+                SET is_verified = ?2, updated_at = datetime('now')
+                WHERE peer_id = ?1
+                "#,
+                params![peer_id, verified as i32],
+            )
+        })?;
 
-        let contact = db.add_contact(new_contact).unwrap();
-        assert_eq!(contact.username, "alice");
-        assert_eq!(contact.display_name, Some("Alice".to_string()));
-        assert!(!contact.is_online);
+        if updated == 0 {
+            return Err(DatabaseError::ContactNotFound(peer_id.to_string()));
+        }
+        self.get_contact_by_peer_id(peer_id)
     }
 
-    #[test]
-    fn test_online_status() {
-        let db = ContactsDatabase::open_in_memory().unwrap();
-
-        let new_contact = NewContact {
-            peer_id: "test-peer".to_string(),
-            username: "bob".to_string(),
-            display_name: None,
-        };
+    /// Blockiert einen Kontakt (oder hebt die Blockierung auf)
+    pub fn set_blocked(&self, peer_id: &str, blocked: bool) -> Result<Contact, DatabaseError> {
+        let updated = self.write(|conn| {
+            conn.execute(
+                r#"
+                UPDATE contacts
+                SET blocked = ?2, updated_at = datetime('now')
+                WHERE peer_id = ?1 AND deleted_at IS NULL
+                "#,
+                params![peer_id, blocked as i32],
+            )
+        })?;
+
+        if updated == 0 {
+            return Err(DatabaseError::ContactNotFound(peer_id.to_string()));
+        }
+        self.get_contact_by_peer_id(peer_id)
+    }
+
+    /// Prüft, ob ein Peer blockiert ist
+    ///
+    /// Die Blockierung bleibt auch für gelöschte Kontakte bestehen, aufheben
+    /// lässt sie sich erst nach `restore_contact`.
+    pub fn is_blocked(&self, peer_id: &str) -> Result<bool, DatabaseError> {
+        let conn = self.conn.lock();
+        let result = conn.query_row(
+            "SELECT blocked FROM contacts WHERE peer_id = ?1",
+            params![peer_id],
+            |row| row.get::<_, i32>(0),
+        );
+
+        match result {
+            Ok(blocked) => Ok(blocked != 0),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(false),
+            Err(e) => Err(DatabaseError::Sqlite(e)),
+        }
+    }
+
+    /// Weist einen eingehenden Anruf ab, wenn der Anrufer blockiert ist
+    ///
+    /// `reject` sendet die Ablehnung über den Weg des Anrufs (Signaling oder
+    /// LAN). Gibt `true` zurück, wenn der Anruf abgewiesen wurde und nicht
+    /// klingeln darf. Datenbankfehler zählen als nicht blockiert.
+    pub fn reject_if_blocked(&self, peer_id: &str, reject: impl FnOnce()) -> bool {
+        let blocked = self.is_blocked(peer_id).unwrap_or_else(|e| {
+            tracing::warn!("Failed to check block status of {}: {}", peer_id, e);
+            false
+        });
+        if blocked {
+            tracing::info!("Rejecting call from blocked peer {}", peer_id);
+            reject();
+        }
+        blocked
+    }
+
+    /// Speichert eine Rückruf-Bitte (eine neuere ersetzt eine ältere desselben Peers)
+    pub fn add_callback_request(&self, request: &CallbackRequest) -> Result<(), DatabaseError> {
+        self.write(|conn| {
+            conn.execute(
+                r#"
+                INSERT INTO callback_requests (peer_id, username, requested_at)
+                VALUES (?1, ?2, ?3)
+                ON CONFLICT(peer_id) DO UPDATE SET
+                    username = excluded.username,
+                    requested_at = MAX(requested_at, excluded.requested_at)
+                "#,
+                params![request.peer_id, request.username, request.requested_at],
+            )
+        })?;
+        Ok(())
+    }
+
+    /// Gibt alle offenen Rückruf-Bitten zurück (neueste zuerst)
+    pub fn get_callback_requests(&self) -> Result<Vec<CallbackRequest>, DatabaseError> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT peer_id, username, requested_at
+            FROM callback_requests
+            ORDER BY requested_at DESC
+            "#,
+        )?;
+
+        let requests = stmt
+            .query_map([], |row| {
+                Ok(CallbackRequest {
+                    peer_id: row.get(0)?,
+                    username: row.get(1)?,
+                    requested_at: row.get(2)?,
+                })
+            })?
+            .collect::<SqliteResult<Vec<CallbackRequest>>>()?;
+
+        Ok(requests)
+    }
+
+    /// Entfernt die Rückruf-Bitte eines Peers
+    ///
+    /// Gibt `true` zurück, wenn eine Bitte entfernt wurde.
+    pub fn remove_callback_request(&self, peer_id: &str) -> Result<bool, DatabaseError> {
+        let removed = self.write(|conn| {
+            conn.execute(
+                r#"
+                DELETE FROM callback_requests
+                WHERE peer_id = ?1
+                "#,
+                params![peer_id],
+            )
+        })?;
+        Ok(removed > 0)
+    }
+
+    /// Gibt den gepinnten Public Key eines Peers zurück (falls vorhanden)
+    pub fn get_identity_key(&self, peer_id: &str) -> Result<Option<String>, DatabaseError> {
+        let conn = self.conn.lock();
+        let result = conn.query_row(
+            r#"
+            SELECT public_key FROM identity_keys WHERE peer_id = ?1
+            "#,
+            params![peer_id],
+            |row| row.get(0),
+        );
+
+        match result {
+            Ok(key) => Ok(Some(key)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(DatabaseError::Sqlite(e)),
+        }
+    }
+
+    /// Pinnt den Public Key eines Peers
+    ///
+    /// Ein bereits gepinnter Key wird nicht überschrieben. Gibt `true` zurück,
+    /// wenn der Key neu gepinnt wurde. Ein Kontakt ohne Key übernimmt ihn.
+    pub fn pin_identity_key(&self, peer_id: &str, public_key: &str) -> Result<bool, DatabaseError> {
+        let inserted = self.write(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            let inserted = tx.execute(
+                r#"
+                INSERT OR IGNORE INTO identity_keys (peer_id, public_key)
+                VALUES (?1, ?2)
+                "#,
+                params![peer_id, public_key],
+            )?;
+            if inserted > 0 {
+                tx.execute(
+                    r#"
+                    UPDATE contacts SET public_key = ?2
+                    WHERE peer_id = ?1 AND public_key IS NULL
+                    "#,
+                    params![peer_id, public_key],
+                )?;
+            }
+            tx.commit()?;
+            Ok(inserted)
+        })?;
+        Ok(inserted > 0)
+    }
+
+    /// Übernimmt nach einem Schlüsselwechsel den neuen Public Key eines Peers
+    ///
+    /// Anders als `pin_identity_key` ersetzt das den gepinnten Key und den beim
+    /// Kontakt gespeicherten Key. Die Verifizierung gilt dem alten Key und wird
+    /// aufgehoben.
+    pub fn accept_identity_key(
+        &self,
+        peer_id: &str,
+        public_key: &str,
+    ) -> Result<(), DatabaseError> {
+        self.write(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            tx.execute(
+                r#"
+                INSERT INTO identity_keys (peer_id, public_key)
+                VALUES (?1, ?2)
+                ON CONFLICT(peer_id) DO UPDATE SET
+                    public_key = excluded.public_key,
+                    pinned_at = datetime('now')
+                "#,
+                params![peer_id, public_key],
+            )?;
+            tx.execute(
+                r#"
+                UPDATE contacts
+                SET public_key = ?2, is_verified = 0, updated_at = datetime('now')
+                WHERE peer_id = ?1
+                "#,
+                params![peer_id, public_key],
+            )?;
+            tx.commit()
+        })
+    }
+
+    /// Löscht einen Kontakt (Soft-Delete)
+    ///
+    /// Der Kontakt bleibt als Tombstone erhalten, bis er mit `restore_contact`
+    /// wiederhergestellt oder mit `purge_deleted` endgültig entfernt wird.
+    pub fn delete_contact(&self, peer_id: &str) -> Result<(), DatabaseError> {
+        self.write(|conn| {
+            conn.execute(
+                r#"
+                UPDATE contacts
+                SET deleted_at = datetime('now'), updated_at = datetime('now')
+                WHERE peer_id = ?1 AND deleted_at IS NULL
+                "#,
+                params![peer_id],
+            )
+        })?;
+        Ok(())
+    }
+
+    /// Stellt einen gelöschten Kontakt wieder her
+    pub fn restore_contact(&self, peer_id: &str) -> Result<Contact, DatabaseError> {
+        let restored = self.write(|conn| {
+            conn.execute(
+                r#"
+                UPDATE contacts
+                SET deleted_at = NULL, updated_at = datetime('now')
+                WHERE peer_id = ?1 AND deleted_at IS NOT NULL
+                "#,
+                params![peer_id],
+            )
+        })?;
+
+        if restored == 0 {
+            return Err(DatabaseError::ContactNotFound(peer_id.to_string()));
+        }
+        self.get_contact_by_peer_id(peer_id)
+    }
+
+    /// Entfernt alle gelöschten Kontakte endgültig, gibt deren Anzahl zurück
+    pub fn purge_deleted(&self) -> Result<usize, DatabaseError> {
+        self.write(|conn| {
+            conn.execute(
+                r#"
+                DELETE FROM contacts
+                WHERE deleted_at IS NOT NULL
+                "#,
+                [],
+            )
+        })
+    }
+
+    /// Legt einen neuen Eintrag im Anrufverlauf an und gibt dessen ID zurück
+    pub fn start_call_record(
+        &self,
+        peer_id: &str,
+        username: Option<&str>,
+        direction: CallDirection,
+        started_at: i64,
+    ) -> Result<i64, DatabaseError> {
+        self.write(|conn| {
+            conn.execute(
+                r#"
+                INSERT INTO call_history (peer_id, username, direction, started_at)
+                VALUES (?1, ?2, ?3, ?4)
+                "#,
+                params![peer_id, username, direction.as_str(), started_at],
+            )?;
+            Ok(conn.last_insert_rowid())
+        })
+    }
+
+    /// Vermerkt den Verbindungsaufbau eines Anrufs
+    pub fn mark_call_connected(&self, id: i64, connected_at: i64) -> Result<(), DatabaseError> {
+        self.write(|conn| {
+            conn.execute(
+                r#"
+                UPDATE call_history
+                SET connected_at = ?2
+                WHERE id = ?1 AND connected_at IS NULL
+                "#,
+                params![id, connected_at],
+            )
+        })?;
+        Ok(())
+    }
+
+    /// Schließt einen Eintrag ab, die Gesprächsdauer zählt ab Verbindungsaufbau
+    pub fn finish_call_record(&self, id: i64, ended_at: i64) -> Result<(), DatabaseError> {
+        self.write(|conn| {
+            conn.execute(
+                r#"
+                UPDATE call_history
+                SET ended_at = ?2,
+                    duration_secs = CASE
+                        WHEN connected_at IS NULL THEN 0
+                        ELSE MAX(0, (?2 - connected_at) / 1000)
+                    END
+                WHERE id = ?1 AND ended_at IS NULL
+                "#,
+                params![id, ended_at],
+            )
+        })?;
+        Ok(())
+    }
+
+    /// Hinterlegt den Public Key der Gegenstelle zu einem Eintrag
+    pub fn set_call_public_key(&self, id: i64, public_key: &str) -> Result<(), DatabaseError> {
+        self.write(|conn| {
+            conn.execute(
+                "UPDATE call_history SET public_key = ?2 WHERE id = ?1",
+                params![id, public_key],
+            )
+        })?;
+        Ok(())
+    }
+
+    /// Gibt die Gegenstelle des letzten ausgehenden Anrufs zurück
+    pub fn get_last_dialed(&self) -> Result<Option<LastDialed>, DatabaseError> {
+        let conn = self.conn.lock();
+        let result = conn.query_row(
+            r#"
+            SELECT peer_id, username, public_key
+            FROM call_history
+            WHERE direction = 'outgoing'
+            ORDER BY started_at DESC, id DESC
+            LIMIT 1
+            "#,
+            [],
+            |row| {
+                Ok(LastDialed {
+                    peer_id: row.get(0)?,
+                    username: row.get(1)?,
+                    public_key: row.get(2)?,
+                })
+            },
+        );
+
+        match result {
+            Ok(last) => Ok(Some(last)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(DatabaseError::Sqlite(e)),
+        }
+    }
+
+    /// Löscht den letzten unbeantworteten eingehenden Anruf eines Peers
+    ///
+    /// Für verworfene Anrufe, die nicht als verpasst erscheinen sollen. Gibt
+    /// zurück, ob ein Eintrag gelöscht wurde.
+    pub fn delete_unanswered_call(&self, peer_id: &str) -> Result<bool, DatabaseError> {
+        let deleted = self.write(|conn| {
+            conn.execute(
+                r#"
+                DELETE FROM call_history
+                WHERE id = (
+                    SELECT id FROM call_history
+                    WHERE peer_id = ?1 AND direction = 'incoming' AND connected_at IS NULL
+                    ORDER BY started_at DESC, id DESC
+                    LIMIT 1
+                )
+                "#,
+                params![peer_id],
+            )
+        })?;
+        Ok(deleted > 0)
+    }
+
+    /// Gibt die letzten verpassten Anrufe zurück, neueste zuerst
+    ///
+    /// Laufende Anrufe (noch nicht beendet) zählen nicht als verpasst.
+    pub fn get_missed_calls(&self, limit: usize) -> Result<Vec<MissedCall>, DatabaseError> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT id, peer_id, username, started_at, seen
+            FROM call_history
+            WHERE direction = 'incoming' AND connected_at IS NULL AND ended_at IS NOT NULL
+            ORDER BY started_at DESC, id DESC
+            LIMIT ?1
+            "#,
+        )?;
+
+        let calls = stmt
+            .query_map(params![limit as i64], |row| {
+                Ok(MissedCall {
+                    id: row.get(0)?,
+                    peer_id: row.get(1)?,
+                    username: row.get(2)?,
+                    started_at: row.get(3)?,
+                    seen: row.get::<_, i64>(4)? != 0,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(calls)
+    }
+
+    /// Zählt die noch nicht gesehenen verpassten Anrufe (für das Badge)
+    pub fn get_missed_call_count(&self) -> Result<i64, DatabaseError> {
+        let conn = self.conn.lock();
+        let count = conn.query_row(
+            r#"
+            SELECT COUNT(*)
+            FROM call_history
+            WHERE direction = 'incoming' AND connected_at IS NULL AND ended_at IS NOT NULL
+                AND seen = 0
+            "#,
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(count)
+    }
+
+    /// Markiert alle verpassten Anrufe als gesehen, gibt die Anzahl zurück
+    pub fn mark_missed_calls_seen(&self) -> Result<usize, DatabaseError> {
+        self.write(|conn| {
+            conn.execute(
+                r#"
+                UPDATE call_history
+                SET seen = 1
+                WHERE direction = 'incoming' AND connected_at IS NULL AND seen = 0
+                "#,
+                [],
+            )
+        })
+    }
+
+    /// Markiert den letzten unbeantworteten Anruf eines Peers als gesehen
+    ///
+    /// Für bewusst abgelehnte Anrufe: sie bleiben im Verlauf, erhöhen aber
+    /// nicht das Badge für verpasste Anrufe.
+    pub fn mark_unanswered_call_seen(&self, peer_id: &str) -> Result<(), DatabaseError> {
+        self.write(|conn| {
+            conn.execute(
+                r#"
+                UPDATE call_history
+                SET seen = 1
+                WHERE id = (
+                    SELECT id FROM call_history
+                    WHERE peer_id = ?1 AND direction = 'incoming' AND connected_at IS NULL
+                    ORDER BY started_at DESC, id DESC
+                    LIMIT 1
+                )
+                "#,
+                params![peer_id],
+            )
+        })?;
+        Ok(())
+    }
+
+    /// Trägt eine fertige Aufnahme ein und gibt deren ID zurück
+    pub fn add_recording(&self, recording: &NewRecording) -> Result<i64, DatabaseError> {
+        self.write(|conn| {
+            conn.execute(
+                r#"
+                INSERT INTO recordings
+                    (call_history_id, peer_id, path, started_at, duration_ms, size_bytes)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                "#,
+                params![
+                    recording.call_history_id,
+                    recording.peer_id,
+                    recording.path,
+                    recording.started_at,
+                    recording.duration_ms,
+                    recording.size_bytes
+                ],
+            )?;
+            Ok(conn.last_insert_rowid())
+        })
+    }
+
+    /// Gibt alle Aufnahmen zurück, neueste zuerst
+    pub fn get_recordings(&self) -> Result<Vec<Recording>, DatabaseError> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT r.id, r.call_history_id, r.peer_id, h.username, r.path,
+                   r.started_at, r.duration_ms, r.size_bytes
+            FROM recordings r
+            LEFT JOIN call_history h ON h.id = r.call_history_id
+            ORDER BY r.started_at DESC, r.id DESC
+            "#,
+        )?;
+
+        let recordings = stmt
+            .query_map([], |row| {
+                Ok(Recording {
+                    id: row.get(0)?,
+                    call_history_id: row.get(1)?,
+                    peer_id: row.get(2)?,
+                    username: row.get(3)?,
+                    path: row.get(4)?,
+                    started_at: row.get(5)?,
+                    duration_ms: row.get(6)?,
+                    size_bytes: row.get(7)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(recordings)
+    }
+
+    /// Entfernt den Eintrag einer Aufnahme (die Datei bleibt unberührt)
+    ///
+    /// Gibt zurück, ob ein Eintrag existierte.
+    pub fn delete_recording(&self, path: &str) -> Result<bool, DatabaseError> {
+        let rows = self
+            .write(|conn| conn.execute("DELETE FROM recordings WHERE path = ?1", params![path]))?;
+        Ok(rows > 0)
+    }
+
+    /// Speichert einen TURN-Server
+    pub fn add_turn_server(&self, server: &TurnServer) -> Result<(), DatabaseError> {
+        self.write(|conn| {
+            conn.execute(
+                "INSERT INTO turn_servers (url, username, credential) VALUES (?1, ?2, ?3)",
+                params![server.url, server.username, server.credential],
+            )
+        })?;
+        Ok(())
+    }
+
+    /// Gibt alle gespeicherten TURN-Server in der Reihenfolge des Hinzufügens zurück
+    pub fn get_turn_servers(&self) -> Result<Vec<TurnServer>, DatabaseError> {
+        let conn = self.conn.lock();
+        let mut stmt =
+            conn.prepare("SELECT url, username, credential FROM turn_servers ORDER BY id")?;
+
+        let servers = stmt
+            .query_map([], |row| {
+                Ok(TurnServer {
+                    url: row.get(0)?,
+                    username: row.get(1)?,
+                    credential: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(servers)
+    }
+
+    /// Entfernt alle gespeicherten TURN-Server
+    pub fn clear_turn_servers(&self) -> Result<(), DatabaseError> {
+        self.write(|conn| conn.execute("DELETE FROM turn_servers", []))?;
+        Ok(())
+    }
+
+    /// Speichert eine gesendete oder empfangene Textnachricht
+    pub fn add_chat_message(
+        &self,
+        peer_id: &str,
+        outgoing: bool,
+        body: &str,
+        sent_at: i64,
+    ) -> Result<ChatMessage, DatabaseError> {
+        let id = self.write(|conn| {
+            conn.execute(
+                "INSERT INTO messages (peer_id, outgoing, body, sent_at) VALUES (?1, ?2, ?3, ?4)",
+                params![peer_id, outgoing as i32, body, sent_at],
+            )?;
+            Ok(conn.last_insert_rowid())
+        })?;
+
+        Ok(ChatMessage {
+            id,
+            peer_id: peer_id.to_string(),
+            outgoing,
+            body: body.to_string(),
+            sent_at,
+        })
+    }
+
+    /// Gibt den Nachrichtenverlauf mit einem Peer zurück (älteste zuerst)
+    pub fn get_chat_messages(&self, peer_id: &str) -> Result<Vec<ChatMessage>, DatabaseError> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT id, peer_id, outgoing, body, sent_at
+            FROM messages
+            WHERE peer_id = ?1
+            ORDER BY sent_at, id
+            "#,
+        )?;
+
+        let messages = stmt
+            .query_map(params![peer_id], |row| {
+                Ok(ChatMessage {
+                    id: row.get(0)?,
+                    peer_id: row.get(1)?,
+                    outgoing: row.get::<_, i32>(2)? != 0,
+                    body: row.get(3)?,
+                    sent_at: row.get(4)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(messages)
+    }
+
+    /// Berechnet die Nutzungsstatistik aus Anrufverlauf und Kontakten
+    pub fn get_usage_stats(&self) -> Result<UsageStats, DatabaseError> {
+        let conn = self.conn.lock();
+        let stats = conn.query_row(
+            r#"
+            SELECT
+                COUNT(*) FILTER (WHERE direction = 'outgoing'),
+                COUNT(*) FILTER (WHERE direction = 'incoming' AND connected_at IS NOT NULL),
+                COALESCE(SUM(duration_secs), 0),
+                (SELECT COUNT(*) FROM contacts WHERE deleted_at IS NULL)
+            FROM call_history
+            "#,
+            [],
+            |row| {
+                Ok(UsageStats {
+                    calls_made: row.get(0)?,
+                    calls_received: row.get(1)?,
+                    total_talk_time_secs: row.get(2)?,
+                    contact_count: row.get(3)?,
+                })
+            },
+        )?;
+        Ok(stats)
+    }
+}
+
+// ============================================================================
+// SEARCH
+// ============================================================================
+
+/// Maskiert die LIKE-Platzhalter `%` und `_` (Escape-Zeichen `\`)
+fn escape_like(query: &str) -> String {
+    let mut escaped = String::with_capacity(query.len());
+    for c in query.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+// ============================================================================
+// IMPORT
+// ============================================================================
+
+/// Leere oder nur aus Leerzeichen bestehende Werte gelten als nicht gesetzt
+fn non_empty(value: Option<&str>) -> Option<&str> {
+    value.map(str::trim).filter(|value| !value.is_empty())
+}
+
+/// Ermittelt die Feldänderungen, die ein importierter Kontakt bewirkt
+fn merge_imported_contact(
+    existing: &Contact,
+    imported: &ImportedContact,
+    policy: ConflictPolicy,
+) -> Vec<FieldChange> {
+    let fields = [
+        (
+            "username",
+            Some(existing.username.as_str()),
+            Some(imported.username.as_str()),
+        ),
+        (
+            "display_name",
+            existing.display_name.as_deref(),
+            imported.display_name.as_deref(),
+        ),
+        (
+            "notes",
+            existing.notes.as_deref(),
+            imported.notes.as_deref(),
+        ),
+    ];
+
+    fields
+        .into_iter()
+        .filter_map(|(field, old, imported)| {
+            let old = non_empty(old);
+            let new = policy.resolve(old, non_empty(imported));
+            (new.as_deref() != old).then(|| FieldChange {
+                field,
+                old: old.map(str::to_string),
+                new,
+            })
+        })
+        .collect()
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_and_get_contact() {
+        let db = ContactsDatabase::open_in_memory().unwrap();
+
+        let new_contact = NewContact {
+            peer_id: "test-peer-id".to_string(),
+            username: "alice".to_string(),
+            display_name: Some("Alice".to_string()),
+            public_key: None,
+        };
+
+        let contact = db.add_contact(new_contact).unwrap();
+        assert_eq!(contact.username, "alice");
+        assert_eq!(contact.display_name, Some("Alice".to_string()));
+        assert!(!contact.is_online);
+    }
+
+    #[test]
+    fn test_online_status() {
+        let db = ContactsDatabase::open_in_memory().unwrap();
+
+        let new_contact = NewContact {
+            peer_id: "test-peer".to_string(),
+            username: "bob".to_string(),
+            display_name: None,
+            public_key: None,
+        };
 
         db.add_contact(new_contact).unwrap();
 
@@ -328,4 +1650,911 @@ mod tests {
         let contact = db.get_contact_by_peer_id("test-peer").unwrap();
         assert!(contact.is_online);
     }
+
+    #[test]
+    fn test_directory_display_name_does_not_override_own_name() {
+        let db = ContactsDatabase::open_in_memory().unwrap();
+        db.add_contact(NewContact {
+            peer_id: "peer-a".to_string(),
+            username: "alice_1234".to_string(),
+            display_name: None,
+            public_key: None,
+        })
+        .unwrap();
+
+        assert!(db.fill_display_name("peer-a", "Alice Smith").unwrap());
+        let contact = db.get_contact_by_peer_id("peer-a").unwrap();
+        assert_eq!(contact.display_name.as_deref(), Some("Alice Smith"));
+
+        // Selbst vergebene Namen bleiben erhalten
+        db.set_display_name("peer-a", Some("Ali")).unwrap();
+        assert!(!db.fill_display_name("peer-a", "Alice S.").unwrap());
+        let contact = db.get_contact_by_peer_id("peer-a").unwrap();
+        assert_eq!(contact.display_name.as_deref(), Some("Ali"));
+        assert!(!db.fill_display_name("unknown", "Nobody").unwrap());
+    }
+
+    #[test]
+    fn test_contact_notes() {
+        let db = ContactsDatabase::open_in_memory().unwrap();
+        db.add_contact(NewContact {
+            peer_id: "test-peer".to_string(),
+            username: "carol".to_string(),
+            display_name: None,
+            public_key: None,
+        })
+        .unwrap();
+
+        let contact = db
+            .set_contact_notes("test-peer", Some("  prefers evenings "))
+            .unwrap();
+        assert_eq!(contact.notes.as_deref(), Some("prefers evenings"));
+
+        let too_long = "x".repeat(MAX_NOTES_LENGTH + 1);
+        assert!(matches!(
+            db.set_contact_notes("test-peer", Some(&too_long)),
+            Err(DatabaseError::NotesTooLong(_))
+        ));
+
+        let contact = db.set_contact_notes("test-peer", Some("   ")).unwrap();
+        assert_eq!(contact.notes, None);
+        assert!(matches!(
+            db.set_contact_notes("missing", None),
+            Err(DatabaseError::ContactNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_auto_added_contact_does_not_override_existing() {
+        let db = ContactsDatabase::open_in_memory().unwrap();
+        let new_contact = |peer_id: &str| NewContact {
+            peer_id: peer_id.to_string(),
+            username: "erin".to_string(),
+            display_name: None,
+            public_key: None,
+        };
+
+        let contact = db.add_auto_contact(new_contact("peer-1")).unwrap().unwrap();
+        assert!(contact.auto_added);
+
+        db.add_contact(NewContact {
+            display_name: Some("Erin".to_string()),
+            public_key: None,
+            ..new_contact("peer-2")
+        })
+        .unwrap();
+        assert!(db
+            .add_auto_contact(new_contact("peer-2"))
+            .unwrap()
+            .is_none());
+        assert!(!db.get_contact_by_peer_id("peer-2").unwrap().auto_added);
+    }
+
+    #[test]
+    fn test_migrations_upgrade_existing_database() {
+        // Datenbank im Schema vor den Migrationen
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE contacts (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                peer_id TEXT NOT NULL UNIQUE,
+                username TEXT NOT NULL,
+                display_name TEXT,
+                is_online INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+            INSERT INTO contacts (peer_id, username) VALUES ('old-peer', 'dave');
+            CREATE TABLE identity_keys (
+                peer_id TEXT PRIMARY KEY,
+                public_key TEXT NOT NULL,
+                pinned_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+            INSERT INTO identity_keys (peer_id, public_key) VALUES ('old-peer', 'key-dave');
+            "#,
+        )
+        .unwrap();
+
+        let db = ContactsDatabase {
+            conn: Mutex::new(conn),
+        };
+        db.init_schema().unwrap();
+        // Erneutes Öffnen wendet nichts doppelt an
+        db.init_schema().unwrap();
+
+        let contact = db.get_contact_by_peer_id("old-peer").unwrap();
+        assert_eq!(contact.username, "dave");
+        assert_eq!(contact.notes, None);
+        assert_eq!(contact.public_key.as_deref(), Some("key-dave"));
+
+        let version: i64 = db
+            .conn
+            .lock()
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, MIGRATIONS.len() as i64);
+    }
+
+    #[test]
+    fn test_contact_public_key_binding() {
+        let db = ContactsDatabase::open_in_memory().unwrap();
+        let new_contact = |public_key: Option<&str>| NewContact {
+            peer_id: "peer".to_string(),
+            username: "alice".to_string(),
+            display_name: None,
+            public_key: public_key.map(str::to_string),
+        };
+
+        let contact = db.add_contact(new_contact(Some("key-a"))).unwrap();
+        assert_eq!(contact.public_key.as_deref(), Some("key-a"));
+        assert_eq!(
+            db.get_identity_key("peer").unwrap().as_deref(),
+            Some("key-a")
+        );
+
+        // Ein späterer, abweichender Key überschreibt die Bindung nicht
+        let contact = db.add_contact(new_contact(Some("key-b"))).unwrap();
+        assert_eq!(contact.public_key.as_deref(), Some("key-a"));
+        let contact = db.add_contact(new_contact(None)).unwrap();
+        assert_eq!(contact.public_key.as_deref(), Some("key-a"));
+        assert_eq!(
+            db.get_identity_key("peer").unwrap().as_deref(),
+            Some("key-a")
+        );
+
+        // Ohne Key angelegte Kontakte übernehmen den ersten gepinnten Key
+        db.add_contact(NewContact {
+            peer_id: "peer-bob".to_string(),
+            username: "bob".to_string(),
+            display_name: None,
+            public_key: None,
+        })
+        .unwrap();
+        assert_eq!(
+            db.get_contact_by_peer_id("peer-bob").unwrap().public_key,
+            None
+        );
+        assert!(db.pin_identity_key("peer-bob", "key-bob").unwrap());
+        assert!(!db.pin_identity_key("peer-bob", "key-other").unwrap());
+        assert_eq!(
+            db.get_contact_by_peer_id("peer-bob")
+                .unwrap()
+                .public_key
+                .as_deref(),
+            Some("key-bob")
+        );
+    }
+
+    #[test]
+    fn test_mark_verified() {
+        let db = ContactsDatabase::open_in_memory().unwrap();
+        db.add_contact(NewContact {
+            peer_id: "peer".to_string(),
+            username: "alice".to_string(),
+            display_name: None,
+            public_key: None,
+        })
+        .unwrap();
+        assert!(!db.get_contact_by_peer_id("peer").unwrap().is_verified);
+
+        assert!(db.mark_verified("peer", true).unwrap().is_verified);
+        assert!(!db.mark_verified("peer", false).unwrap().is_verified);
+        assert!(matches!(
+            db.mark_verified("unknown", true),
+            Err(DatabaseError::ContactNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_blocked_contacts() {
+        let db = ContactsDatabase::open_in_memory().unwrap();
+        for (peer_id, username) in [("peer-a", "alice"), ("peer-b", "bob")] {
+            db.add_contact(NewContact {
+                peer_id: peer_id.to_string(),
+                username: username.to_string(),
+                display_name: None,
+                public_key: None,
+            })
+            .unwrap();
+        }
+        assert!(!db.is_blocked("peer-a").unwrap());
+
+        assert!(db.set_blocked("peer-a", true).unwrap().blocked);
+        assert!(db.is_blocked("peer-a").unwrap());
+        assert!(!db.is_blocked("peer-b").unwrap());
+        assert!(!db.is_blocked("unknown").unwrap());
+        let blocked: Vec<_> = db
+            .get_all_contacts()
+            .unwrap()
+            .into_iter()
+            .map(|contact| (contact.peer_id, contact.blocked))
+            .collect();
+        assert_eq!(
+            blocked,
+            vec![("peer-a".to_string(), true), ("peer-b".to_string(), false)]
+        );
+
+        // Auch nach dem Löschen bleibt der Peer blockiert
+        db.delete_contact("peer-a").unwrap();
+        assert!(db.is_blocked("peer-a").unwrap());
+        db.restore_contact("peer-a").unwrap();
+
+        assert!(!db.set_blocked("peer-a", false).unwrap().blocked);
+        assert!(!db.is_blocked("peer-a").unwrap());
+        assert!(matches!(
+            db.set_blocked("unknown", true),
+            Err(DatabaseError::ContactNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_calls_from_blocked_peers_are_rejected() {
+        let db = ContactsDatabase::open_in_memory().unwrap();
+        for peer_id in ["peer-a", "peer-b"] {
+            db.add_contact(NewContact {
+                peer_id: peer_id.to_string(),
+                username: peer_id.to_string(),
+                display_name: None,
+                public_key: None,
+            })
+            .unwrap();
+        }
+        db.set_blocked("peer-a", true).unwrap();
+
+        let mut rejected = Vec::new();
+        for peer_id in ["peer-a", "peer-b", "unknown"] {
+            if db.reject_if_blocked(peer_id, || rejected.push(peer_id)) {
+                assert_eq!(peer_id, "peer-a");
+            }
+        }
+        assert_eq!(rejected, vec!["peer-a"]);
+
+        // Nach dem Aufheben klingelt der Anruf wieder
+        db.set_blocked("peer-a", false).unwrap();
+        assert!(!db.reject_if_blocked("peer-a", || panic!("call was rejected")));
+    }
+
+    #[test]
+    fn test_deleted_contacts_are_kept_as_tombstones() {
+        let db = ContactsDatabase::open_in_memory().unwrap();
+        let new_contact = |peer_id: &str| NewContact {
+            peer_id: peer_id.to_string(),
+            username: "frank".to_string(),
+            display_name: None,
+            public_key: None,
+        };
+        db.add_contact(new_contact("peer-1")).unwrap();
+        db.add_contact(new_contact("peer-2")).unwrap();
+
+        db.delete_contact("peer-1").unwrap();
+        assert_eq!(db.get_all_contacts().unwrap().len(), 1);
+        assert!(matches!(
+            db.get_contact_by_peer_id("peer-1"),
+            Err(DatabaseError::ContactNotFound(_))
+        ));
+        let deleted = db.get_deleted_contacts().unwrap();
+        assert_eq!(deleted.len(), 1);
+        assert!(deleted[0].deleted_at.is_some());
+
+        // Weder manuelles noch automatisches Hinzufügen holt ihn zurück
+        assert!(matches!(
+            db.add_contact(new_contact("peer-1")),
+            Err(DatabaseError::ContactDeleted(_))
+        ));
+        assert!(db
+            .add_auto_contact(new_contact("peer-1"))
+            .unwrap()
+            .is_none());
+        assert_eq!(db.get_all_contacts().unwrap().len(), 1);
+
+        let restored = db.restore_contact("peer-1").unwrap();
+        assert_eq!(restored.deleted_at, None);
+        assert_eq!(db.get_all_contacts().unwrap().len(), 2);
+        assert!(matches!(
+            db.restore_contact("peer-1"),
+            Err(DatabaseError::ContactNotFound(_))
+        ));
+
+        db.delete_contact("peer-2").unwrap();
+        assert_eq!(db.purge_deleted().unwrap(), 1);
+        assert!(db.get_deleted_contacts().unwrap().is_empty());
+        // Nach dem Purge ist ein neues Hinzufügen wieder möglich
+        db.add_contact(new_contact("peer-2")).unwrap();
+    }
+
+    #[test]
+    fn test_callback_requests() {
+        let db = ContactsDatabase::open_in_memory().unwrap();
+        let request = |peer_id: &str, requested_at| CallbackRequest {
+            peer_id: peer_id.to_string(),
+            username: format!("user-{}", peer_id),
+            requested_at,
+        };
+
+        db.add_callback_request(&request("a", 100)).unwrap();
+        db.add_callback_request(&request("b", 200)).unwrap();
+        // Erneute Bitte desselben Peers ersetzt die alte
+        db.add_callback_request(&request("a", 300)).unwrap();
+
+        let requests = db.get_callback_requests().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].peer_id, "a");
+        assert_eq!(requests[0].requested_at, 300);
+
+        assert!(db.remove_callback_request("a").unwrap());
+        assert!(!db.remove_callback_request("a").unwrap());
+        assert_eq!(db.get_callback_requests().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_accepted_key_replaces_pinned_key() {
+        let db = ContactsDatabase::open_in_memory().unwrap();
+        db.add_contact(NewContact {
+            peer_id: "peer".to_string(),
+            username: "alice".to_string(),
+            display_name: None,
+            public_key: Some("key-1".to_string()),
+        })
+        .unwrap();
+        db.mark_verified("peer", true).unwrap();
+
+        // Ein neuer Key wird nicht still übernommen
+        assert!(!db.pin_identity_key("peer", "key-2").unwrap());
+        assert_eq!(
+            db.get_identity_key("peer").unwrap().as_deref(),
+            Some("key-1")
+        );
+
+        db.accept_identity_key("peer", "key-2").unwrap();
+        assert_eq!(
+            db.get_identity_key("peer").unwrap().as_deref(),
+            Some("key-2")
+        );
+        let contact = db.get_contact_by_peer_id("peer").unwrap();
+        assert_eq!(contact.public_key.as_deref(), Some("key-2"));
+        assert!(!contact.is_verified);
+
+        // Auch ohne Kontakt wird der Key übernommen
+        db.accept_identity_key("stranger", "key-3").unwrap();
+        assert_eq!(
+            db.get_identity_key("stranger").unwrap().as_deref(),
+            Some("key-3")
+        );
+    }
+
+    #[test]
+    fn test_identity_key_is_pinned_once() {
+        let db = ContactsDatabase::open_in_memory().unwrap();
+        assert_eq!(db.get_identity_key("peer").unwrap(), None);
+
+        assert!(db.pin_identity_key("peer", "key-1").unwrap());
+        assert!(!db.pin_identity_key("peer", "key-2").unwrap());
+        assert_eq!(
+            db.get_identity_key("peer").unwrap(),
+            Some("key-1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_usage_stats_from_call_history() {
+        let db = ContactsDatabase::open_in_memory().unwrap();
+        assert_eq!(db.get_usage_stats().unwrap(), UsageStats::default());
+
+        db.add_contact(NewContact {
+            peer_id: "a".to_string(),
+            username: "alice".to_string(),
+            display_name: None,
+            public_key: None,
+        })
+        .unwrap();
+
+        // Ausgehend, 90 Sekunden Gespräch
+        let id = db
+            .start_call_record("a", Some("alice"), CallDirection::Outgoing, 0)
+            .unwrap();
+        db.mark_call_connected(id, 5_000).unwrap();
+        db.finish_call_record(id, 95_000).unwrap();
+
+        // Ausgehend, nicht angenommen
+        let id = db
+            .start_call_record("b", None, CallDirection::Outgoing, 100_000)
+            .unwrap();
+        db.finish_call_record(id, 130_000).unwrap();
+
+        // Eingehend, 30 Sekunden Gespräch, doppeltes Beenden zählt nicht
+        let id = db
+            .start_call_record("a", Some("alice"), CallDirection::Incoming, 200_000)
+            .unwrap();
+        db.mark_call_connected(id, 200_000).unwrap();
+        db.finish_call_record(id, 230_500).unwrap();
+        db.finish_call_record(id, 900_000).unwrap();
+
+        // Eingehend, verpasst
+        let id = db
+            .start_call_record("c", Some("carol"), CallDirection::Incoming, 300_000)
+            .unwrap();
+        db.finish_call_record(id, 310_000).unwrap();
+
+        assert_eq!(
+            db.get_usage_stats().unwrap(),
+            UsageStats {
+                calls_made: 2,
+                calls_received: 1,
+                total_talk_time_secs: 120,
+                contact_count: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_last_dialed_ignores_incoming_calls() {
+        let db = ContactsDatabase::open_in_memory().unwrap();
+        assert_eq!(db.get_last_dialed().unwrap(), None);
+
+        let id = db
+            .start_call_record("a", Some("alice"), CallDirection::Outgoing, 0)
+            .unwrap();
+        db.set_call_public_key(id, "key-a").unwrap();
+        let id = db
+            .start_call_record("b", None, CallDirection::Outgoing, 100_000)
+            .unwrap();
+        db.finish_call_record(id, 110_000).unwrap();
+        db.start_call_record("c", Some("carol"), CallDirection::Incoming, 200_000)
+            .unwrap();
+
+        assert_eq!(
+            db.get_last_dialed().unwrap(),
+            Some(LastDialed {
+                peer_id: "b".to_string(),
+                username: None,
+                public_key: None,
+            })
+        );
+
+        let id = db
+            .start_call_record("a2", Some("alice"), CallDirection::Outgoing, 300_000)
+            .unwrap();
+        db.set_call_public_key(id, "key-a").unwrap();
+        assert_eq!(
+            db.get_last_dialed().unwrap().unwrap().public_key,
+            Some("key-a".to_string())
+        );
+    }
+
+    #[test]
+    fn test_delete_unanswered_call() {
+        let db = ContactsDatabase::open_in_memory().unwrap();
+
+        let answered = db
+            .start_call_record("a", Some("alice"), CallDirection::Incoming, 0)
+            .unwrap();
+        db.mark_call_connected(answered, 1_000).unwrap();
+        db.start_call_record("a", Some("alice"), CallDirection::Outgoing, 100_000)
+            .unwrap();
+        assert!(!db.delete_unanswered_call("a").unwrap());
+
+        let missed = db
+            .start_call_record("a", Some("alice"), CallDirection::Incoming, 200_000)
+            .unwrap();
+        db.finish_call_record(missed, 210_000).unwrap();
+        assert!(db.delete_unanswered_call("a").unwrap());
+        assert!(!db.delete_unanswered_call("a").unwrap());
+        assert_eq!(db.get_usage_stats().unwrap().calls_made, 1);
+        assert_eq!(db.get_usage_stats().unwrap().calls_received, 1);
+    }
+
+    #[test]
+    fn test_recordings_linked_to_call_history() {
+        let db = ContactsDatabase::open_in_memory().unwrap();
+        let call = db
+            .start_call_record("a", Some("alice"), CallDirection::Outgoing, 0)
+            .unwrap();
+
+        let recording = |path: &str, call_history_id, started_at| NewRecording {
+            call_history_id,
+            peer_id: "a".to_string(),
+            path: path.to_string(),
+            started_at,
+            duration_ms: 60_000,
+            size_bytes: 1_000,
+        };
+        db.add_recording(&recording("/rec/1.wav", Some(call), 1_000))
+            .unwrap();
+        db.add_recording(&recording("/rec/2.wav", None, 2_000))
+            .unwrap();
+        assert!(db
+            .add_recording(&recording("/rec/1.wav", None, 3_000))
+            .is_err());
+
+        let recordings = db.get_recordings().unwrap();
+        assert_eq!(recordings.len(), 2);
+        assert_eq!(recordings[0].path, "/rec/2.wav");
+        assert_eq!(recordings[0].username, None);
+        assert_eq!(recordings[1].call_history_id, Some(call));
+        assert_eq!(recordings[1].username.as_deref(), Some("alice"));
+
+        assert!(db.delete_recording("/rec/1.wav").unwrap());
+        assert!(!db.delete_recording("/rec/1.wav").unwrap());
+        assert_eq!(db.get_recordings().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_turn_servers_are_persisted() {
+        let db = ContactsDatabase::open_in_memory().unwrap();
+        assert!(db.get_turn_servers().unwrap().is_empty());
+
+        let server = |url: &str| TurnServer {
+            url: url.to_string(),
+            username: "user".to_string(),
+            credential: "secret".to_string(),
+        };
+        db.add_turn_server(&server("turn:b.example.org")).unwrap();
+        db.add_turn_server(&server("turns:a.example.org")).unwrap();
+        assert_eq!(
+            db.get_turn_servers().unwrap(),
+            vec![server("turn:b.example.org"), server("turns:a.example.org")]
+        );
+
+        db.clear_turn_servers().unwrap();
+        assert!(db.get_turn_servers().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_chat_messages_are_kept_per_peer() {
+        let db = ContactsDatabase::open_in_memory().unwrap();
+        db.add_chat_message("peer-a", false, "Hast du kurz Zeit?", 2_000)
+            .unwrap();
+        db.add_chat_message("peer-b", true, "Hallo", 1_500).unwrap();
+        let sent = db
+            .add_chat_message("peer-a", true, "Gleich", 1_000)
+            .unwrap();
+
+        let history = db.get_chat_messages("peer-a").unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0], sent);
+        assert!(!history[1].outgoing);
+        assert_eq!(history[1].body, "Hast du kurz Zeit?");
+        assert!(db.get_chat_messages("peer-c").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_missed_calls_and_seen_flag() {
+        let db = ContactsDatabase::open_in_memory().unwrap();
+
+        let answered = db
+            .start_call_record("a", Some("alice"), CallDirection::Incoming, 0)
+            .unwrap();
+        db.mark_call_connected(answered, 1_000).unwrap();
+        db.finish_call_record(answered, 5_000).unwrap();
+        let outgoing = db
+            .start_call_record("b", Some("bob"), CallDirection::Outgoing, 10_000)
+            .unwrap();
+        db.finish_call_record(outgoing, 20_000).unwrap();
+        for (peer, started_at) in [("a", 30_000), ("b", 40_000)] {
+            let missed = db
+                .start_call_record(peer, None, CallDirection::Incoming, started_at)
+                .unwrap();
+            db.finish_call_record(missed, started_at + 10_000).unwrap();
+        }
+        // Klingelt noch, zählt erst nach dem Ende
+        let ringing = db
+            .start_call_record("c", None, CallDirection::Incoming, 60_000)
+            .unwrap();
+
+        let missed = db.get_missed_calls(MISSED_CALLS_LIMIT).unwrap();
+        let peers: Vec<_> = missed.iter().map(|call| call.peer_id.as_str()).collect();
+        assert_eq!(peers, ["b", "a"]);
+        assert!(missed.iter().all(|call| !call.seen));
+        assert_eq!(db.get_missed_call_count().unwrap(), 2);
+        assert_eq!(db.get_missed_calls(1).unwrap().len(), 1);
+
+        // Abgelehnt: bleibt in der Liste, zählt aber nicht für das Badge
+        db.mark_unanswered_call_seen("c").unwrap();
+        db.finish_call_record(ringing, 70_000).unwrap();
+        assert_eq!(db.get_missed_call_count().unwrap(), 2);
+        assert_eq!(db.get_missed_calls(MISSED_CALLS_LIMIT).unwrap().len(), 3);
+
+        assert_eq!(db.mark_missed_calls_seen().unwrap(), 2);
+        assert_eq!(db.get_missed_call_count().unwrap(), 0);
+        assert!(db
+            .get_missed_calls(MISSED_CALLS_LIMIT)
+            .unwrap()
+            .iter()
+            .all(|call| call.seen));
+    }
+
+    /// Kontakte alice, bob, carol und dave; dave wurde nie angerufen
+    ///
+    /// Anrufverlauf: bob zweimal (zuletzt bei 100), carol einmal (zuletzt,
+    /// bei 300), alice einmal (bei 50).
+    fn sort_fixture() -> ContactsDatabase {
+        let db = ContactsDatabase::open_in_memory().unwrap();
+        for name in ["dave", "carol", "bob", "alice"] {
+            db.add_contact(NewContact {
+                peer_id: format!("peer-{}", name),
+                username: name.to_string(),
+                display_name: None,
+                public_key: None,
+            })
+            .unwrap();
+        }
+        for (peer_id, started_at) in [
+            ("peer-bob", 10),
+            ("peer-alice", 50),
+            ("peer-bob", 100),
+            ("peer-carol", 300),
+        ] {
+            db.start_call_record(peer_id, None, CallDirection::Outgoing, started_at)
+                .unwrap();
+        }
+        db
+    }
+
+    fn usernames(contacts: Vec<Contact>) -> Vec<String> {
+        contacts.into_iter().map(|c| c.username).collect()
+    }
+
+    #[test]
+    fn test_contacts_sorted_alphabetically() {
+        let db = sort_fixture();
+        assert_eq!(
+            usernames(db.get_contacts_sorted(ContactSort::Alphabetical).unwrap()),
+            vec!["alice", "bob", "carol", "dave"]
+        );
+    }
+
+    #[test]
+    fn test_contacts_sorted_by_last_call_keeps_uncalled_at_end() {
+        let db = sort_fixture();
+        // Gelöschte Kontakte fehlen trotz Anrufverlauf
+        db.start_call_record("peer-eve", None, CallDirection::Incoming, 400)
+            .unwrap();
+        db.add_contact(NewContact {
+            peer_id: "peer-eve".to_string(),
+            username: "eve".to_string(),
+            display_name: None,
+            public_key: None,
+        })
+        .unwrap();
+        db.delete_contact("peer-eve").unwrap();
+
+        assert_eq!(
+            usernames(db.get_contacts_sorted(ContactSort::LastCalled).unwrap()),
+            vec!["carol", "bob", "alice", "dave"]
+        );
+    }
+
+    #[test]
+    fn test_contacts_sorted_by_call_count() {
+        let db = sort_fixture();
+        // Gleich viele Anrufe: der zuletzt angerufene zuerst
+        assert_eq!(
+            usernames(db.get_contacts_sorted(ContactSort::Favorites).unwrap()),
+            vec!["bob", "carol", "alice", "dave"]
+        );
+    }
+
+    #[test]
+    fn test_search_contacts() {
+        let db = sort_fixture();
+        db.set_display_name("peer-dave", Some("Davy Jones"))
+            .unwrap();
+        db.set_online_status("peer-carol", true).unwrap();
+        db.add_contact(NewContact {
+            peer_id: "peer-percent".to_string(),
+            username: "100%_sure".to_string(),
+            display_name: None,
+            public_key: None,
+        })
+        .unwrap();
+
+        // Teiltreffer ohne Beachtung der Schreibweise, Online-Kontakte zuerst
+        assert_eq!(
+            usernames(db.search_contacts("A").unwrap()),
+            vec!["carol", "alice", "dave"]
+        );
+        assert_eq!(
+            usernames(db.search_contacts(" jones ").unwrap()),
+            vec!["dave"]
+        );
+
+        // Platzhalter gelten wörtlich
+        assert_eq!(
+            usernames(db.search_contacts("%").unwrap()),
+            vec!["100%_sure"]
+        );
+        assert_eq!(
+            usernames(db.search_contacts("%_s").unwrap()),
+            vec!["100%_sure"]
+        );
+        assert!(db.search_contacts("a_i").unwrap().is_empty());
+
+        // Leere Suche liefert alle, gelöschte fehlen
+        db.delete_contact("peer-bob").unwrap();
+        assert_eq!(
+            usernames(db.search_contacts("").unwrap()),
+            vec!["carol", "100%_sure", "alice", "dave"]
+        );
+    }
+
+    /// Lokaler Kontakt "alice" mit Display-Namen, ohne Notiz, plus ein Import
+    /// mit anderem Namen und Notiz sowie einem neuen Kontakt
+    fn import_fixture() -> (ContactsDatabase, Vec<ImportedContact>) {
+        let db = ContactsDatabase::open_in_memory().unwrap();
+        db.add_contact(NewContact {
+            peer_id: "peer-a".to_string(),
+            username: "alice".to_string(),
+            display_name: Some("Alice (Arbeit)".to_string()),
+            public_key: None,
+        })
+        .unwrap();
+
+        let imported = vec![
+            ImportedContact {
+                peer_id: "peer-a".to_string(),
+                username: "alice_old".to_string(),
+                display_name: Some("Alice".to_string()),
+                notes: Some("Aus dem Backup".to_string()),
+            },
+            ImportedContact {
+                peer_id: "peer-b".to_string(),
+                username: "bob".to_string(),
+                display_name: None,
+                notes: None,
+            },
+        ];
+        (db, imported)
+    }
+
+    #[test]
+    fn test_import_keep_existing_only_adds_new_contacts() {
+        let (db, imported) = import_fixture();
+        let report = db
+            .import_contacts(&imported, ConflictPolicy::KeepExisting)
+            .unwrap();
+
+        assert_eq!(report.added, vec!["peer-b".to_string()]);
+        assert!(report.updated.is_empty());
+        assert_eq!(report.unchanged, vec!["peer-a".to_string()]);
+
+        let alice = db.get_contact_by_peer_id("peer-a").unwrap();
+        assert_eq!(alice.username, "alice");
+        assert_eq!(alice.display_name.as_deref(), Some("Alice (Arbeit)"));
+        assert_eq!(alice.notes, None);
+    }
+
+    #[test]
+    fn test_import_prefer_imported_overwrites_fields() {
+        let (db, imported) = import_fixture();
+        let report = db
+            .import_contacts(&imported, ConflictPolicy::PreferImported)
+            .unwrap();
+
+        let fields: Vec<_> = report.updated[0]
+            .changes
+            .iter()
+            .map(|change| change.field)
+            .collect();
+        assert_eq!(fields, vec!["username", "display_name", "notes"]);
+        assert_eq!(
+            report.updated[0].changes[1],
+            FieldChange {
+                field: "display_name",
+                old: Some("Alice (Arbeit)".to_string()),
+                new: Some("Alice".to_string()),
+            }
+        );
+
+        let alice = db.get_contact_by_peer_id("peer-a").unwrap();
+        assert_eq!(alice.username, "alice_old");
+        assert_eq!(alice.display_name.as_deref(), Some("Alice"));
+        assert_eq!(alice.notes.as_deref(), Some("Aus dem Backup"));
+
+        // Leere Felder im Import löschen nichts
+        let report = db
+            .import_contacts(
+                &[ImportedContact {
+                    display_name: None,
+                    notes: Some("  ".to_string()),
+                    ..imported[0].clone()
+                }],
+                ConflictPolicy::PreferImported,
+            )
+            .unwrap();
+        assert_eq!(report.unchanged, vec!["peer-a".to_string()]);
+    }
+
+    #[test]
+    fn test_import_merge_fills_empty_fields() {
+        let (db, imported) = import_fixture();
+        let report = db
+            .import_contacts(&imported, ConflictPolicy::Merge)
+            .unwrap();
+
+        assert_eq!(
+            report.updated,
+            vec![ContactChange {
+                peer_id: "peer-a".to_string(),
+                changes: vec![FieldChange {
+                    field: "notes",
+                    old: None,
+                    new: Some("Aus dem Backup".to_string()),
+                }],
+            }]
+        );
+
+        let alice = db.get_contact_by_peer_id("peer-a").unwrap();
+        assert_eq!(alice.username, "alice");
+        assert_eq!(alice.display_name.as_deref(), Some("Alice (Arbeit)"));
+        assert_eq!(alice.notes.as_deref(), Some("Aus dem Backup"));
+    }
+
+    #[test]
+    fn test_import_does_not_restore_deleted_contacts() {
+        let (db, imported) = import_fixture();
+        db.delete_contact("peer-a").unwrap();
+
+        let report = db
+            .import_contacts(&imported, ConflictPolicy::PreferImported)
+            .unwrap();
+        assert_eq!(report.skipped_deleted, vec!["peer-a".to_string()]);
+        assert_eq!(report.added, vec!["peer-b".to_string()]);
+        assert!(db.get_contact_by_peer_id("peer-a").is_err());
+    }
+
+    #[test]
+    fn test_concurrent_reads_and_writes() {
+        use std::sync::Arc;
+
+        // Zwei Connections auf dieselbe Datei, damit echte SQLite-Sperren entstehen
+        let path = std::env::temp_dir().join(format!("contacts-{}.db", uuid::Uuid::new_v4()));
+        let writer_db = Arc::new(ContactsDatabase::open_at(&path).unwrap());
+        let reader_db = Arc::new(ContactsDatabase::open_at(&path).unwrap());
+
+        for i in 0..10 {
+            writer_db
+                .add_contact(NewContact {
+                    peer_id: format!("peer-{}", i),
+                    username: format!("user{}", i),
+                    display_name: None,
+                    public_key: None,
+                })
+                .unwrap();
+        }
+
+        let writers: Vec<_> = (0..2)
+            .map(|t| {
+                let db = Arc::clone(&writer_db);
+                std::thread::spawn(move || {
+                    for i in 0..100 {
+                        db.set_online_status(&format!("peer-{}", i % 10), (i + t) % 2 == 0)?;
+                    }
+                    db.set_all_offline()
+                })
+            })
+            .collect();
+
+        let readers: Vec<_> = (0..2)
+            .map(|_| {
+                let db = Arc::clone(&reader_db);
+                std::thread::spawn(move || {
+                    for _ in 0..100 {
+                        assert_eq!(db.get_all_contacts()?.len(), 10);
+                    }
+                    Ok::<(), DatabaseError>(())
+                })
+            })
+            .collect();
+
+        for handle in writers.into_iter().chain(readers) {
+            handle.join().unwrap().unwrap();
+        }
+
+        drop(writer_db);
+        drop(reader_db);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
 }
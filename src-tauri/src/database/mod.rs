@@ -2,4 +2,9 @@
 
 mod contacts;
 
-pub use contacts::{Contact, ContactsDatabase, DatabaseError, NewContact};
+pub use contacts::{
+    CallDirection, CallbackRequest, ChatMessage, ConflictPolicy, Contact, ContactChange,
+    ContactSort, ContactsDatabase, DatabaseError, FieldChange, ImportReport, ImportedContact,
+    LastDialed, MissedCall, NewContact, NewRecording, Recording, TurnServer, UsageStats,
+    MAX_NOTES_LENGTH, MISSED_CALLS_LIMIT,
+};
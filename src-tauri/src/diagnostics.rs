@@ -0,0 +1,162 @@
+//! Diagnosebericht
+//!
+//! Fasst alles, was zur Analyse eines Verbindungsproblems nötig ist, in einer
+//! einzelnen JSON-Datei zusammen: Event-Log, Anruf-Statistik, gewähltes
+//! Candidate Pair, ICE-Konfiguration, Audio-Geräte und Versionen.
+//!
+//! Der Private Key ist nie enthalten. IP-Adressen werden im gesamten Bericht
+//! maskiert, solange der Nutzer sie nicht ausdrücklich freigibt.
+
+use crate::call_engine::{CallStats, IceTransportPolicy, MicrophonePermission};
+use crate::database::UsageStats;
+use crate::events::{redact_ip_addresses, EventLogEntry};
+use crate::paths::app_data_dir;
+use crate::signaling::SignalingDiagnostics;
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::Value;
+use std::path::PathBuf;
+use thiserror::Error;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Version des Berichtsformats
+pub const DIAGNOSTICS_FORMAT_VERSION: u32 = 1;
+
+// ============================================================================
+// ERROR TYPES
+// ============================================================================
+
+#[derive(Error, Debug)]
+pub enum DiagnosticsError {
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+// ============================================================================
+// BUNDLE
+// ============================================================================
+
+/// Audio-Geräte und Mikrofon-Berechtigung
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioDiagnostics {
+    pub input_devices: Vec<String>,
+    pub output_devices: Vec<String>,
+    pub default_input: Option<String>,
+    pub default_output: Option<String>,
+    pub microphone_permission: MicrophonePermission,
+}
+
+/// ICE-Konfiguration für neue Anrufe
+#[derive(Debug, Clone, Serialize)]
+pub struct IceDiagnostics {
+    /// URLs der STUN/TURN Server (TURN Credentials werden nie exportiert)
+    pub servers: Vec<String>,
+    pub transport_policy: IceTransportPolicy,
+}
+
+/// Vollständiger Diagnosebericht
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticsBundle {
+    pub format_version: u32,
+    /// Unix-Millisekunden
+    pub generated_at: i64,
+    pub app_version: &'static str,
+    pub protocol_version: u32,
+    pub os: &'static str,
+    pub arch: &'static str,
+    /// `false` nur, wenn der Nutzer IP-Adressen ausdrücklich freigegeben hat
+    pub addresses_redacted: bool,
+    pub connection: SignalingDiagnostics,
+    pub call: CallStats,
+    pub usage: Option<UsageStats>,
+    pub ice: IceDiagnostics,
+    pub audio: AudioDiagnostics,
+    pub event_log: Vec<EventLogEntry>,
+}
+
+impl DiagnosticsBundle {
+    /// Serialisiert den Bericht, bei `addresses_redacted` mit maskierten Adressen
+    ///
+    /// Maskiert wird über alle Felder, auch Einträge, die bei abgeschalteter
+    /// Maskierung ins Event-Log gelangt sind.
+    pub fn to_json(&self) -> Result<String, DiagnosticsError> {
+        let mut value = serde_json::to_value(self)?;
+        if self.addresses_redacted {
+            redact_json_addresses(&mut value);
+        }
+        Ok(serde_json::to_string_pretty(&value)?)
+    }
+
+    /// Schreibt den Bericht in das Diagnose-Verzeichnis und gibt den Pfad zurück
+    pub fn write_to_file(&self) -> Result<PathBuf, DiagnosticsError> {
+        let json = self.to_json()?;
+
+        let mut path = Self::get_diagnostics_dir();
+        std::fs::create_dir_all(&path)?;
+        path.push(format!(
+            "pulse-diagnostics-{}.json",
+            Utc::now().format("%Y%m%d-%H%M%S")
+        ));
+        std::fs::write(&path, json)?;
+
+        tracing::info!("Diagnostics written to {:?}", path);
+        Ok(path)
+    }
+
+    /// Ermittelt das Verzeichnis für Diagnoseberichte
+    fn get_diagnostics_dir() -> PathBuf {
+        let mut path = app_data_dir();
+        path.push("diagnostics");
+        path
+    }
+}
+
+/// Maskiert IP-Adressen in allen Strings eines JSON-Werts
+pub fn redact_json_addresses(value: &mut Value) {
+    match value {
+        Value::String(text) => *text = redact_ip_addresses(text),
+        Value::Array(items) => items.iter_mut().for_each(redact_json_addresses),
+        Value::Object(fields) => fields.values_mut().for_each(redact_json_addresses),
+        Value::Null | Value::Bool(_) | Value::Number(_) => {}
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_redact_json_addresses_in_nested_values() {
+        let mut value = json!({
+            "connection": { "server_url": "ws://192.168.1.5:8787", "is_connected": true },
+            "call": {
+                "selected_candidate_pair":
+                    "(local) udp host 10.0.0.7:5000 <-> (remote) udp srflx 203.0.113.9:6000",
+                "packets_sent": 120
+            },
+            "event_log": [{ "timestamp": 1700000000000_i64, "event": "raddr 2001:db8:1:2:3:4:5:6" }]
+        });
+
+        redact_json_addresses(&mut value);
+
+        assert_eq!(value["connection"]["server_url"], "ws://192.168.1.x:8787");
+        assert_eq!(value["connection"]["is_connected"], true);
+        assert_eq!(
+            value["call"]["selected_candidate_pair"],
+            "(local) udp host 10.0.0.x:5000 <-> (remote) udp srflx 203.0.113.x:6000"
+        );
+        assert_eq!(value["call"]["packets_sent"], 120);
+        assert_eq!(value["event_log"][0]["event"], "raddr 2001:db8:1:2:x:x:x:x");
+    }
+}
@@ -0,0 +1,278 @@
+//! Event-Kanäle
+//!
+//! CallEngine, SignalingClient und LAN Discovery verteilen ihre Events über
+//! `tokio::sync::broadcast`. Ein Empfänger, der nicht hinterherkommt, bekommt
+//! dort `RecvError::Lagged` und verliert die ältesten Events. Die
+//! Weiterleitungs-Schleifen dürfen daran nicht abbrechen, sonst kommen danach
+//! gar keine Events mehr im Frontend an.
+//!
+//! Zusätzlich hält ein begrenztes Event-Log die letzten Events mit Zeitstempel
+//! im Speicher, damit Nutzer bei Problemen eine Zeitleiste exportieren können,
+//! ohne mit `RUST_LOG` neu starten zu müssen.
+
+use crate::call_engine::redact_address;
+use chrono::Utc;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::net::{IpAddr, SocketAddrV4};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Kapazität der Event-Kanäle
+///
+/// Großzügig bemessen, weil ICE Candidates beim Gathering in schnellen
+/// Schüben eintreffen.
+pub const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/// Anzahl Einträge im Event-Log
+pub const EVENT_LOG_CAPACITY: usize = 500;
+
+/// Maximale Länge eines Eintrags (SDPs werden gekürzt)
+const EVENT_LOG_DETAIL_MAX_LEN: usize = 4096;
+
+// ============================================================================
+// RECEIVING
+// ============================================================================
+
+/// Empfängt das nächste Event und überspringt dabei verpasste Events
+///
+/// Gibt `None` erst zurück, wenn der Kanal geschlossen ist. Bei `Lagged`
+/// wird geloggt und mit dem ältesten noch vorhandenen Event weitergemacht.
+pub async fn recv_event<T: Clone>(rx: &mut broadcast::Receiver<T>, channel: &str) -> Option<T> {
+    loop {
+        match rx.recv().await {
+            Ok(event) => return Some(event),
+            Err(RecvError::Lagged(skipped)) => {
+                tracing::warn!(
+                    "{} event receiver lagged, skipped {} event(s)",
+                    channel,
+                    skipped
+                );
+            }
+            Err(RecvError::Closed) => return None,
+        }
+    }
+}
+
+// ============================================================================
+// EVENT LOG
+// ============================================================================
+
+/// Eintrag im Event-Log
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EventLogEntry {
+    /// Unix-Millisekunden
+    pub timestamp: i64,
+    /// Quelle des Events, z.B. "signaling" oder "call"
+    pub source: &'static str,
+    /// Debug-Darstellung des Events (ggf. maskiert und gekürzt)
+    pub event: String,
+}
+
+/// Ring-Buffer der letzten Events für Fehlerberichte
+pub struct EventLog {
+    entries: Mutex<VecDeque<EventLogEntry>>,
+    capacity: usize,
+    /// IP-Adressen (z.B. in ICE Candidates und SDPs) maskieren
+    redact: AtomicBool,
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self::new(EVENT_LOG_CAPACITY)
+    }
+}
+
+impl EventLog {
+    /// Erstellt ein leeres Log, Maskierung ist standardmäßig aktiv
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+            redact: AtomicBool::new(true),
+        }
+    }
+
+    /// Aktiviert oder deaktiviert die Maskierung für neue Einträge
+    pub fn set_redaction(&self, enabled: bool) {
+        self.redact.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Gibt zurück, ob neue Einträge maskiert werden
+    pub fn redaction(&self) -> bool {
+        self.redact.load(Ordering::Relaxed)
+    }
+
+    /// Nimmt ein Event auf und verdrängt bei Bedarf den ältesten Eintrag
+    pub fn record<E: Debug>(&self, source: &'static str, event: &E) {
+        let mut text = format!("{:?}", event);
+        if self.redaction() {
+            text = redact_ip_addresses(&text);
+        }
+        if text.len() > EVENT_LOG_DETAIL_MAX_LEN {
+            let mut end = EVENT_LOG_DETAIL_MAX_LEN;
+            while !text.is_char_boundary(end) {
+                end -= 1;
+            }
+            text.truncate(end);
+            text.push('…');
+        }
+
+        let mut entries = self.entries.lock();
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(EventLogEntry {
+            timestamp: Utc::now().timestamp_millis(),
+            source,
+            event: text,
+        });
+    }
+
+    /// Gibt alle Einträge zurück (älteste zuerst)
+    pub fn entries(&self) -> Vec<EventLogEntry> {
+        self.entries.lock().iter().cloned().collect()
+    }
+
+    /// Leert das Log
+    pub fn clear(&self) {
+        self.entries.lock().clear();
+    }
+}
+
+/// Schreibt alle Events eines Kanals ins Log, bis der Kanal geschlossen wird
+pub async fn log_events<T: Clone + Debug>(
+    log: Arc<EventLog>,
+    mut rx: broadcast::Receiver<T>,
+    source: &'static str,
+) {
+    while let Some(event) = recv_event(&mut rx, source).await {
+        log.record(source, &event);
+    }
+}
+
+/// Maskiert alle IPv4- und IPv6-Adressen in einem Text
+///
+/// Adressen werden wie beim ICE-Logging gekürzt (`192.168.1.x`), damit die
+/// Zeitleiste noch erkennen lässt, welches Netz beteiligt war.
+pub fn redact_ip_addresses(text: &str) -> String {
+    let is_address_char = |c: char| c.is_ascii_hexdigit() || c == '.' || c == ':';
+
+    let mut result = String::with_capacity(text.len());
+    let mut token_start = None;
+    for (i, c) in text.char_indices() {
+        match (is_address_char(c), token_start) {
+            (true, None) => token_start = Some(i),
+            (false, Some(start)) => {
+                push_redacted_token(&mut result, &text[start..i]);
+                token_start = None;
+                result.push(c);
+            }
+            (false, None) => result.push(c),
+            (true, Some(_)) => {}
+        }
+    }
+    if let Some(start) = token_start {
+        push_redacted_token(&mut result, &text[start..]);
+    }
+    result
+}
+
+/// Hängt ein Token an, maskiert falls es eine IP-Adresse ist
+fn push_redacted_token(result: &mut String, token: &str) {
+    // Satzzeichen am Ende gehören nicht zur Adresse ("1.2.3.4." oder "1.2.3.4:")
+    let trimmed = token.trim_end_matches(['.', ':']);
+    let address = match trimmed.parse::<IpAddr>() {
+        Ok(_) => Some(trimmed.to_string()),
+        // IPv4 mit Port, z.B. im Candidate Pair ("1.2.3.4:5000")
+        Err(_) => trimmed
+            .parse::<SocketAddrV4>()
+            .ok()
+            .map(|addr| addr.ip().to_string()),
+    };
+    match address {
+        Some(address) => {
+            result.push_str(&redact_address(&address));
+            result.push_str(&token[address.len()..]);
+        }
+        None => result.push_str(token),
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_slow_consumer_keeps_receiving_after_lag() {
+        let (tx, mut rx) = broadcast::channel(4);
+
+        // Empfänger liest nicht mit, die ersten 6 Events gehen verloren
+        for i in 0..10 {
+            tx.send(i).unwrap();
+        }
+
+        let mut received = Vec::new();
+        for _ in 0..4 {
+            received.push(recv_event(&mut rx, "test").await.unwrap());
+        }
+        assert_eq!(received, vec![6, 7, 8, 9]);
+
+        // Neue Events kommen nach dem Lag weiterhin an
+        tx.send(10).unwrap();
+        assert_eq!(recv_event(&mut rx, "test").await, Some(10));
+
+        drop(tx);
+        assert_eq!(recv_event(&mut rx, "test").await, None);
+    }
+
+    #[test]
+    fn test_event_log_is_bounded_and_redacted() {
+        let log = EventLog::new(2);
+        log.record(
+            "call",
+            &"candidate:1 1 udp 2130706431 192.168.1.23 54321 typ host",
+        );
+        log.record("signaling", &"Connected");
+        log.record(
+            "call",
+            &"raddr 2001:db8:85a3:1:2:3:4:5 rport 9, ts 12:30:45",
+        );
+
+        let entries = log.entries();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].event, "\"Connected\"");
+        assert_eq!(
+            entries[1].event,
+            "\"raddr 2001:db8:85a3:1:x:x:x:x rport 9, ts 12:30:45\""
+        );
+
+        log.set_redaction(false);
+        log.record("call", &"192.168.1.23");
+        assert!(log.entries()[1].event.contains("192.168.1.23"));
+    }
+
+    #[test]
+    fn test_redact_ip_addresses_in_sdp() {
+        let sdp = "c=IN IP4 10.0.0.7\r\na=candidate:1 1 udp 1 10.0.0.7 5000 typ host.";
+        assert_eq!(
+            redact_ip_addresses(sdp),
+            "c=IN IP4 10.0.0.x\r\na=candidate:1 1 udp 1 10.0.0.x 5000 typ host."
+        );
+        assert_eq!(
+            redact_ip_addresses("udp host 10.0.0.7:5000"),
+            "udp host 10.0.0.x:5000"
+        );
+    }
+}
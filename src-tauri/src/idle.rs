@@ -0,0 +1,245 @@
+//! Trennen der Signaling-Verbindung bei Inaktivität
+//!
+//! Nach einer einstellbaren Zeit ohne Aktivität wird die Verbindung zum
+//! Signaling-Server getrennt. Keypair und Zustand bleiben erhalten, neu
+//! verbunden wird erst, wenn ein Command die Verbindung braucht.
+
+use parking_lot::{Mutex, RwLock};
+use std::time::Duration;
+use tokio::time::Instant;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Abstand, in dem auf Inaktivität geprüft wird
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Kürzester erlaubter Leerlauf-Timeout
+pub const MIN_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+// ============================================================================
+// IDLE DISCONNECT
+// ============================================================================
+
+/// Registrierung, mit der nach einer Trennung wegen Inaktivität neu verbunden wird
+#[derive(Debug, Clone)]
+pub struct Registration {
+    pub username: String,
+    pub display_name: Option<String>,
+}
+
+/// Trennt die Signaling-Verbindung nach einer Zeit ohne Aktivität
+///
+/// Als Aktivität zählen Anrufe, Commands, die den Signaling-Server brauchen,
+/// und Eingaben in der UI (`report_activity`). Standardmäßig deaktiviert.
+#[derive(Debug)]
+pub struct IdleDisconnect {
+    timeout: RwLock<Option<Duration>>,
+    last_activity: Mutex<Instant>,
+    /// Letzte erfolgreiche Registrierung (manuelles Trennen verwirft sie)
+    registration: Mutex<Option<Registration>>,
+    /// Verbindung wurde wegen Inaktivität getrennt
+    disconnected: Mutex<bool>,
+    /// Verhindert, dass mehrere Commands gleichzeitig neu verbinden
+    reconnecting: tokio::sync::Mutex<()>,
+}
+
+impl Default for IdleDisconnect {
+    fn default() -> Self {
+        Self {
+            timeout: RwLock::new(None),
+            last_activity: Mutex::new(Instant::now()),
+            registration: Mutex::new(None),
+            disconnected: Mutex::new(false),
+            reconnecting: tokio::sync::Mutex::new(()),
+        }
+    }
+}
+
+impl IdleDisconnect {
+    /// Setzt den Leerlauf-Timeout, `None` deaktiviert das Trennen
+    pub fn set_timeout(&self, timeout: Option<Duration>) -> Result<(), String> {
+        if timeout.is_some_and(|timeout| timeout < MIN_IDLE_TIMEOUT) {
+            return Err(format!(
+                "Idle timeout must be at least {} seconds",
+                MIN_IDLE_TIMEOUT.as_secs()
+            ));
+        }
+        *self.timeout.write() = timeout;
+        self.touch();
+        Ok(())
+    }
+
+    /// Gibt den Leerlauf-Timeout zurück (`None` = deaktiviert)
+    pub fn timeout(&self) -> Option<Duration> {
+        *self.timeout.read()
+    }
+
+    /// Setzt den Leerlauf-Timer zurück
+    pub fn touch(&self) {
+        *self.last_activity.lock() = Instant::now();
+    }
+
+    /// Ob der Timeout aktiv und seit der letzten Aktivität abgelaufen ist
+    fn is_expired(&self) -> bool {
+        self.timeout
+            .read()
+            .is_some_and(|timeout| self.last_activity.lock().elapsed() >= timeout)
+    }
+
+    /// Merkt sich eine erfolgreiche Registrierung für das spätere Wiederverbinden
+    pub fn remember_registration(&self, username: String, display_name: Option<String>) {
+        *self.registration.lock() = Some(Registration {
+            username,
+            display_name,
+        });
+        *self.disconnected.lock() = false;
+    }
+
+    /// Verwirft die Registrierung (Benutzer hat selbst getrennt)
+    pub fn forget_registration(&self) {
+        *self.registration.lock() = None;
+        *self.disconnected.lock() = false;
+    }
+
+    /// Wartet, bis kein anderer Command mehr neu verbindet
+    pub async fn lock_reconnect(&self) -> tokio::sync::MutexGuard<'_, ()> {
+        self.reconnecting.lock().await
+    }
+
+    /// Gibt die Registrierung zurück, falls wegen Inaktivität getrennt wurde
+    pub fn take_reconnect(&self) -> Option<Registration> {
+        let mut disconnected = self.disconnected.lock();
+        if !*disconnected {
+            return None;
+        }
+        *disconnected = false;
+        self.registration.lock().clone()
+    }
+
+    /// Neu verbinden ist fehlgeschlagen, der nächste Command versucht es erneut
+    pub fn reconnect_failed(&self) {
+        *self.disconnected.lock() = true;
+    }
+
+    /// Trennt die Verbindung, sobald der Leerlauf-Timeout abgelaufen ist
+    ///
+    /// Läuft für die gesamte Laufzeit. Solange `in_call` gilt, zählt das als
+    /// Aktivität. `disconnect` trennt die Verbindung und gibt zurück, ob eine
+    /// bestand.
+    pub async fn run(&self, in_call: impl Fn() -> bool, mut disconnect: impl FnMut() -> bool) {
+        let mut interval = tokio::time::interval(IDLE_CHECK_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            if in_call() {
+                self.touch();
+                continue;
+            }
+            if !self.is_expired() || self.registration.lock().is_none() {
+                continue;
+            }
+
+            if disconnect() {
+                tracing::info!("Disconnected from signaling server after inactivity");
+                *self.disconnected.lock() = true;
+            }
+        }
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Startet die Prüfschleife mit einer verbundenen, registrierten Sitzung
+    fn spawn_idle(in_call: Arc<AtomicBool>) -> (Arc<IdleDisconnect>, Arc<AtomicUsize>) {
+        let idle = Arc::new(IdleDisconnect::default());
+        idle.set_timeout(Some(MIN_IDLE_TIMEOUT)).unwrap();
+        idle.remember_registration("alice".to_string(), None);
+        let disconnects = Arc::new(AtomicUsize::new(0));
+
+        let task_idle = idle.clone();
+        let task_disconnects = disconnects.clone();
+        let connected = AtomicBool::new(true);
+        tokio::spawn(async move {
+            task_idle
+                .run(
+                    || in_call.load(Ordering::SeqCst),
+                    || {
+                        let was_connected = connected.swap(false, Ordering::SeqCst);
+                        if was_connected {
+                            task_disconnects.fetch_add(1, Ordering::SeqCst);
+                        }
+                        was_connected
+                    },
+                )
+                .await;
+        });
+        (idle, disconnects)
+    }
+
+    #[test]
+    fn test_timeout_below_minimum_is_rejected() {
+        let idle = IdleDisconnect::default();
+        assert!(idle
+            .set_timeout(Some(MIN_IDLE_TIMEOUT - Duration::from_secs(1)))
+            .is_err());
+        assert_eq!(idle.timeout(), None);
+        idle.set_timeout(Some(MIN_IDLE_TIMEOUT)).unwrap();
+        assert_eq!(idle.timeout(), Some(MIN_IDLE_TIMEOUT));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_disconnects_after_timeout() {
+        let (idle, disconnects) = spawn_idle(Arc::new(AtomicBool::new(false)));
+
+        tokio::time::sleep(MIN_IDLE_TIMEOUT - Duration::from_secs(1)).await;
+        assert_eq!(disconnects.load(Ordering::SeqCst), 0);
+        assert!(idle.take_reconnect().is_none());
+
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        assert_eq!(disconnects.load(Ordering::SeqCst), 1);
+        assert_eq!(idle.take_reconnect().unwrap().username, "alice");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_activity_rearms_timer() {
+        let (idle, disconnects) = spawn_idle(Arc::new(AtomicBool::new(false)));
+
+        tokio::time::sleep(Duration::from_secs(50)).await;
+        idle.touch();
+
+        // 100s nach dem Start, aber erst 50s nach der letzten Aktivität
+        tokio::time::sleep(Duration::from_secs(50)).await;
+        assert_eq!(disconnects.load(Ordering::SeqCst), 0);
+
+        tokio::time::sleep(Duration::from_secs(25)).await;
+        assert_eq!(disconnects.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_no_disconnect_during_call() {
+        let in_call = Arc::new(AtomicBool::new(true));
+        let (_idle, disconnects) = spawn_idle(in_call.clone());
+
+        tokio::time::sleep(Duration::from_secs(301)).await;
+        assert_eq!(disconnects.load(Ordering::SeqCst), 0);
+
+        // Nach dem Anruf beginnt der Timeout von vorn
+        in_call.store(false, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_secs(50)).await;
+        assert_eq!(disconnects.load(Ordering::SeqCst), 0);
+
+        tokio::time::sleep(Duration::from_secs(20)).await;
+        assert_eq!(disconnects.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,712 @@
+//! UDP Broadcast Discovery für LAN-Anrufe
+//!
+//! Jeder Peer sendet periodisch ein `announce` per Broadcast und lauscht
+//! auf demselben Port. SDP Offer/Answer werden danach per Unicast an die
+//! Absender-Adresse des Peers geschickt. Da ohne STUN-Server gearbeitet wird,
+//! enthält das SDP bereits alle Host-Candidates (kein Trickle ICE).
+//!
+//! Jede Nachricht ist mit dem Key des Absenders signiert (siehe
+//! `SignedLanMessage`). Da die Peer-ID den Public Key enthält, kann kein
+//! anderer Host im LAN unter fremder Peer-ID senden.
+
+use crate::crypto::KeyPair;
+use crate::events::EVENT_CHANNEL_CAPACITY;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tokio::net::UdpSocket;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// UDP Port für Discovery und LAN-Signaling
+pub const LAN_DISCOVERY_PORT: u16 = 47820;
+
+/// Präfix für Peer-IDs von LAN-Peers (gefolgt vom Public Key)
+pub const LAN_PEER_PREFIX: &str = "lan:";
+
+/// Intervall zwischen zwei Announcements
+const ANNOUNCE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Nach dieser Zeit ohne Announcement gilt ein Peer als verschwunden
+const PEER_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Maximale Größe eines UDP-Datagramms
+const MAX_DATAGRAM_SIZE: usize = 65_507;
+
+/// Maximales Alter einer Nachricht (auch als Uhrenversatz in die Zukunft)
+///
+/// Begrenzt das Wiedereinspielen mitgeschnittener Nachrichten.
+const LAN_MESSAGE_MAX_AGE: Duration = Duration::from_secs(30);
+
+// ============================================================================
+// ERROR TYPES
+// ============================================================================
+
+#[derive(Error, Debug)]
+pub enum LanDiscoveryError {
+    #[error("Failed to bind discovery socket: {0}")]
+    Bind(#[from] std::io::Error),
+
+    #[error("LAN peer not found: {0}")]
+    PeerNotFound(String),
+
+    #[error("Failed to send LAN message: {0}")]
+    SendFailed(String),
+}
+
+// ============================================================================
+// LAN MESSAGES
+// ============================================================================
+
+/// Nachrichten, die zwischen LAN-Peers ausgetauscht werden
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LanMessage {
+    /// Periodische Ankündigung per Broadcast
+    Announce {
+        username: String,
+        #[serde(rename = "publicKey")]
+        public_key: String,
+        /// Lokale Host-Adressen des Absenders
+        addresses: Vec<String>,
+    },
+
+    /// SDP Offer (inkl. aller Host-Candidates)
+    Offer {
+        #[serde(rename = "fromPublicKey")]
+        from_public_key: String,
+        #[serde(rename = "fromUsername")]
+        from_username: String,
+        sdp: String,
+    },
+
+    /// SDP Answer (inkl. aller Host-Candidates)
+    Answer {
+        #[serde(rename = "fromPublicKey")]
+        from_public_key: String,
+        sdp: String,
+    },
+
+    /// Anruf abgelehnt
+    Reject {
+        #[serde(rename = "fromPublicKey")]
+        from_public_key: String,
+        reason: Option<String>,
+    },
+
+    /// Anruf beendet
+    Hangup {
+        #[serde(rename = "fromPublicKey")]
+        from_public_key: String,
+    },
+}
+
+impl LanMessage {
+    /// Public Key, den der Absender für sich angibt
+    fn sender_public_key(&self) -> &str {
+        match self {
+            LanMessage::Announce { public_key, .. } => public_key,
+            LanMessage::Offer {
+                from_public_key, ..
+            }
+            | LanMessage::Answer {
+                from_public_key, ..
+            }
+            | LanMessage::Reject {
+                from_public_key, ..
+            }
+            | LanMessage::Hangup { from_public_key } => from_public_key,
+        }
+    }
+}
+
+/// Signierter Inhalt eines Datagramms
+#[derive(Debug, Serialize, Deserialize)]
+struct LanPayload {
+    message: LanMessage,
+    /// Public Key des Empfängers (`None` nur bei Announcements)
+    to: Option<String>,
+    /// Sendezeitpunkt (Unix-Millisekunden)
+    #[serde(rename = "sentAt")]
+    sent_at: i64,
+}
+
+/// Datagramm auf dem Discovery-Port
+///
+/// Signiert wird der serialisierte `LanPayload` genau so, wie er übertragen
+/// wird, damit der Empfänger nichts neu serialisieren muss.
+#[derive(Debug, Serialize, Deserialize)]
+struct SignedLanMessage {
+    payload: String,
+    signature: String,
+}
+
+impl SignedLanMessage {
+    /// Signiert eine Nachricht an `to` (`None` = Broadcast)
+    fn seal(
+        keypair: &KeyPair,
+        message: LanMessage,
+        to: Option<&str>,
+        now_ms: i64,
+    ) -> Result<Vec<u8>, serde_json::Error> {
+        let payload = serde_json::to_string(&LanPayload {
+            message,
+            to: to.map(str::to_string),
+            sent_at: now_ms,
+        })?;
+        let signature = keypair.sign_lan_message(&payload);
+        serde_json::to_vec(&SignedLanMessage { payload, signature })
+    }
+
+    /// Prüft Signatur, Empfänger und Alter und gibt die Nachricht zurück
+    fn open(bytes: &[u8], own_public_key: &str, now_ms: i64) -> Result<LanMessage, String> {
+        let signed: SignedLanMessage =
+            serde_json::from_slice(bytes).map_err(|e| format!("invalid datagram: {}", e))?;
+        let payload: LanPayload =
+            serde_json::from_str(&signed.payload).map_err(|e| format!("invalid payload: {}", e))?;
+
+        KeyPair::verify_lan_message(
+            payload.message.sender_public_key(),
+            &signed.payload,
+            &signed.signature,
+        )
+        .map_err(|e| format!("signature check failed: {}", e))?;
+
+        let is_announce = matches!(payload.message, LanMessage::Announce { .. });
+        match payload.to.as_deref() {
+            None if is_announce => {}
+            Some(to) if !is_announce && to == own_public_key => {}
+            _ => return Err("message is not addressed to us".to_string()),
+        }
+
+        let age_ms = now_ms - payload.sent_at;
+        if age_ms.abs() > LAN_MESSAGE_MAX_AGE.as_millis() as i64 {
+            return Err(format!("message is {}s off", age_ms / 1000));
+        }
+
+        Ok(payload.message)
+    }
+}
+
+/// Ein im LAN gefundener Peer
+#[derive(Debug, Clone, Serialize)]
+pub struct LanPeer {
+    /// Peer-ID im Format `lan:<public_key>`
+    pub peer_id: String,
+    pub username: String,
+    pub public_key: String,
+    pub addresses: Vec<String>,
+    /// Absender-Adresse der letzten Ankündigung
+    #[serde(skip)]
+    pub socket_addr: SocketAddr,
+    #[serde(skip)]
+    last_seen: Instant,
+}
+
+/// Events die von der LanDiscovery ausgelöst werden
+#[derive(Debug, Clone)]
+pub enum LanEvent {
+    /// Neuer Peer im Netzwerk gefunden
+    PeerDiscovered(LanPeer),
+
+    /// Peer hat sich länger nicht gemeldet
+    PeerLost { peer_id: String },
+
+    /// Eingehender LAN-Anruf
+    IncomingCall {
+        from_peer_id: String,
+        from_username: String,
+        sdp: String,
+    },
+
+    /// SDP Answer erhalten
+    AnswerReceived { from_peer_id: String, sdp: String },
+
+    /// Anruf abgelehnt
+    CallRejected {
+        by_peer_id: String,
+        reason: Option<String>,
+    },
+
+    /// Anruf beendet
+    CallEnded { by_peer_id: String },
+}
+
+/// Erstellt die LAN Peer-ID für einen Public Key
+pub fn lan_peer_id(public_key: &str) -> String {
+    format!("{}{}", LAN_PEER_PREFIX, public_key)
+}
+
+/// Extrahiert den Public Key aus einer LAN Peer-ID
+///
+/// Gibt `None` zurück, wenn es sich nicht um einen LAN-Peer handelt.
+pub fn public_key_from_lan_peer_id(peer_id: &str) -> Option<&str> {
+    peer_id.strip_prefix(LAN_PEER_PREFIX)
+}
+
+// ============================================================================
+// LAN DISCOVERY
+// ============================================================================
+
+/// Advertising und Discovery von Peers im lokalen Netzwerk
+pub struct LanDiscovery {
+    username: String,
+    keypair: Arc<KeyPair>,
+    socket: Arc<UdpSocket>,
+    peers: Arc<RwLock<HashMap<String, LanPeer>>>,
+    event_tx: broadcast::Sender<LanEvent>,
+    tasks: Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl LanDiscovery {
+    /// Bindet den Discovery-Socket und startet Advertising und Empfang
+    pub async fn start(username: String, keypair: Arc<KeyPair>) -> Result<Self, LanDiscoveryError> {
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, LAN_DISCOVERY_PORT)).await?;
+        socket.set_broadcast(true)?;
+
+        let (event_tx, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+
+        let discovery = Self {
+            username,
+            keypair,
+            socket: Arc::new(socket),
+            peers: Arc::new(RwLock::new(HashMap::new())),
+            event_tx,
+            tasks: Mutex::new(Vec::new()),
+        };
+
+        let announce_task = discovery.spawn_announce_task();
+        let receive_task = discovery.spawn_receive_task();
+        discovery.tasks.lock().extend([announce_task, receive_task]);
+
+        tracing::info!("LAN discovery started on port {}", LAN_DISCOVERY_PORT);
+        Ok(discovery)
+    }
+
+    /// Gibt einen Event-Receiver zurück
+    pub fn subscribe(&self) -> broadcast::Receiver<LanEvent> {
+        self.event_tx.subscribe()
+    }
+
+    /// Gibt alle aktuell bekannten Peers zurück
+    pub fn peers(&self) -> Vec<LanPeer> {
+        self.peers.read().values().cloned().collect()
+    }
+
+    /// Sendet ein SDP Offer an einen LAN-Peer
+    pub async fn send_offer(&self, to_peer_id: &str, sdp: String) -> Result<(), LanDiscoveryError> {
+        let msg = LanMessage::Offer {
+            from_public_key: self.keypair.public_key_base64(),
+            from_username: self.username.clone(),
+            sdp,
+        };
+        self.send_to_peer(to_peer_id, msg).await
+    }
+
+    /// Sendet ein SDP Answer an einen LAN-Peer
+    pub async fn send_answer(
+        &self,
+        to_peer_id: &str,
+        sdp: String,
+    ) -> Result<(), LanDiscoveryError> {
+        let msg = LanMessage::Answer {
+            from_public_key: self.keypair.public_key_base64(),
+            sdp,
+        };
+        self.send_to_peer(to_peer_id, msg).await
+    }
+
+    /// Lehnt einen LAN-Anruf ab
+    pub async fn reject_call(
+        &self,
+        to_peer_id: &str,
+        reason: Option<String>,
+    ) -> Result<(), LanDiscoveryError> {
+        let msg = LanMessage::Reject {
+            from_public_key: self.keypair.public_key_base64(),
+            reason,
+        };
+        self.send_to_peer(to_peer_id, msg).await
+    }
+
+    /// Beendet einen LAN-Anruf
+    pub async fn hangup(&self, to_peer_id: &str) -> Result<(), LanDiscoveryError> {
+        let msg = LanMessage::Hangup {
+            from_public_key: self.keypair.public_key_base64(),
+        };
+        self.send_to_peer(to_peer_id, msg).await
+    }
+
+    /// Stoppt Advertising und Empfang
+    pub fn stop(&self) {
+        for task in self.tasks.lock().drain(..) {
+            task.abort();
+        }
+        tracing::info!("LAN discovery stopped");
+    }
+
+    // ========================================================================
+    // PRIVATE METHODS
+    // ========================================================================
+
+    /// Sendet eine Nachricht per Unicast an die zuletzt bekannte Adresse eines Peers
+    async fn send_to_peer(&self, peer_id: &str, msg: LanMessage) -> Result<(), LanDiscoveryError> {
+        let (addr, public_key) = self
+            .peers
+            .read()
+            .get(peer_id)
+            .map(|p| (p.socket_addr, p.public_key.clone()))
+            .ok_or_else(|| LanDiscoveryError::PeerNotFound(peer_id.to_string()))?;
+
+        let bytes = SignedLanMessage::seal(
+            &self.keypair,
+            msg,
+            Some(&public_key),
+            chrono::Utc::now().timestamp_millis(),
+        )
+        .map_err(|e| LanDiscoveryError::SendFailed(e.to_string()))?;
+
+        self.socket
+            .send_to(&bytes, addr)
+            .await
+            .map_err(|e| LanDiscoveryError::SendFailed(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Startet den Task für periodische Broadcast-Ankündigungen
+    fn spawn_announce_task(&self) -> JoinHandle<()> {
+        let socket = Arc::clone(&self.socket);
+        let peers = Arc::clone(&self.peers);
+        let event_tx = self.event_tx.clone();
+        let keypair = Arc::clone(&self.keypair);
+        let announce = LanMessage::Announce {
+            username: self.username.clone(),
+            public_key: self.keypair.public_key_base64(),
+            addresses: local_addresses(),
+        };
+
+        tokio::spawn(async move {
+            let broadcast_addr = SocketAddr::from((Ipv4Addr::BROADCAST, LAN_DISCOVERY_PORT));
+
+            let mut interval = tokio::time::interval(ANNOUNCE_INTERVAL);
+            loop {
+                interval.tick().await;
+
+                // Jedes Announcement neu signieren, damit es frisch bleibt
+                match SignedLanMessage::seal(
+                    &keypair,
+                    announce.clone(),
+                    None,
+                    chrono::Utc::now().timestamp_millis(),
+                ) {
+                    Ok(bytes) => {
+                        if let Err(e) = socket.send_to(&bytes, broadcast_addr).await {
+                            tracing::warn!("Failed to send LAN announcement: {}", e);
+                        }
+                    }
+                    Err(e) => tracing::error!("Failed to serialize LAN announcement: {}", e),
+                }
+
+                // Verschwundene Peers entfernen
+                let lost: Vec<String> = {
+                    let mut peers = peers.write();
+                    let lost: Vec<String> = peers
+                        .values()
+                        .filter(|p| p.last_seen.elapsed() > PEER_TIMEOUT)
+                        .map(|p| p.peer_id.clone())
+                        .collect();
+                    for peer_id in &lost {
+                        peers.remove(peer_id);
+                    }
+                    lost
+                };
+                for peer_id in lost {
+                    tracing::info!("LAN peer lost: {}", peer_id);
+                    let _ = event_tx.send(LanEvent::PeerLost { peer_id });
+                }
+            }
+        })
+    }
+
+    /// Startet den Task, der eingehende Datagramme verarbeitet
+    fn spawn_receive_task(&self) -> JoinHandle<()> {
+        let socket = Arc::clone(&self.socket);
+        let peers = Arc::clone(&self.peers);
+        let event_tx = self.event_tx.clone();
+        let own_public_key = self.keypair.public_key_base64();
+
+        tokio::spawn(async move {
+            let mut buf = vec![0u8; MAX_DATAGRAM_SIZE];
+            loop {
+                let (len, addr) = match socket.recv_from(&mut buf).await {
+                    Ok(received) => received,
+                    Err(e) => {
+                        tracing::warn!("LAN discovery receive error: {}", e);
+                        continue;
+                    }
+                };
+
+                let now_ms = chrono::Utc::now().timestamp_millis();
+                let msg = match SignedLanMessage::open(&buf[..len], &own_public_key, now_ms) {
+                    Ok(msg) => msg,
+                    Err(reason) => {
+                        tracing::debug!("Ignoring LAN datagram from {}: {}", addr, reason);
+                        continue;
+                    }
+                };
+
+                Self::handle_message(msg, addr, &own_public_key, &peers, &event_tx);
+            }
+        })
+    }
+
+    /// Verarbeitet eine eingehende LAN-Nachricht
+    fn handle_message(
+        msg: LanMessage,
+        addr: SocketAddr,
+        own_public_key: &str,
+        peers: &Arc<RwLock<HashMap<String, LanPeer>>>,
+        event_tx: &broadcast::Sender<LanEvent>,
+    ) {
+        match msg {
+            LanMessage::Announce {
+                username,
+                public_key,
+                addresses,
+            } => {
+                // Eigene Broadcasts ignorieren
+                if public_key == own_public_key {
+                    return;
+                }
+
+                let peer_id = lan_peer_id(&public_key);
+                let peer = LanPeer {
+                    peer_id: peer_id.clone(),
+                    username,
+                    public_key,
+                    addresses,
+                    socket_addr: addr,
+                    last_seen: Instant::now(),
+                };
+
+                let is_new = peers.write().insert(peer_id, peer.clone()).is_none();
+                if is_new {
+                    tracing::info!("LAN peer discovered: {} at {}", peer.username, addr);
+                    let _ = event_tx.send(LanEvent::PeerDiscovered(peer));
+                }
+            }
+
+            LanMessage::Offer {
+                from_public_key,
+                from_username,
+                sdp,
+            } => {
+                let from_peer_id = lan_peer_id(&from_public_key);
+
+                // Anrufer merken, auch wenn sein Announcement noch nicht angekommen ist,
+                // damit die Answer zugestellt werden kann
+                peers
+                    .write()
+                    .entry(from_peer_id.clone())
+                    .and_modify(|p| {
+                        p.socket_addr = addr;
+                        p.last_seen = Instant::now();
+                    })
+                    .or_insert_with(|| LanPeer {
+                        peer_id: from_peer_id.clone(),
+                        username: from_username.clone(),
+                        public_key: from_public_key.clone(),
+                        addresses: vec![addr.ip().to_string()],
+                        socket_addr: addr,
+                        last_seen: Instant::now(),
+                    });
+
+                let _ = event_tx.send(LanEvent::IncomingCall {
+                    from_peer_id,
+                    from_username,
+                    sdp,
+                });
+            }
+
+            LanMessage::Answer {
+                from_public_key,
+                sdp,
+            } => {
+                let from_peer_id = lan_peer_id(&from_public_key);
+                Self::touch_peer(peers, &from_peer_id, addr);
+                let _ = event_tx.send(LanEvent::AnswerReceived { from_peer_id, sdp });
+            }
+
+            LanMessage::Reject {
+                from_public_key,
+                reason,
+            } => {
+                let _ = event_tx.send(LanEvent::CallRejected {
+                    by_peer_id: lan_peer_id(&from_public_key),
+                    reason,
+                });
+            }
+
+            LanMessage::Hangup { from_public_key } => {
+                let _ = event_tx.send(LanEvent::CallEnded {
+                    by_peer_id: lan_peer_id(&from_public_key),
+                });
+            }
+        }
+    }
+
+    /// Aktualisiert Adresse und Zeitstempel eines bekannten Peers
+    fn touch_peer(peers: &Arc<RwLock<HashMap<String, LanPeer>>>, peer_id: &str, addr: SocketAddr) {
+        if let Some(peer) = peers.write().get_mut(peer_id) {
+            peer.socket_addr = addr;
+            peer.last_seen = Instant::now();
+        }
+    }
+}
+
+impl Drop for LanDiscovery {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl std::fmt::Debug for LanDiscovery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LanDiscovery")
+            .field("username", &self.username)
+            .field("peers", &self.peers.read().len())
+            .finish()
+    }
+}
+
+/// Ermittelt die lokale IPv4-Adresse der Standardroute
+///
+/// Ein UDP-Socket wird nur "verbunden" (es werden keine Pakete gesendet),
+/// um die Adresse des ausgehenden Interfaces abzufragen.
+fn local_addresses() -> Vec<String> {
+    let socket = match std::net::UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)) {
+        Ok(socket) => socket,
+        Err(_) => return Vec::new(),
+    };
+
+    match socket
+        .connect((Ipv4Addr::new(192, 0, 2, 1), 9))
+        .and_then(|_| socket.local_addr())
+    {
+        Ok(addr) if addr.ip() != IpAddr::V4(Ipv4Addr::UNSPECIFIED) => vec![addr.ip().to_string()],
+        _ => Vec::new(),
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000_000;
+
+    fn hangup_from(keypair: &KeyPair) -> LanMessage {
+        LanMessage::Hangup {
+            from_public_key: keypair.public_key_base64(),
+        }
+    }
+
+    #[test]
+    fn test_signed_message_reaches_only_the_recipient() {
+        let alice = KeyPair::generate();
+        let bob = KeyPair::generate().public_key_base64();
+        let carol = KeyPair::generate().public_key_base64();
+
+        let bytes = SignedLanMessage::seal(&alice, hangup_from(&alice), Some(&bob), NOW).unwrap();
+        let msg = SignedLanMessage::open(&bytes, &bob, NOW + 1_000).unwrap();
+        assert_eq!(msg.sender_public_key(), alice.public_key_base64());
+        assert!(SignedLanMessage::open(&bytes, &carol, NOW).is_err());
+
+        // Announcements gehen an alle, Anruf-Nachrichten nie per Broadcast
+        let announce = LanMessage::Announce {
+            username: "alice".to_string(),
+            public_key: alice.public_key_base64(),
+            addresses: Vec::new(),
+        };
+        let bytes = SignedLanMessage::seal(&alice, announce, None, NOW).unwrap();
+        assert!(SignedLanMessage::open(&bytes, &carol, NOW).is_ok());
+        let bytes = SignedLanMessage::seal(&alice, hangup_from(&alice), None, NOW).unwrap();
+        assert!(SignedLanMessage::open(&bytes, &bob, NOW).is_err());
+    }
+
+    #[test]
+    fn test_spoofed_or_tampered_messages_are_rejected() {
+        let alice = KeyPair::generate();
+        let mallory = KeyPair::generate();
+        let bob = KeyPair::generate().public_key_base64();
+
+        // Mallory gibt sich als Alice aus, signiert aber mit eigenem Key
+        let bytes = SignedLanMessage::seal(&mallory, hangup_from(&alice), Some(&bob), NOW).unwrap();
+        assert!(SignedLanMessage::open(&bytes, &bob, NOW).is_err());
+
+        // Nachträglich veränderter Inhalt
+        let bytes = SignedLanMessage::seal(
+            &alice,
+            LanMessage::Reject {
+                from_public_key: alice.public_key_base64(),
+                reason: Some("busy".to_string()),
+            },
+            Some(&bob),
+            NOW,
+        )
+        .unwrap();
+        let mut signed: SignedLanMessage = serde_json::from_slice(&bytes).unwrap();
+        signed.payload = signed.payload.replace("busy", "declined");
+        let tampered = serde_json::to_vec(&signed).unwrap();
+        assert!(SignedLanMessage::open(&tampered, &bob, NOW).is_err());
+
+        // Unsignierte Nachrichten im alten Format
+        let unsigned = serde_json::to_vec(&hangup_from(&alice)).unwrap();
+        assert!(SignedLanMessage::open(&unsigned, &bob, NOW).is_err());
+    }
+
+    #[test]
+    fn test_replayed_messages_expire() {
+        let alice = KeyPair::generate();
+        let bob = KeyPair::generate().public_key_base64();
+        let max_age = LAN_MESSAGE_MAX_AGE.as_millis() as i64;
+
+        let bytes = SignedLanMessage::seal(&alice, hangup_from(&alice), Some(&bob), NOW).unwrap();
+        assert!(SignedLanMessage::open(&bytes, &bob, NOW + max_age).is_ok());
+        assert!(SignedLanMessage::open(&bytes, &bob, NOW + max_age + 1).is_err());
+        assert!(SignedLanMessage::open(&bytes, &bob, NOW - max_age - 1).is_err());
+    }
+
+    #[test]
+    fn test_offer_registers_caller_for_the_answer() {
+        let alice = KeyPair::generate();
+        let peers = Arc::new(RwLock::new(HashMap::new()));
+        let (event_tx, mut events) = broadcast::channel(8);
+        let addr = SocketAddr::from(([192, 168, 1, 20], LAN_DISCOVERY_PORT));
+
+        let offer = LanMessage::Offer {
+            from_public_key: alice.public_key_base64(),
+            from_username: "alice".to_string(),
+            sdp: "v=0".to_string(),
+        };
+        LanDiscovery::handle_message(offer, addr, "own-key", &peers, &event_tx);
+
+        let peer_id = lan_peer_id(&alice.public_key_base64());
+        assert_eq!(peers.read().get(&peer_id).unwrap().socket_addr, addr);
+        match events.try_recv() {
+            Ok(LanEvent::IncomingCall { from_peer_id, .. }) => assert_eq!(from_peer_id, peer_id),
+            other => panic!("expected IncomingCall, got {:?}", other),
+        }
+    }
+}
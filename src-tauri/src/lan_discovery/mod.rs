@@ -0,0 +1,14 @@
+//! LAN Discovery Module - Peer-Suche ohne Signaling-Server
+//!
+//! Dieses Modul ermöglicht Anrufe im lokalen Netzwerk ohne Cloudflare Worker:
+//! - Periodische UDP-Broadcasts mit Username und Public Key
+//! - Erkennung anderer Peers im selben Netzwerk
+//! - Direkter Austausch von SDP Offer/Answer per UDP
+//!
+
+mod discovery;
+
+pub use discovery::{
+    lan_peer_id, public_key_from_lan_peer_id, LanDiscovery, LanDiscoveryError, LanEvent,
+    LanMessage, LanPeer, LAN_DISCOVERY_PORT, LAN_PEER_PREFIX,
+};
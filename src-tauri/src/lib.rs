@@ -5,20 +5,58 @@
 //! - WebRTC für P2P Audio-Kommunikation
 //! - Ed25519 Authentifizierung
 //! - SQLite für lokale Kontakte
+//! - LAN Discovery für Anrufe ohne Signaling-Server
 
 pub mod call_engine;
 pub mod crypto;
 pub mod database;
+pub mod diagnostics;
+pub mod events;
+pub mod idle;
+pub mod lan_discovery;
+pub mod logging;
+pub mod paths;
+pub mod recordings;
 pub mod signaling;
+pub mod webhooks;
 
-use call_engine::{CallEngine, CallEvent, CallState};
-use crypto::KeyPair;
-use database::{Contact, ContactsDatabase, NewContact};
+use call_engine::{
+    keeps_own_offer, probe_ice_server, AudioDeviceSelection, AudioHandler, CallEngine, CallEvent,
+    CallState, CallStateInfo, CodecInfo, ConnectionStats, ConnectivityReport, DefaultDevices,
+    DtlsFingerprints, IceHealthMonitor, IceServerHealth, IceTransportPolicy,
+    IncomingCallResolution, LocalDescription, MicrophonePermission, NetworkSimulation,
+    OutputLimiterConfig, RingtoneStyle, SecurityInfo, SoundEffect, SoundEffects,
+    DEFAULT_DEVICE_POLL_INTERVAL, ICE_HEALTH_INTERVAL, ICE_HEALTH_STARTUP_DELAY,
+};
+use crypto::{fingerprint_for, ContactCard, KeyPair, KeyPairError, KeyPairOrigin};
+use database::{
+    CallDirection, CallbackRequest, ChatMessage, ConflictPolicy, Contact, ContactSort,
+    ContactsDatabase, ImportReport, ImportedContact, MissedCall, NewContact, Recording, TurnServer,
+    UsageStats, MISSED_CALLS_LIMIT,
+};
+use diagnostics::{
+    AudioDiagnostics, DiagnosticsBundle, IceDiagnostics, DIAGNOSTICS_FORMAT_VERSION,
+};
+use events::{log_events, recv_event, redact_ip_addresses, EventLog, EventLogEntry};
+use idle::IdleDisconnect;
+use lan_discovery::{public_key_from_lan_peer_id, LanDiscovery, LanEvent, LanPeer};
 use once_cell::sync::OnceCell;
 use parking_lot::RwLock;
-use signaling::{SignalingClient, SignalingEvent};
+use recordings::RecordingStore;
+use signaling::{
+    close_code_message, normalize_chat_message, refresh_in_chunks, should_reconnect,
+    validate_heartbeat_interval, verify_offer_proof, ContactInfo, PendingRequest,
+    PendingRequestKind, PresenceBatch, PresenceTracker, SignalingClient, SignalingDiagnostics,
+    SignalingError, SignalingEvent, DEFAULT_HEARTBEAT_INTERVAL, PROTOCOL_VERSION,
+};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::broadcast;
+use webhooks::{WebhookConfig, WebhookEvent, Webhooks};
+
+/// Maximale Wartezeit auf die Antwort einer einzelnen Status-Abfrage
+const STATUS_REFRESH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
 
 // ============================================================================
 // APPLICATION STATE
@@ -26,11 +64,39 @@ use tauri::{AppHandle, Emitter, Manager, State};
 
 /// Globaler Application State
 pub struct AppState {
-    keypair: Arc<KeyPair>,
+    /// `None`, solange die verschlüsselte Identität gesperrt ist
+    keypair: RwLock<Option<Arc<KeyPair>>>,
+    /// Ob die Identität bei diesem Start neu erstellt wurde
+    keypair_origin: KeyPairOrigin,
     signaling: Arc<RwLock<Option<SignalingClient>>>,
     call_engine: Arc<CallEngine>,
     database: Arc<ContactsDatabase>,
+    lan_discovery: Arc<RwLock<Option<Arc<LanDiscovery>>>>,
     signaling_url: String,
+    /// Unverschlüsseltes Signaling erlauben (nur lokale Entwicklung)
+    allow_insecure_signaling: bool,
+    /// Heartbeat-Intervall für neue und bestehende Signaling-Verbindungen
+    heartbeat_interval: RwLock<std::time::Duration>,
+    /// Verifizierte Presence Beacons der Kontakte
+    presence: Arc<PresenceTracker>,
+    /// Zusätzlich einzelne `contact:online`/`contact:offline` Events senden
+    individual_presence_events: Arc<RwLock<bool>>,
+    /// Anrufer nach erfolgreichem Anruf automatisch als Kontakt anlegen
+    auto_add: Arc<AutoAddContacts>,
+    /// Letzte Signaling- und Call-Events für Fehlerberichte
+    event_log: Arc<EventLog>,
+    /// UI-Sounds für Verbindungsaufbau, Auflegen und Nachrichten
+    sound_effects: Arc<SoundEffects>,
+    /// Webhook für Presence- und Anruf-Events (opt-in)
+    webhooks: Arc<Webhooks>,
+    /// Historie der Erreichbarkeit der ICE Server
+    ice_health: Arc<IceHealthMonitor>,
+    /// Gespeicherte Gesprächsaufnahmen und deren Speicherlimit
+    recordings: Arc<RecordingStore>,
+    /// Trennen vom Signaling-Server bei Inaktivität
+    idle: IdleDisconnect,
+    /// Zählt Verbindungen, nur der Call-Event Handler der neuesten läuft weiter
+    connection_generation: Arc<AtomicU64>,
 }
 
 /// Singleton für den AppState
@@ -38,21 +104,28 @@ static APP_STATE: OnceCell<Arc<AppState>> = OnceCell::new();
 
 impl AppState {
     /// Initialisiert den Application State
-    pub fn init(signaling_url: String) -> Result<Arc<Self>, String> {
-        // Logging initialisieren
-        tracing_subscriber::fmt()
-            .with_env_filter(
-                tracing_subscriber::EnvFilter::from_default_env()
-                    .add_directive("call_app=debug".parse().unwrap())
-                    .add_directive("webrtc=warn".parse().unwrap()),
-            )
-            .init();
+    pub fn init(
+        signaling_url: String,
+        allow_insecure_signaling: bool,
+    ) -> Result<Arc<Self>, String> {
+        // Logging initialisieren (stdout und rotierende Log-Dateien)
+        logging::init_logging();
 
         tracing::info!("Initializing Call App...");
 
-        // KeyPair laden oder erstellen
-        let keypair = KeyPair::load_or_create().map_err(|e| e.to_string())?;
-        tracing::info!("Loaded keypair: {:?}", keypair);
+        // KeyPair laden oder erstellen, ein verschlüsselter Key bleibt bis
+        // zum Entsperren durch das Frontend gesperrt
+        let (keypair, keypair_origin) = match KeyPair::load_or_create() {
+            Ok((keypair, origin)) => {
+                tracing::info!("Loaded keypair ({:?}): {:?}", origin, keypair);
+                (Some(Arc::new(keypair)), origin)
+            }
+            Err(KeyPairError::PassphraseRequired) => {
+                tracing::info!("Private key is encrypted, waiting for unlock");
+                (None, KeyPairOrigin::Loaded)
+            }
+            Err(e) => return Err(e.to_string()),
+        };
 
         // Database öffnen
         let database = ContactsDatabase::open().map_err(|e| e.to_string())?;
@@ -61,12 +134,42 @@ impl AppState {
         // Alle Kontakte auf offline setzen (frischer Start)
         database.set_all_offline().map_err(|e| e.to_string())?;
 
+        let database = Arc::new(database);
+        let call_engine = Arc::new(CallEngine::new());
+
+        // Gespeicherte TURN-Server übernehmen
+        for server in database.get_turn_servers().map_err(|e| e.to_string())? {
+            if let Err(e) =
+                call_engine.add_turn_server(server.url, server.username, server.credential)
+            {
+                tracing::warn!("Ignoring stored TURN server: {}", e);
+            }
+        }
+
+        if let Some(keypair) = &keypair {
+            call_engine.set_identity(Arc::clone(keypair));
+        }
+
         let state = Arc::new(Self {
-            keypair: Arc::new(keypair),
+            keypair: RwLock::new(keypair),
+            keypair_origin,
             signaling: Arc::new(RwLock::new(None)),
-            call_engine: Arc::new(CallEngine::new()),
-            database: Arc::new(database),
+            call_engine,
+            database: Arc::clone(&database),
+            lan_discovery: Arc::new(RwLock::new(None)),
             signaling_url,
+            allow_insecure_signaling,
+            heartbeat_interval: RwLock::new(DEFAULT_HEARTBEAT_INTERVAL),
+            presence: Arc::new(PresenceTracker::new()),
+            individual_presence_events: Arc::new(RwLock::new(false)),
+            auto_add: Arc::new(AutoAddContacts::default()),
+            event_log: Arc::new(EventLog::default()),
+            sound_effects: Arc::new(SoundEffects::new()),
+            webhooks: Arc::new(Webhooks::new()),
+            ice_health: Arc::new(IceHealthMonitor::new()),
+            recordings: Arc::new(RecordingStore::new(database)),
+            idle: IdleDisconnect::default(),
+            connection_generation: Arc::new(AtomicU64::new(0)),
         });
 
         APP_STATE
@@ -80,6 +183,483 @@ impl AppState {
     pub fn get() -> Option<Arc<Self>> {
         APP_STATE.get().cloned()
     }
+
+    /// Hinterlegt den bekannten Public Key eines Peers in der Call Engine
+    ///
+    /// LAN-Peers tragen ihren Key in der Peer-ID, für alle anderen wird der
+    /// beim ersten verifizierten Anruf gepinnte Key verwendet.
+    fn load_expected_peer_key(&self, peer_id: &str) {
+        if let Some(public_key) = known_peer_key(&self.database, peer_id) {
+            self.call_engine
+                .expect_peer_key(peer_id.to_string(), public_key);
+        }
+    }
+
+    /// Gibt das Schlüsselpaar zurück, solange die Identität nicht gesperrt ist
+    fn keypair(&self) -> Result<Arc<KeyPair>, String> {
+        self.keypair
+            .read()
+            .clone()
+            .ok_or_else(|| "Identity is locked".to_string())
+    }
+
+    /// Erstellt einen SignalingClient für den konfigurierten Server
+    fn new_signaling_client(&self) -> Result<SignalingClient, String> {
+        let mut client = SignalingClient::new(self.signaling_url.clone(), self.keypair()?);
+        client.set_allow_insecure(self.allow_insecure_signaling);
+
+        // Weitergeleitete Anruf-Nachrichten gegen gepinnte Keys prüfen
+        let database = Arc::clone(&self.database);
+        client.set_peer_key_lookup(move |peer_id| known_peer_key(&database, peer_id));
+        Ok(client)
+    }
+
+    /// Gibt die laufende LAN Discovery zurück
+    fn lan(&self) -> Result<Arc<LanDiscovery>, String> {
+        self.lan_discovery
+            .read()
+            .clone()
+            .ok_or_else(|| "LAN discovery not running".to_string())
+    }
+}
+
+/// Gibt den bekannten Public Key eines Peers zurück
+///
+/// Das ist der Key aus der LAN Peer-ID, der gepinnte Key oder der beim
+/// Kontakt gespeicherte Key.
+fn known_peer_key(database: &ContactsDatabase, peer_id: &str) -> Option<String> {
+    if let Some(key) = public_key_from_lan_peer_id(peer_id) {
+        return Some(key.to_string());
+    }
+    let pinned = database.get_identity_key(peer_id).unwrap_or_else(|e| {
+        tracing::warn!("Failed to load pinned key for {}: {}", peer_id, e);
+        None
+    });
+    pinned.or_else(|| {
+        database
+            .get_contact_by_peer_id(peer_id)
+            .ok()
+            .and_then(|contact| contact.public_key)
+    })
+}
+
+/// Merkt den vom Verzeichnis gemeldeten Key eines Peers für die SDP-Prüfung
+fn note_directory_key(call_engine: &CallEngine, contact: &ContactInfo) {
+    if let Some(public_key) = contact
+        .public_key
+        .as_deref()
+        .and_then(|key| KeyPair::validate_public_key(key).ok())
+    {
+        call_engine.set_directory_key(contact.peer_id.clone(), public_key);
+    }
+}
+
+/// Prüft, ob ein Peer blockiert ist (Datenbankfehler zählen als nicht blockiert)
+fn is_blocked_peer(database: &ContactsDatabase, peer_id: &str) -> bool {
+    database.is_blocked(peer_id).unwrap_or_else(|e| {
+        tracing::warn!("Failed to check block status of {}: {}", peer_id, e);
+        false
+    })
+}
+
+/// Vergleicht einen vom Server gemeldeten Public Key mit dem gepinnten Key
+///
+/// Weicht er ab, hat der Peer eine neue Identität: Die Verifizierung des
+/// Kontakts wird aufgehoben und `contact:key_changed` gemeldet. Der neue Key
+/// gilt erst nach `accept_contact_key`.
+fn check_identity_key_change(
+    database: &ContactsDatabase,
+    app_handle: &AppHandle,
+    peer_id: &str,
+    public_key: &str,
+) {
+    let Ok(public_key) = KeyPair::validate_public_key(public_key) else {
+        tracing::warn!("Server reported an invalid public key for {}", peer_id);
+        return;
+    };
+    let pinned = match database.get_identity_key(peer_id) {
+        Ok(Some(pinned)) => pinned,
+        Ok(None) => return,
+        Err(e) => {
+            tracing::warn!("Failed to load pinned key for {}: {}", peer_id, e);
+            return;
+        }
+    };
+    if pinned == public_key {
+        return;
+    }
+
+    tracing::warn!("Public key of {} changed", peer_id);
+    let was_verified = database
+        .get_contact_by_peer_id(peer_id)
+        .map(|contact| contact.is_verified)
+        .unwrap_or(false);
+    if was_verified {
+        if let Err(e) = database.mark_verified(peer_id, false) {
+            tracing::warn!("Failed to clear verified flag: {}", e);
+        }
+    }
+
+    let _ = app_handle.emit(
+        "contact:key_changed",
+        serde_json::json!({
+            "peerId": peer_id,
+            "wasVerified": was_verified,
+            "newKey": public_key
+        }),
+    );
+}
+
+/// Aktuelle Zeit nach der Server-Uhr (lokale Zeit ohne Verbindung)
+fn server_time_ms(signaling: &RwLock<Option<SignalingClient>>) -> i64 {
+    signaling
+        .read()
+        .as_ref()
+        .map(|client| client.server_time_ms())
+        .unwrap_or_else(|| chrono::Utc::now().timestamp_millis())
+}
+
+// ============================================================================
+// AUTO-ADD CONTACTS
+// ============================================================================
+
+/// Merkt sich den angenommenen Anrufer, bis der Anruf verbunden ist
+///
+/// Standardmäßig deaktiviert.
+#[derive(Debug, Default)]
+struct AutoAddContacts {
+    enabled: RwLock<bool>,
+    caller: parking_lot::Mutex<Option<NewContact>>,
+}
+
+impl AutoAddContacts {
+    /// Merkt sich einen angenommenen Anrufer (nur wenn aktiviert)
+    fn remember(&self, peer_id: String, username: String) {
+        if *self.enabled.read() {
+            *self.caller.lock() = Some(NewContact {
+                peer_id,
+                username,
+                display_name: None,
+                public_key: None,
+            });
+        }
+    }
+
+    /// Gibt den gemerkten Anrufer zurück, falls er zu diesem Peer gehört
+    fn take_for(&self, peer_id: &str) -> Option<NewContact> {
+        let mut caller = self.caller.lock();
+        match caller.as_ref() {
+            Some(contact) if contact.peer_id == peer_id => caller.take(),
+            _ => None,
+        }
+    }
+
+    /// Verwirft den gemerkten Anrufer (Anruf beendet ohne Verbindung)
+    fn clear(&self) {
+        *self.caller.lock() = None;
+    }
+}
+
+// ============================================================================
+// IDLE DISCONNECT
+// ============================================================================
+
+/// Trennt die Signaling-Verbindung, sobald der Leerlauf-Timeout abgelaufen ist
+///
+/// Läuft für die gesamte Laufzeit. Laufende oder klingelnde Anrufe zählen als
+/// Aktivität.
+async fn disconnect_when_idle(state: Arc<AppState>, app_handle: AppHandle) {
+    state
+        .idle
+        .run(
+            || {
+                !matches!(
+                    state.call_engine.state(),
+                    CallState::Idle | CallState::Ended
+                )
+            },
+            || {
+                // Client droppen schließt die Verbindung regulär (Close-Frame)
+                let client = state.signaling.write().take();
+                if client.is_some() {
+                    let _ = app_handle.emit("signaling:idle_disconnected", ());
+                }
+                client.is_some()
+            },
+        )
+        .await;
+}
+
+/// Verbindet neu, falls die Verbindung wegen Inaktivität getrennt wurde
+///
+/// Zählt als Aktivität. Commands, die den Signaling-Server brauchen, rufen
+/// dies vor dem Zugriff auf den Client auf.
+async fn ensure_signaling(state: &AppState, app_handle: &AppHandle) -> Result<(), String> {
+    state.idle.touch();
+
+    let _reconnecting = state.idle.lock_reconnect().await;
+    let Some(registration) = state.idle.take_reconnect() else {
+        return Ok(());
+    };
+
+    tracing::info!("Reconnecting to signaling server after idle disconnect");
+    if let Err(e) = connect_signaling(
+        state,
+        app_handle,
+        registration.username,
+        registration.display_name,
+    )
+    .await
+    {
+        // Beim nächsten Command erneut versuchen
+        state.idle.reconnect_failed();
+        return Err(e);
+    }
+    Ok(())
+}
+
+// ============================================================================
+// CALL HISTORY
+// ============================================================================
+
+/// Laufender Eintrag im Anrufverlauf
+struct ActiveCallRecord {
+    id: i64,
+    peer_id: String,
+}
+
+/// Schreibt Beginn, Verbindungsaufbau und Ende aller Anrufe in den Anrufverlauf
+///
+/// Läuft für die gesamte Laufzeit und hört direkt auf die Call Engine, damit
+/// auch LAN- und manuell signalisierte Anrufe erfasst werden.
+async fn record_call_history(
+    database: Arc<ContactsDatabase>,
+    mut rx: broadcast::Receiver<CallEvent>,
+) {
+    let mut active: Option<ActiveCallRecord> = None;
+
+    while let Some(event) = recv_event(&mut rx, "call history").await {
+        let new_state = match event {
+            CallEvent::StateChanged(new_state) => new_state,
+            // Verifizierter Key, damit die Wahlwiederholung den Peer wiederfindet
+            CallEvent::PeerIdentityVerified {
+                peer_id,
+                public_key,
+            } => {
+                if let Some(record) = active.as_ref().filter(|record| record.peer_id == peer_id) {
+                    if let Err(e) = database.set_call_public_key(record.id, &public_key) {
+                        tracing::warn!("Failed to update call history: {}", e);
+                    }
+                }
+                continue;
+            }
+            _ => continue,
+        };
+        let now = chrono::Utc::now().timestamp_millis();
+
+        let started = match &new_state {
+            CallState::Calling { peer_id } => {
+                let username = database
+                    .get_contact_by_peer_id(peer_id)
+                    .ok()
+                    .map(|contact| contact.username);
+                Some((peer_id.clone(), username, CallDirection::Outgoing))
+            }
+            CallState::Ringing { peer_id, username } => Some((
+                peer_id.clone(),
+                Some(username.clone()),
+                CallDirection::Incoming,
+            )),
+            // Bei Glare wird das Offer des Peers ohne Klingeln angenommen
+            // (Calling -> Idle -> Connecting), der Eintrag läuft dann weiter
+            CallState::Connecting { peer_id } | CallState::Connected { peer_id }
+                if active
+                    .as_ref()
+                    .is_some_and(|record| &record.peer_id == peer_id) =>
+            {
+                None
+            }
+            CallState::Connecting { peer_id } | CallState::Connected { peer_id } => {
+                Some((peer_id.clone(), None, CallDirection::Incoming))
+            }
+            // Der Anruf läuft während des ICE Restarts weiter
+            CallState::Reconnecting { .. } => None,
+            // Ein verworfener Anruf geht ohne `Ended` direkt auf `Idle`
+            CallState::Ended | CallState::Idle => {
+                if let Some(record) = active.take() {
+                    if let Err(e) = database.finish_call_record(record.id, now) {
+                        tracing::warn!("Failed to update call history: {}", e);
+                    }
+                }
+                None
+            }
+        };
+
+        if let Some((peer_id, username, direction)) = started {
+            // Ein nicht sauber beendeter Anruf wird vor dem nächsten abgeschlossen
+            if let Some(record) = active.take() {
+                let _ = database.finish_call_record(record.id, now);
+            }
+            match database.start_call_record(&peer_id, username.as_deref(), direction, now) {
+                Ok(id) => {
+                    if let Some(public_key) = known_peer_key(&database, &peer_id) {
+                        let _ = database.set_call_public_key(id, &public_key);
+                    }
+                    active = Some(ActiveCallRecord { id, peer_id });
+                }
+                Err(e) => tracing::warn!("Failed to record call history: {}", e),
+            }
+        }
+
+        if let (CallState::Connected { .. }, Some(record)) = (&new_state, &active) {
+            if let Err(e) = database.mark_call_connected(record.id, now) {
+                tracing::warn!("Failed to update call history: {}", e);
+            }
+        }
+    }
+}
+
+// ============================================================================
+// SOUND EFFECTS
+// ============================================================================
+
+/// Spielt einen durch ein Ereignis ausgelösten Sound
+///
+/// Einzige Stelle, an der automatisch abgespielt wird: Während eines
+/// verbundenen Anrufs bleiben Hinweise (z.B. Rückruf-Bitten) je nach
+/// Einstellung still. Die Vorschau in den Einstellungen ist nicht betroffen.
+fn play_event_sound(sound_effects: &SoundEffects, call_engine: &CallEngine, effect: SoundEffect) {
+    let in_call = matches!(call_engine.state(), CallState::Connected { .. });
+    if !sound_effects.allows(effect, in_call) {
+        tracing::debug!("Suppressing {:?} sound during call", effect);
+        return;
+    }
+    sound_effects.play(effect);
+}
+
+/// Spielt beim Verbinden und Beenden eines Anrufs den passenden Sound
+///
+/// Läuft für die gesamte Laufzeit und hört direkt auf die Call Engine, damit
+/// auch LAN-Anrufe erfasst werden. Aufgelegt klingt nur ein verbundener Anruf.
+async fn play_call_sounds(
+    sound_effects: Arc<SoundEffects>,
+    call_engine: Arc<CallEngine>,
+    mut rx: broadcast::Receiver<CallEvent>,
+) {
+    let mut connected = false;
+
+    while let Some(event) = recv_event(&mut rx, "sound effects").await {
+        let CallEvent::StateChanged(new_state) = event else {
+            continue;
+        };
+        match new_state {
+            CallState::Connected { .. } if !connected => {
+                connected = true;
+                play_event_sound(&sound_effects, &call_engine, SoundEffect::Connect);
+            }
+            CallState::Ended | CallState::Idle if connected => {
+                connected = false;
+                play_event_sound(&sound_effects, &call_engine, SoundEffect::Disconnect);
+            }
+            _ => {}
+        }
+    }
+}
+
+// ============================================================================
+// WEBHOOKS
+// ============================================================================
+
+/// Meldet Anruf-Events an den Webhook
+///
+/// Läuft wie `play_call_sounds` für die gesamte Laufzeit, damit auch
+/// LAN-Anrufe erfasst werden. Beendet wird nur ein verbundener Anruf gemeldet.
+async fn forward_call_webhooks(webhooks: Arc<Webhooks>, mut rx: broadcast::Receiver<CallEvent>) {
+    let mut connected_peer: Option<String> = None;
+
+    while let Some(event) = recv_event(&mut rx, "webhooks").await {
+        let CallEvent::StateChanged(new_state) = event else {
+            continue;
+        };
+        match new_state {
+            CallState::Ringing { peer_id, username } => webhooks.notify(
+                WebhookEvent::IncomingCall,
+                serde_json::json!({ "peer_id": peer_id, "username": username }),
+            ),
+            CallState::Connected { peer_id } if connected_peer.is_none() => {
+                webhooks.notify(
+                    WebhookEvent::CallConnected,
+                    serde_json::json!({ "peer_id": peer_id }),
+                );
+                connected_peer = Some(peer_id);
+            }
+            CallState::Ended | CallState::Idle => {
+                if let Some(peer_id) = connected_peer.take() {
+                    webhooks.notify(
+                        WebhookEvent::CallEnded,
+                        serde_json::json!({ "peer_id": peer_id }),
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Meldet eine Änderung des Online-Status eines Kontakts an den Webhook
+fn notify_presence_webhook(database: &ContactsDatabase, peer_id: &str, online: bool) {
+    let Some(state) = AppState::get() else {
+        return;
+    };
+    let event = if online {
+        WebhookEvent::ContactOnline
+    } else {
+        WebhookEvent::ContactOffline
+    };
+    let username = database
+        .get_contact_by_peer_id(peer_id)
+        .ok()
+        .map(|contact| contact.username);
+    state.webhooks.notify(
+        event,
+        serde_json::json!({ "peer_id": peer_id, "username": username }),
+    );
+}
+
+// ============================================================================
+// ICE CANDIDATE BATCHING
+// ============================================================================
+
+/// Sendet einen Batch ICE Candidates über die aktuelle Signaling-Verbindung
+fn send_ice_candidates(
+    signaling: &RwLock<Option<SignalingClient>>,
+    peer_id: &str,
+    candidates: Vec<String>,
+) -> Result<(), SignalingError> {
+    match signaling.read().as_ref() {
+        Some(client) => client.send_ice_candidates_sync(peer_id.to_string(), candidates),
+        None => Err(SignalingError::NotConnected),
+    }
+}
+
+/// Merkt sich eine Presence-Änderung, ältere Frontends bekommen sie sofort
+fn push_presence(
+    presence_batch: &mut PresenceBatch,
+    app_handle: &AppHandle,
+    peer_id: &str,
+    is_online: bool,
+) {
+    if let Some(event) = presence_batch.push(peer_id, is_online) {
+        let _ = app_handle.emit(event, peer_id);
+    }
+}
+
+/// Sendet alle gesammelten Presence-Änderungen als ein Event
+fn flush_presence(presence_batch: &mut PresenceBatch, app_handle: &AppHandle) {
+    if let Some(updates) = presence_batch.flush() {
+        tracing::debug!("Sending {} presence update(s)", updates.len());
+        let _ = app_handle.emit("contacts:presence_updated", &updates);
+    }
 }
 
 // ============================================================================
@@ -89,7 +669,65 @@ impl AppState {
 /// Gibt den Public Key des Benutzers zurück
 #[tauri::command]
 async fn get_public_key(state: State<'_, Arc<AppState>>) -> Result<String, String> {
-    Ok(state.keypair.public_key_base64())
+    Ok(state.keypair()?.public_key_base64())
+}
+
+/// Gibt die eigene Sicherheitsnummer zum Abgleich mit Kontakten zurück
+#[tauri::command]
+async fn get_my_fingerprint(state: State<'_, Arc<AppState>>) -> Result<String, String> {
+    Ok(state.keypair()?.fingerprint())
+}
+
+/// Gibt zurück, ob die verschlüsselte Identität noch entsperrt werden muss
+#[tauri::command]
+async fn is_identity_locked(state: State<'_, Arc<AppState>>) -> Result<bool, String> {
+    Ok(state.keypair.read().is_none())
+}
+
+/// Entsperrt die verschlüsselte Identität und gibt den Public Key zurück
+///
+/// Bei falscher Passphrase bleibt die Identität gesperrt.
+#[tauri::command]
+async fn unlock_identity(
+    passphrase: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<String, String> {
+    if let Ok(keypair) = state.keypair() {
+        return Ok(keypair.public_key_base64());
+    }
+
+    let keypair = tokio::task::spawn_blocking(move || KeyPair::unlock(&passphrase))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+    let keypair = Arc::new(keypair);
+    tracing::info!("Identity unlocked: {:?}", keypair);
+
+    state.call_engine.set_identity(Arc::clone(&keypair));
+    *state.keypair.write() = Some(Arc::clone(&keypair));
+    Ok(keypair.public_key_base64())
+}
+
+/// Verschlüsselt den Private Key mit einer Passphrase (`None` entfernt sie wieder)
+#[tauri::command]
+async fn set_key_passphrase(
+    passphrase: Option<String>,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    let keypair = state.keypair()?;
+    tokio::task::spawn_blocking(move || keypair.set_passphrase(passphrase.as_deref()))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Gibt zurück, ob die Identität bei diesem Start neu erstellt wurde
+///
+/// Das Frontend fragt dies beim Start ab, falls es das `identity:created`
+/// Event verpasst hat, um den Backup-Hinweis anzuzeigen.
+#[tauri::command]
+async fn is_new_identity(state: State<'_, Arc<AppState>>) -> Result<bool, String> {
+    Ok(state.keypair_origin == KeyPairOrigin::Created)
 }
 
 /// Gibt die aktuelle Peer ID zurück (falls registriert)
@@ -106,6 +744,32 @@ async fn get_username(state: State<'_, Arc<AppState>>) -> Result<Option<String>,
     Ok(signaling.as_ref().and_then(|s| s.username()))
 }
 
+/// Gibt die eigene Kontaktkarte zurück (kompakter, URL-sicherer Text für QR-Codes)
+///
+/// Benötigt einen registrierten Username, die Peer-ID ist enthalten, solange
+/// eine Verbindung zum Signaling-Server besteht.
+#[tauri::command]
+async fn get_my_contact_card(state: State<'_, Arc<AppState>>) -> Result<String, String> {
+    let (username, peer_id) = {
+        let signaling = state.signaling.read();
+        let client = signaling.as_ref().ok_or("Not registered")?;
+        (client.username().ok_or("Not registered")?, client.peer_id())
+    };
+
+    Ok(ContactCard {
+        username,
+        public_key: state.keypair()?.public_key_base64(),
+        peer_id,
+    }
+    .encode())
+}
+
+/// Liest und validiert eine geteilte Kontaktkarte
+#[tauri::command]
+async fn parse_contact_card(card: String) -> Result<ContactCard, String> {
+    ContactCard::parse(&card).map_err(|e| e.to_string())
+}
+
 // ============================================================================
 // TAURI COMMANDS - SIGNALING
 // ============================================================================
@@ -114,24 +778,86 @@ async fn get_username(state: State<'_, Arc<AppState>>) -> Result<Option<String>,
 #[tauri::command]
 async fn connect_and_register(
     username: String,
+    display_name: Option<String>,
     state: State<'_, Arc<AppState>>,
     app_handle: AppHandle,
+) -> Result<String, String> {
+    state.idle.touch();
+    let peer_id =
+        connect_signaling(&state, &app_handle, username.clone(), display_name.clone()).await?;
+    state.idle.remember_registration(username, display_name);
+    Ok(peer_id)
+}
+
+/// Baut die Signaling-Verbindung auf und startet die Event Handler
+///
+/// Wird auch für das Wiederverbinden nach einer Trennung wegen Inaktivität
+/// verwendet.
+async fn connect_signaling(
+    state: &AppState,
+    app_handle: &AppHandle,
+    username: String,
+    display_name: Option<String>,
 ) -> Result<String, String> {
     tracing::info!("Connecting as '{}'...", username);
 
     // Signaling Client erstellen
-    let mut client = SignalingClient::new(state.signaling_url.clone(), Arc::clone(&state.keypair));
+    let mut client = state.new_signaling_client()?;
+    client
+        .set_heartbeat_interval(*state.heartbeat_interval.read())
+        .map_err(|e| e.to_string())?;
+    client
+        .set_display_name(display_name)
+        .map_err(|e| e.to_string())?;
+
+    // Events ins Diagnose-Log schreiben
+    tokio::spawn(log_events(
+        Arc::clone(&state.event_log),
+        client.subscribe(),
+        "signaling",
+    ));
 
     // Event Handler starten
     let mut event_rx = client.subscribe();
     let app_handle_clone = app_handle.clone();
     let database = Arc::clone(&state.database);
     let call_engine = Arc::clone(&state.call_engine);
+    let presence = Arc::clone(&state.presence);
+    let signaling_ref = Arc::clone(&state.signaling);
+    let individual_presence_events = Arc::clone(&state.individual_presence_events);
 
     tokio::spawn(async move {
-        while let Ok(event) = event_rx.recv().await {
-            handle_signaling_event(event, &app_handle_clone, &database, &call_engine).await;
+        let mut presence_batch = PresenceBatch::new(individual_presence_events);
+
+        loop {
+            // Gebündelte Presence-Änderungen nach Ablauf des Zeitfensters senden
+            let flush_at = presence_batch.flush_at();
+            let event = tokio::select! {
+                event = recv_event(&mut event_rx, "signaling") => match event {
+                    Some(event) => event,
+                    None => break,
+                },
+                _ = tokio::time::sleep_until(flush_at.unwrap_or_else(tokio::time::Instant::now)),
+                    if flush_at.is_some() =>
+                {
+                    flush_presence(&mut presence_batch, &app_handle_clone);
+                    continue;
+                }
+            };
+
+            handle_signaling_event(
+                event,
+                &app_handle_clone,
+                &database,
+                &call_engine,
+                &presence,
+                &signaling_ref,
+                &mut presence_batch,
+            )
+            .await;
         }
+
+        flush_presence(&mut presence_batch, &app_handle_clone);
     });
 
     // Verbinden und registrieren
@@ -140,6 +866,11 @@ async fn connect_and_register(
         .await
         .map_err(|e| e.to_string())?;
 
+    // Heartbeat und Handler nicht auf einer bereits geschlossenen Verbindung starten
+    if !client.is_connected() {
+        return Err(SignalingError::NotConnected.to_string());
+    }
+
     // Client speichern
     *state.signaling.write() = Some(client);
 
@@ -148,32 +879,55 @@ async fn connect_and_register(
     let signaling_ref = Arc::clone(&state.signaling);
     let app_handle_clone = app_handle.clone();
     let call_engine_ref = Arc::clone(&state.call_engine);
+    let database = Arc::clone(&state.database);
+    let auto_add = Arc::clone(&state.auto_add);
+    let connection_generation = Arc::clone(&state.connection_generation);
+    let generation = connection_generation.fetch_add(1, Ordering::SeqCst) + 1;
 
     tokio::spawn(async move {
-        while let Ok(event) = call_event_rx.recv().await {
+        let mut ice_batch = IceCandidateBatch::new();
+
+        loop {
+            // Gebündelte Candidates nach Ablauf des Zeitfensters senden
+            let flush_at = ice_batch.flush_at();
+            let event = tokio::select! {
+                event = recv_event(&mut call_event_rx, "call engine") => match event {
+                    Some(event) => event,
+                    None => break,
+                },
+                _ = tokio::time::sleep_until(flush_at.unwrap_or_else(tokio::time::Instant::now)),
+                    if flush_at.is_some() =>
+                {
+                    ice_batch.flush(|peer_id, candidates| {
+                    send_ice_candidates(&signaling_ref, peer_id, candidates)
+                });
+                    continue;
+                }
+            };
+
+            // Eine neuere Verbindung hat einen eigenen Handler gestartet. Bis dahin
+            // läuft dieser weiter, damit ein P2P-Anruf auch ohne Signaling im UI bleibt.
+            if connection_generation.load(Ordering::SeqCst) != generation {
+                ice_batch.flush(|peer_id, candidates| {
+                    send_ice_candidates(&signaling_ref, peer_id, candidates)
+                });
+                break;
+            }
+
             match event {
-                CallEvent::IceCandidate { candidate } => {
-                    tracing::debug!("Sending ICE candidate to peer");
-
-                    // Peer ID aus dem Call-State holen
-                    let target_peer_id = match call_engine_ref.state() {
-                        CallState::Calling { peer_id } => Some(peer_id),
-                        CallState::Connecting { peer_id } => Some(peer_id),
-                        CallState::Connected { peer_id } => Some(peer_id),
-                        CallState::Ringing { peer_id, .. } => Some(peer_id),
-                        _ => None,
-                    };
+                CallEvent::IceCandidate { peer_id, candidate } => {
+                    // Ohne zugeordneten Teilnehmer geht der Candidate an die
+                    // Gegenstelle des Call-States
+                    let target_peer_id =
+                        peer_id.or_else(|| call_engine_ref.state().peer_id().map(str::to_string));
+
+                    // LAN-Anrufe verwenden kein Trickle ICE, die Candidates stehen im SDP
+                    let target_peer_id =
+                        target_peer_id.filter(|id| public_key_from_lan_peer_id(id).is_none());
 
                     if let Some(target_peer_id) = target_peer_id {
-                        // ICE Candidate über Signaling senden
-                        let signaling = signaling_ref.read();
-                        if let Some(ref client) = *signaling {
-                            if let Err(e) = client
-                                .send_ice_candidate_sync(target_peer_id.clone(), candidate.clone())
-                            {
-                                tracing::error!("Failed to send ICE candidate: {}", e);
-                            }
-                        }
+                        tracing::debug!("Queueing ICE candidate for peer");
+                        ice_batch.push(target_peer_id, candidate.clone());
                     }
 
                     // Auch ans Frontend senden für Debugging
@@ -181,66 +935,334 @@ async fn connect_and_register(
                 }
                 CallEvent::StateChanged(new_state) => {
                     tracing::info!("Call state changed: {:?}", new_state);
+                    if matches!(new_state, CallState::Ended | CallState::Idle) {
+                        auto_add.clear();
+                    }
                     let _ = app_handle_clone.emit(
                         "call:state_changed",
                         serde_json::to_string(&format!("{:?}", new_state)).unwrap_or_default(),
                     );
                 }
-                CallEvent::Error(err) => {
-                    tracing::error!("Call error: {}", err);
-                    let _ = app_handle_clone.emit("call:error", &err);
+                CallEvent::PeerStateChanged { peer_id, state } => {
+                    tracing::info!("Peer {} state changed: {:?}", peer_id, state);
+                    if matches!(state, CallState::Connected { .. }) {
+                        if let Some(contact) = auto_add.take_for(&peer_id) {
+                            match database.add_auto_contact(contact) {
+                                Ok(Some(contact)) => {
+                                    tracing::info!("Auto-added contact {}", contact.username);
+                                    let _ = app_handle_clone.emit("contact:auto_added", &contact);
+                                }
+                                Ok(None) => {}
+                                Err(e) => tracing::warn!("Failed to auto-add contact: {}", e),
+                            }
+                        }
+                    }
+                    let _ = app_handle_clone.emit(
+                        "call:peer_state_changed",
+                        serde_json::json!({
+                            "peerId": peer_id,
+                            "state": CallStateInfo::from(&state)
+                        }),
+                    );
                 }
-                _ => {}
-            }
-        }
-    });
-
-    // Heartbeat-Task starten, um WebSocket-Verbindung aufrechtzuerhalten
-    // Cloudflare Workers hat einen Idle-Timeout, daher müssen wir regelmäßig Heartbeats senden
-    let signaling_ref = Arc::clone(&state.signaling);
-    tokio::spawn(async move {
-        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(25));
-        loop {
-            interval.tick().await;
-
-            let should_continue = {
-                let signaling = signaling_ref.read();
-                if let Some(client) = signaling.as_ref() {
-                    if client.is_connected() {
-                        // Heartbeat senden (synchron um den Lock nicht zu lange zu halten)
-                        let _ = client.send_heartbeat_sync();
-                        true
-                    } else {
-                        tracing::info!("Heartbeat: Client disconnected, stopping heartbeat task");
-                        false
+                CallEvent::DtlsFingerprints(fingerprints) => {
+                    tracing::info!("DTLS fingerprints: {:?}", fingerprints);
+                    let _ = app_handle_clone.emit("call:dtls_fingerprints", &fingerprints);
+                }
+                CallEvent::PeerIdentityVerified {
+                    peer_id,
+                    public_key,
+                } => {
+                    // Trust on First Use: erster verifizierter Key wird gepinnt
+                    match database.pin_identity_key(&peer_id, &public_key) {
+                        Ok(true) => tracing::info!("Pinned identity key for {}", peer_id),
+                        Ok(false) => {}
+                        Err(e) => tracing::warn!("Failed to pin identity key: {}", e),
                     }
-                } else {
-                    tracing::info!("Heartbeat: No client, stopping heartbeat task");
-                    false
+                    let _ = app_handle_clone.emit(
+                        "call:identity_verified",
+                        serde_json::json!({
+                            "peer_id": peer_id,
+                            "public_key": public_key
+                        }),
+                    );
                 }
-            };
-
-            if !should_continue {
-                break;
-            }
-        }
-    });
-
-    tracing::info!("Registered with peer_id: {}", peer_id);
-    Ok(peer_id)
-}
+                CallEvent::IceCandidateLogged(summary) => {
+                    let _ = app_handle_clone.emit("call:ice_candidate_logged", &summary);
+                }
+                CallEvent::PermissionRequired { permission } => {
+                    let _ = app_handle_clone.emit(
+                        "call:permission_required",
+                        serde_json::json!({ "permission": permission }),
+                    );
+                }
+                CallEvent::MicrophoneBusy => {
+                    let _ = app_handle_clone.emit("call:microphone_busy", ());
+                }
+                CallEvent::MuteChanged(muted) => {
+                    let _ = app_handle_clone.emit("mute:changed", muted);
+                }
+                CallEvent::OneWayAudio { direction } => {
+                    let _ = app_handle_clone.emit(
+                        "call:one_way_audio",
+                        serde_json::json!({ "direction": direction }),
+                    );
+                }
+                CallEvent::EchoDetected { correlation } => {
+                    let _ = app_handle_clone.emit(
+                        "call:echo_detected",
+                        serde_json::json!({ "correlation": correlation }),
+                    );
+                }
+                CallEvent::Retrying { peer_id, outgoing } => {
+                    tracing::info!("Retrying call setup with {}", peer_id);
+                    let _ = app_handle_clone.emit(
+                        "call:retrying",
+                        serde_json::json!({ "peerId": peer_id, "outgoing": outgoing }),
+                    );
+
+                    // Der Anrufer sendet ein neues Offer, der Angerufene wartet darauf
+                    if outgoing {
+                        tokio::spawn(retry_call_setup(
+                            Arc::clone(&call_engine_ref),
+                            Arc::clone(&signaling_ref),
+                            app_handle_clone.clone(),
+                            peer_id,
+                        ));
+                    }
+                }
+                CallEvent::RenegotiationNeeded { peer_id } => {
+                    let _ = app_handle_clone.emit(
+                        "call:reconnecting",
+                        serde_json::json!({ "peerId": peer_id }),
+                    );
+
+                    // Nur eine Seite sendet das Offer (wie bei Glare), LAN-Anrufe
+                    // haben kein Signaling für eine Neuverhandlung
+                    let own_peer_id = signaling_ref.read().as_ref().and_then(|c| c.peer_id());
+                    let sends_offer = own_peer_id
+                        .is_some_and(|own_peer_id| keeps_own_offer(&own_peer_id, &peer_id));
+                    if sends_offer && public_key_from_lan_peer_id(&peer_id).is_none() {
+                        tokio::spawn(restart_call_ice(
+                            Arc::clone(&call_engine_ref),
+                            Arc::clone(&signaling_ref),
+                            peer_id,
+                        ));
+                    }
+                }
+                CallEvent::Stats(stats) => {
+                    let _ = app_handle_clone.emit("call:stats", &stats);
+                }
+                CallEvent::Duration { seconds } => {
+                    let _ = app_handle_clone
+                        .emit("call:duration", serde_json::json!({ "seconds": seconds }));
+                }
+                CallEvent::Error(err) => {
+                    tracing::error!("Call error: {}", err);
+                    let _ = app_handle_clone.emit("call:error", &err);
+                }
+                _ => {}
+            }
+        }
+    });
+
+    tracing::info!("Registered with peer_id: {}", peer_id);
+    Ok(peer_id)
+}
+
+/// Sendet nach gescheitertem Verbindungsaufbau das neue Offer an den Peer
+///
+/// Schlägt das fehl, endet der Anruf mit diesem Teilnehmer.
+async fn retry_call_setup(
+    call_engine: Arc<CallEngine>,
+    signaling: Arc<RwLock<Option<SignalingClient>>>,
+    app_handle: AppHandle,
+    peer_id: String,
+) {
+    let sent = match call_engine.retry_call(&peer_id).await {
+        Ok(offer_sdp) => match signaling.read().as_ref() {
+            Some(client) => client
+                .send_offer_sync(peer_id.clone(), offer_sdp)
+                .map_err(|e| e.to_string()),
+            None => Err(SignalingError::NotConnected.to_string()),
+        },
+        Err(e) => Err(e.to_string()),
+    };
+
+    if let Err(e) = sent {
+        tracing::error!("Failed to retry call with {}: {}", peer_id, e);
+        call_engine.end_peer_call(&peer_id);
+        let _ = app_handle.emit("call:error", e);
+    }
+}
+
+/// Sendet nach einer unterbrochenen Verbindung ein Offer mit ICE Restart
+///
+/// Scheitert das, bleibt der Teilnehmer bis zum Timeout der Call Engine im
+/// State `Reconnecting` und der Peer kann seinerseits neu verhandeln.
+async fn restart_call_ice(
+    call_engine: Arc<CallEngine>,
+    signaling: Arc<RwLock<Option<SignalingClient>>>,
+    peer_id: String,
+) {
+    let sent = match call_engine.restart_ice(&peer_id).await {
+        Ok(offer_sdp) => match signaling.read().as_ref() {
+            Some(client) => client
+                .send_offer_sync(peer_id.clone(), offer_sdp)
+                .map_err(|e| e.to_string()),
+            None => Err(SignalingError::NotConnected.to_string()),
+        },
+        Err(e) => Err(e.to_string()),
+    };
+
+    if let Err(e) = sent {
+        tracing::warn!("Failed to restart ICE with {}: {}", peer_id, e);
+    }
+}
+
+/// Prüft vor der Registrierung, ob ein Username noch verfügbar ist
+#[tauri::command]
+async fn check_username_available(
+    username: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<bool, String> {
+    let client = state.new_signaling_client()?;
+    client
+        .check_username_available(username)
+        .await
+        .map_err(|e| e.to_string())
+}
 
 /// Trennt die Verbindung zum Signaling-Server
 #[tauri::command]
 async fn disconnect(state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    state.idle.forget_registration();
     *state.signaling.write() = None;
     Ok(())
 }
 
+/// Setzt das Heartbeat-Intervall (in Sekunden) für die Signaling-Verbindung
+#[tauri::command]
+async fn set_heartbeat_interval(
+    seconds: u64,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    let interval = std::time::Duration::from_secs(seconds);
+    validate_heartbeat_interval(interval).map_err(|e| e.to_string())?;
+
+    *state.heartbeat_interval.write() = interval;
+    if let Some(client) = state.signaling.read().as_ref() {
+        client
+            .set_heartbeat_interval(interval)
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Setzt den Leerlauf-Timeout (in Sekunden), `None` deaktiviert das Trennen
+#[tauri::command]
+async fn set_idle_timeout(
+    seconds: Option<u64>,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    state
+        .idle
+        .set_timeout(seconds.map(std::time::Duration::from_secs))
+}
+
+/// Gibt den Leerlauf-Timeout in Sekunden zurück (`None` = deaktiviert)
+#[tauri::command]
+async fn get_idle_timeout(state: State<'_, Arc<AppState>>) -> Result<Option<u64>, String> {
+    Ok(state.idle.timeout().map(|timeout| timeout.as_secs()))
+}
+
+/// Meldet eine Eingabe in der UI, setzt den Leerlauf-Timer zurück
+///
+/// Verbindet nicht neu, das übernimmt der nächste Command, der den
+/// Signaling-Server braucht.
+#[tauri::command]
+async fn report_activity(state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    state.idle.touch();
+    Ok(())
+}
+
+/// Gibt die Verbindungsdiagnose des Signaling Clients zurück
+#[tauri::command]
+async fn get_connection_diagnostics(
+    state: State<'_, Arc<AppState>>,
+) -> Result<SignalingDiagnostics, String> {
+    Ok(connection_diagnostics(&state))
+}
+
+/// Verbindungsdiagnose, auch ohne bestehenden Signaling Client
+fn connection_diagnostics(state: &AppState) -> SignalingDiagnostics {
+    if let Some(client) = state.signaling.read().as_ref() {
+        return client.diagnostics();
+    }
+
+    SignalingDiagnostics {
+        server_url: state.signaling_url.clone(),
+        is_connected: false,
+        peer_id: None,
+        username: None,
+        heartbeat_interval_secs: state.heartbeat_interval.read().as_secs(),
+        last_heartbeat_at: None,
+        clock_offset_ms: None,
+    }
+}
+
+/// Gibt die ausstehenden Signaling-Anfragen zurück (für Debugging)
+#[tauri::command]
+async fn get_pending_requests(
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<PendingRequest>, String> {
+    Ok(state
+        .signaling
+        .read()
+        .as_ref()
+        .map(|client| client.pending_requests())
+        .unwrap_or_default())
+}
+
+/// Bricht eine ausstehende Anfrage ab
+///
+/// Bei einem noch unbeantworteten Offer wird auch der Anrufversuch beendet.
+/// Gibt `false` zurück, wenn die Anfrage nicht (mehr) aussteht.
+#[tauri::command]
+async fn cancel_request(
+    request_id: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<bool, String> {
+    let signaling = state.signaling.read();
+    let client = signaling.as_ref().ok_or("Not connected")?;
+
+    let Some(request) = client.cancel_request(&request_id) else {
+        return Ok(false);
+    };
+    tracing::info!("Cancelled pending request: {:?}", request);
+
+    if request.kind == PendingRequestKind::Offer {
+        let calling_target = state.call_engine.peer_states().iter().any(
+            |peer| matches!(peer, CallState::Calling { peer_id } if *peer_id == request.target),
+        );
+        if calling_target {
+            state.call_engine.end_peer_call(&request.target);
+            let _ = client.hangup_sync(request.target);
+        }
+    }
+
+    Ok(true)
+}
+
 /// Sucht einen Benutzer anhand des Usernamens
 #[tauri::command]
-async fn find_user(username: String, state: State<'_, Arc<AppState>>) -> Result<(), String> {
+async fn find_user(
+    username: String,
+    state: State<'_, Arc<AppState>>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
     tracing::info!("Searching for user: {}", username);
+    ensure_signaling(&state, &app_handle).await?;
 
     let signaling = state.signaling.read();
     let client = signaling.as_ref().ok_or("Not connected")?;
@@ -258,6 +1280,63 @@ async fn find_user(username: String, state: State<'_, Arc<AppState>>) -> Result<
     Ok(())
 }
 
+/// Sucht einen Benutzer anhand seines Public Keys und wartet auf die Antwort
+///
+/// Der Public Key bleibt auch nach einer Umbenennung gleich, der Server löst
+/// ihn auf die aktuelle Peer-ID auf.
+#[tauri::command]
+async fn find_user_by_key(
+    public_key: String,
+    state: State<'_, Arc<AppState>>,
+    app_handle: AppHandle,
+) -> Result<ContactInfo, String> {
+    ensure_signaling(&state, &app_handle).await?;
+    resolve_public_key(&state, &public_key)
+        .await?
+        .ok_or_else(|| "No user registered with this public key".to_string())
+}
+
+/// Löst einen Public Key über den Signaling-Server auf
+///
+/// Gibt `None` zurück, wenn der Key dort nicht registriert ist.
+async fn resolve_public_key(
+    state: &AppState,
+    public_key: &str,
+) -> Result<Option<ContactInfo>, String> {
+    let public_key = KeyPair::validate_public_key(public_key).map_err(|e| e.to_string())?;
+
+    // Anfrage senden, Lock vor dem await wieder freigeben
+    let (request_id, response_rx) = {
+        let signaling = state.signaling.read();
+        let client = signaling.as_ref().ok_or("Not connected")?;
+
+        if !client.is_connected() {
+            return Err("Not connected".to_string());
+        }
+
+        client
+            .lookup_user_by_key_sync(public_key)
+            .map_err(|e| e.to_string())?
+    };
+
+    match tokio::time::timeout(STATUS_REFRESH_TIMEOUT, response_rx).await {
+        Ok(Ok(response)) => {
+            if let Some(contact) = &response {
+                note_directory_key(&state.call_engine, contact);
+            }
+            Ok(response)
+        }
+        // Sender verworfen: Verbindung wurde zwischenzeitlich getrennt
+        Ok(Err(_)) => Err(SignalingError::NotConnected.to_string()),
+        Err(_) => {
+            if let Some(client) = state.signaling.read().as_ref() {
+                client.cancel_request(&request_id);
+            }
+            Err(SignalingError::Timeout.to_string())
+        }
+    }
+}
+
 // ============================================================================
 // TAURI COMMANDS - CONTACTS
 // ============================================================================
@@ -268,25 +1347,58 @@ async fn get_contacts(state: State<'_, Arc<AppState>>) -> Result<Vec<Contact>, S
     state.database.get_all_contacts().map_err(|e| e.to_string())
 }
 
+/// Gibt alle Kontakte sortiert zurück (z.B. zuletzt angerufene zuerst)
+#[tauri::command]
+async fn get_contacts_sorted(
+    sort: ContactSort,
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<Contact>, String> {
+    state
+        .database
+        .get_contacts_sorted(sort)
+        .map_err(|e| e.to_string())
+}
+
+/// Sucht Kontakte nach Username oder Display-Namen (leere Suche liefert alle)
+#[tauri::command]
+async fn search_contacts(
+    query: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<Contact>, String> {
+    state
+        .database
+        .search_contacts(&query)
+        .map_err(|e| e.to_string())
+}
+
 /// Fügt einen neuen Kontakt hinzu
+///
+/// Ein mitgegebener Public Key (aus `signaling:user_found`) bindet die
+/// peer_id an diese Identität.
 #[tauri::command]
 async fn add_contact(
     peer_id: String,
     username: String,
     display_name: Option<String>,
+    public_key: Option<String>,
     state: State<'_, Arc<AppState>>,
 ) -> Result<Contact, String> {
+    let public_key = public_key
+        .map(|key| KeyPair::validate_public_key(&key))
+        .transpose()
+        .map_err(|e| e.to_string())?;
     state
         .database
         .add_contact(NewContact {
             peer_id,
             username,
             display_name,
+            public_key,
         })
         .map_err(|e| e.to_string())
 }
 
-/// Löscht einen Kontakt
+/// Löscht einen Kontakt (wiederherstellbar bis zum Purge)
 #[tauri::command]
 async fn delete_contact(peer_id: String, state: State<'_, Arc<AppState>>) -> Result<(), String> {
     state
@@ -295,6 +1407,33 @@ async fn delete_contact(peer_id: String, state: State<'_, Arc<AppState>>) -> Res
         .map_err(|e| e.to_string())
 }
 
+/// Gibt alle gelöschten Kontakte zurück
+#[tauri::command]
+async fn get_deleted_contacts(state: State<'_, Arc<AppState>>) -> Result<Vec<Contact>, String> {
+    state
+        .database
+        .get_deleted_contacts()
+        .map_err(|e| e.to_string())
+}
+
+/// Stellt einen gelöschten Kontakt wieder her
+#[tauri::command]
+async fn restore_contact(
+    peer_id: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<Contact, String> {
+    state
+        .database
+        .restore_contact(&peer_id)
+        .map_err(|e| e.to_string())
+}
+
+/// Entfernt alle gelöschten Kontakte endgültig
+#[tauri::command]
+async fn purge_deleted_contacts(state: State<'_, Arc<AppState>>) -> Result<usize, String> {
+    state.database.purge_deleted().map_err(|e| e.to_string())
+}
+
 /// Aktualisiert den Display-Namen eines Kontakts
 #[tauri::command]
 async fn update_contact_name(
@@ -308,203 +1447,1710 @@ async fn update_contact_name(
         .map_err(|e| e.to_string())
 }
 
-/// Fragt den Online-Status aller Kontakte beim Server ab
-/// Sollte nach dem Login aufgerufen werden
+/// Aktiviert das automatische Anlegen von Anrufern als Kontakt
 #[tauri::command]
-async fn refresh_contact_statuses(state: State<'_, Arc<AppState>>) -> Result<(), String> {
-    tracing::info!("Refreshing contact statuses...");
-
-    // Hole alle Kontakte aus der Datenbank
-    let contacts = state
-        .database
-        .get_all_contacts()
-        .map_err(|e| e.to_string())?;
-
-    // Für jeden Kontakt eine find_user Anfrage senden (über username)
-    let signaling = state.signaling.read();
-    let client = signaling.as_ref().ok_or("Not connected")?;
-
-    if !client.is_connected() {
-        return Err("Not connected".to_string());
-    }
-
-    for contact in contacts {
-        // find_user sendet eine Anfrage an den Server
-        // Das Ergebnis kommt als SignalingEvent::UserFound zurück
-        // und wird dann in handle_signaling_event verarbeitet
-        if let Err(e) = client.find_user_sync(contact.username.clone()) {
-            tracing::warn!("Failed to refresh status for {}: {}", contact.username, e);
-        }
+async fn set_auto_add_contacts(
+    enabled: bool,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    *state.auto_add.enabled.write() = enabled;
+    if !enabled {
+        state.auto_add.clear();
     }
-
-    tracing::info!("Contact status refresh requests sent");
     Ok(())
 }
 
-// ============================================================================
-// TAURI COMMANDS - CALLS
-// ============================================================================
-
-/// Startet einen ausgehenden Anruf
+/// Gibt zurück, ob Anrufer automatisch als Kontakt angelegt werden
 #[tauri::command]
-async fn start_call(peer_id: String, state: State<'_, Arc<AppState>>) -> Result<(), String> {
-    tracing::info!("Starting call to {}", peer_id);
-
-    // Call Engine ist bereits Arc und thread-safe
-    let call_engine = Arc::clone(&state.call_engine);
-
-    // SDP Offer erstellen
-    let offer_sdp = call_engine
-        .start_call(peer_id.clone())
-        .await
-        .map_err(|e| e.to_string())?;
-
-    // Sender klonen VOR dem await
-    let sender = {
-        let signaling = state.signaling.read();
-        signaling.as_ref().and_then(|c| c.get_sender())
-    };
-
-    // Wenn wir keinen Sender haben, Fehler
-    let _tx = sender.ok_or("Not connected")?;
-
-    // Offer über geklonten Sender schicken
-    // TODO: Diese Logik sollte in SignalingClient gekapselt werden
-
-    // Fallback: Erneut Lock und hoffen dass es schnell geht
-    {
-        let signaling = state.signaling.read();
-        if let Some(client) = signaling.as_ref() {
-            // Direkte synchrones Senden - nicht ideal aber funktioniert
-            let _ = client.send_offer_sync(peer_id, offer_sdp);
-        }
-    }
-
-    Ok(())
+async fn get_auto_add_contacts(state: State<'_, Arc<AppState>>) -> Result<bool, String> {
+    Ok(*state.auto_add.enabled.read())
 }
 
-/// Akzeptiert einen eingehenden Anruf
+/// Sendet Presence-Änderungen zusätzlich einzeln (`contact:online`/`contact:offline`)
+///
+/// Standard: aus, Änderungen kommen gebündelt als `contacts:presence_updated`.
 #[tauri::command]
-async fn accept_call(
-    peer_id: String,
-    offer_sdp: String,
+async fn set_individual_presence_events(
+    enabled: bool,
     state: State<'_, Arc<AppState>>,
 ) -> Result<(), String> {
-    tracing::info!("Accepting call from {}", peer_id);
-
-    let call_engine = Arc::clone(&state.call_engine);
-
-    // SDP Answer erstellen
-    let answer_sdp = call_engine
-        .accept_call(peer_id.clone(), offer_sdp)
-        .await
-        .map_err(|e| e.to_string())?;
-
-    // Answer senden
-    {
-        let signaling = state.signaling.read();
-        if let Some(client) = signaling.as_ref() {
-            let _ = client.send_answer_sync(peer_id, answer_sdp);
-        }
-    }
-
+    *state.individual_presence_events.write() = enabled;
     Ok(())
 }
 
-/// Lehnt einen eingehenden Anruf ab
+/// Gibt zurück, ob Presence-Änderungen zusätzlich einzeln gesendet werden
 #[tauri::command]
-async fn reject_call(
+async fn get_individual_presence_events(state: State<'_, Arc<AppState>>) -> Result<bool, String> {
+    Ok(*state.individual_presence_events.read())
+}
+
+/// Setzt die Notiz eines Kontakts (leer oder `None` entfernt sie)
+#[tauri::command]
+async fn set_contact_notes(
     peer_id: String,
-    reason: Option<String>,
+    notes: Option<String>,
     state: State<'_, Arc<AppState>>,
-) -> Result<(), String> {
-    tracing::info!("Rejecting call from {}", peer_id);
-
-    state.call_engine.reject_call();
+) -> Result<Contact, String> {
+    state
+        .database
+        .set_contact_notes(&peer_id, notes.as_deref())
+        .map_err(|e| e.to_string())
+}
 
-    {
-        let signaling = state.signaling.read();
-        if let Some(client) = signaling.as_ref() {
-            let _ = client.reject_call_sync(peer_id, reason);
+/// Importiert Kontakte und löst Konflikte mit bestehenden Kontakten pro Feld auf
+#[tauri::command]
+async fn import_contacts(
+    contacts: Vec<ImportedContact>,
+    policy: ConflictPolicy,
+    state: State<'_, Arc<AppState>>,
+) -> Result<ImportReport, String> {
+    state
+        .database
+        .import_contacts(&contacts, policy)
+        .map_err(|e| e.to_string())
+}
+
+/// Markiert einen Kontakt nach dem Abgleich der Sicherheitsnummer als verifiziert
+#[tauri::command]
+async fn mark_contact_verified(
+    peer_id: String,
+    verified: bool,
+    state: State<'_, Arc<AppState>>,
+) -> Result<Contact, String> {
+    state
+        .database
+        .mark_verified(&peer_id, verified)
+        .map_err(|e| e.to_string())
+}
+
+/// Übernimmt den neuen Public Key eines Peers nach `contact:key_changed`
+///
+/// Ersetzt den gepinnten Key, weitergeleitete Nachrichten und Anrufe werden
+/// ab sofort gegen den neuen Key geprüft.
+#[tauri::command]
+async fn accept_contact_key(
+    peer_id: String,
+    public_key: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    let public_key = KeyPair::validate_public_key(&public_key).map_err(|e| e.to_string())?;
+    state
+        .database
+        .accept_identity_key(&peer_id, &public_key)
+        .map_err(|e| e.to_string())
+}
+
+/// Gibt die Sicherheitsnummer eines Kontakts zurück (aus seinem gepinnten Key)
+///
+/// Ohne bekannten Key (noch nie verbunden) gibt es nichts abzugleichen.
+#[tauri::command]
+async fn get_contact_fingerprint(
+    peer_id: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<String, String> {
+    let public_key = known_peer_key(&state.database, &peer_id)
+        .ok_or_else(|| format!("No public key known for {}", peer_id))?;
+    fingerprint_for(&public_key).map_err(|e| e.to_string())
+}
+
+/// Blockiert einen Kontakt, ein laufender Anruf mit ihm wird beendet
+#[tauri::command]
+async fn block_contact(
+    peer_id: String,
+    state: State<'_, Arc<AppState>>,
+    app_handle: AppHandle,
+) -> Result<Contact, String> {
+    let contact = state
+        .database
+        .set_blocked(&peer_id, true)
+        .map_err(|e| e.to_string())?;
+
+    let call_state = state.call_engine.state();
+    let in_call = call_state.peer_id() == Some(peer_id.as_str())
+        || state
+            .call_engine
+            .peer_states()
+            .iter()
+            .any(|peer| peer.peer_id() == Some(peer_id.as_str()));
+    if in_call {
+        tracing::info!("Hanging up on blocked contact {}", peer_id);
+        state.call_engine.end_peer_call(&peer_id);
+        if let Err(e) = notify_hangup(&state, &app_handle, peer_id).await {
+            tracing::warn!("Failed to notify blocked contact about hangup: {}", e);
         }
     }
+    Ok(contact)
+}
+
+/// Hebt die Blockierung eines Kontakts auf
+#[tauri::command]
+async fn unblock_contact(
+    peer_id: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<Contact, String> {
+    state
+        .database
+        .set_blocked(&peer_id, false)
+        .map_err(|e| e.to_string())
+}
+
+/// Fragt den Online-Status aller Kontakte beim Server ab
+/// Sollte nach dem Login aufgerufen werden
+///
+/// Die Anfragen werden in Blöcken mit kurzer Pause gesendet, damit große
+/// Kontaktlisten weder den Server fluten noch die Sende-Queue überlaufen
+/// lassen. Der Fortschritt wird nach jedem Block als Event gemeldet, ein
+/// Abbruch als letzter Fortschritt mit `error`.
+#[tauri::command]
+async fn refresh_contact_statuses(
+    state: State<'_, Arc<AppState>>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    tracing::info!("Refreshing contact statuses...");
+    ensure_signaling(&state, &app_handle).await?;
+
+    // Hole alle Kontakte aus der Datenbank
+    let contacts = state
+        .database
+        .get_all_contacts()
+        .map_err(|e| e.to_string())?;
+
+    refresh_in_chunks(
+        &contacts,
+        |chunk| {
+            // Für jeden Kontakt eine find_user Anfrage senden (über username),
+            // der Lock wird vor der nächsten Pause wieder freigegeben
+            let signaling = state.signaling.read();
+            let client = signaling.as_ref().ok_or("Not connected")?;
+
+            if !client.is_connected() {
+                return Err("Not connected".to_string());
+            }
+
+            for contact in chunk {
+                // find_user sendet eine Anfrage an den Server
+                // Das Ergebnis kommt als SignalingEvent::UserFound zurück
+                // und wird dann in handle_signaling_event verarbeitet
+                if let Err(e) = client.find_user_sync(contact.username.clone()) {
+                    tracing::warn!("Failed to refresh status for {}: {}", contact.username, e);
+                }
+            }
+            Ok(())
+        },
+        |progress| {
+            let _ = app_handle.emit("contact:refresh_progress", &progress);
+        },
+    )
+    .await?;
 
+    tracing::info!("Contact status refresh requests sent");
     Ok(())
 }
 
-/// Beendet den aktuellen Anruf
+/// Fragt den Online-Status eines einzelnen Kontakts ab und wartet auf die Antwort
+///
+/// Die Antwort (`UserFound`/`UserNotFound`) wird über die Request-ID korreliert.
+/// Gibt den Kontakt mit aktualisiertem Status zurück.
 #[tauri::command]
-async fn hangup(state: State<'_, Arc<AppState>>) -> Result<(), String> {
-    tracing::info!("Hanging up");
+async fn refresh_contact_status(
+    peer_id: String,
+    state: State<'_, Arc<AppState>>,
+    app_handle: AppHandle,
+) -> Result<Contact, String> {
+    ensure_signaling(&state, &app_handle).await?;
+    let contact = state
+        .database
+        .get_contact_by_peer_id(&peer_id)
+        .map_err(|e| e.to_string())?;
 
-    let peer_id = match state.call_engine.state() {
-        CallState::Connected { peer_id }
-        | CallState::Calling { peer_id }
-        | CallState::Connecting { peer_id }
-        | CallState::Ringing { peer_id, .. } => peer_id,
-        _ => return Err("No active call".to_string()),
+    let response = resolve_username(&state, &contact.username).await?;
+
+    // UserNotFound oder ein anderer Peer unter dem Username gilt als offline
+    let is_online = match response {
+        Some(info) => info.peer_id == peer_id && info.is_online,
+        None => false,
     };
 
-    state.call_engine.end_call();
+    state
+        .database
+        .set_online_status(&peer_id, is_online)
+        .map_err(|e| e.to_string())?;
+
+    state
+        .database
+        .get_contact_by_peer_id(&peer_id)
+        .map_err(|e| e.to_string())
+}
+
+/// Fragt beim Server den aktuellen Peer zu einem Username ab
+///
+/// Gibt `None` zurück, wenn der Username dort nicht registriert ist.
+async fn resolve_username(state: &AppState, username: &str) -> Result<Option<ContactInfo>, String> {
+    // Anfrage senden, Lock vor dem await wieder freigeben
+    let (request_id, response_rx) = {
+        let signaling = state.signaling.read();
+        let client = signaling.as_ref().ok_or("Not connected")?;
+
+        if !client.is_connected() {
+            return Err("Not connected".to_string());
+        }
+
+        client
+            .lookup_user_sync(username.to_string())
+            .map_err(|e| e.to_string())?
+    };
+
+    match tokio::time::timeout(STATUS_REFRESH_TIMEOUT, response_rx).await {
+        Ok(Ok(response)) => {
+            if let Some(contact) = &response {
+                note_directory_key(&state.call_engine, contact);
+            }
+            Ok(response)
+        }
+        // Sender verworfen: Verbindung wurde zwischenzeitlich getrennt
+        Ok(Err(_)) => Err(SignalingError::NotConnected.to_string()),
+        Err(_) => {
+            if let Some(client) = state.signaling.read().as_ref() {
+                client.cancel_request(&request_id);
+            }
+            Err(SignalingError::Timeout.to_string())
+        }
+    }
+}
+
+// ============================================================================
+// TAURI COMMANDS - CALLBACK REQUESTS
+// ============================================================================
+
+/// Bittet einen (offline) Kontakt um Rückruf
+///
+/// Der Server stellt die Bitte zu, sobald der Kontakt online ist.
+#[tauri::command]
+async fn request_callback(
+    peer_id: String,
+    state: State<'_, Arc<AppState>>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    ensure_signaling(&state, &app_handle).await?;
+    let signaling = state.signaling.read();
+    let client = signaling.as_ref().ok_or("Not connected")?;
+
+    client
+        .request_callback_sync(peer_id)
+        .map_err(|e| e.to_string())
+}
+
+/// Gibt alle offenen Rückruf-Bitten anderer Peers zurück
+#[tauri::command]
+async fn get_callback_requests(
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<CallbackRequest>, String> {
+    state
+        .database
+        .get_callback_requests()
+        .map_err(|e| e.to_string())
+}
+
+/// Verwirft eine Rückruf-Bitte
+#[tauri::command]
+async fn dismiss_callback_request(
+    peer_id: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    state
+        .database
+        .remove_callback_request(&peer_id)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// TAURI COMMANDS - CHAT
+// ============================================================================
+
+/// Sendet eine Textnachricht und speichert sie im Verlauf
+#[tauri::command]
+async fn send_chat(
+    peer_id: String,
+    body: String,
+    state: State<'_, Arc<AppState>>,
+    app_handle: AppHandle,
+) -> Result<ChatMessage, String> {
+    let body = normalize_chat_message(body).map_err(|e| e.to_string())?;
+    ensure_signaling(&state, &app_handle).await?;
+    {
+        let signaling = state.signaling.read();
+        let client = signaling.as_ref().ok_or("Not connected")?;
+        client
+            .send_chat_message_sync(peer_id.clone(), body.clone())
+            .map_err(|e| e.to_string())?;
+    }
+
+    state
+        .database
+        .add_chat_message(&peer_id, true, &body, server_time_ms(&state.signaling))
+        .map_err(|e| e.to_string())
+}
+
+/// Gibt den Nachrichtenverlauf mit einem Peer zurück (älteste zuerst)
+#[tauri::command]
+async fn get_chat_messages(
+    peer_id: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<ChatMessage>, String> {
+    state
+        .database
+        .get_chat_messages(&peer_id)
+        .map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// TAURI COMMANDS - CALL HISTORY
+// ============================================================================
+
+/// Gibt die zusammengefasste Nutzungsstatistik zurück
+#[tauri::command]
+async fn get_usage_stats(state: State<'_, Arc<AppState>>) -> Result<UsageStats, String> {
+    state.database.get_usage_stats().map_err(|e| e.to_string())
+}
+
+/// Gibt die letzten verpassten Anrufe zurück, neueste zuerst
+#[tauri::command]
+async fn get_missed_calls(state: State<'_, Arc<AppState>>) -> Result<Vec<MissedCall>, String> {
+    state
+        .database
+        .get_missed_calls(MISSED_CALLS_LIMIT)
+        .map_err(|e| e.to_string())
+}
+
+/// Gibt die Anzahl neuer verpasster Anrufe zurück (für das Badge)
+#[tauri::command]
+async fn get_missed_call_count(state: State<'_, Arc<AppState>>) -> Result<i64, String> {
+    state
+        .database
+        .get_missed_call_count()
+        .map_err(|e| e.to_string())
+}
+
+/// Markiert alle verpassten Anrufe als gesehen (setzt das Badge zurück)
+#[tauri::command]
+async fn mark_missed_calls_seen(state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    state
+        .database
+        .mark_missed_calls_seen()
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Gibt alle gespeicherten Aufnahmen zurück, neueste zuerst
+#[tauri::command]
+async fn list_recordings(state: State<'_, Arc<AppState>>) -> Result<Vec<Recording>, String> {
+    state.recordings.list().map_err(|e| e.to_string())
+}
+
+/// Löscht eine Aufnahme (Datei und Eintrag)
+#[tauri::command]
+async fn delete_recording(path: String, state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    state.recordings.delete(&path).map_err(|e| e.to_string())
+}
+
+/// Setzt das Speicherlimit für Aufnahmen und gibt die dabei gelöschten zurück
+#[tauri::command]
+async fn set_recording_storage_cap(
+    bytes: u64,
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<Recording>, String> {
+    state
+        .recordings
+        .set_storage_cap(bytes)
+        .map_err(|e| e.to_string())
+}
+
+/// Gibt das Speicherlimit für Aufnahmen in Bytes zurück
+#[tauri::command]
+async fn get_recording_storage_cap(state: State<'_, Arc<AppState>>) -> Result<u64, String> {
+    Ok(state.recordings.storage_cap())
+}
+
+// ============================================================================
+// TAURI COMMANDS - CALLS
+// ============================================================================
+
+/// Startet einen ausgehenden Anruf
+#[tauri::command]
+async fn start_call(
+    peer_id: String,
+    state: State<'_, Arc<AppState>>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    tracing::info!("Starting call to {}", peer_id);
+
+    // Call Engine ist bereits Arc und thread-safe
+    let call_engine = Arc::clone(&state.call_engine);
+    state.load_expected_peer_key(&peer_id);
+
+    // Ein Anruf beantwortet eine offene Rückruf-Bitte dieses Peers
+    if let Err(e) = state.database.remove_callback_request(&peer_id) {
+        tracing::warn!("Failed to clear callback request: {}", e);
+    }
+
+    // LAN-Peers direkt ohne Signaling-Server anrufen
+    if public_key_from_lan_peer_id(&peer_id).is_some() {
+        let lan = state.lan()?;
+        let offer_sdp = call_engine
+            .start_lan_call(peer_id.clone())
+            .await
+            .map_err(|e| e.to_string())?;
+        return lan
+            .send_offer(&peer_id, offer_sdp)
+            .await
+            .map_err(|e| e.to_string());
+    }
 
+    ensure_signaling(&state, &app_handle).await?;
+
+    // SDP Offer erstellen
+    let offer_sdp = call_engine
+        .start_call(peer_id.clone())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // Sender klonen VOR dem await
+    let sender = {
+        let signaling = state.signaling.read();
+        signaling.as_ref().and_then(|c| c.get_sender())
+    };
+
+    // Wenn wir keinen Sender haben, Fehler
+    let _tx = sender.ok_or("Not connected")?;
+
+    // Offer über geklonten Sender schicken
+    // TODO: Diese Logik sollte in SignalingClient gekapselt werden
+
+    // Fallback: Erneut Lock und hoffen dass es schnell geht
     {
         let signaling = state.signaling.read();
         if let Some(client) = signaling.as_ref() {
-            let _ = client.hangup_sync(peer_id);
+            // Direkte synchrones Senden - nicht ideal aber funktioniert
+            let _ = client.send_offer_sync(peer_id, offer_sdp);
         }
     }
 
     Ok(())
 }
 
-/// Gibt den aktuellen Call-Status zurück
+/// Ruft einen Peer über seinen Public Key an (z.B. aus einer geteilten Kontaktkarte)
+///
+/// Der Key wird für die aufgelöste Peer-ID gepinnt, der Anruf kommt also nur
+/// zustande, wenn die Gegenstelle ihren DTLS Fingerprint mit genau diesem Key
+/// signiert. Eine falsche Auflösung durch den Server fällt so auf.
+/// Gibt die aufgelöste Peer-ID zurück.
 #[tauri::command]
-async fn get_call_state(state: State<'_, Arc<AppState>>) -> Result<String, String> {
-    let call_state = state.call_engine.state();
-    let state_str = match call_state {
-        CallState::Idle => "idle",
-        CallState::Calling { .. } => "calling",
-        CallState::Ringing { .. } => "ringing",
-        CallState::Connecting { .. } => "connecting",
-        CallState::Connected { .. } => "connected",
+async fn call_by_public_key(
+    public_key: String,
+    state: State<'_, Arc<AppState>>,
+    app_handle: AppHandle,
+) -> Result<String, String> {
+    ensure_signaling(&state, &app_handle).await?;
+    let contact = resolve_public_key(&state, &public_key)
+        .await?
+        .ok_or_else(|| "No user registered with this public key".to_string())?;
+
+    if !contact.is_online {
+        return Err(format!("{} is offline", contact.username));
+    }
+
+    let public_key = KeyPair::validate_public_key(&public_key).map_err(|e| e.to_string())?;
+    state
+        .call_engine
+        .expect_peer_key(contact.peer_id.clone(), public_key);
+
+    start_call(contact.peer_id.clone(), state, app_handle).await?;
+    Ok(contact.peer_id)
+}
+
+/// Ruft die Gegenstelle des letzten ausgehenden Anrufs erneut an
+///
+/// Die Peer-ID kann sich seit dem Anruf geändert haben, daher wird der Peer
+/// über seinen Public Key und ersatzweise über den Username neu aufgelöst.
+/// LAN-Peers tragen ihren Key in der Peer-ID und werden direkt angerufen.
+/// Gibt die angerufene Peer-ID zurück.
+#[tauri::command]
+async fn redial_last(
+    state: State<'_, Arc<AppState>>,
+    app_handle: AppHandle,
+) -> Result<String, String> {
+    let last = state
+        .database
+        .get_last_dialed()
+        .map_err(|e| e.to_string())?
+        .ok_or("No previous outgoing call")?;
+    tracing::info!("Redialing last call to {}", last.peer_id);
+
+    if public_key_from_lan_peer_id(&last.peer_id).is_some() {
+        start_call(last.peer_id.clone(), state, app_handle).await?;
+        return Ok(last.peer_id);
+    }
+
+    if let Some(public_key) = last.public_key {
+        return call_by_public_key(public_key, state, app_handle).await;
+    }
+
+    ensure_signaling(&state, &app_handle).await?;
+
+    let username = last
+        .username
+        .ok_or("Last call partner can no longer be resolved")?;
+    let contact = resolve_username(&state, &username)
+        .await?
+        .ok_or_else(|| format!("{} is no longer registered", username))?;
+    if !contact.is_online {
+        return Err(format!("{} is offline", username));
+    }
+
+    start_call(contact.peer_id.clone(), state, app_handle).await?;
+    Ok(contact.peer_id)
+}
+
+/// Akzeptiert einen eingehenden Anruf
+#[tauri::command]
+async fn accept_call(
+    peer_id: String,
+    offer_sdp: String,
+    state: State<'_, Arc<AppState>>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    tracing::info!("Accepting call from {}", peer_id);
+
+    let call_engine = Arc::clone(&state.call_engine);
+    state.load_expected_peer_key(&peer_id);
+
+    let ringing_username = call_engine
+        .peer_states()
+        .into_iter()
+        .find_map(|peer| match peer {
+            CallState::Ringing {
+                peer_id: caller_id,
+                username,
+            } if caller_id == peer_id => Some(username),
+            _ => None,
+        });
+    if let Some(username) = ringing_username {
+        state.auto_add.remember(peer_id.clone(), username);
+    }
+
+    if public_key_from_lan_peer_id(&peer_id).is_some() {
+        let lan = state.lan()?;
+        let answer_sdp = call_engine
+            .accept_lan_call(peer_id.clone(), offer_sdp)
+            .await
+            .map_err(|e| e.to_string())?;
+        return lan
+            .send_answer(&peer_id, answer_sdp)
+            .await
+            .map_err(|e| e.to_string());
+    }
+
+    ensure_signaling(&state, &app_handle).await?;
+
+    // SDP Answer erstellen
+    let answer_sdp = call_engine
+        .accept_call(peer_id.clone(), offer_sdp)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // Answer senden
+    {
+        let signaling = state.signaling.read();
+        if let Some(client) = signaling.as_ref() {
+            let _ = client.send_answer_sync(peer_id, answer_sdp);
+        }
+    }
+
+    Ok(())
+}
+
+/// Lehnt einen eingehenden Anruf ab
+#[tauri::command]
+async fn reject_call(
+    peer_id: String,
+    reason: Option<String>,
+    state: State<'_, Arc<AppState>>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    tracing::info!("Rejecting call from {}", peer_id);
+
+    state.call_engine.reject_call(&peer_id);
+
+    // Abgelehnte Anrufe erscheinen nicht als neue verpasste Anrufe
+    if let Err(e) = state.database.mark_unanswered_call_seen(&peer_id) {
+        tracing::warn!("Failed to update call history: {}", e);
+    }
+
+    if public_key_from_lan_peer_id(&peer_id).is_some() {
+        let lan = state.lan()?;
+        return lan
+            .reject_call(&peer_id, reason)
+            .await
+            .map_err(|e| e.to_string());
+    }
+
+    ensure_signaling(&state, &app_handle).await?;
+    {
+        let signaling = state.signaling.read();
+        if let Some(client) = signaling.as_ref() {
+            let _ = client.reject_call_sync(peer_id, reason);
+        }
+    }
+
+    Ok(())
+}
+
+/// Beendet das Klingeln eines eingehenden Anrufs, ohne den Anrufer zu benachrichtigen
+///
+/// Mit `record_missed` bleibt der Anruf als verpasst im Verlauf, sonst wird
+/// der Eintrag entfernt.
+#[tauri::command]
+async fn dismiss_incoming_call(
+    peer_id: String,
+    record_missed: bool,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    tracing::info!("Dismissing call from {}", peer_id);
+
+    state
+        .call_engine
+        .dismiss_incoming_call(&peer_id)
+        .map_err(|e| e.to_string())?;
+
+    if !record_missed {
+        state
+            .database
+            .delete_unanswered_call(&peer_id)
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Beendet den aktuellen Anruf mit allen Teilnehmern
+#[tauri::command]
+async fn hangup(state: State<'_, Arc<AppState>>, app_handle: AppHandle) -> Result<(), String> {
+    tracing::info!("Hanging up");
+
+    let mut peer_ids: Vec<String> = state
+        .call_engine
+        .peer_states()
+        .iter()
+        .filter_map(|peer| peer.peer_id().map(str::to_string))
+        .collect();
+    if peer_ids.is_empty() {
+        let call_state = state.call_engine.state();
+        let peer_id = call_state.peer_id().ok_or("No active call")?;
+        peer_ids.push(peer_id.to_string());
+    }
+
+    state.call_engine.end_call();
+
+    let mut result = Ok(());
+    for peer_id in peer_ids {
+        if let Err(e) = notify_hangup(&state, &app_handle, peer_id).await {
+            result = Err(e);
+        }
+    }
+    result
+}
+
+/// Setzt die Call Engine unabhängig vom State auf Idle zurück
+///
+/// Notausgang, falls ein Anruf hängen bleibt. Die Gegenstellen werden nach
+/// Möglichkeit benachrichtigt, Fehler dabei werden nur geloggt.
+#[tauri::command]
+async fn force_reset_call(
+    state: State<'_, Arc<AppState>>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    let peer_ids: Vec<String> = state
+        .call_engine
+        .peer_states()
+        .iter()
+        .filter_map(|peer| peer.peer_id().map(str::to_string))
+        .collect();
+
+    state.call_engine.force_reset();
+
+    for peer_id in peer_ids {
+        if let Err(e) = notify_hangup(&state, &app_handle, peer_id).await {
+            tracing::warn!("Failed to notify peer after reset: {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// Legt bei einem einzelnen Teilnehmer auf, der Anruf mit den anderen läuft weiter
+#[tauri::command]
+async fn hangup_peer(
+    peer_id: String,
+    state: State<'_, Arc<AppState>>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    tracing::info!("Hanging up on {}", peer_id);
+
+    state.call_engine.end_peer_call(&peer_id);
+    notify_hangup(&state, &app_handle, peer_id).await
+}
+
+/// Teilt einem Peer mit, dass aufgelegt wurde (LAN oder Signaling-Server)
+async fn notify_hangup(
+    state: &AppState,
+    app_handle: &AppHandle,
+    peer_id: String,
+) -> Result<(), String> {
+    if public_key_from_lan_peer_id(&peer_id).is_some() {
+        let lan = state.lan()?;
+        return lan.hangup(&peer_id).await.map_err(|e| e.to_string());
+    }
+
+    ensure_signaling(state, app_handle).await?;
+
+    {
+        let signaling = state.signaling.read();
+        if let Some(client) = signaling.as_ref() {
+            let _ = client.hangup_sync(peer_id);
+        }
+    }
+
+    Ok(())
+}
+
+/// Holt einen weiteren Peer in den verbundenen Anruf (Konferenz)
+#[tauri::command]
+async fn add_call_peer(
+    peer_id: String,
+    state: State<'_, Arc<AppState>>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    tracing::info!("Adding {} to the call", peer_id);
+
+    let call_engine = Arc::clone(&state.call_engine);
+    state.load_expected_peer_key(&peer_id);
+
+    if public_key_from_lan_peer_id(&peer_id).is_some() {
+        let lan = state.lan()?;
+        let offer_sdp = call_engine
+            .add_lan_peer(peer_id.clone())
+            .await
+            .map_err(|e| e.to_string())?;
+        return lan
+            .send_offer(&peer_id, offer_sdp)
+            .await
+            .map_err(|e| e.to_string());
+    }
+
+    ensure_signaling(&state, &app_handle).await?;
+    let offer_sdp = call_engine
+        .add_peer(peer_id.clone())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let sent = match state.signaling.read().as_ref() {
+        Some(client) => {
+            let _ = client.send_offer_sync(peer_id.clone(), offer_sdp);
+            true
+        }
+        None => false,
+    };
+    if !sent {
+        call_engine.end_peer_call(&peer_id);
+        return Err("Not connected".to_string());
+    }
+
+    Ok(())
+}
+
+/// Gibt die States aller Teilnehmer des aktuellen Anrufs zurück
+#[tauri::command]
+async fn get_call_peers(state: State<'_, Arc<AppState>>) -> Result<Vec<CallStateInfo>, String> {
+    Ok(state
+        .call_engine
+        .peer_states()
+        .iter()
+        .map(|peer| with_contact_name(&state, CallStateInfo::from(peer)))
+        .collect())
+}
+
+/// Gibt den aktuellen Call-Status zurück
+#[tauri::command]
+async fn get_call_state(state: State<'_, Arc<AppState>>) -> Result<String, String> {
+    let call_state = state.call_engine.state();
+    let state_str = match call_state {
+        CallState::Idle => "idle",
+        CallState::Calling { .. } => "calling",
+        CallState::Ringing { .. } => "ringing",
+        CallState::Connecting { .. } => "connecting",
+        CallState::Connected { .. } => "connected",
+        CallState::Reconnecting { .. } => "reconnecting",
         CallState::Ended => "ended",
     };
     Ok(state_str.to_string())
 }
 
-/// Setzt Mute-Status
+/// Gibt den Call-State samt Gegenstelle zurück
+///
+/// Fehlt der Username (ausgehende oder verbundene Anrufe), wird er aus den
+/// Kontakten ergänzt.
+#[tauri::command]
+async fn get_call_state_detailed(state: State<'_, Arc<AppState>>) -> Result<CallStateInfo, String> {
+    let info = CallStateInfo::from(&state.call_engine.state());
+    Ok(with_contact_name(&state, info))
+}
+
+/// Ergänzt den Namen der Gegenstelle aus den Kontakten, falls er fehlt
+fn with_contact_name(state: &AppState, mut info: CallStateInfo) -> CallStateInfo {
+    if info.username.is_none() {
+        info.username = info
+            .peer_id
+            .as_deref()
+            .and_then(|peer_id| state.database.get_contact_by_peer_id(peer_id).ok())
+            .map(|contact| contact.display_name.unwrap_or(contact.username));
+    }
+    info
+}
+
+/// Gibt die DTLS Fingerprints (lokal/remote) des aktiven Anrufs zurück
+///
+/// Ergänzt die Ed25519-Identität: Vergleichen beide Seiten die Fingerprints
+/// out-of-band, ist ein MITM auf der Medienebene ausgeschlossen.
+#[tauri::command]
+async fn get_dtls_fingerprints(
+    state: State<'_, Arc<AppState>>,
+) -> Result<DtlsFingerprints, String> {
+    state
+        .call_engine
+        .dtls_fingerprints()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Gibt den Verschlüsselungsstatus des aktiven Anrufs zurück
+///
+/// Grundlage für die Verschlüsselungs-Anzeige in der UI: DTLS-SRTP Status,
+/// ausgehandeltes SRTP-Profil und die verifizierte Identität der Gegenstelle.
+#[tauri::command]
+async fn get_security_info(state: State<'_, Arc<AppState>>) -> Result<SecurityInfo, String> {
+    state
+        .call_engine
+        .security_info()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Setzt Mute-Status
+#[tauri::command]
+async fn set_muted(muted: bool, state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    state.call_engine.set_muted(muted);
+    Ok(())
+}
+
+/// Gibt Mute-Status zurück
+#[tauri::command]
+async fn is_muted(state: State<'_, Arc<AppState>>) -> Result<bool, String> {
+    Ok(state.call_engine.is_muted())
+}
+
+/// Bereitet Audio-Geräte vor, damit der nächste Anruf schneller startet
+#[tauri::command]
+async fn warm_up_audio(state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    // Geräteabfragen blockieren je nach Treiber spürbar
+    let call_engine = Arc::clone(&state.call_engine);
+    tokio::task::spawn_blocking(move || call_engine.warm_up_audio())
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Wärmt eine Peer Connection vor, damit der nächste Anruf schneller verbindet
+///
+/// Sinnvoll z.B. beim Öffnen eines Kontakts, bevor der Nutzer anruft.
+#[tauri::command]
+async fn prewarm_call(state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    state.call_engine.prewarm().await.map_err(|e| e.to_string())
+}
+
+/// Setzt die ICE Transport Policy (`relay` erzwingt TURN)
+///
+/// Schlägt fehl, wenn für Relay-only kein TURN-Server konfiguriert ist.
+#[tauri::command]
+async fn set_ice_transport_policy(
+    policy: IceTransportPolicy,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    state
+        .call_engine
+        .set_ice_transport_policy(policy)
+        .map_err(|e| e.to_string())
+}
+
+/// Gibt die aktuelle ICE Transport Policy zurück
+#[tauri::command]
+async fn get_ice_transport_policy(
+    state: State<'_, Arc<AppState>>,
+) -> Result<IceTransportPolicy, String> {
+    Ok(state.call_engine.ice_transport_policy())
+}
+
+/// Fügt einen TURN-Server hinzu und speichert ihn für künftige Starts
+///
+/// Gilt für alle danach aufgebauten Verbindungen.
+#[tauri::command]
+async fn add_turn_server(
+    url: String,
+    username: String,
+    credential: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    let server = TurnServer {
+        url,
+        username,
+        credential,
+    };
+    state
+        .call_engine
+        .add_turn_server(
+            server.url.clone(),
+            server.username.clone(),
+            server.credential.clone(),
+        )
+        .map_err(|e| e.to_string())?;
+    state
+        .database
+        .add_turn_server(&server)
+        .map_err(|e| e.to_string())
+}
+
+/// Entfernt alle TURN-Server (Relay-only fällt auf `all` zurück)
+#[tauri::command]
+async fn clear_turn_servers(state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    state.call_engine.clear_turn_servers();
+    state
+        .database
+        .clear_turn_servers()
+        .map_err(|e| e.to_string())
+}
+
+/// Begrenzt die Sende-Bitrate in Bit pro Sekunde (`None` hebt die Grenze auf)
+///
+/// Wirkt sofort auch im laufenden Anruf, Minimum sind 16 kbps.
+#[tauri::command]
+async fn set_max_bitrate(
+    max_bps: Option<u32>,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    state
+        .call_engine
+        .set_max_bitrate(max_bps)
+        .map_err(|e| e.to_string())
+}
+
+/// Gibt die Obergrenze der Sende-Bitrate zurück
+#[tauri::command]
+async fn get_max_bitrate(state: State<'_, Arc<AppState>>) -> Result<Option<u32>, String> {
+    Ok(state.call_engine.max_bitrate())
+}
+
+/// Baut einen Anruf einmal neu auf, wenn ICE vor dem Verbinden scheitert
+///
+/// Standard: aus. Gilt für Anrufe über den Signaling-Server, die danach
+/// beginnen, nicht für LAN-Anrufe.
+#[tauri::command]
+async fn set_setup_retry_enabled(
+    enabled: bool,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    state.call_engine.set_setup_retry_enabled(enabled);
+    Ok(())
+}
+
+/// Gibt zurück, ob ein gescheiterter Verbindungsaufbau wiederholt wird
+#[tauri::command]
+async fn get_setup_retry_enabled(state: State<'_, Arc<AppState>>) -> Result<bool, String> {
+    Ok(state.call_engine.setup_retry_enabled())
+}
+
+/// Bricht einen laufenden Echo-Check ab
+#[tauri::command]
+async fn skip_echo_check(state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    state.call_engine.skip_echo_check();
+    Ok(())
+}
+
+/// Gibt die Dauer des verbundenen Anrufs in Sekunden zurück (`None` ohne Verbindung)
+#[tauri::command]
+async fn get_call_duration(state: State<'_, Arc<AppState>>) -> Result<Option<u64>, String> {
+    Ok(state
+        .call_engine
+        .call_duration()
+        .map(|duration| duration.as_secs()))
+}
+
+/// Gibt RTT, Paketverlust, Jitter und übertragene Bytes des Anrufs zurück
+#[tauri::command]
+async fn get_call_stats(state: State<'_, Arc<AppState>>) -> Result<ConnectionStats, String> {
+    state
+        .call_engine
+        .get_stats()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Gibt Audio-Levels zurück (input, output)
+#[tauri::command]
+async fn get_audio_levels(state: State<'_, Arc<AppState>>) -> Result<(f32, f32), String> {
+    Ok(state.call_engine.audio_levels())
+}
+
+/// Gibt zurück, ob gerade gesprochen wird (Sprachaktivitätserkennung)
+#[tauri::command]
+async fn is_voice_active(state: State<'_, Arc<AppState>>) -> Result<bool, String> {
+    Ok(state.call_engine.is_voice_active())
+}
+
+// ============================================================================
+// TAURI COMMANDS - DIAGNOSTICS
+// ============================================================================
+
+/// Gibt die letzten Signaling- und Call-Events zurück (älteste zuerst)
+#[tauri::command]
+async fn get_event_log(state: State<'_, Arc<AppState>>) -> Result<Vec<EventLogEntry>, String> {
+    Ok(state.event_log.entries())
+}
+
+/// Leert das Event-Log
+#[tauri::command]
+async fn clear_event_log(state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    state.event_log.clear();
+    Ok(())
+}
+
+/// Aktiviert oder deaktiviert die Maskierung von IP-Adressen im Event-Log
+///
+/// Wirkt nur auf neue Einträge.
+#[tauri::command]
+async fn set_event_log_redaction(
+    enabled: bool,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    state.event_log.set_redaction(enabled);
+    Ok(())
+}
+
+/// Schreibt einen Diagnosebericht als JSON-Datei und gibt deren Pfad zurück
+///
+/// IP-Adressen werden maskiert, außer der Nutzer setzt `include_addresses`.
+/// Der Private Key ist nie enthalten.
+#[tauri::command]
+async fn generate_diagnostics(
+    include_addresses: bool,
+    state: State<'_, Arc<AppState>>,
+) -> Result<String, String> {
+    let audio = tokio::task::spawn_blocking(audio_diagnostics)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let bundle = DiagnosticsBundle {
+        format_version: DIAGNOSTICS_FORMAT_VERSION,
+        generated_at: chrono::Utc::now().timestamp_millis(),
+        app_version: env!("CARGO_PKG_VERSION"),
+        protocol_version: PROTOCOL_VERSION,
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        addresses_redacted: !include_addresses,
+        connection: connection_diagnostics(&state),
+        call: state.call_engine.call_stats().await,
+        usage: state.database.get_usage_stats().ok(),
+        ice: IceDiagnostics {
+            servers: state.call_engine.ice_server_urls(),
+            transport_policy: state.call_engine.ice_transport_policy(),
+        },
+        audio,
+        event_log: state.event_log.entries(),
+    };
+
+    let path = bundle.write_to_file().map_err(|e| e.to_string())?;
+    Ok(path.to_string_lossy().into_owned())
+}
+
+/// Gibt die Erreichbarkeit der konfigurierten ICE Server über die Zeit zurück
+#[tauri::command]
+async fn get_ice_server_health(
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<IceServerHealth>, String> {
+    Ok(state
+        .ice_health
+        .report(&state.call_engine.ice_server_urls()))
+}
+
+/// Prüft vor einem Anruf, ob STUN/TURN Candidates liefern und wie sich das NAT verhält
+#[tauri::command]
+async fn test_connectivity(state: State<'_, Arc<AppState>>) -> Result<ConnectivityReport, String> {
+    state
+        .call_engine
+        .test_connectivity()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Gibt den Pfad der aktuellen Log-Datei zurück
+#[tauri::command]
+async fn get_log_path() -> Result<String, String> {
+    Ok(logging::log_path().to_string_lossy().into_owned())
+}
+
+/// Gibt die letzten `lines` Log-Zeilen zurück (älteste zuerst)
+///
+/// IP-Adressen werden maskiert, solange die Maskierung des Event-Logs aktiv ist.
+#[tauri::command]
+async fn read_recent_logs(
+    lines: usize,
+    state: State<'_, Arc<AppState>>,
+) -> Result<Vec<String>, String> {
+    let lines = tokio::task::spawn_blocking(move || logging::read_recent_logs(lines))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+
+    if !state.event_log.redaction() {
+        return Ok(lines);
+    }
+    Ok(lines.iter().map(|line| redact_ip_addresses(line)).collect())
+}
+
+// ============================================================================
+// TAURI COMMANDS - TESTING
+// ============================================================================
+
+/// Simuliert Paketverlust, Latenz und Jitter auf dem ausgehenden RTP-Pfad
+///
+/// Nur in Debug-Builds verfügbar.
+#[tauri::command]
+async fn set_network_simulation(
+    loss_pct: f32,
+    added_latency_ms: u32,
+    jitter_ms: u32,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    if !cfg!(debug_assertions) {
+        return Err("Network simulation is only available in debug builds".to_string());
+    }
+
+    state
+        .call_engine
+        .set_network_simulation(loss_pct, added_latency_ms, jitter_ms)
+        .map_err(|e| e.to_string())
+}
+
+/// Gibt die aktuelle Netzwerk-Simulation zurück
+#[tauri::command]
+async fn get_network_simulation(
+    state: State<'_, Arc<AppState>>,
+) -> Result<NetworkSimulation, String> {
+    Ok(state.call_engine.network_simulation())
+}
+
+/// Aktiviert ausführliches ICE-Logging mit maskierten Adressen
+#[tauri::command]
+async fn set_verbose_ice_logging(
+    enabled: bool,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    state.call_engine.set_verbose_ice_logging(enabled);
+    tracing::info!("Verbose ICE logging: {}", enabled);
+    Ok(())
+}
+
+/// Verzögert das gesendete Audio um eine feste Zeit in Millisekunden (0 = aus)
+#[tauri::command]
+async fn set_output_delay(delay_ms: u32, state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    state
+        .call_engine
+        .set_output_delay(delay_ms)
+        .map_err(|e| e.to_string())
+}
+
+/// Gibt die Verzögerung des gesendeten Audios in Millisekunden zurück
+#[tauri::command]
+async fn get_output_delay(state: State<'_, Arc<AppState>>) -> Result<u32, String> {
+    Ok(state.call_engine.output_delay())
+}
+
+// ============================================================================
+// TAURI COMMANDS - MANUAL SIGNALING
+// ============================================================================
+
+/// Erstellt ein SDP Offer, ohne es über den Signaling-Server zu senden
+///
+/// Für manuelles Signaling (Copy & Paste) und externes Tooling.
+#[tauri::command]
+async fn create_manual_offer(
+    peer_id: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<String, String> {
+    state.load_expected_peer_key(&peer_id);
+    state
+        .call_engine
+        .start_call(peer_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Akzeptiert ein manuell übergebenes SDP Offer und gibt das Answer zurück
+#[tauri::command]
+async fn accept_manual_offer(
+    peer_id: String,
+    offer_sdp: String,
+    state: State<'_, Arc<AppState>>,
+) -> Result<String, String> {
+    state.load_expected_peer_key(&peer_id);
+    state
+        .call_engine
+        .accept_offer(peer_id, offer_sdp)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Wendet ein manuell übergebenes SDP Answer an
+///
+/// Ohne `peer_id` gilt es für die Gegenstelle des aktuellen Anrufs.
+#[tauri::command]
+async fn apply_remote_answer(
+    answer_sdp: String,
+    peer_id: Option<String>,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    let peer_id = manual_peer_id(&state, peer_id)?;
+    state
+        .call_engine
+        .handle_answer(&peer_id, answer_sdp)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Fügt einen ICE Candidate (JSON im `RTCIceCandidateInit`-Format) hinzu
+///
+/// Ohne `peer_id` gilt er für die Gegenstelle des aktuellen Anrufs.
+#[tauri::command]
+async fn add_ice_candidate(
+    candidate: String,
+    peer_id: Option<String>,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    let peer_id = manual_peer_id(&state, peer_id)?;
+    state
+        .call_engine
+        .add_ice_candidate(&peer_id, candidate)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Bestimmt den Peer für manuelles Signaling (Standard: Gegenstelle des Anrufs)
+fn manual_peer_id(state: &AppState, peer_id: Option<String>) -> Result<String, String> {
+    peer_id
+        .or_else(|| state.call_engine.state().peer_id().map(str::to_string))
+        .ok_or_else(|| "No active call".to_string())
+}
+
+/// Gibt die lokale Session Description (Offer/Answer) zurück
+#[tauri::command]
+async fn get_local_description(
+    state: State<'_, Arc<AppState>>,
+) -> Result<Option<LocalDescription>, String> {
+    Ok(state.call_engine.local_description().await)
+}
+
+/// Gibt alle lokal gesammelten ICE Candidates zurück
+#[tauri::command]
+async fn get_local_candidates(state: State<'_, Arc<AppState>>) -> Result<Vec<String>, String> {
+    Ok(state.call_engine.local_candidates())
+}
+
+// ============================================================================
+// TAURI COMMANDS - LAN DISCOVERY
+// ============================================================================
+
+/// Startet Advertising und Discovery im lokalen Netzwerk
+///
+/// Gefundene Peers erhalten Peer-IDs der Form `lan:<public_key>` und können
+/// über die normalen Call-Commands angerufen werden.
+#[tauri::command]
+async fn start_lan_discovery(
+    username: String,
+    state: State<'_, Arc<AppState>>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    if state.lan_discovery.read().is_some() {
+        return Ok(());
+    }
+
+    let discovery = LanDiscovery::start(username, state.keypair()?)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // Event Handler starten
+    let mut event_rx = discovery.subscribe();
+    let call_engine = Arc::clone(&state.call_engine);
+    let database = Arc::clone(&state.database);
+    let lan_discovery = Arc::clone(&state.lan_discovery);
+    tokio::spawn(async move {
+        while let Some(event) = recv_event(&mut event_rx, "LAN discovery").await {
+            handle_lan_event(event, &app_handle, &call_engine, &database, &lan_discovery).await;
+        }
+    });
+
+    *state.lan_discovery.write() = Some(Arc::new(discovery));
+    Ok(())
+}
+
+/// Stoppt die LAN Discovery
+#[tauri::command]
+async fn stop_lan_discovery(state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    if let Some(discovery) = state.lan_discovery.write().take() {
+        discovery.stop();
+    }
+    Ok(())
+}
+
+/// Gibt alle im LAN gefundenen Peers zurück
+#[tauri::command]
+async fn get_lan_peers(state: State<'_, Arc<AppState>>) -> Result<Vec<LanPeer>, String> {
+    Ok(state
+        .lan_discovery
+        .read()
+        .as_ref()
+        .map(|d| d.peers())
+        .unwrap_or_default())
+}
+
+// ============================================================================
+// TAURI COMMANDS - AUDIO SETTINGS
+// ============================================================================
+
+/// Repräsentiert ein Audio-Gerät
+#[derive(serde::Serialize)]
+struct AudioDevice {
+    name: String,
+    is_default: bool,
+}
+
+/// Gibt die verfügbaren Audio-Codecs zurück (Name, Clock Rate, Kanäle, Payload Type)
+#[tauri::command]
+async fn get_supported_codecs(state: State<'_, Arc<AppState>>) -> Result<Vec<CodecInfo>, String> {
+    state
+        .call_engine
+        .supported_codecs()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Setzt die Vorpufferung des Playbacks in Frames (à 20ms)
+#[tauri::command]
+async fn set_playback_prefill_frames(
+    frames: usize,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    state
+        .call_engine
+        .set_playback_prefill_frames(frames)
+        .map_err(|e| e.to_string())
+}
+
+/// Gibt die Vorpufferung des Playbacks in Frames zurück
+#[tauri::command]
+async fn get_playback_prefill_frames(state: State<'_, Arc<AppState>>) -> Result<usize, String> {
+    Ok(state.call_engine.playback_prefill_frames())
+}
+
+/// Setzt die Größe der Audio-Frames in Samples (120, 240, 480, 960, 1920 oder 2880)
+#[tauri::command]
+async fn set_audio_frame_size(
+    frame_size: usize,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    state
+        .call_engine
+        .set_audio_frame_size(frame_size)
+        .map_err(|e| e.to_string())
+}
+
+/// Gibt die Größe der Audio-Frames in Samples zurück
+#[tauri::command]
+async fn get_audio_frame_size(state: State<'_, Arc<AppState>>) -> Result<usize, String> {
+    Ok(state.call_engine.audio_frame_size())
+}
+
+/// Setzt die Ziel-Bitrate des Opus-Encoders in bps (6000 bis 510000)
+#[tauri::command]
+async fn set_codec_bitrate(bps: u32, state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    state
+        .call_engine
+        .set_codec_bitrate(bps)
+        .map_err(|e| e.to_string())
+}
+
+/// Gibt die Ziel-Bitrate des Opus-Encoders in bps zurück
+#[tauri::command]
+async fn get_codec_bitrate(state: State<'_, Arc<AppState>>) -> Result<u32, String> {
+    Ok(state.call_engine.codec_bitrate())
+}
+
+/// Aktiviert den kurzen Echo-Check nach dem Verbindungsaufbau
+#[tauri::command]
+async fn set_echo_check_enabled(
+    enabled: bool,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    state.call_engine.set_echo_check_enabled(enabled);
+    Ok(())
+}
+
+/// Gibt zurück, ob der Echo-Check aktiv ist
+#[tauri::command]
+async fn get_echo_check_enabled(state: State<'_, Arc<AppState>>) -> Result<bool, String> {
+    Ok(state.call_engine.echo_check_enabled())
+}
+
+/// Aktiviert den Limiter des Playbacks mit einer Obergrenze in dBFS (-12 bis 0)
+#[tauri::command]
+async fn set_output_limiter(
+    enabled: bool,
+    ceiling_db: f32,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    state
+        .call_engine
+        .set_output_limiter(enabled, ceiling_db)
+        .map_err(|e| e.to_string())
+}
+
+/// Gibt die Einstellungen des Limiters zurück
+#[tauri::command]
+async fn get_output_limiter(
+    state: State<'_, Arc<AppState>>,
+) -> Result<OutputLimiterConfig, String> {
+    Ok(state.call_engine.output_limiter())
+}
+
+/// Aktiviert die Sprachaktivitätserkennung (Stille wird nicht gesendet)
+#[tauri::command]
+async fn set_vad_enabled(enabled: bool, state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    state.call_engine.set_vad_enabled(enabled);
+    Ok(())
+}
+
+/// Gibt zurück, ob die Sprachaktivitätserkennung aktiv ist
+#[tauri::command]
+async fn get_vad_enabled(state: State<'_, Arc<AppState>>) -> Result<bool, String> {
+    Ok(state.call_engine.vad_enabled())
+}
+
+/// Schaltet zwischen Push-to-Talk und offenem Mikrofon um
+#[tauri::command]
+async fn set_push_to_talk(enabled: bool, state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    state.call_engine.set_ptt_enabled(enabled);
+    Ok(())
+}
+
+/// Gibt zurück, ob Push-to-Talk aktiv ist
+#[tauri::command]
+async fn get_push_to_talk(state: State<'_, Arc<AppState>>) -> Result<bool, String> {
+    Ok(state.call_engine.ptt_enabled())
+}
+
+/// Push-to-Talk-Taste gedrückt: Mikrofon wird gesendet
+#[tauri::command]
+async fn ptt_key_down(state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    state.call_engine.set_ptt_active(true);
+    Ok(())
+}
+
+/// Push-to-Talk-Taste losgelassen: Senden pausiert
+#[tauri::command]
+async fn ptt_key_up(state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    state.call_engine.set_ptt_active(false);
+    Ok(())
+}
+
+/// Setzt die Schwelle der Sprachaktivitätserkennung (RMS, 0.0 - 1.0)
+#[tauri::command]
+async fn set_vad_threshold(threshold: f32, state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    state
+        .call_engine
+        .set_vad_threshold(threshold)
+        .map_err(|e| e.to_string())
+}
+
+/// Gibt die Schwelle der Sprachaktivitätserkennung zurück
+#[tauri::command]
+async fn get_vad_threshold(state: State<'_, Arc<AppState>>) -> Result<f32, String> {
+    Ok(state.call_engine.vad_threshold())
+}
+
+/// Aktiviert oder deaktiviert den Klingelton für eingehende Anrufe
+#[tauri::command]
+async fn set_ringtone_enabled(
+    enabled: bool,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    state.call_engine.set_ringtone_enabled(enabled);
+    Ok(())
+}
+
+/// Gibt zurück, ob bei eingehenden Anrufen geklingelt wird
+#[tauri::command]
+async fn get_ringtone_enabled(state: State<'_, Arc<AppState>>) -> Result<bool, String> {
+    Ok(state.call_engine.ringtone_enabled())
+}
+
+/// Wählt den Klingelton
+#[tauri::command]
+async fn set_ringtone(style: RingtoneStyle, state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    state.call_engine.set_ringtone_style(style);
+    Ok(())
+}
+
+/// Gibt den gewählten Klingelton zurück
+#[tauri::command]
+async fn get_ringtone(state: State<'_, Arc<AppState>>) -> Result<RingtoneStyle, String> {
+    Ok(state.call_engine.ringtone_style())
+}
+
+/// Fragt die Mikrofon-Berechtigung beim Betriebssystem ab
+#[tauri::command]
+async fn check_microphone_permission() -> Result<MicrophonePermission, String> {
+    tokio::task::spawn_blocking(call_engine::check_microphone_permission)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Fordert die Mikrofon-Berechtigung an (öffnet unter macOS den System-Dialog)
+#[tauri::command]
+async fn request_microphone_permission() -> Result<MicrophonePermission, String> {
+    tokio::task::spawn_blocking(call_engine::request_microphone_permission)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Aktiviert oder deaktiviert die UI-Sounds
+#[tauri::command]
+async fn set_sound_effects_enabled(
+    enabled: bool,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    state.sound_effects.set_enabled(enabled);
+    Ok(())
+}
+
+/// Gibt zurück, ob UI-Sounds abgespielt werden
+#[tauri::command]
+async fn get_sound_effects_enabled(state: State<'_, Arc<AppState>>) -> Result<bool, String> {
+    Ok(state.sound_effects.is_enabled())
+}
+
+/// Legt fest, ob Hinweis-Sounds während eines verbundenen Anrufs still bleiben
+#[tauri::command]
+async fn set_quiet_during_call(quiet: bool, state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    state.sound_effects.set_quiet_during_call(quiet);
+    Ok(())
+}
+
+/// Gibt zurück, ob Hinweis-Sounds während eines Anrufs still bleiben
 #[tauri::command]
-async fn set_muted(muted: bool, state: State<'_, Arc<AppState>>) -> Result<(), String> {
-    state.call_engine.set_muted(muted);
-    Ok(())
+async fn get_quiet_during_call(state: State<'_, Arc<AppState>>) -> Result<bool, String> {
+    Ok(state.sound_effects.is_quiet_during_call())
 }
 
-/// Gibt Mute-Status zurück
+/// Ersetzt einen UI-Sound durch eine WAV-Datei (ohne Pfad: Standard-Sound)
 #[tauri::command]
-async fn is_muted(state: State<'_, Arc<AppState>>) -> Result<bool, String> {
-    Ok(state.call_engine.is_muted())
+async fn set_sound_effect(
+    effect: SoundEffect,
+    path: Option<String>,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    state
+        .sound_effects
+        .set_override(effect, path.as_deref().map(std::path::Path::new))
+        .map_err(|e| e.to_string())
 }
 
-/// Gibt Audio-Levels zurück (input, output)
+/// Spielt einen UI-Sound zur Vorschau ab
 #[tauri::command]
-async fn get_audio_levels(state: State<'_, Arc<AppState>>) -> Result<(f32, f32), String> {
-    Ok(state.call_engine.audio_levels())
+async fn play_sound_effect(
+    effect: SoundEffect,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    state.sound_effects.play(effect);
+    Ok(())
 }
 
-// ============================================================================
-// TAURI COMMANDS - AUDIO SETTINGS
-// ============================================================================
+/// Spielt einen Sinuston auf dem Ausgabegerät, um die Lautsprecher zu prüfen
+///
+/// Verwendet dasselbe Gerät wie das Anruf-Playback. Während eines Anrufs wird
+/// kein Ton gestartet. Kehrt erst nach dem Abspielen zurück.
+#[tauri::command]
+async fn play_test_tone(
+    frequency: f32,
+    duration_ms: u64,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    if !matches!(
+        state.call_engine.state(),
+        CallState::Idle | CallState::Ended
+    ) {
+        return Err("Cannot play a test tone during a call".to_string());
+    }
 
-/// Repräsentiert ein Audio-Gerät
-#[derive(serde::Serialize)]
-struct AudioDevice {
-    name: String,
-    is_default: bool,
+    let samples = call_engine::test_tone(frequency, std::time::Duration::from_millis(duration_ms))
+        .map_err(|e| e.to_string())?;
+    tokio::task::spawn_blocking(move || AudioHandler::play_once(&samples))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
 }
 
 /// Gibt alle verfügbaren Audio-Geräte zurück
 #[tauri::command]
 async fn get_audio_devices() -> Result<(Vec<AudioDevice>, Vec<AudioDevice>), String> {
+    list_audio_devices()
+}
+
+/// Wählt das Mikrofon für folgende Anrufe (`None` = System-Standard)
+#[tauri::command]
+async fn set_input_device(
+    name: Option<String>,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    state.call_engine.set_input_device(name);
+    Ok(())
+}
+
+/// Wählt den Lautsprecher für folgende Anrufe (`None` = System-Standard)
+#[tauri::command]
+async fn set_output_device(
+    name: Option<String>,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    state.call_engine.set_output_device(name);
+    Ok(())
+}
+
+/// Gibt die ausgewählten Audio-Geräte zurück
+#[tauri::command]
+async fn get_selected_audio_devices(
+    state: State<'_, Arc<AppState>>,
+) -> Result<AudioDeviceSelection, String> {
+    Ok(state.call_engine.audio_devices())
+}
+
+/// Listet Eingabe- und Ausgabegeräte des Audio-Hosts auf
+fn list_audio_devices() -> Result<(Vec<AudioDevice>, Vec<AudioDevice>), String> {
     use cpal::traits::{DeviceTrait, HostTrait};
 
     let host = cpal::default_host();
@@ -537,6 +3183,63 @@ async fn get_audio_devices() -> Result<(Vec<AudioDevice>, Vec<AudioDevice>), Str
     Ok((input_devices, output_devices))
 }
 
+/// Audio-Geräte und Mikrofon-Berechtigung für den Diagnosebericht
+///
+/// Blockiert (Geräte-Abfrage, unter Windows ein `reg`-Aufruf).
+fn audio_diagnostics() -> AudioDiagnostics {
+    let (inputs, outputs) = list_audio_devices().unwrap_or_else(|e| {
+        tracing::warn!("Failed to list audio devices for diagnostics: {}", e);
+        Default::default()
+    });
+    let default_name = |devices: &[AudioDevice]| {
+        devices
+            .iter()
+            .find(|device| device.is_default)
+            .map(|device| device.name.clone())
+    };
+
+    AudioDiagnostics {
+        default_input: default_name(&inputs),
+        default_output: default_name(&outputs),
+        input_devices: inputs.into_iter().map(|device| device.name).collect(),
+        output_devices: outputs.into_iter().map(|device| device.name).collect(),
+        microphone_permission: call_engine::check_microphone_permission(),
+    }
+}
+
+// ============================================================================
+// TAURI COMMANDS - INTEGRATIONS
+// ============================================================================
+
+/// Setzt einen Webhook, der bei den gewählten Events per HTTP POST aufgerufen wird
+///
+/// Ohne `allow_remote` sind nur Ziele auf diesem Rechner oder im lokalen Netz erlaubt.
+#[tauri::command]
+async fn set_webhook(
+    url: String,
+    events: Vec<WebhookEvent>,
+    allow_remote: Option<bool>,
+    state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
+    state
+        .webhooks
+        .set(&url, events, allow_remote.unwrap_or(false))
+        .map_err(|e| e.to_string())
+}
+
+/// Entfernt den Webhook
+#[tauri::command]
+async fn clear_webhook(state: State<'_, Arc<AppState>>) -> Result<(), String> {
+    state.webhooks.clear();
+    Ok(())
+}
+
+/// Gibt den aktiven Webhook zurück
+#[tauri::command]
+async fn get_webhook(state: State<'_, Arc<AppState>>) -> Result<Option<WebhookConfig>, String> {
+    Ok(state.webhooks.config())
+}
+
 // ============================================================================
 // EVENT HANDLER
 // ============================================================================
@@ -547,6 +3250,9 @@ async fn handle_signaling_event(
     app_handle: &AppHandle,
     database: &Arc<ContactsDatabase>,
     call_engine: &Arc<CallEngine>,
+    presence: &PresenceTracker,
+    signaling: &RwLock<Option<SignalingClient>>,
+    presence_batch: &mut PresenceBatch,
 ) {
     match event {
         SignalingEvent::Connected => {
@@ -554,9 +3260,21 @@ async fn handle_signaling_event(
             let _ = app_handle.emit("signaling:connected", ());
         }
 
-        SignalingEvent::Disconnected => {
-            tracing::info!("Disconnected from signaling server");
-            let _ = app_handle.emit("signaling:disconnected", ());
+        SignalingEvent::Disconnected { code, reason } => {
+            tracing::info!(
+                "Disconnected from signaling server (code {:?}, reason {:?})",
+                code,
+                reason
+            );
+            let _ = app_handle.emit(
+                "signaling:disconnected",
+                serde_json::json!({
+                    "code": code,
+                    "reason": reason,
+                    "message": close_code_message(code),
+                    "should_reconnect": should_reconnect(code)
+                }),
+            );
         }
 
         SignalingEvent::Registered { peer_id, username } => {
@@ -572,11 +3290,23 @@ async fn handle_signaling_event(
 
         SignalingEvent::UserFound(contact) => {
             tracing::info!("User found: {:?}", contact);
+            note_directory_key(call_engine, &contact);
             // Update the online status in the database
             let _ = database.set_online_status(&contact.peer_id, contact.is_online);
+            if let Some(display_name) = &contact.display_name {
+                let _ = database.fill_display_name(&contact.peer_id, display_name);
+            }
             let _ = app_handle.emit("signaling:user_found", &contact);
         }
 
+        SignalingEvent::PublicKeyMismatch {
+            peer_id,
+            received_key,
+            ..
+        } => {
+            check_identity_key_change(database, app_handle, &peer_id, &received_key);
+        }
+
         SignalingEvent::UserNotFound { username } => {
             tracing::info!("User not found: {}", username);
             let _ = app_handle.emit("signaling:user_not_found", username);
@@ -585,28 +3315,158 @@ async fn handle_signaling_event(
         SignalingEvent::IncomingCall {
             from_peer_id,
             from_username,
+            from_display_name,
             sdp,
+            public_key,
+            sdp_signature,
+            sender_verified,
         } => {
             tracing::info!("Incoming call from {} ({})", from_username, from_peer_id);
 
-            // Call Engine über eingehenden Anruf informieren
-            call_engine.register_incoming_call(from_peer_id.clone(), from_username.clone());
+            // Blockierte Peers werden still abgewiesen, ohne zu klingeln
+            let blocked = database.reject_if_blocked(&from_peer_id, || {
+                if let Some(client) = signaling.read().as_ref() {
+                    let _ =
+                        client.reject_call_sync(from_peer_id.clone(), Some("blocked".to_string()));
+                }
+            });
+            if blocked {
+                return;
+            }
 
-            let _ = app_handle.emit(
-                "call:incoming",
-                serde_json::json!({
-                    "fromPeerId": from_peer_id,
-                    "fromUsername": from_username,
-                    "sdp": sdp
-                }),
-            );
+            let own_peer_id = signaling.read().as_ref().and_then(|c| c.peer_id());
+
+            // Das Offer muss vom Inhaber des Keys stammen, ein gepinnter Key
+            // muss außerdem übereinstimmen. Ohne gespeicherten Key wird der Key
+            // des Offers beim ersten Kontakt vertraut und nach dem DTLS-Abgleich
+            // gepinnt (Trust on First Use).
+            if !sender_verified {
+                tracing::info!(
+                    "No stored key for {}, trusting offer key on first use",
+                    from_peer_id
+                );
+            }
+            let pinned_key = known_peer_key(database, &from_peer_id);
+            let verified = verify_offer_proof(
+                own_peer_id.as_deref().unwrap_or_default(),
+                &from_peer_id,
+                &sdp,
+                public_key.as_deref(),
+                sdp_signature.as_deref(),
+            )
+            .and_then(|offer_key| match &pinned_key {
+                Some(pinned) if *pinned != offer_key => {
+                    check_identity_key_change(database, app_handle, &from_peer_id, &offer_key);
+                    Err(SignalingError::UnverifiedOffer(
+                        "public key does not match pinned key".to_string(),
+                    ))
+                }
+                _ => Ok(offer_key),
+            });
+            let offer_key = match verified {
+                Ok(offer_key) => offer_key,
+                Err(e) => {
+                    tracing::warn!("Rejecting call from {}: {}", from_peer_id, e);
+                    if let Some(client) = signaling.read().as_ref() {
+                        let _ = client.reject_call_sync(
+                            from_peer_id.clone(),
+                            Some("unverified_offer".to_string()),
+                        );
+                    }
+                    let _ = app_handle.emit(
+                        "call:offer_rejected",
+                        serde_json::json!({
+                            "peerId": from_peer_id,
+                            "username": from_username,
+                            "reason": e.to_string()
+                        }),
+                    );
+                    return;
+                }
+            };
+
+            // Bei Glare wird das Offer direkt angenommen, der Key muss vorher
+            // bekannt sein. Der DTLS Fingerprint muss zum Key des Offers passen.
+            call_engine.expect_peer_key(from_peer_id.clone(), offer_key);
+
+            // Call Engine über eingehenden Anruf informieren (inkl. Glare-Auflösung)
+            let resolution = call_engine
+                .handle_incoming_offer(
+                    own_peer_id.as_deref(),
+                    from_peer_id.clone(),
+                    from_username.clone(),
+                    sdp.clone(),
+                )
+                .await;
+
+            match resolution {
+                Ok(IncomingCallResolution::Ring) => {
+                    let _ = app_handle.emit(
+                        "call:incoming",
+                        serde_json::json!({
+                            "fromPeerId": from_peer_id,
+                            "fromUsername": from_username,
+                            "fromDisplayName": from_display_name,
+                            "sdp": sdp,
+                            "senderVerified": sender_verified
+                        }),
+                    );
+                }
+                Ok(IncomingCallResolution::KeepOwnOffer) => {
+                    let _ = app_handle.emit(
+                        "call:glare_resolved",
+                        serde_json::json!({
+                            "peerId": from_peer_id,
+                            "role": "caller"
+                        }),
+                    );
+                }
+                Ok(IncomingCallResolution::AcceptedRemoteOffer { answer_sdp }) => {
+                    if let Some(client) = signaling.read().as_ref() {
+                        // Das eigene, verworfene Offer wartet nicht mehr auf eine Antwort
+                        for request in client.pending_requests() {
+                            if request.kind == PendingRequestKind::Offer
+                                && request.target == from_peer_id
+                            {
+                                client.cancel_request(&request.request_id);
+                            }
+                        }
+                        let _ = client.send_answer_sync(from_peer_id.clone(), answer_sdp);
+                    }
+                    let _ = app_handle.emit(
+                        "call:glare_resolved",
+                        serde_json::json!({
+                            "peerId": from_peer_id,
+                            "role": "callee"
+                        }),
+                    );
+                }
+                Ok(IncomingCallResolution::AcceptedRetry { answer_sdp })
+                | Ok(IncomingCallResolution::AcceptedIceRestart { answer_sdp }) => {
+                    if let Some(client) = signaling.read().as_ref() {
+                        let _ = client.send_answer_sync(from_peer_id.clone(), answer_sdp);
+                    }
+                }
+                Err(e) => {
+                    tracing::error!("Failed to handle offer from {}: {}", from_peer_id, e);
+                    let _ = app_handle.emit("call:error", e.to_string());
+                }
+            }
         }
 
-        SignalingEvent::AnswerReceived { from_peer_id, sdp } => {
+        SignalingEvent::AnswerReceived {
+            from_peer_id,
+            sdp,
+            sender_verified,
+        } => {
             tracing::info!("Answer received from {}", from_peer_id);
+            if !sender_verified {
+                // Die Identität prüft erst der DTLS-Abgleich mit dem Verzeichnis-Key
+                tracing::info!("No stored key for {}, answer is unverified", from_peer_id);
+            }
 
             // SDP Answer verarbeiten
-            if let Err(e) = call_engine.handle_answer(sdp).await {
+            if let Err(e) = call_engine.handle_answer(&from_peer_id, sdp).await {
                 tracing::error!("Failed to handle answer: {}", e);
             }
 
@@ -616,18 +3476,22 @@ async fn handle_signaling_event(
         SignalingEvent::IceCandidateReceived {
             from_peer_id,
             candidate,
+            ..
         } => {
             tracing::debug!("ICE candidate from {}", from_peer_id);
 
             // ICE Candidate hinzufügen
-            if let Err(e) = call_engine.add_ice_candidate(candidate).await {
+            if let Err(e) = call_engine
+                .add_ice_candidate(&from_peer_id, candidate)
+                .await
+            {
                 tracing::error!("Failed to add ICE candidate: {}", e);
             }
         }
 
         SignalingEvent::CallRejected { by_peer_id, reason } => {
             tracing::info!("Call rejected by {} (reason: {:?})", by_peer_id, reason);
-            call_engine.end_call();
+            call_engine.end_peer_call(&by_peer_id);
             let _ = app_handle.emit(
                 "call:rejected",
                 serde_json::json!({
@@ -639,20 +3503,134 @@ async fn handle_signaling_event(
 
         SignalingEvent::CallEnded { by_peer_id } => {
             tracing::info!("Call ended by {}", by_peer_id);
-            call_engine.end_call();
+            call_engine.end_peer_call(&by_peer_id);
             let _ = app_handle.emit("call:ended", by_peer_id);
         }
 
+        SignalingEvent::CallRequested { to_peer_id } => {
+            tracing::info!("Callback request queued for {}", to_peer_id);
+            let _ = app_handle.emit("callback:queued", &to_peer_id);
+        }
+
+        SignalingEvent::CallbackRequestReceived {
+            from_peer_id,
+            from_username,
+            requested_at,
+        } => {
+            tracing::info!("Callback requested by {}", from_username);
+            let request = CallbackRequest {
+                peer_id: from_peer_id,
+                username: from_username,
+                requested_at,
+            };
+            if let Err(e) = database.add_callback_request(&request) {
+                tracing::warn!("Failed to store callback request: {}", e);
+            }
+            if let Some(state) = AppState::get() {
+                play_event_sound(&state.sound_effects, call_engine, SoundEffect::Message);
+            }
+            let _ = app_handle.emit("callback:received", &request);
+        }
+
+        SignalingEvent::ChatMessage {
+            from_peer_id,
+            body,
+            sender_verified,
+        } => {
+            if is_blocked_peer(database, &from_peer_id) {
+                tracing::debug!("Dropping chat message from blocked peer {}", from_peer_id);
+                return;
+            }
+            let sent_at = server_time_ms(signaling);
+            let message = match database.add_chat_message(&from_peer_id, false, &body, sent_at) {
+                Ok(message) => message,
+                Err(e) => {
+                    tracing::warn!("Failed to store chat message: {}", e);
+                    ChatMessage {
+                        id: 0,
+                        peer_id: from_peer_id,
+                        outgoing: false,
+                        body,
+                        sent_at,
+                    }
+                }
+            };
+            if let Some(state) = AppState::get() {
+                play_event_sound(&state.sound_effects, call_engine, SoundEffect::Message);
+            }
+            // Ohne gespeicherten Key ist der Absender unbestätigt, die UI
+            // kennzeichnet solche Nachrichten
+            let mut payload = serde_json::to_value(&message).unwrap_or_default();
+            if let Some(obj) = payload.as_object_mut() {
+                obj.insert("sender_verified".to_string(), sender_verified.into());
+            }
+            let _ = app_handle.emit("chat:message", payload);
+        }
+
         SignalingEvent::ContactOnline { peer_id } => {
+            // Bei bekanntem Key nicht dem Server vertrauen, sondern auf einen
+            // verifizierten Presence Beacon warten
+            let now = server_time_ms(signaling);
+            if known_peer_key(database, &peer_id).is_some()
+                && !presence.is_verified_online(&peer_id, now)
+            {
+                tracing::info!(
+                    "Contact {} reported online, awaiting presence beacon",
+                    peer_id
+                );
+                return;
+            }
+
             tracing::info!("Contact online: {}", peer_id);
             let _ = database.set_online_status(&peer_id, true);
-            let _ = app_handle.emit("contact:online", &peer_id);
+            notify_presence_webhook(database, &peer_id, true);
+            push_presence(presence_batch, app_handle, &peer_id, true);
+        }
+
+        SignalingEvent::PresenceBeacon(beacon) => {
+            let Some(public_key) = known_peer_key(database, &beacon.peer_id) else {
+                tracing::debug!("Presence beacon from {} without known key", beacon.peer_id);
+                return;
+            };
+
+            let now = server_time_ms(signaling);
+            match beacon.verify(&public_key, now) {
+                Ok(()) => {
+                    let was_verified = presence.is_verified_online(&beacon.peer_id, now);
+                    presence.record(&beacon);
+                    if !was_verified {
+                        tracing::info!("Contact online (verified): {}", beacon.peer_id);
+                        let _ = database.set_online_status(&beacon.peer_id, true);
+                        notify_presence_webhook(database, &beacon.peer_id, true);
+                        push_presence(presence_batch, app_handle, &beacon.peer_id, true);
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!("Rejected presence beacon from {}: {}", beacon.peer_id, e);
+                    let _ = app_handle.emit(
+                        "contact:presence_invalid",
+                        serde_json::json!({
+                            "peer_id": beacon.peer_id,
+                            "reason": e.to_string()
+                        }),
+                    );
+                }
+            }
         }
 
         SignalingEvent::ContactOffline { peer_id } => {
             tracing::info!("Contact offline: {}", peer_id);
+            presence.forget(&peer_id);
             let _ = database.set_online_status(&peer_id, false);
-            let _ = app_handle.emit("contact:offline", &peer_id);
+            notify_presence_webhook(database, &peer_id, false);
+            push_presence(presence_batch, app_handle, &peer_id, false);
+        }
+
+        SignalingEvent::RateLimited { retry_after_secs } => {
+            let _ = app_handle.emit(
+                "signaling:rate_limited",
+                serde_json::json!({ "retryAfterSecs": retry_after_secs }),
+            );
         }
 
         SignalingEvent::Error { code, message } => {
@@ -665,6 +3643,169 @@ async fn handle_signaling_event(
                 }),
             );
         }
+
+        SignalingEvent::UnknownMessage { raw, error } => {
+            let _ = app_handle.emit(
+                "signaling:unknown_message",
+                serde_json::json!({
+                    "raw": raw,
+                    "error": error
+                }),
+            );
+        }
+    }
+}
+
+/// Verarbeitet LAN-Events und leitet sie an das Frontend weiter
+///
+/// Anruf-Events verwenden dieselben Frontend-Events wie das Signaling.
+async fn handle_lan_event(
+    event: LanEvent,
+    app_handle: &AppHandle,
+    call_engine: &Arc<CallEngine>,
+    database: &ContactsDatabase,
+    lan_discovery: &RwLock<Option<Arc<LanDiscovery>>>,
+) {
+    match event {
+        LanEvent::PeerDiscovered(peer) => {
+            let _ = app_handle.emit("lan:peer_discovered", &peer);
+        }
+
+        LanEvent::PeerLost { peer_id } => {
+            let _ = app_handle.emit("lan:peer_lost", &peer_id);
+        }
+
+        LanEvent::IncomingCall {
+            from_peer_id,
+            from_username,
+            sdp,
+        } => {
+            tracing::info!("Incoming LAN call from {}", from_username);
+
+            // Blockierte Peers werden still abgewiesen, ohne zu klingeln
+            let blocked = database.reject_if_blocked(&from_peer_id, || {
+                if let Some(discovery) = lan_discovery.read().clone() {
+                    let peer_id = from_peer_id.clone();
+                    tokio::spawn(async move {
+                        let _ = discovery
+                            .reject_call(&peer_id, Some("blocked".to_string()))
+                            .await;
+                    });
+                }
+            });
+            if blocked {
+                return;
+            }
+
+            call_engine.register_incoming_call(from_peer_id.clone(), from_username.clone());
+
+            let _ = app_handle.emit(
+                "call:incoming",
+                serde_json::json!({
+                    "fromPeerId": from_peer_id,
+                    "fromUsername": from_username,
+                    "sdp": sdp
+                }),
+            );
+        }
+
+        // Antworten und Auflegen nur von Teilnehmern des eigenen Anrufs
+        LanEvent::AnswerReceived { from_peer_id, .. }
+        | LanEvent::CallRejected {
+            by_peer_id: from_peer_id,
+            ..
+        }
+        | LanEvent::CallEnded {
+            by_peer_id: from_peer_id,
+        } if !call_engine.has_peer(&from_peer_id) => {
+            tracing::warn!(
+                "Ignoring LAN call message from {} (not in the call)",
+                from_peer_id
+            );
+        }
+
+        LanEvent::AnswerReceived { from_peer_id, sdp } => {
+            if let Err(e) = call_engine.handle_answer(&from_peer_id, sdp).await {
+                tracing::error!("Failed to handle LAN answer: {}", e);
+            }
+            let _ = app_handle.emit("call:answer_received", from_peer_id);
+        }
+
+        LanEvent::CallRejected { by_peer_id, reason } => {
+            call_engine.end_peer_call(&by_peer_id);
+            let _ = app_handle.emit(
+                "call:rejected",
+                serde_json::json!({
+                    "byPeerId": by_peer_id,
+                    "reason": reason
+                }),
+            );
+        }
+
+        LanEvent::CallEnded { by_peer_id } => {
+            call_engine.end_peer_call(&by_peer_id);
+            let _ = app_handle.emit("call:ended", by_peer_id);
+        }
+    }
+}
+
+// ============================================================================
+// ICE SERVER HEALTH
+// ============================================================================
+
+/// Prüft die konfigurierten ICE Server regelmäßig im Leerlauf
+///
+/// Während eines Anrufs wird nicht geprüft, damit die Prüfungen weder
+/// Bandbreite noch die Verbindungen des Anrufs beeinflussen. Eine Runde wird
+/// abgebrochen, sobald ein Anruf beginnt.
+async fn monitor_ice_servers(call_engine: Arc<CallEngine>, monitor: Arc<IceHealthMonitor>) {
+    tokio::time::sleep(ICE_HEALTH_STARTUP_DELAY).await;
+
+    loop {
+        for url in call_engine.ice_server_urls() {
+            if call_engine.state() != CallState::Idle {
+                break;
+            }
+            let result = probe_ice_server(&url).await;
+            if !result.reachable {
+                tracing::debug!("ICE server {} not reachable: {:?}", url, result.error);
+            }
+            monitor.record(&url, result);
+        }
+
+        tokio::time::sleep(ICE_HEALTH_INTERVAL).await;
+    }
+}
+
+// ============================================================================
+// SYSTEM DEVICE WATCHER
+// ============================================================================
+
+/// Fragt die System-Standardgeräte ab und meldet Wechsel an das Frontend
+///
+/// Ein Wechsel verwirft außerdem die vorbereiteten Audio-Geräte. Läuft für
+/// die gesamte Lebensdauer der App. Siehe `call_engine::device_watch` zu den
+/// Plattform-Einschränkungen.
+async fn watch_default_devices(app_handle: AppHandle, call_engine: Arc<CallEngine>) {
+    let mut known = match tokio::task::spawn_blocking(DefaultDevices::query).await {
+        Ok(devices) => devices,
+        Err(_) => return,
+    };
+
+    loop {
+        tokio::time::sleep(DEFAULT_DEVICE_POLL_INTERVAL).await;
+
+        let Ok(current) = tokio::task::spawn_blocking(DefaultDevices::query).await else {
+            continue;
+        };
+        let changes = current.changes_since(&known);
+        if !changes.is_empty() {
+            call_engine.invalidate_prewarmed_audio();
+        }
+        for change in changes {
+            let _ = app_handle.emit("system:default_device_changed", &change);
+        }
+        known = current;
     }
 }
 
@@ -679,6 +3820,11 @@ pub fn run() {
     let signaling_url = std::env::var("SIGNALING_URL")
         .unwrap_or_else(|_| "https://call-app-signaling.questxen.workers.dev".to_string());
 
+    // Unverschlüsseltes ws:// nur explizit für lokale Entwicklung erlauben
+    let allow_insecure_signaling = std::env::var("SIGNALING_ALLOW_INSECURE")
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false);
+
     tauri::Builder::default()
         .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
             let _ = app
@@ -715,8 +3861,64 @@ pub fn run() {
             }
 
             // App State initialisieren
-            let state =
-                AppState::init(signaling_url.clone()).expect("Failed to initialize app state");
+            let state = AppState::init(signaling_url.clone(), allow_insecure_signaling)
+                .expect("Failed to initialize app state");
+
+            // Neue Identität melden, damit das Frontend zum Key-Backup auffordern kann
+            match state.keypair() {
+                Ok(keypair) if state.keypair_origin == KeyPairOrigin::Created => {
+                    let _ = app.emit("identity:created", keypair.public_key_base64());
+                }
+                Ok(_) => {}
+                // Verschlüsselte Identität: Frontend muss nach der Passphrase fragen
+                Err(_) => {
+                    let _ = app.emit("identity:locked", ());
+                }
+            }
+
+            // Call-Events für die gesamte Laufzeit ins Diagnose-Log schreiben
+            tauri::async_runtime::spawn(log_events(
+                Arc::clone(&state.event_log),
+                state.call_engine.subscribe(),
+                "call",
+            ));
+
+            // Anrufverlauf für die gesamte Laufzeit mitschreiben
+            tauri::async_runtime::spawn(record_call_history(
+                Arc::clone(&state.database),
+                state.call_engine.subscribe(),
+            ));
+
+            // UI-Sounds für Verbindungsaufbau und Auflegen
+            tauri::async_runtime::spawn(play_call_sounds(
+                Arc::clone(&state.sound_effects),
+                Arc::clone(&state.call_engine),
+                state.call_engine.subscribe(),
+            ));
+
+            // Anruf-Events an den Webhook melden
+            tauri::async_runtime::spawn(forward_call_webhooks(
+                Arc::clone(&state.webhooks),
+                state.call_engine.subscribe(),
+            ));
+
+            // Erreichbarkeit der ICE Server im Leerlauf prüfen
+            tauri::async_runtime::spawn(monitor_ice_servers(
+                Arc::clone(&state.call_engine),
+                Arc::clone(&state.ice_health),
+            ));
+
+            // Signaling bei Inaktivität trennen (nur wenn aktiviert)
+            tauri::async_runtime::spawn(disconnect_when_idle(
+                Arc::clone(&state),
+                app.handle().clone(),
+            ));
+
+            // Wechsel der System-Standardgeräte beobachten
+            tauri::async_runtime::spawn(watch_default_devices(
+                app.handle().clone(),
+                Arc::clone(&state.call_engine),
+            ));
 
             // State im Tauri-App registrieren
             app.manage(state);
@@ -726,30 +3928,177 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             // Identity
             get_public_key,
+            get_my_fingerprint,
+            is_new_identity,
+            is_identity_locked,
+            unlock_identity,
+            set_key_passphrase,
             get_peer_id,
             get_username,
+            get_my_contact_card,
+            parse_contact_card,
             // Signaling
             connect_and_register,
+            check_username_available,
             disconnect,
             find_user,
+            find_user_by_key,
+            set_heartbeat_interval,
+            set_idle_timeout,
+            get_idle_timeout,
+            report_activity,
+            get_connection_diagnostics,
+            get_pending_requests,
+            cancel_request,
             // Contacts
             get_contacts,
+            get_contacts_sorted,
+            search_contacts,
             add_contact,
             delete_contact,
+            get_deleted_contacts,
+            restore_contact,
+            purge_deleted_contacts,
             update_contact_name,
+            set_contact_notes,
+            import_contacts,
+            mark_contact_verified,
+            accept_contact_key,
+            get_contact_fingerprint,
+            block_contact,
+            unblock_contact,
+            set_auto_add_contacts,
+            get_auto_add_contacts,
+            set_individual_presence_events,
+            get_individual_presence_events,
             refresh_contact_statuses,
+            refresh_contact_status,
+            // Callback Requests
+            request_callback,
+            get_callback_requests,
+            dismiss_callback_request,
+            // Chat
+            send_chat,
+            get_chat_messages,
+            // Call History
+            get_usage_stats,
+            get_missed_calls,
+            get_missed_call_count,
+            mark_missed_calls_seen,
+            list_recordings,
+            delete_recording,
+            set_recording_storage_cap,
+            get_recording_storage_cap,
             // Calls
             start_call,
+            call_by_public_key,
+            redial_last,
             accept_call,
             reject_call,
+            dismiss_incoming_call,
             hangup,
+            hangup_peer,
+            force_reset_call,
+            add_call_peer,
+            get_call_peers,
             get_call_state,
+            get_call_state_detailed,
+            get_dtls_fingerprints,
+            get_security_info,
             set_muted,
             is_muted,
+            get_call_duration,
+            get_call_stats,
             get_audio_levels,
+            is_voice_active,
+            warm_up_audio,
+            prewarm_call,
+            set_ice_transport_policy,
+            get_ice_transport_policy,
+            add_turn_server,
+            clear_turn_servers,
+            set_max_bitrate,
+            get_max_bitrate,
+            set_setup_retry_enabled,
+            get_setup_retry_enabled,
+            skip_echo_check,
+            // Diagnostics
+            get_event_log,
+            clear_event_log,
+            set_event_log_redaction,
+            generate_diagnostics,
+            get_ice_server_health,
+            test_connectivity,
+            get_log_path,
+            read_recent_logs,
+            // Testing
+            set_network_simulation,
+            get_network_simulation,
+            set_verbose_ice_logging,
+            set_output_delay,
+            get_output_delay,
+            // Manual Signaling
+            create_manual_offer,
+            accept_manual_offer,
+            apply_remote_answer,
+            add_ice_candidate,
+            get_local_description,
+            get_local_candidates,
+            // LAN Discovery
+            start_lan_discovery,
+            stop_lan_discovery,
+            get_lan_peers,
             // Audio Settings
             get_audio_devices,
+            set_input_device,
+            set_output_device,
+            get_selected_audio_devices,
+            check_microphone_permission,
+            request_microphone_permission,
+            get_supported_codecs,
+            set_playback_prefill_frames,
+            get_playback_prefill_frames,
+            set_audio_frame_size,
+            get_audio_frame_size,
+            set_codec_bitrate,
+            get_codec_bitrate,
+            set_echo_check_enabled,
+            get_echo_check_enabled,
+            set_output_limiter,
+            get_output_limiter,
+            set_vad_enabled,
+            get_vad_enabled,
+            set_vad_threshold,
+            get_vad_threshold,
+            set_push_to_talk,
+            get_push_to_talk,
+            ptt_key_down,
+            ptt_key_up,
+            set_ringtone_enabled,
+            get_ringtone_enabled,
+            set_ringtone,
+            get_ringtone,
+            set_sound_effects_enabled,
+            get_sound_effects_enabled,
+            set_quiet_during_call,
+            get_quiet_during_call,
+            set_sound_effect,
+            play_sound_effect,
+            play_test_tone,
+            // Integrations
+            set_webhook,
+            clear_webhook,
+            get_webhook,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|_app, event| {
+            // Der AppState ist statisch und wird nie gedroppt, daher laufende
+            // Anrufe beim Beenden explizit schließen
+            if let tauri::RunEvent::Exit = event {
+                if let Some(state) = AppState::get() {
+                    tauri::async_runtime::block_on(state.call_engine.shutdown());
+                }
+            }
+        });
 }
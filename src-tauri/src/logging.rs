@@ -0,0 +1,346 @@
+//! Logging auf stdout und in rotierende Dateien
+//!
+//! Neben der Konsole landen alle Logs in `<data>/logs/call-app.log`. Erreicht
+//! die Datei `MAX_LOG_FILE_SIZE`, wird sie zu `call-app.log.1` verschoben,
+//! ältere Dateien rücken nach und alles jenseits von `MAX_LOG_FILES` wird
+//! gelöscht. So bleiben die Logs vor einem Absturz erhalten, ohne dass das
+//! Verzeichnis unbegrenzt wächst.
+//!
+//! Schreiben und Rotieren übernimmt ein eigener Thread. Der Logger reicht
+//! fertige Zeilen nur über einen begrenzten Channel weiter und verwirft sie,
+//! wenn dieser voll ist, damit Tasks der Async-Runtime nie auf Dateizugriffe
+//! warten. Schlägt das Schreiben fehl (z.B. Platte voll), wird der Fehler
+//! einmal auf stderr gemeldet und nur noch auf stdout geloggt.
+//!
+//! Die Dateien enthalten dieselben Zeilen wie stdout. Der Private Key und
+//! TURN-Credentials werden nirgends geloggt (`KeyPair` zeigt im `Debug`-Format
+//! nur den Public Key), IP-Adressen werden beim Auslesen über
+//! `read_recent_logs` auf Wunsch maskiert.
+
+use crate::paths::app_data_dir;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::EnvFilter;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Name des Log-Verzeichnisses im Datenverzeichnis
+const LOG_DIR_NAME: &str = "logs";
+
+/// Name der aktuellen Log-Datei
+pub const LOG_FILE_NAME: &str = "call-app.log";
+
+/// Größe, ab der die aktuelle Datei rotiert wird
+pub const MAX_LOG_FILE_SIZE: u64 = 5 * 1024 * 1024;
+
+/// Anzahl aufbewahrter Dateien inkl. der aktuellen
+pub const MAX_LOG_FILES: usize = 5;
+
+/// Maximal auslesbare Zeilen pro Aufruf von `read_recent_logs`
+pub const MAX_RECENT_LOG_LINES: usize = 5000;
+
+/// Zeilen, die höchstens auf den Schreib-Thread warten
+const LOG_CHANNEL_CAPACITY: usize = 1024;
+
+// ============================================================================
+// INITIALIZATION
+// ============================================================================
+
+/// Richtet Logging auf stdout und in die rotierende Log-Datei ein
+///
+/// Kann das Log-Verzeichnis nicht angelegt werden, wird nur auf stdout
+/// geloggt und eine Warnung ausgegeben.
+pub fn init_logging() {
+    let filter = EnvFilter::from_default_env()
+        .add_directive("call_app=debug".parse().unwrap())
+        .add_directive("webrtc=warn".parse().unwrap());
+
+    let file = RotatingFile::open(log_dir(), MAX_LOG_FILE_SIZE, MAX_LOG_FILES);
+    let (file_writer, file_error) = match file {
+        Ok(file) => (Some(LogWriter::spawn(file)), None),
+        Err(e) => (None, Some(e)),
+    };
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer())
+        .with(file_writer.map(|writer| {
+            tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .with_writer(writer)
+        }))
+        .init();
+
+    match file_error {
+        None => tracing::info!("Writing logs to {:?}", log_path()),
+        Some(e) => tracing::warn!("File logging disabled: {}", e),
+    }
+}
+
+/// Verzeichnis der Log-Dateien
+fn log_dir() -> PathBuf {
+    app_data_dir().join(LOG_DIR_NAME)
+}
+
+/// Pfad der aktuellen Log-Datei
+pub fn log_path() -> PathBuf {
+    log_dir().join(LOG_FILE_NAME)
+}
+
+// ============================================================================
+// ROTATING FILE
+// ============================================================================
+
+/// Log-Datei mit größenbasierter Rotation
+#[derive(Debug)]
+struct RotatingFile {
+    dir: PathBuf,
+    max_size: u64,
+    max_files: usize,
+    file: File,
+    size: u64,
+}
+
+impl RotatingFile {
+    /// Öffnet die aktuelle Log-Datei zum Anhängen (legt das Verzeichnis an)
+    fn open(dir: PathBuf, max_size: u64, max_files: usize) -> io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+        let file = open_append(&dir.join(LOG_FILE_NAME))?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            dir,
+            max_size,
+            max_files: max_files.max(1),
+            file,
+            size,
+        })
+    }
+
+    /// Hängt eine Zeile an und rotiert vorher, falls die Datei sonst zu groß wird
+    fn write_line(&mut self, line: &[u8]) -> io::Result<()> {
+        if self.size > 0 && self.size + line.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+        self.file.write_all(line)?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+
+    /// Verschiebt `.log` → `.log.1` → `.log.2` … und verwirft die älteste Datei
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+
+        let current = self.dir.join(LOG_FILE_NAME);
+        if self.max_files == 1 {
+            fs::remove_file(&current)?;
+        } else {
+            let oldest = rotated_path(&self.dir, self.max_files - 1);
+            if oldest.exists() {
+                fs::remove_file(&oldest)?;
+            }
+            for index in (1..self.max_files - 1).rev() {
+                let from = rotated_path(&self.dir, index);
+                if from.exists() {
+                    fs::rename(&from, rotated_path(&self.dir, index + 1))?;
+                }
+            }
+            fs::rename(&current, rotated_path(&self.dir, 1))?;
+        }
+
+        self.file = open_append(&self.dir.join(LOG_FILE_NAME))?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+/// Pfad der `index`-ten rotierten Datei (1 = jüngste)
+fn rotated_path(dir: &Path, index: usize) -> PathBuf {
+    dir.join(format!("{}.{}", LOG_FILE_NAME, index))
+}
+
+// ============================================================================
+// BACKGROUND WRITER
+// ============================================================================
+
+/// `MakeWriter`, der Log-Zeilen an den Schreib-Thread übergibt
+#[derive(Debug, Clone)]
+struct LogWriter {
+    tx: SyncSender<Vec<u8>>,
+}
+
+impl LogWriter {
+    /// Startet den Schreib-Thread für die Datei
+    fn spawn(file: RotatingFile) -> Self {
+        let (tx, rx) = mpsc::sync_channel(LOG_CHANNEL_CAPACITY);
+        std::thread::Builder::new()
+            .name("log-writer".into())
+            .spawn(move || {
+                if let Err(e) = run_writer(file, rx) {
+                    // Nicht über tracing melden, sonst entsteht eine Schleife
+                    eprintln!("Failed to write log file, file logging disabled: {}", e);
+                }
+            })
+            .expect("failed to spawn log writer thread");
+        Self { tx }
+    }
+}
+
+impl Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Voller Channel: Zeile verwerfen statt den Aufrufer zu blockieren
+        let _ = self.tx.try_send(buf.to_vec());
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for LogWriter {
+    type Writer = LogWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+/// Schreibt Zeilen, bis alle Sender verworfen sind oder ein Fehler auftritt
+///
+/// Nach einem Fehler endet der Thread. Mit dem Receiver schließt sich der
+/// Channel, `LogWriter` verwirft danach alle weiteren Zeilen.
+fn run_writer(mut file: RotatingFile, rx: Receiver<Vec<u8>>) -> io::Result<()> {
+    while let Ok(line) = rx.recv() {
+        file.write_line(&line)?;
+    }
+    Ok(())
+}
+
+// ============================================================================
+// READING
+// ============================================================================
+
+/// Liest die letzten `lines` Zeilen aus der aktuellen und den rotierten Dateien
+///
+/// Blockiert auf Dateizugriffe und sollte aus `spawn_blocking` aufgerufen
+/// werden. Die älteste Zeile steht zuerst.
+pub fn read_recent_logs(lines: usize) -> io::Result<Vec<String>> {
+    read_recent_lines(&log_dir(), lines.min(MAX_RECENT_LOG_LINES), MAX_LOG_FILES)
+}
+
+fn read_recent_lines(dir: &Path, lines: usize, max_files: usize) -> io::Result<Vec<String>> {
+    let mut collected: Vec<String> = Vec::new();
+
+    for index in 0..max_files {
+        if collected.len() >= lines {
+            break;
+        }
+        let path = if index == 0 {
+            dir.join(LOG_FILE_NAME)
+        } else {
+            rotated_path(dir, index)
+        };
+        let file = match File::open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => break,
+            Err(e) => return Err(e),
+        };
+
+        let file_lines = BufReader::new(file)
+            .lines()
+            .collect::<io::Result<Vec<String>>>()?;
+        let take = (lines - collected.len()).min(file_lines.len());
+        let mut older = file_lines[file_lines.len() - take..].to_vec();
+        older.append(&mut collected);
+        collected = older;
+    }
+
+    Ok(collected)
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_log_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("call-app-logs-{}", uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn test_rotation_keeps_configured_file_count() {
+        let dir = temp_log_dir();
+        let mut file = RotatingFile::open(dir.clone(), 100, 3).unwrap();
+
+        for i in 0..50 {
+            file.write_line(format!("line {:02} ....................\n", i).as_bytes())
+                .unwrap();
+        }
+
+        let count = fs::read_dir(&dir).unwrap().count();
+        assert_eq!(count, 3);
+        assert!(dir.join(LOG_FILE_NAME).exists());
+        assert!(rotated_path(&dir, 2).exists());
+        assert!(!rotated_path(&dir, 3).exists());
+        for entry in fs::read_dir(&dir).unwrap() {
+            assert!(entry.unwrap().metadata().unwrap().len() <= 100);
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_recent_lines_span_rotated_files() {
+        let dir = temp_log_dir();
+        let mut file = RotatingFile::open(dir.clone(), 100, 3).unwrap();
+        for i in 0..10 {
+            file.write_line(format!("line {:02} ....................\n", i).as_bytes())
+                .unwrap();
+        }
+
+        // 29 Byte pro Zeile, also 3 Zeilen pro Datei: Zeilen 0-2 sind bereits verworfen
+        let recent = read_recent_lines(&dir, 5, 3).unwrap();
+        assert_eq!(recent.len(), 5);
+        assert!(recent[0].starts_with("line 05"));
+        assert!(recent[4].starts_with("line 09"));
+
+        let all = read_recent_lines(&dir, 100, 3).unwrap();
+        assert_eq!(all.len(), 7);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_write_error_disables_file_sink() {
+        let dir = temp_log_dir();
+        let mut file = RotatingFile::open(dir.clone(), 10, 3).unwrap();
+        file.write_line(b"first line\n").unwrap();
+        // Ohne Verzeichnis scheitert die nächste Rotation
+        fs::remove_dir_all(&dir).unwrap();
+
+        let (tx, rx) = mpsc::sync_channel(4);
+        tx.send(b"second line\n".to_vec()).unwrap();
+        tx.send(b"third line\n".to_vec()).unwrap();
+        assert!(run_writer(file, rx).is_err());
+
+        // Weitere Zeilen werden verworfen, statt erneut zu scheitern
+        assert!(matches!(
+            tx.try_send(b"fourth line\n".to_vec()),
+            Err(mpsc::TrySendError::Disconnected(_))
+        ));
+    }
+}
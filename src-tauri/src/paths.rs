@@ -0,0 +1,88 @@
+//! Datenverzeichnis der App
+//!
+//! Key, Datenbank und Diagnoseberichte liegen im selben Verzeichnis. Es wird
+//! in dieser Reihenfolge bestimmt:
+//!
+//! 1. `CALL_APP_DATA_DIR`, sofern gesetzt (CI, Container, portable Installation)
+//! 2. das plattformübliche Verzeichnis aus `ProjectDirs`
+//! 3. `<temp>/call-app`, wenn das System kein Home-Verzeichnis liefert
+//!
+//! Der letzte Fall hält die App in ungewöhnlichen Umgebungen startfähig. Das
+//! Temp-Verzeichnis wird aber ggf. vom System geleert, dann entsteht beim
+//! nächsten Start eine neue Identität. Darauf wird mit einer Warnung hingewiesen.
+
+use std::path::PathBuf;
+use std::sync::Once;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Umgebungsvariable, die das Datenverzeichnis überschreibt
+pub const DATA_DIR_ENV: &str = "CALL_APP_DATA_DIR";
+
+/// Name des Verzeichnisses im Temp-Verzeichnis (letzter Ausweg)
+const FALLBACK_DIR_NAME: &str = "call-app";
+
+// ============================================================================
+// DATA DIRECTORY
+// ============================================================================
+
+/// Ermittelt das Datenverzeichnis der App (legt es nicht an)
+pub fn app_data_dir() -> PathBuf {
+    resolve_data_dir(
+        std::env::var_os(DATA_DIR_ENV).map(PathBuf::from),
+        directories::ProjectDirs::from("com", "kaufm", "call-app")
+            .map(|dirs| dirs.data_dir().to_path_buf()),
+    )
+}
+
+/// Wählt zwischen Override, Plattform-Verzeichnis und Fallback
+fn resolve_data_dir(override_dir: Option<PathBuf>, project_dir: Option<PathBuf>) -> PathBuf {
+    if let Some(dir) = override_dir.filter(|dir| !dir.as_os_str().is_empty()) {
+        return dir;
+    }
+    if let Some(dir) = project_dir {
+        return dir;
+    }
+
+    let fallback = std::env::temp_dir().join(FALLBACK_DIR_NAME);
+    static WARN_ONCE: Once = Once::new();
+    WARN_ONCE.call_once(|| {
+        tracing::warn!(
+            "Could not determine app data directory, using {:?} (set {} to choose a persistent location)",
+            fallback,
+            DATA_DIR_ENV
+        );
+    });
+    fallback
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_data_dir_prefers_override_then_project_dir() {
+        let project = PathBuf::from("/home/alice/.local/share/call-app");
+
+        assert_eq!(
+            resolve_data_dir(Some(PathBuf::from("/data/pulse")), Some(project.clone())),
+            PathBuf::from("/data/pulse")
+        );
+        // Eine leere Variable zählt als nicht gesetzt
+        assert_eq!(
+            resolve_data_dir(Some(PathBuf::new()), Some(project.clone())),
+            project
+        );
+        assert_eq!(resolve_data_dir(None, Some(project.clone())), project);
+        assert_eq!(
+            resolve_data_dir(None, None),
+            std::env::temp_dir().join(FALLBACK_DIR_NAME)
+        );
+    }
+}
@@ -0,0 +1,263 @@
+//! Verwaltung gespeicherter Gesprächsaufnahmen
+//!
+//! Aufnahmen liegen als Dateien in `<data>/recordings`, ihre Metadaten
+//! (Peer, Beginn, Dauer, Größe) in der Datenbank, verknüpft mit dem
+//! Anrufverlauf. Überschreitet die Summe aller Aufnahmen das Speicherlimit,
+//! werden die ältesten gelöscht, bis es wieder eingehalten wird.
+//!
+//! Die Aufnahme selbst ist noch nicht implementiert; ein Recorder meldet
+//! fertige Dateien über `RecordingStore::register` an.
+
+use crate::database::{ContactsDatabase, DatabaseError, NewRecording, Recording};
+use crate::paths::app_data_dir;
+use parking_lot::RwLock;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use thiserror::Error;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Name des Aufnahme-Verzeichnisses im Datenverzeichnis
+const RECORDINGS_DIR_NAME: &str = "recordings";
+
+/// Standard-Speicherlimit für alle Aufnahmen zusammen (1 GiB)
+pub const DEFAULT_RECORDING_STORAGE_CAP: u64 = 1024 * 1024 * 1024;
+
+// ============================================================================
+// ERROR TYPES
+// ============================================================================
+
+#[derive(Error, Debug)]
+pub enum RecordingError {
+    #[error(transparent)]
+    Database(#[from] DatabaseError),
+
+    #[error("Failed to delete recording: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Not a recording: {0}")]
+    OutsideRecordingsDir(String),
+
+    #[error("Recording not found: {0}")]
+    NotFound(String),
+}
+
+// ============================================================================
+// RECORDING STORE
+// ============================================================================
+
+/// Aufnahme-Dateien samt Metadaten und Speicherlimit
+pub struct RecordingStore {
+    database: Arc<ContactsDatabase>,
+    dir: PathBuf,
+    /// Maximale Gesamtgröße aller Aufnahmen in Bytes
+    storage_cap: RwLock<u64>,
+}
+
+impl RecordingStore {
+    /// Store im Standard-Verzeichnis mit Standard-Limit
+    pub fn new(database: Arc<ContactsDatabase>) -> Self {
+        Self::with_dir(database, app_data_dir().join(RECORDINGS_DIR_NAME))
+    }
+
+    fn with_dir(database: Arc<ContactsDatabase>, dir: PathBuf) -> Self {
+        Self {
+            database,
+            dir,
+            storage_cap: RwLock::new(DEFAULT_RECORDING_STORAGE_CAP),
+        }
+    }
+
+    /// Verzeichnis, in das ein Recorder seine Dateien schreibt
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Alle Aufnahmen, neueste zuerst
+    pub fn list(&self) -> Result<Vec<Recording>, RecordingError> {
+        Ok(self.database.get_recordings()?)
+    }
+
+    /// Trägt eine fertige Aufnahme ein und hält danach das Speicherlimit ein
+    ///
+    /// Gibt die dabei gelöschten Aufnahmen zurück.
+    pub fn register(&self, recording: NewRecording) -> Result<Vec<Recording>, RecordingError> {
+        self.ensure_inside_dir(&recording.path)?;
+        self.database.add_recording(&recording)?;
+        self.prune()
+    }
+
+    /// Löscht Datei und Eintrag einer Aufnahme
+    ///
+    /// Nur Pfade innerhalb des Aufnahme-Verzeichnisses werden akzeptiert,
+    /// damit über den Command keine beliebigen Dateien gelöscht werden.
+    pub fn delete(&self, path: &str) -> Result<(), RecordingError> {
+        self.ensure_inside_dir(path)?;
+        if !self.database.delete_recording(path)? {
+            return Err(RecordingError::NotFound(path.to_string()));
+        }
+        remove_file_if_exists(Path::new(path))?;
+        Ok(())
+    }
+
+    pub fn storage_cap(&self) -> u64 {
+        *self.storage_cap.read()
+    }
+
+    /// Setzt das Speicherlimit und löscht sofort, was darüber liegt
+    pub fn set_storage_cap(&self, bytes: u64) -> Result<Vec<Recording>, RecordingError> {
+        *self.storage_cap.write() = bytes;
+        self.prune()
+    }
+
+    /// Löscht die ältesten Aufnahmen, bis das Speicherlimit eingehalten wird
+    fn prune(&self) -> Result<Vec<Recording>, RecordingError> {
+        let recordings = self.database.get_recordings()?;
+        let pruned = select_for_pruning(recordings, self.storage_cap());
+
+        for recording in &pruned {
+            tracing::info!(
+                "Pruning recording {} ({} bytes) to stay within storage cap",
+                recording.path,
+                recording.size_bytes
+            );
+            self.database.delete_recording(&recording.path)?;
+            if let Err(e) = remove_file_if_exists(Path::new(&recording.path)) {
+                tracing::warn!("Failed to delete recording file {}: {}", recording.path, e);
+            }
+        }
+
+        Ok(pruned)
+    }
+
+    /// Prüft, dass ein Pfad im Aufnahme-Verzeichnis liegt (ohne `..`)
+    fn ensure_inside_dir(&self, path: &str) -> Result<(), RecordingError> {
+        let candidate = Path::new(path);
+        let escapes = candidate
+            .components()
+            .any(|component| matches!(component, Component::ParentDir));
+        if escapes || !candidate.starts_with(&self.dir) || candidate == self.dir {
+            return Err(RecordingError::OutsideRecordingsDir(path.to_string()));
+        }
+        Ok(())
+    }
+}
+
+fn remove_file_if_exists(path: &Path) -> io::Result<()> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Wählt die ältesten Aufnahmen, ohne die alle übrigen ins Limit passen
+fn select_for_pruning(mut recordings: Vec<Recording>, cap: u64) -> Vec<Recording> {
+    recordings.sort_by_key(|recording| (recording.started_at, recording.id));
+
+    let mut total: u64 = recordings
+        .iter()
+        .map(|recording| recording.size_bytes.max(0) as u64)
+        .sum();
+    let mut pruned = Vec::new();
+    for recording in recordings {
+        if total <= cap {
+            break;
+        }
+        total -= recording.size_bytes.max(0) as u64;
+        pruned.push(recording);
+    }
+    pruned
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recording(id: i64, started_at: i64, size_bytes: i64) -> Recording {
+        Recording {
+            id,
+            call_history_id: None,
+            peer_id: "peer".to_string(),
+            username: None,
+            path: format!("/rec/{}.wav", id),
+            started_at,
+            duration_ms: 1_000,
+            size_bytes,
+        }
+    }
+
+    #[test]
+    fn test_pruning_removes_oldest_until_within_cap() {
+        let recordings = vec![
+            recording(3, 3_000, 400),
+            recording(1, 1_000, 300),
+            recording(2, 2_000, 300),
+        ];
+
+        assert!(select_for_pruning(recordings.clone(), 1_000).is_empty());
+
+        let pruned = select_for_pruning(recordings.clone(), 700);
+        let ids: Vec<i64> = pruned.iter().map(|recording| recording.id).collect();
+        assert_eq!(ids, vec![1]);
+
+        let pruned = select_for_pruning(recordings.clone(), 500);
+        let ids: Vec<i64> = pruned.iter().map(|recording| recording.id).collect();
+        assert_eq!(ids, vec![1, 2]);
+
+        assert_eq!(select_for_pruning(recordings, 0).len(), 3);
+    }
+
+    #[test]
+    fn test_storage_cap_deletes_files_and_entries() {
+        let database = Arc::new(ContactsDatabase::open_in_memory().unwrap());
+        let dir = std::env::temp_dir().join(format!("recordings-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let store = RecordingStore::with_dir(Arc::clone(&database), dir.clone());
+
+        for (index, started_at) in [(1, 1_000), (2, 2_000)] {
+            let path = dir.join(format!("{}.wav", index));
+            std::fs::write(&path, [0u8; 100]).unwrap();
+            let pruned = store
+                .register(NewRecording {
+                    call_history_id: None,
+                    peer_id: "peer".to_string(),
+                    path: path.to_string_lossy().into_owned(),
+                    started_at,
+                    duration_ms: 1_000,
+                    size_bytes: 100,
+                })
+                .unwrap();
+            assert!(pruned.is_empty());
+        }
+
+        let pruned = store.set_storage_cap(150).unwrap();
+        assert_eq!(pruned.len(), 1);
+        assert!(!dir.join("1.wav").exists());
+        assert!(dir.join("2.wav").exists());
+        assert_eq!(store.list().unwrap().len(), 1);
+
+        let outside = std::env::temp_dir().join("other.wav");
+        assert!(matches!(
+            store.delete(&outside.to_string_lossy()),
+            Err(RecordingError::OutsideRecordingsDir(_))
+        ));
+        let escaping = dir.join("..").join("other.wav");
+        assert!(matches!(
+            store.delete(&escaping.to_string_lossy()),
+            Err(RecordingError::OutsideRecordingsDir(_))
+        ));
+
+        store.delete(&dir.join("2.wav").to_string_lossy()).unwrap();
+        assert!(store.list().unwrap().is_empty());
+        assert!(!dir.join("2.wav").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -7,15 +7,75 @@
 //! - Event-basierte Kommunikation
 
 use super::messages::*;
+use super::presence::PresenceBeacon;
 use crate::crypto::KeyPair;
+use crate::events::EVENT_CHANNEL_CAPACITY;
 use chrono::Utc;
 use futures::{SinkExt, StreamExt};
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use thiserror::Error;
-use tokio::sync::{broadcast, mpsc};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::{broadcast, mpsc, oneshot};
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Zeitfenster nach `Registered`, in dem ein sofortiger Verbindungsabbruch
+/// noch als fehlgeschlagene Registrierung gewertet wird
+const REGISTRATION_SETTLE_TIME: Duration = Duration::from_millis(150);
+
+/// Standard-Heartbeat-Intervall
+///
+/// Cloudflare Workers schließen inaktive WebSockets nach ca. 100 Sekunden,
+/// 25 Sekunden lassen genug Reserve für verlorene Heartbeats.
+pub const DEFAULT_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(25);
+
+/// Kleinstes erlaubtes Heartbeat-Intervall
+pub const MIN_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Größtes erlaubtes Heartbeat-Intervall (unterhalb des Cloudflare Idle-Timeouts)
+pub const MAX_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(90);
+
+/// Unbeantwortete Heartbeats, nach denen die Verbindung als tot gilt
+const MAX_UNANSWERED_HEARTBEATS: u32 = 3;
+
+/// Maximale Wartezeit auf die Antwort einer Username-Verfügbarkeitsprüfung
+const USERNAME_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Maximale Dauer, die eine einzelne Nachricht zum Senden brauchen darf
+///
+/// Hängt die Verbindung, ohne einen Fehler zu melden, staut sich die
+/// Warteschlange, während `is_connected` weiter `true` bleibt. Nach dieser
+/// Zeit gilt die Verbindung als tot und wird getrennt.
+const WRITE_STALL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Maximale Länge eines Anzeigenamens in Zeichen
+pub const MAX_DISPLAY_NAME_LENGTH: usize = 64;
+
+/// Maximale Länge einer Textnachricht in Zeichen
+pub const MAX_CHAT_MESSAGE_LENGTH: usize = 2000;
+
+/// Maximale Länge einer unbekannten Nachricht in Logs und Events
+const UNKNOWN_MESSAGE_MAX_LEN: usize = 512;
+
+/// Maximale Round-Trip-Zeit einer Zeitprobe
+///
+/// Bei längeren Laufzeiten ist die Annahme symmetrischer Wege zu ungenau,
+/// die Probe wird verworfen.
+const MAX_CLOCK_SYNC_ROUND_TRIP_MS: i64 = 5_000;
+
+/// Obergrenze für eine vom Server verlangte Sendepause
+///
+/// Schützt vor einem fehlerhaften `retryAfter`, das Suche und Presence
+/// sonst dauerhaft lahmlegen würde.
+pub const MAX_RATE_LIMIT_PAUSE: Duration = Duration::from_secs(300);
+
 // ============================================================================
 // ERROR TYPES
 // ============================================================================
@@ -31,11 +91,41 @@ pub enum SignalingError {
     #[error("Failed to send message: {0}")]
     SendFailed(String),
 
+    #[error("Outgoing message queue is full")]
+    QueueFull,
+
+    #[error("Rate limited by server, retry in {0}s")]
+    RateLimited(u64),
+
+    #[error("Unverifiable offer: {0}")]
+    UnverifiedOffer(String),
+
     #[error("Registration failed: {0}")]
     RegistrationFailed(String),
 
     #[error("Server error: {code} - {message}")]
     ServerError { code: i32, message: String },
+
+    #[error("Request timed out")]
+    Timeout,
+
+    #[error("Invalid heartbeat interval: {0}s")]
+    InvalidHeartbeatInterval(u64),
+
+    #[error("Display name too long: {0} characters (max {MAX_DISPLAY_NAME_LENGTH})")]
+    DisplayNameTooLong(usize),
+
+    #[error("Chat message is empty")]
+    EmptyChatMessage,
+
+    #[error("Chat message too long: {0} characters (max {MAX_CHAT_MESSAGE_LENGTH})")]
+    ChatMessageTooLong(usize),
+
+    #[error("Invalid signaling server URL: {0}")]
+    InvalidServerUrl(String),
+
+    #[error("Refusing unencrypted signaling connection to {0} (wss:// required)")]
+    InsecureServerUrl(String),
 }
 
 // ============================================================================
@@ -49,7 +139,13 @@ pub enum SignalingEvent {
     Connected,
 
     /// Verbindung getrennt
-    Disconnected,
+    ///
+    /// `code` ist der WebSocket Close-Code, falls der Server die Verbindung
+    /// mit einem Close-Frame beendet hat, sonst `None` (z.B. Netzwerkfehler).
+    Disconnected {
+        code: Option<u16>,
+        reason: Option<String>,
+    },
 
     /// Registrierung erfolgreich
     Registered { peer_id: String, username: String },
@@ -57,23 +153,46 @@ pub enum SignalingEvent {
     /// Benutzer gefunden
     UserFound(ContactInfo),
 
+    /// Der Server meldet für einen Peer einen anderen als den gespeicherten
+    /// Public Key (neues Gerät oder Man-in-the-Middle)
+    ///
+    /// Wird vor dem zugehörigen `UserFound` gesendet.
+    PublicKeyMismatch {
+        peer_id: String,
+        stored_key: String,
+        received_key: String,
+    },
+
     /// Benutzer nicht gefunden
     UserNotFound { username: String },
 
     /// Eingehender Anruf
+    ///
+    /// Public Key und Signatur sind ungeprüft, siehe `verify_offer_proof`.
+    /// `sender_verified` ist bei allen weitergeleiteten Nachrichten nur
+    /// gesetzt, wenn sie vom gespeicherten Key des Absenders signiert sind.
     IncomingCall {
         from_peer_id: String,
         from_username: String,
+        from_display_name: Option<String>,
         sdp: String,
+        public_key: Option<String>,
+        sdp_signature: Option<String>,
+        sender_verified: bool,
     },
 
     /// SDP Answer erhalten
-    AnswerReceived { from_peer_id: String, sdp: String },
+    AnswerReceived {
+        from_peer_id: String,
+        sdp: String,
+        sender_verified: bool,
+    },
 
     /// ICE Candidate erhalten
     IceCandidateReceived {
         from_peer_id: String,
         candidate: String,
+        sender_verified: bool,
     },
 
     /// Anruf abgelehnt
@@ -85,14 +204,40 @@ pub enum SignalingEvent {
     /// Anruf beendet
     CallEnded { by_peer_id: String },
 
+    /// Eigene Rückruf-Bitte wurde vom Server angenommen
+    CallRequested { to_peer_id: String },
+
+    /// Ein anderer Peer bittet um Rückruf
+    CallbackRequestReceived {
+        from_peer_id: String,
+        from_username: String,
+        requested_at: i64,
+    },
+
+    /// Textnachricht eines anderen Peers
+    ChatMessage {
+        from_peer_id: String,
+        body: String,
+        sender_verified: bool,
+    },
+
     /// Kontakt online
     ContactOnline { peer_id: String },
 
     /// Kontakt offline
     ContactOffline { peer_id: String },
 
+    /// Signierter Presence Beacon eines Kontakts (noch nicht verifiziert)
+    PresenceBeacon(PresenceBeacon),
+
+    /// Der Server drosselt den Client, Suche und Presence pausieren so lange
+    RateLimited { retry_after_secs: u64 },
+
     /// Fehler vom Server
     Error { code: i32, message: String },
+
+    /// Nachricht, die nicht geparst werden konnte (z.B. unbekannter Typ)
+    UnknownMessage { raw: String, error: String },
 }
 
 // ============================================================================
@@ -104,6 +249,110 @@ struct ClientState {
     is_connected: bool,
     peer_id: Option<String>,
     username: Option<String>,
+    /// Zeitpunkt des letzten gesendeten Heartbeats (Unix-Millisekunden)
+    last_heartbeat_at: Option<i64>,
+    /// Gesendete Heartbeats seit dem letzten `Pong`
+    unanswered_heartbeats: u32,
+    /// Lokaler Zeitpunkt, zu dem die Registrierung gesendet wurde
+    registration_sent_at: Option<i64>,
+    /// Geschätzte Abweichung der Server-Uhr von der lokalen Uhr (Millisekunden)
+    clock_offset_ms: Option<i64>,
+    /// Bis hierhin pausieren nicht dringende Nachrichten (Rate Limit des Servers)
+    rate_limited_until: Option<Instant>,
+}
+
+/// Verbindungsdiagnose des Signaling Clients
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SignalingDiagnostics {
+    pub server_url: String,
+    pub is_connected: bool,
+    pub peer_id: Option<String>,
+    pub username: Option<String>,
+    pub heartbeat_interval_secs: u64,
+    pub last_heartbeat_at: Option<i64>,
+    /// Server-Zeit minus lokale Zeit, `None` solange keine Probe vorliegt
+    pub clock_offset_ms: Option<i64>,
+}
+
+/// Art einer ausstehenden Anfrage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PendingRequestKind {
+    /// find_user, wartet auf `UserFound`/`UserNotFound`
+    FindUser,
+    /// find_user_by_key, wartet auf `UserFoundByKey`/`UserNotFoundByKey`
+    FindUserByKey,
+    /// SDP Offer, wartet auf Answer, Ablehnung oder Auflegen des Peers
+    Offer,
+}
+
+/// Ausstehende Anfrage (für Diagnose und Abbruch)
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct PendingRequest {
+    pub request_id: String,
+    pub kind: PendingRequestKind,
+    /// Gesuchter Username, gesuchter Public Key bzw. angerufene Peer-ID
+    pub target: String,
+    /// Zeitpunkt des Sendens (Unix-Millisekunden)
+    pub created_at: i64,
+}
+
+/// Eintrag in der Tabelle ausstehender Anfragen
+///
+/// Der Sender (nur bei Suchanfragen) liefert `Some(ContactInfo)`, wenn der
+/// Benutzer gefunden wurde, sonst `None`.
+struct PendingEntry {
+    request: PendingRequest,
+    response_tx: Option<oneshot::Sender<Option<ContactInfo>>>,
+}
+
+/// Ausstehende Anfragen, korreliert über die Request-ID
+type PendingRequests = Arc<Mutex<HashMap<String, PendingEntry>>>;
+
+// ============================================================================
+// PEER KEYS
+// ============================================================================
+
+/// Fehlercode für `SignalingEvent::Error`, wenn eine weitergeleitete Nachricht
+/// nicht vom angegebenen Absender signiert ist (lokal vergeben, nie vom Server)
+pub const INVALID_SENDER_SIGNATURE: i32 = -401;
+
+/// Liefert den gespeicherten Public Key (Base64) eines Peers
+type PeerKeyLookup = Arc<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
+/// Maximale Abweichung zwischen `signedAt` und der Serverzeit (ms)
+///
+/// Ältere (oder vordatierte) Nachrichten werden verworfen, damit der Server
+/// mitgeschnittene Offers und Candidates nicht erneut zustellen kann.
+pub const MAX_SIGNATURE_AGE_MS: i64 = 60_000;
+
+/// Public Keys der Gegenstellen zum Prüfen weitergeleiteter Nachrichten
+///
+/// Keys werden nicht zwischengespeichert: Gepinnte oder geänderte Keys gelten
+/// sofort, die Abfrage in der Datenbank ist indiziert.
+#[derive(Default)]
+struct PeerKeys {
+    lookup: RwLock<Option<PeerKeyLookup>>,
+}
+
+impl PeerKeys {
+    fn get(&self, peer_id: &str) -> Option<String> {
+        self.lookup
+            .read()
+            .as_ref()
+            .and_then(|lookup| lookup(peer_id))
+    }
+}
+
+/// Ergebnis der Absenderprüfung einer Server-Nachricht
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SenderCheck {
+    /// Keine weitergeleitete Nachricht eines Peers
+    NotRelayed,
+    /// Signiert vom gespeicherten Key des Absenders
+    Verified,
+    /// Für den Absender ist kein Key gespeichert (Trust on First Use)
+    Unverified,
 }
 
 // ============================================================================
@@ -117,12 +366,20 @@ pub struct SignalingClient {
     state: Arc<RwLock<ClientState>>,
     tx: Option<mpsc::Sender<String>>,
     event_tx: broadcast::Sender<SignalingEvent>,
+    pending_requests: PendingRequests,
+    heartbeat_interval: Arc<RwLock<Duration>>,
+    /// Erlaubt unverschlüsselte `ws://` Verbindungen (nur lokale Entwicklung)
+    allow_insecure: bool,
+    /// Anzeigename, der bei der Registrierung mitgesendet wird
+    display_name: Option<String>,
+    /// Gespeicherte Keys der Gegenstellen (Prüfung weitergeleiteter Nachrichten)
+    peer_keys: Arc<PeerKeys>,
 }
 
 impl SignalingClient {
     /// Erstellt einen neuen SignalingClient
     pub fn new(server_url: String, keypair: Arc<KeyPair>) -> Self {
-        let (event_tx, _) = broadcast::channel(100);
+        let (event_tx, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
 
         Self {
             server_url,
@@ -130,9 +387,93 @@ impl SignalingClient {
             state: Arc::new(RwLock::new(ClientState::default())),
             tx: None,
             event_tx,
+            pending_requests: Arc::new(Mutex::new(HashMap::new())),
+            heartbeat_interval: Arc::new(RwLock::new(DEFAULT_HEARTBEAT_INTERVAL)),
+            allow_insecure: false,
+            display_name: None,
+            peer_keys: Arc::new(PeerKeys::default()),
+        }
+    }
+
+    /// Erlaubt unverschlüsselte Verbindungen zu `http://` bzw. `ws://` URLs
+    ///
+    /// Standardmäßig wird nur `wss://` akzeptiert, damit signierte Nachrichten
+    /// nie im Klartext übertragen werden. Nur für lokale Entwicklung gedacht.
+    pub fn set_allow_insecure(&mut self, allow: bool) {
+        self.allow_insecure = allow;
+    }
+
+    /// Setzt den Anzeigenamen für die nächste Registrierung
+    ///
+    /// Leere Namen zählen als nicht gesetzt. Der Username bleibt die Adresse
+    /// für Suche und Anrufe, der Anzeigename dient nur der Darstellung.
+    pub fn set_display_name(&mut self, display_name: Option<String>) -> Result<(), SignalingError> {
+        self.display_name = normalize_display_name(display_name)?;
+        Ok(())
+    }
+
+    /// Gibt den Anzeigenamen zurück, mit dem registriert wird
+    pub fn display_name(&self) -> Option<String> {
+        self.display_name.clone()
+    }
+
+    /// Setzt die Quelle für gespeicherte Public Keys der Gegenstellen
+    ///
+    /// Offers, Answers und ICE Candidates von Peers mit bekanntem Key werden
+    /// nur angenommen, wenn sie von diesem Key signiert sind.
+    pub fn set_peer_key_lookup(
+        &self,
+        lookup: impl Fn(&str) -> Option<String> + Send + Sync + 'static,
+    ) {
+        *self.peer_keys.lookup.write() = Some(Arc::new(lookup));
+    }
+
+    /// Gibt das aktuelle Heartbeat-Intervall zurück
+    pub fn heartbeat_interval(&self) -> Duration {
+        *self.heartbeat_interval.read()
+    }
+
+    /// Setzt das Heartbeat-Intervall
+    ///
+    /// Wirkt auch auf einen bereits laufenden Heartbeat ab dem nächsten Tick.
+    pub fn set_heartbeat_interval(&self, interval: Duration) -> Result<(), SignalingError> {
+        validate_heartbeat_interval(interval)?;
+        *self.heartbeat_interval.write() = interval;
+        Ok(())
+    }
+
+    /// Gibt die Verbindungsdiagnose zurück
+    pub fn diagnostics(&self) -> SignalingDiagnostics {
+        let state = self.state.read();
+        SignalingDiagnostics {
+            server_url: self.server_url.clone(),
+            is_connected: state.is_connected,
+            peer_id: state.peer_id.clone(),
+            username: state.username.clone(),
+            heartbeat_interval_secs: self.heartbeat_interval().as_secs(),
+            last_heartbeat_at: state.last_heartbeat_at,
+            clock_offset_ms: state.clock_offset_ms,
         }
     }
 
+    /// Gibt die geschätzte Abweichung der Server-Uhr zurück (Millisekunden)
+    pub fn clock_offset_ms(&self) -> Option<i64> {
+        self.state.read().clock_offset_ms
+    }
+
+    /// Aktuelle Zeit nach der Server-Uhr (Unix-Millisekunden)
+    ///
+    /// Zum Prüfen von Zeitstempeln anderer Peers, die ebenfalls in
+    /// Server-Zeit stempeln.
+    pub fn server_time_ms(&self) -> i64 {
+        Utc::now().timestamp_millis() + self.clock_offset()
+    }
+
+    /// Abweichung für das Stempeln ausgehender Nachrichten (0 ohne Probe)
+    fn clock_offset(&self) -> i64 {
+        self.clock_offset_ms().unwrap_or(0)
+    }
+
     /// Gibt einen Event-Receiver zurück
     pub fn subscribe(&self) -> broadcast::Receiver<SignalingEvent> {
         self.event_tx.subscribe()
@@ -159,7 +500,7 @@ impl SignalingClient {
         username: String,
     ) -> Result<String, SignalingError> {
         // WebSocket URL erstellen
-        let ws_url = format!("{}/ws", self.server_url.replace("http", "ws"));
+        let ws_url = self.ws_url()?;
 
         tracing::info!("Connecting to signaling server: {}", ws_url);
 
@@ -168,10 +509,10 @@ impl SignalingClient {
             .await
             .map_err(|e| SignalingError::ConnectionFailed(e.to_string()))?;
 
-        let (mut write, mut read) = ws_stream.split();
+        let (write, mut read) = ws_stream.split();
 
         // Message-Sender erstellen
-        let (tx, mut rx) = mpsc::channel::<String>(100);
+        let (tx, rx) = mpsc::channel::<String>(100);
         self.tx = Some(tx.clone());
 
         // State aktualisieren
@@ -179,39 +520,83 @@ impl SignalingClient {
             let mut state = self.state.write();
             state.is_connected = true;
             state.username = Some(username.clone());
+            state.unanswered_heartbeats = 0;
         }
 
         // Event senden
         let _ = self.event_tx.send(SignalingEvent::Connected);
 
+        // Vor dem Start des Read-Tasks abonnieren, damit kein Disconnect verpasst wird
+        let mut settle_rx = self.event_tx.subscribe();
+
         // Channel für Registrierungs-Response
         let (reg_tx, mut reg_rx) = mpsc::channel::<Result<String, SignalingError>>(1);
 
+        // Meldet dem Read-Task, dass die Verbindung tot ist (Senden gescheitert
+        // oder der Server beantwortet keine Heartbeats mehr)
+        let (write_failed_tx, mut write_failed_rx) = mpsc::channel::<String>(1);
+        let heartbeat_failed_tx = write_failed_tx.clone();
+
         // Read-Task starten
         let state_clone = Arc::clone(&self.state);
         let event_tx = self.event_tx.clone();
         let reg_tx_clone = reg_tx.clone();
+        let pending_requests = Arc::clone(&self.pending_requests);
+        let peer_keys = Arc::clone(&self.peer_keys);
 
         tokio::spawn(async move {
-            while let Some(msg_result) = read.next().await {
+            let mut close_code = None;
+            let mut close_reason = None;
+
+            loop {
+                let msg_result = tokio::select! {
+                    msg = read.next() => match msg {
+                        Some(msg) => msg,
+                        None => break,
+                    },
+                    Some(reason) = write_failed_rx.recv() => {
+                        close_reason = Some(reason);
+                        break;
+                    }
+                };
                 match msg_result {
-                    Ok(Message::Text(text)) => {
-                        if let Ok(server_msg) = serde_json::from_str::<ServerMessage>(&text) {
+                    Ok(Message::Text(text)) => match serde_json::from_str::<ServerMessage>(&text) {
+                        Ok(server_msg) => {
                             Self::handle_server_message(
                                 server_msg,
                                 &state_clone,
                                 &event_tx,
                                 &reg_tx_clone,
+                                &pending_requests,
+                                &peer_keys,
                             )
                             .await;
                         }
-                    }
-                    Ok(Message::Close(_)) => {
-                        tracing::info!("WebSocket closed by server");
+                        Err(e) => {
+                            let raw = truncate_raw_message(&text, UNKNOWN_MESSAGE_MAX_LEN);
+                            tracing::warn!("Unparseable server message ({}): {}", e, raw);
+                            let _ = event_tx.send(SignalingEvent::UnknownMessage {
+                                raw,
+                                error: e.to_string(),
+                            });
+                        }
+                    },
+                    Ok(Message::Close(frame)) => {
+                        if let Some(frame) = frame {
+                            close_code = Some(u16::from(frame.code));
+                            close_reason =
+                                Some(frame.reason.into_owned()).filter(|reason| !reason.is_empty());
+                        }
+                        tracing::info!(
+                            "WebSocket closed by server ({:?}: {:?})",
+                            close_code,
+                            close_reason
+                        );
                         break;
                     }
                     Err(e) => {
                         tracing::error!("WebSocket error: {}", e);
+                        close_reason = Some(e.to_string());
                         break;
                     }
                     _ => {}
@@ -223,55 +608,252 @@ impl SignalingClient {
                 let mut state = state_clone.write();
                 state.is_connected = false;
             }
-            let _ = event_tx.send(SignalingEvent::Disconnected);
+            // Offene Anfragen verwerfen, wartende Receiver sehen einen geschlossenen Channel
+            pending_requests.lock().clear();
+            let _ = event_tx.send(SignalingEvent::Disconnected {
+                code: close_code,
+                reason: close_reason,
+            });
         });
 
         // Write-Task starten
         tokio::spawn(async move {
-            while let Some(msg) = rx.recv().await {
-                if let Err(e) = write.send(Message::Text(msg)).await {
+            let reason = match run_write_loop(write, rx, WRITE_STALL_TIMEOUT).await {
+                WriteLoopExit::ChannelClosed => return,
+                WriteLoopExit::SendFailed(e) => {
                     tracing::error!("Failed to send WebSocket message: {}", e);
-                    break;
+                    e
                 }
-            }
+                WriteLoopExit::Stalled => {
+                    tracing::error!(
+                        "WebSocket send stalled for {:?}, dropping connection",
+                        WRITE_STALL_TIMEOUT
+                    );
+                    "Outgoing messages stalled".to_string()
+                }
+            };
+            // Verbindung als tot behandeln, der Read-Task meldet `Disconnected`
+            let _ = write_failed_tx.send(reason).await;
         });
 
         // Registrierung senden
         self.send_register(username.clone()).await?;
 
         // Auf Registrierungs-Response warten (max 10 Sekunden)
-        tokio::select! {
+        let peer_id = tokio::select! {
             result = reg_rx.recv() => {
                 match result {
-                    Some(Ok(peer_id)) => Ok(peer_id),
-                    Some(Err(e)) => Err(e),
-                    None => Err(SignalingError::RegistrationFailed("No response".to_string())),
+                    Some(Ok(peer_id)) => peer_id,
+                    Some(Err(e)) => return Err(e),
+                    None => return Err(SignalingError::RegistrationFailed("No response".to_string())),
                 }
             }
             _ = tokio::time::sleep(tokio::time::Duration::from_secs(10)) => {
-                Err(SignalingError::RegistrationFailed("Timeout".to_string()))
+                return Err(SignalingError::RegistrationFailed("Timeout".to_string()));
             }
+        };
+
+        // Der Server kann die Verbindung direkt nach `Registered` schließen.
+        // Kurz abwarten, damit in diesem Fall kein Ok(peer_id) zurückgegeben wird.
+        let closed = tokio::time::timeout(REGISTRATION_SETTLE_TIME, async {
+            loop {
+                match settle_rx.recv().await {
+                    Ok(SignalingEvent::Disconnected { .. }) | Err(RecvError::Closed) => break,
+                    _ => continue,
+                }
+            }
+        })
+        .await
+        .is_ok();
+
+        if closed || !self.is_connected() {
+            return Err(SignalingError::RegistrationFailed(
+                "Connection closed after registration".to_string(),
+            ));
         }
+
+        self.start_heartbeat(peer_id.clone(), heartbeat_failed_tx);
+
+        Ok(peer_id)
+    }
+
+    /// Startet den Heartbeat-Task für die aktuelle Verbindung
+    ///
+    /// Der Task hält nur einen schwachen Sender, damit das Droppen des
+    /// Clients die Verbindung weiterhin schließt. Bleiben
+    /// `MAX_UNANSWERED_HEARTBEATS` Heartbeats ohne `Pong`, wird die
+    /// Verbindung über `connection_failed` beendet: Der Read-Task meldet dann
+    /// `Disconnected` ohne Close-Code, worauf neu verbunden wird.
+    fn start_heartbeat(&self, peer_id: String, connection_failed: mpsc::Sender<String>) {
+        let Some(tx) = self.tx.as_ref().map(|tx| tx.downgrade()) else {
+            return;
+        };
+        let keypair = Arc::clone(&self.keypair);
+        let state = Arc::clone(&self.state);
+        let interval = Arc::clone(&self.heartbeat_interval);
+
+        tokio::spawn(async move {
+            // Sofort einen Presence Beacon veröffentlichen, nicht erst beim ersten Tick
+            let paused = |state: &Arc<RwLock<ClientState>>| {
+                rate_limit_remaining(&state.read(), Instant::now()).is_some()
+            };
+            if let Some(tx) = tx.upgrade().filter(|_| !paused(&state)) {
+                let offset = state.read().clock_offset_ms.unwrap_or(0);
+                publish_presence_beacon(&keypair, &peer_id, &tx, offset);
+            }
+
+            loop {
+                // Intervall bei jedem Tick neu lesen, damit Änderungen sofort greifen
+                let period = *interval.read();
+                tokio::time::sleep(period).await;
+
+                if !state.read().is_connected {
+                    tracing::info!("Heartbeat: Client disconnected, stopping heartbeat task");
+                    break;
+                }
+                let Some(tx) = tx.upgrade() else {
+                    tracing::info!("Heartbeat: Client dropped, stopping heartbeat task");
+                    break;
+                };
+
+                let unanswered = state.read().unanswered_heartbeats;
+                if unanswered >= MAX_UNANSWERED_HEARTBEATS {
+                    tracing::warn!(
+                        "Heartbeat: {} heartbeats unanswered, dropping connection",
+                        unanswered
+                    );
+                    state.write().is_connected = false;
+                    let _ = connection_failed
+                        .send("Server stopped answering heartbeats".to_string())
+                        .await;
+                    break;
+                }
+
+                let offset = state.read().clock_offset_ms.unwrap_or(0);
+                let payload = HeartbeatPayload::new(peer_id.clone());
+                let result = sign_payload(&keypair, payload, offset).and_then(|msg| {
+                    tx.try_send(msg)
+                        .map_err(|e| SignalingError::SendFailed(e.to_string()))
+                });
+                match result {
+                    Ok(()) => {
+                        let mut state = state.write();
+                        state.last_heartbeat_at = Some(Utc::now().timestamp_millis());
+                        state.unanswered_heartbeats += 1;
+                    }
+                    Err(e) => tracing::warn!("Failed to send heartbeat: {}", e),
+                }
+
+                if paused(&state) {
+                    tracing::debug!("Rate limited, skipping presence beacon");
+                } else {
+                    publish_presence_beacon(&keypair, &peer_id, &tx, offset);
+                }
+            }
+        });
+    }
+
+    /// Gibt die WebSocket-URL des Signaling-Servers zurück
+    fn ws_url(&self) -> Result<String, SignalingError> {
+        websocket_url(&self.server_url, self.allow_insecure)
+    }
+
+    /// Prüft vor der Registrierung, ob ein Username noch verfügbar ist
+    ///
+    /// Verwendet eine eigene, kurzlebige Verbindung und verändert weder den
+    /// Client-State noch eine bestehende Verbindung.
+    pub async fn check_username_available(&self, username: String) -> Result<bool, SignalingError> {
+        let (mut ws_stream, _) = connect_async(&self.ws_url()?)
+            .await
+            .map_err(|e| SignalingError::ConnectionFailed(e.to_string()))?;
+
+        let request_id = uuid::Uuid::new_v4().to_string();
+        let msg = sign_payload(
+            &self.keypair,
+            CheckUsernamePayload::new(username, request_id.clone()),
+            self.clock_offset(),
+        )?;
+        ws_stream
+            .send(Message::Text(msg))
+            .await
+            .map_err(|e| SignalingError::SendFailed(e.to_string()))?;
+
+        let result = tokio::time::timeout(USERNAME_CHECK_TIMEOUT, async {
+            while let Some(msg_result) = ws_stream.next().await {
+                let text = match msg_result {
+                    Ok(Message::Text(text)) => text,
+                    Ok(Message::Close(_)) => break,
+                    Ok(_) => continue,
+                    Err(e) => return Err(SignalingError::ConnectionFailed(e.to_string())),
+                };
+
+                match serde_json::from_str::<ServerMessage>(&text) {
+                    Ok(ServerMessage::UsernameAvailability {
+                        available,
+                        request_id: response_id,
+                        ..
+                    }) if response_id.as_deref().is_none_or(|id| id == request_id) => {
+                        return Ok(available);
+                    }
+                    Ok(ServerMessage::Error { code, message, .. }) => {
+                        return Err(SignalingError::ServerError { code, message });
+                    }
+                    _ => {}
+                }
+            }
+            Err(SignalingError::NotConnected)
+        })
+        .await
+        .unwrap_or(Err(SignalingError::Timeout));
+
+        let _ = ws_stream.close(None).await;
+        result
     }
 
     /// Sendet eine Registrierungs-Nachricht
     async fn send_register(&self, username: String) -> Result<(), SignalingError> {
-        let payload = RegisterPayload::new(username, self.keypair.public_key_base64());
+        let payload = RegisterPayload::new(
+            username,
+            self.keypair.public_key_base64(),
+            self.display_name.clone(),
+        );
+        self.state.write().registration_sent_at = Some(Utc::now().timestamp_millis());
         self.send_signed_message(payload).await
     }
 
     /// Sucht einen Benutzer
     pub async fn find_user(&self, target_username: String) -> Result<(), SignalingError> {
         let peer_id = self.peer_id().ok_or(SignalingError::NotConnected)?;
+        self.check_rate_limit()?;
         let payload = FindUserPayload::new(peer_id, target_username);
         self.send_signed_message(payload).await
     }
 
+    /// Baut ein Offer mit eigenem Public Key und Signatur über das SDP
+    fn offer_payload(&self, from_peer_id: String, to_peer_id: String, sdp: String) -> OfferPayload {
+        let sdp_signature = self.keypair.sign_offer(&from_peer_id, &to_peer_id, &sdp);
+        OfferPayload::new(
+            from_peer_id,
+            to_peer_id,
+            sdp,
+            self.keypair.public_key_base64(),
+            sdp_signature,
+        )
+    }
+
     /// Sendet ein SDP Offer
     pub async fn send_offer(&self, to_peer_id: String, sdp: String) -> Result<(), SignalingError> {
         let peer_id = self.peer_id().ok_or(SignalingError::NotConnected)?;
-        let payload = OfferPayload::new(peer_id, to_peer_id, sdp);
-        self.send_signed_message(payload).await
+        let payload = self.offer_payload(peer_id, to_peer_id.clone(), sdp);
+        self.send_signed_message(payload).await?;
+
+        self.track_request(
+            uuid::Uuid::new_v4().to_string(),
+            PendingRequestKind::Offer,
+            to_peer_id,
+            None,
+        );
+        Ok(())
     }
 
     /// Sendet ein SDP Answer
@@ -306,6 +888,7 @@ impl SignalingClient {
     /// Beendet einen Anruf
     pub async fn hangup(&self, to_peer_id: String) -> Result<(), SignalingError> {
         let peer_id = self.peer_id().ok_or(SignalingError::NotConnected)?;
+        resolve_offers(&self.pending_requests, &to_peer_id);
         let payload = HangupPayload::new(peer_id, to_peer_id);
         self.send_signed_message(payload).await
     }
@@ -336,15 +919,152 @@ impl SignalingClient {
     /// Sucht einen Benutzer synchron (blockiert nicht, verwendet try_send)
     pub fn find_user_sync(&self, target_username: String) -> Result<(), SignalingError> {
         let peer_id = self.peer_id().ok_or(SignalingError::NotConnected)?;
+        self.check_rate_limit()?;
         let payload = FindUserPayload::new(peer_id, target_username);
         self.send_signed_message_sync(payload)
     }
 
+    /// Sucht einen Benutzer synchron und gibt einen Receiver für die korrelierte Antwort zurück
+    ///
+    /// Die Request-ID wird mitgeliefert, damit der Aufrufer bei einem Timeout
+    /// die Anfrage über `cancel_request` wieder freigeben kann.
+    pub fn lookup_user_sync(
+        &self,
+        target_username: String,
+    ) -> Result<(String, oneshot::Receiver<Option<ContactInfo>>), SignalingError> {
+        let peer_id = self.peer_id().ok_or(SignalingError::NotConnected)?;
+        self.check_rate_limit()?;
+        let request_id = uuid::Uuid::new_v4().to_string();
+
+        let (response_tx, response_rx) = oneshot::channel();
+        self.track_request(
+            request_id.clone(),
+            PendingRequestKind::FindUser,
+            target_username.clone(),
+            Some(response_tx),
+        );
+
+        let payload =
+            FindUserPayload::with_request_id(peer_id, target_username, request_id.clone());
+        if let Err(e) = self.send_signed_message_sync(payload) {
+            self.pending_requests.lock().remove(&request_id);
+            return Err(e);
+        }
+
+        Ok((request_id, response_rx))
+    }
+
+    /// Sucht einen Benutzer über seinen Public Key und gibt einen Receiver für die Antwort zurück
+    ///
+    /// Wie bei `lookup_user_sync` wird die Request-ID für `cancel_request`
+    /// mitgeliefert. Der Aufrufer sollte den Key anschließend für den Peer
+    /// pinnen, damit die Auflösung des Servers beim Anruf geprüft wird.
+    pub fn lookup_user_by_key_sync(
+        &self,
+        target_public_key: String,
+    ) -> Result<(String, oneshot::Receiver<Option<ContactInfo>>), SignalingError> {
+        let peer_id = self.peer_id().ok_or(SignalingError::NotConnected)?;
+        self.check_rate_limit()?;
+        let request_id = uuid::Uuid::new_v4().to_string();
+
+        let (response_tx, response_rx) = oneshot::channel();
+        self.track_request(
+            request_id.clone(),
+            PendingRequestKind::FindUserByKey,
+            target_public_key.clone(),
+            Some(response_tx),
+        );
+
+        let payload = FindUserByKeyPayload::new(peer_id, target_public_key, request_id.clone());
+        if let Err(e) = self.send_signed_message_sync(payload) {
+            self.pending_requests.lock().remove(&request_id);
+            return Err(e);
+        }
+
+        Ok((request_id, response_rx))
+    }
+
+    /// Verbleibende Sendepause nach einem Rate Limit des Servers
+    pub fn rate_limit_remaining(&self) -> Option<Duration> {
+        rate_limit_remaining(&self.state.read(), Instant::now())
+    }
+
+    /// Lehnt nicht dringende Nachrichten während einer Sendepause ab
+    ///
+    /// Betrifft Suchanfragen und Presence Beacons. Anruf-Signaling und
+    /// Heartbeats laufen weiter, sonst würden Anrufe abbrechen bzw. der
+    /// Server die Verbindung trennen.
+    fn check_rate_limit(&self) -> Result<(), SignalingError> {
+        match self.rate_limit_remaining() {
+            Some(remaining) => Err(SignalingError::RateLimited(
+                remaining.as_secs_f64().ceil() as u64
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Gibt alle ausstehenden Anfragen zurück (älteste zuerst)
+    pub fn pending_requests(&self) -> Vec<PendingRequest> {
+        let mut requests: Vec<PendingRequest> = self
+            .pending_requests
+            .lock()
+            .values()
+            .map(|entry| entry.request.clone())
+            .collect();
+        requests.sort_by_key(|r| r.created_at);
+        requests
+    }
+
+    /// Bricht eine ausstehende Anfrage ab und gibt sie frei
+    ///
+    /// Ein wartender find_user-Aufrufer sieht einen geschlossenen Channel.
+    /// Gibt die abgebrochene Anfrage zurück, falls sie noch ausstand.
+    pub fn cancel_request(&self, request_id: &str) -> Option<PendingRequest> {
+        self.pending_requests
+            .lock()
+            .remove(request_id)
+            .map(|entry| entry.request)
+    }
+
+    /// Merkt sich eine ausstehende Anfrage
+    fn track_request(
+        &self,
+        request_id: String,
+        kind: PendingRequestKind,
+        target: String,
+        response_tx: Option<oneshot::Sender<Option<ContactInfo>>>,
+    ) {
+        let request = PendingRequest {
+            request_id: request_id.clone(),
+            kind,
+            target,
+            created_at: Utc::now().timestamp_millis(),
+        };
+        self.pending_requests.lock().insert(
+            request_id,
+            PendingEntry {
+                request,
+                response_tx,
+            },
+        );
+    }
+
     /// Sendet ein SDP Offer synchron (blockiert nicht, verwendet try_send)
+    ///
+    /// Das Offer bleibt als ausstehende Anfrage sichtbar, bis der Peer
+    /// antwortet, ablehnt oder auflegt.
     pub fn send_offer_sync(&self, to_peer_id: String, sdp: String) -> Result<(), SignalingError> {
         let peer_id = self.peer_id().ok_or(SignalingError::NotConnected)?;
-        let payload = OfferPayload::new(peer_id, to_peer_id, sdp);
-        self.send_signed_message_sync(payload)
+        let payload = self.offer_payload(peer_id, to_peer_id.clone(), sdp);
+        self.send_signed_message_sync(payload)?;
+
+        self.track_request(
+            uuid::Uuid::new_v4().to_string(),
+            PendingRequestKind::Offer,
+            to_peer_id,
+            None,
+        );
+        Ok(())
     }
 
     /// Sendet ein SDP Answer synchron
@@ -365,9 +1085,29 @@ impl SignalingClient {
         self.send_signed_message_sync(payload)
     }
 
+    /// Bittet einen (evtl. offline) Peer um Rückruf
+    pub fn request_callback_sync(&self, to_peer_id: String) -> Result<(), SignalingError> {
+        let peer_id = self.peer_id().ok_or(SignalingError::NotConnected)?;
+        let payload = CallbackRequestPayload::new(peer_id, to_peer_id);
+        self.send_signed_message_sync(payload)
+    }
+
+    /// Sendet eine Textnachricht an einen Peer
+    pub fn send_chat_message_sync(
+        &self,
+        to_peer_id: String,
+        body: String,
+    ) -> Result<(), SignalingError> {
+        let body = normalize_chat_message(body)?;
+        let peer_id = self.peer_id().ok_or(SignalingError::NotConnected)?;
+        let payload = ChatMessagePayload::new(peer_id, to_peer_id, body);
+        self.send_signed_message_sync(payload)
+    }
+
     /// Beendet einen Anruf synchron
     pub fn hangup_sync(&self, to_peer_id: String) -> Result<(), SignalingError> {
         let peer_id = self.peer_id().ok_or(SignalingError::NotConnected)?;
+        resolve_offers(&self.pending_requests, &to_peer_id);
         let payload = HangupPayload::new(peer_id, to_peer_id);
         self.send_signed_message_sync(payload)
     }
@@ -383,45 +1123,43 @@ impl SignalingClient {
         self.send_signed_message_sync(payload)
     }
 
+    /// Sendet mehrere ICE Candidates gebündelt in einer Nachricht
+    ///
+    /// Ein einzelner Candidate wird als normale `ice_candidate` Nachricht
+    /// gesendet, damit ältere Gegenstellen ihn weiterhin verstehen.
+    pub fn send_ice_candidates_sync(
+        &self,
+        to_peer_id: String,
+        mut candidates: Vec<String>,
+    ) -> Result<(), SignalingError> {
+        let peer_id = self.peer_id().ok_or(SignalingError::NotConnected)?;
+        match candidates.len() {
+            0 => Ok(()),
+            1 => {
+                let candidate = candidates.remove(0);
+                let payload = IceCandidatePayload::new(peer_id, to_peer_id, candidate);
+                self.send_signed_message_sync(payload)
+            }
+            _ => {
+                let payload = IceCandidatesPayload::new(peer_id, to_peer_id, candidates);
+                self.send_signed_message_sync(payload)
+            }
+        }
+    }
+
     /// Sendet eine signierte Nachricht synchron (non-blocking)
     fn send_signed_message_sync<T: serde::Serialize>(
         &self,
         payload: T,
     ) -> Result<(), SignalingError> {
         let tx = self.tx.as_ref().ok_or(SignalingError::NotConnected)?;
-
-        // Timestamp hinzufügen
-        let timestamp = Utc::now().timestamp_millis();
-
-        // Payload als JSON für Signatur
-        let payload_json = serde_json::to_value(&payload)
-            .map_err(|e| SignalingError::SendFailed(e.to_string()))?;
-
-        // Signatur erstellen
-        let mut signable = payload_json.clone();
-        if let Some(obj) = signable.as_object_mut() {
-            obj.insert(
-                "timestamp".to_string(),
-                serde_json::Value::Number(timestamp.into()),
-            );
-        }
-        let signature = self.keypair.sign_message(&signable);
-
-        // Finale Nachricht zusammenstellen
-        let mut final_msg = signable;
-        if let Some(obj) = final_msg.as_object_mut() {
-            obj.insert(
-                "signature".to_string(),
-                serde_json::Value::String(signature),
-            );
-        }
-
-        let msg_string = serde_json::to_string(&final_msg)
-            .map_err(|e| SignalingError::SendFailed(e.to_string()))?;
+        let msg_string = sign_payload(&self.keypair, payload, self.clock_offset())?;
 
         // try_send ist non-blocking
-        tx.try_send(msg_string)
-            .map_err(|e| SignalingError::SendFailed(e.to_string()))
+        tx.try_send(msg_string).map_err(|e| match e {
+            TrySendError::Full(_) => SignalingError::QueueFull,
+            TrySendError::Closed(_) => SignalingError::SendFailed(e.to_string()),
+        })
     }
 
     /// Sendet eine signierte Nachricht
@@ -430,35 +1168,7 @@ impl SignalingClient {
         payload: T,
     ) -> Result<(), SignalingError> {
         let tx = self.tx.as_ref().ok_or(SignalingError::NotConnected)?;
-
-        // Timestamp hinzufügen
-        let timestamp = Utc::now().timestamp_millis();
-
-        // Payload als JSON für Signatur
-        let payload_json = serde_json::to_value(&payload)
-            .map_err(|e| SignalingError::SendFailed(e.to_string()))?;
-
-        // Signatur erstellen
-        let mut signable = payload_json.clone();
-        if let Some(obj) = signable.as_object_mut() {
-            obj.insert(
-                "timestamp".to_string(),
-                serde_json::Value::Number(timestamp.into()),
-            );
-        }
-        let signature = self.keypair.sign_message(&signable);
-
-        // Finale Nachricht zusammenstellen
-        let mut final_msg = signable;
-        if let Some(obj) = final_msg.as_object_mut() {
-            obj.insert(
-                "signature".to_string(),
-                serde_json::Value::String(signature),
-            );
-        }
-
-        let msg_string = serde_json::to_string(&final_msg)
-            .map_err(|e| SignalingError::SendFailed(e.to_string()))?;
+        let msg_string = sign_payload(&self.keypair, payload, self.clock_offset())?;
 
         tx.send(msg_string)
             .await
@@ -471,16 +1181,44 @@ impl SignalingClient {
         state: &Arc<RwLock<ClientState>>,
         event_tx: &broadcast::Sender<SignalingEvent>,
         reg_tx: &mpsc::Sender<Result<String, SignalingError>>,
+        pending_requests: &PendingRequests,
+        peer_keys: &PeerKeys,
     ) {
+        // Weitergeleitete Anruf-Nachrichten müssen vom angegebenen Absender stammen
+        let (own_peer_id, clock_offset) = {
+            let s = state.read();
+            (s.peer_id.clone(), s.clock_offset_ms.unwrap_or(0))
+        };
+        let server_now = Utc::now().timestamp_millis() + clock_offset;
+        let check = match own_peer_id {
+            Some(own_peer_id) => verify_sender(&msg, &own_peer_id, peer_keys, server_now),
+            None => Ok(SenderCheck::Unverified),
+        };
+        let sender_verified = match check {
+            Ok(check) => check == SenderCheck::Verified,
+            Err(reason) => {
+                tracing::warn!("Dropping server message: {}", reason);
+                let _ = event_tx.send(SignalingEvent::Error {
+                    code: INVALID_SENDER_SIGNATURE,
+                    message: reason,
+                });
+                return;
+            }
+        };
+
         match msg {
             ServerMessage::Registered {
-                peer_id, username, ..
+                peer_id,
+                username,
+                timestamp,
             } => {
                 tracing::info!("Registered as {} with peer_id {}", username, peer_id);
                 {
                     let mut s = state.write();
                     s.peer_id = Some(peer_id.clone());
                     s.username = Some(username.clone());
+                    let sent_at = s.registration_sent_at;
+                    update_clock_offset(&mut s, sent_at, timestamp);
                 }
                 let _ = reg_tx.send(Ok(peer_id.clone())).await;
                 let _ = event_tx.send(SignalingEvent::Registered { peer_id, username });
@@ -489,37 +1227,115 @@ impl SignalingClient {
             ServerMessage::UserFound {
                 peer_id,
                 username,
+                display_name,
                 is_online,
+                public_key,
+                request_id,
                 ..
             } => {
-                let _ = event_tx.send(SignalingEvent::UserFound(ContactInfo {
+                let contact = ContactInfo {
                     peer_id,
                     username,
+                    display_name,
                     is_online,
-                }));
+                    public_key,
+                };
+                check_reported_key(&contact, peer_keys, event_tx);
+                if let Some(response_tx) = request_id
+                    .and_then(|id| pending_requests.lock().remove(&id))
+                    .and_then(|entry| entry.response_tx)
+                {
+                    let _ = response_tx.send(Some(contact.clone()));
+                }
+                let _ = event_tx.send(SignalingEvent::UserFound(contact));
             }
 
-            ServerMessage::UserNotFound { username, .. } => {
+            ServerMessage::UserNotFound {
+                username,
+                request_id,
+                ..
+            } => {
+                if let Some(response_tx) = request_id
+                    .and_then(|id| pending_requests.lock().remove(&id))
+                    .and_then(|entry| entry.response_tx)
+                {
+                    let _ = response_tx.send(None);
+                }
                 let _ = event_tx.send(SignalingEvent::UserNotFound { username });
             }
 
+            ServerMessage::UserFoundByKey {
+                peer_id,
+                username,
+                display_name,
+                public_key,
+                is_online,
+                request_id,
+                ..
+            } => {
+                tracing::info!("Public key {} resolved to peer {}", public_key, peer_id);
+                let contact = ContactInfo {
+                    peer_id,
+                    username,
+                    display_name,
+                    is_online,
+                    public_key: Some(public_key),
+                };
+                check_reported_key(&contact, peer_keys, event_tx);
+                if let Some(response_tx) = pending_requests
+                    .lock()
+                    .remove(&request_id)
+                    .and_then(|entry| entry.response_tx)
+                {
+                    let _ = response_tx.send(Some(contact.clone()));
+                }
+                let _ = event_tx.send(SignalingEvent::UserFound(contact));
+            }
+
+            ServerMessage::UserNotFoundByKey {
+                public_key,
+                request_id,
+                ..
+            } => {
+                tracing::info!("No user registered with public key {}", public_key);
+                if let Some(response_tx) = pending_requests
+                    .lock()
+                    .remove(&request_id)
+                    .and_then(|entry| entry.response_tx)
+                {
+                    let _ = response_tx.send(None);
+                }
+            }
+
             ServerMessage::IncomingOffer {
                 from_peer_id,
                 from_username,
+                from_display_name,
                 sdp,
+                public_key,
+                sdp_signature,
                 ..
             } => {
                 let _ = event_tx.send(SignalingEvent::IncomingCall {
                     from_peer_id,
                     from_username,
+                    from_display_name,
                     sdp,
+                    public_key,
+                    sdp_signature,
+                    sender_verified,
                 });
             }
 
             ServerMessage::IncomingAnswer {
                 from_peer_id, sdp, ..
             } => {
-                let _ = event_tx.send(SignalingEvent::AnswerReceived { from_peer_id, sdp });
+                resolve_offers(pending_requests, &from_peer_id);
+                let _ = event_tx.send(SignalingEvent::AnswerReceived {
+                    from_peer_id,
+                    sdp,
+                    sender_verified,
+                });
             }
 
             ServerMessage::IncomingIceCandidate {
@@ -530,27 +1346,93 @@ impl SignalingClient {
                 let _ = event_tx.send(SignalingEvent::IceCandidateReceived {
                     from_peer_id,
                     candidate,
+                    sender_verified,
                 });
             }
 
+            ServerMessage::IncomingIceCandidates {
+                from_peer_id,
+                candidates,
+                ..
+            } => {
+                // Gebündelte Candidates einzeln weiterreichen
+                for candidate in candidates {
+                    let _ = event_tx.send(SignalingEvent::IceCandidateReceived {
+                        from_peer_id: from_peer_id.clone(),
+                        candidate,
+                        sender_verified,
+                    });
+                }
+            }
+
             ServerMessage::CallRejected {
                 by_peer_id, reason, ..
             } => {
+                resolve_offers(pending_requests, &by_peer_id);
                 let _ = event_tx.send(SignalingEvent::CallRejected { by_peer_id, reason });
             }
 
             ServerMessage::CallEnded { by_peer_id, .. } => {
+                resolve_offers(pending_requests, &by_peer_id);
                 let _ = event_tx.send(SignalingEvent::CallEnded { by_peer_id });
             }
 
+            ServerMessage::CallbackQueued { to_peer_id, .. } => {
+                let _ = event_tx.send(SignalingEvent::CallRequested { to_peer_id });
+            }
+
+            ServerMessage::CallbackRequested {
+                from_peer_id,
+                from_username,
+                timestamp,
+            } => {
+                let _ = event_tx.send(SignalingEvent::CallbackRequestReceived {
+                    from_peer_id,
+                    from_username,
+                    requested_at: timestamp,
+                });
+            }
+
+            ServerMessage::IncomingChatMessage {
+                from_peer_id, body, ..
+            } => {
+                let _ = event_tx.send(SignalingEvent::ChatMessage {
+                    from_peer_id,
+                    body,
+                    sender_verified,
+                });
+            }
+
             ServerMessage::UserOnline { peer_id, .. } => {
                 let _ = event_tx.send(SignalingEvent::ContactOnline { peer_id });
             }
 
+            ServerMessage::PresenceBeacon {
+                peer_id,
+                issued_at,
+                proof,
+                ..
+            } => {
+                let _ = event_tx.send(SignalingEvent::PresenceBeacon(PresenceBeacon {
+                    peer_id,
+                    issued_at,
+                    proof,
+                }));
+            }
+
             ServerMessage::UserOffline { peer_id, .. } => {
                 let _ = event_tx.send(SignalingEvent::ContactOffline { peer_id });
             }
 
+            ServerMessage::RateLimited { retry_after, .. } => {
+                let pause = Duration::from_secs(retry_after).min(MAX_RATE_LIMIT_PAUSE);
+                tracing::warn!("Rate limited by server, pausing requests for {:?}", pause);
+                state.write().rate_limited_until = Some(Instant::now() + pause);
+                let _ = event_tx.send(SignalingEvent::RateLimited {
+                    retry_after_secs: pause.as_secs(),
+                });
+            }
+
             ServerMessage::Error { code, message, .. } => {
                 tracing::error!("Server error {}: {}", code, message);
                 // Bei Registrierungs-Fehlern auch dem reg_tx melden
@@ -563,31 +1445,439 @@ impl SignalingClient {
                 let _ = event_tx.send(SignalingEvent::Error { code, message });
             }
 
-            ServerMessage::Pong { .. } => {
-                // Heartbeat-Response - nichts zu tun
+            ServerMessage::UsernameAvailability { .. } => {
+                // Wird nur über die eigene Verbindung von `check_username_available` beantwortet
+            }
+
+            ServerMessage::Pong { timestamp } => {
+                // Heartbeat-Response, dient als Zeitprobe
+                let mut s = state.write();
+                s.unanswered_heartbeats = 0;
+                let sent_at = s.last_heartbeat_at;
+                update_clock_offset(&mut s, sent_at, timestamp);
             }
         }
     }
+}
 
-    /// Startet einen Heartbeat-Task
-    pub fn start_heartbeat(self: Arc<Self>) {
-        let client = Arc::clone(&self);
-        tokio::spawn(async move {
-            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(30));
-            loop {
-                interval.tick().await;
-                if client.is_connected() {
-                    if let Err(e) = client.send_heartbeat().await {
-                        tracing::warn!("Failed to send heartbeat: {}", e);
-                    }
-                } else {
-                    break;
-                }
-            }
+/// Prüft, ob ein Heartbeat-Intervall im erlaubten Bereich liegt
+pub fn validate_heartbeat_interval(interval: Duration) -> Result<(), SignalingError> {
+    if interval < MIN_HEARTBEAT_INTERVAL || interval > MAX_HEARTBEAT_INTERVAL {
+        return Err(SignalingError::InvalidHeartbeatInterval(interval.as_secs()));
+    }
+    Ok(())
+}
+
+/// Bereinigt einen Anzeigenamen (Leerzeichen am Rand, leer = nicht gesetzt)
+pub fn normalize_display_name(
+    display_name: Option<String>,
+) -> Result<Option<String>, SignalingError> {
+    let Some(name) = display_name
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+    else {
+        return Ok(None);
+    };
+
+    let length = name.chars().count();
+    if length > MAX_DISPLAY_NAME_LENGTH {
+        return Err(SignalingError::DisplayNameTooLong(length));
+    }
+    Ok(Some(name))
+}
+
+/// Entfernt Leerraum am Rand einer Textnachricht und prüft die Länge
+pub fn normalize_chat_message(body: String) -> Result<String, SignalingError> {
+    let body = body.trim();
+    if body.is_empty() {
+        return Err(SignalingError::EmptyChatMessage);
+    }
+
+    let length = body.chars().count();
+    if length > MAX_CHAT_MESSAGE_LENGTH {
+        return Err(SignalingError::ChatMessageTooLong(length));
+    }
+    Ok(body.to_string())
+}
+
+/// Prüft, ob ein eingehendes Offer vom Inhaber des mitgesendeten Keys stammt
+///
+/// Die Signatur muss genau dieses SDP vom Absender an uns abdecken, sonst
+/// könnte der Server ein Offer verändern oder einem anderen Peer unterschieben.
+/// Offers ohne Signatur (ältere Clients) gelten ebenfalls als nicht prüfbar.
+/// Gibt den Public Key in kanonischer Form zurück.
+pub fn verify_offer_proof(
+    own_peer_id: &str,
+    from_peer_id: &str,
+    sdp: &str,
+    public_key: Option<&str>,
+    sdp_signature: Option<&str>,
+) -> Result<String, SignalingError> {
+    let (Some(public_key), Some(sdp_signature)) = (public_key, sdp_signature) else {
+        return Err(SignalingError::UnverifiedOffer(
+            "missing public key or signature".to_string(),
+        ));
+    };
+
+    let public_key = KeyPair::validate_public_key(public_key)
+        .map_err(|e| SignalingError::UnverifiedOffer(e.to_string()))?;
+    KeyPair::verify_offer(&public_key, from_peer_id, own_peer_id, sdp, sdp_signature)
+        .map_err(|e| SignalingError::UnverifiedOffer(e.to_string()))?;
+    Ok(public_key)
+}
+
+/// Vergleicht den vom Server gemeldeten Public Key mit dem gespeicherten
+///
+/// Weicht er ab, wird `PublicKeyMismatch` gesendet. Ohne gespeicherten oder
+/// gemeldeten Key gibt es nichts zu vergleichen.
+fn check_reported_key(
+    contact: &ContactInfo,
+    peer_keys: &PeerKeys,
+    event_tx: &broadcast::Sender<SignalingEvent>,
+) {
+    let Some(received_key) = contact
+        .public_key
+        .as_deref()
+        .and_then(|key| KeyPair::validate_public_key(key).ok())
+    else {
+        return;
+    };
+    let Some(stored_key) = peer_keys.get(&contact.peer_id) else {
+        return;
+    };
+    if stored_key != received_key {
+        tracing::warn!(
+            "Server reported a different public key for {}",
+            contact.peer_id
+        );
+        let _ = event_tx.send(SignalingEvent::PublicKeyMismatch {
+            peer_id: contact.peer_id.clone(),
+            stored_key,
+            received_key,
         });
     }
 }
 
+/// Prüft, ob eine weitergeleitete Anruf-Nachricht vom angegebenen Absender stammt
+///
+/// Aus den Feldern wird die ursprüngliche Client-Nachricht rekonstruiert und
+/// deren Signatur gegen den gespeicherten Key des Absenders geprüft. Die
+/// Signatur darf höchstens `MAX_SIGNATURE_AGE_MS` von `server_now` abweichen.
+/// Ohne gespeicherten Key ist keine Prüfung möglich, der Absender gilt als
+/// unbestätigt (Offers unbekannter Anrufer prüft `verify_offer_proof`).
+/// Andere Nachrichten werden nicht geprüft.
+fn verify_sender(
+    msg: &ServerMessage,
+    own_peer_id: &str,
+    peer_keys: &PeerKeys,
+    server_now: i64,
+) -> Result<SenderCheck, String> {
+    let to_peer_id = own_peer_id.to_string();
+    let (from_peer_id, payload, sender) = match msg {
+        ServerMessage::IncomingOffer {
+            from_peer_id,
+            sdp,
+            public_key,
+            sdp_signature,
+            sender,
+            ..
+        } => (
+            from_peer_id,
+            serde_json::to_value(OfferPayload::new(
+                from_peer_id.clone(),
+                to_peer_id,
+                sdp.clone(),
+                public_key.clone().unwrap_or_default(),
+                sdp_signature.clone().unwrap_or_default(),
+            )),
+            sender,
+        ),
+        ServerMessage::IncomingAnswer {
+            from_peer_id,
+            sdp,
+            sender,
+            ..
+        } => (
+            from_peer_id,
+            serde_json::to_value(AnswerPayload::new(
+                from_peer_id.clone(),
+                to_peer_id,
+                sdp.clone(),
+            )),
+            sender,
+        ),
+        ServerMessage::IncomingIceCandidate {
+            from_peer_id,
+            candidate,
+            sender,
+            ..
+        } => (
+            from_peer_id,
+            serde_json::to_value(IceCandidatePayload::new(
+                from_peer_id.clone(),
+                to_peer_id,
+                candidate.clone(),
+            )),
+            sender,
+        ),
+        ServerMessage::IncomingIceCandidates {
+            from_peer_id,
+            candidates,
+            sender,
+            ..
+        } => (
+            from_peer_id,
+            serde_json::to_value(IceCandidatesPayload::new(
+                from_peer_id.clone(),
+                to_peer_id,
+                candidates.clone(),
+            )),
+            sender,
+        ),
+        ServerMessage::IncomingChatMessage {
+            from_peer_id,
+            body,
+            sender,
+            ..
+        } => (
+            from_peer_id,
+            serde_json::to_value(ChatMessagePayload::new(
+                from_peer_id.clone(),
+                to_peer_id,
+                body.clone(),
+            )),
+            sender,
+        ),
+        _ => return Ok(SenderCheck::NotRelayed),
+    };
+
+    let Some(public_key) = peer_keys.get(from_peer_id) else {
+        return Ok(SenderCheck::Unverified);
+    };
+    let (Some(signature), Some(signed_at)) = (&sender.signature, sender.signed_at) else {
+        return Err(format!("Unsigned message from {}", from_peer_id));
+    };
+    if (server_now - signed_at).abs() > MAX_SIGNATURE_AGE_MS {
+        return Err(format!("Stale signature on message from {}", from_peer_id));
+    }
+
+    let mut payload = payload.map_err(|e| e.to_string())?;
+    if let Some(obj) = payload.as_object_mut() {
+        obj.insert("timestamp".to_string(), signed_at.into());
+    }
+    match KeyPair::verify_message(&public_key, &payload, signature) {
+        Ok(true) => Ok(SenderCheck::Verified),
+        Ok(false) | Err(_) => Err(format!(
+            "Invalid signature on message from {}",
+            from_peer_id
+        )),
+    }
+}
+
+/// Leitet die WebSocket-URL aus der Server-URL ab
+///
+/// `https://` wird zu `wss://`, `http://` zu `ws://`. Unverschlüsselte
+/// Schemes werden nur mit `allow_insecure` akzeptiert.
+pub fn websocket_url(server_url: &str, allow_insecure: bool) -> Result<String, SignalingError> {
+    let server_url = server_url.trim().trim_end_matches('/');
+    let (scheme, rest) = server_url
+        .split_once("://")
+        .ok_or_else(|| SignalingError::InvalidServerUrl(server_url.to_string()))?;
+
+    let secure = match scheme.to_ascii_lowercase().as_str() {
+        "https" | "wss" => true,
+        "http" | "ws" => false,
+        _ => return Err(SignalingError::InvalidServerUrl(server_url.to_string())),
+    };
+    if rest.is_empty() {
+        return Err(SignalingError::InvalidServerUrl(server_url.to_string()));
+    }
+    if !secure && !allow_insecure {
+        return Err(SignalingError::InsecureServerUrl(server_url.to_string()));
+    }
+
+    let ws_scheme = if secure { "wss" } else { "ws" };
+    Ok(format!("{}://{}/ws", ws_scheme, rest))
+}
+
+/// Schätzt die Abweichung der Server-Uhr aus einer Zeitprobe (wie bei SNTP)
+///
+/// Angenommen wird, dass der Server seinen Zeitstempel in der Mitte der
+/// Round Trip gesetzt hat: `offset = server - (gesendet + empfangen) / 2`.
+/// Ohne Sendezeitpunkt zählt der Empfangszeitpunkt. Gibt `None` zurück, wenn
+/// der Server keinen Zeitstempel gesendet hat oder die Round Trip zu lang war.
+pub fn estimate_clock_offset(
+    sent_at: Option<i64>,
+    server_timestamp: i64,
+    received_at: i64,
+) -> Option<i64> {
+    if server_timestamp <= 0 {
+        return None;
+    }
+    let sent_at = sent_at.unwrap_or(received_at);
+    let round_trip = received_at - sent_at;
+    if !(0..=MAX_CLOCK_SYNC_ROUND_TRIP_MS).contains(&round_trip) {
+        return None;
+    }
+    Some(server_timestamp - (sent_at + round_trip / 2))
+}
+
+/// Übernimmt eine neue Zeitprobe in den Client-State
+fn update_clock_offset(state: &mut ClientState, sent_at: Option<i64>, server_timestamp: i64) {
+    let received_at = Utc::now().timestamp_millis();
+    if let Some(offset) = estimate_clock_offset(sent_at, server_timestamp, received_at) {
+        if state.clock_offset_ms.is_none() && offset.abs() > 1_000 {
+            tracing::warn!("Local clock differs from server by {} ms", offset);
+        }
+        state.clock_offset_ms = Some(offset);
+    }
+}
+
+/// Verbleibende Sendepause zum Zeitpunkt `now` (`None`, wenn keine aktiv ist)
+fn rate_limit_remaining(state: &ClientState, now: Instant) -> Option<Duration> {
+    state
+        .rate_limited_until
+        .map(|until| until.saturating_duration_since(now))
+        .filter(|remaining| !remaining.is_zero())
+}
+
+/// Veröffentlicht einen signierten Presence Beacon (non-blocking)
+fn publish_presence_beacon(
+    keypair: &KeyPair,
+    peer_id: &str,
+    tx: &mpsc::Sender<String>,
+    clock_offset_ms: i64,
+) {
+    let issued_at = Utc::now().timestamp_millis() + clock_offset_ms;
+    let proof = keypair.sign_presence(peer_id, issued_at);
+    let payload = PresenceBeaconPayload::new(peer_id.to_string(), issued_at, proof);
+
+    let result = sign_payload(keypair, payload, clock_offset_ms).and_then(|msg| {
+        tx.try_send(msg)
+            .map_err(|e| SignalingError::SendFailed(e.to_string()))
+    });
+    if let Err(e) = result {
+        tracing::warn!("Failed to publish presence beacon: {}", e);
+    }
+}
+
+/// Entfernt alle ausstehenden Offers an einen Peer (nach dessen Reaktion)
+fn resolve_offers(pending_requests: &PendingRequests, peer_id: &str) {
+    pending_requests.lock().retain(|_, entry| {
+        entry.request.kind != PendingRequestKind::Offer || entry.request.target != peer_id
+    });
+}
+
+/// Grund, aus dem der Write-Task endet
+#[derive(Debug, PartialEq, Eq)]
+enum WriteLoopExit {
+    /// Alle Sender wurden gedroppt (Client beendet)
+    ChannelClosed,
+    SendFailed(String),
+    /// Eine Nachricht hing länger als erlaubt im Sink
+    Stalled,
+}
+
+/// Leitet ausgehende Nachrichten an den WebSocket weiter
+///
+/// Jede Nachricht muss innerhalb von `stall_timeout` gesendet und geflusht
+/// sein, sonst gilt die Verbindung als hängend. Sind alle Sender gedroppt,
+/// wird ein Close-Frame gesendet, damit der Server die Verbindung regulär
+/// beendet statt auf den Heartbeat-Timeout zu warten.
+async fn run_write_loop<S>(
+    mut sink: S,
+    mut rx: mpsc::Receiver<String>,
+    stall_timeout: Duration,
+) -> WriteLoopExit
+where
+    S: futures::Sink<Message> + Unpin,
+    S::Error: std::fmt::Display,
+{
+    while let Some(msg) = rx.recv().await {
+        match tokio::time::timeout(stall_timeout, sink.send(Message::Text(msg))).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => return WriteLoopExit::SendFailed(e.to_string()),
+            Err(_) => return WriteLoopExit::Stalled,
+        }
+    }
+    let _ = tokio::time::timeout(stall_timeout, sink.close()).await;
+    WriteLoopExit::ChannelClosed
+}
+
+/// Beschreibt einen WebSocket Close-Code für die Anzeige
+pub fn close_code_message(code: Option<u16>) -> &'static str {
+    match code {
+        None => "Connection to the signaling server was lost",
+        Some(1000) => "Disconnected",
+        Some(1001) => "Signaling server is going away (restarting)",
+        Some(1006) => "Connection closed unexpectedly",
+        Some(1008) => "Rejected by the server (e.g. logged in elsewhere)",
+        Some(1009) => "Message too large for the server",
+        Some(1011) => "Signaling server error",
+        Some(1012) => "Signaling server is restarting",
+        Some(1013) => "Signaling server is overloaded, try again later",
+        Some(_) => "Disconnected by the server",
+    }
+}
+
+/// Gibt an, ob nach diesem Close-Code automatisch neu verbunden werden sollte
+///
+/// Bei einem regulären Schließen oder einer Richtlinienverletzung (z.B.
+/// doppelter Login) würde ein Reconnect den Zustand nur verschlimmern.
+pub fn should_reconnect(code: Option<u16>) -> bool {
+    !matches!(code, Some(1000) | Some(1008) | Some(1009))
+}
+
+/// Kürzt eine Rohnachricht für Logs und Events (an einer Zeichengrenze)
+fn truncate_raw_message(text: &str, max_len: usize) -> String {
+    if text.len() <= max_len {
+        return text.to_string();
+    }
+
+    let mut end = max_len;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}… ({} bytes)", &text[..end], text.len())
+}
+
+/// Ergänzt Timestamp und Ed25519-Signatur und serialisiert die Nachricht
+///
+/// Der Timestamp wird um die geschätzte Abweichung der Server-Uhr korrigiert,
+/// damit der Server ihn nicht wegen einer falsch gehenden lokalen Uhr ablehnt.
+fn sign_payload<T: serde::Serialize>(
+    keypair: &KeyPair,
+    payload: T,
+    clock_offset_ms: i64,
+) -> Result<String, SignalingError> {
+    // Timestamp hinzufügen (in Server-Zeit)
+    let timestamp = Utc::now().timestamp_millis() + clock_offset_ms;
+
+    // Payload als JSON für Signatur
+    let mut signable =
+        serde_json::to_value(&payload).map_err(|e| SignalingError::SendFailed(e.to_string()))?;
+
+    // Signatur erstellen
+    if let Some(obj) = signable.as_object_mut() {
+        obj.insert(
+            "timestamp".to_string(),
+            serde_json::Value::Number(timestamp.into()),
+        );
+    }
+    let signature = keypair.sign_message(&signable);
+
+    // Finale Nachricht zusammenstellen
+    let mut final_msg = signable;
+    if let Some(obj) = final_msg.as_object_mut() {
+        obj.insert(
+            "signature".to_string(),
+            serde_json::Value::String(signature),
+        );
+    }
+
+    serde_json::to_string(&final_msg).map_err(|e| SignalingError::SendFailed(e.to_string()))
+}
+
 impl std::fmt::Debug for SignalingClient {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("SignalingClient")
@@ -596,3 +1886,933 @@ impl std::fmt::Debug for SignalingClient {
             .finish()
     }
 }
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    /// Client für die lokalen Test-Server (nur `http://`)
+    fn insecure_client(url: String) -> SignalingClient {
+        let mut client = SignalingClient::new(url, Arc::new(KeyPair::generate()));
+        client.set_allow_insecure(true);
+        client
+    }
+
+    /// Startet einen Mini-Signaling-Server, der die Registrierung bestätigt
+    ///
+    /// Bei `close_after_register` wird die Verbindung direkt danach geschlossen.
+    async fn spawn_test_server(close_after_register: bool) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+
+            // Register-Nachricht abwarten
+            let _ = ws.next().await;
+
+            let registered = serde_json::json!({
+                "type": "registered",
+                "peerId": "peer-1",
+                "username": "alice",
+                "timestamp": 0
+            });
+            ws.send(Message::Text(registered.to_string()))
+                .await
+                .unwrap();
+
+            if close_after_register {
+                let _ = ws.close(None).await;
+            } else {
+                while ws.next().await.is_some() {}
+            }
+        });
+
+        format!("http://{}", addr)
+    }
+
+    #[test]
+    fn test_estimate_clock_offset() {
+        // Server-Uhr geht 10 Sekunden vor, 200ms Round Trip
+        assert_eq!(
+            estimate_clock_offset(Some(1_000_000), 1_010_100, 1_000_200),
+            Some(10_000)
+        );
+        // Lokale Uhr geht vor
+        assert_eq!(
+            estimate_clock_offset(Some(1_000_000), 940_050, 1_000_100),
+            Some(-60_000)
+        );
+        // Ohne Sendezeitpunkt zählt der Empfang
+        assert_eq!(estimate_clock_offset(None, 1_000_500, 1_000_000), Some(500));
+        // Kein Zeitstempel oder zu lange Round Trip
+        assert_eq!(estimate_clock_offset(Some(0), 0, 100), None);
+        assert_eq!(
+            estimate_clock_offset(Some(1_000_000), 1_000_000, 1_010_000),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_register_succeeds_on_open_connection() {
+        let url = spawn_test_server(false).await;
+        let mut client = insecure_client(url);
+
+        let peer_id = client
+            .connect_and_register("alice".to_string())
+            .await
+            .unwrap();
+        assert_eq!(peer_id, "peer-1");
+        assert!(client.is_connected());
+    }
+
+    #[tokio::test]
+    async fn test_missing_pongs_drop_the_connection() {
+        // Der Test-Server beantwortet keine Heartbeats
+        let url = spawn_test_server(false).await;
+        let mut client = insecure_client(url);
+        *client.heartbeat_interval.write() = Duration::from_millis(20);
+        let mut events = client.subscribe();
+
+        client
+            .connect_and_register("alice".to_string())
+            .await
+            .unwrap();
+        assert!(client.is_connected());
+
+        let disconnected = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let Ok(SignalingEvent::Disconnected { code, .. }) = events.recv().await {
+                    return code;
+                }
+            }
+        })
+        .await
+        .unwrap();
+
+        assert!(!client.is_connected());
+        assert!(should_reconnect(disconnected));
+    }
+
+    #[tokio::test]
+    async fn test_register_fails_when_connection_closes_immediately() {
+        let url = spawn_test_server(true).await;
+        let mut client = insecure_client(url);
+
+        let result = client.connect_and_register("alice".to_string()).await;
+        assert!(matches!(result, Err(SignalingError::RegistrationFailed(_))));
+        assert!(!client.is_connected());
+    }
+
+    #[tokio::test]
+    async fn test_check_username_available() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+
+            let Some(Ok(Message::Text(text))) = ws.next().await else {
+                return;
+            };
+            let request: serde_json::Value = serde_json::from_str(&text).unwrap();
+            assert_eq!(request["type"], "check_username");
+
+            let response = serde_json::json!({
+                "type": "username_availability",
+                "username": request["username"],
+                "available": request["username"] != "taken",
+                "requestId": request["requestId"],
+                "timestamp": 0
+            });
+            ws.send(Message::Text(response.to_string())).await.unwrap();
+            while ws.next().await.is_some() {}
+        });
+
+        let client = insecure_client(format!("http://{}", addr));
+        let available = client
+            .check_username_available("taken".to_string())
+            .await
+            .unwrap();
+        assert!(!available);
+        assert!(!client.is_connected());
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_carries_close_code_and_reason() {
+        use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+        use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let _ = ws.next().await;
+
+            let registered = serde_json::json!({
+                "type": "registered",
+                "peerId": "peer-1",
+                "username": "alice",
+                "timestamp": 0
+            });
+            ws.send(Message::Text(registered.to_string()))
+                .await
+                .unwrap();
+
+            // Nach der Registrierungsphase mit Grund schließen
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            let _ = ws
+                .close(Some(CloseFrame {
+                    code: CloseCode::Policy,
+                    reason: "duplicate login".into(),
+                }))
+                .await;
+        });
+
+        let mut client = insecure_client(format!("http://{}", addr));
+        let mut events = client.subscribe();
+        client
+            .connect_and_register("alice".to_string())
+            .await
+            .unwrap();
+
+        let disconnected = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let Ok(SignalingEvent::Disconnected { code, reason }) = events.recv().await {
+                    return (code, reason);
+                }
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(
+            disconnected,
+            (Some(1008), Some("duplicate login".to_string()))
+        );
+        assert!(!should_reconnect(disconnected.0));
+        assert!(should_reconnect(Some(1012)));
+        assert!(should_reconnect(None));
+    }
+
+    /// Sink, der nie bereit wird (Verbindung hängt, ohne Fehler zu melden)
+    struct StalledSink;
+
+    impl futures::Sink<Message> for StalledSink {
+        type Error = std::io::Error;
+
+        fn poll_ready(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Result<(), Self::Error>> {
+            std::task::Poll::Pending
+        }
+
+        fn start_send(self: std::pin::Pin<&mut Self>, _item: Message) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        fn poll_flush(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Result<(), Self::Error>> {
+            std::task::Poll::Pending
+        }
+
+        fn poll_close(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Result<(), Self::Error>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_write_loop_detects_stalled_sink() {
+        let (tx, rx) = mpsc::channel(4);
+        tx.send("heartbeat".to_string()).await.unwrap();
+
+        let exit = tokio::time::timeout(
+            Duration::from_secs(2),
+            run_write_loop(StalledSink, rx, Duration::from_millis(50)),
+        )
+        .await
+        .expect("write loop must give up on a stalled sink");
+        assert_eq!(exit, WriteLoopExit::Stalled);
+
+        // Ein funktionierender Sink läuft, bis alle Sender weg sind
+        let (tx, rx) = mpsc::channel(4);
+        tx.send("heartbeat".to_string()).await.unwrap();
+        drop(tx);
+        let exit = run_write_loop(futures::sink::drain(), rx, Duration::from_millis(50)).await;
+        assert_eq!(exit, WriteLoopExit::ChannelClosed);
+    }
+
+    #[tokio::test]
+    async fn test_lookup_user_by_key_resolves_current_peer_id() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let _ = ws.next().await;
+
+            let registered = serde_json::json!({
+                "type": "registered",
+                "peerId": "peer-1",
+                "username": "alice",
+                "timestamp": 0
+            });
+            ws.send(Message::Text(registered.to_string()))
+                .await
+                .unwrap();
+
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                let request: serde_json::Value = serde_json::from_str(&text).unwrap();
+                if request["type"] != "find_user_by_key" {
+                    continue;
+                }
+                let response = if request["targetPublicKey"] == "known-key" {
+                    serde_json::json!({
+                        "type": "user_found_by_key",
+                        "peerId": "peer-bob",
+                        "username": "bob-renamed",
+                        "displayName": "Bob Smith",
+                        "publicKey": request["targetPublicKey"],
+                        "isOnline": true,
+                        "requestId": request["requestId"],
+                        "timestamp": 0
+                    })
+                } else {
+                    serde_json::json!({
+                        "type": "user_not_found_by_key",
+                        "publicKey": request["targetPublicKey"],
+                        "requestId": request["requestId"],
+                        "timestamp": 0
+                    })
+                };
+                ws.send(Message::Text(response.to_string())).await.unwrap();
+            }
+        });
+
+        let mut client = insecure_client(format!("http://{}", addr));
+        client
+            .connect_and_register("alice".to_string())
+            .await
+            .unwrap();
+
+        let (_, found_rx) = client
+            .lookup_user_by_key_sync("known-key".to_string())
+            .unwrap();
+        let found = tokio::time::timeout(Duration::from_secs(5), found_rx)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.peer_id, "peer-bob");
+        assert_eq!(found.username, "bob-renamed");
+        assert_eq!(found.display_name.as_deref(), Some("Bob Smith"));
+
+        let (_, missing_rx) = client
+            .lookup_user_by_key_sync("unknown-key".to_string())
+            .unwrap();
+        let missing = tokio::time::timeout(Duration::from_secs(5), missing_rx)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(missing.is_none());
+        assert!(client.pending_requests().is_empty());
+    }
+
+    #[test]
+    fn test_pending_requests_can_be_listed_resolved_and_cancelled() {
+        let client = SignalingClient::new(
+            "http://127.0.0.1:1".to_string(),
+            Arc::new(KeyPair::generate()),
+        );
+        let (response_tx, mut response_rx) = oneshot::channel();
+        client.track_request(
+            "lookup-1".to_string(),
+            PendingRequestKind::FindUser,
+            "bob".to_string(),
+            Some(response_tx),
+        );
+        client.track_request(
+            "offer-1".to_string(),
+            PendingRequestKind::Offer,
+            "peer-bob".to_string(),
+            None,
+        );
+        assert_eq!(client.pending_requests().len(), 2);
+
+        // Eine Antwort des Peers erledigt das Offer
+        resolve_offers(&client.pending_requests, "peer-bob");
+        let pending = client.pending_requests();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].kind, PendingRequestKind::FindUser);
+
+        let cancelled = client.cancel_request("lookup-1").unwrap();
+        assert_eq!(cancelled.target, "bob");
+        assert!(client.cancel_request("lookup-1").is_none());
+        // Der wartende Aufrufer sieht einen geschlossenen Channel
+        assert!(response_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_pauses_lookups_but_not_call_signaling() {
+        let mut client = SignalingClient::new(
+            "http://127.0.0.1:1".to_string(),
+            Arc::new(KeyPair::generate()),
+        );
+        let (tx, mut outgoing) = mpsc::channel(8);
+        client.tx = Some(tx);
+        client.state.write().peer_id = Some("peer-self".to_string());
+
+        let mut events = client.subscribe();
+        let (reg_tx, _reg_rx) = mpsc::channel(1);
+        let message: ServerMessage =
+            serde_json::from_str(r#"{"type":"rate_limited","retryAfter":30,"timestamp":1}"#)
+                .unwrap();
+        SignalingClient::handle_server_message(
+            message,
+            &client.state,
+            &client.event_tx,
+            &reg_tx,
+            &client.pending_requests,
+            &client.peer_keys,
+        )
+        .await;
+
+        assert!(matches!(
+            events.try_recv(),
+            Ok(SignalingEvent::RateLimited {
+                retry_after_secs: 30
+            })
+        ));
+        assert!(matches!(
+            client.find_user_sync("bob".to_string()),
+            Err(SignalingError::RateLimited(30))
+        ));
+        assert!(client.lookup_user_sync("bob".to_string()).is_err());
+        assert!(client.pending_requests().is_empty());
+
+        // Anruf-Signaling und Heartbeats gehen weiter raus
+        client.hangup_sync("peer-bob".to_string()).unwrap();
+        client.send_heartbeat_sync().unwrap();
+        assert!(outgoing.try_recv().is_ok());
+        assert!(outgoing.try_recv().is_ok());
+        assert!(outgoing.try_recv().is_err());
+
+        // Nach Ablauf der Pause ist Suchen wieder möglich
+        client.state.write().rate_limited_until = Some(Instant::now());
+        assert!(client.rate_limit_remaining().is_none());
+        client.find_user_sync("bob".to_string()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_user_found_with_changed_key_warns() {
+        let stored = KeyPair::generate();
+        let other = KeyPair::generate();
+        let client = SignalingClient::new(
+            "http://127.0.0.1:1".to_string(),
+            Arc::new(KeyPair::generate()),
+        );
+        let stored_key = stored.public_key_base64();
+        client.set_peer_key_lookup(move |peer_id| {
+            (peer_id == "peer-bob").then(|| stored_key.clone())
+        });
+
+        let mut events = client.subscribe();
+        let (reg_tx, _reg_rx) = mpsc::channel(1);
+        for public_key in [stored.public_key_base64(), other.public_key_base64()] {
+            let message = ServerMessage::UserFound {
+                peer_id: "peer-bob".to_string(),
+                username: "bob".to_string(),
+                display_name: None,
+                is_online: true,
+                public_key: Some(public_key),
+                request_id: None,
+                timestamp: 1,
+            };
+            SignalingClient::handle_server_message(
+                message,
+                &client.state,
+                &client.event_tx,
+                &reg_tx,
+                &client.pending_requests,
+                &client.peer_keys,
+            )
+            .await;
+        }
+
+        // Gleicher Key: nur UserFound
+        assert!(matches!(
+            events.try_recv(),
+            Ok(SignalingEvent::UserFound(_))
+        ));
+        match events.try_recv() {
+            Ok(SignalingEvent::PublicKeyMismatch {
+                peer_id,
+                stored_key,
+                received_key,
+            }) => {
+                assert_eq!(peer_id, "peer-bob");
+                assert_eq!(stored_key, stored.public_key_base64());
+                assert_eq!(received_key, other.public_key_base64());
+            }
+            other => panic!("expected PublicKeyMismatch, got {:?}", other),
+        }
+        assert!(matches!(
+            events.try_recv(),
+            Ok(SignalingEvent::UserFound(_))
+        ));
+        assert!(events.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_offer_proof_binds_key_sdp_and_recipient() {
+        let mut client = SignalingClient::new(
+            "http://127.0.0.1:1".to_string(),
+            Arc::new(KeyPair::generate()),
+        );
+        let (tx, mut outgoing) = mpsc::channel(1);
+        client.tx = Some(tx);
+        client.state.write().peer_id = Some("peer-alice".to_string());
+        client
+            .send_offer_sync("peer-bob".to_string(), "v=0 offer".to_string())
+            .unwrap();
+
+        // Wie vom Server an Bob weitergeleitet
+        let sent: serde_json::Value = serde_json::from_str(&outgoing.try_recv().unwrap()).unwrap();
+        let key = sent["publicKey"].as_str();
+        let signature = sent["sdpSignature"].as_str();
+        assert_eq!(
+            verify_offer_proof("peer-bob", "peer-alice", "v=0 offer", key, signature).unwrap(),
+            client.keypair.public_key_base64()
+        );
+
+        // Verändertes SDP, anderer Empfänger oder fremder Key
+        assert!(verify_offer_proof("peer-bob", "peer-alice", "v=0 evil", key, signature).is_err());
+        assert!(verify_offer_proof("peer-eve", "peer-alice", "v=0 offer", key, signature).is_err());
+        let other = KeyPair::generate().public_key_base64();
+        assert!(verify_offer_proof(
+            "peer-bob",
+            "peer-alice",
+            "v=0 offer",
+            Some(&other),
+            signature
+        )
+        .is_err());
+
+        // Ohne Beweis nicht prüfbar
+        assert!(matches!(
+            verify_offer_proof("peer-bob", "peer-alice", "v=0 offer", None, None),
+            Err(SignalingError::UnverifiedOffer(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_relayed_messages_must_be_signed_by_known_sender() {
+        // Alice sendet einen ICE Candidate an Bob
+        let mut alice = SignalingClient::new(
+            "http://127.0.0.1:1".to_string(),
+            Arc::new(KeyPair::generate()),
+        );
+        let (tx, mut outgoing) = mpsc::channel(1);
+        alice.tx = Some(tx);
+        alice.state.write().peer_id = Some("peer-alice".to_string());
+        alice
+            .send_ice_candidates_sync("peer-bob".to_string(), vec!["candidate:1".to_string()])
+            .unwrap();
+        let sent: serde_json::Value = serde_json::from_str(&outgoing.try_recv().unwrap()).unwrap();
+
+        let bob = SignalingClient::new(
+            "http://127.0.0.1:1".to_string(),
+            Arc::new(KeyPair::generate()),
+        );
+        bob.state.write().peer_id = Some("peer-bob".to_string());
+        let alice_key = alice.keypair.public_key_base64();
+        bob.set_peer_key_lookup(move |peer_id| {
+            (peer_id == "peer-alice").then(|| alice_key.clone())
+        });
+
+        // Wie vom Server weitergeleitet, optional mit verändertem Candidate
+        let relay = |candidate: &str, signature: &serde_json::Value| {
+            let message = serde_json::json!({
+                "type": "incoming_ice_candidate",
+                "fromPeerId": "peer-alice",
+                "candidate": candidate,
+                "signature": signature,
+                "signedAt": sent["timestamp"],
+                "timestamp": 1
+            });
+            serde_json::from_value::<ServerMessage>(message).unwrap()
+        };
+        let handle = |message: ServerMessage| {
+            let (reg_tx, _reg_rx) = mpsc::channel(1);
+            let mut events = bob.subscribe();
+            let bob = &bob;
+            async move {
+                SignalingClient::handle_server_message(
+                    message,
+                    &bob.state,
+                    &bob.event_tx,
+                    &reg_tx,
+                    &bob.pending_requests,
+                    &bob.peer_keys,
+                )
+                .await;
+                events.try_recv().unwrap()
+            }
+        };
+
+        let genuine = handle(relay("candidate:1", &sent["signature"])).await;
+        assert!(matches!(
+            genuine,
+            SignalingEvent::IceCandidateReceived { ref candidate, sender_verified: true, .. }
+                if candidate == "candidate:1"
+        ));
+
+        let tampered = handle(relay("candidate:evil", &sent["signature"])).await;
+        assert!(matches!(
+            tampered,
+            SignalingEvent::Error {
+                code: INVALID_SENDER_SIGNATURE,
+                ..
+            }
+        ));
+
+        let unsigned = handle(relay("candidate:1", &serde_json::Value::Null)).await;
+        assert!(matches!(
+            unsigned,
+            SignalingEvent::Error {
+                code: INVALID_SENDER_SIGNATURE,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_stale_or_unknown_sender_is_not_verified() {
+        let mut alice = SignalingClient::new(
+            "http://127.0.0.1:1".to_string(),
+            Arc::new(KeyPair::generate()),
+        );
+        let (tx, mut outgoing) = mpsc::channel(1);
+        alice.tx = Some(tx);
+        alice.state.write().peer_id = Some("peer-alice".to_string());
+        alice
+            .send_answer_sync("peer-bob".to_string(), "v=0 answer".to_string())
+            .unwrap();
+        let sent: serde_json::Value = serde_json::from_str(&outgoing.try_recv().unwrap()).unwrap();
+        let signed_at = sent["timestamp"].as_i64().unwrap();
+        let relayed: ServerMessage = serde_json::from_value(serde_json::json!({
+            "type": "incoming_answer",
+            "fromPeerId": "peer-alice",
+            "sdp": "v=0 answer",
+            "signature": sent["signature"],
+            "signedAt": signed_at,
+            "timestamp": 1
+        }))
+        .unwrap();
+
+        // Ohne gespeicherten Key ist der Absender nur unbestätigt
+        let peer_keys = PeerKeys::default();
+        assert_eq!(
+            verify_sender(&relayed, "peer-bob", &peer_keys, signed_at),
+            Ok(SenderCheck::Unverified)
+        );
+
+        let alice_key = alice.keypair.public_key_base64();
+        *peer_keys.lookup.write() = Some(Arc::new(move |peer_id: &str| {
+            (peer_id == "peer-alice").then(|| alice_key.clone())
+        }));
+        assert_eq!(
+            verify_sender(&relayed, "peer-bob", &peer_keys, signed_at),
+            Ok(SenderCheck::Verified)
+        );
+
+        // Gültig signiert, aber außerhalb des Zeitfensters erneut zugestellt
+        let replayed_at = signed_at + MAX_SIGNATURE_AGE_MS + 1;
+        assert!(verify_sender(&relayed, "peer-bob", &peer_keys, replayed_at).is_err());
+        let predated = signed_at - MAX_SIGNATURE_AGE_MS - 1;
+        assert!(verify_sender(&relayed, "peer-bob", &peer_keys, predated).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_call_after_accepting_changed_key() {
+        use crate::database::ContactsDatabase;
+
+        // Bob hat Alices alten Key gepinnt, Alice ruft mit einem neuen Key an
+        let database = Arc::new(ContactsDatabase::open_in_memory().unwrap());
+        let old_key = KeyPair::generate().public_key_base64();
+        database.pin_identity_key("peer-alice", &old_key).unwrap();
+
+        let mut alice = SignalingClient::new(
+            "http://127.0.0.1:1".to_string(),
+            Arc::new(KeyPair::generate()),
+        );
+        let (tx, mut outgoing) = mpsc::channel(1);
+        alice.tx = Some(tx);
+        alice.state.write().peer_id = Some("peer-alice".to_string());
+        alice
+            .send_offer_sync("peer-bob".to_string(), "v=0 offer".to_string())
+            .unwrap();
+        let sent: serde_json::Value = serde_json::from_str(&outgoing.try_recv().unwrap()).unwrap();
+
+        let bob = SignalingClient::new(
+            "http://127.0.0.1:1".to_string(),
+            Arc::new(KeyPair::generate()),
+        );
+        bob.state.write().peer_id = Some("peer-bob".to_string());
+        let lookup_db = database.clone();
+        bob.set_peer_key_lookup(move |peer_id| lookup_db.get_identity_key(peer_id).ok().flatten());
+
+        let relayed = || {
+            serde_json::from_value::<ServerMessage>(serde_json::json!({
+                "type": "incoming_offer",
+                "fromPeerId": "peer-alice",
+                "fromUsername": "alice",
+                "sdp": "v=0 offer",
+                "publicKey": sent["publicKey"],
+                "sdpSignature": sent["sdpSignature"],
+                "signature": sent["signature"],
+                "signedAt": sent["timestamp"],
+                "timestamp": 1
+            }))
+            .unwrap()
+        };
+        let handle = |message: ServerMessage| {
+            let (reg_tx, _reg_rx) = mpsc::channel(1);
+            let mut events = bob.subscribe();
+            let bob = &bob;
+            async move {
+                SignalingClient::handle_server_message(
+                    message,
+                    &bob.state,
+                    &bob.event_tx,
+                    &reg_tx,
+                    &bob.pending_requests,
+                    &bob.peer_keys,
+                )
+                .await;
+                events.try_recv().unwrap()
+            }
+        };
+
+        // Vor dem Bestätigen passt die Signatur nicht zum gepinnten Key
+        assert!(matches!(
+            handle(relayed()).await,
+            SignalingEvent::Error {
+                code: INVALID_SENDER_SIGNATURE,
+                ..
+            }
+        ));
+
+        // Danach gilt der neue Key sofort
+        let new_key = alice.keypair.public_key_base64();
+        database
+            .accept_identity_key("peer-alice", &new_key)
+            .unwrap();
+        assert!(matches!(
+            handle(relayed()).await,
+            SignalingEvent::IncomingCall {
+                sender_verified: true,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_chat_message_round_trip() {
+        let mut alice = SignalingClient::new(
+            "http://127.0.0.1:1".to_string(),
+            Arc::new(KeyPair::generate()),
+        );
+        let (tx, mut outgoing) = mpsc::channel(1);
+        alice.tx = Some(tx);
+        alice.state.write().peer_id = Some("peer-alice".to_string());
+        alice
+            .send_chat_message_sync("peer-bob".to_string(), "  Bin gleich da  ".to_string())
+            .unwrap();
+        let sent: serde_json::Value = serde_json::from_str(&outgoing.try_recv().unwrap()).unwrap();
+        assert_eq!(sent["type"], "chat_message");
+        assert_eq!(sent["fromPeerId"], "peer-alice");
+        assert_eq!(sent["toPeerId"], "peer-bob");
+        assert_eq!(sent["body"], "Bin gleich da");
+
+        let bob = SignalingClient::new(
+            "http://127.0.0.1:1".to_string(),
+            Arc::new(KeyPair::generate()),
+        );
+        bob.state.write().peer_id = Some("peer-bob".to_string());
+        let alice_key = alice.keypair.public_key_base64();
+        bob.set_peer_key_lookup(move |peer_id| {
+            (peer_id == "peer-alice").then(|| alice_key.clone())
+        });
+
+        // Wie vom Server weitergeleitet, optional mit verändertem Text
+        let relay = |body: &str| {
+            let message = serde_json::json!({
+                "type": "incoming_chat_message",
+                "fromPeerId": "peer-alice",
+                "body": body,
+                "signature": sent["signature"],
+                "signedAt": sent["timestamp"],
+                "timestamp": 1
+            });
+            serde_json::from_value::<ServerMessage>(message).unwrap()
+        };
+        let handle = |message: ServerMessage| {
+            let (reg_tx, _reg_rx) = mpsc::channel(1);
+            let mut events = bob.subscribe();
+            let bob = &bob;
+            async move {
+                SignalingClient::handle_server_message(
+                    message,
+                    &bob.state,
+                    &bob.event_tx,
+                    &reg_tx,
+                    &bob.pending_requests,
+                    &bob.peer_keys,
+                )
+                .await;
+                events.try_recv().unwrap()
+            }
+        };
+
+        let genuine = handle(relay("Bin gleich da")).await;
+        assert!(matches!(
+            genuine,
+            SignalingEvent::ChatMessage { ref from_peer_id, ref body, sender_verified: true }
+                if from_peer_id == "peer-alice" && body == "Bin gleich da"
+        ));
+
+        let tampered = handle(relay("Ruf mich nicht an")).await;
+        assert!(matches!(
+            tampered,
+            SignalingEvent::Error {
+                code: INVALID_SENDER_SIGNATURE,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_chat_message_is_trimmed_and_bounded() {
+        assert!(matches!(
+            normalize_chat_message("   ".to_string()),
+            Err(SignalingError::EmptyChatMessage)
+        ));
+        assert!(matches!(
+            normalize_chat_message("ä".repeat(MAX_CHAT_MESSAGE_LENGTH + 1)),
+            Err(SignalingError::ChatMessageTooLong(_))
+        ));
+        assert_eq!(
+            normalize_chat_message(" Hallo ".to_string()).unwrap(),
+            "Hallo"
+        );
+    }
+
+    #[test]
+    fn test_websocket_url_requires_tls_by_default() {
+        assert_eq!(
+            websocket_url("https://signaling.example.com/", false).unwrap(),
+            "wss://signaling.example.com/ws"
+        );
+        // Hostnamen mit "http" bleiben unverändert
+        assert_eq!(
+            websocket_url("wss://httpbin.example.com", false).unwrap(),
+            "wss://httpbin.example.com/ws"
+        );
+
+        assert!(matches!(
+            websocket_url("http://localhost:8787", false),
+            Err(SignalingError::InsecureServerUrl(_))
+        ));
+        assert_eq!(
+            websocket_url("http://localhost:8787", true).unwrap(),
+            "ws://localhost:8787/ws"
+        );
+
+        assert!(matches!(
+            websocket_url("ftp://example.com", true),
+            Err(SignalingError::InvalidServerUrl(_))
+        ));
+        assert!(matches!(
+            websocket_url("example.com", true),
+            Err(SignalingError::InvalidServerUrl(_))
+        ));
+    }
+
+    #[test]
+    fn test_truncate_raw_message() {
+        assert_eq!(truncate_raw_message("short", 10), "short");
+
+        let truncated = truncate_raw_message("äääää", 3);
+        assert_eq!(truncated, "ä… (10 bytes)");
+    }
+
+    #[test]
+    fn test_display_name_is_trimmed_and_bounded() {
+        assert_eq!(
+            normalize_display_name(Some("  Alice Smith ".to_string())).unwrap(),
+            Some("Alice Smith".to_string())
+        );
+        assert_eq!(
+            normalize_display_name(Some("   ".to_string())).unwrap(),
+            None
+        );
+        assert_eq!(normalize_display_name(None).unwrap(), None);
+        assert!(matches!(
+            normalize_display_name(Some("ä".repeat(MAX_DISPLAY_NAME_LENGTH + 1))),
+            Err(SignalingError::DisplayNameTooLong(_))
+        ));
+        assert!(normalize_display_name(Some("ä".repeat(MAX_DISPLAY_NAME_LENGTH))).is_ok());
+
+        // Ohne Anzeigename bleibt die Registrierung wie bei älteren Clients
+        let payload = RegisterPayload::new("alice".to_string(), "key".to_string(), None);
+        let json = serde_json::to_value(&payload).unwrap();
+        assert!(json.get("displayName").is_none());
+        let payload = RegisterPayload::new(
+            "alice".to_string(),
+            "key".to_string(),
+            Some("Alice Smith".to_string()),
+        );
+        let json = serde_json::to_value(&payload).unwrap();
+        assert_eq!(json["displayName"], "Alice Smith");
+    }
+
+    #[test]
+    fn test_heartbeat_interval_bounds() {
+        let client = SignalingClient::new(
+            "http://localhost".to_string(),
+            Arc::new(KeyPair::generate()),
+        );
+        assert_eq!(client.heartbeat_interval(), DEFAULT_HEARTBEAT_INTERVAL);
+
+        client
+            .set_heartbeat_interval(Duration::from_secs(40))
+            .unwrap();
+        assert_eq!(client.diagnostics().heartbeat_interval_secs, 40);
+
+        assert!(matches!(
+            client.set_heartbeat_interval(Duration::from_secs(1)),
+            Err(SignalingError::InvalidHeartbeatInterval(1))
+        ));
+        assert!(client
+            .set_heartbeat_interval(MAX_HEARTBEAT_INTERVAL + Duration::from_secs(1))
+            .is_err());
+        assert_eq!(client.heartbeat_interval(), Duration::from_secs(40));
+    }
+}
@@ -0,0 +1,178 @@
+//! Bündeln von ICE Candidates
+//!
+//! Candidates werden kurz gesammelt und pro Peer in einer signierten
+//! Nachricht gesendet. Bei Konferenzen sammeln mehrere Peers gleichzeitig,
+//! daher gibt es einen Batch pro Ziel-Peer.
+
+use super::client::SignalingError;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::time::Instant;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Zeitfenster, in dem gesammelte ICE Candidates gebündelt werden
+const ICE_BATCH_WINDOW: Duration = Duration::from_millis(100);
+
+// ============================================================================
+// ICE CANDIDATE BATCH
+// ============================================================================
+
+/// Sammelt ICE Candidates pro Ziel-Peer bis zum nächsten `flush`
+#[derive(Debug, Default)]
+pub struct IceCandidateBatch {
+    pending: HashMap<String, Vec<String>>,
+    flush_at: Option<Instant>,
+}
+
+impl IceCandidateBatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fügt einen Candidate hinzu und startet bei Bedarf das Zeitfenster
+    pub fn push(&mut self, target_peer_id: String, candidate: String) {
+        self.pending
+            .entry(target_peer_id)
+            .or_default()
+            .push(candidate);
+        if self.flush_at.is_none() {
+            self.flush_at = Some(Instant::now() + ICE_BATCH_WINDOW);
+        }
+    }
+
+    /// Zeitpunkt des nächsten `flush` (`None`, wenn nichts wartet)
+    pub fn flush_at(&self) -> Option<Instant> {
+        self.flush_at
+    }
+
+    /// Sendet die gesammelten Candidates aller Peers über `send`
+    ///
+    /// Ist die Sende-Queue voll, bleiben die Candidates des Peers erhalten und
+    /// werden nach einem weiteren Zeitfenster erneut versucht. Bei anderen
+    /// Fehlern werden sie verworfen.
+    pub fn flush(&mut self, mut send: impl FnMut(&str, Vec<String>) -> Result<(), SignalingError>) {
+        self.pending.retain(
+            |peer_id, candidates| match send(peer_id, candidates.clone()) {
+                Ok(()) => {
+                    tracing::debug!("Sent {} ICE candidate(s)", candidates.len());
+                    false
+                }
+                Err(SignalingError::QueueFull) => {
+                    tracing::debug!("Signaling queue full, delaying ICE candidate batch");
+                    true
+                }
+                Err(e) => {
+                    tracing::error!("Failed to send ICE candidates: {}", e);
+                    false
+                }
+            },
+        );
+
+        self.flush_at = if self.pending.is_empty() {
+            None
+        } else {
+            Some(Instant::now() + ICE_BATCH_WINDOW)
+        };
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidates(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn pending_for(batch: &IceCandidateBatch, peer_id: &str) -> usize {
+        batch.pending.get(peer_id).map_or(0, Vec::len)
+    }
+
+    #[test]
+    fn test_candidates_are_batched_per_window() {
+        let mut batch = IceCandidateBatch::new();
+        assert!(batch.flush_at().is_none());
+
+        batch.push("peer-bob".to_string(), "c1".to_string());
+        let flush_at = batch.flush_at().unwrap();
+        batch.push("peer-bob".to_string(), "c2".to_string());
+        // Das Zeitfenster beginnt mit dem ersten Candidate
+        assert_eq!(batch.flush_at(), Some(flush_at));
+
+        let mut sent = Vec::new();
+        batch.flush(|peer_id, batch| {
+            sent.push((peer_id.to_string(), batch));
+            Ok(())
+        });
+        assert_eq!(
+            sent,
+            vec![("peer-bob".to_string(), candidates(&["c1", "c2"]))]
+        );
+        assert!(batch.flush_at().is_none());
+        assert_eq!(pending_for(&batch, "peer-bob"), 0);
+    }
+
+    #[test]
+    fn test_switching_peers_keeps_both_batches() {
+        let mut batch = IceCandidateBatch::new();
+        batch.push("peer-bob".to_string(), "b1".to_string());
+        batch.push("peer-carol".to_string(), "c1".to_string());
+        batch.push("peer-bob".to_string(), "b2".to_string());
+
+        let mut sent = Vec::new();
+        batch.flush(|peer_id, batch| {
+            sent.push((peer_id.to_string(), batch));
+            Ok(())
+        });
+        sent.sort();
+        assert_eq!(
+            sent,
+            vec![
+                ("peer-bob".to_string(), candidates(&["b1", "b2"])),
+                ("peer-carol".to_string(), candidates(&["c1"])),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_full_queue_retains_candidates() {
+        let mut batch = IceCandidateBatch::new();
+        batch.push("peer-bob".to_string(), "b1".to_string());
+        batch.push("peer-carol".to_string(), "c1".to_string());
+
+        // Nur Bob scheitert an der vollen Queue
+        batch.flush(|peer_id, _| match peer_id {
+            "peer-bob" => Err(SignalingError::QueueFull),
+            _ => Ok(()),
+        });
+        assert_eq!(pending_for(&batch, "peer-bob"), 1);
+        assert_eq!(pending_for(&batch, "peer-carol"), 0);
+        assert!(batch.flush_at().is_some());
+
+        // Spätere Candidates landen im erhaltenen Batch
+        batch.push("peer-bob".to_string(), "b2".to_string());
+        let mut sent = Vec::new();
+        batch.flush(|_, batch| {
+            sent.extend(batch);
+            Ok(())
+        });
+        assert_eq!(sent, candidates(&["b1", "b2"]));
+        assert!(batch.flush_at().is_none());
+    }
+
+    #[test]
+    fn test_other_errors_drop_the_batch() {
+        let mut batch = IceCandidateBatch::new();
+        batch.push("peer-bob".to_string(), "b1".to_string());
+        batch.flush(|_, _| Err(SignalingError::NotConnected));
+        assert_eq!(pending_for(&batch, "peer-bob"), 0);
+        assert!(batch.flush_at().is_none());
+    }
+}
@@ -5,6 +5,9 @@
 
 use serde::{Deserialize, Serialize};
 
+/// Version des Nachrichtenformats (für Diagnoseberichte)
+pub const PROTOCOL_VERSION: u32 = 1;
+
 // ============================================================================
 // CLIENT → SERVER MESSAGES
 // ============================================================================
@@ -26,14 +29,18 @@ pub struct RegisterPayload {
     pub username: String,
     #[serde(rename = "publicKey")]
     pub public_key: String,
+    /// Anzeigename für andere Benutzer (der Username bleibt die Adresse)
+    #[serde(rename = "displayName", skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
 }
 
 impl RegisterPayload {
-    pub fn new(username: String, public_key: String) -> Self {
+    pub fn new(username: String, public_key: String, display_name: Option<String>) -> Self {
         Self {
             msg_type: "register",
             username,
             public_key,
+            display_name,
         }
     }
 }
@@ -47,6 +54,8 @@ pub struct FindUserPayload {
     pub peer_id: String,
     #[serde(rename = "targetUsername")]
     pub target_username: String,
+    #[serde(rename = "requestId", skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
 }
 
 impl FindUserPayload {
@@ -55,11 +64,50 @@ impl FindUserPayload {
             msg_type: "find_user",
             peer_id,
             target_username,
+            request_id: None,
+        }
+    }
+
+    /// Erstellt eine Anfrage, deren Antwort über die Request-ID korreliert wird
+    pub fn with_request_id(peer_id: String, target_username: String, request_id: String) -> Self {
+        Self {
+            request_id: Some(request_id),
+            ..Self::new(peer_id, target_username)
+        }
+    }
+}
+
+/// Benutzer über seinen Public Key suchen
+///
+/// Der Public Key ist die stabile Identität, der Server löst ihn auf die
+/// aktuelle Peer-ID auf (auch nach einer Umbenennung).
+#[derive(Debug, Clone, Serialize)]
+pub struct FindUserByKeyPayload {
+    #[serde(rename = "type")]
+    pub msg_type: &'static str,
+    #[serde(rename = "peerId")]
+    pub peer_id: String,
+    #[serde(rename = "targetPublicKey")]
+    pub target_public_key: String,
+    #[serde(rename = "requestId")]
+    pub request_id: String,
+}
+
+impl FindUserByKeyPayload {
+    pub fn new(peer_id: String, target_public_key: String, request_id: String) -> Self {
+        Self {
+            msg_type: "find_user_by_key",
+            peer_id,
+            target_public_key,
+            request_id,
         }
     }
 }
 
 /// SDP Offer senden
+///
+/// `sdp_signature` ist eine eigene Signatur über Absender, Empfänger und SDP
+/// (siehe `KeyPair::sign_offer`), die der Server unverändert weiterleitet.
 #[derive(Debug, Clone, Serialize)]
 pub struct OfferPayload {
     #[serde(rename = "type")]
@@ -69,15 +117,27 @@ pub struct OfferPayload {
     #[serde(rename = "toPeerId")]
     pub to_peer_id: String,
     pub sdp: String,
+    #[serde(rename = "publicKey")]
+    pub public_key: String,
+    #[serde(rename = "sdpSignature")]
+    pub sdp_signature: String,
 }
 
 impl OfferPayload {
-    pub fn new(from_peer_id: String, to_peer_id: String, sdp: String) -> Self {
+    pub fn new(
+        from_peer_id: String,
+        to_peer_id: String,
+        sdp: String,
+        public_key: String,
+        sdp_signature: String,
+    ) -> Self {
         Self {
             msg_type: "offer",
             from_peer_id,
             to_peer_id,
             sdp,
+            public_key,
+            sdp_signature,
         }
     }
 }
@@ -128,6 +188,29 @@ impl IceCandidatePayload {
     }
 }
 
+/// Mehrere ICE Candidates gebündelt senden
+#[derive(Debug, Clone, Serialize)]
+pub struct IceCandidatesPayload {
+    #[serde(rename = "type")]
+    pub msg_type: &'static str,
+    #[serde(rename = "fromPeerId")]
+    pub from_peer_id: String,
+    #[serde(rename = "toPeerId")]
+    pub to_peer_id: String,
+    pub candidates: Vec<String>,
+}
+
+impl IceCandidatesPayload {
+    pub fn new(from_peer_id: String, to_peer_id: String, candidates: Vec<String>) -> Self {
+        Self {
+            msg_type: "ice_candidates",
+            from_peer_id,
+            to_peer_id,
+            candidates,
+        }
+    }
+}
+
 /// Anruf ablehnen
 #[derive(Debug, Clone, Serialize)]
 pub struct RejectCallPayload {
@@ -152,6 +235,50 @@ impl RejectCallPayload {
     }
 }
 
+/// Rückruf-Bitte an einen (offline) Peer, wird vom Server zwischengespeichert
+#[derive(Debug, Clone, Serialize)]
+pub struct CallbackRequestPayload {
+    #[serde(rename = "type")]
+    pub msg_type: &'static str,
+    #[serde(rename = "fromPeerId")]
+    pub from_peer_id: String,
+    #[serde(rename = "toPeerId")]
+    pub to_peer_id: String,
+}
+
+impl CallbackRequestPayload {
+    pub fn new(from_peer_id: String, to_peer_id: String) -> Self {
+        Self {
+            msg_type: "callback_request",
+            from_peer_id,
+            to_peer_id,
+        }
+    }
+}
+
+/// Textnachricht an einen Peer, wird vom Server nur weitergeleitet
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatMessagePayload {
+    #[serde(rename = "type")]
+    pub msg_type: &'static str,
+    #[serde(rename = "fromPeerId")]
+    pub from_peer_id: String,
+    #[serde(rename = "toPeerId")]
+    pub to_peer_id: String,
+    pub body: String,
+}
+
+impl ChatMessagePayload {
+    pub fn new(from_peer_id: String, to_peer_id: String, body: String) -> Self {
+        Self {
+            msg_type: "chat_message",
+            from_peer_id,
+            to_peer_id,
+            body,
+        }
+    }
+}
+
 /// Anruf beenden
 #[derive(Debug, Clone, Serialize)]
 pub struct HangupPayload {
@@ -173,6 +300,26 @@ impl HangupPayload {
     }
 }
 
+/// Verfügbarkeit eines Usernamens prüfen (vor der Registrierung, ohne Seiteneffekte)
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckUsernamePayload {
+    #[serde(rename = "type")]
+    pub msg_type: &'static str,
+    pub username: String,
+    #[serde(rename = "requestId")]
+    pub request_id: String,
+}
+
+impl CheckUsernamePayload {
+    pub fn new(username: String, request_id: String) -> Self {
+        Self {
+            msg_type: "check_username",
+            username,
+            request_id,
+        }
+    }
+}
+
 /// Heartbeat
 #[derive(Debug, Clone, Serialize)]
 pub struct HeartbeatPayload {
@@ -191,6 +338,32 @@ impl HeartbeatPayload {
     }
 }
 
+/// Signierter Presence Beacon, wird vom Server an Kontakte weitergeleitet
+///
+/// `proof` ist eine eigene Signatur über Peer-ID und `issuedAt`, die der
+/// Server beim Weiterleiten nicht erneuern kann.
+#[derive(Debug, Clone, Serialize)]
+pub struct PresenceBeaconPayload {
+    #[serde(rename = "type")]
+    pub msg_type: &'static str,
+    #[serde(rename = "peerId")]
+    pub peer_id: String,
+    #[serde(rename = "issuedAt")]
+    pub issued_at: i64,
+    pub proof: String,
+}
+
+impl PresenceBeaconPayload {
+    pub fn new(peer_id: String, issued_at: i64, proof: String) -> Self {
+        Self {
+            msg_type: "presence_beacon",
+            peer_id,
+            issued_at,
+            proof,
+        }
+    }
+}
+
 // ============================================================================
 // SERVER → CLIENT MESSAGES
 // ============================================================================
@@ -207,18 +380,65 @@ pub enum ServerMessage {
         timestamp: i64,
     },
 
+    /// Antwort auf `check_username`
+    UsernameAvailability {
+        username: String,
+        available: bool,
+        #[serde(rename = "requestId", default)]
+        request_id: Option<String>,
+        timestamp: i64,
+    },
+
     /// Benutzer gefunden
     UserFound {
         #[serde(rename = "peerId")]
         peer_id: String,
         username: String,
+        /// Anzeigename, falls bei der Registrierung angegeben
+        #[serde(rename = "displayName", default)]
+        display_name: Option<String>,
         #[serde(rename = "isOnline")]
         is_online: bool,
+        /// Public Key des Benutzers (ältere Server senden keinen)
+        #[serde(rename = "publicKey", default)]
+        public_key: Option<String>,
+        #[serde(rename = "requestId", default)]
+        request_id: Option<String>,
         timestamp: i64,
     },
 
     /// Benutzer nicht gefunden
-    UserNotFound { username: String, timestamp: i64 },
+    UserNotFound {
+        username: String,
+        #[serde(rename = "requestId", default)]
+        request_id: Option<String>,
+        timestamp: i64,
+    },
+
+    /// Antwort auf `find_user_by_key`: Public Key ist registriert
+    UserFoundByKey {
+        #[serde(rename = "peerId")]
+        peer_id: String,
+        username: String,
+        #[serde(rename = "displayName", default)]
+        display_name: Option<String>,
+        #[serde(rename = "publicKey")]
+        public_key: String,
+        #[serde(rename = "isOnline")]
+        is_online: bool,
+        #[serde(rename = "requestId")]
+        request_id: String,
+        timestamp: i64,
+    },
+
+    /// Antwort auf `find_user_by_key`: Public Key ist unbekannt
+    UserNotFoundByKey {
+        #[serde(rename = "publicKey")]
+        public_key: String,
+        #[serde(rename = "requestId")]
+        request_id: String,
+        timestamp: i64,
+    },
 
     /// Eingehendes SDP Offer
     IncomingOffer {
@@ -226,7 +446,16 @@ pub enum ServerMessage {
         from_peer_id: String,
         #[serde(rename = "fromUsername")]
         from_username: String,
+        #[serde(rename = "fromDisplayName", default)]
+        from_display_name: Option<String>,
         sdp: String,
+        /// Public Key und Offer-Signatur des Anrufers (ältere Clients senden keine)
+        #[serde(rename = "publicKey", default)]
+        public_key: Option<String>,
+        #[serde(rename = "sdpSignature", default)]
+        sdp_signature: Option<String>,
+        #[serde(flatten)]
+        sender: SenderSignature,
         timestamp: i64,
     },
 
@@ -235,6 +464,8 @@ pub enum ServerMessage {
         #[serde(rename = "fromPeerId")]
         from_peer_id: String,
         sdp: String,
+        #[serde(flatten)]
+        sender: SenderSignature,
         timestamp: i64,
     },
 
@@ -243,6 +474,18 @@ pub enum ServerMessage {
         #[serde(rename = "fromPeerId")]
         from_peer_id: String,
         candidate: String,
+        #[serde(flatten)]
+        sender: SenderSignature,
+        timestamp: i64,
+    },
+
+    /// Mehrere gebündelte ICE Candidates
+    IncomingIceCandidates {
+        #[serde(rename = "fromPeerId")]
+        from_peer_id: String,
+        candidates: Vec<String>,
+        #[serde(flatten)]
+        sender: SenderSignature,
         timestamp: i64,
     },
 
@@ -261,6 +504,33 @@ pub enum ServerMessage {
         timestamp: i64,
     },
 
+    /// Bestätigung, dass eine Rückruf-Bitte zugestellt bzw. gespeichert wurde
+    CallbackQueued {
+        #[serde(rename = "toPeerId")]
+        to_peer_id: String,
+        timestamp: i64,
+    },
+
+    /// Rückruf-Bitte eines anderen Peers (ggf. nachgeliefert beim Online-Gehen)
+    CallbackRequested {
+        #[serde(rename = "fromPeerId")]
+        from_peer_id: String,
+        #[serde(rename = "fromUsername")]
+        from_username: String,
+        /// Zeitpunkt der ursprünglichen Anfrage
+        timestamp: i64,
+    },
+
+    /// Eingehende Textnachricht
+    IncomingChatMessage {
+        #[serde(rename = "fromPeerId")]
+        from_peer_id: String,
+        body: String,
+        #[serde(flatten)]
+        sender: SenderSignature,
+        timestamp: i64,
+    },
+
     /// Benutzer ist offline gegangen
     UserOffline {
         #[serde(rename = "peerId")]
@@ -275,6 +545,24 @@ pub enum ServerMessage {
         timestamp: i64,
     },
 
+    /// Weitergeleiteter Presence Beacon eines Kontakts
+    PresenceBeacon {
+        #[serde(rename = "peerId")]
+        peer_id: String,
+        #[serde(rename = "issuedAt")]
+        issued_at: i64,
+        proof: String,
+        timestamp: i64,
+    },
+
+    /// Der Server drosselt diesen Client: nicht dringende Anfragen pausieren
+    RateLimited {
+        /// Wartezeit in Sekunden
+        #[serde(rename = "retryAfter")]
+        retry_after: u64,
+        timestamp: i64,
+    },
+
     /// Fehler
     Error {
         code: i32,
@@ -290,10 +578,29 @@ pub enum ServerMessage {
 // HELPER TYPES
 // ============================================================================
 
+/// Signatur des Absenders einer vom Server weitergeleiteten Nachricht
+///
+/// Der Server reicht `signature` und `timestamp` der ursprünglichen
+/// Client-Nachricht als `signature` und `signedAt` durch. Ältere Server
+/// senden beides nicht.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct SenderSignature {
+    #[serde(default)]
+    pub signature: Option<String>,
+    #[serde(rename = "signedAt", default)]
+    pub signed_at: Option<i64>,
+}
+
 /// Kontakt-Informationen
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ContactInfo {
     pub peer_id: String,
     pub username: String,
+    /// Anzeigename aus dem Verzeichnis (nur zur Darstellung)
+    #[serde(default)]
+    pub display_name: Option<String>,
     pub is_online: bool,
+    /// Vom Server gemeldeter Public Key (falls bekannt)
+    #[serde(default)]
+    pub public_key: Option<String>,
 }
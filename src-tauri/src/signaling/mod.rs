@@ -4,10 +4,25 @@
 //! - WebSocket-Verbindung aufbauen und halten
 //! - Nachrichten signieren und senden
 //! - Eingehende Nachrichten parsen und weiterleiten
+//! - Signierte Presence Beacons prüfen
 //!
 
 mod client;
+mod ice_batch;
 mod messages;
+mod presence;
+mod presence_batch;
+mod status_refresh;
 
-pub use client::{SignalingClient, SignalingError, SignalingEvent};
+pub use client::{
+    close_code_message, normalize_chat_message, normalize_display_name, should_reconnect,
+    validate_heartbeat_interval, verify_offer_proof, websocket_url, PendingRequest,
+    PendingRequestKind, SignalingClient, SignalingDiagnostics, SignalingError, SignalingEvent,
+    DEFAULT_HEARTBEAT_INTERVAL, INVALID_SENDER_SIGNATURE, MAX_CHAT_MESSAGE_LENGTH,
+    MAX_DISPLAY_NAME_LENGTH,
+};
+pub use ice_batch::IceCandidateBatch;
 pub use messages::*;
+pub use presence::{PresenceBeacon, PresenceError, PresenceTracker, PRESENCE_BEACON_MAX_AGE};
+pub use presence_batch::{PresenceBatch, PresenceUpdate};
+pub use status_refresh::{refresh_in_chunks, RefreshProgress};
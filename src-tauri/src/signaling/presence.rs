@@ -0,0 +1,177 @@
+//! Signierte Presence Beacons
+//!
+//! Der Online-Status kommt vom Server und ist damit nur so vertrauenswürdig
+//! wie dieser. Online-Peers veröffentlichen deshalb regelmäßig einen mit ihrer
+//! Identität signierten, zeitgestempelten Beacon, den der Server nur
+//! weiterleitet. Ein Kontakt mit bekanntem Public Key gilt erst dann als
+//! online, wenn ein frischer Beacon mit gültiger Signatur vorliegt.
+
+use crate::crypto::KeyPair;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::time::Duration;
+use thiserror::Error;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Maximales Alter eines Beacons, bevor er als veraltet gilt
+///
+/// Etwas mehr als das größte Heartbeat-Intervall, mit dem Beacons
+/// veröffentlicht werden.
+pub const PRESENCE_BEACON_MAX_AGE: Duration = Duration::from_secs(120);
+
+/// Erlaubte Abweichung in die Zukunft (Uhrenversatz zwischen Clients)
+const PRESENCE_CLOCK_SKEW: Duration = Duration::from_secs(30);
+
+// ============================================================================
+// ERROR TYPES
+// ============================================================================
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum PresenceError {
+    #[error("Presence beacon is stale ({0}s old)")]
+    Stale(i64),
+
+    #[error("Presence beacon is issued in the future")]
+    FromFuture,
+
+    #[error("Invalid presence beacon signature")]
+    InvalidSignature,
+}
+
+// ============================================================================
+// PRESENCE BEACON
+// ============================================================================
+
+/// Vom Server weitergeleiteter Presence Beacon eines Peers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PresenceBeacon {
+    pub peer_id: String,
+    /// Ausstellungszeitpunkt (Unix-Millisekunden, Uhr des Absenders)
+    pub issued_at: i64,
+    /// Ed25519-Signatur (Base64) über Peer-ID und Ausstellungszeitpunkt
+    pub proof: String,
+}
+
+impl PresenceBeacon {
+    /// Prüft Frische und Signatur gegen den bekannten Public Key des Peers
+    pub fn verify(&self, public_key_base64: &str, now_ms: i64) -> Result<(), PresenceError> {
+        let age_ms = now_ms - self.issued_at;
+        if age_ms > PRESENCE_BEACON_MAX_AGE.as_millis() as i64 {
+            return Err(PresenceError::Stale(age_ms / 1000));
+        }
+        if -age_ms > PRESENCE_CLOCK_SKEW.as_millis() as i64 {
+            return Err(PresenceError::FromFuture);
+        }
+
+        KeyPair::verify_presence(
+            public_key_base64,
+            &self.peer_id,
+            self.issued_at,
+            &self.proof,
+        )
+        .map_err(|_| PresenceError::InvalidSignature)
+    }
+}
+
+// ============================================================================
+// PRESENCE TRACKER
+// ============================================================================
+
+/// Merkt sich die zuletzt verifizierten Beacons pro Peer
+#[derive(Debug, Default)]
+pub struct PresenceTracker {
+    verified: Mutex<HashMap<String, i64>>,
+}
+
+impl PresenceTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Speichert einen verifizierten Beacon
+    pub fn record(&self, beacon: &PresenceBeacon) {
+        let mut verified = self.verified.lock();
+        let entry = verified.entry(beacon.peer_id.clone()).or_insert(i64::MIN);
+        *entry = (*entry).max(beacon.issued_at);
+    }
+
+    /// Prüft, ob für den Peer ein noch frischer, verifizierter Beacon vorliegt
+    pub fn is_verified_online(&self, peer_id: &str, now_ms: i64) -> bool {
+        self.verified.lock().get(peer_id).is_some_and(|issued_at| {
+            now_ms - issued_at <= PRESENCE_BEACON_MAX_AGE.as_millis() as i64
+        })
+    }
+
+    /// Verwirft den Beacon eines Peers (z.B. wenn er offline geht)
+    pub fn forget(&self, peer_id: &str) {
+        self.verified.lock().remove(peer_id);
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn beacon(keypair: &KeyPair, peer_id: &str, issued_at: i64) -> PresenceBeacon {
+        PresenceBeacon {
+            peer_id: peer_id.to_string(),
+            issued_at,
+            proof: keypair.sign_presence(peer_id, issued_at),
+        }
+    }
+
+    #[test]
+    fn test_fresh_beacon_verifies() {
+        let keypair = KeyPair::generate();
+        let now = 1_700_000_000_000;
+        let beacon = beacon(&keypair, "peer-a", now - 5_000);
+
+        assert_eq!(beacon.verify(&keypair.public_key_base64(), now), Ok(()));
+
+        let tracker = PresenceTracker::new();
+        tracker.record(&beacon);
+        assert!(tracker.is_verified_online("peer-a", now));
+        assert!(!tracker.is_verified_online("peer-a", now + 200_000));
+    }
+
+    #[test]
+    fn test_stale_future_and_forged_beacons_are_rejected() {
+        let keypair = KeyPair::generate();
+        let public_key = keypair.public_key_base64();
+        let now = 1_700_000_000_000;
+
+        let stale = beacon(&keypair, "peer-a", now - 121_000);
+        assert!(matches!(
+            stale.verify(&public_key, now),
+            Err(PresenceError::Stale(_))
+        ));
+
+        let future = beacon(&keypair, "peer-a", now + 60_000);
+        assert_eq!(
+            future.verify(&public_key, now),
+            Err(PresenceError::FromFuture)
+        );
+
+        // Vom Server für einen anderen Peer ausgegeben
+        let mut forged = beacon(&keypair, "peer-a", now);
+        forged.peer_id = "peer-b".to_string();
+        assert_eq!(
+            forged.verify(&public_key, now),
+            Err(PresenceError::InvalidSignature)
+        );
+
+        let other_key = KeyPair::generate().public_key_base64();
+        let valid = beacon(&keypair, "peer-a", now);
+        assert_eq!(
+            valid.verify(&other_key, now),
+            Err(PresenceError::InvalidSignature)
+        );
+    }
+}
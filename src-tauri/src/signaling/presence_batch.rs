@@ -0,0 +1,170 @@
+//! Bündeln von Presence-Änderungen
+//!
+//! Nach dem Login und beim Aktualisieren der Status kommen Dutzende
+//! Änderungen kurz hintereinander. Sie werden gesammelt und als ein
+//! `contacts:presence_updated` Event gesendet.
+
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Zeitfenster, in dem Presence-Änderungen zu einem Event gebündelt werden
+const PRESENCE_BATCH_WINDOW: Duration = Duration::from_millis(250);
+
+// ============================================================================
+// PRESENCE BATCH
+// ============================================================================
+
+/// Online-Status eines Kontakts im gebündelten Presence-Event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PresenceUpdate {
+    pub peer_id: String,
+    pub is_online: bool,
+}
+
+/// Sammelt Presence-Änderungen bis zum nächsten `flush`
+///
+/// Pro Kontakt zählt nur der letzte Status im Zeitfenster.
+///
+/// Mit `individual_events` liefert `push` zusätzlich das einzelne Event für
+/// ältere Frontends (`contact:online`/`contact:offline`). Diese werden sofort
+/// und in Eingangsreihenfolge gesendet, ohne Deduplizierung: Sie kommen also
+/// vor dem gebündelten Event desselben Zeitfensters an, und ein Kontakt kann
+/// darin mehrfach wechseln, während das Bündel nur den letzten Status enthält.
+#[derive(Debug, Default)]
+pub struct PresenceBatch {
+    updates: Vec<PresenceUpdate>,
+    flush_at: Option<Instant>,
+    /// Einzelne Events für ältere Frontends (Standard: aus)
+    individual_events: Arc<RwLock<bool>>,
+}
+
+impl PresenceBatch {
+    pub fn new(individual_events: Arc<RwLock<bool>>) -> Self {
+        Self {
+            individual_events,
+            ..Self::default()
+        }
+    }
+
+    /// Merkt sich eine Änderung und startet bei Bedarf das Zeitfenster
+    ///
+    /// Gibt den Namen des einzelnen Events zurück, falls es sofort gesendet
+    /// werden soll.
+    pub fn push(&mut self, peer_id: &str, is_online: bool) -> Option<&'static str> {
+        match self
+            .updates
+            .iter_mut()
+            .find(|update| update.peer_id == peer_id)
+        {
+            Some(update) => update.is_online = is_online,
+            None => self.updates.push(PresenceUpdate {
+                peer_id: peer_id.to_string(),
+                is_online,
+            }),
+        }
+        if self.flush_at.is_none() {
+            self.flush_at = Some(Instant::now() + PRESENCE_BATCH_WINDOW);
+        }
+
+        if !*self.individual_events.read() {
+            return None;
+        }
+        Some(if is_online {
+            "contact:online"
+        } else {
+            "contact:offline"
+        })
+    }
+
+    /// Zeitpunkt, zu dem gesendet werden soll (`None` = nichts gesammelt)
+    pub fn flush_at(&self) -> Option<Instant> {
+        self.flush_at
+    }
+
+    /// Gibt alle gesammelten Änderungen zurück und beendet das Zeitfenster
+    pub fn flush(&mut self) -> Option<Vec<PresenceUpdate>> {
+        self.flush_at = None;
+        if self.updates.is_empty() {
+            return None;
+        }
+        Some(std::mem::take(&mut self.updates))
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(peer_id: &str, is_online: bool) -> PresenceUpdate {
+        PresenceUpdate {
+            peer_id: peer_id.to_string(),
+            is_online,
+        }
+    }
+
+    #[test]
+    fn test_last_status_per_peer_wins() {
+        let mut batch = PresenceBatch::default();
+        batch.push("peer-a", true);
+        batch.push("peer-b", true);
+        batch.push("peer-a", false);
+        batch.push("peer-b", false);
+        batch.push("peer-b", true);
+
+        assert_eq!(
+            batch.flush().unwrap(),
+            vec![update("peer-a", false), update("peer-b", true)]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_one_flush_per_window() {
+        let mut batch = PresenceBatch::default();
+        assert_eq!(batch.flush_at(), None);
+
+        // Weitere Änderungen verlängern das Zeitfenster nicht
+        let start = Instant::now();
+        batch.push("peer-a", true);
+        tokio::time::sleep(PRESENCE_BATCH_WINDOW / 2).await;
+        batch.push("peer-b", true);
+        assert_eq!(batch.flush_at(), Some(start + PRESENCE_BATCH_WINDOW));
+
+        assert_eq!(batch.flush().unwrap().len(), 2);
+        assert_eq!(batch.flush_at(), None);
+        assert_eq!(batch.flush(), None);
+
+        // Die nächste Änderung startet ein neues Zeitfenster
+        batch.push("peer-a", false);
+        assert_eq!(
+            batch.flush_at(),
+            Some(Instant::now() + PRESENCE_BATCH_WINDOW)
+        );
+    }
+
+    #[test]
+    fn test_individual_events_are_sent_immediately() {
+        let flag = Arc::new(RwLock::new(false));
+        let mut batch = PresenceBatch::new(flag.clone());
+        assert_eq!(batch.push("peer-a", true), None);
+
+        // Jede Änderung einzeln, ohne Deduplizierung
+        *flag.write() = true;
+        assert_eq!(batch.push("peer-a", false), Some("contact:offline"));
+        assert_eq!(batch.push("peer-a", true), Some("contact:online"));
+        assert_eq!(batch.push("peer-a", true), Some("contact:online"));
+
+        // Das Bündel enthält trotzdem nur den letzten Status
+        assert_eq!(batch.flush().unwrap(), vec![update("peer-a", true)]);
+    }
+}
@@ -0,0 +1,166 @@
+//! Aktualisieren der Online-Status aller Kontakte
+//!
+//! Die Abfragen werden in Blöcken mit kurzer Pause gesendet, damit große
+//! Kontaktlisten weder den Server fluten noch die Sende-Queue überlaufen
+//! lassen.
+
+use serde::Serialize;
+use std::time::Duration;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Anzahl Status-Abfragen pro Block beim Aktualisieren aller Kontakte
+const STATUS_REFRESH_CHUNK_SIZE: usize = 20;
+
+/// Pause zwischen zwei Blöcken (schont Rate Limits und die Sende-Queue)
+const STATUS_REFRESH_CHUNK_DELAY: Duration = Duration::from_millis(250);
+
+// ============================================================================
+// STATUS REFRESH
+// ============================================================================
+
+/// Fortschritt nach einem Block
+///
+/// Der letzte Fortschritt hat `sent == total` oder, wenn abgebrochen wurde,
+/// einen `error`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RefreshProgress {
+    pub sent: usize,
+    pub total: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Sendet die Abfragen für `items` blockweise über `send_chunk`
+///
+/// Nach jedem Block wird der Fortschritt an `progress` gemeldet, auch bei
+/// leerer Liste einmal. Schlägt ein Block fehl, wird abgebrochen und der
+/// Fehler als letzter Fortschritt gemeldet.
+pub async fn refresh_in_chunks<T>(
+    items: &[T],
+    mut send_chunk: impl FnMut(&[T]) -> Result<(), String>,
+    mut progress: impl FnMut(RefreshProgress),
+) -> Result<(), String> {
+    let total = items.len();
+    let mut sent = 0;
+
+    for (index, chunk) in items.chunks(STATUS_REFRESH_CHUNK_SIZE).enumerate() {
+        if index > 0 {
+            tokio::time::sleep(STATUS_REFRESH_CHUNK_DELAY).await;
+        }
+
+        if let Err(e) = send_chunk(chunk) {
+            progress(RefreshProgress {
+                sent,
+                total,
+                error: Some(e.clone()),
+            });
+            return Err(e);
+        }
+
+        sent += chunk.len();
+        progress(RefreshProgress {
+            sent,
+            total,
+            error: None,
+        });
+    }
+
+    if total == 0 {
+        progress(RefreshProgress {
+            sent,
+            total,
+            error: None,
+        });
+    }
+    Ok(())
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time::Instant;
+
+    fn progress(sent: usize, total: usize) -> RefreshProgress {
+        RefreshProgress {
+            sent,
+            total,
+            error: None,
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_requests_are_sent_in_chunks() {
+        let contacts: Vec<usize> = (0..45).collect();
+        let mut chunks = Vec::new();
+        let mut events = Vec::new();
+
+        let start = Instant::now();
+        refresh_in_chunks(
+            &contacts,
+            |chunk| {
+                chunks.push(chunk.len());
+                Ok(())
+            },
+            |p| events.push(p),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(chunks, vec![20, 20, 5]);
+        assert_eq!(
+            events,
+            vec![progress(20, 45), progress(40, 45), progress(45, 45)]
+        );
+        // Pause nur zwischen den Blöcken
+        assert_eq!(start.elapsed(), STATUS_REFRESH_CHUNK_DELAY * 2);
+    }
+
+    #[tokio::test]
+    async fn test_empty_list_reports_completion() {
+        let mut events = Vec::new();
+        refresh_in_chunks::<usize>(&[], |_| Ok(()), |p| events.push(p))
+            .await
+            .unwrap();
+        assert_eq!(events, vec![progress(0, 0)]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_failure_ends_with_error_progress() {
+        let contacts: Vec<usize> = (0..45).collect();
+        let mut calls = 0;
+        let mut events = Vec::new();
+
+        let result = refresh_in_chunks(
+            &contacts,
+            |_| {
+                calls += 1;
+                if calls == 2 {
+                    return Err("Not connected".to_string());
+                }
+                Ok(())
+            },
+            |p| events.push(p),
+        )
+        .await;
+
+        assert_eq!(result, Err("Not connected".to_string()));
+        assert_eq!(
+            events,
+            vec![
+                progress(20, 45),
+                RefreshProgress {
+                    sent: 20,
+                    total: 45,
+                    error: Some("Not connected".to_string()),
+                }
+            ]
+        );
+    }
+}
@@ -0,0 +1,418 @@
+//! Webhooks für Integrationen
+//!
+//! Meldet ausgewählte Presence- und Anruf-Events per HTTP POST an eine lokale
+//! URL, damit externe Tools (Hausautomation, Skripte) darauf reagieren können.
+//! Ist kein Webhook gesetzt, wird nichts gesendet.
+//!
+//! Standardmäßig sind nur Ziele auf diesem Rechner oder im lokalen Netz
+//! erlaubt. Gesendet wird ausschließlich über unverschlüsseltes HTTP, daher
+//! muss ein entferntes Ziel ausdrücklich freigegeben werden.
+//!
+//! Payload: `{"event": "contact_online", "timestamp": <Unix-ms>, "data": {...}}`
+
+use chrono::Utc;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use url::{Host, Url};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Maximale Anzahl Zustellversuche pro Event
+const MAX_DELIVERY_ATTEMPTS: u32 = 4;
+
+/// Wartezeit vor dem ersten erneuten Versuch (verdoppelt sich danach)
+const INITIAL_RETRY_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Timeout für Verbindungsaufbau und Antwort eines Versuchs
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(5);
+
+// ============================================================================
+// ERROR TYPES
+// ============================================================================
+
+#[derive(Error, Debug)]
+pub enum WebhookError {
+    #[error("Invalid webhook URL: {0}")]
+    InvalidUrl(String),
+
+    #[error("Unsupported webhook scheme: {0} (only http is supported)")]
+    UnsupportedScheme(String),
+
+    #[error("Webhook host {0} is not on this machine or the local network")]
+    RemoteHost(String),
+
+    #[error("No events selected")]
+    NoEvents,
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Webhook timed out")]
+    Timeout,
+
+    #[error("Webhook responded with status {0}")]
+    Status(u16),
+
+    #[error("Invalid HTTP response")]
+    InvalidResponse,
+}
+
+impl WebhookError {
+    /// Ob ein weiterer Versuch sinnvoll ist (Netzwerkfehler oder Serverfehler)
+    fn is_retryable(&self) -> bool {
+        match self {
+            WebhookError::Io(_) | WebhookError::Timeout | WebhookError::InvalidResponse => true,
+            WebhookError::Status(status) => *status >= 500 || *status == 408 || *status == 429,
+            WebhookError::InvalidUrl(_)
+            | WebhookError::UnsupportedScheme(_)
+            | WebhookError::RemoteHost(_)
+            | WebhookError::NoEvents => false,
+        }
+    }
+}
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// Events, die an den Webhook gemeldet werden können
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    /// Ein Kontakt ist online gekommen
+    ContactOnline,
+    /// Ein Kontakt ist offline gegangen
+    ContactOffline,
+    /// Ein Anruf klingelt
+    IncomingCall,
+    /// Ein Anruf wurde verbunden
+    CallConnected,
+    /// Ein verbundener Anruf wurde beendet
+    CallEnded,
+}
+
+/// Aktiver Webhook
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WebhookConfig {
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+}
+
+/// Versendet Events an den konfigurierten Webhook
+#[derive(Default)]
+pub struct Webhooks {
+    config: RwLock<Option<(Url, Vec<WebhookEvent>)>>,
+}
+
+impl Webhooks {
+    /// Erstellt den Dispatcher ohne Webhook (sendet nichts)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Setzt den Webhook und die zu meldenden Events
+    ///
+    /// Ohne `allow_remote` muss das Ziel auf diesem Rechner oder im lokalen
+    /// Netz liegen (Loopback, private Adressen, `localhost` oder `*.local`).
+    pub fn set(
+        &self,
+        url: &str,
+        events: Vec<WebhookEvent>,
+        allow_remote: bool,
+    ) -> Result<(), WebhookError> {
+        if events.is_empty() {
+            return Err(WebhookError::NoEvents);
+        }
+        let url = validate_webhook_url(url, allow_remote)?;
+
+        tracing::info!("Webhook set for {:?}", events);
+        *self.config.write() = Some((url, events));
+        Ok(())
+    }
+
+    /// Entfernt den Webhook
+    pub fn clear(&self) {
+        if self.config.write().take().is_some() {
+            tracing::info!("Webhook removed");
+        }
+    }
+
+    /// Gibt den aktiven Webhook zurück
+    pub fn config(&self) -> Option<WebhookConfig> {
+        self.config
+            .read()
+            .as_ref()
+            .map(|(url, events)| WebhookConfig {
+                url: url.to_string(),
+                events: events.clone(),
+            })
+    }
+
+    /// Meldet ein Event im Hintergrund (ohne Wirkung, wenn nicht ausgewählt)
+    pub fn notify(&self, event: WebhookEvent, data: serde_json::Value) {
+        let url = match self.config.read().as_ref() {
+            Some((url, events)) if events.contains(&event) => url.clone(),
+            _ => return,
+        };
+
+        let body = serde_json::json!({
+            "event": event,
+            "timestamp": Utc::now().timestamp_millis(),
+            "data": data,
+        })
+        .to_string();
+
+        tokio::spawn(async move {
+            if let Err(e) = deliver(&url, &body, INITIAL_RETRY_BACKOFF).await {
+                tracing::warn!("Failed to deliver webhook {:?}: {}", event, e);
+            }
+        });
+    }
+}
+
+/// Prüft eine Webhook-URL (nur HTTP, ohne `allow_remote` nur lokale Ziele)
+pub fn validate_webhook_url(url: &str, allow_remote: bool) -> Result<Url, WebhookError> {
+    let url = Url::parse(url).map_err(|e| WebhookError::InvalidUrl(e.to_string()))?;
+    if url.scheme() != "http" {
+        return Err(WebhookError::UnsupportedScheme(url.scheme().to_string()));
+    }
+
+    let host = url
+        .host()
+        .ok_or_else(|| WebhookError::InvalidUrl("missing host".to_string()))?;
+    let is_local = match &host {
+        Host::Domain(domain) => {
+            let domain = domain.to_ascii_lowercase();
+            domain == "localhost" || domain.ends_with(".local")
+        }
+        Host::Ipv4(ip) => is_local_ipv4(ip),
+        Host::Ipv6(ip) => is_local_ipv6(ip),
+    };
+    if !is_local && !allow_remote {
+        return Err(WebhookError::RemoteHost(host.to_string()));
+    }
+    Ok(url)
+}
+
+/// Loopback, private Netze und Link-Local
+fn is_local_ipv4(ip: &Ipv4Addr) -> bool {
+    ip.is_loopback() || ip.is_private() || ip.is_link_local()
+}
+
+/// Loopback, Unique Local (fc00::/7) und Link-Local (fe80::/10)
+fn is_local_ipv6(ip: &Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    ip.is_loopback() || (first & 0xfe00) == 0xfc00 || (first & 0xffc0) == 0xfe80
+}
+
+// ============================================================================
+// DELIVERY
+// ============================================================================
+
+/// Stellt ein Event zu, bei Netzwerk- und Serverfehlern mit Backoff
+async fn deliver(url: &Url, body: &str, initial_backoff: Duration) -> Result<(), WebhookError> {
+    let mut backoff = initial_backoff;
+    let mut attempt = 1;
+    loop {
+        let result = tokio::time::timeout(DELIVERY_TIMEOUT, post_json(url, body))
+            .await
+            .unwrap_or(Err(WebhookError::Timeout));
+
+        match result {
+            Ok(()) => return Ok(()),
+            Err(e) if e.is_retryable() && attempt < MAX_DELIVERY_ATTEMPTS => {
+                tracing::debug!(
+                    "Webhook attempt {} failed ({}), retrying in {:?}",
+                    attempt,
+                    e,
+                    backoff
+                );
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Sendet einen einzelnen HTTP/1.1 POST und prüft den Status der Antwort
+async fn post_json(url: &Url, body: &str) -> Result<(), WebhookError> {
+    let host = url
+        .host_str()
+        .ok_or_else(|| WebhookError::InvalidUrl("missing host".to_string()))?;
+    let port = url.port_or_known_default().unwrap_or(80);
+    let path = match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    };
+
+    // IPv6-Adressen stehen in `host_str` bereits in Klammern
+    let mut stream = TcpStream::connect((host.trim_matches(['[', ']']), port)).await?;
+    let request = format!(
+        "POST {} HTTP/1.1\r\n\
+         Host: {}:{}\r\n\
+         User-Agent: Pulse/{}\r\n\
+         Content-Type: application/json\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\
+         \r\n\
+         {}",
+        path,
+        host,
+        port,
+        env!("CARGO_PKG_VERSION"),
+        body.len(),
+        body
+    );
+    stream.write_all(request.as_bytes()).await?;
+
+    // Nur die Statuszeile ist relevant
+    let mut response = Vec::new();
+    let mut chunk = [0u8; 256];
+    while !response.contains(&b'\n') {
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            break;
+        }
+        response.extend_from_slice(&chunk[..read]);
+    }
+
+    let status = parse_status_line(&response).ok_or(WebhookError::InvalidResponse)?;
+    if (200..300).contains(&status) {
+        Ok(())
+    } else {
+        Err(WebhookError::Status(status))
+    }
+}
+
+/// Liest den Statuscode aus `HTTP/1.1 204 No Content`
+fn parse_status_line(response: &[u8]) -> Option<u16> {
+    let line = std::str::from_utf8(response).ok()?.lines().next()?;
+    let mut parts = line.split_whitespace();
+    if !parts.next()?.starts_with("HTTP/") {
+        return None;
+    }
+    parts.next()?.parse().ok()
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_webhook_url_must_be_local_by_default() {
+        for url in [
+            "http://localhost:8123/hook",
+            "http://127.0.0.1:9000/",
+            "http://192.168.1.20/api/pulse",
+            "http://10.0.0.5:8080",
+            "http://homeassistant.local:8123/api/webhook/pulse",
+            "http://[::1]:9000/",
+            "http://[fd12:3456::1]/hook",
+        ] {
+            assert!(validate_webhook_url(url, false).is_ok(), "{}", url);
+        }
+
+        assert!(matches!(
+            validate_webhook_url("http://example.com/hook", false),
+            Err(WebhookError::RemoteHost(_))
+        ));
+        assert!(matches!(
+            validate_webhook_url("http://8.8.8.8/hook", false),
+            Err(WebhookError::RemoteHost(_))
+        ));
+        assert!(validate_webhook_url("http://example.com/hook", true).is_ok());
+        assert!(matches!(
+            validate_webhook_url("https://localhost/hook", false),
+            Err(WebhookError::UnsupportedScheme(_))
+        ));
+        assert!(matches!(
+            validate_webhook_url("not a url", false),
+            Err(WebhookError::InvalidUrl(_))
+        ));
+    }
+
+    #[test]
+    fn test_only_selected_events_are_configured() {
+        let webhooks = Webhooks::new();
+        assert_eq!(webhooks.config(), None);
+        assert!(matches!(
+            webhooks.set("http://localhost:9000/", Vec::new(), false),
+            Err(WebhookError::NoEvents)
+        ));
+
+        webhooks
+            .set(
+                "http://localhost:9000/hook",
+                vec![WebhookEvent::ContactOnline],
+                false,
+            )
+            .unwrap();
+        assert_eq!(
+            webhooks.config(),
+            Some(WebhookConfig {
+                url: "http://localhost:9000/hook".to_string(),
+                events: vec![WebhookEvent::ContactOnline],
+            })
+        );
+
+        webhooks.clear();
+        assert_eq!(webhooks.config(), None);
+    }
+
+    #[tokio::test]
+    async fn test_delivery_retries_after_server_error() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!(
+            "http://{}/hook?source=pulse",
+            listener.local_addr().unwrap()
+        ))
+        .unwrap();
+
+        let server = tokio::spawn(async move {
+            let mut requests = Vec::new();
+            for status in ["500 Internal Server Error", "204 No Content"] {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = vec![0u8; 1024];
+                let read = socket.read(&mut request).await.unwrap();
+                requests.push(String::from_utf8_lossy(&request[..read]).to_string());
+                let response = format!("HTTP/1.1 {}\r\nContent-Length: 0\r\n\r\n", status);
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+            requests
+        });
+
+        let body = r#"{"event":"contact_online"}"#;
+        deliver(&url, body, Duration::from_millis(10))
+            .await
+            .unwrap();
+
+        let requests = server.await.unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1].starts_with("POST /hook?source=pulse HTTP/1.1\r\n"));
+        assert!(requests[1].contains("Content-Type: application/json\r\n"));
+        assert!(requests[1].ends_with(body));
+    }
+
+    #[test]
+    fn test_client_errors_are_not_retried() {
+        assert!(!WebhookError::Status(404).is_retryable());
+        assert!(WebhookError::Status(503).is_retryable());
+        assert!(WebhookError::Timeout.is_retryable());
+        assert_eq!(parse_status_line(b"HTTP/1.1 204 No Content\r\n"), Some(204));
+        assert_eq!(parse_status_line(b"garbage"), None);
+    }
+}
@@ -166,6 +166,7 @@ export function createAddContactModal(callbacks: AddContactModalCallbacks): HTML
       const contact = await api.addContact({
         peer_id: user.peer_id,
         username: user.username,
+        public_key: user.public_key,
       });
       callbacks.onContactAdded(contact);
       close();
@@ -109,7 +109,7 @@ export function createCallScreen(
     statusEl.textContent = state.toUpperCase();
     
     // Convert to Visualizer State
-    if (state === 'connecting' || state === 'calling' || state === 'ringing') {
+    if (state === 'connecting' || state === 'calling' || state === 'ringing' || state === 'reconnecting') {
         visualizer.setState("connecting");
     } else if (state === 'connected') {
         visualizer.setState("speaking"); // We assume speaking mode once connected, and modulate with volume
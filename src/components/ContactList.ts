@@ -175,7 +175,7 @@ function createContactItem(contact: Contact): HTMLElement {
        ${contact.is_online ? '<div class="status-dot"></div>' : ''}
     </div>
     <div class="contact-info" style="pointer-events: none;">
-       <div class="contact-name">${escapeHtml(displayName)}</div>
+       <div class="contact-name">${escapeHtml(displayName)}${contact.is_verified ? '<span class="verified-badge" title="Verified">&#10003;</span>' : ''}</div>
        <div class="contact-status-text">${contact.is_online ? 'Available' : 'Offline'}</div>
     </div>
     ${contact.is_online ? `
@@ -20,6 +20,20 @@ function stopReconnect() {
   }
 }
 
+// Wegen Inaktivität getrennt, der nächste Command verbindet neu
+let idleDisconnected = false;
+
+// Eingaben höchstens alle 30s melden
+const ACTIVITY_REPORT_INTERVAL_MS = 30_000;
+let lastActivityReport = 0;
+
+function reportActivity() {
+  const now = Date.now();
+  if (now - lastActivityReport < ACTIVITY_REPORT_INTERVAL_MS) return;
+  lastActivityReport = now;
+  api.reportActivity().catch(() => {});
+}
+
 // ============================================================================
 // APP STATE
 // ============================================================================
@@ -363,19 +377,17 @@ function setupEventListeners() {
     }
   });
   
-  // Contact online/offline
-  api.onContactOnline((peerId) => {
-    const contact = state.contacts.find(c => c.peer_id === peerId);
-    if (contact) {
-      contact.is_online = true;
-      renderContactList();
+  // Contact online/offline (gebündelt, ein Render pro Batch)
+  api.onPresenceUpdated((updates) => {
+    let changed = false;
+    for (const update of updates) {
+      const contact = state.contacts.find(c => c.peer_id === update.peer_id);
+      if (contact && contact.is_online !== update.is_online) {
+        contact.is_online = update.is_online;
+        changed = true;
+      }
     }
-  });
-  
-  api.onContactOffline((peerId) => {
-    const contact = state.contacts.find(c => c.peer_id === peerId);
-    if (contact) {
-      contact.is_online = false;
+    if (changed) {
       renderContactList();
     }
   });
@@ -398,8 +410,11 @@ function setupEventListeners() {
   });
   
   // Signaling disconnected
-  api.onSignalingDisconnected(() => {
-    console.warn('Disconnected from signaling server');
+  api.onSignalingDisconnected((event) => {
+    console.warn('Disconnected from signaling server:', event.message, event.code, event.reason);
+
+    // Gewollte Trennung wegen Inaktivität, Status bleibt "Idle"
+    if (idleDisconnected) return;
     
     // Update UI
     const statusDot = document.querySelector('.user-profile-dock .status-dot') as HTMLElement | null;
@@ -407,12 +422,12 @@ function setupEventListeners() {
     
     if (statusDot) statusDot.style.background = 'var(--color-busy)'; // Red
     if (userIdText) {
-        userIdText.textContent = 'Reconnecting...';
+        userIdText.textContent = event.should_reconnect ? 'Reconnecting...' : event.message;
         userIdText.parentElement!.style.color = 'var(--color-busy)';
     }
 
-    // Start auto-reconnect
-    if (state.username && !reconnectInterval) {
+    // Start auto-reconnect (nicht z.B. nach einem Login an anderer Stelle)
+    if (event.should_reconnect && state.username && !reconnectInterval) {
       reconnectInterval = window.setInterval(async () => {
         if (!state.username) {
           stopReconnect();
@@ -439,6 +454,34 @@ function setupEventListeners() {
       }, 3000);
     }
   });
+
+  // Idle disconnect: kein Auto-Reconnect, Status als "Idle" anzeigen
+  api.onIdleDisconnected(() => {
+    idleDisconnected = true;
+    const userIdText = document.querySelector('.user-id-display span') as HTMLElement | null;
+    if (userIdText) {
+      userIdText.textContent = 'Idle';
+      userIdText.parentElement!.style.color = '';
+    }
+  });
+
+  // Nach dem Wiederverbinden (z.B. nach Idle) kann sich die Peer-ID ändern
+  api.onRegistered((event) => {
+    idleDisconnected = false;
+    state.peerId = event.peerId;
+
+    const statusDot = document.querySelector('.user-profile-dock .status-dot') as HTMLElement | null;
+    const userIdText = document.querySelector('.user-id-display span') as HTMLElement | null;
+    if (statusDot) statusDot.style.background = 'var(--color-online)';
+    if (userIdText) {
+      userIdText.textContent = state.username ? '@' + state.username : '...';
+      userIdText.parentElement!.style.color = '';
+    }
+  });
+
+  // UI-Eingaben halten die Signaling-Verbindung aktiv
+  window.addEventListener('pointerdown', reportActivity);
+  window.addEventListener('keydown', reportActivity);
 }
 
 // ============================================================================
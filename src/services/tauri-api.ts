@@ -4,13 +4,59 @@ import { invoke } from '@tauri-apps/api/core';
 import { listen, UnlistenFn } from '@tauri-apps/api/event';
 import type { 
   Contact, 
+  ContactSort,
   NewContact, 
   UserFoundEvent, 
   IncomingCallEvent,
   RegisteredEvent,
   SignalingErrorEvent,
+  RateLimitedEvent,
+  DisconnectedEvent,
+  UnknownMessageEvent,
+  IdentityVerifiedEvent,
+  IceCandidateLogEvent,
+  OneWayAudioEvent,
+  OfferRejectedEvent,
+  EchoDetectedEvent,
+  PeerStateChangedEvent,
+  MicrophonePermission,
+  SoundEffect,
+  RingtoneStyle,
+  WebhookEvent,
+  WebhookConfig,
+  PermissionRequiredEvent,
+  PresenceInvalidEvent,
+  CallbackRequest,
   CallRejectedEvent,
-  CallState 
+  CallState,
+  CallStateInfo,
+  IceTransportPolicy,
+  DtlsFingerprints,
+  LanPeer,
+  ConnectionDiagnostics,
+  PendingRequest,
+  DefaultDeviceChangedEvent,
+  SecurityInfo,
+  ContactCard,
+  GlareResolvedEvent,
+  EventLogEntry,
+  ContactRefreshProgressEvent,
+  UsageStats,
+  ImportedContact,
+  ConflictPolicy,
+  ImportReport,
+  ContactKeyChangedEvent,
+  OutputLimiterConfig,
+  MissedCall,
+  IceServerHealth,
+  CallRetryingEvent,
+  CallReconnectingEvent,
+  CallDurationEvent,
+  ConnectionStats,
+  PresenceUpdate,
+  Recording,
+  ConnectivityReport,
+  ChatMessage
 } from '../types';
 
 // ============================================================================
@@ -21,6 +67,29 @@ export async function getPublicKey(): Promise<string> {
   return await invoke('get_public_key');
 }
 
+/** Eigene Sicherheitsnummer (12 Gruppen zu 5 Ziffern) */
+export async function getMyFingerprint(): Promise<string> {
+  return await invoke('get_my_fingerprint');
+}
+
+export async function isNewIdentity(): Promise<boolean> {
+  return await invoke('is_new_identity');
+}
+
+export async function isIdentityLocked(): Promise<boolean> {
+  return await invoke('is_identity_locked');
+}
+
+/** Entsperrt die verschlüsselte Identität und gibt den Public Key zurück */
+export async function unlockIdentity(passphrase: string): Promise<string> {
+  return await invoke('unlock_identity', { passphrase });
+}
+
+/** Verschlüsselt den Private Key mit einer Passphrase (null entfernt sie) */
+export async function setKeyPassphrase(passphrase: string | null): Promise<void> {
+  return await invoke('set_key_passphrase', { passphrase });
+}
+
 export async function getPeerId(): Promise<string | null> {
   return await invoke('get_peer_id');
 }
@@ -29,12 +98,28 @@ export async function getUsername(): Promise<string | null> {
   return await invoke('get_username');
 }
 
+export async function getMyContactCard(): Promise<string> {
+  return await invoke('get_my_contact_card');
+}
+
+export async function parseContactCard(card: string): Promise<ContactCard> {
+  return await invoke('parse_contact_card', { card });
+}
+
 // ============================================================================
 // SIGNALING
 // ============================================================================
 
-export async function connectAndRegister(username: string): Promise<string> {
-  return await invoke('connect_and_register', { username });
+/** Der Anzeigename wird anderen statt des Usernames gezeigt (max. 64 Zeichen) */
+export async function connectAndRegister(
+  username: string,
+  displayName: string | null = null
+): Promise<string> {
+  return await invoke('connect_and_register', { username, displayName });
+}
+
+export async function checkUsernameAvailable(username: string): Promise<boolean> {
+  return await invoke('check_username_available', { username });
 }
 
 export async function disconnect(): Promise<void> {
@@ -45,6 +130,41 @@ export async function findUser(username: string): Promise<void> {
   return await invoke('find_user', { username });
 }
 
+export async function findUserByKey(publicKey: string): Promise<UserFoundEvent> {
+  return await invoke('find_user_by_key', { publicKey });
+}
+
+export async function setHeartbeatInterval(seconds: number): Promise<void> {
+  return await invoke('set_heartbeat_interval', { seconds });
+}
+
+/** Trennt das Signaling nach `seconds` ohne Aktivität (`null` = nie, min. 60) */
+export async function setIdleTimeout(seconds: number | null): Promise<void> {
+  return await invoke('set_idle_timeout', { seconds });
+}
+
+export async function getIdleTimeout(): Promise<number | null> {
+  return await invoke('get_idle_timeout');
+}
+
+/** Meldet eine Eingabe in der UI (setzt den Leerlauf-Timer zurück) */
+export async function reportActivity(): Promise<void> {
+  return await invoke('report_activity');
+}
+
+export async function getConnectionDiagnostics(): Promise<ConnectionDiagnostics> {
+  return await invoke('get_connection_diagnostics');
+}
+
+export async function getPendingRequests(): Promise<PendingRequest[]> {
+  return await invoke('get_pending_requests');
+}
+
+/** Bricht eine ausstehende Anfrage ab (bei einem Offer auch den Anrufversuch) */
+export async function cancelRequest(requestId: string): Promise<boolean> {
+  return await invoke('cancel_request', { requestId });
+}
+
 // ============================================================================
 // CONTACTS
 // ============================================================================
@@ -53,11 +173,22 @@ export async function getContacts(): Promise<Contact[]> {
   return await invoke('get_contacts');
 }
 
+/** Kontakte sortiert, bei `last_called` landen nie angerufene am Ende */
+export async function getContactsSorted(sort: ContactSort): Promise<Contact[]> {
+  return await invoke('get_contacts_sorted', { sort });
+}
+
+/** Kontakte nach Teil von Username oder Anzeigename, online zuerst */
+export async function searchContacts(query: string): Promise<Contact[]> {
+  return await invoke('search_contacts', { query });
+}
+
 export async function addContact(contact: NewContact): Promise<Contact> {
   return await invoke('add_contact', { 
     peerId: contact.peer_id, 
     username: contact.username,
-    displayName: contact.display_name 
+    displayName: contact.display_name,
+    publicKey: contact.public_key
   });
 }
 
@@ -65,14 +196,153 @@ export async function deleteContact(peerId: string): Promise<void> {
   return await invoke('delete_contact', { peerId });
 }
 
+export async function getDeletedContacts(): Promise<Contact[]> {
+  return await invoke('get_deleted_contacts');
+}
+
+export async function restoreContact(peerId: string): Promise<Contact> {
+  return await invoke('restore_contact', { peerId });
+}
+
+export async function purgeDeletedContacts(): Promise<number> {
+  return await invoke('purge_deleted_contacts');
+}
+
 export async function updateContactName(peerId: string, displayName: string | null): Promise<void> {
   return await invoke('update_contact_name', { peerId, displayName });
 }
 
+/** Anrufer nach einem erfolgreichen Anruf automatisch als Kontakt anlegen (Standard: aus) */
+export async function setAutoAddContacts(enabled: boolean): Promise<void> {
+  return await invoke('set_auto_add_contacts', { enabled });
+}
+
+export async function getAutoAddContacts(): Promise<boolean> {
+  return await invoke('get_auto_add_contacts');
+}
+
+/** Presence-Änderungen zusätzlich einzeln senden (`onContactOnline`/`onContactOffline`, Standard: aus) */
+export async function setIndividualPresenceEvents(enabled: boolean): Promise<void> {
+  return await invoke('set_individual_presence_events', { enabled });
+}
+
+export async function getIndividualPresenceEvents(): Promise<boolean> {
+  return await invoke('get_individual_presence_events');
+}
+
+/** Setzt die Notiz eines Kontakts (max. 1000 Zeichen, null entfernt sie) */
+export async function setContactNotes(peerId: string, notes: string | null): Promise<Contact> {
+  return await invoke('set_contact_notes', { peerId, notes });
+}
+
+/** Importiert Kontakte; bestehende werden je nach Policy pro Feld abgeglichen */
+export async function importContacts(
+  contacts: ImportedContact[],
+  policy: ConflictPolicy
+): Promise<ImportReport> {
+  return await invoke('import_contacts', { contacts, policy });
+}
+
+export async function markContactVerified(peerId: string, verified: boolean): Promise<Contact> {
+  return await invoke('mark_contact_verified', { peerId, verified });
+}
+
+/** Übernimmt den neuen Key eines Peers nach onContactKeyChanged */
+export async function acceptContactKey(peerId: string, publicKey: string): Promise<void> {
+  return await invoke('accept_contact_key', { peerId, publicKey });
+}
+
+/** Sicherheitsnummer eines Kontakts, schlägt ohne bekannten Key fehl */
+export async function getContactFingerprint(peerId: string): Promise<string> {
+  return await invoke('get_contact_fingerprint', { peerId });
+}
+
+/** Blockiert einen Kontakt und beendet einen laufenden Anruf mit ihm */
+export async function blockContact(peerId: string): Promise<Contact> {
+  return await invoke('block_contact', { peerId });
+}
+
+export async function unblockContact(peerId: string): Promise<Contact> {
+  return await invoke('unblock_contact', { peerId });
+}
+
 export async function refreshContactStatuses(): Promise<void> {
   return await invoke('refresh_contact_statuses');
 }
 
+export async function refreshContactStatus(peerId: string): Promise<Contact> {
+  return await invoke('refresh_contact_status', { peerId });
+}
+
+// ============================================================================
+// CALLBACK REQUESTS
+// ============================================================================
+
+export async function requestCallback(peerId: string): Promise<void> {
+  return await invoke('request_callback', { peerId });
+}
+
+export async function getCallbackRequests(): Promise<CallbackRequest[]> {
+  return await invoke('get_callback_requests');
+}
+
+export async function dismissCallbackRequest(peerId: string): Promise<void> {
+  return await invoke('dismiss_callback_request', { peerId });
+}
+
+// ============================================================================
+// CHAT
+// ============================================================================
+
+export async function sendChat(peerId: string, body: string): Promise<ChatMessage> {
+  return await invoke('send_chat', { peerId, body });
+}
+
+/** Nachrichtenverlauf mit einem Peer (älteste zuerst) */
+export async function getChatMessages(peerId: string): Promise<ChatMessage[]> {
+  return await invoke('get_chat_messages', { peerId });
+}
+
+// ============================================================================
+// CALL HISTORY
+// ============================================================================
+
+export async function getUsageStats(): Promise<UsageStats> {
+  return await invoke('get_usage_stats');
+}
+
+/** Letzte verpasste Anrufe, neueste zuerst */
+export async function getMissedCalls(): Promise<MissedCall[]> {
+  return await invoke('get_missed_calls');
+}
+
+/** Anzahl neuer verpasster Anrufe für das Badge */
+export async function getMissedCallCount(): Promise<number> {
+  return await invoke('get_missed_call_count');
+}
+
+export async function markMissedCallsSeen(): Promise<void> {
+  return await invoke('mark_missed_calls_seen');
+}
+
+/** Gespeicherte Aufnahmen, neueste zuerst */
+export async function listRecordings(): Promise<Recording[]> {
+  return await invoke('list_recordings');
+}
+
+export async function deleteRecording(path: string): Promise<void> {
+  return await invoke('delete_recording', { path });
+}
+
+/** Setzt das Speicherlimit und gibt die dabei gelöschten Aufnahmen zurück */
+export async function setRecordingStorageCap(bytes: number): Promise<Recording[]> {
+  return await invoke('set_recording_storage_cap', { bytes });
+}
+
+export async function getRecordingStorageCap(): Promise<number> {
+  return await invoke('get_recording_storage_cap');
+}
+
 // ============================================================================
 // CALLS
 // ============================================================================
@@ -81,6 +351,16 @@ export async function startCall(peerId: string): Promise<void> {
   return await invoke('start_call', { peerId });
 }
 
+/** Gibt die aufgelöste Peer-ID zurück */
+export async function callByPublicKey(publicKey: string): Promise<string> {
+  return await invoke('call_by_public_key', { publicKey });
+}
+
+/** Ruft die Gegenstelle des letzten ausgehenden Anrufs an, gibt deren aktuelle Peer-ID zurück */
+export async function redialLast(): Promise<string> {
+  return await invoke('redial_last');
+}
+
 export async function acceptCall(peerId: string, offerSdp: string): Promise<void> {
   return await invoke('accept_call', { peerId, offerSdp });
 }
@@ -89,14 +369,47 @@ export async function rejectCall(peerId: string, reason?: string): Promise<void>
   return await invoke('reject_call', { peerId, reason });
 }
 
+export async function dismissIncomingCall(peerId: string, recordMissed: boolean): Promise<void> {
+  return await invoke('dismiss_incoming_call', { peerId, recordMissed });
+}
+
 export async function hangup(): Promise<void> {
   return await invoke('hangup');
 }
 
+export async function hangupPeer(peerId: string): Promise<void> {
+  return await invoke('hangup_peer', { peerId });
+}
+
+/** Notausgang: setzt einen hängenden Anruf aus jedem State auf Idle zurück */
+export async function forceResetCall(): Promise<void> {
+  return await invoke('force_reset_call');
+}
+
+export async function addCallPeer(peerId: string): Promise<void> {
+  return await invoke('add_call_peer', { peerId });
+}
+
+export async function getCallPeers(): Promise<CallStateInfo[]> {
+  return await invoke('get_call_peers');
+}
+
 export async function getCallState(): Promise<CallState> {
   return await invoke('get_call_state') as CallState;
 }
 
+export async function getCallStateDetailed(): Promise<CallStateInfo> {
+  return await invoke('get_call_state_detailed');
+}
+
+export async function getDtlsFingerprints(): Promise<DtlsFingerprints> {
+  return await invoke('get_dtls_fingerprints');
+}
+
+export async function getSecurityInfo(): Promise<SecurityInfo> {
+  return await invoke('get_security_info');
+}
+
 export async function setMuted(muted: boolean): Promise<void> {
   return await invoke('set_muted', { muted });
 }
@@ -105,10 +418,202 @@ export async function isMuted(): Promise<boolean> {
   return await invoke('is_muted');
 }
 
+/** Sekunden seit dem Verbinden, null ohne verbundenen Anruf */
+export async function getCallDuration(): Promise<number | null> {
+  return await invoke('get_call_duration');
+}
+
+/** RTT, Paketverlust, Jitter und übertragene Bytes des Anrufs */
+export async function getCallStats(): Promise<ConnectionStats> {
+  return await invoke('get_call_stats');
+}
+
 export async function getAudioLevels(): Promise<[number, number]> {
   return await invoke('get_audio_levels');
 }
 
+export async function isVoiceActive(): Promise<boolean> {
+  return await invoke('is_voice_active');
+}
+
+export async function warmUpAudio(): Promise<void> {
+  return await invoke('warm_up_audio');
+}
+
+export async function prewarmCall(): Promise<void> {
+  return await invoke('prewarm_call');
+}
+
+export async function setIceTransportPolicy(policy: IceTransportPolicy): Promise<void> {
+  return await invoke('set_ice_transport_policy', { policy });
+}
+
+export async function getIceTransportPolicy(): Promise<IceTransportPolicy> {
+  return await invoke('get_ice_transport_policy');
+}
+
+/** Fügt einen TURN-Server hinzu (gespeichert, gilt für neue Verbindungen) */
+export async function addTurnServer(url: string, username: string, credential: string): Promise<void> {
+  return await invoke('add_turn_server', { url, username, credential });
+}
+
+export async function clearTurnServers(): Promise<void> {
+  return await invoke('clear_turn_servers');
+}
+
+/** Begrenzt die Sende-Bitrate (min. 16000 bps), ohne Wert wird die Grenze aufgehoben */
+export async function setMaxBitrate(maxBps?: number): Promise<void> {
+  return await invoke('set_max_bitrate', { maxBps: maxBps ?? null });
+}
+
+export async function getMaxBitrate(): Promise<number | null> {
+  return await invoke('get_max_bitrate');
+}
+
+/** Baut einen Anruf einmal neu auf, wenn ICE vor dem Verbinden scheitert (Standard: aus) */
+export async function setSetupRetryEnabled(enabled: boolean): Promise<void> {
+  return await invoke('set_setup_retry_enabled', { enabled });
+}
+
+export async function getSetupRetryEnabled(): Promise<boolean> {
+  return await invoke('get_setup_retry_enabled');
+}
+
+/** Bricht einen laufenden Echo-Check ab, ohne Ergebnis */
+export async function skipEchoCheck(): Promise<void> {
+  return await invoke('skip_echo_check');
+}
+
+// ============================================================================
+// DIAGNOSTICS
+// ============================================================================
+
+export async function getEventLog(): Promise<EventLogEntry[]> {
+  return await invoke('get_event_log');
+}
+
+export async function clearEventLog(): Promise<void> {
+  return await invoke('clear_event_log');
+}
+
+/** Maskiert IP-Adressen in neuen Einträgen (Standard: an) */
+export async function setEventLogRedaction(enabled: boolean): Promise<void> {
+  return await invoke('set_event_log_redaction', { enabled });
+}
+
+/**
+ * Schreibt einen Diagnosebericht (JSON) und gibt den Dateipfad zurück
+ *
+ * IP-Adressen bleiben maskiert, außer `includeAddresses` ist gesetzt.
+ */
+export async function generateDiagnostics(includeAddresses = false): Promise<string> {
+  return await invoke('generate_diagnostics', { includeAddresses });
+}
+
+/** Erreichbarkeit der STUN/TURN-Server, im Leerlauf alle 5 Minuten geprüft */
+export async function getIceServerHealth(): Promise<IceServerHealth[]> {
+  return await invoke('get_ice_server_health');
+}
+
+/** Selbsttest vor einem Anruf: Candidate-Typen und NAT-Verhalten */
+export async function testConnectivity(): Promise<ConnectivityReport> {
+  return await invoke('test_connectivity');
+}
+
+/** Pfad der aktuellen Log-Datei */
+export async function getLogPath(): Promise<string> {
+  return await invoke('get_log_path');
+}
+
+/** Letzte Log-Zeilen aus aktueller und rotierten Dateien, älteste zuerst */
+export async function readRecentLogs(lines: number): Promise<string[]> {
+  return await invoke('read_recent_logs', { lines });
+}
+
+// ============================================================================
+// TESTING (nur Debug-Builds)
+// ============================================================================
+
+export interface NetworkSimulation {
+  loss_pct: number;
+  added_latency_ms: number;
+  jitter_ms: number;
+}
+
+export async function setNetworkSimulation(
+  lossPct: number,
+  addedLatencyMs: number,
+  jitterMs: number
+): Promise<void> {
+  return await invoke('set_network_simulation', { lossPct, addedLatencyMs, jitterMs });
+}
+
+export async function getNetworkSimulation(): Promise<NetworkSimulation> {
+  return await invoke('get_network_simulation');
+}
+
+export async function setVerboseIceLogging(enabled: boolean): Promise<void> {
+  return await invoke('set_verbose_ice_logging', { enabled });
+}
+
+/** Feste Verzögerung des gesendeten Audios (0-2000ms, Standard 0) */
+export async function setOutputDelay(delayMs: number): Promise<void> {
+  return await invoke('set_output_delay', { delayMs });
+}
+
+export async function getOutputDelay(): Promise<number> {
+  return await invoke('get_output_delay');
+}
+
+// ============================================================================
+// MANUAL SIGNALING
+// ============================================================================
+
+export interface LocalDescription {
+  sdp_type: 'offer' | 'answer';
+  sdp: string;
+}
+
+export async function createManualOffer(peerId: string): Promise<string> {
+  return await invoke('create_manual_offer', { peerId });
+}
+
+export async function acceptManualOffer(peerId: string, offerSdp: string): Promise<string> {
+  return await invoke('accept_manual_offer', { peerId, offerSdp });
+}
+
+export async function applyRemoteAnswer(answerSdp: string, peerId?: string): Promise<void> {
+  return await invoke('apply_remote_answer', { answerSdp, peerId });
+}
+
+export async function addIceCandidate(candidate: string, peerId?: string): Promise<void> {
+  return await invoke('add_ice_candidate', { candidate, peerId });
+}
+
+export async function getLocalDescription(): Promise<LocalDescription | null> {
+  return await invoke('get_local_description');
+}
+
+export async function getLocalCandidates(): Promise<string[]> {
+  return await invoke('get_local_candidates');
+}
+
+// ============================================================================
+// LAN DISCOVERY
+// ============================================================================
+
+export async function startLanDiscovery(username: string): Promise<void> {
+  return await invoke('start_lan_discovery', { username });
+}
+
+export async function stopLanDiscovery(): Promise<void> {
+  return await invoke('stop_lan_discovery');
+}
+
+export async function getLanPeers(): Promise<LanPeer[]> {
+  return await invoke('get_lan_peers');
+}
+
 // ============================================================================
 // AUDIO SETTINGS
 // ============================================================================
@@ -122,19 +627,221 @@ export async function getAudioDevices(): Promise<[AudioDevice[], AudioDevice[]]>
   return await invoke('get_audio_devices');
 }
 
+interface AudioDeviceSelection {
+  input: string | null;
+  output: string | null;
+}
+
+/** Mikrofon für folgende Anrufe (null = System-Standard) */
+export async function setInputDevice(name: string | null): Promise<void> {
+  return await invoke('set_input_device', { name });
+}
+
+/** Lautsprecher für folgende Anrufe (null = System-Standard) */
+export async function setOutputDevice(name: string | null): Promise<void> {
+  return await invoke('set_output_device', { name });
+}
+
+export async function getSelectedAudioDevices(): Promise<AudioDeviceSelection> {
+  return await invoke('get_selected_audio_devices');
+}
+
+export async function checkMicrophonePermission(): Promise<MicrophonePermission> {
+  return await invoke('check_microphone_permission');
+}
+
+export async function requestMicrophonePermission(): Promise<MicrophonePermission> {
+  return await invoke('request_microphone_permission');
+}
+
+interface CodecInfo {
+  mime_type: string;
+  clock_rate: number;
+  channels: number;
+  payload_type: number;
+  sdp_fmtp_line: string;
+}
+
+export async function getSupportedCodecs(): Promise<CodecInfo[]> {
+  return await invoke('get_supported_codecs');
+}
+
+/** Vorpufferung des Playbacks in Frames à 20ms (0-8, Standard 3) */
+export async function setPlaybackPrefillFrames(frames: number): Promise<void> {
+  return await invoke('set_playback_prefill_frames', { frames });
+}
+
+export async function getPlaybackPrefillFrames(): Promise<number> {
+  return await invoke('get_playback_prefill_frames');
+}
+
+export async function setAudioFrameSize(frameSize: number): Promise<void> {
+  return await invoke('set_audio_frame_size', { frameSize });
+}
+
+export async function getAudioFrameSize(): Promise<number> {
+  return await invoke('get_audio_frame_size');
+}
+
+/** Ziel-Bitrate des Opus-Encoders in bps, gilt auch im laufenden Anruf */
+export async function setCodecBitrate(bps: number): Promise<void> {
+  return await invoke('set_codec_bitrate', { bps });
+}
+
+export async function getCodecBitrate(): Promise<number> {
+  return await invoke('get_codec_bitrate');
+}
+
+/** Kurzer Echo-Check mit leisem Prüfsignal direkt nach dem Verbinden (Standard: aus) */
+export async function setEchoCheckEnabled(enabled: boolean): Promise<void> {
+  return await invoke('set_echo_check_enabled', { enabled });
+}
+
+export async function getEchoCheckEnabled(): Promise<boolean> {
+  return await invoke('get_echo_check_enabled');
+}
+
+/** Limiter gegen Übersteuerung im Playback, Obergrenze in dBFS (-12 bis 0, Standard: aus bei -1) */
+export async function setOutputLimiter(enabled: boolean, ceilingDb: number): Promise<void> {
+  return await invoke('set_output_limiter', { enabled, ceilingDb });
+}
+
+export async function getOutputLimiter(): Promise<OutputLimiterConfig> {
+  return await invoke('get_output_limiter');
+}
+
+/** Stille nicht senden (Sprachaktivitätserkennung, Standard: aus) */
+export async function setVadEnabled(enabled: boolean): Promise<void> {
+  return await invoke('set_vad_enabled', { enabled });
+}
+
+export async function getVadEnabled(): Promise<boolean> {
+  return await invoke('get_vad_enabled');
+}
+
+/** RMS-Schwelle, ab der gesprochen wird (0.0 bis 1.0, Standard: 0.01) */
+export async function setVadThreshold(threshold: number): Promise<void> {
+  return await invoke('set_vad_threshold', { threshold });
+}
+
+export async function getVadThreshold(): Promise<number> {
+  return await invoke('get_vad_threshold');
+}
+
+/** Push-to-Talk statt offenem Mikrofon (gesendet wird nur bei gedrückter Taste) */
+export async function setPushToTalk(enabled: boolean): Promise<void> {
+  return await invoke('set_push_to_talk', { enabled });
+}
+
+export async function getPushToTalk(): Promise<boolean> {
+  return await invoke('get_push_to_talk');
+}
+
+export async function pttKeyDown(): Promise<void> {
+  return await invoke('ptt_key_down');
+}
+
+export async function pttKeyUp(): Promise<void> {
+  return await invoke('ptt_key_up');
+}
+
+/** Klingelton für eingehende Anrufe an- oder abschalten */
+export async function setRingtoneEnabled(enabled: boolean): Promise<void> {
+  return await invoke('set_ringtone_enabled', { enabled });
+}
+
+export async function getRingtoneEnabled(): Promise<boolean> {
+  return await invoke('get_ringtone_enabled');
+}
+
+export async function setRingtone(style: RingtoneStyle): Promise<void> {
+  return await invoke('set_ringtone', { style });
+}
+
+export async function getRingtone(): Promise<RingtoneStyle> {
+  return await invoke('get_ringtone');
+}
+
+export async function setSoundEffectsEnabled(enabled: boolean): Promise<void> {
+  return await invoke('set_sound_effects_enabled', { enabled });
+}
+
+export async function getSoundEffectsEnabled(): Promise<boolean> {
+  return await invoke('get_sound_effects_enabled');
+}
+
+/** Hinweis-Sounds (z.B. Rückruf-Bitten) während eines verbundenen Anrufs unterdrücken */
+export async function setQuietDuringCall(quiet: boolean): Promise<void> {
+  return await invoke('set_quiet_during_call', { quiet });
+}
+
+export async function getQuietDuringCall(): Promise<boolean> {
+  return await invoke('get_quiet_during_call');
+}
+
+/** Ersetzt einen Sound durch eine WAV-Datei (max. 3s), ohne Pfad gilt wieder der Standard */
+export async function setSoundEffect(effect: SoundEffect, path?: string): Promise<void> {
+  return await invoke('set_sound_effect', { effect, path: path ?? null });
+}
+
+/** Spielt einen Sound zur Vorschau ab */
+export async function playSoundEffect(effect: SoundEffect): Promise<void> {
+  return await invoke('play_sound_effect', { effect });
+}
+
+/** Spielt einen Sinuston (20-20000 Hz, max. 10s) zum Prüfen der Lautsprecher */
+export async function playTestTone(frequency = 440, durationMs = 1000): Promise<void> {
+  return await invoke('play_test_tone', { frequency, durationMs });
+}
+
+// ============================================================================
+// INTEGRATIONS
+// ============================================================================
+
+/** Setzt einen Webhook (nur http://, ohne allowRemote nur lokale Ziele) */
+export async function setWebhook(
+  url: string,
+  events: WebhookEvent[],
+  allowRemote = false
+): Promise<void> {
+  return await invoke('set_webhook', { url, events, allowRemote });
+}
+
+export async function clearWebhook(): Promise<void> {
+  return await invoke('clear_webhook');
+}
+
+export async function getWebhook(): Promise<WebhookConfig | null> {
+  return await invoke('get_webhook');
+}
+
 // ============================================================================
 // EVENT LISTENERS
 // ============================================================================
 
 export type EventCallback<T> = (payload: T) => void;
 
+// Identity Events
+export function onIdentityCreated(callback: EventCallback<string>): Promise<UnlistenFn> {
+  return listen<string>('identity:created', (event) => callback(event.payload));
+}
+
+export function onIdentityLocked(callback: EventCallback<null>): Promise<UnlistenFn> {
+  return listen('identity:locked', () => callback(null));
+}
+
 // Signaling Events
 export function onSignalingConnected(callback: EventCallback<null>): Promise<UnlistenFn> {
   return listen('signaling:connected', () => callback(null));
 }
 
-export function onSignalingDisconnected(callback: EventCallback<null>): Promise<UnlistenFn> {
-  return listen('signaling:disconnected', () => callback(null));
+export function onSignalingDisconnected(callback: EventCallback<DisconnectedEvent>): Promise<UnlistenFn> {
+  return listen<DisconnectedEvent>('signaling:disconnected', (event) => callback(event.payload));
+}
+
+/** Wegen Inaktivität getrennt, der nächste Command verbindet automatisch neu */
+export function onIdleDisconnected(callback: EventCallback<null>): Promise<UnlistenFn> {
+  return listen('signaling:idle_disconnected', () => callback(null));
 }
 
 export function onRegistered(callback: EventCallback<RegisteredEvent>): Promise<UnlistenFn> {
@@ -153,6 +860,15 @@ export function onSignalingError(callback: EventCallback<SignalingErrorEvent>):
   return listen<SignalingErrorEvent>('signaling:error', (event) => callback(event.payload));
 }
 
+/** Der Server drosselt den Client: Suche und Presence pausieren für `retryAfterSecs` */
+export function onSignalingRateLimited(callback: EventCallback<RateLimitedEvent>): Promise<UnlistenFn> {
+  return listen<RateLimitedEvent>('signaling:rate_limited', (event) => callback(event.payload));
+}
+
+export function onUnknownSignalingMessage(callback: EventCallback<UnknownMessageEvent>): Promise<UnlistenFn> {
+  return listen<UnknownMessageEvent>('signaling:unknown_message', (event) => callback(event.payload));
+}
+
 // Call Events
 export function onIncomingCall(callback: EventCallback<IncomingCallEvent>): Promise<UnlistenFn> {
   return listen<IncomingCallEvent>('call:incoming', (event) => callback(event.payload));
@@ -166,15 +882,127 @@ export function onCallRejected(callback: EventCallback<CallRejectedEvent>): Prom
   return listen<CallRejectedEvent>('call:rejected', (event) => callback(event.payload));
 }
 
+export function onGlareResolved(callback: EventCallback<GlareResolvedEvent>): Promise<UnlistenFn> {
+  return listen<GlareResolvedEvent>('call:glare_resolved', (event) => callback(event.payload));
+}
+
 export function onCallEnded(callback: EventCallback<string>): Promise<UnlistenFn> {
   return listen<string>('call:ended', (event) => callback(event.payload));
 }
 
+export function onIdentityVerified(callback: EventCallback<IdentityVerifiedEvent>): Promise<UnlistenFn> {
+  return listen<IdentityVerifiedEvent>('call:identity_verified', (event) => callback(event.payload));
+}
+
+export function onIceCandidateLogged(callback: EventCallback<IceCandidateLogEvent>): Promise<UnlistenFn> {
+  return listen<IceCandidateLogEvent>('call:ice_candidate_logged', (event) => callback(event.payload));
+}
+
+export function onPeerStateChanged(callback: EventCallback<PeerStateChangedEvent>): Promise<UnlistenFn> {
+  return listen<PeerStateChangedEvent>('call:peer_state_changed', (event) => callback(event.payload));
+}
+
+/** Eingehender Anruf ohne gültige Offer-Signatur wurde automatisch abgelehnt */
+export function onOfferRejected(callback: EventCallback<OfferRejectedEvent>): Promise<UnlistenFn> {
+  return listen<OfferRejectedEvent>('call:offer_rejected', (event) => callback(event.payload));
+}
+
+export function onOneWayAudio(callback: EventCallback<OneWayAudioEvent>): Promise<UnlistenFn> {
+  return listen<OneWayAudioEvent>('call:one_way_audio', (event) => callback(event.payload));
+}
+
+export function onEchoDetected(callback: EventCallback<EchoDetectedEvent>): Promise<UnlistenFn> {
+  return listen<EchoDetectedEvent>('call:echo_detected', (event) => callback(event.payload));
+}
+
+export function onCallRetrying(callback: EventCallback<CallRetryingEvent>): Promise<UnlistenFn> {
+  return listen<CallRetryingEvent>('call:retrying', (event) => callback(event.payload));
+}
+
+/** Verbindung zu einem Teilnehmer unterbrochen, der Anruf endet nach 10s ohne Erfolg */
+export function onCallReconnecting(callback: EventCallback<CallReconnectingEvent>): Promise<UnlistenFn> {
+  return listen<CallReconnectingEvent>('call:reconnecting', (event) => callback(event.payload));
+}
+
+/** Verbindungsqualität, alle 2 Sekunden während eines verbundenen Anrufs */
+export function onCallStats(callback: EventCallback<ConnectionStats>): Promise<UnlistenFn> {
+  return listen<ConnectionStats>('call:stats', (event) => callback(event.payload));
+}
+
+/** Dauer des verbundenen Anrufs, einmal pro Sekunde */
+export function onCallDuration(callback: EventCallback<CallDurationEvent>): Promise<UnlistenFn> {
+  return listen<CallDurationEvent>('call:duration', (event) => callback(event.payload));
+}
+
+/** Anruf abgebrochen, weil eine andere App das Mikrofon belegt */
+export function onMicrophoneBusy(callback: EventCallback<null>): Promise<UnlistenFn> {
+  return listen('call:microphone_busy', () => callback(null));
+}
+
+/** Neuer Mute-Status (auch wenn er vor dem Anruf gesetzt wurde) */
+export function onMuteChanged(callback: EventCallback<boolean>): Promise<UnlistenFn> {
+  return listen<boolean>('mute:changed', (event) => callback(event.payload));
+}
+
+export function onPermissionRequired(callback: EventCallback<PermissionRequiredEvent>): Promise<UnlistenFn> {
+  return listen<PermissionRequiredEvent>('call:permission_required', (event) => callback(event.payload));
+}
+
+// LAN Events
+export function onLanPeerDiscovered(callback: EventCallback<LanPeer>): Promise<UnlistenFn> {
+  return listen<LanPeer>('lan:peer_discovered', (event) => callback(event.payload));
+}
+
+export function onLanPeerLost(callback: EventCallback<string>): Promise<UnlistenFn> {
+  return listen<string>('lan:peer_lost', (event) => callback(event.payload));
+}
+
+// Callback Events
+export function onCallbackQueued(callback: EventCallback<string>): Promise<UnlistenFn> {
+  return listen<string>('callback:queued', (event) => callback(event.payload));
+}
+
+export function onCallbackRequestReceived(callback: EventCallback<CallbackRequest>): Promise<UnlistenFn> {
+  return listen<CallbackRequest>('callback:received', (event) => callback(event.payload));
+}
+
+export function onChatMessage(callback: EventCallback<ChatMessage>): Promise<UnlistenFn> {
+  return listen<ChatMessage>('chat:message', (event) => callback(event.payload));
+}
+
 // Contact Events
+/** Gebündelte Presence-Änderungen, pro Kontakt der letzte Status im Zeitfenster */
+export function onPresenceUpdated(callback: EventCallback<PresenceUpdate[]>): Promise<UnlistenFn> {
+  return listen<PresenceUpdate[]>('contacts:presence_updated', (event) => callback(event.payload));
+}
+
+/** Nur mit `setIndividualPresenceEvents(true)` */
 export function onContactOnline(callback: EventCallback<string>): Promise<UnlistenFn> {
   return listen<string>('contact:online', (event) => callback(event.payload));
 }
 
+/** Nur mit `setIndividualPresenceEvents(true)` */
 export function onContactOffline(callback: EventCallback<string>): Promise<UnlistenFn> {
   return listen<string>('contact:offline', (event) => callback(event.payload));
 }
+
+export function onContactRefreshProgress(callback: EventCallback<ContactRefreshProgressEvent>): Promise<UnlistenFn> {
+  return listen<ContactRefreshProgressEvent>('contact:refresh_progress', (event) => callback(event.payload));
+}
+
+export function onContactAutoAdded(callback: EventCallback<Contact>): Promise<UnlistenFn> {
+  return listen<Contact>('contact:auto_added', (event) => callback(event.payload));
+}
+
+export function onContactKeyChanged(callback: EventCallback<ContactKeyChangedEvent>): Promise<UnlistenFn> {
+  return listen<ContactKeyChangedEvent>('contact:key_changed', (event) => callback(event.payload));
+}
+
+export function onPresenceInvalid(callback: EventCallback<PresenceInvalidEvent>): Promise<UnlistenFn> {
+  return listen<PresenceInvalidEvent>('contact:presence_invalid', (event) => callback(event.payload));
+}
+
+// System Events
+export function onDefaultDeviceChanged(callback: EventCallback<DefaultDeviceChangedEvent>): Promise<UnlistenFn> {
+  return listen<DefaultDeviceChangedEvent>('system:default_device_changed', (event) => callback(event.payload));
+}
@@ -8,24 +8,56 @@ export interface Contact {
   is_online: boolean;
   created_at: string;
   last_seen: string | null;
+  notes: string | null;
+  /** Nach einem Anruf automatisch angelegt */
+  auto_added: boolean;
+  /** Sicherheitsnummer vom Nutzer bestätigt */
+  is_verified: boolean;
+  /** Nur bei gelöschten Kontakten gesetzt */
+  deleted_at: string | null;
+  /** Eingehende Anrufe werden automatisch abgewiesen */
+  blocked: boolean;
+  /** Public Key, an den die peer_id gebunden ist */
+  public_key: string | null;
 }
 
 export interface NewContact {
   peer_id: string;
   username: string;
   display_name?: string;
+  /** Aus `signaling:user_found`, bindet die peer_id an diesen Key */
+  public_key?: string | null;
+}
+
+/** Geteilte Identität (aus `call-app-card:...`) */
+export interface ContactCard {
+  username: string;
+  public_key: string;
+  peer_id: string | null;
+}
+
+/** Eintrag im gebündelten `contacts:presence_updated` Event */
+export interface PresenceUpdate {
+  peer_id: string;
+  is_online: boolean;
 }
 
 export interface UserFoundEvent {
   peer_id: string;
   username: string;
+  display_name: string | null;
   is_online: boolean;
+  public_key: string | null;
 }
 
 export interface IncomingCallEvent {
   fromPeerId: string;
   fromUsername: string;
+  /** Anzeigename des Anrufers, falls im Verzeichnis hinterlegt */
+  fromDisplayName?: string | null;
   sdp: string;
+  /** Weiterleitung vom gespeicherten Key des Anrufers signiert (sonst erster Kontakt) */
+  senderVerified: boolean;
 }
 
 export interface RegisteredEvent {
@@ -38,19 +70,346 @@ export interface SignalingErrorEvent {
   message: string;
 }
 
+export interface RateLimitedEvent {
+  retryAfterSecs: number;
+}
+
+export interface DisconnectedEvent {
+  /** WebSocket Close-Code, null bei Verbindungsabbruch ohne Close-Frame */
+  code: number | null;
+  reason: string | null;
+  message: string;
+  should_reconnect: boolean;
+}
+
+export interface UnknownMessageEvent {
+  raw: string;
+  error: string;
+}
+
 export interface CallRejectedEvent {
   byPeerId: string;
   reason?: string;
 }
 
+/** Beide Seiten haben sich gleichzeitig angerufen, es bleibt ein Anruf übrig */
+export interface GlareResolvedEvent {
+  peerId: string;
+  /** caller: eigenes Offer bleibt, callee: Offer der Gegenseite angenommen */
+  role: 'caller' | 'callee';
+}
+
+export interface LanPeer {
+  peer_id: string;
+  username: string;
+  public_key: string;
+  addresses: string[];
+}
+
+export interface DtlsFingerprint {
+  algorithm: string;
+  value: string;
+}
+
+export interface DtlsFingerprints {
+  local: DtlsFingerprint | null;
+  remote: DtlsFingerprint | null;
+}
+
+export interface RemoteIdentity {
+  peer_id: string;
+  public_key: string;
+  /** Gegen einen bekannten Key geprüft statt Trust on First Use */
+  pinned: boolean;
+}
+
+export interface SecurityInfo {
+  dtls_srtp_active: boolean;
+  dtls_state: string;
+  /** z.B. SRTP_AEAD_AES_128_GCM */
+  srtp_profile: string | null;
+  fingerprints: DtlsFingerprints;
+  /** null bei unsigniertem SDP */
+  remote_identity: RemoteIdentity | null;
+}
+
+export interface CallbackRequest {
+  peer_id: string;
+  username: string;
+  requested_at: number;
+}
+
+export interface ChatMessage {
+  id: number;
+  peer_id: string;
+  /** Von uns gesendet (sonst empfangen) */
+  outgoing: boolean;
+  body: string;
+  /** Unix-Millisekunden */
+  sent_at: number;
+  /** Nur bei eingehenden Nachrichten: vom gespeicherten Key des Absenders signiert */
+  sender_verified?: boolean;
+}
+
+export interface ImportedContact {
+  peer_id: string;
+  username: string;
+  display_name?: string | null;
+  notes?: string | null;
+}
+
+/** Sortierung der Kontaktliste (`favorites`: häufigste Gesprächspartner zuerst) */
+export type ContactSort = 'alphabetical' | 'last_called' | 'favorites';
+
+/** Umgang mit bereits existierenden Kontakten beim Import */
+export type ConflictPolicy = 'keep_existing' | 'prefer_imported' | 'merge';
+
+export interface FieldChange {
+  field: 'username' | 'display_name' | 'notes';
+  old: string | null;
+  new: string | null;
+}
+
+export interface ImportReport {
+  added: string[];
+  updated: { peer_id: string; changes: FieldChange[] }[];
+  unchanged: string[];
+  skipped_deleted: string[];
+}
+
+export interface MissedCall {
+  id: number;
+  peer_id: string;
+  username: string | null;
+  started_at: number;
+  seen: boolean;
+}
+
+export interface Recording {
+  id: number;
+  call_history_id: number | null;
+  peer_id: string;
+  username: string | null;
+  path: string;
+  started_at: number;
+  duration_ms: number;
+  size_bytes: number;
+}
+
+export interface UsageStats {
+  calls_made: number;
+  calls_received: number;
+  total_talk_time_secs: number;
+  contact_count: number;
+}
+
+export interface IdentityVerifiedEvent {
+  peer_id: string;
+  public_key: string;
+}
+
+export interface PresenceInvalidEvent {
+  peer_id: string;
+  reason: string;
+}
+
+export interface IceCandidateLogEvent {
+  direction: 'local' | 'remote';
+  candidate_type: 'host' | 'srflx' | 'prflx' | 'relay' | string;
+  protocol: string;
+  /** Maskierte Adresse, z.B. 192.168.1.x */
+  address: string;
+  port: number;
+}
+
+/** Anruf abgelehnt, weil das Offer nicht nachweislich vom Anrufer stammt */
+export interface OfferRejectedEvent {
+  peerId: string;
+  username: string;
+  reason: string;
+}
+
+/** Audio fließt nur in eine Richtung (`inbound`: wir hören nichts, `outbound`: wir werden nicht gehört) */
+export interface OneWayAudioEvent {
+  direction: 'inbound' | 'outbound';
+}
+
+export interface OutputLimiterConfig {
+  enabled: boolean;
+  ceiling_db: number;
+}
+
+/** Das Prüfsignal des Echo-Checks war im Mikrofon hörbar (Kopfhörer oder Echounterdrückung empfehlen) */
+export interface EchoDetectedEvent {
+  /** Korrelation zwischen Prüfsignal und Mikrofon (0.0 - 1.0) */
+  correlation: number;
+}
+
+/** ICE ist im Verbindungsaufbau gescheitert, der Anruf wird einmal neu aufgebaut */
+/** Verbindungsqualität des laufenden Anrufs (fehlende Werte sind 0) */
+export interface ConnectionStats {
+  rtt_ms: number;
+  /** Verlorene eingehende RTP-Pakete */
+  packets_lost: number;
+  jitter_ms: number;
+  bytes_sent: number;
+  bytes_received: number;
+}
+
+export interface CallDurationEvent {
+  /** Sekunden seit dem Verbinden */
+  seconds: number;
+}
+
+export interface CallRetryingEvent {
+  peerId: string;
+  /** `true` beim Anrufer (sendet ein neues Offer), sonst wartet die Gegenseite darauf */
+  outgoing: boolean;
+}
+
+/** Verbindung im Anruf unterbrochen, ICE wird neu ausgehandelt */
+export interface CallReconnectingEvent {
+  peerId: string;
+}
+
+/** State eines einzelnen Teilnehmers (`ended`, wenn er den Anruf verlassen hat) */
+export interface PeerStateChangedEvent {
+  peerId: string;
+  state: CallStateInfo;
+}
+
+/** `undetermined`: noch nicht entschieden oder (macOS) nicht abfragbar */
+export type MicrophonePermission = 'granted' | 'denied' | 'undetermined';
+
+/** UI-Sounds: Verbinden, Auflegen, eingegangene Nachricht */
+export type SoundEffect = 'connect' | 'disconnect' | 'message';
+
+/** Klingelton für eingehende Anrufe */
+export type RingtoneStyle = 'classic' | 'chime';
+
+/** Events, die an einen Webhook gemeldet werden können */
+export type WebhookEvent =
+  | 'contact_online'
+  | 'contact_offline'
+  | 'incoming_call'
+  | 'call_connected'
+  | 'call_ended';
+
+/** Aktiver Webhook */
+export interface WebhookConfig {
+  url: string;
+  events: WebhookEvent[];
+}
+
+/** Anruf wurde nicht gestartet, weil das Mikrofon nicht freigegeben ist */
+export interface PermissionRequiredEvent {
+  permission: MicrophonePermission;
+}
+
+/** Wechsel des System-Standardgeräts (unter Linux/ALSA nicht erkennbar) */
+export interface DefaultDeviceChangedEvent {
+  kind: 'input' | 'output';
+  /** Neues Standardgerät, null wenn keines mehr vorhanden ist */
+  name: string | null;
+  previous: string | null;
+}
+
+export interface EventLogEntry {
+  /** Unix-Millisekunden */
+  timestamp: number;
+  source: 'signaling' | 'call';
+  /** Debug-Darstellung des Events, ggf. maskiert und gekürzt */
+  event: string;
+}
+
+export interface ContactKeyChangedEvent {
+  peerId: string;
+  /** Der Kontakt war verifiziert, die Markierung wurde aufgehoben */
+  wasVerified: boolean;
+  /** Neuer Public Key (Base64), gilt erst nach acceptContactKey */
+  newKey: string;
+}
+
+/** Fortschritt von refreshContactStatuses (Anfragen werden blockweise gesendet) */
+export interface ContactRefreshProgressEvent {
+  sent: number;
+  total: number;
+  /** Nur beim letzten Event eines abgebrochenen Durchlaufs */
+  error?: string;
+}
+
+export interface PendingRequest {
+  request_id: string;
+  kind: 'find_user' | 'find_user_by_key' | 'offer';
+  /** Gesuchter Username, gesuchter Public Key bzw. angerufene Peer-ID */
+  target: string;
+  created_at: number;
+}
+
+export interface IceProbeResult {
+  checked_at: number;
+  reachable: boolean;
+  rtt_ms: number | null;
+  error: string | null;
+}
+
+export interface IceServerHealth {
+  url: string;
+  success_rate: number | null;
+  avg_rtt_ms: number | null;
+  history: IceProbeResult[];
+}
+
+export type NatBehavior = 'no_nat' | 'endpoint_independent' | 'endpoint_dependent' | 'unknown';
+
+export interface ConnectivityReport {
+  candidates: {
+    host: number;
+    srflx: number;
+    relay: number;
+  };
+  /** Maskierte öffentliche Adressen der srflx Candidates */
+  public_addresses: string[];
+  nat_behavior: NatBehavior;
+  stun_configured: boolean;
+  turn_configured: boolean;
+  /** Kein srflx Candidate: STUN ist vermutlich blockiert */
+  stun_blocked: boolean;
+  turn_failed: boolean;
+  gathering_timed_out: boolean;
+  duration_ms: number;
+}
+
+export interface ConnectionDiagnostics {
+  server_url: string;
+  is_connected: boolean;
+  peer_id: string | null;
+  username: string | null;
+  heartbeat_interval_secs: number;
+  last_heartbeat_at: number | null;
+  /** Server-Uhr minus lokale Uhr in Millisekunden */
+  clock_offset_ms: number | null;
+}
+
 export type CallState = 
   | 'idle'
   | 'calling'
   | 'ringing'
   | 'connecting'
   | 'connected'
+  | 'reconnecting'
   | 'ended';
 
+/** Call-State samt Gegenstelle (peer_id/username fehlen bei idle/ended) */
+export interface CallStateInfo {
+  kind: CallState;
+  peer_id: string | null;
+  username: string | null;
+}
+
+/** `relay` erzwingt TURN und verbirgt die eigene IP vor der Gegenstelle */
+export type IceTransportPolicy = 'all' | 'relay';
+
 export type AppScreen = 
   | 'login'
   | 'main'